@@ -444,6 +444,7 @@ fn main() {
     // ========
     // Generate FLASH regions
     cfgs.declare("flash");
+    cfgs.declare("flash_otp");
     let mut has_flash = false;
     if !chip_name.starts_with("stm32n6") {
         cfgs.enable("flash");
@@ -483,6 +484,9 @@ fn main() {
         let bank_2 = bank_2_base
             .map(|a| quote!(#a))
             .unwrap_or_else(|| quote!(panic!("Bank 2 not present")));
+        if otp_base.is_some() {
+            cfgs.enable("flash_otp");
+        }
         let otp = otp_base
             .map(|a| quote!(#a))
             .unwrap_or_else(|| quote!(panic!("OTP not present")));
@@ -1118,6 +1122,23 @@ fn main() {
         (("lpuart", "RTS"), quote!(crate::usart::RtsPin)),
         (("lpuart", "CK"), quote!(crate::usart::CkPin)),
         (("lpuart", "DE"), quote!(crate::usart::DePin)),
+        (("dfsdm", "CKOUT"), quote!(crate::dfsdm::CkoutPin)),
+        (("dfsdm", "CKIN0"), quote!(crate::dfsdm::CkinPin<Ch0>)),
+        (("dfsdm", "DATIN0"), quote!(crate::dfsdm::DatinPin<Ch0>)),
+        (("dfsdm", "CKIN1"), quote!(crate::dfsdm::CkinPin<Ch1>)),
+        (("dfsdm", "DATIN1"), quote!(crate::dfsdm::DatinPin<Ch1>)),
+        (("dfsdm", "CKIN2"), quote!(crate::dfsdm::CkinPin<Ch2>)),
+        (("dfsdm", "DATIN2"), quote!(crate::dfsdm::DatinPin<Ch2>)),
+        (("dfsdm", "CKIN3"), quote!(crate::dfsdm::CkinPin<Ch3>)),
+        (("dfsdm", "DATIN3"), quote!(crate::dfsdm::DatinPin<Ch3>)),
+        (("dfsdm", "CKIN4"), quote!(crate::dfsdm::CkinPin<Ch4>)),
+        (("dfsdm", "DATIN4"), quote!(crate::dfsdm::DatinPin<Ch4>)),
+        (("dfsdm", "CKIN5"), quote!(crate::dfsdm::CkinPin<Ch5>)),
+        (("dfsdm", "DATIN5"), quote!(crate::dfsdm::DatinPin<Ch5>)),
+        (("dfsdm", "CKIN6"), quote!(crate::dfsdm::CkinPin<Ch6>)),
+        (("dfsdm", "DATIN6"), quote!(crate::dfsdm::DatinPin<Ch6>)),
+        (("dfsdm", "CKIN7"), quote!(crate::dfsdm::CkinPin<Ch7>)),
+        (("dfsdm", "DATIN7"), quote!(crate::dfsdm::DatinPin<Ch7>)),
         (("sai", "SCK_A"), quote!(crate::sai::SckPin<A>)),
         (("sai", "SCK_B"), quote!(crate::sai::SckPin<B>)),
         (("sai", "FS_A"), quote!(crate::sai::FsPin<A>)),
@@ -1126,6 +1147,11 @@ fn main() {
         (("sai", "SD_B"), quote!(crate::sai::SdPin<B>)),
         (("sai", "MCLK_A"), quote!(crate::sai::MclkPin<A>)),
         (("sai", "MCLK_B"), quote!(crate::sai::MclkPin<B>)),
+        (("sai", "CK1"), quote!(crate::sai::Ck1Pin)),
+        (("sai", "D1"), quote!(crate::sai::D1Pin)),
+        (("sai", "D2"), quote!(crate::sai::D2Pin)),
+        (("sai", "D3"), quote!(crate::sai::D3Pin)),
+        (("sai", "D4"), quote!(crate::sai::D4Pin)),
         (("sai", "WS"), quote!(crate::sai::WsPin)),
         (("spi", "SCK"), quote!(crate::spi::SckPin)),
         (("spi", "MOSI"), quote!(crate::spi::MosiPin)),
@@ -1379,6 +1405,8 @@ fn main() {
         (("lptim", "CH1"), quote!(crate::lptim::Channel1Pin)),
         (("lptim", "CH2"), quote!(crate::lptim::Channel2Pin)),
         (("lptim", "OUT"), quote!(crate::lptim::OutputPin)),
+        (("lptim", "IN1"), quote!(crate::lptim::Input1Pin)),
+        (("lptim", "IN2"), quote!(crate::lptim::Input2Pin)),
         (("sdmmc", "CK"), quote!(crate::sdmmc::CkPin)),
         (("sdmmc", "CKIN"), quote!(crate::sdmmc::CkinPin)),
         (("sdmmc", "CMD"), quote!(crate::sdmmc::CmdPin)),
@@ -2041,6 +2069,10 @@ fn main() {
         (("usart", "TX"), quote!(crate::usart::TxDma)),
         (("lpuart", "RX"), quote!(crate::usart::RxDma)),
         (("lpuart", "TX"), quote!(crate::usart::TxDma)),
+        (("dfsdm", "FLT0"), quote!(crate::dfsdm::Dma<Flt0>)),
+        (("dfsdm", "FLT1"), quote!(crate::dfsdm::Dma<Flt1>)),
+        (("dfsdm", "FLT2"), quote!(crate::dfsdm::Dma<Flt2>)),
+        (("dfsdm", "FLT3"), quote!(crate::dfsdm::Dma<Flt3>)),
         (("sai", "A"), quote!(crate::sai::Dma<A>)),
         (("sai", "B"), quote!(crate::sai::Dma<B>)),
         (("spi", "RX"), quote!(crate::spi::RxDma)),
@@ -2093,6 +2125,8 @@ fn main() {
     .into();
 
     let mut trigger_list: BTreeSet<&str> = BTreeSet::new();
+    // (peripheral, signal) => [(source, signal number)], for the per-instance ADC trigger enums
+    let mut adc_trigger_enums: BTreeMap<(&str, &str), Vec<(&str, u8)>> = BTreeMap::new();
 
     let trigger_expr = Regex::new(r"(?m)(.+?)(\d+)$").unwrap();
 
@@ -2132,6 +2166,13 @@ fn main() {
 
             trigger_list.insert(trigger.source);
 
+            if regs.kind == "adc" && (signal == "ADC_EXT_TRG" || signal == "ADC_JEXT_TRG") {
+                let sources = adc_trigger_enums.entry((p.name, signal)).or_default();
+                if !sources.iter().any(|(source, _)| *source == trigger.source) {
+                    sources.push((trigger.source, idx));
+                }
+            }
+
             if let Some(tr) = triggers.get(&(regs.kind, signal)) {
                 let peri = format_ident!("{}", p.name);
                 let source = format_ident!("{}", trigger.source);
@@ -2234,9 +2275,48 @@ fn main() {
             })
             .collect();
 
+        let adc_trigger_enums: TokenStream = adc_trigger_enums
+            .iter()
+            .map(|((peri, signal), sources)| {
+                let (tr, kind) = match *signal {
+                    "ADC_EXT_TRG" => (quote!(crate::adc::RegularTrigger), "regular"),
+                    _ => (quote!(crate::adc::InjectedTrigger), "injected"),
+                };
+                let name = format_ident!("{}_{}", peri, signal.strip_prefix("ADC_").unwrap());
+                let peri = format_ident!("{}", peri);
+                let doc = format!("Trigger sources for {} conversions of {}.", kind, peri);
+                let variants = sources.iter().map(|(source, _)| format_ident!("{}", source));
+                let arms = sources.iter().map(|(source, idx)| {
+                    let source = format_ident!("{}", source);
+                    quote!(Self::#source => #idx)
+                });
+
+                quote! {
+                    #[doc = #doc]
+                    ///
+                    /// Unlike the source types, this can be chosen at runtime.
+                    #[allow(non_camel_case_types)]
+                    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+                    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+                    pub enum #name {
+                        #(#variants,)*
+                    }
+
+                    impl #tr<crate::peripherals::#peri> for #name {
+                        fn signal(&self) -> u8 {
+                            match self {
+                                #(#arms,)*
+                            }
+                        }
+                    }
+                }
+            })
+            .collect();
+
         g.extend(quote! {
             pub mod triggers {
                 #triggers_mod
+                #adc_trigger_enums
             }
         });
     }
@@ -2325,6 +2405,142 @@ fn main() {
         }
     }
 
+    // ========
+    // Generate the interrupts of configurable EXTI lines
+
+    let mut exti_line_irqs = BTreeSet::new();
+    for p in METADATA.peripherals {
+        if p.name != "EXTI" {
+            continue;
+        }
+        for irq in p.interrupts {
+            let Some(line) = irq.signal.strip_prefix("EXTI").and_then(|n| n.parse::<u8>().ok()) else {
+                continue;
+            };
+            if !(16..32).contains(&line) {
+                continue;
+            }
+            let line_name = format_ident!("Line{}", line);
+            let irq_name = format_ident!("{}", irq.interrupt);
+            g.extend(quote! {
+                #[cfg(feature = "exti")]
+                impl crate::exti::Line for crate::exti::#line_name {
+                    type IRQ = crate::interrupt::typelevel::#irq_name;
+                }
+            });
+            exti_line_irqs.insert(irq.interrupt);
+        }
+    }
+    for irq in exti_line_irqs {
+        let irq = format_ident!("{}", irq);
+        g.extend(quote! {
+            #[cfg(feature = "exti")]
+            impl crate::exti::SealedLineInterrupt for crate::interrupt::typelevel::#irq {}
+            #[cfg(feature = "exti")]
+            impl crate::exti::LineInterrupt for crate::interrupt::typelevel::#irq {}
+        });
+    }
+
+    // ========
+    // Generate RTC interrupts
+
+    let mut rtc_irqs = BTreeSet::new();
+    for p in METADATA.peripherals {
+        if p.registers
+            .as_ref()
+            .is_some_and(|r| r.kind == "rtc" || r.kind == "tamp")
+        {
+            for irq in p.interrupts {
+                rtc_irqs.insert(irq.interrupt);
+            }
+        }
+    }
+    // The RTC driver doesn't support RTCv1.
+    if METADATA.peripherals.iter().any(|p| {
+        p.registers
+            .as_ref()
+            .is_some_and(|r| r.kind == "rtc" && r.version != "v1")
+    }) {
+        for irq in rtc_irqs {
+            let irq = format_ident!("{}", irq);
+            g.extend(quote! {
+                impl crate::rtc::SealedRtcInterrupt for crate::interrupt::typelevel::#irq {}
+                impl crate::rtc::RtcInterrupt for crate::interrupt::typelevel::#irq {}
+            });
+        }
+    }
+
+    // ========
+    // Generate DFSDM filter sets and filter interrupts
+
+    let mut dfsdm_filters = TokenStream::new();
+    for p in METADATA.peripherals {
+        if !p.registers.as_ref().is_some_and(|r| r.kind == "dfsdm") {
+            continue;
+        }
+
+        let pname = format_ident!("{}", p.name);
+        let mut name = p.name.to_ascii_lowercase();
+        name[..1].make_ascii_uppercase();
+        let filters_name = format_ident!("{}Filters", name);
+
+        let mut fields = TokenStream::new();
+        let mut inits = TokenStream::new();
+        let mut irqs: Vec<_> = p
+            .interrupts
+            .iter()
+            .filter_map(|irq| {
+                let n: u8 = irq.signal.strip_prefix("FLT")?.parse().ok()?;
+                Some((n, irq.interrupt))
+            })
+            .collect();
+        irqs.sort();
+        for (n, irq) in irqs {
+            let field = format_ident!("flt{}", n);
+            let flt = format_ident!("Flt{}", n);
+            let irq = format_ident!("{}", irq);
+            fields.extend(quote! {
+                pub #field: crate::dfsdm::FilterPeri<'d, crate::peripherals::#pname, crate::dfsdm::#flt>,
+            });
+            inits.extend(quote! {
+                #field: crate::dfsdm::FilterPeri::new(),
+            });
+            g.extend(quote! {
+                impl crate::dfsdm::FilterInterrupt<crate::dfsdm::#flt> for crate::peripherals::#pname {
+                    type Interrupt = crate::interrupt::typelevel::#irq;
+                }
+            });
+        }
+
+        let doc = format!(
+            "Filters of {}, returned by [`Dfsdm::new()`](crate::dfsdm::Dfsdm::new).",
+            p.name
+        );
+        dfsdm_filters.extend(quote! {
+            #[doc = #doc]
+            #[allow(missing_docs)]
+            pub struct #filters_name<'d> {
+                #fields
+            }
+
+            impl crate::dfsdm::SealedFilters for #filters_name<'_> {
+                fn new() -> Self {
+                    Self { #inits }
+                }
+            }
+        });
+        g.extend(quote! {
+            impl crate::dfsdm::Instance for crate::peripherals::#pname {
+                type Filters<'d> = dfsdm_filters::#filters_name<'d>;
+            }
+        });
+    }
+    if !dfsdm_filters.is_empty() {
+        g.extend(quote! {
+            pub mod dfsdm_filters { #dfsdm_filters }
+        });
+    }
+
     // ========
     // Write peripheral_interrupts module.
     let mut mt = TokenStream::new();
@@ -46,6 +46,12 @@ impl AdcRegs for crate::pac::adc::Adc {
     }
 
     fn enable(&self) {
+        if self.cfgr1().read().autoff() {
+            // In AUTOFF mode the ADC wakes automatically when conversion starts,
+            // so waiting for ADRDY here can stall instead of helping.
+            return;
+        }
+
         if !self.cr().read().aden() {
             self.isr().modify(|w| w.set_adrdy(true));
             self.cr().modify(|w| w.set_aden(true));
@@ -227,6 +233,35 @@ impl<'d, T: Instance<Regs = crate::pac::adc::Adc>> Adc<'d, T> {
         Self { adc }
     }
 
+    /// Enable auto-off mode.
+    ///
+    /// The ADC is powered down automatically when no conversion is ongoing and woken up by the
+    /// next trigger, so it draws no current between conversions while keeping its configuration
+    /// and calibration.
+    pub fn enable_auto_off(&mut self) {
+        T::regs().cfgr1().modify(|reg| reg.set_autoff(true));
+    }
+
+    /// Disable auto-off mode.
+    pub fn disable_auto_off(&mut self) {
+        T::regs().cfgr1().modify(|reg| reg.set_autoff(false));
+    }
+
+    /// Enable wait (auto-delayed conversion) mode.
+    ///
+    /// A new conversion only starts once the previous data has been read, which avoids overruns
+    /// without having to poll and lets the ADC idle in between when combined with auto-off.
+    pub fn enable_auto_delay(&mut self) {
+        T::regs().stop(false);
+        T::regs().cfgr1().modify(|reg| reg.set_wait(true));
+    }
+
+    /// Disable wait (auto-delayed conversion) mode.
+    pub fn disable_auto_delay(&mut self) {
+        T::regs().stop(false);
+        T::regs().cfgr1().modify(|reg| reg.set_wait(false));
+    }
+
     /// Enable reading the voltage reference internal channel.
     pub fn enable_vrefint(&mut self) -> super::VrefInt {
         T::common_regs().ccr().modify(|reg| {
@@ -0,0 +1,119 @@
+//! Factory calibration of the internal ADC channels.
+//!
+//! During production ST measures the internal temperature sensor and voltage reference and stores
+//! the raw readings in system memory. The addresses, calibration temperatures and the supply
+//! voltage used for the measurement differ per family; this module hides those details behind
+//! conversion helpers on [`VrefInt`], [`Temperature`] and [`Vbat`].
+//!
+//! All helpers expect right-aligned 12-bit samples, which is the resolution the factory values
+//! were taken at.
+
+use cfg_if::cfg_if;
+
+use super::{Temperature, Vbat, VrefInt};
+
+cfg_if! {
+    if #[cfg(stm32f3)] {
+        const CAL_VDDA_MV: u32 = 3300;
+        const TS_CAL1: *const u16 = 0x1FFF_F7B8 as _;
+        const TS_CAL2: *const u16 = 0x1FFF_F7C2 as _;
+        const TS_CAL1_TEMP: f32 = 30.0;
+        const TS_CAL2_TEMP: f32 = 110.0;
+        const VBAT_DIVIDER: u32 = 2;
+    } else if #[cfg(stm32f4)] {
+        const CAL_VDDA_MV: u32 = 3300;
+        pub(super) const VREFINT_CAL: *const u16 = 0x1FFF_7A2A as _;
+        const TS_CAL1: *const u16 = 0x1FFF_7A2C as _;
+        const TS_CAL2: *const u16 = 0x1FFF_7A2E as _;
+        const TS_CAL1_TEMP: f32 = 30.0;
+        const TS_CAL2_TEMP: f32 = 110.0;
+        #[cfg(any(stm32f40x, stm32f41x))]
+        const VBAT_DIVIDER: u32 = 2;
+        #[cfg(not(any(stm32f40x, stm32f41x)))]
+        const VBAT_DIVIDER: u32 = 4;
+    } else if #[cfg(stm32l0)] {
+        const CAL_VDDA_MV: u32 = 3000;
+        const TS_CAL1: *const u16 = 0x1FF8_007A as _;
+        const TS_CAL2: *const u16 = 0x1FF8_007E as _;
+        const TS_CAL1_TEMP: f32 = 30.0;
+        const TS_CAL2_TEMP: f32 = 130.0;
+    } else if #[cfg(stm32g0)] {
+        // G0 only provides TS_CAL1, the temperature conversion is not supported.
+        const CAL_VDDA_MV: u32 = 3000;
+        const VBAT_DIVIDER: u32 = 3;
+    } else if #[cfg(any(stm32g4, stm32l4, stm32wb, stm32wl))] {
+        const CAL_VDDA_MV: u32 = 3000;
+        const TS_CAL1: *const u16 = 0x1FFF_75A8 as _;
+        const TS_CAL2: *const u16 = 0x1FFF_75CA as _;
+        const TS_CAL1_TEMP: f32 = 30.0;
+        #[cfg(any(stm32l47x, stm32l48x))]
+        const TS_CAL2_TEMP: f32 = 110.0;
+        #[cfg(not(any(stm32l47x, stm32l48x)))]
+        const TS_CAL2_TEMP: f32 = 130.0;
+        const VBAT_DIVIDER: u32 = 3;
+    } else if #[cfg(stm32l5)] {
+        const CAL_VDDA_MV: u32 = 3000;
+        const TS_CAL1: *const u16 = 0x0BFA_05A8 as _;
+        const TS_CAL2: *const u16 = 0x0BFA_05CA as _;
+        const TS_CAL1_TEMP: f32 = 30.0;
+        const TS_CAL2_TEMP: f32 = 110.0;
+        const VBAT_DIVIDER: u32 = 3;
+    } else if #[cfg(stm32h5)] {
+        const CAL_VDDA_MV: u32 = 3300;
+        pub(super) const VREFINT_CAL: *const u16 = 0x08FF_F810 as _;
+        const TS_CAL1: *const u16 = 0x08FF_F814 as _;
+        const TS_CAL2: *const u16 = 0x08FF_F818 as _;
+        const TS_CAL1_TEMP: f32 = 30.0;
+        const TS_CAL2_TEMP: f32 = 130.0;
+        const VBAT_DIVIDER: u32 = 4;
+    }
+}
+
+#[cfg(not(stm32g0))]
+pub(super) fn read_cal(addr: *const u16) -> u16 {
+    // Safety: the address points into the read-only system memory area of this family.
+    unsafe { core::ptr::read_volatile(addr) }
+}
+
+impl VrefInt {
+    /// Compute the actual VDDA in millivolts from a VREFINT sample.
+    ///
+    /// `VDDA = VDDA_CAL * VREFINT_CAL / sample`, where `VDDA_CAL` is the supply voltage used by the
+    /// factory calibration. Returns `None` if `sample` is zero.
+    pub fn vdda_mv(&self, sample: u16) -> Option<u32> {
+        if sample == 0 {
+            return None;
+        }
+
+        Some(CAL_VDDA_MV * self.calibrated_value() as u32 / sample as u32)
+    }
+}
+
+#[cfg(not(stm32g0))]
+impl Temperature {
+    /// Read the factory calibration points `(TS_CAL1, TS_CAL2)` of the temperature sensor.
+    pub fn calibration_values(&self) -> (u16, u16) {
+        (read_cal(TS_CAL1), read_cal(TS_CAL2))
+    }
+
+    /// Convert a temperature sensor sample to degrees Celsius.
+    ///
+    /// `vdda_mv` is the supply voltage the sample was taken with, typically obtained from
+    /// [`VrefInt::vdda_mv`]. It is used to rescale the sample to the factory calibration voltage.
+    pub fn to_celsius(&self, sample: u16, vdda_mv: u32) -> f32 {
+        let (cal1, cal2) = self.calibration_values();
+        let sample = sample as f32 * vdda_mv as f32 / CAL_VDDA_MV as f32;
+
+        (TS_CAL2_TEMP - TS_CAL1_TEMP) / (cal2 as f32 - cal1 as f32) * (sample - cal1 as f32) + TS_CAL1_TEMP
+    }
+}
+
+#[cfg(not(stm32l0))]
+impl Vbat {
+    /// Convert a VBAT sample to the battery voltage in millivolts.
+    ///
+    /// This compensates for the internal bridge divider in front of the VBAT channel.
+    pub fn to_mv(&self, sample: u16, vdda_mv: u32) -> u32 {
+        sample as u32 * vdda_mv * VBAT_DIVIDER / 4095
+    }
+}
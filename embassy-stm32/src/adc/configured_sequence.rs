@@ -67,3 +67,28 @@ impl<R: AdcRegs> Drop for ConfiguredSequence<'_, R> {
         compiler_fence(Ordering::SeqCst);
     }
 }
+
+/// A [`ConfiguredSequence`] with a length fixed at compile time.
+///
+/// Each [`read`](ScanSequence::read) converts the whole scan once and returns the results as an
+/// array in sequence order, so no separate buffer has to be managed.
+///
+/// Obtain via [`Adc::configure_scan`](super::Adc::configure_scan).
+#[allow(private_bounds)]
+pub struct ScanSequence<'adc, R: AdcRegs, const N: usize> {
+    inner: ConfiguredSequence<'adc, R>,
+}
+
+#[allow(private_bounds)]
+impl<'adc, R: AdcRegs, const N: usize> ScanSequence<'adc, R, N> {
+    pub(crate) fn new(inner: ConfiguredSequence<'adc, R>) -> Self {
+        Self { inner }
+    }
+
+    /// Convert all channels of the scan once and return the results in sequence order.
+    pub async fn read(&mut self) -> [u16; N] {
+        let mut buf = [0u16; N];
+        self.inner.read(&mut buf).await;
+        buf
+    }
+}
@@ -17,6 +17,16 @@ use crate::{Peri, pac, rcc};
 mod injected;
 pub use injected::InjectedAdc;
 
+#[cfg(stm32g4)]
+mod watchdog_g4;
+#[cfg(stm32g4)]
+pub use watchdog_g4::{AnalogWatchdog, Awd1Channels, InterruptHandler, WatchdogChannelMask, WatchdogSelection};
+
+#[cfg(stm32g4)]
+mod timer_sync_g4;
+#[cfg(stm32g4)]
+pub use timer_sync_g4::{SamplingTiming, TimerSyncedAdc};
+
 /// Default VREF voltage used for sample conversion to millivolts.
 pub const VREF_DEFAULT_MV: u32 = 3300;
 /// VREF voltage used for factory calibration of VREFINTCAL register.
@@ -24,6 +34,19 @@ pub const VREF_CALIB_MV: u32 = 3000;
 
 pub const NR_INJECTED_RANKS: usize = 4;
 
+/// Number of user offset registers (OFR1..OFR4).
+pub const NR_OFFSETS: usize = 4;
+
+/// ADC calibration factors as stored in the CALFACT register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Calibration {
+    /// Calibration factor for single-ended conversions (CALFACT_S).
+    pub single_ended: u8,
+    /// Calibration factor for differential conversions (CALFACT_D).
+    pub differential: u8,
+}
+
 /// Max single ADC operation clock frequency
 #[cfg(stm32g4)]
 const MAX_ADC_CLK_FREQ: Hertz = Hertz::mhz(60);
@@ -133,6 +156,21 @@ impl super::AdcRegs for crate::pac::adc::Adc {
         });
     }
 
+    fn configure_trigger_mode(&self, discontinuous: Option<u8>, gated: bool) {
+        self.cfgr().modify(|reg| {
+            reg.set_discen(discontinuous.is_some());
+            reg.set_discnum(discontinuous.unwrap_or(1) - 1);
+        });
+
+        #[cfg(stm32g4)]
+        self.cfgr2().modify(|reg| {
+            reg.set_bulb(false);
+            reg.set_smptrig(gated);
+        });
+        #[cfg(not(stm32g4))]
+        assert!(!gated, "Gated conversion mode is not supported on this ADC");
+    }
+
     fn configure_sequence(&self, sequence: impl ExactSizeIterator<Item = ((u8, bool), SampleTime)>) {
         #[cfg(stm32g4)]
         let mut difsel = DifselReg::default();
@@ -305,25 +343,7 @@ impl<'d, T: DefaultInstance> Adc<'d, T> {
             }
         });
 
-        T::regs().cr().modify(|w| {
-            w.set_adcaldif(Adcaldif::SingleEnded);
-        });
-
-        T::regs().cr().modify(|w| w.set_adcal(true));
-
-        while T::regs().cr().read().adcal() {}
-
-        block_for_us(20);
-
-        T::regs().cr().modify(|w| {
-            w.set_adcaldif(Adcaldif::Differential);
-        });
-
-        T::regs().cr().modify(|w| w.set_adcal(true));
-
-        while T::regs().cr().read().adcal() {}
-
-        block_for_us(20);
+        Self::run_calibration();
 
         T::regs().enable();
 
@@ -363,6 +383,124 @@ impl<'d, T: DefaultInstance> Adc<'d, T> {
         Self { adc }
     }
 
+    /// Run the single-ended and differential calibration. The ADC must be disabled.
+    fn run_calibration() {
+        T::regs().cr().modify(|w| {
+            w.set_adcaldif(Adcaldif::SingleEnded);
+        });
+
+        T::regs().cr().modify(|w| w.set_adcal(true));
+
+        while T::regs().cr().read().adcal() {}
+
+        block_for_us(20);
+
+        T::regs().cr().modify(|w| {
+            w.set_adcaldif(Adcaldif::Differential);
+        });
+
+        T::regs().cr().modify(|w| w.set_adcal(true));
+
+        while T::regs().cr().read().adcal() {}
+
+        block_for_us(20);
+    }
+
+    /// Disable the ADC, waiting until ADDIS has taken effect.
+    fn disable() {
+        T::regs().stop(false);
+
+        if T::regs().cr().read().aden() {
+            T::regs().cr().modify(|w| w.set_addis(true));
+            while T::regs().cr().read().aden() {}
+        }
+    }
+
+    /// Re-run the offset calibration for single-ended and differential inputs.
+    ///
+    /// The ADC is stopped and disabled for the duration of the calibration and re-enabled
+    /// afterwards. The resulting factors are returned so they can be stored and later restored
+    /// with [`Adc::set_calibration`], avoiding the calibration delay after a power cycle.
+    pub fn calibrate(&mut self) -> Calibration {
+        Self::disable();
+        Self::run_calibration();
+        T::regs().enable();
+
+        self.calibration()
+    }
+
+    /// Read the current calibration factors from CALFACT.
+    pub fn calibration(&self) -> Calibration {
+        let calfact = T::regs().calfact().read();
+
+        Calibration {
+            single_ended: calfact.calfact_s(),
+            differential: calfact.calfact_d(),
+        }
+    }
+
+    /// Restore previously saved calibration factors into CALFACT.
+    ///
+    /// CALFACT can only be written while the ADC is enabled and no conversion is ongoing, so
+    /// ongoing regular conversions are stopped first.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        T::regs().stop(false);
+        T::regs().enable();
+
+        T::regs().calfact().write(|w| {
+            w.set_calfact_s(calibration.single_ended);
+            w.set_calfact_d(calibration.differential);
+        });
+    }
+
+    /// Configure one of the user offset registers (OFR1..OFR4) for a channel.
+    ///
+    /// Once enabled, `offset` is subtracted in hardware from every conversion of `channel`
+    /// before the result is written to the data register. This is typically used to remove the
+    /// zero-current offset of a shunt amplifier. Only one offset register should be assigned to
+    /// a given channel.
+    ///
+    /// # Panics
+    /// Panics if `index` is not in `0..NR_OFFSETS`.
+    pub fn set_offset<'a>(&mut self, index: usize, channel: impl super::BorrowedChannel<'a, T>, offset: u16) {
+        assert!(index < NR_OFFSETS, "Offset index must be less than {}", NR_OFFSETS);
+
+        let channel = channel.reborrow_adc();
+
+        T::regs().stop(false);
+        T::regs().ofr(index).write(|w| {
+            w.set_offset_ch(channel.channel());
+            w.set_offset(offset);
+            w.set_offset_en(true);
+        });
+    }
+
+    /// Disable one of the user offset registers (OFR1..OFR4).
+    ///
+    /// # Panics
+    /// Panics if `index` is not in `0..NR_OFFSETS`.
+    pub fn disable_offset(&mut self, index: usize) {
+        assert!(index < NR_OFFSETS, "Offset index must be less than {}", NR_OFFSETS);
+
+        T::regs().stop(false);
+        T::regs().ofr(index).modify(|w| w.set_offset_en(false));
+    }
+
+    /// Enable auto-delayed conversion mode (AUTDLY).
+    ///
+    /// A new regular conversion only starts once the previous data has been read, which avoids
+    /// overruns without having to poll.
+    pub fn enable_auto_delay(&mut self) {
+        T::regs().stop(false);
+        T::regs().cfgr().modify(|reg| reg.set_autdly(true));
+    }
+
+    /// Disable auto-delayed conversion mode (AUTDLY).
+    pub fn disable_auto_delay(&mut self) {
+        T::regs().stop(false);
+        T::regs().cfgr().modify(|reg| reg.set_autdly(false));
+    }
+
     /// Enable reading the voltage reference internal channel.
     pub fn enable_vrefint(&mut self) -> super::VrefInt
     where
@@ -17,6 +17,10 @@
 #[cfg_attr(adc_c0, path = "c0.rs")]
 mod _version;
 
+#[cfg(any(
+    stm32f3, stm32f4, stm32g0, stm32g4, stm32h5, stm32l0, stm32l4, stm32l5, stm32wb, stm32wl
+))]
+mod calibration;
 mod configured_sequence;
 mod ringbuffered;
 
@@ -25,8 +29,8 @@ use core::marker::PhantomData;
 #[allow(unused)]
 #[cfg(not(any(adc_f3v3, adc_wba, adc_wb1)))]
 pub use _version::*;
-pub use configured_sequence::ConfiguredSequence;
-#[cfg(any(adc_f1, adc_f3v1, adc_v1, adc_l0, adc_f3v2, adc_u5, adc_wba))]
+pub use configured_sequence::{ConfiguredSequence, ScanSequence};
+#[cfg(any(adc_f1, adc_f3v1, adc_v1, adc_l0, adc_f3v2, adc_u5, adc_wba, adc_g4))]
 use embassy_sync::waitqueue::AtomicWaker;
 pub use ringbuffered::{OverrunError, RingBufferedAdc};
 
@@ -42,7 +46,9 @@ pub mod adc4;
 use embassy_hal_internal::drop::OnDrop;
 
 pub use crate::pac::adc::vals;
-#[cfg(any(adc_v2, adc_g4, adc_g0, adc_c0, adc_f3v1, adc_wba, adc_u5))]
+#[cfg(any(
+    adc_v2, adc_g4, adc_g0, adc_c0, adc_f3v1, adc_wba, adc_u5, adc_v3, adc_h5, adc_h7rs, adc_u0, adc_v4, adc_u3
+))]
 pub use crate::pac::adc::vals::Exten;
 #[cfg(not(any(adc_f1, adc_f3v3)))]
 pub use crate::pac::adc::vals::Res as Resolution;
@@ -52,26 +58,73 @@ use crate::{peripherals, rcc};
 
 dma_trait!(RxDma, Instance);
 
-#[cfg(not(any(adc_v2, adc_g4, adc_g0, adc_c0, adc_f3v1, adc_wba, adc_u5)))]
+#[cfg(not(any(
+    adc_v2, adc_g4, adc_g0, adc_c0, adc_f3v1, adc_wba, adc_u5, adc_v3, adc_h5, adc_h7rs, adc_u0, adc_v4, adc_u3
+)))]
 /// Trigger edge stub.
 pub struct Exten;
 
+/// External trigger for regular conversions.
+///
+/// Combines a trigger source from [`crate::triggers`] (for example a timer TRGO or CCx event,
+/// or an EXTI line) with the edge it should react to. Only the sources that are wired to the
+/// given ADC instance implement [`RegularTrigger`], so selecting an invalid source is a compile error.
+///
+/// To pick the source at runtime, use the generated per-instance enum instead, e.g.
+/// `triggers::ADC1_EXT_TRG` (and `triggers::ADC1_JEXT_TRG` for injected conversions), which
+/// lists the sources of that ADC.
 pub struct RegularAdcTrigger<T: Instance> {
     _trigger: u8,
     _edge: Exten,
+    _discontinuous: Option<u8>,
+    _gated: bool,
     _marker: PhantomData<T>,
 }
 
 impl<T: Instance> RegularAdcTrigger<T> {
+    /// Create a regular conversion trigger from a trigger source and edge.
     pub fn from(trigger: impl RegularTrigger<T>, edge: Exten) -> Option<Self> {
         Some(Self {
             _trigger: trigger.signal(),
             _edge: edge,
+            _discontinuous: None,
+            _gated: false,
             _marker: PhantomData,
         })
     }
+
+    /// Use discontinuous mode: convert only `count` channels of the sequence per trigger.
+    ///
+    /// This spreads a long scan over several trigger events, e.g. one group of channels per PWM
+    /// period. The sequence restarts from the first channel after the last group.
+    ///
+    /// # Panics
+    /// Panics if `count` is not in `1..=8`, or later when the trigger is applied on an ADC that
+    /// does not support discontinuous mode or a group size other than 1.
+    pub fn discontinuous(mut self, count: u8) -> Self {
+        assert!(
+            (1..=8).contains(&count),
+            "Discontinuous group size must be between 1 and 8"
+        );
+        self._discontinuous = Some(count);
+        self
+    }
+
+    /// Use trigger-gated sampling: the sampling phase lasts as long as the trigger signal is
+    /// high and the conversion starts on its falling edge (SMPTRIG).
+    ///
+    /// The edge passed to [`RegularAdcTrigger::from`] must be the rising edge.
+    #[cfg(adc_g4)]
+    pub fn gated(mut self) -> Self {
+        self._gated = true;
+        self
+    }
 }
 
+/// External trigger for injected conversions.
+///
+/// See [`RegularAdcTrigger`]; only the sources implementing [`InjectedTrigger`] for the ADC instance
+/// can be selected.
 pub struct InjectedAdcTrigger<T: Instance> {
     _trigger: u8,
     _edge: Exten,
@@ -79,6 +132,7 @@ pub struct InjectedAdcTrigger<T: Instance> {
 }
 
 impl<T: Instance> InjectedAdcTrigger<T> {
+    /// Create an injected conversion trigger from a trigger source and edge.
     pub fn from(trigger: impl InjectedTrigger<T>, edge: Exten) -> Self {
         Self {
             _trigger: trigger.signal(),
@@ -108,18 +162,34 @@ impl State {
     }
 }
 
-/// ADC state for U5/WBA with per-AWD trigger flags for the analog watchdog driver.
-#[cfg(any(adc_u5, adc_wba))]
+/// ADC state for G4/U5/WBA with per-AWD trigger flags for the analog watchdog driver.
+#[cfg(any(adc_u5, adc_wba, adc_g4))]
 pub struct State {
+    #[cfg(any(adc_u5, adc_wba))]
     pub waker: AtomicWaker,
+    /// One waker per watchdog, so guards of different watchdogs can wait concurrently.
+    #[cfg(adc_g4)]
+    pub awd_wakers: [AtomicWaker; 3],
+    /// Set while a guard holds the watchdog, so each watchdog is handed out once.
+    #[cfg(adc_g4)]
+    pub awd_taken: [core::sync::atomic::AtomicBool; 3],
     pub awd_triggered: [core::sync::atomic::AtomicBool; 3],
 }
 
-#[cfg(any(adc_u5, adc_wba))]
+#[cfg(any(adc_u5, adc_wba, adc_g4))]
 impl State {
     pub const fn new() -> Self {
         Self {
+            #[cfg(any(adc_u5, adc_wba))]
             waker: AtomicWaker::new(),
+            #[cfg(adc_g4)]
+            awd_wakers: [AtomicWaker::new(), AtomicWaker::new(), AtomicWaker::new()],
+            #[cfg(adc_g4)]
+            awd_taken: [
+                core::sync::atomic::AtomicBool::new(false),
+                core::sync::atomic::AtomicBool::new(false),
+                core::sync::atomic::AtomicBool::new(false),
+            ],
             awd_triggered: [
                 core::sync::atomic::AtomicBool::new(false),
                 core::sync::atomic::AtomicBool::new(false),
@@ -152,6 +222,13 @@ trait AdcRegs: BasicAdcRegs {
     fn configure_dma(&self, conversion_mode: ConversionMode);
     fn configure_sequence(&self, sequence: impl ExactSizeIterator<Item = ((u8, bool), Self::SampleTime)>);
     fn data(&self) -> *mut u16;
+    /// Configure discontinuous and trigger-gated conversion; must be called after `configure_dma`.
+    fn configure_trigger_mode(&self, discontinuous: Option<u8>, gated: bool) {
+        assert!(
+            discontinuous.is_none() && !gated,
+            "Discontinuous and gated conversion modes are not supported on this ADC"
+        );
+    }
 }
 
 #[cfg(any(adc_v2, adc_g4))]
@@ -180,7 +257,7 @@ trait SealedInstance: BasicInstance {
     #[cfg(not(any(adc_f1, adc_v1, adc_l0, adc_f3v3, adc_f3v2, adc_g0)))]
     #[allow(unused)]
     fn common_regs() -> crate::pac::adccommon::AdcCommon;
-    #[cfg(any(adc_f1, adc_f3v1, adc_v1, adc_l0, adc_f3v2, adc_u5, adc_wba))]
+    #[cfg(any(adc_f1, adc_f3v1, adc_v1, adc_l0, adc_f3v2, adc_u5, adc_wba, adc_g4))]
     fn state() -> &'static State;
 }
 
@@ -381,7 +458,13 @@ impl<'d, T: Instance> Adc<'d, T> {
         T::regs().enable();
 
         // Use repeated mode and use the dma to stop the transfer
-        T::regs().configure_dma(ConversionMode::Repeated(trigger.map(|t| (t._trigger, t._edge))));
+        T::regs().configure_dma(ConversionMode::Repeated(
+            trigger.as_ref().map(|t| (t._trigger, t._edge)),
+        ));
+        T::regs().configure_trigger_mode(
+            trigger.as_ref().and_then(|t| t._discontinuous),
+            trigger.as_ref().is_some_and(|t| t._gated),
+        );
 
         let request = rx_dma.request();
         let mut dma_channel = crate::dma::Channel::new(rx_dma, irq);
@@ -447,6 +530,7 @@ impl<'d, T: Instance> Adc<'d, T> {
 
         // Configure DMA once, reused across all subsequent read() calls.
         T::regs().configure_dma(ConversionMode::Repeated(Some((trigger._trigger, trigger._edge))));
+        T::regs().configure_trigger_mode(trigger._discontinuous, trigger._gated);
 
         let dma_request = rx_dma.request();
         let mut dma_channel = crate::dma::Channel::new(rx_dma, irq);
@@ -531,6 +615,40 @@ impl<'d, T: Instance> Adc<'d, T> {
         ConfiguredSequence::new(self, rx_dma, len, irq)
     }
 
+    /// Configure a fixed scan sequence where every channel has its own sampling time.
+    ///
+    /// This is the array counterpart of [`Adc::configure_sequence`]: the `N` channels are
+    /// converted in order during a single scan and transferred with DMA, and
+    /// [`ScanSequence::read`] returns the results as `[u16; N]`.
+    ///
+    /// Example
+    /// ```rust,ignore
+    /// let mut scan = adc.configure_scan(
+    ///     p.DMA1_CH1.reborrow(),
+    ///     [
+    ///         (pin0.reborrow_adc(), SampleTime::CYCLES12_5),
+    ///         (pin1.reborrow_adc(), SampleTime::CYCLES247_5),
+    ///         (vrefint.reborrow_adc(), SampleTime::CYCLES640_5),
+    ///     ],
+    ///     Irqs,
+    /// );
+    /// let [current, voltage, vref] = scan.read().await;
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `N` is zero or exceeds the sequence length supported by the hardware.
+    pub fn configure_scan<'adc, 'ch, const N: usize, D: RxDma<T>>(
+        &'adc mut self,
+        rx_dma: crate::Peri<'adc, D>,
+        sequence: [(BorrowedAdcChannel<'ch, T>, <T::Regs as BasicAdcRegs>::SampleTime); N],
+        irq: impl crate::interrupt::typelevel::Binding<D::Interrupt, crate::dma::InterruptHandler<D>> + 'd,
+    ) -> ScanSequence<'adc, T::Regs, N>
+    where
+        'ch: 'adc,
+    {
+        ScanSequence::new(self.configure_sequence(rx_dma, sequence.into_iter(), irq))
+    }
+
     /// Configures the ADC to use a DMA ring buffer for continuous data acquisition.
     ///
     /// Use the [`Self::read`] method to retrieve measurements from the DMA ring buffer. The read buffer
@@ -576,7 +694,13 @@ impl<'d, T: Instance> Adc<'d, T> {
         );
 
         T::regs().enable();
-        T::regs().configure_dma(ConversionMode::Repeated(trigger.map(|t| (t._trigger, t._edge))));
+        T::regs().configure_dma(ConversionMode::Repeated(
+            trigger.as_ref().map(|t| (t._trigger, t._edge)),
+        ));
+        T::regs().configure_trigger_mode(
+            trigger.as_ref().and_then(|t| t._discontinuous),
+            trigger.as_ref().is_some_and(|t| t._gated),
+        );
 
         core::mem::forget(self);
 
@@ -749,6 +873,12 @@ impl VrefInt {
     pub fn calibrated_value(&self) -> u16 {
         crate::pac::VREFINTCAL.data().read()
     }
+
+    #[cfg(any(stm32f4, stm32h5))]
+    /// The value that vref would be if vdda was at the factory calibration voltage `VREF_CALIB_MV`.
+    pub fn calibrated_value(&self) -> u16 {
+        calibration::read_cal(calibration::VREFINT_CAL)
+    }
 }
 
 /// Internal temperature channel.
@@ -964,7 +1094,7 @@ foreach_adc!(
                 return crate::pac::$common_inst
             }
 
-            #[cfg(any(adc_f1, adc_f3v1, adc_f3v2, adc_v1, adc_l0))]
+            #[cfg(any(adc_f1, adc_f3v1, adc_f3v2, adc_v1, adc_l0, adc_g4))]
             fn state() -> &'static State {
                 static STATE: State = State::new();
                 &STATE
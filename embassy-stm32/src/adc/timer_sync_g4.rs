@@ -0,0 +1,178 @@
+use super::{
+    Adc, BorrowedAdcChannel, DefaultInstance, Exten, OverrunError, RegularAdcTrigger, RegularTrigger, RingBufferedAdc,
+    RxDma,
+};
+use crate::Peri;
+use crate::adc::{Resolution, SampleTime};
+use crate::time::Hertz;
+use crate::timer::GeneralInstance4Channel;
+use crate::timer::low_level::{MasterMode, RoundTo, Timer};
+
+/// Timing of a timer-synchronized ADC pipeline.
+///
+/// Returned by [`TimerSyncedAdc::timing`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SamplingTiming {
+    /// Achieved trigger rate, i.e. the number of full sequences converted per second.
+    ///
+    /// This can differ from the requested rate because of the timer prescaler and period resolution.
+    pub sample_rate: Hertz,
+    /// Time from the trigger edge until the result of the first channel is available, in nanoseconds.
+    ///
+    /// This is the sampling time of the first channel plus its conversion time. The resynchronization
+    /// of the trigger to the ADC clock adds up to a few ADC clock cycles on top of this.
+    pub latency_ns: u32,
+    /// Time needed to convert the whole sequence, in nanoseconds.
+    pub sequence_ns: u32,
+}
+
+/// An ADC ring buffer paced by a timer.
+///
+/// Created by [`Adc::into_timer_synced`]. The timer is running from creation; conversions start with
+/// [`TimerSyncedAdc::start`] or the first [`TimerSyncedAdc::read`].
+pub struct TimerSyncedAdc<'a, T: DefaultInstance, TIM: GeneralInstance4Channel> {
+    adc: RingBufferedAdc<'a, T::Regs>,
+    timer: Timer<'a, TIM>,
+    timing: SamplingTiming,
+}
+
+impl<'a, T: DefaultInstance, TIM: GeneralInstance4Channel> TimerSyncedAdc<'a, T, TIM> {
+    /// Achieved sample rate and latency of the pipeline.
+    pub fn timing(&self) -> SamplingTiming {
+        self.timing
+    }
+
+    /// Start the conversions and the DMA transfer.
+    pub fn start(&mut self) {
+        self.adc.start();
+    }
+
+    /// Stop the conversions, see [`RingBufferedAdc::stop`]. The timer keeps running.
+    pub fn stop(&mut self) {
+        self.adc.stop();
+    }
+
+    /// Read measurements from the DMA ring buffer, see [`RingBufferedAdc::read`].
+    pub async fn read(&mut self, measurements: &mut [u16]) -> Result<usize, OverrunError> {
+        self.adc.read(measurements).await
+    }
+
+    /// Read the most recent measurements, see [`RingBufferedAdc::read_latest`].
+    pub fn read_latest(&mut self, measurements: &mut [u16]) -> usize {
+        self.adc.read_latest(measurements)
+    }
+
+    /// Clear the DMA ring buffer.
+    pub fn clear(&mut self) {
+        self.adc.clear();
+    }
+
+    /// Access the underlying timer, e.g. to retune the sample rate.
+    pub fn timer(&mut self) -> &mut Timer<'a, TIM> {
+        &mut self.timer
+    }
+}
+
+/// Sampling time in half ADC clock cycles.
+fn sample_half_cycles(sample_time: SampleTime) -> u32 {
+    match sample_time {
+        SampleTime::Cycles25 => 5,
+        SampleTime::Cycles65 => 13,
+        SampleTime::Cycles125 => 25,
+        SampleTime::Cycles245 => 49,
+        SampleTime::Cycles475 => 95,
+        SampleTime::Cycles925 => 185,
+        SampleTime::Cycles2475 => 495,
+        SampleTime::Cycles6405 => 1281,
+    }
+}
+
+/// Successive approximation time in half ADC clock cycles.
+fn conversion_half_cycles(resolution: Resolution) -> u32 {
+    match resolution {
+        Resolution::Bits12 => 25,
+        Resolution::Bits10 => 21,
+        Resolution::Bits8 => 17,
+        Resolution::Bits6 => 13,
+    }
+}
+
+fn half_cycles_to_ns(half_cycles: u32, adc_clock: Hertz) -> u32 {
+    (half_cycles as u64 * 500_000_000 / adc_clock.0 as u64) as u32
+}
+
+impl<'d, T: DefaultInstance> Adc<'d, T> {
+    /// Configure a timer, the ADC trigger and a DMA ring buffer for periodic sampling.
+    ///
+    /// `timer` is set up to emit a TRGO on every update event at `sample_rate`, and `trigger` must be
+    /// the TRGO of that same timer (e.g. [`crate::triggers::TIM3_TRGO`] for `TIM3`). Each trigger
+    /// converts the whole `sequence` once, see [`Adc::into_ring_buffered`] for the buffer requirements.
+    ///
+    /// The achieved sample rate and the trigger-to-sample latency are available from
+    /// [`TimerSyncedAdc::timing`].
+    ///
+    /// # Panics
+    /// Panics if the sequence takes longer to convert than one trigger period.
+    pub fn into_timer_synced<'a, 'ch, TIM: GeneralInstance4Channel, D: RxDma<T>>(
+        self,
+        timer: Peri<'a, TIM>,
+        trigger: impl RegularTrigger<T>,
+        sample_rate: Hertz,
+        dma: Peri<'a, D>,
+        dma_buf: &'a mut [u16],
+        irq: impl crate::interrupt::typelevel::Binding<D::Interrupt, crate::dma::InterruptHandler<D>> + 'a,
+        sequence: impl ExactSizeIterator<Item = (BorrowedAdcChannel<'ch, T>, SampleTime)>,
+    ) -> TimerSyncedAdc<'a, T, TIM> {
+        let adc_clock = T::frequency() / T::common_regs().ccr().read().presc();
+        let conversion = conversion_half_cycles(T::regs().cfgr().read().res());
+
+        let mut latency = 0;
+        let mut sequence_time = 0;
+        let sequence = sequence.inspect(|(_, sample_time)| {
+            let cycles = sample_half_cycles(*sample_time) + conversion;
+            if sequence_time == 0 {
+                latency = cycles;
+            }
+            sequence_time += cycles;
+        });
+
+        let adc = self.into_ring_buffered(
+            dma,
+            dma_buf,
+            irq,
+            sequence,
+            RegularAdcTrigger::from(trigger, Exten::RisingEdge),
+        );
+
+        let timer = Timer::new(timer);
+        timer.stop();
+        timer.set_frequency(sample_rate, RoundTo::Slower);
+        timer.set_master_mode(MasterMode::Update);
+        timer.generate_update_event();
+
+        let arr: u32 = timer.get_max_compare_value().into();
+        let period_clocks = (timer.regs_core().psc().read() as u64 + 1) * (arr as u64 + 1);
+        let achieved = Hertz((TIM::frequency().0 as u64 / period_clocks) as u32);
+
+        let timing = SamplingTiming {
+            sample_rate: achieved,
+            latency_ns: half_cycles_to_ns(latency, adc_clock),
+            sequence_ns: half_cycles_to_ns(sequence_time, adc_clock),
+        };
+
+        assert!(
+            timing.sequence_ns as u64 * achieved.0 as u64 <= 1_000_000_000,
+            "ADC sequence does not fit into one trigger period"
+        );
+
+        trace!(
+            "ADC synced to timer at {}, latency {} ns, sequence {} ns",
+            achieved, timing.latency_ns, timing.sequence_ns
+        );
+
+        timer.start();
+
+        TimerSyncedAdc { adc, timer, timing }
+    }
+}
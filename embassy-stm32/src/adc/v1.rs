@@ -283,16 +283,39 @@ impl<'d, T: DefaultInstance> Adc<'d, T> {
         Temperature
     }
 
+    /// Enable auto-off mode.
+    ///
+    /// The ADC is powered down automatically when no conversion is ongoing and woken up by the
+    /// next trigger, so it draws no current between conversions while keeping its configuration
+    /// and calibration.
     #[cfg(adc_l0)]
     pub fn enable_auto_off(&mut self) {
         T::regs().cfgr1().modify(|reg| reg.set_autoff(true));
     }
 
+    /// Disable auto-off mode.
     #[cfg(adc_l0)]
     pub fn disable_auto_off(&mut self) {
         T::regs().cfgr1().modify(|reg| reg.set_autoff(false));
     }
 
+    /// Enable wait (auto-delayed conversion) mode.
+    ///
+    /// A new conversion only starts once the previous data has been read, which avoids overruns
+    /// without having to poll and lets the ADC idle in between when combined with auto-off.
+    #[cfg(adc_l0)]
+    pub fn enable_auto_delay(&mut self) {
+        T::regs().stop(false);
+        T::regs().cfgr1().modify(|reg| reg.set_wait(true));
+    }
+
+    /// Disable wait (auto-delayed conversion) mode.
+    #[cfg(adc_l0)]
+    pub fn disable_auto_delay(&mut self) {
+        T::regs().stop(false);
+        T::regs().cfgr1().modify(|reg| reg.set_wait(false));
+    }
+
     pub fn set_resolution(&mut self, resolution: Resolution) {
         T::regs().cfgr1().modify(|reg| reg.set_res(resolution.into()));
     }
@@ -170,6 +170,15 @@ impl AdcRegs for crate::pac::adc::Adc {
         });
     }
 
+    fn configure_trigger_mode(&self, discontinuous: Option<u8>, gated: bool) {
+        assert!(!gated, "Gated conversion mode is not supported on this ADC");
+
+        self.cr1().modify(|w| {
+            w.set_discen(discontinuous.is_some());
+            w.set_discnum(discontinuous.unwrap_or(1) - 1);
+        });
+    }
+
     fn configure_sequence(&self, sequence: impl ExactSizeIterator<Item = ((u8, bool), SampleTime)>) {
         let mut sqr1 = Sqr1::default();
         let mut sqr2 = Sqr2::default();
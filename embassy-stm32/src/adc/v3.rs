@@ -12,7 +12,7 @@ pub use pac::adc::vals::{Ovsr, Ovss, Presc};
 #[allow(unused_imports)]
 use crate::adc::SealedAdcChannel;
 use crate::adc::{
-    Adc, AdcRegs, Averaging, ConversionMode, Instance, Resolution, SampleTime, Temperature, Vbat, VrefInt,
+    Adc, AdcRegs, Averaging, ConversionMode, Exten, Instance, Resolution, SampleTime, Temperature, Vbat, VrefInt,
 };
 use crate::wait::block_for_us;
 use crate::{Peri, pac, rcc};
@@ -162,7 +162,7 @@ impl super::AdcRegs for crate::pac::adc::Adc {
 
     // Enable ADC only when it is not already running.
     fn enable(&self) {
-        #[cfg(adc_u0)]
+        #[cfg(any(adc_g0, adc_u0))]
         if self.cfgr1().read().autoff() {
             // In AUTOFF mode the ADC wakes automatically when conversion starts,
             // so waiting for ADRDY here can stall instead of helping.
@@ -248,15 +248,36 @@ impl super::AdcRegs for crate::pac::adc::Adc {
             w.set_cont(matches!(conversion_mode, ConversionMode::Repeated(None)));
             w.set_dmacfg(Dmacfg::Circular);
 
-            #[cfg(any(adc_v2, adc_g4, adc_v3, adc_g0, adc_u0, adc_wba, adc_c0))]
-            if let ConversionMode::Repeated(Some((signal, _edge))) = conversion_mode {
-                #[cfg(adc_g0)]
-                w.set_exten(_edge);
+            if let ConversionMode::Repeated(Some((signal, edge))) = conversion_mode {
+                w.set_exten(edge);
                 w.set_extsel(signal.into());
+            } else {
+                // Fall back to software start
+                w.set_exten(Exten::Disabled);
             }
         });
     }
 
+    fn configure_trigger_mode(&self, discontinuous: Option<u8>, gated: bool) {
+        assert!(!gated, "Gated conversion mode is not supported on this ADC");
+
+        // G0 and U0 always convert a single channel per trigger in discontinuous mode.
+        #[cfg(any(adc_g0, adc_u0))]
+        {
+            assert!(
+                matches!(discontinuous, None | Some(1)),
+                "Discontinuous mode only supports a group size of 1 on this ADC"
+            );
+            self.cfgr1().modify(|w| w.set_discen(discontinuous.is_some()));
+        }
+
+        #[cfg(not(any(adc_g0, adc_u0)))]
+        self.cfgr().modify(|w| {
+            w.set_discen(discontinuous.is_some());
+            w.set_discnum(discontinuous.unwrap_or(1) - 1);
+        });
+    }
+
     fn configure_sequence(&self, sequence: impl ExactSizeIterator<Item = ((u8, bool), SampleTime)>) {
         #[cfg(adc_g0)]
         {
@@ -423,9 +444,9 @@ impl<'d, T: Instance<Regs = crate::pac::adc::Adc>> Adc<'d, T> {
 
     /// Calibrate to remove conversion offset
     fn init_calibrate() {
-        #[cfg(adc_u0)]
+        #[cfg(any(adc_g0, adc_u0))]
         let auto_off = T::regs().cfgr1().read().autoff();
-        #[cfg(adc_u0)]
+        #[cfg(any(adc_g0, adc_u0))]
         T::regs().cfgr1().modify(|reg| {
             reg.set_autoff(false);
         });
@@ -438,7 +459,7 @@ impl<'d, T: Instance<Regs = crate::pac::adc::Adc>> Adc<'d, T> {
             // spin
         }
 
-        #[cfg(adc_u0)]
+        #[cfg(any(adc_g0, adc_u0))]
         T::regs().cfgr1().modify(|reg| {
             reg.set_autoff(auto_off);
         });
@@ -572,20 +593,49 @@ impl<'d, T: Instance<Regs = crate::pac::adc::Adc>> Adc<'d, T> {
         }
     }
 
-    #[cfg(adc_u0)]
+    /// Enable auto-off mode.
+    ///
+    /// The ADC is powered down automatically when no conversion is ongoing and woken up by the
+    /// next trigger, so it draws no current between conversions while keeping its configuration
+    /// and calibration.
+    #[cfg(any(adc_g0, adc_u0))]
     pub fn enable_auto_off(&mut self) {
         T::regs().cfgr1().modify(|reg| {
             reg.set_autoff(true);
         });
     }
 
-    #[cfg(adc_u0)]
+    /// Disable auto-off mode.
+    #[cfg(any(adc_g0, adc_u0))]
     pub fn disable_auto_off(&mut self) {
         T::regs().cfgr1().modify(|reg| {
             reg.set_autoff(false);
         });
     }
 
+    /// Enable wait (auto-delayed conversion) mode.
+    ///
+    /// A new conversion only starts once the previous data has been read, which avoids overruns
+    /// without having to poll and lets the ADC idle in between when combined with auto-off.
+    pub fn enable_auto_delay(&mut self) {
+        Self::set_auto_delay(true);
+    }
+
+    /// Disable wait (auto-delayed conversion) mode.
+    pub fn disable_auto_delay(&mut self) {
+        Self::set_auto_delay(false);
+    }
+
+    fn set_auto_delay(enable: bool) {
+        // AUTDLY/WAIT can only be changed while no conversion is ongoing.
+        T::regs().stop(false);
+
+        #[cfg(any(adc_g0, adc_u0))]
+        T::regs().cfgr1().modify(|reg| reg.set_wait(enable));
+        #[cfg(not(any(adc_g0, adc_u0)))]
+        T::regs().cfgr().modify(|reg| reg.set_autdly(enable));
+    }
+
     pub fn enable_vrefint(&mut self) -> VrefInt {
         #[cfg(not(any(adc_g0, adc_u0)))]
         T::common_regs().ccr().modify(|reg| {
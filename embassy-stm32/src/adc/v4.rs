@@ -147,11 +147,19 @@ impl AdcRegs for crate::pac::adc::Adc {
         });
 
         self.cfgr().modify(|w| {
-            w.set_cont(false);
             w.set_dmngt(match conversion_mode {
                 ConversionMode::NoDma => Dmngt::from_bits(0),
                 _ => Dmngt::DmaCircular,
             });
+            w.set_cont(matches!(conversion_mode, ConversionMode::Repeated(None)));
+
+            if let ConversionMode::Repeated(Some((signal, edge))) = conversion_mode {
+                w.set_extsel(signal);
+                w.set_exten(edge);
+            } else {
+                // Fall back to software start
+                w.set_exten(Exten::Disabled);
+            }
         });
     }
 
@@ -0,0 +1,274 @@
+use core::future::poll_fn;
+use core::marker::PhantomData;
+use core::sync::atomic::Ordering;
+use core::task::Poll;
+
+use crate::adc::{Adc, AdcChannel, DefaultInstance};
+use crate::interrupt;
+use crate::interrupt::typelevel::Interrupt;
+
+/// Number of channels that can be selected in AWD2CR/AWD3CR.
+const NR_AWD_CHANNELS: usize = 19;
+
+/// Interrupt handler for the analog watchdogs.
+pub struct InterruptHandler<T: DefaultInstance> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: DefaultInstance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandler<T> {
+    unsafe fn on_interrupt() {
+        let isr = T::regs().isr().read();
+        let ier = T::regs().ier().read();
+
+        let triggered = |i| ier.awdie(i) && isr.awd(i);
+        if !(0..3).any(triggered) {
+            return;
+        }
+
+        // Disable AWDIE + clear ISR flag to deassert the interrupt line.
+        T::regs().ier().modify(|w| {
+            for i in (0..3).filter(|&i| triggered(i)) {
+                w.set_awdie(i, false);
+            }
+        });
+        T::regs().isr().write(|w| {
+            for i in (0..3).filter(|&i| triggered(i)) {
+                w.set_awd(i, true);
+            }
+        });
+
+        for i in (0..3).filter(|&i| triggered(i)) {
+            T::state().awd_triggered[i].store(true, Ordering::Release);
+            T::state().awd_wakers[i].wake();
+        }
+    }
+}
+
+/// Channel selection for the first analog watchdog (AWD1).
+///
+/// AWD1 compares 12-bit thresholds and monitors both regular and injected conversions of the
+/// selected channels.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Awd1Channels {
+    /// Monitor all regular and injected channels.
+    All,
+    /// Monitor a single channel.
+    Single(u8),
+}
+
+impl Awd1Channels {
+    /// Monitor a single channel.
+    pub fn from_channel<T>(channel: &impl AdcChannel<T>) -> Self {
+        Self::Single(channel.channel())
+    }
+}
+
+/// Channel bitmask for the second and third analog watchdogs (AWD2/AWD3).
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WatchdogChannelMask(u32);
+
+impl WatchdogChannelMask {
+    /// Create an empty selection.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Monitor a single channel.
+    pub fn from_channel<T>(channel: &impl AdcChannel<T>) -> Self {
+        Self::new().add_channel(channel)
+    }
+
+    /// Add a channel to the selection.
+    pub fn add_channel<T>(self, channel: &impl AdcChannel<T>) -> Self {
+        Self(self.0 | channel_bit(channel.channel()))
+    }
+}
+
+/// Select which of the three hardware analog watchdogs to use, and the channels it monitors.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchdogSelection {
+    /// First analog watchdog (AWD1), 12-bit thresholds.
+    Awd1(Awd1Channels),
+    /// Second analog watchdog (AWD2), 8-bit thresholds.
+    Awd2(WatchdogChannelMask),
+    /// Third analog watchdog (AWD3), 8-bit thresholds.
+    Awd3(WatchdogChannelMask),
+}
+
+impl WatchdogSelection {
+    fn index(&self) -> usize {
+        match self {
+            WatchdogSelection::Awd1(_) => 0,
+            WatchdogSelection::Awd2(_) => 1,
+            WatchdogSelection::Awd3(_) => 2,
+        }
+    }
+}
+
+fn check_channel(ch: u8) {
+    assert!((ch as usize) < NR_AWD_CHANNELS, "ADC channel {} cannot be watched", ch);
+}
+
+fn channel_bit(ch: u8) -> u32 {
+    check_channel(ch);
+    1 << ch
+}
+
+/// A driver for one of the ADC analog watchdogs.
+///
+/// Created by [`Adc::enable_watchdog`]. The guard does not borrow the [`Adc`], so conversions
+/// can keep running (e.g. a DMA ring buffer) while one or more watchdogs supervise them. Each
+/// watchdog has its own event, so several guards can be awaited concurrently.
+///
+/// Dropping the guard disables the watchdog and its interrupt, and releases it so it can be
+/// enabled again.
+pub struct AnalogWatchdog<T: DefaultInstance> {
+    index: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: DefaultInstance> AnalogWatchdog<T> {
+    /// Wait until a monitored channel leaves the threshold window.
+    ///
+    /// Conversions must be running, either triggered or continuous, for the watchdog to
+    /// observe samples.
+    pub async fn wait(&mut self) {
+        let index = self.index;
+
+        T::state().awd_triggered[index].store(false, Ordering::Release);
+        T::regs().isr().write(|w| w.set_awd(index, true));
+        T::regs().ier().modify(|w| w.set_awdie(index, true));
+
+        poll_fn(|cx| {
+            T::state().awd_wakers[index].register(cx.waker());
+
+            if T::state().awd_triggered[index].load(Ordering::Acquire) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+
+    /// Update the threshold window without reconfiguring the channel selection.
+    ///
+    /// Thresholds are 12-bit values in the same space as right-aligned 12-bit results. AWD2
+    /// and AWD3 only compare the 8 most significant bits.
+    pub fn set_thresholds(&mut self, low_threshold: u16, high_threshold: u16) {
+        set_thresholds::<T>(self.index, low_threshold, high_threshold);
+    }
+}
+
+impl<T: DefaultInstance> Drop for AnalogWatchdog<T> {
+    fn drop(&mut self) {
+        T::regs().ier().modify(|w| w.set_awdie(self.index, false));
+
+        match self.index {
+            0 => T::regs().cfgr().modify(|w| w.set_awd1en(false)),
+            1 => T::regs().awd2cr().write_value(Default::default()),
+            _ => T::regs().awd3cr().write_value(Default::default()),
+        }
+
+        T::state().awd_taken[self.index].store(false, Ordering::Release);
+    }
+}
+
+fn set_thresholds<T: DefaultInstance>(index: usize, low_threshold: u16, high_threshold: u16) {
+    assert!(
+        low_threshold <= high_threshold,
+        "low_threshold must be <= high_threshold"
+    );
+
+    match index {
+        0 => T::regs().tr1().modify(|w| {
+            w.set_lt1(low_threshold);
+            w.set_ht1(high_threshold);
+        }),
+        // AWD2/AWD3 compare against the 8 MSBs of the 12-bit result.
+        1 => T::regs().tr2().modify(|w| {
+            w.set_lt2((low_threshold >> 4) as u8);
+            w.set_ht2((high_threshold >> 4) as u8);
+        }),
+        _ => T::regs().tr3().modify(|w| {
+            w.set_lt3((low_threshold >> 4) as u8);
+            w.set_ht3((high_threshold >> 4) as u8);
+        }),
+    }
+}
+
+impl<'d, T: DefaultInstance> Adc<'d, T> {
+    /// Enable an analog watchdog and return a guard.
+    ///
+    /// `watchdog` selects which of the three hardware watchdogs to use and the channels it
+    /// monitors; each has its own thresholds and channel selection, so up to three supply rails
+    /// or signals can be supervised independently. `low_threshold` and `high_threshold` are
+    /// 12-bit values; the watchdog fires when a sample falls outside
+    /// `[low_threshold, high_threshold]`.
+    ///
+    /// Returns `None` if the selected watchdog is already held by another guard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low_threshold > high_threshold`, or if an [`Awd1Channels::Single`] channel
+    /// does not exist.
+    #[must_use]
+    pub fn enable_watchdog(
+        &mut self,
+        watchdog: WatchdogSelection,
+        low_threshold: u16,
+        high_threshold: u16,
+        _irq: impl interrupt::typelevel::Binding<T::Interrupt, InterruptHandler<T>> + 'd,
+    ) -> Option<AnalogWatchdog<T>> {
+        let index = watchdog.index();
+
+        if let WatchdogSelection::Awd1(Awd1Channels::Single(ch)) = watchdog {
+            check_channel(ch);
+        }
+
+        if T::state().awd_taken[index].swap(true, Ordering::AcqRel) {
+            return None;
+        }
+
+        set_thresholds::<T>(index, low_threshold, high_threshold);
+
+        match watchdog {
+            WatchdogSelection::Awd1(channels) => T::regs().cfgr().modify(|w| {
+                match channels {
+                    Awd1Channels::All => w.set_awd1sgl(false),
+                    Awd1Channels::Single(ch) => {
+                        w.set_awd1sgl(true);
+                        w.set_awd1ch(ch);
+                    }
+                }
+                // Watch injected conversions too, so AWD1 also covers channels that are only
+                // sampled through the injected group.
+                w.set_awd1en(true);
+                w.set_jawd1en(true);
+            }),
+            WatchdogSelection::Awd2(WatchdogChannelMask(mask)) => T::regs().awd2cr().write(|w| {
+                for n in 0..NR_AWD_CHANNELS {
+                    w.set_awd2ch(n, mask & (1 << n) != 0);
+                }
+            }),
+            WatchdogSelection::Awd3(WatchdogChannelMask(mask)) => T::regs().awd3cr().write(|w| {
+                for n in 0..NR_AWD_CHANNELS {
+                    w.set_awd3ch(n, mask & (1 << n) != 0);
+                }
+            }),
+        }
+
+        T::Interrupt::unpend();
+        unsafe {
+            T::Interrupt::enable();
+        }
+
+        Some(AnalogWatchdog {
+            index,
+            _marker: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use aead::consts::{U0, U12, U13, U16};
+use aead::{AeadCore, AeadMutInPlace, Nonce, Tag};
+
+use super::{
+    AES_BLOCK_SIZE, Aes, AesCcm, AesGcm, Cipher, CipherAuthenticated, CipherSized, Direction, IVSized, Instance,
+};
+use crate::mode::Mode;
+
+/// AES-GCM with a 96-bit nonce and 128-bit tag, implementing the [`aead`] traits.
+///
+/// Data is processed with the blocking API, so this works with drivers in either mode.
+pub struct GcmAead<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> {
+    aes: &'a mut Aes<'d, T, M>,
+    key: [u8; KEY_SIZE],
+}
+
+impl<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> GcmAead<'a, 'd, T, M, KEY_SIZE> {
+    /// Creates an AES-GCM AEAD on `aes`, with `key` (16 or 32 bytes).
+    pub fn new(aes: &'a mut Aes<'d, T, M>, key: &[u8; KEY_SIZE]) -> Self {
+        Self { aes, key: *key }
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> AeadCore for GcmAead<'_, '_, T, M, KEY_SIZE> {
+    type NonceSize = U12;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> AeadMutInPlace for GcmAead<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesGcm<'c, KEY_SIZE>: CipherSized,
+{
+    fn encrypt_in_place_detached(
+        &mut self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        let nonce: [u8; 12] = (*nonce).into();
+        let cipher = AesGcm::new(&self.key, &nonce);
+        let tag = seal(self.aes, &cipher, Direction::Encrypt, associated_data, buffer)?;
+        Ok(tag.into())
+    }
+
+    fn decrypt_in_place_detached(
+        &mut self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        let nonce: [u8; 12] = (*nonce).into();
+        let cipher = AesGcm::new(&self.key, &nonce);
+        let computed = seal(self.aes, &cipher, Direction::Decrypt, associated_data, buffer)?;
+        check_tag(&computed, tag, buffer)
+    }
+}
+
+/// AES-CCM with a 104-bit nonce and 128-bit tag, implementing the [`aead`] traits.
+///
+/// The AAD length prefix required by CCM is added by the adapter. Like [`GcmAead`], data is
+/// processed with the blocking API.
+pub struct CcmAead<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> {
+    aes: &'a mut Aes<'d, T, M>,
+    key: [u8; KEY_SIZE],
+}
+
+impl<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> CcmAead<'a, 'd, T, M, KEY_SIZE> {
+    /// Creates an AES-CCM AEAD on `aes`, with `key` (16 or 32 bytes).
+    pub fn new(aes: &'a mut Aes<'d, T, M>, key: &[u8; KEY_SIZE]) -> Self {
+        Self { aes, key: *key }
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> AeadCore for CcmAead<'_, '_, T, M, KEY_SIZE> {
+    type NonceSize = U13;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> AeadMutInPlace for CcmAead<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesCcm<'c, KEY_SIZE, 13, 16>: CipherSized,
+{
+    fn encrypt_in_place_detached(
+        &mut self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> aead::Result<Tag<Self>> {
+        let nonce: [u8; 13] = (*nonce).into();
+        let cipher = AesCcm::<KEY_SIZE, 13, 16>::new(&self.key, &nonce, associated_data.len(), buffer.len());
+        let tag = seal(self.aes, &cipher, Direction::Encrypt, associated_data, buffer)?;
+        Ok(tag.into())
+    }
+
+    fn decrypt_in_place_detached(
+        &mut self,
+        nonce: &Nonce<Self>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag<Self>,
+    ) -> aead::Result<()> {
+        let nonce: [u8; 13] = (*nonce).into();
+        let cipher = AesCcm::<KEY_SIZE, 13, 16>::new(&self.key, &nonce, associated_data.len(), buffer.len());
+        let computed = seal(self.aes, &cipher, Direction::Decrypt, associated_data, buffer)?;
+        check_tag(&computed, tag, buffer)
+    }
+}
+
+/// Run a complete GCM/CCM operation in place, returning the computed tag.
+fn seal<'c, T, M, C>(
+    aes: &mut Aes<'_, T, M>,
+    cipher: &'c C,
+    dir: Direction,
+    associated_data: &[u8],
+    buffer: &mut [u8],
+) -> aead::Result<[u8; 16]>
+where
+    T: Instance,
+    M: Mode,
+    C: Cipher<'c> + CipherSized + IVSized + CipherAuthenticated<16>,
+{
+    let mut ctx = aes.start(cipher, dir);
+
+    if !associated_data.is_empty() {
+        if cipher.is_ccm_mode() {
+            let (prefix, prefix_len) = cipher.ccm_format_aad_header(associated_data.len());
+            aes.aad_blocking(&mut ctx, &prefix[..prefix_len], false)
+                .map_err(|_| aead::Error)?;
+        }
+        aes.aad_blocking(&mut ctx, associated_data, true)
+            .map_err(|_| aead::Error)?;
+    }
+
+    // The payload is processed block by block through a copy, as input and output can't alias.
+    let blocks = buffer.len().div_ceil(AES_BLOCK_SIZE);
+    for (i, chunk) in buffer.chunks_mut(AES_BLOCK_SIZE).enumerate() {
+        let mut input = [0u8; AES_BLOCK_SIZE];
+        input[..chunk.len()].copy_from_slice(chunk);
+        aes.payload_blocking(&mut ctx, &input[..chunk.len()], chunk, i + 1 == blocks)
+            .map_err(|_| aead::Error)?;
+    }
+    if blocks == 0 {
+        aes.payload_blocking(&mut ctx, &[], &mut [], true)
+            .map_err(|_| aead::Error)?;
+    }
+
+    aes.finish_blocking(ctx).map_err(|_| aead::Error)?.ok_or(aead::Error)
+}
+
+/// Compare tags in constant time, and wipe the decrypted data on a mismatch.
+fn check_tag(computed: &[u8; 16], tag: &[u8], buffer: &mut [u8]) -> aead::Result<()> {
+    // Don't return early on a mismatch, to not leak where it is.
+    if computed.iter().zip(tag).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0 {
+        Ok(())
+    } else {
+        buffer.fill(0);
+        Err(aead::Error)
+    }
+}
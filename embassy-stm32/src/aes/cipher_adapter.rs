@@ -0,0 +1,269 @@
+use cipher::consts::{U1, U16};
+use cipher::inout::{InOut, InOutBuf};
+use cipher::{
+    Block, BlockBackend, BlockClosure, BlockDecryptMut, BlockEncryptMut, BlockSizeUser, ParBlocksSizeUser,
+    StreamCipher, StreamCipherError,
+};
+
+use super::{AES_BLOCK_SIZE, Aes, AesCbc, AesCtr, AesEcb, Cipher, CipherSized, Context, Direction, IVSized, Instance};
+use crate::mode::Mode;
+
+/// AES in ECB mode, implementing the [`cipher`] block cipher traits.
+///
+/// Every call starts the peripheral with the key of the adapter, so it can be used in between
+/// other operations on the same driver.
+pub struct EcbCipher<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> {
+    aes: &'a mut Aes<'d, T, M>,
+    key: [u8; KEY_SIZE],
+}
+
+impl<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> EcbCipher<'a, 'd, T, M, KEY_SIZE> {
+    /// Creates an AES block cipher on `aes`, with `key` (16 or 32 bytes).
+    pub fn new(aes: &'a mut Aes<'d, T, M>, key: &[u8; KEY_SIZE]) -> Self {
+        Self { aes, key: *key }
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockSizeUser for EcbCipher<'_, '_, T, M, KEY_SIZE> {
+    type BlockSize = U16;
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockEncryptMut for EcbCipher<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesEcb<'c, KEY_SIZE>: CipherSized,
+{
+    fn encrypt_with_backend_mut(&mut self, f: impl BlockClosure<BlockSize = U16>) {
+        let cipher = AesEcb::new(&self.key);
+        process_blocks(self.aes, &cipher, Direction::Encrypt, [0; 16], f);
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockDecryptMut for EcbCipher<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesEcb<'c, KEY_SIZE>: CipherSized,
+{
+    fn decrypt_with_backend_mut(&mut self, f: impl BlockClosure<BlockSize = U16>) {
+        let cipher = AesEcb::new(&self.key);
+        process_blocks(self.aes, &cipher, Direction::Decrypt, [0; 16], f);
+    }
+}
+
+/// AES-CBC encryption, implementing [`BlockEncryptMut`].
+///
+/// The chaining value is kept by the adapter between calls, so a message can be encrypted in
+/// several calls, interleaved with other operations on the same driver.
+pub struct CbcEncryptor<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> {
+    aes: &'a mut Aes<'d, T, M>,
+    key: [u8; KEY_SIZE],
+    iv: [u8; 16],
+}
+
+impl<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> CbcEncryptor<'a, 'd, T, M, KEY_SIZE> {
+    /// Creates an AES-CBC encryptor on `aes`, with `key` (16 or 32 bytes) and `iv`.
+    pub fn new(aes: &'a mut Aes<'d, T, M>, key: &[u8; KEY_SIZE], iv: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            key: *key,
+            iv: *iv,
+        }
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockSizeUser for CbcEncryptor<'_, '_, T, M, KEY_SIZE> {
+    type BlockSize = U16;
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockEncryptMut for CbcEncryptor<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesCbc<'c, KEY_SIZE>: CipherSized,
+{
+    fn encrypt_with_backend_mut(&mut self, f: impl BlockClosure<BlockSize = U16>) {
+        let iv = self.iv;
+        let cipher = AesCbc::new(&self.key, &iv);
+        self.iv = process_blocks(self.aes, &cipher, Direction::Encrypt, iv, f);
+    }
+}
+
+/// AES-CBC decryption, implementing [`BlockDecryptMut`].
+///
+/// Like [`CbcEncryptor`], the chaining value is kept by the adapter between calls.
+pub struct CbcDecryptor<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> {
+    aes: &'a mut Aes<'d, T, M>,
+    key: [u8; KEY_SIZE],
+    iv: [u8; 16],
+}
+
+impl<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> CbcDecryptor<'a, 'd, T, M, KEY_SIZE> {
+    /// Creates an AES-CBC decryptor on `aes`, with `key` (16 or 32 bytes) and `iv`.
+    pub fn new(aes: &'a mut Aes<'d, T, M>, key: &[u8; KEY_SIZE], iv: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            key: *key,
+            iv: *iv,
+        }
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockSizeUser for CbcDecryptor<'_, '_, T, M, KEY_SIZE> {
+    type BlockSize = U16;
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> BlockDecryptMut for CbcDecryptor<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesCbc<'c, KEY_SIZE>: CipherSized,
+{
+    fn decrypt_with_backend_mut(&mut self, f: impl BlockClosure<BlockSize = U16>) {
+        let iv = self.iv;
+        let cipher = AesCbc::new(&self.key, &iv);
+        self.iv = process_blocks(self.aes, &cipher, Direction::Decrypt, iv, f);
+    }
+}
+
+/// AES-CTR, implementing [`StreamCipher`].
+///
+/// The counter is the last 32 bits of the counter block, big-endian, incremented by the
+/// peripheral like `ctr::Ctr32BE`. The position in the keystream is kept by the adapter between
+/// calls, which can have any length.
+pub struct CtrCipher<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> {
+    aes: &'a mut Aes<'d, T, M>,
+    key: [u8; KEY_SIZE],
+    counter: [u8; 16],
+    /// Keystream of the last partial block, and how much of it is used.
+    keystream: [u8; 16],
+    keystream_pos: usize,
+}
+
+impl<'a, 'd, T: Instance, M: Mode, const KEY_SIZE: usize> CtrCipher<'a, 'd, T, M, KEY_SIZE> {
+    /// Creates an AES-CTR stream cipher on `aes`, with `key` (16 or 32 bytes) and the initial
+    /// counter block `iv`.
+    pub fn new(aes: &'a mut Aes<'d, T, M>, key: &[u8; KEY_SIZE], iv: &[u8; 16]) -> Self {
+        Self {
+            aes,
+            key: *key,
+            counter: *iv,
+            keystream: [0; 16],
+            keystream_pos: AES_BLOCK_SIZE,
+        }
+    }
+
+    fn increment_counter(&mut self, blocks: usize) {
+        let low = u32::from_be_bytes(unwrap!(self.counter[12..].try_into()));
+        self.counter[12..].copy_from_slice(&low.wrapping_add(blocks as u32).to_be_bytes());
+    }
+}
+
+impl<T: Instance, M: Mode, const KEY_SIZE: usize> StreamCipher for CtrCipher<'_, '_, T, M, KEY_SIZE>
+where
+    for<'c> AesCtr<'c, KEY_SIZE>: CipherSized,
+{
+    fn try_apply_keystream_inout(&mut self, mut buf: InOutBuf<'_, '_, u8>) -> Result<(), StreamCipherError> {
+        let len = buf.len();
+
+        // Use up the keystream left from a previous partial block.
+        let mut pos = core::cmp::min(AES_BLOCK_SIZE - self.keystream_pos, len);
+        let mut input = [0; AES_BLOCK_SIZE];
+        input[..pos].copy_from_slice(&buf.get_in()[..pos]);
+        let keystream = &self.keystream[self.keystream_pos..self.keystream_pos + pos];
+        xor(&mut buf.get_out()[..pos], &input[..pos], keystream);
+        self.keystream_pos += pos;
+        if pos == len {
+            return Ok(());
+        }
+
+        let counter = self.counter;
+        let cipher = AesCtr::new(&self.key, &counter);
+        let mut ctx = self.aes.start(&cipher, Direction::Encrypt);
+
+        let mut blocks = 0;
+        while pos < len {
+            let n = core::cmp::min(AES_BLOCK_SIZE, len - pos);
+            let mut input = [0; AES_BLOCK_SIZE];
+            input[..n].copy_from_slice(&buf.get_in()[pos..pos + n]);
+            let mut output = [0; AES_BLOCK_SIZE];
+            // A partial block is run as a full block of zeros, to keep its keystream.
+            let block = if n == AES_BLOCK_SIZE {
+                input
+            } else {
+                [0; AES_BLOCK_SIZE]
+            };
+            unwrap!(self.aes.payload_blocking(&mut ctx, &block, &mut output, false));
+
+            if n == AES_BLOCK_SIZE {
+                buf.get_out()[pos..pos + n].copy_from_slice(&output);
+            } else {
+                xor(&mut buf.get_out()[pos..pos + n], &input[..n], &output[..n]);
+                self.keystream = output;
+                self.keystream_pos = n;
+            }
+            blocks += 1;
+            pos += n;
+        }
+
+        unwrap!(self.aes.finish_blocking(ctx));
+        self.increment_counter(blocks);
+        Ok(())
+    }
+}
+
+fn xor(out: &mut [u8], a: &[u8], b: &[u8]) {
+    for (o, (a, b)) in out.iter_mut().zip(a.iter().zip(b)) {
+        *o = a ^ b;
+    }
+}
+
+/// Run the blocks of a [`BlockClosure`] through the peripheral, returning the CBC chaining value.
+fn process_blocks<'c, T, M, C>(
+    aes: &mut Aes<'_, T, M>,
+    cipher: &'c C,
+    dir: Direction,
+    iv: [u8; 16],
+    f: impl BlockClosure<BlockSize = U16>,
+) -> [u8; 16]
+where
+    T: Instance,
+    M: Mode,
+    C: Cipher<'c> + CipherSized + IVSized,
+{
+    let ctx = aes.start(cipher, dir);
+    let mut backend = Backend {
+        aes,
+        ctx,
+        dir,
+        chain: iv,
+    };
+    f.call(&mut backend);
+    let Backend { aes, ctx, chain, .. } = backend;
+    unwrap!(aes.finish_blocking(ctx));
+    chain
+}
+
+struct Backend<'x, 'd, 'c, T: Instance, M: Mode, C: Cipher<'c>> {
+    aes: &'x mut Aes<'d, T, M>,
+    ctx: Context<'c, C>,
+    dir: Direction,
+    /// Last ciphertext block.
+    chain: [u8; 16],
+}
+
+impl<'c, T: Instance, M: Mode, C: Cipher<'c>> BlockSizeUser for Backend<'_, '_, 'c, T, M, C> {
+    type BlockSize = U16;
+}
+
+impl<'c, T: Instance, M: Mode, C: Cipher<'c>> ParBlocksSizeUser for Backend<'_, '_, 'c, T, M, C> {
+    type ParBlocksSize = U1;
+}
+
+impl<'c, T: Instance, M: Mode, C: Cipher<'c>> BlockBackend for Backend<'_, '_, 'c, T, M, C> {
+    fn proc_block(&mut self, mut block: InOut<'_, '_, Block<Self>>) {
+        // Input and output may be the same buffer.
+        let input = block.clone_in();
+        unwrap!(
+            self.aes
+                .payload_blocking(&mut self.ctx, input.as_slice(), block.get_out().as_mut_slice(), false)
+        );
+        self.chain = match self.dir {
+            Direction::Encrypt => unwrap!(block.get_out().as_slice().try_into()),
+            Direction::Decrypt => unwrap!(input.as_slice().try_into()),
+        };
+    }
+}
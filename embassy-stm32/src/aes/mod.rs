@@ -154,6 +154,17 @@ use crate::mode::{Async, Blocking, Mode};
 use crate::{interrupt, pac, peripherals, rcc};
 
 const AES_BLOCK_SIZE: usize = 16; // 128 bits
+/// Data moved by a single DMA transfer, through buffers on the stack.
+const DMA_CHUNK_SIZE: usize = 16 * AES_BLOCK_SIZE;
+
+#[cfg(feature = "cipher")]
+mod cipher_adapter;
+#[cfg(feature = "cipher")]
+pub use cipher_adapter::*;
+#[cfg(feature = "aead")]
+mod aead_adapter;
+#[cfg(feature = "aead")]
+pub use aead_adapter::*;
 
 static AES_WAKER: AtomicWaker = AtomicWaker::new();
 
@@ -166,10 +177,9 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
     unsafe fn on_interrupt() {
         let sr = T::regs().sr().read();
 
-        // Wake on completion flag
+        // Wake on completion flag, which is cleared by the waiting task
         if sr.ccf() {
-            // Clear all interrupt flags by writing 1s to ICR
-            T::regs().icr().write(|w| w.0 = 0xFFFF_FFFF);
+            T::regs().ier().modify(|w| w.set_ccfie(false));
             AES_WAKER.wake();
         }
 
@@ -180,6 +190,25 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
     }
 }
 
+/// Wait for the computation complete flag (CCF) with the interrupt. The flag is not cleared.
+async fn wait_complete(p: pac::aes::Aes) {
+    poll_fn(|cx| {
+        if p.sr().read().ccf() {
+            return Poll::Ready(());
+        }
+        AES_WAKER.register(cx.waker());
+        p.ier().modify(|w| w.set_ccfie(true));
+        // Re-check after registering waker
+        if p.sr().read().ccf() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    p.ier().modify(|w| w.set_ccfie(false));
+}
+
 /// AES error
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -568,21 +597,8 @@ impl<'c, const KEY_SIZE: usize> Cipher<'c> for AesGcm<'c, KEY_SIZE> {
         // Set GCM phase to init (GCMPH = 0)
         p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(0)));
         p.cr().modify(|w| w.set_en(true));
-        // Wait for completion
-        poll_fn(|cx| {
-            if p.sr().read().ccf() {
-                Poll::Ready(())
-            } else {
-                AES_WAKER.register(cx.waker());
-                // Re-check after registering waker
-                if p.sr().read().ccf() {
-                    Poll::Ready(())
-                } else {
-                    Poll::Pending
-                }
-            }
-        })
-        .await;
+        wait_complete(p).await;
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
     }
 
     fn pre_final(&self, p: pac::aes::Aes, _dir: Direction, padding_len: usize) -> [u32; 4] {
@@ -674,19 +690,8 @@ impl<'c, const KEY_SIZE: usize> Cipher<'c> for AesGmac<'c, KEY_SIZE> {
     async fn init_phase<T: Instance>(&self, p: pac::aes::Aes, _aes: &mut Aes<'_, T, Async>) {
         p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(0)));
         p.cr().modify(|w| w.set_en(true));
-        poll_fn(|cx| {
-            if p.sr().read().ccf() {
-                Poll::Ready(())
-            } else {
-                AES_WAKER.register(cx.waker());
-                if p.sr().read().ccf() {
-                    Poll::Ready(())
-                } else {
-                    Poll::Pending
-                }
-            }
-        })
-        .await;
+        wait_complete(p).await;
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
     }
 
     fn uses_gcm_phases(&self) -> bool {
@@ -790,21 +795,8 @@ impl<'c, const KEY_SIZE: usize, const IV_SIZE: usize, const TAG_SIZE: usize> Cip
         // Set CCM phase to init (GCMPH = 0)
         p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(0)));
         p.cr().modify(|w| w.set_en(true));
-        // Wait for completion
-        poll_fn(|cx| {
-            if p.sr().read().ccf() {
-                Poll::Ready(())
-            } else {
-                AES_WAKER.register(cx.waker());
-                // Re-check after registering waker
-                if p.sr().read().ccf() {
-                    Poll::Ready(())
-                } else {
-                    Poll::Pending
-                }
-            }
-        })
-        .await;
+        wait_complete(p).await;
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
     }
 
     fn pre_final(&self, p: pac::aes::Aes, _dir: Direction, padding_len: usize) -> [u32; 4] {
@@ -874,9 +866,7 @@ pub struct Context<'c, C: Cipher<'c>> {
 pub struct Aes<'d, T: Instance, M: Mode> {
     _peripheral: Peri<'d, T>,
     _marker: PhantomData<M>,
-    #[allow(dead_code)] // Reserved for future async/DMA implementation
     dma_in: Option<ChannelAndRequest<'d>>,
-    #[allow(dead_code)] // Reserved for future async/DMA implementation
     dma_out: Option<ChannelAndRequest<'d>>,
 }
 
@@ -931,13 +921,35 @@ impl<'d, T: Instance> Aes<'d, T, Async> {
 impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
     /// Starts a new cipher operation and returns the context.
     pub fn start<'c, C>(&mut self, cipher: &'c C, dir: Direction) -> Context<'c, C>
+    where
+        C: Cipher<'c> + CipherSized + IVSized,
+    {
+        self.start_with_key(cipher, dir, false)
+    }
+
+    /// Starts a new cipher operation with a key shared by SAES, and returns the context.
+    ///
+    /// The key must have been decrypted by SAES in shared-key mode before, e.g. with
+    /// [`Saes::share_key`](crate::saes::Saes::share_key). The key of `cipher` is not used,
+    /// only its size.
+    pub fn start_shared_key<'c, C>(&mut self, cipher: &'c C, dir: Direction) -> Context<'c, C>
+    where
+        C: Cipher<'c> + CipherSized + IVSized,
+    {
+        self.start_with_key(cipher, dir, true)
+    }
+
+    fn start_with_key<'c, C>(&mut self, cipher: &'c C, dir: Direction, shared_key: bool) -> Context<'c, C>
     where
         C: Cipher<'c> + CipherSized + IVSized,
     {
         let p = T::regs();
 
-        // Disable the peripheral
-        p.cr().modify(|w| w.set_en(false));
+        // Disable the peripheral, and leave shared-key mode from a previous operation
+        p.cr().modify(|w| {
+            w.set_en(false);
+            w.set_kmod(pac::aes::vals::Kmod::from_bits(0));
+        });
 
         // Configure data type based on cipher mode (NO_SWAP, BYTE_SWAP, or BIT_SWAP)
         p.cr()
@@ -967,13 +979,13 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
 
         if is_gcm_ccm {
             // GCM/CCM mode (RM 26.4.8): Load key first, then IV
-            self.load_key(cipher.key());
+            self.load_key_or_shared(cipher.key(), shared_key);
             if !cipher.iv().is_empty() {
                 self.load_iv(cipher.iv());
             }
         } else if needs_key_prep {
             // ECB/CBC decryption (RM 26.4.6): Key prep, then IV
-            self.load_key(cipher.key());
+            self.load_key_or_shared(cipher.key(), shared_key);
             cipher.prepare_key(p, dir);
 
             // Step 8: Select cipher mode and decryption mode (keep other params)
@@ -990,7 +1002,7 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
             if !cipher.iv().is_empty() {
                 self.load_iv(cipher.iv());
             }
-            self.load_key(cipher.key());
+            self.load_key_or_shared(cipher.key(), shared_key);
         }
 
         // Perform init phase for GCM/CCM modes (RM step 6-8)
@@ -1026,68 +1038,74 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
     where
         C: Cipher<'c> + CipherAuthenticated<16>,
     {
-        let p = T::regs();
-
         if ctx.header_processed && last {
             return Ok(());
         }
 
+        let aad = self.aad_begin(ctx, aad)?;
+
+        // Process complete blocks
+        let complete_len = aad.len() - aad.len() % AES_BLOCK_SIZE;
+        for block in aad[..complete_len].chunks_exact(AES_BLOCK_SIZE) {
+            self.aad_block_blocking(block)?;
+        }
+        ctx.header_len += complete_len as u64;
+
+        self.aad_end(ctx, &aad[complete_len..], last)
+    }
+
+    /// Switch to the header phase, and complete the buffered AAD block if possible.
+    /// Returns the AAD that is left.
+    fn aad_begin<'a, 'c, C>(&mut self, ctx: &mut Context<'c, C>, aad: &'a [u8]) -> Result<&'a [u8], Error>
+    where
+        C: Cipher<'c>,
+    {
+        let p = T::regs();
+
         // Set GCM phase to header (GCMPH = 1)
         p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(1)));
         // Enable the peripheral for header phase
         p.cr().modify(|w| w.set_en(true));
 
-        let mut aad_remaining = aad.len();
-        let mut aad_index = 0;
-
         // Process buffered AAD first if any
-        if ctx.aad_buffer_len > 0 {
-            let space_available = 16 - ctx.aad_buffer_len;
-            let to_copy = core::cmp::min(space_available, aad_remaining);
-            ctx.aad_buffer[ctx.aad_buffer_len..ctx.aad_buffer_len + to_copy].copy_from_slice(&aad[..to_copy]);
-            ctx.aad_buffer_len += to_copy;
-            aad_index += to_copy;
-            aad_remaining -= to_copy;
-
-            if ctx.aad_buffer_len == 16 {
-                self.write_block_blocking(&ctx.aad_buffer)?;
-                // Wait for CCF (block processed) - no read in header phase
-                while !p.sr().read().ccf() {}
-                p.icr().write(|w| w.0 = 0xFFFF_FFFF);
-                ctx.header_len += 16;
-                ctx.aad_buffer_len = 0;
-            }
+        if ctx.aad_buffer_len == 0 {
+            return Ok(aad);
         }
 
-        // Process complete blocks
-        while aad_remaining >= 16 {
-            self.write_block_blocking(&aad[aad_index..aad_index + 16])?;
-            // Wait for CCF (block processed) - no read in header phase
-            while !p.sr().read().ccf() {}
-            p.icr().write(|w| w.0 = 0xFFFF_FFFF);
-            ctx.header_len += 16;
-            aad_index += 16;
-            aad_remaining -= 16;
+        let space_available = AES_BLOCK_SIZE - ctx.aad_buffer_len;
+        let to_copy = core::cmp::min(space_available, aad.len());
+        ctx.aad_buffer[ctx.aad_buffer_len..ctx.aad_buffer_len + to_copy].copy_from_slice(&aad[..to_copy]);
+        ctx.aad_buffer_len += to_copy;
+
+        if ctx.aad_buffer_len == AES_BLOCK_SIZE {
+            let block = ctx.aad_buffer;
+            self.aad_block_blocking(&block)?;
+            ctx.header_len += AES_BLOCK_SIZE as u64;
+            ctx.aad_buffer_len = 0;
         }
 
+        Ok(&aad[to_copy..])
+    }
+
+    /// Buffer the AAD of a partial block, and process it if this is the last AAD.
+    fn aad_end<'c, C>(&mut self, ctx: &mut Context<'c, C>, aad: &[u8], last: bool) -> Result<(), Error>
+    where
+        C: Cipher<'c>,
+    {
         // Buffer any remaining partial block
-        if aad_remaining > 0 {
-            ctx.aad_buffer[..aad_remaining].copy_from_slice(&aad[aad_index..aad_index + aad_remaining]);
-            ctx.aad_buffer_len = aad_remaining;
+        if !aad.is_empty() {
+            ctx.aad_buffer[..aad.len()].copy_from_slice(aad);
+            ctx.aad_buffer_len = aad.len();
         }
 
         // If this is the last AAD block, pad and process
         if last {
             if ctx.aad_buffer_len > 0 {
                 // Pad with zeros (per GCM spec, AAD is zero-padded to 16-byte boundary)
-                for i in ctx.aad_buffer_len..16 {
-                    ctx.aad_buffer[i] = 0;
-                }
+                ctx.aad_buffer[ctx.aad_buffer_len..].fill(0);
                 // Note: Do NOT set NPBLB for header phase - NPBLB is only for payload phase
-                self.write_block_blocking(&ctx.aad_buffer)?;
-                // Wait for CCF (block processed)
-                while !p.sr().read().ccf() {}
-                p.icr().write(|w| w.0 = 0xFFFF_FFFF);
+                let block = ctx.aad_buffer;
+                self.aad_block_blocking(&block)?;
                 ctx.header_len += ctx.aad_buffer_len as u64;
                 ctx.aad_buffer_len = 0;
             }
@@ -1097,6 +1115,16 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
         Ok(())
     }
 
+    /// Process a block of AAD.
+    fn aad_block_blocking(&mut self, block: &[u8]) -> Result<(), Error> {
+        let p = T::regs();
+        self.write_block_blocking(block)?;
+        // Wait for CCF (block processed) - no read in header phase
+        while !p.sr().read().ccf() {}
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
+        Ok(())
+    }
+
     /// Process payload data in blocking mode.
     ///
     /// Set `last` to true for the final block. Intermediate chunks (`last=false`)
@@ -1111,30 +1139,11 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
     where
         C: Cipher<'c>,
     {
-        let p = T::regs();
-
         if output.len() < input.len() {
             return Err(Error::ConfigError);
         }
 
-        // For GCM/CCM, switch to payload phase
-        if ctx.is_gcm_ccm {
-            let header_was_skipped = !ctx.header_processed;
-            if header_was_skipped {
-                // No AAD provided, mark header as done
-                ctx.header_processed = true;
-            }
-            // Set GCM phase to payload (per RM step 11a)
-            // ST HAL shows: just change GCMPH, DON'T disable EN between phases
-            p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(2)));
-            // Reset NPBLB to 0 (per ST HAL)
-            p.cr().modify(|w| w.set_npblb(0));
-            // Only enable if header was skipped (per RM step 11b)
-            if header_was_skipped {
-                p.cr().modify(|w| w.set_en(true));
-            }
-            // If header was processed, EN is already enabled - don't touch it
-        }
+        self.payload_begin(ctx);
 
         let block_size = C::BLOCK_SIZE;
         let mut processed = 0;
@@ -1147,11 +1156,7 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
         }
 
         // Process complete blocks
-        let complete_blocks = if last {
-            input.len() / block_size
-        } else {
-            input.len() / block_size
-        };
+        let complete_blocks = input.len() / block_size;
 
         for _ in 0..complete_blocks {
             let block = &input[processed..processed + block_size];
@@ -1162,15 +1167,55 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
             ctx.payload_len += block_size as u64;
         }
 
+        if last {
+            self.payload_end(ctx, &input[processed..], &mut output[processed..input.len()])?;
+        }
+
+        Ok(())
+    }
+
+    /// For GCM/CCM, switch to the payload phase.
+    fn payload_begin<'c, C>(&mut self, ctx: &mut Context<'c, C>)
+    where
+        C: Cipher<'c>,
+    {
+        let p = T::regs();
+
+        if ctx.is_gcm_ccm {
+            let header_was_skipped = !ctx.header_processed;
+            if header_was_skipped {
+                // No AAD provided, mark header as done
+                ctx.header_processed = true;
+            }
+            // Set GCM phase to payload (per RM step 11a)
+            // ST HAL shows: just change GCMPH, DON'T disable EN between phases
+            p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(2)));
+            // Reset NPBLB to 0 (per ST HAL)
+            p.cr().modify(|w| w.set_npblb(0));
+            // Only enable if header was skipped (per RM step 11b)
+            if header_was_skipped {
+                p.cr().modify(|w| w.set_en(true));
+            }
+            // If header was processed, EN is already enabled - don't touch it
+        }
+    }
+
+    /// Process the partial block left at the end of the payload, if any.
+    fn payload_end<'c, C>(&mut self, ctx: &mut Context<'c, C>, input: &[u8], output: &mut [u8]) -> Result<(), Error>
+    where
+        C: Cipher<'c>,
+    {
+        let p = T::regs();
+
         // Handle partial block if last
-        if last && processed < input.len() {
+        if !input.is_empty() {
             if C::REQUIRES_PADDING {
                 return Err(Error::ConfigError); // Padding modes don't support partial blocks
             }
 
-            let remaining = input.len() - processed;
+            let remaining = input.len();
             let mut partial_block = [0u8; 16];
-            partial_block[..remaining].copy_from_slice(&input[processed..]);
+            partial_block[..remaining].copy_from_slice(input);
 
             // Set NPBLB (Number of Padding Bytes in Last Block)
             // Per ST HAL:
@@ -1191,13 +1236,11 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
             self.write_block_blocking(&partial_block)?;
             self.read_block_blocking(&mut partial_block)?;
 
-            output[processed..processed + remaining].copy_from_slice(&partial_block[..remaining]);
+            output.copy_from_slice(&partial_block[..remaining]);
             ctx.payload_len += remaining as u64;
         }
 
-        if last {
-            ctx.last_block_processed = true;
-        }
+        ctx.last_block_processed = true;
 
         Ok(())
     }
@@ -1211,49 +1254,12 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
 
         // For GCM/CCM, perform final phase to get tag
         if ctx.is_gcm_ccm {
-            // Wait for BUSY flag to clear before modifying CR (per ST HAL)
-            while p.sr().read().busy() {}
-
-            // Set GCM/CCM phase to final (GCMPH = 3)
-            p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(3)));
-
-            // GCM and CCM have different final phase handling:
-            // - GCM: Write length block (64-bit header len || 64-bit payload len in bits)
-            // - CCM: Enable peripheral to trigger final tag computation
-            if ctx.cipher.is_ccm_mode() {
-                // CCM: Just enable the peripheral to trigger final computation
-                // Per ST HAL HAL_CRYPEx_AESCCM_GenerateAuthTAG
-                p.cr().modify(|w| w.set_en(true));
-            } else {
-                // GCM: Write lengths (in bits) as final block per GCM spec
-                // ST HAL writes: DINR=0, DINR=header_bits, DINR=0, DINR=payload_bits
-                let header_bits = (ctx.header_len * 8) as u32;
-                let payload_bits = (ctx.payload_len * 8) as u32;
-
-                p.dinr().write_value(0);
-                p.dinr().write_value(header_bits);
-                p.dinr().write_value(0);
-                p.dinr().write_value(payload_bits);
-            }
+            self.final_begin(&ctx);
 
             // Wait for CCF flag
             while !p.sr().read().ccf() {}
 
-            // Read the authentication tag
-            // With NO_SWAP mode, use big-endian byte order for consistency
-            let mut tag = [0u8; 16];
-            for i in 0..4 {
-                let word = p.doutr().read();
-                tag[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
-            }
-
-            // Clear CCF flag
-            p.icr().write(|w| w.0 = 0xFFFF_FFFF);
-
-            // Disable peripheral
-            p.cr().modify(|w| w.set_en(false));
-
-            Ok(Some(tag))
+            Ok(Some(self.final_end()))
         } else {
             // For non-authenticated modes, just disable
             p.cr().modify(|w| w.set_en(false));
@@ -1261,6 +1267,71 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
         }
     }
 
+    /// Start the final phase of GCM/CCM, computing the tag.
+    fn final_begin<'c, C>(&mut self, ctx: &Context<'c, C>)
+    where
+        C: Cipher<'c>,
+    {
+        let p = T::regs();
+
+        // Wait for BUSY flag to clear before modifying CR (per ST HAL)
+        while p.sr().read().busy() {}
+
+        // Set GCM/CCM phase to final (GCMPH = 3)
+        p.cr().modify(|w| w.set_gcmph(pac::aes::vals::Gcmph::from_bits(3)));
+
+        // GCM and CCM have different final phase handling:
+        // - GCM: Write length block (64-bit header len || 64-bit payload len in bits)
+        // - CCM: Enable peripheral to trigger final tag computation
+        if ctx.cipher.is_ccm_mode() {
+            // CCM: Just enable the peripheral to trigger final computation
+            // Per ST HAL HAL_CRYPEx_AESCCM_GenerateAuthTAG
+            p.cr().modify(|w| w.set_en(true));
+        } else {
+            // GCM: Write lengths (in bits) as final block per GCM spec
+            // ST HAL writes: DINR=0, DINR=header_bits, DINR=0, DINR=payload_bits
+            let header_bits = (ctx.header_len * 8) as u32;
+            let payload_bits = (ctx.payload_len * 8) as u32;
+
+            p.dinr().write_value(0);
+            p.dinr().write_value(header_bits);
+            p.dinr().write_value(0);
+            p.dinr().write_value(payload_bits);
+        }
+    }
+
+    /// Read the tag at the end of the final phase, and disable the peripheral.
+    fn final_end(&mut self) -> [u8; 16] {
+        let p = T::regs();
+
+        // Read the authentication tag
+        // With NO_SWAP mode, use big-endian byte order for consistency
+        let mut tag = [0u8; 16];
+        for i in 0..4 {
+            let word = p.doutr().read();
+            tag[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+
+        // Clear CCF flag
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
+
+        // Disable peripheral
+        p.cr().modify(|w| w.set_en(false));
+
+        tag
+    }
+
+    /// Load the key, or in shared-key mode (KMOD = 2) wait for SAES to transfer it.
+    fn load_key_or_shared(&mut self, key: &[u8], shared: bool) {
+        if shared {
+            let p = T::regs();
+            p.cr().modify(|w| w.set_kmod(pac::aes::vals::Kmod::from_bits(2)));
+            while !p.sr().read().keyvalid() {}
+        } else {
+            self.load_key(key);
+        }
+    }
+
     /// Load key into AES peripheral.
     fn load_key(&mut self, key: &[u8]) {
         let p = T::regs();
@@ -1341,6 +1412,146 @@ impl<'d, T: Instance, M: Mode> Aes<'d, T, M> {
     }
 }
 
+impl<'d, T: Instance> Aes<'d, T, Async> {
+    /// Process Additional Authenticated Data (AAD), with DMA.
+    ///
+    /// Complete blocks are written by DMA, a partial block is buffered like with
+    /// [`aad_blocking`](Self::aad_blocking). Set `last` to true for the final AAD chunk.
+    pub async fn aad<'c, C>(&mut self, ctx: &mut Context<'c, C>, aad: &[u8], last: bool) -> Result<(), Error>
+    where
+        C: Cipher<'c> + CipherAuthenticated<16>,
+    {
+        if ctx.header_processed && last {
+            return Ok(());
+        }
+
+        let aad = self.aad_begin(ctx, aad)?;
+
+        let complete_len = aad.len() - aad.len() % AES_BLOCK_SIZE;
+        for chunk in aad[..complete_len].chunks(DMA_CHUNK_SIZE) {
+            self.write_dma(chunk).await;
+        }
+        ctx.header_len += complete_len as u64;
+
+        self.aad_end(ctx, &aad[complete_len..], last)
+    }
+
+    /// Process payload data, with DMA.
+    ///
+    /// Set `last` to true for the final chunk. Intermediate chunks (`last=false`)
+    /// must be block-aligned (16 bytes). Only the final chunk can be a partial block,
+    /// which is processed without DMA.
+    pub async fn payload<'c, C>(
+        &mut self,
+        ctx: &mut Context<'c, C>,
+        input: &[u8],
+        output: &mut [u8],
+        last: bool,
+    ) -> Result<(), Error>
+    where
+        C: Cipher<'c>,
+    {
+        if output.len() < input.len() {
+            return Err(Error::ConfigError);
+        }
+
+        if !last && input.len() % C::BLOCK_SIZE != 0 {
+            return Err(Error::ConfigError);
+        }
+
+        self.payload_begin(ctx);
+
+        let complete_len = input.len() - input.len() % C::BLOCK_SIZE;
+        for (input, output) in input[..complete_len]
+            .chunks(DMA_CHUNK_SIZE)
+            .zip(output[..complete_len].chunks_mut(DMA_CHUNK_SIZE))
+        {
+            self.transform_dma(input, output).await;
+        }
+        ctx.payload_len += complete_len as u64;
+
+        if last {
+            self.payload_end(ctx, &input[complete_len..], &mut output[complete_len..input.len()])?;
+        }
+
+        Ok(())
+    }
+
+    /// Finishes the cipher operation and returns the authentication tag (for GCM/CCM).
+    ///
+    /// Waits for the tag computation with the interrupt.
+    pub async fn finish<'c, C>(&mut self, ctx: Context<'c, C>) -> Result<Option<[u8; 16]>, Error>
+    where
+        C: Cipher<'c>,
+    {
+        let p = T::regs();
+
+        if ctx.is_gcm_ccm {
+            self.final_begin(&ctx);
+            wait_complete(p).await;
+            Ok(Some(self.final_end()))
+        } else {
+            p.cr().modify(|w| w.set_en(false));
+            Ok(None)
+        }
+    }
+
+    /// Write complete blocks with DMA, without reading output (header phase).
+    async fn write_dma(&mut self, input: &[u8]) {
+        let p = T::regs();
+
+        let mut words = [0u32; DMA_CHUNK_SIZE / 4];
+        let words = &mut words[..input.len() / 4];
+        to_words(input, words);
+
+        p.cr().modify(|w| w.set_dmainen(true));
+        let dma_in = unwrap!(self.dma_in.as_mut());
+        unsafe { dma_in.write(words, p.dinr().as_ptr(), Default::default()) }.await;
+        p.cr().modify(|w| w.set_dmainen(false));
+
+        // The DMA is done once the last word is written, wait for the block to be processed.
+        while p.sr().read().busy() {}
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
+    }
+
+    /// Process complete blocks with DMA in both directions.
+    async fn transform_dma(&mut self, input: &[u8], output: &mut [u8]) {
+        let p = T::regs();
+
+        let mut in_words = [0u32; DMA_CHUNK_SIZE / 4];
+        let in_words = &mut in_words[..input.len() / 4];
+        let mut out_words = [0u32; DMA_CHUNK_SIZE / 4];
+        let out_words = &mut out_words[..input.len() / 4];
+        to_words(input, in_words);
+
+        p.cr().modify(|w| {
+            w.set_dmainen(true);
+            w.set_dmaouten(true);
+        });
+        let dma_in = unwrap!(self.dma_in.as_mut());
+        let dma_out = unwrap!(self.dma_out.as_mut());
+        let write = unsafe { dma_in.write(in_words, p.dinr().as_ptr(), Default::default()) };
+        let read = unsafe { dma_out.read(p.doutr().as_ptr(), out_words, Default::default()) };
+        embassy_futures::join::join(write, read).await;
+        p.cr().modify(|w| {
+            w.set_dmainen(false);
+            w.set_dmaouten(false);
+        });
+        p.icr().write(|w| w.0 = 0xFFFF_FFFF);
+
+        for (bytes, word) in output.chunks_exact_mut(4).zip(out_words.iter()) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+/// Convert bytes to words in the byte order used by the blocking API.
+fn to_words(bytes: &[u8], words: &mut [u32]) {
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_be_bytes(unwrap!(bytes.try_into()));
+    }
+}
+
 trait SealedInstance {
     fn regs() -> pac::aes::Aes;
 }
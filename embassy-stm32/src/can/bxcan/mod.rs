@@ -1,6 +1,7 @@
 pub mod filter;
 mod registers;
 
+use core::cell::Cell;
 use core::future::poll_fn;
 use core::marker::PhantomData;
 use core::task::Poll;
@@ -18,7 +19,7 @@ pub use super::common::{BufferedCanReceiver, BufferedCanSender};
 use super::common::{InfoRef, RxInfoRef, TxInfoRef};
 use super::frame::{Envelope, Frame};
 use super::util;
-use crate::can::enums::{BusError, RefCountOp, TryReadError};
+use crate::can::enums::{BusError, ErrorStatus, RefCountOp, TryReadError};
 use crate::gpio::{AfType, OutputType, Pull, Speed};
 use crate::interrupt::typelevel::Interrupt;
 use crate::rcc::{self, RccPeripheral};
@@ -38,6 +39,9 @@ impl<T: Instance> interrupt::typelevel::Handler<T::TXInterrupt> for TxInterruptH
         });
         T::info().state.lock(|state| {
             state.borrow().tx_mode.on_interrupt::<T>();
+            // A successful transmission decrements the transmit error counter, which may leave
+            // the error warning or error passive state without raising an error interrupt.
+            state.borrow().error_state_waker.wake();
         });
     }
 }
@@ -84,15 +88,18 @@ impl<T: Instance> interrupt::typelevel::Handler<T::SCEInterrupt> for SceInterrup
                 state.borrow().err_waker.wake();
             });
         } else if msr_val.erri() {
-            // Disable the interrupt, but don't acknowledge the error, so that it can be
-            // forwarded off the bus message consumer. If we don't provide some way for
-            // downstream code to determine that it has already provided this bus error instance
-            // to the bus message consumer, we are doomed to re-provide a single error instance for
-            // an indefinite amount of time.
-            let ier = T::regs().ier();
-            ier.modify(|i| i.set_errie(false));
+            // Acknowledge the error right away, so that the following error state changes raise
+            // the interrupt again. The bus error is latched until the bus message consumer takes
+            // it, so that a single error instance is only provided once.
+            msr.write(|m| m.set_erri(true));
+            let error = Registers(T::regs()).last_bus_error();
             T::info().state.lock(|state| {
-                state.borrow().err_waker.wake();
+                let state = state.borrow();
+                if error.is_some() {
+                    state.bus_error.set(error);
+                }
+                state.err_waker.wake();
+                state.error_state_waker.wake();
             });
         }
     }
@@ -144,6 +151,22 @@ impl CanConfig<'_> {
         self
     }
 
+    /// Enables or disables time triggered communication mode.
+    ///
+    /// In this mode the internal 16-bit counter, incremented once per CAN bit time, is captured
+    /// at the start of frame of every received and transmitted frame, see
+    /// [`Can::transmit_timestamp`] and [`Can::try_write_with_global_time`]. Without the `time`
+    /// feature, the captured value is returned as the timestamp of received frames.
+    ///
+    /// The counter can not be reset by a reference frame, schedules have to be derived from the
+    /// captured values in software.
+    ///
+    /// Time triggered communication mode is enabled by default.
+    pub fn set_time_triggered_mode(self, enabled: bool) -> Self {
+        self.info.regs.set_time_triggered_mode(enabled);
+        self
+    }
+
     /// Enables or disables automatic retransmission of frames.
     ///
     /// If this is enabled, the CAN peripheral will automatically try to retransmit each frame
@@ -356,6 +379,18 @@ impl<'d> Can<'d> {
         self.split().0.try_write(frame)
     }
 
+    /// Attempts to transmit a frame without blocking, sending the value of the internal counter at
+    /// the start of frame in the last two data bytes, see [`CanTx::try_write_with_global_time`].
+    pub fn try_write_with_global_time(&mut self, frame: &Frame) -> Result<TransmitStatus, TryWriteError> {
+        self.split().0.try_write_with_global_time(frame)
+    }
+
+    /// Returns the value of the internal counter captured at the start of frame of the last frame
+    /// transmitted from `mailbox`.
+    pub fn transmit_timestamp(&self, mailbox: Mailbox) -> u16 {
+        self.info.regs.transmit_timestamp(mailbox)
+    }
+
     /// Waits for a specific transmit mailbox to become empty
     pub async fn flush(&self, mb: Mailbox) {
         CanTx {
@@ -407,6 +442,16 @@ impl<'d> Can<'d> {
         self.info.regs.is_idle()
     }
 
+    /// Get the error counters, the error warning state, the bus error mode and the last error code
+    pub fn error_status(&self) -> ErrorStatus {
+        self.info.regs.error_status()
+    }
+
+    /// Wait until the error warning state or the bus error mode changes, and return the new status
+    pub async fn wait_for_error_state_change(&self) -> ErrorStatus {
+        wait_for_error_state_change(&self.info).await
+    }
+
     /// Read a CAN frame.
     ///
     /// If no CAN frame is in the RX buffer, this will wait until there is one.
@@ -553,6 +598,24 @@ impl<'d> CanTx<'d> {
         self.info.regs.transmit(frame).map_err(|_| TryWriteError::Full)
     }
 
+    /// Attempts to transmit a frame without blocking, sending the value of the internal counter at
+    /// the start of frame in the last two data bytes.
+    ///
+    /// The hardware replaces data bytes 6 and 7 of the frame, so it must have a data length of 8.
+    /// Requires time triggered communication mode, see [`CanConfig::set_time_triggered_mode`].
+    pub fn try_write_with_global_time(&mut self, frame: &Frame) -> Result<TransmitStatus, TryWriteError> {
+        self.info
+            .regs
+            .transmit_with_global_time(frame)
+            .map_err(|_| TryWriteError::Full)
+    }
+
+    /// Returns the value of the internal counter captured at the start of frame of the last frame
+    /// transmitted from `mailbox`.
+    pub fn transmit_timestamp(&self, mailbox: Mailbox) -> u16 {
+        self.info.regs.transmit_timestamp(mailbox)
+    }
+
     async fn flush_inner(&self, mb: Mailbox) {
         poll_fn(|cx| {
             self.info.state.lock(|s| {
@@ -728,6 +791,16 @@ impl<'d> CanRx<'d> {
         RxMode::wait_not_empty(&self.info).await
     }
 
+    /// Get the error counters, the error warning state, the bus error mode and the last error code
+    pub fn error_status(&self) -> ErrorStatus {
+        self.info.regs.error_status()
+    }
+
+    /// Wait until the error warning state or the bus error mode changes, and return the new status
+    pub async fn wait_for_error_state_change(&self) -> ErrorStatus {
+        wait_for_error_state_change(&self.info).await
+    }
+
     /// Return a buffered instance of driver. User must supply Buffers
     pub fn buffered<const RX_BUF_SIZE: usize>(
         self,
@@ -795,7 +868,7 @@ impl<'d, const RX_BUF_SIZE: usize> BufferedCanRx<'d, RX_BUF_SIZE> {
                         Err(e) => Err(TryReadError::BusError(e)),
                     }
                 } else {
-                    if let Some(err) = self.info.regs.curr_error() {
+                    if let Some(err) = s.borrow().bus_error.take() {
                         return Err(TryReadError::BusError(err));
                     } else {
                         Err(TryReadError::Empty)
@@ -940,31 +1013,34 @@ impl RxMode {
         .await
     }
     pub(crate) fn try_read(info: &Info) -> Result<Envelope, TryReadError> {
-        info.state.lock(|state| match state.borrow().rx_mode {
-            Self::NonBuffered(_) => {
-                let registers = &info.regs;
-                if let Some(msg) = registers.receive_fifo(RxFifo::Fifo0) {
-                    registers.0.ier().modify(|w| {
-                        w.set_fmpie(0, true);
-                    });
-                    Ok(msg)
-                } else if let Some(msg) = registers.receive_fifo(RxFifo::Fifo1) {
-                    registers.0.ier().modify(|w| {
-                        w.set_fmpie(1, true);
-                    });
-                    Ok(msg)
-                } else if let Some(err) = registers.curr_error() {
-                    Err(TryReadError::BusError(err))
-                } else {
-                    registers.0.ier().modify(|w| {
-                        w.set_fmpie(0, true);
-                        w.set_fmpie(1, true);
-                    });
-                    Err(TryReadError::Empty)
+        info.state.lock(|state| {
+            let state = state.borrow();
+            match state.rx_mode {
+                Self::NonBuffered(_) => {
+                    let registers = &info.regs;
+                    if let Some(msg) = registers.receive_fifo(RxFifo::Fifo0) {
+                        registers.0.ier().modify(|w| {
+                            w.set_fmpie(0, true);
+                        });
+                        Ok(msg)
+                    } else if let Some(msg) = registers.receive_fifo(RxFifo::Fifo1) {
+                        registers.0.ier().modify(|w| {
+                            w.set_fmpie(1, true);
+                        });
+                        Ok(msg)
+                    } else if let Some(err) = state.bus_error.take() {
+                        Err(TryReadError::BusError(err))
+                    } else {
+                        registers.0.ier().modify(|w| {
+                            w.set_fmpie(0, true);
+                            w.set_fmpie(1, true);
+                        });
+                        Err(TryReadError::Empty)
+                    }
+                }
+                _ => {
+                    panic!("Bad Mode")
                 }
-            }
-            _ => {
-                panic!("Bad Mode")
             }
         })
     }
@@ -1035,10 +1111,29 @@ impl TxMode {
     }
 }
 
+async fn wait_for_error_state_change(info: &Info) -> ErrorStatus {
+    let initial = info.regs.error_status();
+
+    poll_fn(|cx| {
+        info.state.lock(|s| s.borrow().error_state_waker.register(cx.waker()));
+
+        let status = info.regs.error_status();
+        if (status.error_warning, status.mode) != (initial.error_warning, initial.mode) {
+            return Poll::Ready(status);
+        }
+
+        Poll::Pending
+    })
+    .await
+}
+
 pub(crate) struct State {
     pub(crate) rx_mode: RxMode,
     pub(crate) tx_mode: TxMode,
     pub err_waker: AtomicWaker,
+    error_state_waker: AtomicWaker,
+    /// Bus error latched by the SCE interrupt until the receiver reports it.
+    bus_error: Cell<Option<BusError>>,
     receiver_instance_count: usize,
     sender_instance_count: usize,
 }
@@ -1049,6 +1144,8 @@ impl State {
             rx_mode: RxMode::NonBuffered(AtomicWaker::new()),
             tx_mode: TxMode::NonBuffered(AtomicWaker::new()),
             err_waker: AtomicWaker::new(),
+            error_state_waker: AtomicWaker::new(),
+            bus_error: Cell::new(None),
             receiver_instance_count: 1,
             sender_instance_count: 1,
         }
@@ -5,7 +5,7 @@ pub use embedded_can::{ExtendedId, Id, StandardId};
 use stm32_metapac::can::vals::{Lec, Rtr};
 
 use super::{Mailbox, TransmitStatus};
-use crate::can::enums::BusError;
+use crate::can::enums::{BusError, BusErrorMode, ErrorStatus};
 use crate::can::frame::{Envelope, Frame, Header};
 
 pub(crate) struct Registers(pub crate::pac::can::Can);
@@ -70,6 +70,20 @@ impl Registers {
         self.0.mcr().modify(|reg| reg.set_nart(!enabled));
     }
 
+    /// Enables or disables time triggered communication mode.
+    ///
+    /// In this mode the internal 16-bit counter, running at the CAN bit rate, is captured at the
+    /// start of frame of every received and transmitted frame.
+    pub fn set_time_triggered_mode(&self, enabled: bool) {
+        self.0.mcr().modify(|reg| reg.set_ttcm(enabled));
+    }
+
+    /// Returns the value of the internal counter captured at the start of frame of the last
+    /// frame transmitted from `mailbox`.
+    pub fn transmit_timestamp(&self, mailbox: Mailbox) -> u16 {
+        self.0.tx(mailbox as usize).tdtr().read().time()
+    }
+
     /// Enables or disables loopback mode: Internally connects the TX and RX
     /// signals together.
     pub fn set_loopback(&self, enabled: bool) {
@@ -144,35 +158,34 @@ impl Registers {
         }
     }
 
-    pub fn curr_error(&self) -> Option<BusError> {
-        if !self.0.msr().read().erri() {
-            // This ensures that once a single error instance has
-            // been acknowledged and forwared to the bus message consumer
-            // we don't continue to re-forward the same error occurrance for an
-            // in-definite amount of time.
-            return None;
+    /// Reads the last error code, set by the hardware on each bus error.
+    pub fn last_bus_error(&self) -> Option<BusError> {
+        Self::lec_to_error(self.0.esr().read().lec())
+    }
+
+    fn lec_to_error(lec: Lec) -> Option<BusError> {
+        match lec {
+            Lec::Stuff => Some(BusError::Stuff),
+            Lec::Form => Some(BusError::Form),
+            Lec::Ack => Some(BusError::Acknowledge),
+            Lec::BitRecessive => Some(BusError::BitRecessive),
+            Lec::BitDominant => Some(BusError::BitDominant),
+            Lec::Crc => Some(BusError::Crc),
+            Lec::Custom => Some(BusError::Software),
+            Lec::NoError => None,
         }
+    }
 
-        // Since we have not already acknowledge the error, and the interrupt was
-        // disabled in the ISR, we will acknowledge the current error and re-enable the interrupt
-        // so futher errors are captured
-        self.0.msr().modify(|m| m.set_erri(true));
-        self.0.ier().modify(|i| i.set_errie(true));
-
-        let err = self.0.esr().read();
-        if err.lec() != Lec::NoError {
-            return Some(match err.lec() {
-                Lec::Stuff => BusError::Stuff,
-                Lec::Form => BusError::Form,
-                Lec::Ack => BusError::Acknowledge,
-                Lec::BitRecessive => BusError::BitRecessive,
-                Lec::BitDominant => BusError::BitDominant,
-                Lec::Crc => BusError::Crc,
-                Lec::Custom => BusError::Software,
-                Lec::NoError => unreachable!(),
-            });
+    /// Reads the error counters and protocol status
+    pub fn error_status(&self) -> ErrorStatus {
+        let esr = self.0.esr().read();
+        ErrorStatus {
+            tx_error_count: esr.tec(),
+            rx_error_count: esr.rec(),
+            error_warning: esr.ewgf(),
+            mode: BusErrorMode::from_flags(esr.boff(), esr.epvf()),
+            last_error: Self::lec_to_error(esr.lec()),
         }
-        None
     }
 
     /// Enables or disables FIFO scheduling of outgoing mailboxes.
@@ -211,6 +224,20 @@ impl Registers {
     ///
     /// If all transmit mailboxes are full, this function returns [`nb::Error::WouldBlock`].
     pub fn transmit(&self, frame: &Frame) -> nb::Result<TransmitStatus, Infallible> {
+        self.transmit_inner(frame, false)
+    }
+
+    /// Like [`Self::transmit()`], but replaces the last two data bytes with the value of the
+    /// internal counter at the start of frame. Requires time triggered communication mode.
+    pub fn transmit_with_global_time(&self, frame: &Frame) -> nb::Result<TransmitStatus, Infallible> {
+        assert!(
+            frame.header().len() == 8,
+            "Transmit global time requires a data length of 8"
+        );
+        self.transmit_inner(frame, true)
+    }
+
+    fn transmit_inner(&self, frame: &Frame, global_time: bool) -> nb::Result<TransmitStatus, Infallible> {
         // Check if FIFO scheduling is enabled.
         let fifo_scheduling = self.0.mcr().read().txfp();
 
@@ -255,7 +282,7 @@ impl Registers {
             pending_frame = None
         }
 
-        self.write_mailbox(idx, frame);
+        self.write_mailbox(idx, frame, global_time);
 
         let mailbox = match idx {
             0 => Mailbox::Mailbox0,
@@ -286,11 +313,14 @@ impl Registers {
         Ok(())
     }
 
-    fn write_mailbox(&self, idx: usize, frame: &Frame) {
+    fn write_mailbox(&self, idx: usize, frame: &Frame, global_time: bool) {
         debug_assert!(idx < 3);
 
         let mb = self.0.tx(idx);
-        mb.tdtr().write(|w| w.set_dlc(frame.header().len() as u8));
+        mb.tdtr().write(|w| {
+            w.set_dlc(frame.header().len() as u8);
+            w.set_tgt(global_time);
+        });
 
         mb.tdlr()
             .write(|w| w.0 = u32::from_ne_bytes(unwrap!(frame.raw_data()[0..4].try_into())));
@@ -412,15 +442,16 @@ impl Registers {
         let data_len = rdtr.dlc();
         let rtr = rir.rtr() == stm32_metapac::can::vals::Rtr::Remote;
 
+        let raw_ts = rdtr.time();
         #[cfg(not(feature = "time"))]
-        let ts = rdtr.time();
+        let ts = raw_ts;
 
         let mut data: [u8; 8] = [0; 8];
         data[0..4].copy_from_slice(&fifo.rdlr().read().0.to_ne_bytes());
         data[4..8].copy_from_slice(&fifo.rdhr().read().0.to_ne_bytes());
 
         let frame = unwrap!(Frame::new(Header::new(id, data_len, rtr), &data));
-        let envelope = Envelope { ts, frame };
+        let envelope = Envelope { ts, raw_ts, frame };
 
         rfr.modify(|v| v.set_rfom(true));
 
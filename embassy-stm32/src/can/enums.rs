@@ -1,7 +1,7 @@
 //! Enums shared between CAN controller types.
 
 /// Bus error
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum BusError {
     /// Bit stuffing error - more than 5 equal bits
@@ -27,7 +27,7 @@ pub enum BusError {
 ///
 /// Contrary to the `BusError` enum which also includes last-seen acute protocol
 /// errors, this enum includes only the mutually exclusive bus error modes.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum BusErrorMode {
     /// Error active mode (default). Controller will transmit an active error
@@ -41,6 +41,32 @@ pub enum BusErrorMode {
     BusOff,
 }
 
+impl BusErrorMode {
+    pub(crate) fn from_flags(bus_off: bool, error_passive: bool) -> Self {
+        match (bus_off, error_passive) {
+            (false, false) => BusErrorMode::ErrorActive,
+            (false, true) => BusErrorMode::ErrorPassive,
+            (true, _) => BusErrorMode::BusOff,
+        }
+    }
+}
+
+/// Error counters and protocol status of a CAN controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ErrorStatus {
+    /// Transmit error counter (TEC).
+    pub tx_error_count: u8,
+    /// Receive error counter (REC).
+    pub rx_error_count: u8,
+    /// At least one of the error counters reached the error warning limit of 96.
+    pub error_warning: bool,
+    /// Current bus error mode.
+    pub mode: BusErrorMode,
+    /// Last error code, i.e. the type of the last protocol error seen on the bus.
+    pub last_error: Option<BusError>,
+}
+
 /// Frame Create Errors
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
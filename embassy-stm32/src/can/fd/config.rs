@@ -323,6 +323,104 @@ impl From<crate::pac::can::vals::Tfqm> for TxBufferMode {
     }
 }
 
+/// Partitioning of the message RAM of an FDCAN instance.
+///
+/// All sizes are numbers of elements. RX and TX elements are sized for 64 data bytes, so every
+/// RX FIFO, TX event and TX buffer element as well as every filter takes a fixed amount of RAM;
+/// shrinking one section frees space for the others. The default matches the fixed layout used
+/// by FDCAN instances without a configurable message RAM.
+///
+/// Only FDCAN instances with a configurable message RAM (e.g. on STM32H7) support this. There,
+/// all instances share one message RAM of [`WORDS`](Self::WORDS) words, and each instance uses the
+/// section starting at [`start_word`](Self::start_word). Sections of instances used at the same
+/// time must not overlap.
+#[cfg(can_fdcan_v2)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MessageRamConfig {
+    /// First word of the section of the instance in the shared message RAM.
+    ///
+    /// `None` uses the default start of the instance: word 0 for FDCAN1, 768 for FDCAN2 and 1536
+    /// for FDCAN3.
+    pub start_word: Option<u16>,
+    /// Number of standard ID filter elements, at most [`STANDARD_FILTERS_MAX`](Self::STANDARD_FILTERS_MAX).
+    pub standard_filters: u8,
+    /// Number of extended ID filter elements, at most [`EXTENDED_FILTERS_MAX`](Self::EXTENDED_FILTERS_MAX).
+    pub extended_filters: u8,
+    /// Number of elements in RX FIFO 0, at most 64.
+    pub rx_fifo0: u8,
+    /// Number of elements in RX FIFO 1, at most 64.
+    pub rx_fifo1: u8,
+    /// Number of elements in the TX event FIFO, at most 32.
+    pub tx_events: u8,
+    /// Number of TX buffers used as TX FIFO or priority queue, 1 to 32.
+    pub tx_buffers: u8,
+}
+
+#[cfg(can_fdcan_v2)]
+impl MessageRamConfig {
+    /// Message RAM shared by all FDCAN instances, in 32-bit words.
+    pub const WORDS: usize = 0x2800 / 4;
+    /// Maximum number of standard ID filter elements of an instance.
+    pub const STANDARD_FILTERS_MAX: u8 = 128;
+    /// Maximum number of extended ID filter elements of an instance.
+    pub const EXTENDED_FILTERS_MAX: u8 = 64;
+
+    /// Number of 32-bit words of message RAM used by this configuration.
+    pub const fn words(&self) -> usize {
+        self.standard_filters as usize
+            + 2 * self.extended_filters as usize
+            + 18 * (self.rx_fifo0 as usize + self.rx_fifo1 as usize)
+            + 2 * self.tx_events as usize
+            + 18 * self.tx_buffers as usize
+    }
+
+    /// First word of the section of the instance, given its default start.
+    pub(crate) fn start(&self, default_start_word: usize) -> usize {
+        self.start_word.map_or(default_start_word, |start| start as usize)
+    }
+
+    pub(crate) fn validate(&self, default_start_word: usize) {
+        assert!(
+            self.standard_filters <= Self::STANDARD_FILTERS_MAX,
+            "Too many standard filters"
+        );
+        assert!(
+            self.extended_filters <= Self::EXTENDED_FILTERS_MAX,
+            "Too many extended filters"
+        );
+        assert!(
+            self.rx_fifo0 <= 64 && self.rx_fifo1 <= 64,
+            "RX FIFOs can hold at most 64 elements"
+        );
+        assert!(self.tx_events <= 32, "TX event FIFO can hold at most 32 elements");
+        assert!(
+            (1..=32).contains(&self.tx_buffers),
+            "Number of TX buffers must be between 1 and 32"
+        );
+        assert!(
+            self.start(default_start_word) + self.words() <= Self::WORDS,
+            "Message RAM configuration does not fit into the message RAM"
+        );
+    }
+}
+
+#[cfg(can_fdcan_v2)]
+impl Default for MessageRamConfig {
+    fn default() -> Self {
+        use crate::can::fd::message_ram::*;
+
+        Self {
+            start_word: None,
+            standard_filters: STANDARD_FILTER_MAX,
+            extended_filters: EXTENDED_FILTER_MAX,
+            rx_fifo0: RX_FIFO_MAX,
+            rx_fifo1: RX_FIFO_MAX,
+            tx_events: TX_EVENT_MAX,
+            tx_buffers: TX_FIFO_MAX,
+        }
+    }
+}
+
 /// FdCan Config Struct
 #[derive(Clone, Copy, Debug)]
 pub struct FdCanConfig {
@@ -371,6 +469,9 @@ pub struct FdCanConfig {
     pub tx_buffer_mode: TxBufferMode,
     /// Automatic recovery from bus off state
     pub automatic_bus_off_recovery: bool,
+    /// Message RAM partitioning
+    #[cfg(can_fdcan_v2)]
+    pub message_ram: MessageRamConfig,
 }
 
 impl FdCanConfig {
@@ -388,6 +489,20 @@ impl FdCanConfig {
         self
     }
 
+    /// Enables transceiver delay compensation for the data phase of frames with bit rate switching
+    ///
+    /// `offset` is the position of the secondary sample point relative to the measured transceiver
+    /// delay, and `filter_window_length` the minimum delay that is accepted as a valid measurement,
+    /// both in periods of the FDCAN kernel clock. Ignored with a data bit rate prescaler above 2, as
+    /// the hardware only supports it with a prescaler of 1 or 2.
+    #[inline]
+    pub const fn set_transceiver_delay_compensation(mut self, offset: u8, filter_window_length: u8) -> Self {
+        self.dbtr.transceiver_delay_compensation = true;
+        self.dbtr.tdc_offset = offset;
+        self.dbtr.tdc_filter_window_length = filter_window_length;
+        self
+    }
+
     /// Enables or disables automatic retransmission of messages
     ///
     /// If this is enabled, the CAN peripheral will automatically try to retransmit each frame
@@ -448,6 +563,12 @@ impl FdCanConfig {
     }
 
     /// Sets the timestamp source
+    ///
+    /// The counter value captured at the start of each received frame is reported as the raw
+    /// timestamp of [`Envelope`](crate::can::frame::Envelope) and [`FdEnvelope`](crate::can::frame::FdEnvelope).
+    /// Conversion to an `Instant` is only done for the prescaler source in classic CAN mode.
+    ///
+    /// Defaults to counting the FdCan input clock without prescaling.
     #[inline]
     pub const fn set_timestamp_source(mut self, tss: TimestampSource) -> Self {
         self.timestamp_source = tss;
@@ -477,6 +598,14 @@ impl FdCanConfig {
         self.automatic_bus_off_recovery = enabled;
         self
     }
+
+    /// Sets the partitioning of the message RAM between filters, RX FIFOs and TX buffers
+    #[cfg(can_fdcan_v2)]
+    #[inline]
+    pub const fn set_message_ram_config(mut self, config: MessageRamConfig) -> Self {
+        self.message_ram = config;
+        self
+    }
 }
 
 impl Default for FdCanConfig {
@@ -492,10 +621,12 @@ impl Default for FdCanConfig {
             edge_filtering: false,
             protocol_exception_handling: true,
             clock_divider: ClockDivider::_1,
-            timestamp_source: TimestampSource::None,
+            timestamp_source: TimestampSource::Prescaler(TimestampPrescaler::_1),
             global_filter: GlobalFilter::default(),
             tx_buffer_mode: TxBufferMode::Priority,
             automatic_bus_off_recovery: true,
+            #[cfg(can_fdcan_v2)]
+            message_ram: MessageRamConfig::default(),
         }
     }
 }
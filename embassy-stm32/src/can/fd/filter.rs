@@ -54,6 +54,38 @@ impl StandardFilter {
             action: Action::Disable,
         }
     }
+
+    /// Match all IDs from `from` to `to`, both inclusive
+    pub fn range(from: StandardId, to: StandardId, action: Action) -> StandardFilter {
+        StandardFilter {
+            filter: FilterType::Range { from, to },
+            action,
+        }
+    }
+
+    /// Match a single ID
+    pub fn single(id: StandardId, action: Action) -> StandardFilter {
+        StandardFilter {
+            filter: FilterType::DedicatedSingle(id),
+            action,
+        }
+    }
+
+    /// Match either of two IDs
+    pub fn dual(id1: StandardId, id2: StandardId, action: Action) -> StandardFilter {
+        StandardFilter {
+            filter: FilterType::DedicatedDual(id1, id2),
+            action,
+        }
+    }
+
+    /// Match all IDs for which the bits set in `mask` equal those of `filter`
+    pub fn mask(filter: u16, mask: u16, action: Action) -> StandardFilter {
+        StandardFilter {
+            filter: FilterType::BitMask { filter, mask },
+            action,
+        }
+    }
 }
 
 impl ExtendedFilter {
@@ -88,6 +120,38 @@ impl ExtendedFilter {
             action: Action::Disable,
         }
     }
+
+    /// Match all IDs from `from` to `to`, both inclusive
+    pub fn range(from: ExtendedId, to: ExtendedId, action: Action) -> ExtendedFilter {
+        ExtendedFilter {
+            filter: FilterType::Range { from, to },
+            action,
+        }
+    }
+
+    /// Match a single ID
+    pub fn single(id: ExtendedId, action: Action) -> ExtendedFilter {
+        ExtendedFilter {
+            filter: FilterType::DedicatedSingle(id),
+            action,
+        }
+    }
+
+    /// Match either of two IDs
+    pub fn dual(id1: ExtendedId, id2: ExtendedId, action: Action) -> ExtendedFilter {
+        ExtendedFilter {
+            filter: FilterType::DedicatedDual(id1, id2),
+            action,
+        }
+    }
+
+    /// Match all IDs for which the bits set in `mask` equal those of `filter`
+    pub fn mask(filter: u32, mask: u32, action: Action) -> ExtendedFilter {
+        ExtendedFilter {
+            filter: FilterType::BitMask { filter, mask },
+            action,
+        }
+    }
 }
 
 /// Filter Type
@@ -326,6 +390,8 @@ where
     UNIT: Copy + Clone + core::fmt::Debug,
 {
     fn activate(&mut self, f: Filter<ID, UNIT>);
+    /// Whether the filter element is in use, i.e. its action is not [`Action::Disable`]
+    fn is_enabled(&self) -> bool;
     // fn read(&self) -> Filter<ID, UNIT>;
 }
 
@@ -349,6 +415,9 @@ impl ActivateFilter<StandardId, u16> for message_ram::StandardFilter {
                 .set_filter_element_config(sfec)
         });
     }
+    fn is_enabled(&self) -> bool {
+        self.read().sfec().bits() != 0
+    }
     // fn read(&self) -> Filter<StandardId, u16> {
     //     todo!()
     // }
@@ -373,6 +442,9 @@ impl ActivateFilter<ExtendedId, u32> for message_ram::ExtendedFilter {
                 .set_filter_element_config(efec)
         });
     }
+    fn is_enabled(&self) -> bool {
+        self.read().efec().bits() != 0
+    }
     // fn read(&self) -> Filter<ExtendedId, u32> {
     //     todo!()
     // }
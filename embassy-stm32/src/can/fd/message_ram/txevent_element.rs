@@ -5,6 +5,7 @@
 #![allow(unused)]
 
 use super::common::{BRS_R, DLC_R, ESI_R, RTR_R, XTD_R};
+use super::enums::{DataLength, FrameFormat};
 use super::generic;
 
 #[doc = "Reader of register TxEventElement"]
@@ -135,4 +136,24 @@ impl R {
     pub fn mm(&self) -> MM_R {
         MM_R::new(((self.bits[1] >> 24) & 0xFF) as u8)
     }
+    pub fn to_data_length(&self) -> DataLength {
+        let dlc = self.dlc().bits();
+        if self.edl().is_fdcan_length() {
+            // See RM0433 Rev 7 Table 475. DLC coding
+            let len = match dlc {
+                0..=8 => dlc,
+                9 => 12,
+                10 => 16,
+                11 => 20,
+                12 => 24,
+                13 => 32,
+                14 => 48,
+                15 => 64,
+                _ => panic!("DLC > 15"),
+            };
+            DataLength::new(len, FrameFormat::Fdcan)
+        } else {
+            DataLength::new(dlc.min(8), FrameFormat::Classic)
+        }
+    }
 }
@@ -7,8 +7,10 @@ use cfg_if::cfg_if;
 
 use crate::can::enums::*;
 use crate::can::fd::config::*;
+#[cfg(not(can_fdcan_v2))]
+use crate::can::fd::message_ram::RegisterBlock;
 use crate::can::fd::message_ram::enums::*;
-use crate::can::fd::message_ram::{RegisterBlock, RxFifoElement, TxBufferElement};
+use crate::can::fd::message_ram::{ExtendedFilter, RxFifoElement, StandardFilter, TxBufferElement, TxEventElement};
 use crate::can::frame::*;
 
 /// Loopback Mode
@@ -27,21 +29,91 @@ pub struct Registers {
 }
 
 impl Registers {
+    /// Element `index` of a message RAM section starting at `start_words`.
+    ///
+    /// The start addresses are read back from the section configuration registers, so this
+    /// follows whatever partitioning was configured.
+    #[cfg(can_fdcan_v2)]
+    fn msg_ram_element<E>(&self, start_words: u16, index: usize) -> &mut E {
+        let ptr = self.msgram.ram(start_words as usize).as_ptr() as *mut E;
+        unsafe { &mut *ptr.add(index) }
+    }
+
     fn tx_buffer_element(&self, bufidx: usize) -> &mut TxBufferElement {
-        &mut self.msg_ram_mut().transmit.tbsa[bufidx]
+        #[cfg(can_fdcan_v2)]
+        return self.msg_ram_element(self.regs.txbc().read().tbsa(), bufidx);
+        #[cfg(not(can_fdcan_v2))]
+        return &mut self.msg_ram_mut().transmit.tbsa[bufidx];
     }
-    pub fn msg_ram_mut(&self) -> &mut RegisterBlock {
+
+    fn tx_event_element(&self, idx: usize) -> &mut TxEventElement {
+        #[cfg(can_fdcan_v2)]
+        return self.msg_ram_element(self.regs.txefc().read().efsa(), idx);
+        #[cfg(not(can_fdcan_v2))]
+        return &mut self.msg_ram_mut().transmit.efsa[idx];
+    }
+
+    /// Standard ID filter element in `slot`.
+    pub(crate) fn standard_filter(&self, slot: usize) -> &mut StandardFilter {
+        assert!(
+            slot < self.standard_filter_count(),
+            "Standard filter slot not configured"
+        );
+        #[cfg(can_fdcan_v2)]
+        return self.msg_ram_element(self.regs.sidfc().read().flssa(), slot);
+        #[cfg(not(can_fdcan_v2))]
+        return &mut self.msg_ram_mut().filters.flssa[slot];
+    }
+
+    /// Extended ID filter element in `slot`.
+    pub(crate) fn extended_filter(&self, slot: usize) -> &mut ExtendedFilter {
+        assert!(
+            slot < self.extended_filter_count(),
+            "Extended filter slot not configured"
+        );
+        #[cfg(can_fdcan_v2)]
+        return self.msg_ram_element(self.regs.xidfc().read().flesa(), slot);
+        #[cfg(not(can_fdcan_v2))]
+        return &mut self.msg_ram_mut().filters.flesa[slot];
+    }
+
+    /// Number of standard ID filter elements in the message RAM.
+    pub(crate) fn standard_filter_count(&self) -> usize {
         #[cfg(can_fdcan_v2)]
-        let ptr = self.msgram.ram(self.msg_ram_offset / 4).as_ptr() as *mut RegisterBlock;
+        return self.regs.sidfc().read().lss() as usize;
+        #[cfg(not(can_fdcan_v2))]
+        return crate::can::fd::message_ram::STANDARD_FILTER_MAX as usize;
+    }
 
+    /// Number of extended ID filter elements in the message RAM.
+    pub(crate) fn extended_filter_count(&self) -> usize {
+        #[cfg(can_fdcan_v2)]
+        return self.regs.xidfc().read().lse() as usize;
         #[cfg(not(can_fdcan_v2))]
+        return crate::can::fd::message_ram::EXTENDED_FILTER_MAX as usize;
+    }
+
+    /// Number of TX buffers used by the TX FIFO or priority queue.
+    pub(crate) fn tx_buffer_count(&self) -> usize {
+        #[cfg(can_fdcan_v2)]
+        return self.regs.txbc().read().tfqs() as usize;
+        #[cfg(not(can_fdcan_v2))]
+        return crate::can::fd::message_ram::TX_FIFO_MAX as usize;
+    }
+
+    /// Fixed message RAM layout, only valid on instances without a configurable message RAM.
+    #[cfg(not(can_fdcan_v2))]
+    pub fn msg_ram_mut(&self) -> &mut RegisterBlock {
         let ptr = self.msgram.as_ptr() as *mut RegisterBlock;
 
         unsafe { &mut (*ptr) }
     }
 
     fn rx_fifo_element(&self, fifonr: usize, bufnum: usize) -> &mut RxFifoElement {
-        &mut self.msg_ram_mut().receive[fifonr].fxsa[bufnum]
+        #[cfg(can_fdcan_v2)]
+        return self.msg_ram_element(self.regs.rxfc(fifonr).read().fsa(), bufnum);
+        #[cfg(not(can_fdcan_v2))]
+        return &mut self.msg_ram_mut().receive[fifonr].fxsa[bufnum];
     }
 
     pub fn read<F: CanHeader>(&self, fifonr: usize) -> Option<(F, u16)> {
@@ -71,6 +143,35 @@ impl Registers {
         }
     }
 
+    /// Pops the oldest element from the TX event FIFO.
+    ///
+    /// Returns the header of the transmitted frame, its message marker and the timestamp counter
+    /// value captured at the start of frame.
+    pub fn read_tx_event(&self) -> Option<(Header, u8, u16)> {
+        let txefs = self.regs.txefs().read();
+        if txefs.effl() < 1 {
+            return None;
+        }
+
+        let get_idx = txefs.efgi();
+        let event = self.tx_event_element(get_idx as usize).read();
+
+        let id = make_id(event.id().bits(), event.xtd().bits());
+        let len = event.to_data_length().len();
+        let header = if event.edl().is_fdcan_length() {
+            Header::new_fd(id, len, event.rtr().bits(), event.brs().bits())
+        } else {
+            Header::new(id, len, event.rtr().bits())
+        };
+        let marker = event.mm().bits();
+        let ts = event.txts().bits();
+
+        // Acknowledge the element, frees it and increments the get index
+        self.regs.txefa().modify(|w| w.set_efai(get_idx));
+
+        Some((header, marker, ts))
+    }
+
     #[cfg(feature = "time")]
     pub fn calc_timestamp(&self, ns_per_timer_tick: u64, ts_val: u16) -> Timestamp {
         let now_embassy = embassy_time::Instant::now();
@@ -88,10 +189,10 @@ impl Registers {
         ts_val
     }
 
-    pub fn put_tx_frame(&self, bufidx: usize, header: &Header, buffer: &[u8]) {
+    pub fn put_tx_frame(&self, bufidx: usize, header: &Header, buffer: &[u8], event: Event) {
         let mailbox = self.tx_buffer_element(bufidx);
         mailbox.reset();
-        put_tx_header(mailbox, header);
+        put_tx_header(mailbox, header, event);
         put_tx_data(mailbox, buffer);
 
         // Set <idx as Mailbox> as ready to transmit
@@ -112,6 +213,28 @@ impl Registers {
         }
     }
 
+    /// Reads the error counters and protocol status
+    ///
+    /// Reading the protocol status register clears the last error code.
+    pub fn error_status(&self) -> ErrorStatus {
+        let ecr = self.regs.ecr().read();
+        let psr = self.regs.psr().read();
+        cfg_if! {
+            if #[cfg(can_fdcan_v2)] {
+                let lec = psr.lec();
+            } else {
+                let lec = psr.lec().to_bits();
+            }
+        }
+        ErrorStatus {
+            tx_error_count: ecr.tec(),
+            rx_error_count: ecr.rec(),
+            error_warning: psr.ew(),
+            mode: BusErrorMode::from_flags(psr.bo(), psr.ep()),
+            last_error: Self::reg_to_error(lec),
+        }
+    }
+
     pub fn curr_error(&self) -> Option<BusError> {
         let err = { self.regs.psr().read() };
         cfg_if! {
@@ -221,6 +344,19 @@ impl Registers {
     }
 
     pub fn write<F: embedded_can::Frame + CanHeader>(&self, frame: &F) -> nb::Result<Option<F>, Infallible> {
+        self.write_with_event(frame, Event::NoEvent)
+            .map(|(_, pending_frame)| pending_frame)
+    }
+
+    /// Like [`Registers::write`], storing an element with the message marker of `event` in the
+    /// TX event FIFO once the frame was transmitted.
+    ///
+    /// Also returns the index of the TX buffer the frame was put in.
+    pub fn write_with_event<F: embedded_can::Frame + CanHeader>(
+        &self,
+        frame: &F,
+        event: Event,
+    ) -> nb::Result<(usize, Option<F>), Infallible> {
         let (idx, pending_frame) = if self.tx_queue_is_full() {
             if self.tx_queue_mode() == TxBufferMode::Fifo {
                 // Does not make sense to cancel a pending frame when using FIFO
@@ -229,16 +365,13 @@ impl Registers {
             // If the queue is full,
             // Discard the first slot with a lower priority message
             let id = frame.header().id();
-            if self.is_available(0, id) {
-                (0, self.abort_pending_mailbox(0))
-            } else if self.is_available(1, id) {
-                (1, self.abort_pending_mailbox(1))
-            } else if self.is_available(2, id) {
-                (2, self.abort_pending_mailbox(2))
-            } else {
-                // For now we bail when there is no lower priority slot available
-                // Can this lead to priority inversion?
-                return Err(nb::Error::WouldBlock);
+            match (0..self.tx_buffer_count()).find(|&idx| self.is_available(idx, id)) {
+                Some(idx) => (idx as u8, self.abort_pending_mailbox(idx)),
+                None => {
+                    // For now we bail when there is no lower priority slot available
+                    // Can this lead to priority inversion?
+                    return Err(nb::Error::WouldBlock);
+                }
             }
         } else {
             // Read the Write Pointer
@@ -247,16 +380,29 @@ impl Registers {
             (idx, None)
         };
 
-        self.put_tx_frame(idx as usize, frame.header(), frame.data());
+        self.put_tx_frame(idx as usize, frame.header(), frame.data(), event);
 
-        Ok(pending_frame)
+        Ok((idx as usize, pending_frame))
     }
 
+    #[cfg(not(can_fdcan_v2))]
     #[inline]
-    fn reset_msg_ram(&self) {
+    fn reset_msg_ram(&self, _config: &FdCanConfig) {
         self.msg_ram_mut().reset();
     }
 
+    #[cfg(can_fdcan_v2)]
+    #[inline]
+    fn reset_msg_ram(&self, config: &FdCanConfig) {
+        let ram = config.message_ram;
+        ram.validate(self.msg_ram_offset / 4);
+
+        let start = ram.start(self.msg_ram_offset / 4);
+        for word in start..start + ram.words() {
+            self.msgram.ram(word).write_value(0);
+        }
+    }
+
     #[inline]
     fn enter_init_mode(&self) {
         self.regs.cccr().modify(|w| w.set_init(true));
@@ -309,10 +455,10 @@ impl Registers {
 
     /// Moves out of PoweredDownMode and into ConfigMode
     #[inline]
-    pub fn into_config_mode(&self, _config: FdCanConfig) {
+    pub fn into_config_mode(&self, config: FdCanConfig) {
         self.set_power_down_mode(false);
         self.enter_init_mode();
-        self.reset_msg_ram();
+        self.reset_msg_ram(&config);
 
         // check the FDCAN core matches our expections
         assert!(
@@ -343,6 +489,7 @@ impl Registers {
         // set extended filters list size to 8
         // REQUIRED: we use the memory map as if these settings are set
         // instead of re-calculating them.
+        // On FDCAN instances with a configurable message RAM, the sizes are set in `configure_msg_ram`.
         #[cfg(not(can_fdcan_v2))]
         {
             self.regs.rxgfc().modify(|w| {
@@ -350,25 +497,11 @@ impl Registers {
                 w.set_lse(crate::can::fd::message_ram::EXTENDED_FILTER_MAX);
             });
         }
-        #[cfg(can_fdcan_v2)]
-        {
-            self.regs
-                .sidfc()
-                .modify(|w| w.set_lss(crate::can::fd::message_ram::STANDARD_FILTER_MAX));
-            self.regs
-                .xidfc()
-                .modify(|w| w.set_lse(crate::can::fd::message_ram::EXTENDED_FILTER_MAX));
-        }
 
-        self.configure_msg_ram();
+        self.configure_msg_ram(&config);
 
         // Enable timestamping
-        #[cfg(not(can_fdcan_v2))]
-        self.regs
-            .tscc()
-            .write(|w| w.set_tss(stm32_metapac::can::vals::Tss::Increment));
-        #[cfg(can_fdcan_v2)]
-        self.regs.tscc().write(|w| w.set_tss(0x01));
+        self.set_timestamp_counter_source(config.timestamp_source);
 
         // this isn't really documented in the reference manual
         // but corresponding txbtie bit has to be set for the TC (TxComplete) interrupt to fire
@@ -377,7 +510,10 @@ impl Registers {
             w.set_rfne(0, true); // Rx Fifo 0 New Msg
             w.set_rfne(1, true); // Rx Fifo 1 New Msg
             w.set_tce(true); //  Tx Complete
+            w.set_tefne(true); // Tx Event FIFO New Entry
             w.set_boe(true); // Bus-Off Status Changed
+            w.set_epe(true); // Error Passive Status Changed
+            w.set_ewe(true); // Error Warning Status Changed
         });
         self.regs.ile().modify(|w| {
             w.set_eint0(true); // Interrupt Line 0
@@ -409,6 +545,10 @@ impl Registers {
     /// Moves out of ConfigMode and into specified mode
     #[inline]
     pub fn into_mode(&self, config: FdCanConfig, mode: crate::can::_version::OperatingMode) {
+        // Clear the bits of a previous mode
+        self.set_loopback_mode(LoopbackMode::None);
+        self.set_restricted_operations(false);
+
         match mode {
             crate::can::OperatingMode::InternalLoopbackMode => self.set_loopback_mode(LoopbackMode::Internal),
             crate::can::OperatingMode::ExternalLoopbackMode => self.set_loopback_mode(LoopbackMode::External),
@@ -419,6 +559,15 @@ impl Registers {
         self.leave_init_mode(config);
     }
 
+    /// Switches between operating modes without resetting the message RAM
+    ///
+    /// Filters are kept. Entering configuration mode cancels all pending transmission requests.
+    #[inline]
+    pub fn set_operating_mode(&self, config: FdCanConfig, mode: crate::can::_version::OperatingMode) {
+        self.enter_init_mode();
+        self.into_mode(config, mode);
+    }
+
     /// Configures the bit timings.
     ///
     /// You can use <http://www.bittiming.can-wiki.info/> to calculate the `btr` parameter. Enter
@@ -450,7 +599,8 @@ impl Registers {
             w.set_dtseg1(btr.dtseg1() - 1);
             w.set_dtseg2(btr.dtseg2() - 1);
             w.set_dsjw(btr.dsjw() - 1);
-            w.set_tdc(btr.transceiver_delay_compensation);
+            // Transceiver delay compensation is only supported with a prescaler of 1 or 2.
+            w.set_tdc(btr.transceiver_delay_compensation && btr.dbrp() <= 2);
         });
     }
 
@@ -595,49 +745,55 @@ impl Registers {
     }
 
     #[cfg(not(can_fdcan_v2))]
-    fn configure_msg_ram(&self) {}
+    fn configure_msg_ram(&self, _config: &FdCanConfig) {}
 
     #[cfg(can_fdcan_v2)]
-    fn configure_msg_ram(&self) {
+    fn configure_msg_ram(&self, config: &FdCanConfig) {
         let r = self.regs;
+        let ram = config.message_ram;
+        ram.validate(self.msg_ram_offset / 4);
 
-        use crate::can::fd::message_ram::*;
-        //use fdcan::message_ram::*;
-        let mut offset_words = (self.msg_ram_offset / 4) as u16;
+        let mut offset_words = ram.start(self.msg_ram_offset / 4) as u16;
 
         // 11-bit filter
-        r.sidfc().modify(|w| w.set_flssa(offset_words));
-        offset_words += STANDARD_FILTER_MAX as u16;
+        r.sidfc().modify(|w| {
+            w.set_flssa(offset_words);
+            w.set_lss(ram.standard_filters);
+        });
+        offset_words += ram.standard_filters as u16;
 
         // 29-bit filter
-        r.xidfc().modify(|w| w.set_flesa(offset_words));
-        offset_words += 2 * EXTENDED_FILTER_MAX as u16;
+        r.xidfc().modify(|w| {
+            w.set_flesa(offset_words);
+            w.set_lse(ram.extended_filters);
+        });
+        offset_words += 2 * ram.extended_filters as u16;
 
         // Rx FIFO 0 and 1
-        for i in 0..=1 {
+        for (i, size) in [ram.rx_fifo0, ram.rx_fifo1].into_iter().enumerate() {
             r.rxfc(i).modify(|w| {
                 w.set_fsa(offset_words);
-                w.set_fs(RX_FIFO_MAX);
-                w.set_fwm(RX_FIFO_MAX);
+                w.set_fs(size);
+                w.set_fwm(size);
             });
-            offset_words += 18 * RX_FIFO_MAX as u16;
+            offset_words += 18 * size as u16;
         }
 
         // Rx buffer - see below
         // Tx event FIFO
         r.txefc().modify(|w| {
             w.set_efsa(offset_words);
-            w.set_efs(TX_EVENT_MAX);
-            w.set_efwm(TX_EVENT_MAX);
+            w.set_efs(ram.tx_events);
+            w.set_efwm(ram.tx_events);
         });
-        offset_words += 2 * TX_EVENT_MAX as u16;
+        offset_words += 2 * ram.tx_events as u16;
 
         // Tx buffers
         r.txbc().modify(|w| {
             w.set_tbsa(offset_words);
-            w.set_tfqs(TX_FIFO_MAX);
+            w.set_tfqs(ram.tx_buffers);
         });
-        offset_words += 18 * TX_FIFO_MAX as u16;
+        offset_words += 18 * ram.tx_buffers as u16;
 
         // Rx Buffer - not used
         r.rxbc().modify(|w| {
@@ -669,7 +825,7 @@ fn make_id(id: u32, extended: bool) -> embedded_can::Id {
     }
 }
 
-fn put_tx_header(mailbox: &mut TxBufferElement, header: &Header) {
+fn put_tx_header(mailbox: &mut TxBufferElement, header: &Header, event: Event) {
     let (id, id_type) = match header.id() {
         // A standard identifier has to be written to ID[28:18].
         embedded_can::Id::Standard(id) => ((id.as_raw() as u32) << 18, IdType::StandardId),
@@ -691,7 +847,7 @@ fn put_tx_header(mailbox: &mut TxBufferElement, header: &Header) {
             .xtd()
             .set_id_type(id_type)
             .set_len(DataLength::new(header.len(), frame_format))
-            .set_event(Event::NoEvent)
+            .set_event(event)
             .fdf()
             .set_format(frame_format)
             .brs()
@@ -19,6 +19,7 @@ pub(crate) mod fd;
 
 use self::fd::config::*;
 use self::fd::filter::*;
+use self::fd::message_ram::enums::Event;
 pub use self::fd::{config, filter};
 pub use super::common::{BufferedCanReceiver, BufferedCanSender};
 use super::common::{InfoRef, RxInfoRef, TxInfoRef};
@@ -79,6 +80,10 @@ impl<T: Instance> interrupt::typelevel::Handler<T::IT0Interrupt> for IT0Interrup
                 }
             }
 
+            if ir.tefn() {
+                state.tx_event_waker.wake();
+            }
+
             if ir.rfn(0) {
                 state.rx_mode.on_interrupt::<T>(0, state.ns_per_timer_tick);
             }
@@ -98,6 +103,15 @@ impl<T: Instance> interrupt::typelevel::Handler<T::IT0Interrupt> for IT0Interrup
                 regs.cccr().modify(|w| w.set_init(false));
             }
         }
+        if ir.ep() {
+            regs.ir().write(|w| w.set_ep(true));
+        }
+        if ir.ew() {
+            regs.ir().write(|w| w.set_ew(true));
+        }
+        if ir.bo() || ir.ep() || ir.ew() {
+            T::info().state.lock(|s| s.borrow().error_state_waker.wake());
+        }
     }
 }
 
@@ -153,16 +167,18 @@ fn calc_ns_per_timer_tick(
     info: &'static Info,
     freq: crate::time::Hertz,
     mode: crate::can::fd::config::FrameTransmissionConfig,
+    source: TimestampSource,
 ) -> u64 {
-    match mode {
+    match (mode, source) {
         // Use timestamp from Rx FIFO to adjust timestamp reported to user
-        crate::can::fd::config::FrameTransmissionConfig::ClassicCanOnly => {
+        (crate::can::fd::config::FrameTransmissionConfig::ClassicCanOnly, TimestampSource::Prescaler(_)) => {
             let prescale: u64 = ({ info.regs.regs.nbtp().read().nbrp() } + 1) as u64
                 * ({ info.regs.regs.tscc().read().tcp() } + 1) as u64;
             1_000_000_000 as u64 / (freq.0 as u64 * prescale)
         }
         // For VBR this is too hard because the FDCAN timer switches clock rate you need to configure to use
-        // timer3 instead which is too hard to do from this module.
+        // timer3 instead which is too hard to do from this module. The TIM3 tick rate is not known here
+        // either, so those users have to work with the raw timestamp.
         _ => 0,
     }
 }
@@ -247,24 +263,24 @@ impl<'d> CanConfigurator<'d> {
     }
 
     /// Configures the bit timings for VBR data calculated from supplied bitrate. This also sets config to allow can FD and VBR
+    ///
+    /// The smallest possible prescaler is used, so that transceiver delay compensation is available
+    /// for data bitrates of several Mbit/s. Frames are only sent with bit rate switching if requested
+    /// in their header, see [`FdFrame::set_bit_rate_switching`].
+    ///
+    /// Transceiver delay compensation is only enabled if the resulting prescaler is 1 or 2, as the
+    /// hardware doesn't support it otherwise.
     pub fn set_fd_data_bitrate(&mut self, bitrate: u32, transceiver_delay_compensation: bool) {
-        let bit_timing = unwrap!(util::calc_can_timings(self.properties.kernel_input_clock(), bitrate));
-        // Note, used existing calculation for normal(non-VBR) bitrate, appears to work for 250k/1M
-        let tdc_offset = if transceiver_delay_compensation {
-            // sets at the end of tseg1
-            (1 + u8::from(bit_timing.seg1)) * u16::from(bit_timing.prescaler) as u8
-        } else {
-            0
-        };
-        let dbtr = crate::can::fd::config::DataBitTiming {
-            sync_jump_width: bit_timing.sync_jump_width,
-            prescaler: bit_timing.prescaler,
-            seg1: bit_timing.seg1,
-            seg2: bit_timing.seg2,
-            transceiver_delay_compensation,
-            tdc_offset,
-            tdc_filter_window_length: 0,
-        };
+        let mut dbtr = unwrap!(util::calc_can_fd_data_timings(
+            self.properties.kernel_input_clock(),
+            bitrate
+        ));
+        dbtr.transceiver_delay_compensation = transceiver_delay_compensation && u16::from(dbtr.prescaler) <= 2;
+        if dbtr.transceiver_delay_compensation {
+            // Place the secondary sample point at the sample point of the data bit, in kernel clock periods
+            let offset = (1 + u8::from(dbtr.seg1) as u16) * u16::from(dbtr.prescaler);
+            dbtr.tdc_offset = offset.min(0x3F) as u8;
+        }
         self.config.frame_transmit = FrameTransmissionConfig::AllowFdCanAndBRS;
         self.config = self.config.set_data_bit_timing(dbtr);
     }
@@ -275,6 +291,7 @@ impl<'d> CanConfigurator<'d> {
             &self.info,
             self.properties.kernel_input_clock(),
             self.config.frame_transmit,
+            self.config.timestamp_source,
         );
         self.info.state.lock(|s| {
             let mut state = s.borrow_mut();
@@ -306,6 +323,11 @@ impl<'d> CanConfigurator<'d> {
     pub fn into_external_loopback_mode(self) -> Can<'d> {
         self.start(OperatingMode::ExternalLoopbackMode)
     }
+
+    /// Start, entering mode. Does same as start(mode)
+    pub fn into_bus_monitoring_mode(self) -> Can<'d> {
+        self.start(OperatingMode::BusMonitoringMode)
+    }
 }
 
 /// FDCAN Instance
@@ -324,6 +346,22 @@ impl<'d> Can<'d> {
         &self.properties
     }
 
+    /// Get the current operating mode
+    pub fn operating_mode(&self) -> OperatingMode {
+        self._mode
+    }
+
+    /// Switch the operating mode at runtime, e.g. into [`OperatingMode::BusMonitoringMode`] to
+    /// listen silently, or into a loopback mode for a self-test.
+    ///
+    /// The configuration and the filters are kept. The peripheral briefly enters initialization
+    /// mode, so frames on the bus may be missed during the switch, and pending transmissions are
+    /// cancelled. Use [`flush`](Self::flush) first to finish them.
+    pub fn set_operating_mode(&mut self, mode: OperatingMode) {
+        self.info.regs.set_operating_mode(self.config, mode);
+        self._mode = mode;
+    }
+
     /// Flush one of the TX mailboxes.
     pub async fn flush(&self, idx: usize) {
         poll_fn(|cx| {
@@ -331,10 +369,9 @@ impl<'d> Can<'d> {
                 s.borrow_mut().tx_mode.register(cx.waker());
             });
 
-            if idx > 3 {
+            if idx >= self.info.regs.tx_buffer_count() {
                 panic!("Bad mailbox");
             }
-            let idx = 1 << idx;
             if !self.info.regs.regs.txbrp().read().trp(idx) {
                 return Poll::Ready(());
             }
@@ -365,6 +402,50 @@ impl<'d> Can<'d> {
         TxMode::write_fd(&self.info, frame).await
     }
 
+    /// Like [`Self::write`], additionally storing a [`TxEvent`] with `marker` in the TX event
+    /// FIFO once the frame was transmitted.
+    pub async fn write_with_marker(&mut self, frame: &Frame, marker: u8) -> Option<Frame> {
+        TxMode::write_generic(&self.info, frame, Event::Event(marker)).await.1
+    }
+
+    /// Like [`Self::write_fd`], additionally storing a [`TxEvent`] with `marker` in the TX event
+    /// FIFO once the frame was transmitted.
+    pub async fn write_fd_with_marker(&mut self, frame: &FdFrame, marker: u8) -> Option<FdFrame> {
+        TxMode::write_generic(&self.info, frame, Event::Event(marker)).await.1
+    }
+
+    /// Returns the next event from the TX event FIFO.
+    pub async fn read_tx_event(&mut self) -> TxEvent {
+        TxEvent::read(&self.info).await
+    }
+
+    /// Returns the next event from the TX event FIFO, or `None` if it is empty.
+    pub fn try_read_tx_event(&mut self) -> Option<TxEvent> {
+        TxEvent::try_read(&self.info)
+    }
+
+    /// Like [`Self::write`], additionally returning the [`TxBuffer`] the frame was queued in, which
+    /// can be used to cancel the transmission with [`Self::abort`].
+    pub async fn write_cancellable(&mut self, frame: &Frame) -> (TxBuffer, Option<Frame>) {
+        TxMode::write_generic(&self.info, frame, Event::NoEvent).await
+    }
+
+    /// Like [`Self::write_fd`], additionally returning the [`TxBuffer`] the frame was queued in,
+    /// which can be used to cancel the transmission with [`Self::abort`].
+    pub async fn write_fd_cancellable(&mut self, frame: &FdFrame) -> (TxBuffer, Option<FdFrame>) {
+        TxMode::write_generic(&self.info, frame, Event::NoEvent).await
+    }
+
+    /// Attempts to abort the sending of a frame that is pending in a TX buffer, e.g. because its
+    /// data was superseded.
+    ///
+    /// Returns `true` if the frame was aborted, or `false` if it was already transmitted or the
+    /// buffer was empty. In the latter case the buffer may already hold a newer frame, so only
+    /// abort buffers of frames which are known to be pending.
+    pub fn abort(&mut self, buffer: TxBuffer) -> bool {
+        self.info.regs.abort(buffer.index())
+    }
+
     /// Returns the next received message frame
     pub async fn read_fd(&mut self) -> Result<FdEnvelope, BusError> {
         RxMode::read_fd(&self.info).await
@@ -654,6 +735,108 @@ impl<'c, 'd> CanTx<'d> {
     pub async fn write_fd(&mut self, frame: &FdFrame) -> Option<FdFrame> {
         TxMode::write_fd(&self.info, frame).await
     }
+
+    /// Like [`Self::write`], additionally storing a [`TxEvent`] with `marker` in the TX event
+    /// FIFO once the frame was transmitted.
+    pub async fn write_with_marker(&mut self, frame: &Frame, marker: u8) -> Option<Frame> {
+        TxMode::write_generic(&self.info, frame, Event::Event(marker)).await.1
+    }
+
+    /// Like [`Self::write_fd`], additionally storing a [`TxEvent`] with `marker` in the TX event
+    /// FIFO once the frame was transmitted.
+    pub async fn write_fd_with_marker(&mut self, frame: &FdFrame, marker: u8) -> Option<FdFrame> {
+        TxMode::write_generic(&self.info, frame, Event::Event(marker)).await.1
+    }
+
+    /// Returns the next event from the TX event FIFO.
+    pub async fn read_tx_event(&mut self) -> TxEvent {
+        TxEvent::read(&self.info).await
+    }
+
+    /// Returns the next event from the TX event FIFO, or `None` if it is empty.
+    pub fn try_read_tx_event(&mut self) -> Option<TxEvent> {
+        TxEvent::try_read(&self.info)
+    }
+
+    /// Like [`Self::write`], additionally returning the [`TxBuffer`] the frame was queued in, which
+    /// can be used to cancel the transmission with [`Self::abort`].
+    pub async fn write_cancellable(&mut self, frame: &Frame) -> (TxBuffer, Option<Frame>) {
+        TxMode::write_generic(&self.info, frame, Event::NoEvent).await
+    }
+
+    /// Like [`Self::write_fd`], additionally returning the [`TxBuffer`] the frame was queued in,
+    /// which can be used to cancel the transmission with [`Self::abort`].
+    pub async fn write_fd_cancellable(&mut self, frame: &FdFrame) -> (TxBuffer, Option<FdFrame>) {
+        TxMode::write_generic(&self.info, frame, Event::NoEvent).await
+    }
+
+    /// Attempts to abort the sending of a frame that is pending in a TX buffer, e.g. because its
+    /// data was superseded.
+    ///
+    /// Returns `true` if the frame was aborted, or `false` if it was already transmitted or the
+    /// buffer was empty. In the latter case the buffer may already hold a newer frame, so only
+    /// abort buffers of frames which are known to be pending.
+    pub fn abort(&mut self, buffer: TxBuffer) -> bool {
+        self.info.regs.abort(buffer.index())
+    }
+}
+
+/// TX buffer a frame was queued in, returned by [`Can::write_cancellable`].
+///
+/// With [`TxBufferMode::Priority`] the pending frame with the highest priority is sent first, and a
+/// new frame replaces a pending one of lower priority if all buffers are in use. With
+/// [`TxBufferMode::Fifo`] frames are sent in the order they were queued. The mode is selected with
+/// [`FdCanConfig::set_tx_buffer_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TxBuffer(u8);
+
+impl TxBuffer {
+    /// Index of the TX buffer in the message RAM
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// An element of the TX event FIFO, confirming that a frame was transmitted.
+///
+/// Only frames queued with a message marker, e.g. with [`Can::write_with_marker`], generate TX
+/// events.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TxEvent {
+    /// Header of the transmitted frame.
+    pub header: Header,
+    /// Message marker the frame was queued with.
+    pub marker: u8,
+    /// Start of frame transmission.
+    ///
+    /// With the `time` feature this is converted from the timestamp counter relative to the
+    /// current time, so events must be read before the 16-bit counter wraps around.
+    pub ts: Timestamp,
+}
+
+impl TxEvent {
+    fn try_read(info: &'static Info) -> Option<TxEvent> {
+        let ns_per_timer_tick = info.state.lock(|s| s.borrow().ns_per_timer_tick);
+        info.regs.read_tx_event().map(|(header, marker, ts)| TxEvent {
+            header,
+            marker,
+            ts: info.regs.calc_timestamp(ns_per_timer_tick, ts),
+        })
+    }
+
+    async fn read(info: &'static Info) -> TxEvent {
+        poll_fn(|cx| {
+            info.state.lock(|s| s.borrow().tx_event_waker.register(cx.waker()));
+
+            match TxEvent::try_read(info) {
+                Some(event) => Poll::Ready(event),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
 }
 
 enum RxMode {
@@ -717,42 +900,31 @@ impl RxMode {
 
     //async fn read_classic<T: Instance>(&self) -> Result<Envelope, BusError> {
     fn try_read<T: Instance>(&self, ns_per_timer_tick: u64) -> Option<Result<Envelope, BusError>> {
-        if let Some((frame, ts)) = T::registers().read(0) {
-            let ts = T::registers().calc_timestamp(ns_per_timer_tick, ts);
-            Some(Ok(Envelope { ts, frame }))
-        } else if let Some((frame, ts)) = T::registers().read(1) {
-            let ts = T::registers().calc_timestamp(ns_per_timer_tick, ts);
-            Some(Ok(Envelope { ts, frame }))
-        } else if let Some(err) = T::registers().curr_error() {
-            // TODO: this is probably wrong
-            Some(Err(err))
-        } else {
-            None
+        match Self::read(T::info(), ns_per_timer_tick) {
+            Some(Ok((frame, ts, raw_ts))) => Some(Ok(Envelope { ts, raw_ts, frame })),
+            Some(Err(err)) => Some(Err(err)),
+            None => None,
         }
     }
 
     fn try_read_fd<T: Instance>(&self, ns_per_timer_tick: u64) -> Option<Result<FdEnvelope, BusError>> {
-        if let Some((frame, ts)) = T::registers().read(0) {
-            let ts = T::registers().calc_timestamp(ns_per_timer_tick, ts);
-            Some(Ok(FdEnvelope { ts, frame }))
-        } else if let Some((frame, ts)) = T::registers().read(1) {
-            let ts = T::registers().calc_timestamp(ns_per_timer_tick, ts);
-            Some(Ok(FdEnvelope { ts, frame }))
-        } else if let Some(err) = T::registers().curr_error() {
-            // TODO: this is probably wrong
-            Some(Err(err))
-        } else {
-            None
+        match Self::read(T::info(), ns_per_timer_tick) {
+            Some(Ok((frame, ts, raw_ts))) => Some(Ok(FdEnvelope { ts, raw_ts, frame })),
+            Some(Err(err)) => Some(Err(err)),
+            None => None,
         }
     }
 
-    fn read<F: CanHeader>(info: &'static Info, ns_per_timer_tick: u64) -> Option<Result<(F, Timestamp), BusError>> {
-        if let Some((msg, ts)) = info.regs.read(0) {
-            let ts = info.regs.calc_timestamp(ns_per_timer_tick, ts);
-            Some(Ok((msg, ts)))
-        } else if let Some((msg, ts)) = info.regs.read(1) {
-            let ts = info.regs.calc_timestamp(ns_per_timer_tick, ts);
-            Some(Ok((msg, ts)))
+    fn read<F: CanHeader>(
+        info: &'static Info,
+        ns_per_timer_tick: u64,
+    ) -> Option<Result<(F, Timestamp, u16), BusError>> {
+        if let Some((msg, raw_ts)) = info.regs.read(0) {
+            let ts = info.regs.calc_timestamp(ns_per_timer_tick, raw_ts);
+            Some(Ok((msg, ts, raw_ts)))
+        } else if let Some((msg, raw_ts)) = info.regs.read(1) {
+            let ts = info.regs.calc_timestamp(ns_per_timer_tick, raw_ts);
+            Some(Ok((msg, ts, raw_ts)))
         } else if let Some(err) = info.regs.curr_error() {
             // TODO: this is probably wrong
             Some(Err(err))
@@ -761,7 +933,7 @@ impl RxMode {
         }
     }
 
-    async fn read_async<F: CanHeader>(info: &'static Info) -> Result<(F, Timestamp), BusError> {
+    async fn read_async<F: CanHeader>(info: &'static Info) -> Result<(F, Timestamp, u16), BusError> {
         poll_fn(move |cx| {
             let ns_per_timer_tick = info.state.lock(|s| {
                 let state = s.borrow_mut();
@@ -779,14 +951,14 @@ impl RxMode {
 
     async fn read_classic(info: &'static Info) -> Result<Envelope, BusError> {
         match RxMode::read_async::<_>(info).await {
-            Ok((frame, ts)) => Ok(Envelope { ts, frame }),
+            Ok((frame, ts, raw_ts)) => Ok(Envelope { ts, raw_ts, frame }),
             Err(e) => Err(e),
         }
     }
 
     async fn read_fd(info: &'static Info) -> Result<FdEnvelope, BusError> {
         match RxMode::read_async::<_>(info).await {
-            Ok((frame, ts)) => Ok(FdEnvelope { ts, frame }),
+            Ok((frame, ts, raw_ts)) => Ok(FdEnvelope { ts, raw_ts, frame }),
             Err(e) => Err(e),
         }
     }
@@ -814,14 +986,18 @@ impl TxMode {
     /// frame is dropped from the mailbox, it is returned.  If no lower-priority frames
     /// can be replaced, this call asynchronously waits for a frame to be successfully
     /// transmitted, then tries again.
-    async fn write_generic<F: embedded_can::Frame + CanHeader>(info: &'static Info, frame: &F) -> Option<F> {
+    async fn write_generic<F: embedded_can::Frame + CanHeader>(
+        info: &'static Info,
+        frame: &F,
+        event: Event,
+    ) -> (TxBuffer, Option<F>) {
         poll_fn(|cx| {
             info.state.lock(|s| {
                 s.borrow_mut().tx_mode.register(cx.waker());
             });
 
-            if let Ok(dropped) = info.regs.write(frame) {
-                return Poll::Ready(dropped);
+            if let Ok((idx, dropped)) = info.regs.write_with_event(frame, event) {
+                return Poll::Ready((TxBuffer(idx as u8), dropped));
             }
 
             // Couldn't replace any lower priority frames.  Need to wait for some mailboxes
@@ -836,7 +1012,7 @@ impl TxMode {
     /// can be replaced, this call asynchronously waits for a frame to be successfully
     /// transmitted, then tries again.
     async fn write(info: &'static Info, frame: &Frame) -> Option<Frame> {
-        TxMode::write_generic::<_>(info, frame).await
+        TxMode::write_generic::<_>(info, frame, Event::NoEvent).await.1
     }
 
     /// Queues the message to be sent but exerts backpressure.  If a lower-priority
@@ -844,7 +1020,7 @@ impl TxMode {
     /// can be replaced, this call asynchronously waits for a frame to be successfully
     /// transmitted, then tries again.
     async fn write_fd(info: &'static Info, frame: &FdFrame) -> Option<FdFrame> {
-        TxMode::write_generic::<_>(info, frame).await
+        TxMode::write_generic::<_>(info, frame, Event::NoEvent).await.1
     }
 }
 
@@ -869,30 +1045,105 @@ impl Properties {
     }
 
     /// Set a standard address CAN filter in the specified slot in FDCAN memory.
+    ///
+    /// # Panics
+    /// Panics if `slot` is not part of the message RAM, see `config::MessageRamConfig` on
+    /// instances with a configurable message RAM.
     #[inline]
     pub fn set_standard_filter(&self, slot: StandardFilterSlot, filter: StandardFilter) {
-        self.info.regs.msg_ram_mut().filters.flssa[slot as usize].activate(filter);
+        self.info.regs.standard_filter(slot as usize).activate(filter);
+    }
+
+    /// Set a standard address CAN filter at `index` in FDCAN memory.
+    ///
+    /// Unlike [`Properties::set_standard_filter`], this reaches every filter element of the
+    /// message RAM, including those beyond the range of [`StandardFilterSlot`].
+    ///
+    /// # Panics
+    /// Panics if `index` is not part of the message RAM, see `config::MessageRamConfig`.
+    #[cfg(can_fdcan_v2)]
+    #[inline]
+    pub fn set_standard_filter_at(&self, index: usize, filter: StandardFilter) {
+        self.info.regs.standard_filter(index).activate(filter);
     }
 
     /// Set the full array of standard address CAN filters in FDCAN memory.
     /// Overwrites all standard address filters in memory.
+    ///
+    /// Filters beyond the number of standard filter elements in the message RAM are ignored.
     pub fn set_standard_filters(&self, filters: &[StandardFilter; STANDARD_FILTER_MAX as usize]) {
-        for (i, f) in filters.iter().enumerate() {
-            self.info.regs.msg_ram_mut().filters.flssa[i].activate(*f);
+        let count = self.info.regs.standard_filter_count();
+        for (i, f) in filters.iter().take(count).enumerate() {
+            self.info.regs.standard_filter(i).activate(*f);
         }
     }
 
     /// Set an extended address CAN filter in the specified slot in FDCAN memory.
+    ///
+    /// # Panics
+    /// Panics if `slot` is not part of the message RAM, see `config::MessageRamConfig` on
+    /// instances with a configurable message RAM.
     #[inline]
     pub fn set_extended_filter(&self, slot: ExtendedFilterSlot, filter: ExtendedFilter) {
-        self.info.regs.msg_ram_mut().filters.flesa[slot as usize].activate(filter);
+        self.info.regs.extended_filter(slot as usize).activate(filter);
+    }
+
+    /// Set an extended address CAN filter at `index` in FDCAN memory.
+    ///
+    /// Unlike [`Properties::set_extended_filter`], this reaches every filter element of the
+    /// message RAM, including those beyond the range of [`ExtendedFilterSlot`].
+    ///
+    /// # Panics
+    /// Panics if `index` is not part of the message RAM, see `config::MessageRamConfig`.
+    #[cfg(can_fdcan_v2)]
+    #[inline]
+    pub fn set_extended_filter_at(&self, index: usize, filter: ExtendedFilter) {
+        self.info.regs.extended_filter(index).activate(filter);
     }
 
     /// Set the full array of extended address CAN filters in FDCAN memory.
     /// Overwrites all extended address filters in memory.
+    ///
+    /// Filters beyond the number of extended filter elements in the message RAM are ignored.
     pub fn set_extended_filters(&self, filters: &[ExtendedFilter; EXTENDED_FILTER_MAX as usize]) {
-        for (i, f) in filters.iter().enumerate() {
-            self.info.regs.msg_ram_mut().filters.flesa[i].activate(*f);
+        let count = self.info.regs.extended_filter_count();
+        for (i, f) in filters.iter().take(count).enumerate() {
+            self.info.regs.extended_filter(i).activate(*f);
+        }
+    }
+
+    /// Add a standard address CAN filter in the first unused slot in FDCAN memory.
+    ///
+    /// A slot is unused if its filter action is [`Action::Disable`]. Returns the slot the filter
+    /// was written to, or `None` if all standard filter slots are in use. Only the elements in the
+    /// range of [`StandardFilterSlot`] are searched.
+    pub fn add_standard_filter(&self, filter: StandardFilter) -> Option<StandardFilterSlot> {
+        let regs = &self.info.regs;
+        let count = regs.standard_filter_count().min(STANDARD_FILTER_MAX as usize);
+        let slot = (0..count).find(|&i| !regs.standard_filter(i).is_enabled())?;
+        regs.standard_filter(slot).activate(filter);
+        Some(StandardFilterSlot::from(slot as u8))
+    }
+
+    /// Add an extended address CAN filter in the first unused slot in FDCAN memory.
+    ///
+    /// A slot is unused if its filter action is [`Action::Disable`]. Returns the slot the filter
+    /// was written to, or `None` if all extended filter slots are in use. Only the elements in the
+    /// range of [`ExtendedFilterSlot`] are searched.
+    pub fn add_extended_filter(&self, filter: ExtendedFilter) -> Option<ExtendedFilterSlot> {
+        let regs = &self.info.regs;
+        let count = regs.extended_filter_count().min(EXTENDED_FILTER_MAX as usize);
+        let slot = (0..count).find(|&i| !regs.extended_filter(i).is_enabled())?;
+        regs.extended_filter(slot).activate(filter);
+        Some(ExtendedFilterSlot::from(slot as u8))
+    }
+
+    /// Remove a CAN filter, freeing its slot for [`Properties::add_standard_filter`] or
+    /// [`Properties::add_extended_filter`].
+    pub fn remove_filter(&self, id: FilterId) {
+        match id {
+            FilterId::Standard(slot) => self.set_standard_filter(slot, StandardFilter::disable()),
+            FilterId::Extended(slot) => self.set_extended_filter(slot, ExtendedFilter::disable()),
         }
     }
 
@@ -912,11 +1163,36 @@ impl Properties {
         // error reporting in this driver should have a big ol' FIXME on it
         // anyway!
         let psr = self.info.regs.regs.psr().read();
-        match (psr.bo(), psr.ep()) {
-            (false, false) => BusErrorMode::ErrorActive,
-            (false, true) => BusErrorMode::ErrorPassive,
-            (true, _) => BusErrorMode::BusOff,
-        }
+        BusErrorMode::from_flags(psr.bo(), psr.ep())
+    }
+
+    /// Get the error counters, the error warning state, the bus error mode and the last error code
+    ///
+    /// Like [`Self::bus_error_mode`], this clears the last error code in the peripheral.
+    pub fn error_status(&self) -> ErrorStatus {
+        self.info.regs.error_status()
+    }
+
+    /// Wait until the error warning state or the bus error mode changes, and return the new status
+    ///
+    /// Like [`Self::error_status`], this clears the last error code in the peripheral.
+    pub async fn wait_for_error_state_change(&self) -> ErrorStatus {
+        let regs = &self.info.regs;
+        let initial = regs.error_status();
+
+        poll_fn(|cx| {
+            self.info
+                .state
+                .lock(|s| s.borrow().error_state_waker.register(cx.waker()));
+
+            let status = regs.error_status();
+            if (status.error_warning, status.mode) != (initial.error_warning, initial.mode) {
+                Poll::Ready(status)
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
     }
 }
 
@@ -930,6 +1206,8 @@ struct State {
     rx_pin_port: Option<u8>,
     automatic_bus_off_recovery: Option<bool>, // controlled by CanConfigurator::start()
     pub err_waker: AtomicWaker,
+    tx_event_waker: AtomicWaker,
+    error_state_waker: AtomicWaker,
 }
 
 impl State {
@@ -939,6 +1217,8 @@ impl State {
             tx_mode: TxMode::NonBuffered(AtomicWaker::new()),
             ns_per_timer_tick: 0,
             err_waker: AtomicWaker::new(),
+            tx_event_waker: AtomicWaker::new(),
+            error_state_waker: AtomicWaker::new(),
             receiver_instance_count: 0,
             sender_instance_count: 0,
             tx_pin_port: None,
@@ -84,6 +84,17 @@ impl Header {
         self.flags.get_bit(Self::FLAG_BRS)
     }
 
+    /// Request bit rate switching for the data phase
+    ///
+    /// Enabling bit rate switching also makes this a CAN FD frame, as classic CAN frames are always
+    /// sent with the nominal bit rate.
+    pub fn set_bit_rate_switching(&mut self, brs: bool) {
+        if brs {
+            self.flags.set_bit(Self::FLAG_FDCAN, true);
+        }
+        self.flags.set_bit(Self::FLAG_BRS, brs);
+    }
+
     /// Get priority of frame
     pub(crate) fn priority(&self) -> u32 {
         match self.id() {
@@ -296,6 +307,10 @@ impl CanHeader for Frame {
 pub struct Envelope {
     /// Reception time.
     pub ts: Timestamp,
+    /// Raw value of the 16-bit timestamp counter captured at the start of frame.
+    ///
+    /// See [`TimestampExtender`](crate::can::util::TimestampExtender) to turn it into a monotonic value.
+    pub raw_ts: u16,
     /// The actual CAN frame.
     pub frame: Frame,
 }
@@ -425,6 +440,11 @@ impl FdFrame {
     pub fn data_mut(&mut self) -> &mut [u8] {
         &mut self.data.raw_mut()[..self.can_header.len as usize]
     }
+
+    /// Send this frame with bit rate switching, i.e. transmit the data phase with the data bit rate
+    pub fn set_bit_rate_switching(&mut self, brs: bool) {
+        self.can_header.set_bit_rate_switching(brs);
+    }
 }
 
 impl embedded_can::Frame for FdFrame {
@@ -484,6 +504,11 @@ pub struct FdEnvelope {
     /// Reception time.
     pub ts: Timestamp,
 
+    /// Raw value of the 16-bit timestamp counter captured at the start of frame.
+    ///
+    /// See [`TimestampExtender`](crate::can::util::TimestampExtender) to turn it into a monotonic value.
+    pub raw_ts: u16,
+
     /// The actual CAN frame.
     pub frame: FdFrame,
 }
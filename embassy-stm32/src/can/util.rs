@@ -124,3 +124,97 @@ pub fn calc_can_timings(
         seg2,
     })
 }
+
+/// Calculate CAN FD data phase bit timing based on data bitrate and periphial clock frequency
+///
+/// Picks the smallest prescaler that gives a valid number of time quanta per bit, so that
+/// transceiver delay compensation can be used (it requires a data prescaler of 1 or 2). The sample
+/// point is placed at about 80%, the usual choice for the data phase. Transceiver delay
+/// compensation is left disabled.
+#[cfg(any(can_fdcan_v1, can_fdcan_v2))]
+pub fn calc_can_fd_data_timings(
+    periph_clock: crate::time::Hertz,
+    can_bitrate: u32,
+) -> Result<crate::can::fd::config::DataBitTiming, TimingCalcError> {
+    const DTSEG1_MAX: u32 = 32;
+    const DTSEG2_MAX: u32 = 16;
+    const DBRP_MAX: u32 = 32;
+
+    let periph_clock = periph_clock.0;
+
+    if can_bitrate < 1000 {
+        return Err(TimingCalcError::BitrateTooLow { bitrate: can_bitrate });
+    }
+
+    let prescaler_bs = periph_clock / can_bitrate;
+
+    // One sync quantum plus at least 2 quanta for the segments.
+    let prescaler = (1..=DBRP_MAX)
+        .find(|&p| {
+            let quanta = prescaler_bs / p;
+            prescaler_bs % p == 0 && quanta >= 4 && quanta <= 1 + DTSEG1_MAX + DTSEG2_MAX
+        })
+        .ok_or(TimingCalcError::InvalidPrescaler {
+            prescaler: prescaler_bs,
+        })?;
+
+    // Move the sample point later if BS1 would not fit otherwise.
+    let quanta = prescaler_bs / prescaler;
+    let bs2 = (quanta / 5)
+        .max(quanta.saturating_sub(1 + DTSEG1_MAX))
+        .clamp(1, DTSEG2_MAX);
+    let bs1 = quanta - 1 - bs2;
+
+    let calculated = periph_clock / (prescaler * quanta);
+    if can_bitrate != calculated {
+        return Err(TimingCalcError::NoMatch {
+            requested: can_bitrate,
+            final_calculated: calculated,
+        });
+    }
+
+    // One is recommended by DS-015, CANOpen, and DeviceNet
+    let sync_jump_width = core::num::NonZeroU8::new(1).ok_or(TimingCalcError::CoreNumNew)?;
+
+    let seg1 = core::num::NonZeroU8::new(bs1 as u8).ok_or(TimingCalcError::CoreNumNew)?;
+    let seg2 = core::num::NonZeroU8::new(bs2 as u8).ok_or(TimingCalcError::CoreNumNew)?;
+    let nz_prescaler = core::num::NonZeroU16::new(prescaler as u16).ok_or(TimingCalcError::CoreNumNew)?;
+
+    Ok(crate::can::fd::config::DataBitTiming {
+        sync_jump_width,
+        prescaler: nz_prescaler,
+        seg1,
+        seg2,
+        transceiver_delay_compensation: false,
+        tdc_offset: 0,
+        tdc_filter_window_length: 0,
+    })
+}
+
+/// Extends the 16-bit hardware timestamp counter to 64 bits.
+///
+/// The controller only captures 16 bits, which wrap quickly at high bit rates. This keeps track
+/// of the wrap-arounds in software. Values have to be passed to [`TimestampExtender::extend`] in
+/// capture order and at least once per counter period, otherwise a wrap-around goes unnoticed.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimestampExtender {
+    last: u16,
+    high: u64,
+}
+
+impl TimestampExtender {
+    /// Creates a new extender starting at zero.
+    pub const fn new() -> Self {
+        Self { last: 0, high: 0 }
+    }
+
+    /// Extends a raw 16-bit timestamp, e.g. [`Envelope::raw_ts`](crate::can::frame::Envelope::raw_ts).
+    pub fn extend(&mut self, raw: u16) -> u64 {
+        if raw < self.last {
+            self.high += 1 << 16;
+        }
+        self.last = raw;
+        self.high | raw as u64
+    }
+}
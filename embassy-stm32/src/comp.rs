@@ -3,13 +3,20 @@
 //! This driver supports chips with the comp_u5 peripheral version
 //! (STM32WBA and STM32U5 series), comp_v1 (STM32G0 series) and
 //! comp_v2 (STM32G4 series).
+//!
+//! The comparator output can be routed to a pin with [`Comp::new_with_output`], and to the
+//! break inputs of advanced timers from the timer side, see
+//! [`Timer::set_break_comparator_enable`](crate::timer::low_level::Timer::set_break_comparator_enable).
 #![macro_use]
 
-use core::future::poll_fn;
+use core::future::{Future, poll_fn};
 use core::marker::PhantomData;
+use core::pin::pin;
 use core::task::Poll;
 
+use embassy_futures::select::select;
 use embassy_hal_internal::PeripheralType;
+use embassy_hal_internal::drop::OnDrop;
 use embassy_sync::waitqueue::AtomicWaker;
 use stm32_metapac::comp::vals;
 
@@ -195,6 +202,111 @@ impl Default for Config {
     }
 }
 
+impl PowerMode {
+    #[cfg(any(comp_u5, comp_v1))]
+    fn to_vals(self) -> vals::PowerMode {
+        match self {
+            PowerMode::HighSpeed => vals::PowerMode::HighSpeed,
+            PowerMode::MediumSpeed => vals::PowerMode::MediumSpeed,
+            #[cfg(comp_u5)]
+            PowerMode::UltraLowPower => vals::PowerMode::UltraLow,
+        }
+    }
+}
+
+impl Hysteresis {
+    #[cfg(comp_v2)]
+    fn to_vals(self) -> vals::Hysteresis {
+        match self {
+            Hysteresis::None => vals::Hysteresis::None,
+            Hysteresis::Hyst10M => vals::Hysteresis::Hyst10m,
+            Hysteresis::Hyst20M => vals::Hysteresis::Hyst20m,
+            Hysteresis::Hyst30M => vals::Hysteresis::Hyst30m,
+            Hysteresis::Hyst40M => vals::Hysteresis::Hyst40m,
+            Hysteresis::Hyst50M => vals::Hysteresis::Hyst50m,
+            Hysteresis::Hyst60M => vals::Hysteresis::Hyst60m,
+            Hysteresis::Hyst70M => vals::Hysteresis::Hyst70m,
+        }
+    }
+
+    #[cfg(any(comp_u5, comp_v1))]
+    fn to_vals(self) -> vals::Hysteresis {
+        match self {
+            Hysteresis::None => vals::Hysteresis::None,
+            Hysteresis::Low => vals::Hysteresis::Low,
+            Hysteresis::Medium => vals::Hysteresis::Medium,
+            Hysteresis::High => vals::Hysteresis::High,
+        }
+    }
+}
+
+impl OutputPolarity {
+    fn to_vals(self) -> vals::Polarity {
+        match self {
+            OutputPolarity::NotInverted => vals::Polarity::NotInverted,
+            OutputPolarity::Inverted => vals::Polarity::Inverted,
+        }
+    }
+}
+
+impl InvertingInput {
+    fn to_vals(self) -> vals::Inm {
+        match self {
+            InvertingInput::OneQuarterVref => vals::Inm::QuarterVRef,
+            InvertingInput::HalfVref => vals::Inm::HalfVRef,
+            InvertingInput::ThreeQuarterVref => vals::Inm::ThreeQuarterVRef,
+            InvertingInput::Vref => vals::Inm::VRef,
+            #[cfg(any(comp_u5, comp_v1))]
+            InvertingInput::Dac1 => vals::Inm::Dac1,
+            #[cfg(any(comp_u5, comp_v1))]
+            InvertingInput::Dac2 => vals::Inm::Dac2,
+            #[cfg(comp_v2)]
+            InvertingInput::Dac1 => vals::Inm::Daca,
+            #[cfg(comp_v2)]
+            InvertingInput::Dac2 => vals::Inm::Dacb,
+
+            InvertingInput::InputPin => vals::Inm::Inm1,
+            #[cfg(any(comp_v1, comp_v2))]
+            InvertingInput::InputPin2 => vals::Inm::Inm2,
+            #[cfg(comp_v1)]
+            InvertingInput::InputPin3 => vals::Inm::Inm3,
+        }
+    }
+}
+
+impl BlankingSource {
+    fn to_vals(self) -> vals::Blanking {
+        match self {
+            BlankingSource::None => vals::Blanking::NoBlanking,
+            BlankingSource::Blank1 => vals::Blanking::Blank1,
+            BlankingSource::Blank2 => vals::Blanking::Blank2,
+            BlankingSource::Blank3 => vals::Blanking::Blank3,
+            #[cfg(any(comp_v1, comp_v2))]
+            BlankingSource::Blank4 => vals::Blanking::Blank4,
+            #[cfg(any(comp_v1, comp_v2))]
+            BlankingSource::Blank5 => vals::Blanking::Blank5,
+            #[cfg(comp_v2)]
+            BlankingSource::Blank6 => vals::Blanking::Blank6,
+            #[cfg(comp_v2)]
+            BlankingSource::Blank7 => vals::Blanking::Blank7,
+        }
+    }
+}
+
+// G4 COMP needs SCALEN/BRGEN bits to enable internal voltage references.
+// SCALEN enables the Vrefint scaler, BRGEN enables the bridge resistor divider.
+#[cfg(comp_v2)]
+fn set_scaler(w: &mut crate::pac::comp::regs::Csr, inmsel: vals::Inm) {
+    w.set_scalen(matches!(
+        inmsel,
+        vals::Inm::QuarterVRef | vals::Inm::HalfVRef | vals::Inm::ThreeQuarterVRef | vals::Inm::VRef
+    ));
+    w.set_brgen(matches!(
+        inmsel,
+        vals::Inm::QuarterVRef | vals::Inm::HalfVRef | vals::Inm::ThreeQuarterVRef
+    ));
+}
+
 /// Comparator state for async operations.
 pub struct State {
     waker: AtomicWaker,
@@ -346,53 +458,10 @@ impl<'d, T: Instance> Comp<'d, T> {
 
     fn configure_raw(inp_channel: u8, inmsel: vals::Inm, config: Config) {
         #[cfg(any(comp_u5, comp_v1))]
-        let pwrmode = match config.power_mode {
-            PowerMode::HighSpeed => vals::PowerMode::HighSpeed,
-            PowerMode::MediumSpeed => vals::PowerMode::MediumSpeed,
-            #[cfg(comp_u5)]
-            PowerMode::UltraLowPower => vals::PowerMode::UltraLow,
-        };
-
-        #[cfg(comp_v2)]
-        let hyst = match config.hysteresis {
-            Hysteresis::None => vals::Hysteresis::None,
-            Hysteresis::Hyst10M => vals::Hysteresis::Hyst10m,
-            Hysteresis::Hyst20M => vals::Hysteresis::Hyst20m,
-            Hysteresis::Hyst30M => vals::Hysteresis::Hyst30m,
-            Hysteresis::Hyst40M => vals::Hysteresis::Hyst40m,
-            Hysteresis::Hyst50M => vals::Hysteresis::Hyst50m,
-            Hysteresis::Hyst60M => vals::Hysteresis::Hyst60m,
-            Hysteresis::Hyst70M => vals::Hysteresis::Hyst70m,
-        };
-
-        #[cfg(any(comp_u5, comp_v1))]
-        let hyst = match config.hysteresis {
-            Hysteresis::None => vals::Hysteresis::None,
-            Hysteresis::Low => vals::Hysteresis::Low,
-            Hysteresis::Medium => vals::Hysteresis::Medium,
-            Hysteresis::High => vals::Hysteresis::High,
-        };
-
-        let polarity = match config.output_polarity {
-            OutputPolarity::NotInverted => vals::Polarity::NotInverted,
-            OutputPolarity::Inverted => vals::Polarity::Inverted,
-        };
-
-        #[cfg(any(comp_u5, comp_v1, comp_v2))]
-        let blanksel = match config.blanking_source {
-            BlankingSource::None => vals::Blanking::NoBlanking,
-            BlankingSource::Blank1 => vals::Blanking::Blank1,
-            BlankingSource::Blank2 => vals::Blanking::Blank2,
-            BlankingSource::Blank3 => vals::Blanking::Blank3,
-            #[cfg(any(comp_v1, comp_v2))]
-            BlankingSource::Blank4 => vals::Blanking::Blank4,
-            #[cfg(any(comp_v1, comp_v2))]
-            BlankingSource::Blank5 => vals::Blanking::Blank5,
-            #[cfg(comp_v2)]
-            BlankingSource::Blank6 => vals::Blanking::Blank6,
-            #[cfg(comp_v2)]
-            BlankingSource::Blank7 => vals::Blanking::Blank7,
-        };
+        let pwrmode = config.power_mode.to_vals();
+        let hyst = config.hysteresis.to_vals();
+        let polarity = config.output_polarity.to_vals();
+        let blanksel = config.blanking_source.to_vals();
 
         #[cfg(any(comp_u5, comp_v1))]
         let winmode = match config.window_mode {
@@ -415,20 +484,8 @@ impl<'d, T: Instance> Comp<'d, T> {
             w.set_hyst(hyst);
             w.set_polarity(polarity);
             w.set_blanksel(blanksel);
-
-            // G4 COMP needs SCALEN/BRGEN bits to enable internal voltage references.
-            // SCALEN enables the Vrefint scaler, BRGEN enables the bridge resistor divider.
             #[cfg(comp_v2)]
-            {
-                w.set_scalen(matches!(
-                    inmsel,
-                    vals::Inm::QuarterVRef | vals::Inm::HalfVRef | vals::Inm::ThreeQuarterVRef | vals::Inm::VRef
-                ));
-                w.set_brgen(matches!(
-                    inmsel,
-                    vals::Inm::QuarterVRef | vals::Inm::HalfVRef | vals::Inm::ThreeQuarterVRef
-                ));
-            }
+            set_scaler(w, inmsel);
 
             w.set_en(true);
             #[cfg(any(comp_u5, comp_v1))]
@@ -441,28 +498,7 @@ impl<'d, T: Instance> Comp<'d, T> {
     }
 
     fn configure(inp_channel: u8, config: Config) {
-        let inmsel = match config.inverting_input {
-            InvertingInput::OneQuarterVref => vals::Inm::QuarterVRef,
-            InvertingInput::HalfVref => vals::Inm::HalfVRef,
-            InvertingInput::ThreeQuarterVref => vals::Inm::ThreeQuarterVRef,
-            InvertingInput::Vref => vals::Inm::VRef,
-            #[cfg(any(comp_u5, comp_v1))]
-            InvertingInput::Dac1 => vals::Inm::Dac1,
-            #[cfg(any(comp_u5, comp_v1))]
-            InvertingInput::Dac2 => vals::Inm::Dac2,
-            #[cfg(comp_v2)]
-            InvertingInput::Dac1 => vals::Inm::Daca,
-            #[cfg(comp_v2)]
-            InvertingInput::Dac2 => vals::Inm::Dacb,
-
-            InvertingInput::InputPin => vals::Inm::Inm1,
-            #[cfg(any(comp_v1, comp_v2))]
-            InvertingInput::InputPin2 => vals::Inm::Inm2,
-            #[cfg(comp_v1)]
-            InvertingInput::InputPin3 => vals::Inm::Inm3,
-        };
-
-        Self::configure_raw(inp_channel, inmsel, config);
+        Self::configure_raw(inp_channel, config.inverting_input.to_vals(), config);
     }
 
     fn configure_with_input_minus_pin(inp_channel: u8, inm_channel: u8, config: Config) {
@@ -502,26 +538,62 @@ impl<'d, T: Instance> Comp<'d, T> {
 
     /// Set the blanking source.
     pub fn set_blanking_source(&mut self, source: BlankingSource) {
-        let blanksel = match source {
-            BlankingSource::None => vals::Blanking::NoBlanking,
-            BlankingSource::Blank1 => vals::Blanking::Blank1,
-            BlankingSource::Blank2 => vals::Blanking::Blank2,
-            BlankingSource::Blank3 => vals::Blanking::Blank3,
-            #[cfg(any(comp_v1, comp_v2))]
-            BlankingSource::Blank4 => vals::Blanking::Blank4,
-            #[cfg(any(comp_v1, comp_v2))]
-            BlankingSource::Blank5 => vals::Blanking::Blank5,
-            #[cfg(comp_v2)]
-            BlankingSource::Blank6 => vals::Blanking::Blank6,
+        T::regs().csr().modify(|w| {
+            w.set_blanksel(source.to_vals());
+        });
+    }
+
+    /// Set the hysteresis level.
+    pub fn set_hysteresis(&mut self, hysteresis: Hysteresis) {
+        T::regs().csr().modify(|w| {
+            w.set_hyst(hysteresis.to_vals());
+        });
+    }
+
+    /// Set the output polarity.
+    pub fn set_output_polarity(&mut self, polarity: OutputPolarity) {
+        T::regs().csr().modify(|w| {
+            w.set_polarity(polarity.to_vals());
+        });
+    }
+
+    /// Set the power mode.
+    #[cfg(any(comp_u5, comp_v1))]
+    pub fn set_power_mode(&mut self, power_mode: PowerMode) {
+        T::regs().csr().modify(|w| {
+            w.set_pwrmode(power_mode.to_vals());
+        });
+    }
+
+    /// Select an internal inverting input.
+    ///
+    /// Selecting one of the input pins only makes sense if the pin was set to analog mode, e.g.
+    /// with [`Self::new_with_input_minus_pin`].
+    pub fn set_inverting_input(&mut self, input: InvertingInput) {
+        let inmsel = input.to_vals();
+        T::regs().csr().modify(|w| {
+            w.set_inmsel(inmsel);
             #[cfg(comp_v2)]
-            BlankingSource::Blank7 => vals::Blanking::Blank7,
-        };
+            set_scaler(w, inmsel);
+        });
+    }
 
+    /// Lock the configuration of the comparator until the next reset.
+    ///
+    /// After this, the control register is read-only, including the enable bit, so the
+    /// comparator keeps running even when the driver is dropped. This is meant for safety
+    /// functions, e.g. a comparator driving a timer break input.
+    pub fn lock(&mut self) {
         T::regs().csr().modify(|w| {
-            w.set_blanksel(blanksel);
+            w.set_lock(true);
         });
     }
 
+    /// Check if the configuration of the comparator is locked.
+    pub fn is_locked(&self) -> bool {
+        T::regs().csr().read().lock()
+    }
+
     /// Wait for the comparator output to go high.
     ///
     /// This method enables the comparator if it's not already enabled,
@@ -556,54 +628,30 @@ impl<'d, T: Instance> Comp<'d, T> {
     ///
     /// This method waits asynchronously for the output to transition from low to high.
     pub async fn wait_for_rising_edge(&mut self) {
-        self.enable();
-
-        // Configure EXTI for rising edge
-        T::configure_exti(true, false);
-
-        poll_fn(|cx| {
-            T::state().waker.register(cx.waker());
-
-            // Check if interrupt already fired (IMR was cleared by handler)
-            if !T::is_exti_interrupt_enabled() {
-                return Poll::Ready(());
-            }
-
-            Poll::Pending
-        })
-        .await;
+        self.wait_for_edge(true, false).await
     }
 
     /// Wait for a falling edge on the comparator output.
     ///
     /// This method waits asynchronously for the output to transition from high to low.
     pub async fn wait_for_falling_edge(&mut self) {
-        self.enable();
-
-        // Configure EXTI for falling edge
-        T::configure_exti(false, true);
-
-        poll_fn(|cx| {
-            T::state().waker.register(cx.waker());
-
-            // Check if interrupt already fired (IMR was cleared by handler)
-            if !T::is_exti_interrupt_enabled() {
-                return Poll::Ready(());
-            }
-
-            Poll::Pending
-        })
-        .await;
+        self.wait_for_edge(false, true).await
     }
 
     /// Wait for any edge (rising or falling) on the comparator output.
     ///
     /// This method waits asynchronously for any output transition.
     pub async fn wait_for_any_edge(&mut self) {
+        self.wait_for_edge(true, true).await
+    }
+
+    async fn wait_for_edge(&mut self, rising: bool, falling: bool) {
         self.enable();
 
-        // Configure EXTI for both edges
-        T::configure_exti(true, true);
+        T::configure_exti(rising, falling);
+
+        // Don't leave the interrupt enabled if the future is dropped before the edge.
+        let on_drop = OnDrop::new(|| T::disable_exti_interrupt());
 
         poll_fn(|cx| {
             T::state().waker.register(cx.waker());
@@ -616,6 +664,8 @@ impl<'d, T: Instance> Comp<'d, T> {
             Poll::Pending
         })
         .await;
+
+        on_drop.defuse();
     }
 }
 
@@ -629,6 +679,121 @@ impl<'d, T: Instance> Drop for Comp<'d, T> {
     }
 }
 
+/// Position of a signal relative to a [`Window`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WindowState {
+    /// Below the lower threshold.
+    Below,
+    /// Between the two thresholds.
+    Inside,
+    /// Above the upper threshold.
+    Above,
+}
+
+/// Two comparators watching the same signal against a lower and an upper threshold.
+///
+/// Both comparators must use non-inverted output polarity, and compare the signal on their
+/// non-inverting input to their threshold on the inverting input. On chips with hardware window
+/// mode, the signal can be connected to one comparator only, with [`WindowMode::Enabled`] in the
+/// configuration of the other one.
+///
+/// Useful for supply voltage supervision, or for detecting a signal leaving a band around zero.
+pub struct Window<'d, U: Instance, L: Instance> {
+    upper: Comp<'d, U>,
+    lower: Comp<'d, L>,
+}
+
+impl<'d, U: Instance, L: Instance> Window<'d, U, L> {
+    /// Create a window from the comparators of the `upper` and `lower` thresholds, and enable them.
+    pub fn new(mut upper: Comp<'d, U>, mut lower: Comp<'d, L>) -> Self {
+        upper.enable();
+        lower.enable();
+        Self { upper, lower }
+    }
+
+    /// Get the current position of the signal.
+    pub fn state(&self) -> WindowState {
+        window_state::<U, L>()
+    }
+
+    /// Check if the signal is between the two thresholds.
+    pub fn is_inside(&self) -> bool {
+        self.state() == WindowState::Inside
+    }
+
+    /// Wait until the signal is in `state`.
+    ///
+    /// This returns immediately if the signal already is in `state`.
+    pub async fn wait_for_state(&mut self, state: WindowState) {
+        let mut current = self.state();
+        while current != state {
+            current = self.wait_for_change_from(current).await;
+        }
+    }
+
+    /// Wait until the signal is between the two thresholds.
+    ///
+    /// This returns immediately if the signal already is inside.
+    pub async fn wait_for_inside(&mut self) {
+        self.wait_for_state(WindowState::Inside).await
+    }
+
+    /// Wait until the signal leaves the band between the two thresholds, returning on which side.
+    ///
+    /// This returns immediately if the signal already is outside.
+    pub async fn wait_for_outside(&mut self) -> WindowState {
+        let mut current = self.state();
+        while current == WindowState::Inside {
+            current = self.wait_for_change_from(current).await;
+        }
+        current
+    }
+
+    /// Wait until the position of the signal changes, returning the new position.
+    pub async fn wait_for_change(&mut self) -> WindowState {
+        let initial = self.state();
+        self.wait_for_change_from(initial).await
+    }
+
+    async fn wait_for_change_from(&mut self, initial: WindowState) -> WindowState {
+        loop {
+            let mut edge = pin!(select(self.upper.wait_for_any_edge(), self.lower.wait_for_any_edge()));
+
+            // Poll once to arm both EXTI lines before checking the outputs again, so that an
+            // edge in between is not missed.
+            let fired = poll_fn(|cx| Poll::Ready(edge.as_mut().poll(cx).is_ready())).await;
+            let state = window_state::<U, L>();
+            if state != initial {
+                return state;
+            }
+            if !fired {
+                edge.await;
+            }
+
+            let state = window_state::<U, L>();
+            if state != initial {
+                return state;
+            }
+        }
+    }
+
+    /// Consume the window, returning the `upper` and `lower` comparators.
+    pub fn split(self) -> (Comp<'d, U>, Comp<'d, L>) {
+        (self.upper, self.lower)
+    }
+}
+
+fn window_state<U: Instance, L: Instance>() -> WindowState {
+    if U::regs().csr().read().value() {
+        WindowState::Above
+    } else if L::regs().csr().read().value() {
+        WindowState::Inside
+    } else {
+        WindowState::Below
+    }
+}
+
 pub(crate) struct Info {
     rcc: RccInfo,
 }
@@ -654,6 +819,11 @@ pub trait Instance: SealedInstance + PeripheralType + 'static {
 
 pin_trait!(OutputPin, Instance);
 
+/// Split an EXTI line number into register bank and bit, some comparators use lines above 31.
+fn exti_bank_line(line: u8) -> (usize, usize) {
+    (line as usize / 32, line as usize % 32)
+}
+
 macro_rules! impl_comp {
     ($inst:ident, $exti_line:expr) => {
         impl SealedInstance for crate::peripherals::$inst {
@@ -681,12 +851,12 @@ macro_rules! impl_comp {
             fn configure_exti(rising: bool, falling: bool) {
                 use crate::pac::EXTI;
 
-                let line = Self::exti_line() as usize;
+                let (bank, line) = exti_bank_line(Self::exti_line());
 
                 critical_section::with(|_| {
                     // Configure rising/falling edge triggers
-                    EXTI.rtsr(0).modify(|w| w.set_line(line, rising));
-                    EXTI.ftsr(0).modify(|w| w.set_line(line, falling));
+                    EXTI.rtsr(bank).modify(|w| w.set_line(line, rising));
+                    EXTI.ftsr(bank).modify(|w| w.set_line(line, falling));
 
                     // Clear any pending interrupt
                     Self::clear_exti_pending();
@@ -698,46 +868,37 @@ macro_rules! impl_comp {
 
             fn enable_exti_interrupt() {
                 use crate::pac::EXTI;
-                let line = Self::exti_line() as usize;
-
-                #[cfg(any(
-                    exti_c0, exti_g0, exti_u0, exti_l5, exti_u5, exti_u3, exti_h5, exti_h50, exti_n6
-                ))]
-                EXTI.imr(0).modify(|w| w.set_line(line, true));
-
-                #[cfg(not(any(
-                    exti_c0, exti_g0, exti_u0, exti_l5, exti_u5, exti_u3, exti_h5, exti_h50, exti_n6
-                )))]
-                EXTI.imr(0).modify(|w| w.set_line(line, true));
+                let (bank, line) = exti_bank_line(Self::exti_line());
+                EXTI.imr(bank).modify(|w| w.set_line(line, true));
             }
 
             fn disable_exti_interrupt() {
                 use crate::pac::EXTI;
-                let line = Self::exti_line() as usize;
-                EXTI.imr(0).modify(|w| w.set_line(line, false));
+                let (bank, line) = exti_bank_line(Self::exti_line());
+                EXTI.imr(bank).modify(|w| w.set_line(line, false));
             }
 
             fn is_exti_interrupt_enabled() -> bool {
                 use crate::pac::EXTI;
-                let line = Self::exti_line() as usize;
-                EXTI.imr(0).read().line(line)
+                let (bank, line) = exti_bank_line(Self::exti_line());
+                EXTI.imr(bank).read().line(line)
             }
 
             fn clear_exti_pending() {
                 use crate::pac::EXTI;
-                let line = Self::exti_line() as usize;
+                let (bank, line) = exti_bank_line(Self::exti_line());
 
                 #[cfg(not(any(
                     exti_c0, exti_g0, exti_u0, exti_l5, exti_u5, exti_u3, exti_h5, exti_h50, exti_n6
                 )))]
-                EXTI.pr(0).write(|w| w.set_line(line, true));
+                EXTI.pr(bank).write(|w| w.set_line(line, true));
 
                 #[cfg(any(
                     exti_c0, exti_g0, exti_u0, exti_l5, exti_u5, exti_u3, exti_h5, exti_h50, exti_n6
                 ))]
                 {
-                    EXTI.rpr(0).write(|w| w.set_line(line, true));
-                    EXTI.fpr(0).write(|w| w.set_line(line, true));
+                    EXTI.rpr(bank).write(|w| w.set_line(line, true));
+                    EXTI.fpr(bank).write(|w| w.set_line(line, true));
                 }
             }
         }
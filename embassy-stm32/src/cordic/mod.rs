@@ -1,6 +1,9 @@
 //! Coordinate Rotation Digital Computer (CORDIC)
 
 use core::convert::Infallible;
+use core::future::poll_fn;
+use core::marker::PhantomData;
+use core::task::Poll;
 use core::{mem, slice};
 
 use aligned::{A4, Aligned};
@@ -8,9 +11,11 @@ use dsp_fixedpoint::{Q16, Q32};
 use embassy_embedded_hal::SetConfig;
 use embassy_hal_internal::drop::OnDrop;
 use embassy_hal_internal::{Peri, PeripheralType};
+use embassy_sync::waitqueue::AtomicWaker;
 
+use crate::interrupt::typelevel::{Binding, Interrupt};
 use crate::pac::cordic::vals;
-use crate::{dma, peripherals, rcc};
+use crate::{dma, interrupt, peripherals, rcc};
 
 mod enums;
 pub use enums::*;
@@ -18,6 +23,27 @@ pub use enums::*;
 mod errors;
 pub use errors::*;
 
+mod utils;
+pub use utils::*;
+
+static CORDIC_WAKER: AtomicWaker = AtomicWaker::new();
+
+/// 0.559 in q1.31, the largest argument of the hyperbolic functions at scale 1.
+const HYPERBOLIC_ARG_MAX: i32 = 1_200_443_359;
+
+/// CORDIC interrupt handler, for [`Cordic32::irq_calc`].
+pub struct InterruptHandler<T: Instance> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandler<T> {
+    unsafe fn on_interrupt() {
+        // RRDY is cleared by reading the results, which the waiting task does.
+        T::regs().csr().modify(|v| v.set_ien(false));
+        CORDIC_WAKER.wake();
+    }
+}
+
 /// CORDIC driver
 pub struct Cordic<'d, T: Instance> {
     peri: Peri<'d, T>,
@@ -56,7 +82,10 @@ trait SealedInstance {
 
 /// CORDIC instance trait
 #[allow(private_bounds)]
-pub trait Instance: SealedInstance + PeripheralType + crate::rcc::RccPeripheral {}
+pub trait Instance: SealedInstance + PeripheralType + crate::rcc::RccPeripheral {
+    /// Interrupt for this CORDIC instance.
+    type Interrupt: interrupt::typelevel::Interrupt;
+}
 
 /// CORDIC configuration
 #[derive(Clone, Debug)]
@@ -219,6 +248,112 @@ impl<'d, T: Instance> Cordic<'d, T> {
         Cordic16 { inner: self }
     }
 
+    /// Sine and cosine of `angle`, in units of π (`-1..=1` maps to `-π..=π`).
+    ///
+    /// Like the other single-calculation helpers, this runs with the precision of the current
+    /// [`Config`] and restores the configuration afterwards. ARG2 is left at +1.
+    pub fn sin_cos(&mut self, angle: Q32<31>) -> (Q32<31>, Q32<31>) {
+        let mut res = [0; 2];
+        self.calc_once(
+            Function::Sin,
+            Scale::Arg1Res1,
+            &[angle.inner as u32, 0x7FFF_FFFF],
+            &mut res,
+        );
+        (Q32::new(res[0] as i32), Q32::new(res[1] as i32))
+    }
+
+    /// Angle of the vector `(x, y)`, in units of π: `atan2(y, x) / π`.
+    ///
+    /// ARG2 is left at `y`.
+    pub fn atan2(&mut self, y: Q32<31>, x: Q32<31>) -> Q32<31> {
+        let mut res = [0; 1];
+        self.calc_once(
+            Function::Phase,
+            Scale::Arg1Res1,
+            &[x.inner as u32, y.inner as u32],
+            &mut res,
+        );
+        Q32::new(res[0] as i32)
+    }
+
+    /// Square root of `x`, for `0 <= x < 1`.
+    ///
+    /// The argument is scaled into the range supported by the hardware, so the whole range
+    /// is accepted.
+    pub fn sqrt(&mut self, x: Q32<31>) -> Result<Q32<31>, NumberOutOfRange> {
+        if x.inner < 0 {
+            return Err(NumberOutOfRange::BelowLowerBound);
+        }
+        if x.inner == 0 {
+            return Ok(x);
+        }
+
+        // Below 0.027 the hardware is not accurate: use sqrt(x) = sqrt(x * 4^k) / 2^k.
+        let mut arg = x.inner as u32;
+        let mut k = 0;
+        while arg < 0x0400_0000 {
+            arg <<= 2;
+            k += 1;
+        }
+
+        // From 0.75, the argument is divided by 2 (SCALE = 1), and the result multiplied by 2.
+        let (scale, arg, shift) = if arg >= 0x6000_0000 {
+            (Scale::Arg1o2Res2, arg >> 1, 1)
+        } else {
+            (Scale::Arg1Res1, arg, 0)
+        };
+
+        let mut res = [0; 1];
+        self.calc_once(Function::Sqrt, scale, &[arg], &mut res);
+        Ok(Q32::new(((res[0] << shift) >> k) as i32))
+    }
+
+    /// Hyperbolic sine and cosine of `x`, for `|x| <= 1.118`.
+    ///
+    /// Arguments and results are in q2.30, as the hardware divides both by 2.
+    pub fn sinh_cosh(&mut self, x: Q32<30>) -> Result<(Q32<30>, Q32<30>), NumberOutOfRange> {
+        // x/2 in q1.31 has the same bits as x in q2.30.
+        if x.inner < -HYPERBOLIC_ARG_MAX {
+            return Err(NumberOutOfRange::BelowLowerBound);
+        }
+        if x.inner > HYPERBOLIC_ARG_MAX {
+            return Err(NumberOutOfRange::AboveUpperBound);
+        }
+
+        let mut res = [0; 2];
+        self.calc_once(Function::Sinh, Scale::Arg1o2Res2, &[x.inner as u32], &mut res);
+        Ok((Q32::new(res[0] as i32), Q32::new(res[1] as i32)))
+    }
+
+    /// Run one blocking calculation in q1.31 format with `function`, and restore the configuration.
+    fn calc_once(&mut self, function: Function, scale: Scale, args: &[u32], res: &mut [u32]) {
+        let num = |n: usize| if n == 2 { vals::Num::Num2 } else { vals::Num::Num1 };
+
+        let csr = T::regs().csr().read();
+        T::regs().csr().modify(|v| {
+            v.set_ien(false);
+            v.set_dmaren(false);
+            v.set_dmawen(false);
+            v.set_func(vals::Func::from_bits(function as u8));
+            v.set_scale(vals::Scale::from_bits(scale as u8));
+            v.set_nargs(num(args.len()));
+            v.set_nres(num(res.len()));
+            v.set_argsize(vals::Size::Bits32);
+            v.set_ressize(vals::Size::Bits32);
+        });
+
+        for &arg in args {
+            self.peri.write_argument(arg);
+        }
+        // Reading RDATA stalls the bus until the result is ready.
+        for r in res {
+            *r = self.peri.read_result();
+        }
+
+        T::regs().csr().write_value(csr);
+    }
+
     fn clean_rrdy_flag(&mut self) {
         while self.peri.ready_to_read() {
             self.peri.read_result();
@@ -423,6 +558,53 @@ impl<'d, 'a, T: Instance> Cordic32<'d, 'a, T> {
         Ok(res_cnt)
     }
 
+    /// Run a CORDIC calculation in q1.31 format, waiting for each result with the interrupt.
+    ///
+    /// Unlike [`Self::async_calc`] this needs no DMA channels, but the CPU moves every argument and
+    /// result. Uses `arg_count` and `res_count` like [`Self::blocking_calc`].
+    pub async fn irq_calc(
+        &mut self,
+        _irq: impl Binding<T::Interrupt, InterruptHandler<T>>,
+        arg: &[Q32<31>],
+        res: &mut [Q32<31>],
+    ) -> Result<usize, CordicError> {
+        if arg.is_empty() {
+            return Ok(0);
+        }
+
+        let arg1_only = matches!(self.arg_count, AccessCount::One);
+        let res1_only = matches!(self.res_count, AccessCount::One);
+        let res_cnt = Self::check_arg_res_length(arg.len(), res.len(), arg1_only, res1_only)?;
+
+        T::Interrupt::unpend();
+        unsafe { T::Interrupt::enable() };
+
+        let args_per_calc = if arg1_only { 1 } else { 2 };
+        let res_per_calc = if res1_only { 1 } else { 2 };
+        for (args, results) in arg.chunks(args_per_calc).zip(res[..res_cnt].chunks_mut(res_per_calc)) {
+            for &a in args {
+                self.inner.peri.write_q32(a);
+            }
+
+            poll_fn(|cx| {
+                CORDIC_WAKER.register(cx.waker());
+                if self.inner.peri.ready_to_read() {
+                    Poll::Ready(())
+                } else {
+                    T::regs().csr().modify(|v| v.set_ien(true));
+                    Poll::Pending
+                }
+            })
+            .await;
+
+            for r in results {
+                *r = self.inner.peri.read_q32();
+            }
+        }
+
+        Ok(res_cnt)
+    }
+
     fn check_arg_res_length(
         arg_len: usize,
         res_len: usize,
@@ -720,6 +902,7 @@ check_arg_value!(check_f32_arg1, check_f32_arg2, &f32);
 foreach_interrupt!(
     ($inst:ident, cordic, $block:ident, GLOBAL, $irq:ident) => {
         impl Instance for peripherals::$inst {
+            type Interrupt = crate::interrupt::typelevel::$irq;
         }
 
         impl SealedInstance for peripherals::$inst {
@@ -0,0 +1,33 @@
+//! Checked conversions between floating point and the fixed point formats of the CORDIC.
+
+use dsp_fixedpoint::{Q16, Q32};
+
+use super::NumberOutOfRange;
+
+macro_rules! floating_fixed_convert {
+    ($f_to_q:ident, $float_ty:ty, $q_ty:ty, $inner_ty:ty, $offset:literal) => {
+        /// Convert a floating point number in `[-1, 1]` to fixed point, rounding to nearest.
+        ///
+        /// `1.0` can't be represented, it is saturated to the largest positive value.
+        pub fn $f_to_q(value: $float_ty) -> Result<$q_ty, NumberOutOfRange> {
+            if value < -1.0 {
+                Err(NumberOutOfRange::BelowLowerBound)
+            } else if value > 1.0 {
+                Err(NumberOutOfRange::AboveUpperBound)
+            } else {
+                let scaled = value as f64 * (1u64 << $offset) as f64;
+                // `as` truncates, and saturates at `MAX` for `1.0`.
+                Ok(<$q_ty>::new(add_half(scaled) as $inner_ty))
+            }
+        }
+    };
+}
+
+floating_fixed_convert!(f64_to_q1_31, f64, Q32<31>, i32, 31);
+floating_fixed_convert!(f32_to_q1_31, f32, Q32<31>, i32, 31);
+floating_fixed_convert!(f32_to_q1_15, f32, Q16<15>, i16, 15);
+
+/// Add half away from zero, so truncating rounds to nearest (`f64::round` is not in `core`).
+fn add_half(x: f64) -> f64 {
+    if x < 0.0 { x - 0.5 } else { x + 0.5 }
+}
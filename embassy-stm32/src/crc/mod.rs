@@ -5,3 +5,14 @@
 mod _version;
 
 pub use _version::*;
+
+use crate::dma;
+use crate::dma::word::Word;
+
+/// Stream `buf` into the data register `dr` with memory-to-memory DMA transfers.
+async fn feed_dma<W: Word>(dma: &mut dma::Channel<'_>, buf: &[W], dr: *mut W) {
+    // Transfers are limited to 65535 bytes on GPDMA, and to 65535 items on the other controllers.
+    for chunk in buf.chunks(0xFFFF / core::mem::size_of::<W>()) {
+        unsafe { dma.write_to_fixed(chunk, dr, Default::default()) }.await;
+    }
+}
@@ -1,23 +1,81 @@
+use embassy_futures::join::join;
+
 use crate::pac::CRC as PAC_CRC;
 use crate::peripherals::CRC;
-use crate::{Peri, rcc};
+use crate::{Peri, dma, interrupt, rcc};
 
 /// CRC driver.
 pub struct Crc<'d> {
     _peri: Peri<'d, CRC>,
+    config: Config,
+}
+
+/// CRC configuration.
+///
+/// The hardware computes CRC-32/MPEG-2 (polynomial `0x04C11DB7`, initial value `0xFFFF_FFFF`) on
+/// 32-bit words. Input and output reversal and the final XOR are done in software, which allows
+/// computing the CRC-32 used by Ethernet and zlib.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Reverse the bits of each input word.
+    pub reverse_in: bool,
+    /// Reverse the bits of the result.
+    pub reverse_out: bool,
+    /// Value XORed with the result.
+    pub xor_out: u32,
+}
+
+impl Config {
+    /// CRC-32/MPEG-2, computed by the hardware as is.
+    pub const fn mpeg2() -> Self {
+        Self {
+            reverse_in: false,
+            reverse_out: false,
+            xor_out: 0,
+        }
+    }
+
+    /// CRC-32 (ISO-HDLC), as used by Ethernet and zlib, for data fed as little-endian words.
+    pub const fn crc32() -> Self {
+        Self {
+            reverse_in: true,
+            reverse_out: true,
+            xor_out: 0xFFFF_FFFF,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::mpeg2()
+    }
 }
 
 impl<'d> Crc<'d> {
     /// Instantiates the CRC32 peripheral and initializes it to default values.
     pub fn new(peripheral: Peri<'d, CRC>) -> Self {
+        Self::new_with_config(peripheral, Config::mpeg2())
+    }
+
+    /// Instantiates the CRC32 peripheral with the given software processing.
+    pub fn new_with_config(peripheral: Peri<'d, CRC>, config: Config) -> Self {
         // Note: enable and reset come from RccPeripheral.
         // enable CRC clock in RCC.
         rcc::enable_and_reset::<CRC>();
-        let mut instance = Self { _peri: peripheral };
+        let mut instance = Self {
+            _peri: peripheral,
+            config,
+        };
         instance.reset();
         instance
     }
 
+    /// Changes the software processing. Doesn't reset.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
     /// Resets the CRC unit to default value (0xFFFF_FFFF)
     pub fn reset(&mut self) {
         PAC_CRC.cr().write(|w| w.set_reset(true));
@@ -26,21 +84,85 @@ impl<'d> Crc<'d> {
     /// Feeds a word into the CRC peripheral. Returns the computed CRC.
     pub fn feed_word(&mut self, word: u32) -> u32 {
         // write a single byte to the device, and return the result
-        PAC_CRC.dr().write_value(word);
+        PAC_CRC.dr().write_value(self.input(word));
         self.read()
     }
 
     /// Feeds a slice of words into the CRC peripheral. Returns the computed CRC.
     pub fn feed_words(&mut self, words: &[u32]) -> u32 {
         for word in words {
-            PAC_CRC.dr().write_value(*word);
+            PAC_CRC.dr().write_value(self.input(*word));
+        }
+
+        self.read()
+    }
+
+    /// Feeds a slice of words into the CRC peripheral using DMA. Returns the computed CRC.
+    ///
+    /// The words are moved by memory-to-memory transfers, which leaves the CPU free while large
+    /// buffers, e.g. firmware images, are checked. `words` must be in memory the DMA controller
+    /// can access.
+    ///
+    /// With `reverse_in`, the words are bit-reversed in chunks into a buffer on the stack, each
+    /// chunk while the previous one is transferred.
+    pub async fn feed_words_dma<D: dma::ChannelInstance>(
+        &mut self,
+        dma: Peri<'_, D>,
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>>,
+        words: &[u32],
+    ) -> u32 {
+        let mut dma = dma::Channel::new(dma, irq);
+        let dr = PAC_CRC.dr().as_ptr();
+
+        if !self.config.reverse_in {
+            super::feed_dma(&mut dma, words, dr).await;
+            return self.read();
+        }
+
+        const CHUNK: usize = 64;
+        let (mut a, mut b) = ([0u32; CHUNK], [0u32; CHUNK]);
+        let (mut current, mut next) = (&mut a, &mut b);
+        let reverse = |buf: &mut [u32; CHUNK], chunk: &[u32]| {
+            for (r, w) in buf.iter_mut().zip(chunk) {
+                *r = w.reverse_bits();
+            }
+            chunk.len()
+        };
+
+        let mut chunks = words.chunks(CHUNK);
+        let Some(first) = chunks.next() else {
+            return self.read();
+        };
+        let mut len = reverse(current, first);
+        for chunk in chunks {
+            let (_, next_len) = join(super::feed_dma(&mut dma, &current[..len], dr), async {
+                reverse(next, chunk)
+            })
+            .await;
+            core::mem::swap(&mut current, &mut next);
+            len = next_len;
         }
+        super::feed_dma(&mut dma, &current[..len], dr).await;
 
         self.read()
     }
 
     /// Read the CRC result value.
     pub fn read(&self) -> u32 {
-        PAC_CRC.dr().read()
+        let crc = PAC_CRC.dr().read();
+        let crc = if self.config.reverse_out {
+            crc.reverse_bits()
+        } else {
+            crc
+        };
+        crc ^ self.config.xor_out
+    }
+
+    fn input(&self, word: u32) -> u32 {
+        if self.config.reverse_in {
+            word.reverse_bits()
+        } else {
+            word
+        }
     }
 }
@@ -1,12 +1,12 @@
 use crate::pac::CRC as PAC_CRC;
 use crate::pac::crc::vals;
 use crate::peripherals::CRC;
-use crate::{Peri, rcc};
+use crate::{Peri, dma, interrupt, rcc};
 
 /// CRC driver.
 pub struct Crc<'d> {
     _peripheral: Peri<'d, CRC>,
-    _config: Config,
+    config: Config,
 }
 
 /// CRC configuration error
@@ -18,6 +18,7 @@ pub enum ConfigError {
 }
 
 /// CRC configuration
+#[derive(Debug, Clone, Copy)]
 pub struct Config {
     reverse_in: InputReverseConfig,
     reverse_out: bool,
@@ -26,9 +27,11 @@ pub struct Config {
     crc_init_value: u32,
     #[cfg(crc_v3)]
     crc_poly: u32,
+    xor_out: u32,
 }
 
 /// Input reverse configuration.
+#[derive(Debug, Clone, Copy)]
 pub enum InputReverseConfig {
     /// Don't reverse anything
     None,
@@ -54,6 +57,10 @@ impl Config {
         if crc_poly % 2 == 0 {
             return Err(ConfigError::InvalidPolynomial);
         }
+        #[cfg(crc_v3)]
+        if crc_poly & !poly_size.mask() != 0 {
+            return Err(ConfigError::InvalidPolynomial);
+        }
         Ok(Config {
             reverse_in,
             reverse_out,
@@ -62,13 +69,121 @@ impl Config {
             crc_init_value,
             #[cfg(crc_v3)]
             crc_poly,
+            xor_out: 0,
         })
     }
+
+    /// Set the value XORed with the result, in software.
+    pub const fn with_xor_out(mut self, xor_out: u32) -> Self {
+        self.xor_out = xor_out;
+        self
+    }
+
+    /// CRC-32 (ISO-HDLC), as used by Ethernet and zlib.
+    pub const fn crc32() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::Byte,
+            reverse_out: true,
+            #[cfg(crc_v3)]
+            poly_size: PolySize::Width32,
+            crc_init_value: 0xFFFF_FFFF,
+            #[cfg(crc_v3)]
+            crc_poly: 0x04C1_1DB7,
+            xor_out: 0xFFFF_FFFF,
+        }
+    }
+
+    /// CRC-32/MPEG-2, the reset configuration of the unit.
+    pub const fn crc32_mpeg2() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::None,
+            reverse_out: false,
+            #[cfg(crc_v3)]
+            poly_size: PolySize::Width32,
+            crc_init_value: 0xFFFF_FFFF,
+            #[cfg(crc_v3)]
+            crc_poly: 0x04C1_1DB7,
+            xor_out: 0,
+        }
+    }
+
+    /// CRC-32C (Castagnoli), as used by iSCSI and ext4.
+    #[cfg(crc_v3)]
+    pub const fn crc32c() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::Byte,
+            reverse_out: true,
+            poly_size: PolySize::Width32,
+            crc_init_value: 0xFFFF_FFFF,
+            crc_poly: 0x1EDC_6F41,
+            xor_out: 0xFFFF_FFFF,
+        }
+    }
+
+    /// CRC-16/MODBUS.
+    #[cfg(crc_v3)]
+    pub const fn crc16_modbus() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::Byte,
+            reverse_out: true,
+            poly_size: PolySize::Width16,
+            crc_init_value: 0xFFFF,
+            crc_poly: 0x8005,
+            xor_out: 0,
+        }
+    }
+
+    /// CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE.
+    #[cfg(crc_v3)]
+    pub const fn crc16_ibm_3740() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::None,
+            reverse_out: false,
+            poly_size: PolySize::Width16,
+            crc_init_value: 0xFFFF,
+            crc_poly: 0x1021,
+            xor_out: 0,
+        }
+    }
+
+    /// CRC-8/SMBUS.
+    #[cfg(crc_v3)]
+    pub const fn crc8_smbus() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::None,
+            reverse_out: false,
+            poly_size: PolySize::Width8,
+            crc_init_value: 0,
+            crc_poly: 0x07,
+            xor_out: 0,
+        }
+    }
+
+    /// CRC-7/MMC, as used by SD cards.
+    #[cfg(crc_v3)]
+    pub const fn crc7_mmc() -> Self {
+        Self {
+            reverse_in: InputReverseConfig::None,
+            reverse_out: false,
+            poly_size: PolySize::Width7,
+            crc_init_value: 0,
+            crc_poly: 0x09,
+            xor_out: 0,
+        }
+    }
+
+    fn mask(&self) -> u32 {
+        #[cfg(crc_v3)]
+        return self.poly_size.mask();
+        #[cfg(not(crc_v3))]
+        return u32::MAX;
+    }
 }
 
 /// Polynomial size
 #[cfg(crc_v3)]
 #[allow(missing_docs)]
+#[derive(Debug, Clone, Copy)]
 pub enum PolySize {
     Width7,
     Width8,
@@ -76,6 +191,18 @@ pub enum PolySize {
     Width32,
 }
 
+#[cfg(crc_v3)]
+impl PolySize {
+    const fn mask(self) -> u32 {
+        match self {
+            PolySize::Width7 => 0x7F,
+            PolySize::Width8 => 0xFF,
+            PolySize::Width16 => 0xFFFF,
+            PolySize::Width32 => 0xFFFF_FFFF,
+        }
+    }
+}
+
 impl<'d> Crc<'d> {
     /// Instantiates the CRC32 peripheral and initializes it to default values.
     pub fn new(peripheral: Peri<'d, CRC>, config: Config) -> Self {
@@ -84,13 +211,20 @@ impl<'d> Crc<'d> {
         rcc::enable_and_reset::<CRC>();
         let mut instance = Self {
             _peripheral: peripheral,
-            _config: config,
+            config,
         };
         instance.reconfigure();
         instance.reset();
         instance
     }
 
+    /// Change the configuration and reset the CRC engine.
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+        self.reconfigure();
+        self.reset();
+    }
+
     /// Reset the CRC engine.
     pub fn reset(&mut self) {
         PAC_CRC.cr().modify(|w| w.set_reset(true));
@@ -99,20 +233,20 @@ impl<'d> Crc<'d> {
     /// Reconfigures the CRC peripheral. Doesn't reset.
     fn reconfigure(&mut self) {
         // Init CRC value
-        PAC_CRC.init().write_value(self._config.crc_init_value);
+        PAC_CRC.init().write_value(self.config.crc_init_value);
         #[cfg(crc_v3)]
-        PAC_CRC.pol().write_value(self._config.crc_poly);
+        PAC_CRC.pol().write_value(self.config.crc_poly);
 
         // configure CR components
         // (reverse I/O, polysize, poly)
         PAC_CRC.cr().write(|w| {
             // configure reverse output
-            w.set_rev_out(match self._config.reverse_out {
+            w.set_rev_out(match self.config.reverse_out {
                 true => vals::RevOut::Reversed,
                 false => vals::RevOut::Normal,
             });
             // configure reverse input
-            w.set_rev_in(match self._config.reverse_in {
+            w.set_rev_in(match self.config.reverse_in {
                 InputReverseConfig::None => vals::RevIn::Normal,
                 InputReverseConfig::Byte => vals::RevIn::Byte,
                 InputReverseConfig::Halfword => vals::RevIn::HalfWord,
@@ -120,7 +254,7 @@ impl<'d> Crc<'d> {
             });
             // configure the polynomial.
             #[cfg(crc_v3)]
-            w.set_polysize(match self._config.poly_size {
+            w.set_polysize(match self.config.poly_size {
                 PolySize::Width7 => vals::Polysize::Polysize7,
                 PolySize::Width8 => vals::Polysize::Polysize8,
                 PolySize::Width16 => vals::Polysize::Polysize16,
@@ -130,8 +264,11 @@ impl<'d> Crc<'d> {
     }
 
     /// Read the CRC result value.
+    ///
+    /// The final XOR of the configuration is applied, and the value is truncated to the
+    /// polynomial size.
     pub fn read(&self) -> u32 {
-        PAC_CRC.dr32().read()
+        (PAC_CRC.dr32().read() ^ self.config.xor_out) & self.config.mask()
     }
 
     /// Feeds a byte into the CRC peripheral.
@@ -158,6 +295,22 @@ impl<'d> Crc<'d> {
         }
     }
 
+    /// Feeds a slice of bytes into the CRC peripheral using DMA. Returns the computed CRC.
+    ///
+    /// The bytes are moved by memory-to-memory transfers, which leaves the CPU free while large
+    /// buffers, e.g. firmware images, are checked. `bytes` must be in memory the DMA controller
+    /// can access.
+    pub async fn feed_bytes_dma<D: dma::ChannelInstance>(
+        &mut self,
+        dma: Peri<'_, D>,
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>>,
+        bytes: &[u8],
+    ) -> u32 {
+        let mut dma = dma::Channel::new(dma, irq);
+        super::feed_dma(&mut dma, bytes, PAC_CRC.dr8().as_ptr()).await;
+        self.read()
+    }
+
     /// Feeds a word into the CRC peripheral.
     pub fn feed_word(&mut self, word: u32) {
         PAC_CRC.dr32().write_value(word as u32);
@@ -169,4 +322,18 @@ impl<'d> Crc<'d> {
             PAC_CRC.dr32().write_value(*word as u32);
         }
     }
+
+    /// Feeds a slice of words into the CRC peripheral using DMA. Returns the computed CRC.
+    ///
+    /// See [`Self::feed_bytes_dma`].
+    pub async fn feed_words_dma<D: dma::ChannelInstance>(
+        &mut self,
+        dma: Peri<'_, D>,
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>>,
+        words: &[u32],
+    ) -> u32 {
+        let mut dma = dma::Channel::new(dma, irq);
+        super::feed_dma(&mut dma, words, PAC_CRC.dr32().as_ptr()).await;
+        self.read()
+    }
 }
@@ -3,16 +3,28 @@
 
 pub mod ringbuffered;
 
+#[cfg(not(dac_v1))]
+use core::future::poll_fn;
 use core::marker::PhantomData;
 use core::slice;
+#[cfg(not(dac_v1))]
+use core::task::Poll;
 
 #[cfg(stm32g4)]
 use dac::vals;
 use embassy_hal_internal::PeripheralType;
+#[cfg(not(dac_v1))]
+use embassy_hal_internal::drop::OnDrop;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+#[cfg(not(dac_v1))]
+use embassy_sync::waitqueue::AtomicWaker;
 pub use ringbuffered::RingBufferedDacChannel;
 
 use crate::dma::{ChannelAndRequest, word as dma};
+#[cfg(not(dac_v1))]
+use crate::interrupt;
+#[cfg(not(dac_v1))]
+use crate::interrupt::typelevel::Interrupt;
 use crate::mode::{Async, Blocking, Mode as PeriMode};
 #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
 use crate::pac::dac;
@@ -78,6 +90,10 @@ impl<T: Instance> ChannelTrigger<T> for SOFTWARE {
 }
 
 /// Operating mode for DAC channel
+///
+/// Besides normal and sample-and-hold operation, the mode selects where the output is routed:
+/// to the external pin, to on-chip peripherals such as comparators and OPAMPs, or both. An
+/// internal-only mode leaves the pin free, e.g. when the DAC only generates a comparator threshold.
 #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -103,6 +119,21 @@ pub enum Mode {
     SampleHoldInternalUnbuffered,
 }
 
+/// Sample-and-hold timing of a DAC channel.
+///
+/// All values are in cycles of the low-speed sample-and-hold clock (LSI or LSE).
+#[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SampleHoldConfig {
+    /// Duration of the sampling phase, during which the output capacitor is charged (TSAMPLE, 10 bits).
+    pub sample_cycles: u16,
+    /// Duration of the hold phase, during which the channel is powered down (THOLD, 10 bits).
+    pub hold_cycles: u16,
+    /// Duration of the refresh phase, during which the capacitor is recharged (TREFRESH).
+    pub refresh_cycles: u8,
+}
+
 #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
 impl Mode {
     fn mode(&self) -> dac::vals::Mode {
@@ -117,6 +148,108 @@ impl Mode {
             Mode::SampleHoldInternalUnbuffered => dac::vals::Mode::SampholdIntBufdis,
         }
     }
+
+    fn from_mode(mode: dac::vals::Mode) -> Self {
+        match mode {
+            dac::vals::Mode::NormalExtBufen => Mode::NormalExternalBuffered,
+            dac::vals::Mode::NormalExtIntBufen => Mode::NormalBothBuffered,
+            dac::vals::Mode::NormalExtBufdis => Mode::NormalExternalUnbuffered,
+            dac::vals::Mode::NormalIntBufdis => Mode::NormalInternalUnbuffered,
+            dac::vals::Mode::SampholdExtBufen => Mode::SampleHoldExternalBuffered,
+            dac::vals::Mode::SampholdExtIntBufen => Mode::SampleHoldBothBuffered,
+            dac::vals::Mode::SampholdExtIntBufdis => Mode::SampleHoldBothUnbuffered,
+            dac::vals::Mode::SampholdIntBufdis => Mode::SampleHoldInternalUnbuffered,
+        }
+    }
+
+    /// Whether the channel drives its external pin in this mode.
+    pub fn is_external(&self) -> bool {
+        !matches!(
+            self,
+            Mode::NormalInternalUnbuffered | Mode::SampleHoldInternalUnbuffered
+        )
+    }
+
+    /// Whether the channel is connected to on-chip peripherals in this mode.
+    pub fn is_internal(&self) -> bool {
+        !matches!(
+            self,
+            Mode::NormalExternalBuffered | Mode::NormalExternalUnbuffered | Mode::SampleHoldExternalBuffered
+        )
+    }
+
+    /// Whether the output buffer is enabled in this mode.
+    pub fn is_buffered(&self) -> bool {
+        matches!(
+            self,
+            Mode::NormalExternalBuffered
+                | Mode::NormalBothBuffered
+                | Mode::SampleHoldExternalBuffered
+                | Mode::SampleHoldBothBuffered
+        )
+    }
+}
+
+/// Built-in waveform generator of a DAC channel.
+///
+/// The generated value is added to the data holding register, which therefore sets the offset
+/// of the waveform.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Waveform {
+    /// No waveform generation, the data holding register is output as-is.
+    Disabled,
+    /// Pseudo-noise from a 12-bit LFSR, e.g. for dithering.
+    ///
+    /// Only the `bits` least significant bits (1..=12) of the LFSR are used, the others are masked.
+    Noise {
+        /// Number of unmasked LFSR bits.
+        bits: u8,
+    },
+    /// Triangle counting up from 0 to `2^bits - 1` and back down (`bits` in 1..=12).
+    Triangle {
+        /// Amplitude of the triangle, as a number of bits.
+        bits: u8,
+    },
+}
+
+/// DMA underrun error.
+///
+/// A trigger arrived before the DMA delivered the next sample. The DAC stops issuing DMA
+/// requests once an underrun is detected.
+#[cfg(not(dac_v1))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Underrun;
+
+/// Interrupt handler for DMA underrun detection.
+#[cfg(not(dac_v1))]
+pub struct InterruptHandler<T: InterruptInstance> {
+    _phantom: PhantomData<T>,
+}
+
+#[cfg(not(dac_v1))]
+impl<T: InterruptInstance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandler<T> {
+    unsafe fn on_interrupt() {
+        let regs = T::info().regs;
+        let sr = regs.sr().read();
+        let cr = regs.cr().read();
+
+        if !(0..2).any(|idx| sr.dmaudr(idx) && cr.dmaudrie(idx)) {
+            return;
+        }
+
+        // The flag is cleared by the channel, only mask the interrupt here.
+        regs.cr().modify(|w| {
+            for idx in 0..2 {
+                if sr.dmaudr(idx) {
+                    w.set_dmaudrie(idx, false);
+                }
+            }
+        });
+
+        T::state().waker.wake();
+    }
 }
 
 #[derive(Debug)]
@@ -133,6 +266,8 @@ struct InnerState {
 type SharedState = embassy_sync::blocking_mutex::Mutex<CriticalSectionRawMutex, core::cell::RefCell<InnerState>>;
 struct State {
     state: SharedState,
+    #[cfg(not(dac_v1))]
+    waker: AtomicWaker,
 }
 
 impl State {
@@ -336,6 +471,77 @@ impl<'d> DacChannel<'d, Async> {
             w.set_dmaen(self.idx, false);
         });
     }
+
+    /// Continuously output the waveform in `data` via circular DMA until a DMA underrun occurs.
+    ///
+    /// The channel should have been created with a trigger (e.g. a timer TRGO, see
+    /// [`Self::new_triggered`]); triggering is enabled by this method and each trigger outputs
+    /// the next sample, so the timer sets the sample rate and no CPU work is needed per sample.
+    ///
+    /// The returned future only completes when the DAC reports a DMA underrun, i.e. a trigger
+    /// arrived before the DMA delivered the next sample. Drop the future to stop the playback.
+    ///
+    /// `T` must be the DAC instance this channel was created from.
+    #[cfg(all(not(gpdma), not(dac_v1)))]
+    pub async fn write_waveform<T: InterruptInstance, W: Word>(
+        &mut self,
+        data: &[W],
+        _irq: impl interrupt::typelevel::Binding<T::Interrupt, InterruptHandler<T>> + 'd,
+    ) -> Underrun {
+        assert!(
+            core::ptr::eq(self.info, T::info()),
+            "Interrupt binding does not belong to this DAC instance"
+        );
+
+        let regs = self.info.regs;
+        let state = self.state;
+        let idx = self.idx;
+
+        regs.sr().write(|w| w.set_dmaudr(idx, true));
+
+        T::Interrupt::unpend();
+        unsafe { T::Interrupt::enable() };
+
+        regs.cr().modify(|w| {
+            w.set_ten(idx, true);
+            w.set_en(idx, true);
+            w.set_dmaen(idx, true);
+            w.set_dmaudrie(idx, true);
+        });
+
+        let _on_drop = OnDrop::new(|| {
+            regs.cr().modify(|w| {
+                w.set_dmaudrie(idx, false);
+                w.set_dmaen(idx, false);
+            });
+        });
+
+        let dma = self.dma.as_mut().unwrap();
+
+        let tx_options = crate::dma::TransferOptions {
+            circular: true,
+            half_transfer_ir: false,
+            complete_transfer_ir: false,
+            ..Default::default()
+        };
+
+        // Dropped before `_on_drop`, so the DMA is stopped before the DAC requests are disabled.
+        let _transfer = unsafe { dma.write_raw(W::dma_buf(data), W::dma_ptr(regs, idx), tx_options) };
+
+        poll_fn(|cx| {
+            state.waker.register(cx.waker());
+
+            if regs.sr().read().dmaudr(idx) {
+                regs.sr().write(|w| w.set_dmaudr(idx, true));
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+
+        Underrun
+    }
 }
 
 impl<'d> DacChannel<'d, Blocking> {
@@ -519,6 +725,54 @@ impl<'d, M: PeriMode> DacChannel<'d, M> {
         });
     }
 
+    /// Select the trigger source of this channel.
+    ///
+    /// The trigger is only used once triggering is enabled with [`Self::set_triggering`].
+    /// This method disables the channel, so you may need to re-enable afterwards.
+    pub fn set_trigger<T: Instance>(&mut self, trigger: impl ChannelTrigger<T>) {
+        assert!(
+            core::ptr::eq(self.info, T::info()),
+            "Trigger does not belong to this DAC instance"
+        );
+
+        critical_section::with(|_| {
+            self.info.regs.cr().modify(|reg| {
+                reg.set_en(self.idx, false);
+                reg.set_tsel(self.idx, trigger.signal());
+            });
+        });
+    }
+
+    /// Select the built-in noise or triangle generator of this channel.
+    ///
+    /// The generator advances on every trigger, so a trigger must be selected with
+    /// [`Self::set_trigger`] and enabled with [`Self::set_triggering`]; [`SOFTWARE`] together
+    /// with [`Self::trigger`] steps it manually.
+    ///
+    /// This method disables the channel, so you may need to re-enable afterwards.
+    ///
+    /// # Panics
+    /// Panics if the number of bits is not in `1..=12`.
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        let (wave, bits) = match waveform {
+            Waveform::Disabled => (crate::pac::dac::vals::Wave::Disabled, 1),
+            Waveform::Noise { bits } => (crate::pac::dac::vals::Wave::Noise, bits),
+            Waveform::Triangle { bits } => (crate::pac::dac::vals::Wave::Triangle, bits),
+        };
+        assert!(
+            (1..=12).contains(&bits),
+            "Waveform amplitude must be between 1 and 12 bits"
+        );
+
+        critical_section::with(|_| {
+            self.info.regs.cr().modify(|reg| {
+                reg.set_en(self.idx, false);
+                reg.set_wave(self.idx, wave);
+                reg.set_mamp(self.idx, bits - 1);
+            });
+        });
+    }
+
     /// Software trigger this channel.
     ///
     /// NOTE: In sawtooth mode, this only works with [SOFTWARE] as reset_trigger source
@@ -553,6 +807,94 @@ impl<'d, M: PeriMode> DacChannel<'d, M> {
         });
     }
 
+    /// Run the user trimming procedure and return the resulting trim value.
+    ///
+    /// The output buffer offset is calibrated against the current VDDA by stepping the trim
+    /// value (OTRIM) until the calibration comparator flips. Run this after VDDA or the
+    /// temperature changed significantly; the result can be stored and restored later with
+    /// [`Self::set_trim`] to skip the procedure.
+    ///
+    /// The calibration is done with the output buffer enabled, the mode is restored afterwards.
+    /// This method disables the channel, so you may need to re-enable afterwards.
+    #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
+    pub fn calibrate(&mut self) -> u8 {
+        let regs = self.info.regs;
+        let mode = regs.mcr().read().mode(self.idx);
+
+        self.set_mode(Mode::NormalExternalBuffered);
+        regs.cr().modify(|reg| reg.set_cen(self.idx, true));
+
+        let mut trim = 0;
+        while trim < 0x1F {
+            regs.ccr().modify(|reg| reg.set_otrim(self.idx, trim));
+            // Wait for tTRIM before checking the comparator output.
+            crate::wait::block_for_us(50);
+            if regs.sr().read().cal_flag(self.idx) {
+                break;
+            }
+            trim += 1;
+        }
+
+        regs.ccr().modify(|reg| reg.set_otrim(self.idx, trim));
+        regs.cr().modify(|reg| reg.set_cen(self.idx, false));
+        regs.mcr().modify(|reg| reg.set_mode(self.idx, mode));
+
+        trim
+    }
+
+    /// Read the current trim value (OTRIM) of this channel.
+    #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
+    pub fn trim(&self) -> u8 {
+        self.info.regs.ccr().read().otrim(self.idx)
+    }
+
+    /// Restore a trim value previously obtained from [`Self::calibrate`].
+    #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
+    pub fn set_trim(&mut self, trim: u8) {
+        assert!(trim <= 0x1F, "Trim value must be at most 31");
+        self.info.regs.ccr().modify(|reg| reg.set_otrim(self.idx, trim));
+    }
+
+    /// Get the current mode of this channel.
+    #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
+    pub fn mode(&self) -> Mode {
+        Mode::from_mode(self.info.regs.mcr().read().mode(self.idx))
+    }
+
+    /// Configure the sample-and-hold timing of this channel.
+    ///
+    /// The timing only applies in the sample-and-hold modes (e.g. [`Mode::SampleHoldExternalBuffered`],
+    /// see [`Self::set_mode`]). The sample-and-hold unit runs from the low-speed clock (LSI or LSE),
+    /// which must be enabled, and keeps working in Stop mode: the output is held on an external
+    /// capacitor while the channel is powered down and refreshed periodically.
+    ///
+    /// This method disables the channel, so you may need to re-enable afterwards.
+    #[cfg(any(dac_v3, dac_v4, dac_v5, dac_v6, dac_v7))]
+    pub fn set_sample_hold(&mut self, config: SampleHoldConfig) {
+        assert!(config.sample_cycles < 1024, "Sample time must be below 1024 cycles");
+        assert!(config.hold_cycles < 1024, "Hold time must be below 1024 cycles");
+
+        critical_section::with(|_| {
+            self.info.regs.cr().modify(|reg| {
+                reg.set_en(self.idx, false);
+            });
+
+            // SHSR cannot be written while a previous write is still being synchronized.
+            while self.info.regs.sr().read().bwst(self.idx) {}
+
+            self.info
+                .regs
+                .shsr(self.idx)
+                .write(|reg| reg.set_tsample(config.sample_cycles));
+            self.info.regs.shhr().modify(|reg| {
+                reg.set_thold(self.idx, config.hold_cycles);
+            });
+            self.info.regs.shrr().modify(|reg| {
+                reg.set_trefresh(self.idx, config.refresh_cycles);
+            });
+        });
+    }
+
     /// Set mode of this channel.
     ///
     /// This method disables the channel, so you may need to re-enable afterwards.
@@ -891,6 +1233,46 @@ impl<'d> Dac<'d, Async> {
             Mode::NormalInternalUnbuffered,
         )
     }
+
+    /// Write interleaved samples for both channels via DMA.
+    ///
+    /// Each [`DualSample`] is transferred to the dual 12-bit right-aligned holding register
+    /// (DHR12RD) with a single DMA request from channel 1, so both outputs are updated together.
+    /// When triggering is enabled, both channels must use the same trigger source to keep them
+    /// phase aligned, e.g. for I/Q or stereo output.
+    ///
+    /// To prevent delays or glitches when outputing a periodic waveform, the `circular`
+    /// flag can be set. This configures a circular DMA transfer that continually outputs
+    /// `data`. Note that for performance reasons in circular mode the transfer-complete
+    /// interrupt is disabled.
+    #[cfg(not(gpdma))]
+    pub async fn write_dual(&mut self, data: &[DualSample], circular: bool) {
+        let regs = self.info.regs;
+
+        // Only channel 1 issues DMA requests, channel 2 is served by the same transfer.
+        regs.cr().modify(|w| {
+            w.set_en(0, true);
+            w.set_en(1, true);
+            w.set_dmaen(0, true);
+            w.set_dmaen(1, false);
+        });
+
+        let dma = self.ch1.dma.as_mut().unwrap();
+
+        let tx_options = crate::dma::TransferOptions {
+            circular,
+            half_transfer_ir: false,
+            complete_transfer_ir: !circular,
+            ..Default::default()
+        };
+
+        let data: &[u32] = unsafe { slice::from_raw_parts(data.as_ptr() as *const u32, data.len()) };
+        let tx_f = unsafe { dma.write_raw(data, regs.dhr12rd().as_ptr() as *mut u32, tx_options) };
+
+        tx_f.await;
+
+        regs.cr().modify(|w| w.set_dmaen(0, false));
+    }
 }
 
 impl<'d> Dac<'d, Blocking> {
@@ -1026,6 +1408,30 @@ impl<'d, M: PeriMode> Dac<'d, M> {
     }
 }
 
+/// Sample pair for both channels of a [`Dac`], packed for the dual 12-bit right-aligned
+/// holding register (DHR12RD).
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DualSample(pub u32);
+
+impl DualSample {
+    /// Pack 12-bit right-aligned values for channel 1 and channel 2.
+    pub const fn new(ch1: u16, ch2: u16) -> Self {
+        Self((ch1 as u32 & 0xFFF) | ((ch2 as u32 & 0xFFF) << 16))
+    }
+
+    /// Value for channel 1.
+    pub const fn ch1(&self) -> u16 {
+        (self.0 & 0xFFF) as u16
+    }
+
+    /// Value for channel 2.
+    pub const fn ch2(&self) -> u16 {
+        ((self.0 >> 16) & 0xFFF) as u16
+    }
+}
+
 trait SealedCast<T: ?Sized> {}
 
 /// Convert between slice types
@@ -1179,6 +1585,8 @@ trait SealedInstance {
     fn state() -> &'static State {
         static STATE: State = State {
             state: embassy_sync::blocking_mutex::Mutex::new(core::cell::RefCell::new(InnerState { channel_count: 0 })),
+            #[cfg(not(dac_v1))]
+            waker: AtomicWaker::new(),
         };
         &STATE
     }
@@ -1209,6 +1617,13 @@ impl SealedChannel for Ch2 {
 impl Channel for Ch1 {}
 impl Channel for Ch2 {}
 
+/// DAC instance with an interrupt, required for DMA underrun detection.
+#[cfg(not(dac_v1))]
+pub trait InterruptInstance: Instance {
+    /// Interrupt for this DAC instance.
+    type Interrupt: interrupt::typelevel::Interrupt;
+}
+
 trigger_trait!(ChannelTrigger, Instance);
 trigger_trait!(ChannelIncTrigger, Instance);
 dma_trait!(Dma, Instance, Channel);
@@ -1234,3 +1649,12 @@ foreach_peripheral!(
         impl crate::dac::Instance for peripherals::$inst {}
     };
 );
+
+#[cfg(not(dac_v1))]
+foreach_interrupt!(
+    ($inst:ident, dac, DAC, GLOBAL, $irq:ident) => {
+        impl crate::dac::InterruptInstance for peripherals::$inst {
+            type Interrupt = crate::interrupt::typelevel::$irq;
+        }
+    };
+);
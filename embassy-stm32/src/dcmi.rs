@@ -1,12 +1,13 @@
 //! Digital Camera Interface (DCMI)
 use core::future::poll_fn;
 use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering, compiler_fence};
 use core::task::Poll;
 
 use embassy_hal_internal::PeripheralType;
 use embassy_sync::waitqueue::AtomicWaker;
 
-use crate::dma::ChannelAndRequest;
+use crate::dma::{ChannelAndRequest, ReadableRingBuffer, TransferOptions};
 use crate::gpio::{AfType, Pull};
 use crate::interrupt::typelevel::Interrupt;
 use crate::{Peri, interrupt, rcc};
@@ -29,7 +30,13 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
         }
         if ris.frame_ris() {
             trace!("DCMI IRQ: Frame captured.");
-            crate::pac::DCMI.ier().modify(|ier| ier.set_frame_ie(false));
+            if STATE.continuous.load(Ordering::Relaxed) {
+                // Keep counting frames, the ring buffer is filled without stopping.
+                crate::pac::DCMI.icr().write(|r| r.set_frame_isc(true));
+                STATE.frames.fetch_add(1, Ordering::Release);
+            } else {
+                crate::pac::DCMI.ier().modify(|ier| ier.set_frame_ie(false));
+            }
         }
         STATE.waker.wake();
     }
@@ -60,12 +67,18 @@ pub enum PixelClockPolarity {
 
 struct State {
     waker: AtomicWaker,
+    /// Set while a [`FrameRing`] is capturing.
+    continuous: AtomicBool,
+    /// Frames completed since the [`FrameRing`] was started.
+    frames: AtomicU32,
 }
 
 impl State {
     const fn new() -> State {
         State {
             waker: AtomicWaker::new(),
+            continuous: AtomicBool::new(false),
+            frames: AtomicU32::new(0),
         }
     }
 }
@@ -81,6 +94,24 @@ pub enum Error {
     Overrun,
     /// Internal peripheral error.
     PeripheralError,
+    /// The buffer was filled before the end of the frame.
+    BufferFull,
+}
+
+/// Crop window, capturing only a rectangle of the frames.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CropWindow {
+    /// Pixel clocks to skip at the start of each line.
+    pub x_offset: u16,
+    /// Lines to skip at the start of the frame.
+    pub y_offset: u16,
+    /// Pixel clocks captured per line.
+    ///
+    /// A pixel takes two pixel clocks in 8-bit RGB565 or YCbCr formats.
+    pub width: u16,
+    /// Lines captured.
+    pub height: u16,
 }
 
 /// DCMI configuration.
@@ -92,6 +123,15 @@ pub struct Config {
     pub hsync_level: HSyncDataInvalidLevel,
     /// PIXCLK polarity.
     pub pixclk_polarity: PixelClockPolarity,
+    /// Crop window, or `None` to capture the whole frames.
+    ///
+    /// Cropping can't be used in JPEG mode.
+    pub crop: Option<CropWindow>,
+    /// JPEG mode, for cameras sending compressed frames.
+    ///
+    /// Frames have a variable length, use [`Dcmi::capture_jpeg()`] to capture them. HSYNC is
+    /// used as a data valid signal.
+    pub jpeg: bool,
 }
 
 impl Default for Config {
@@ -100,6 +140,8 @@ impl Default for Config {
             vsync_level: VSyncDataInvalidLevel::High,
             hsync_level: HSyncDataInvalidLevel::Low,
             pixclk_polarity: PixelClockPolarity::RisingEdge,
+            crop: None,
+            jpeg: false,
         }
     }
 }
@@ -360,6 +402,22 @@ where
     ) -> Self {
         rcc::enable_and_reset::<T>();
 
+        assert!(
+            !(config.jpeg && config.crop.is_some()),
+            "Cropping can't be used in JPEG mode"
+        );
+        if let Some(crop) = config.crop {
+            assert!(crop.width > 0 && crop.width <= 0x4000 && crop.height > 0 && crop.height <= 0x4000);
+            peri.regs().cwstrt().write(|w| {
+                w.set_hoffcnt(crop.x_offset);
+                w.set_vst(crop.y_offset);
+            });
+            peri.regs().cwsize().write(|w| {
+                w.set_capcnt(crop.width - 1);
+                w.set_vline(crop.height - 1);
+            });
+        }
+
         peri.regs().cr().modify(|r| {
             r.set_cm(true); // disable continuous mode (snapshot mode)
             r.set_ess(use_embedded_synchronization);
@@ -368,6 +426,8 @@ where
             r.set_hspol(config.hsync_level == HSyncDataInvalidLevel::High);
             r.set_fcrc(0x00); // capture every frame
             r.set_edm(edm); // extended data mode
+            r.set_crop(config.crop.is_some());
+            r.set_jpeg(config.jpeg);
         });
 
         T::Interrupt::unpend();
@@ -438,6 +498,224 @@ where
 
         result
     }
+
+    /// Capture a JPEG frame into `buffer`, returning the number of words written.
+    ///
+    /// The capture finishes at the end of the frame, so `buffer` only needs to be large enough
+    /// for the largest frame. The last word may be padded after the end of image marker.
+    ///
+    /// The driver must have been configured with [`Config::jpeg`].
+    pub async fn capture_jpeg(&mut self, buffer: &mut [u32]) -> Result<usize, Error> {
+        let r = self.inner.regs();
+        let src = r.dr().as_ptr() as *mut u32;
+        let len = buffer.len();
+        let mut dma_read = unsafe { self.dma.channel.read(self.dma.request, src, buffer, Default::default()) };
+
+        Self::clear_interrupt_flags();
+        Self::enable_irqs();
+
+        Self::toggle(true);
+
+        let frame = poll_fn(|cx| {
+            STATE.waker.register(cx.waker());
+
+            let ris = crate::pac::DCMI.ris().read();
+            if ris.err_ris() {
+                crate::pac::DCMI.icr().write(|r| r.set_err_isc(true));
+                Poll::Ready(Err(Error::PeripheralError))
+            } else if ris.ovr_ris() {
+                crate::pac::DCMI.icr().write(|r| r.set_ovr_isc(true));
+                Poll::Ready(Err(Error::Overrun))
+            } else if ris.frame_ris() {
+                crate::pac::DCMI.icr().write(|r| r.set_frame_isc(true));
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        });
+
+        // The DMA only completes if the frame is larger than the buffer.
+        let result = match embassy_futures::select::select(&mut dma_read, frame).await {
+            embassy_futures::select::Either::First(()) => Err(Error::BufferFull),
+            embassy_futures::select::Either::Second(result) => {
+                result.map(|()| len - dma_read.get_remaining_transfers() as usize)
+            }
+        };
+
+        Self::toggle(false);
+        dma_read.request_reset();
+        while dma_read.is_running() {}
+
+        result
+    }
+
+    /// Start capturing continuously into `buffer`, split in frames of `frame_len` words.
+    ///
+    /// Frames are written one after the other by a circular DMA transfer, wrapping around at the
+    /// end of `buffer`, so no frame is missed between two calls to [`FrameRing::next_frame()`].
+    /// With two frames this is double buffering, with three triple buffering.
+    ///
+    /// Like for [`Self::capture()`], `frame_len` must be exactly the size of a captured frame.
+    pub fn capture_ring<'a>(&'a mut self, buffer: &'a mut [u32], frame_len: usize) -> FrameRing<'a, T> {
+        assert!(frame_len > 0 && buffer.len() % frame_len == 0);
+        let slots = buffer.len() / frame_len;
+        assert!(slots >= 2, "The buffer must hold at least two frames");
+
+        let r = self.inner.regs();
+        let src = r.dr().as_ptr() as *mut u32;
+        let data = buffer.as_ptr();
+        let mut ring_buf = unsafe {
+            ReadableRingBuffer::new(
+                self.dma.channel.reborrow(),
+                self.dma.request,
+                src,
+                buffer,
+                TransferOptions::default(),
+            )
+        };
+        ring_buf.start();
+
+        STATE.frames.store(0, Ordering::Relaxed);
+        STATE.continuous.store(true, Ordering::Relaxed);
+        r.cr().modify(|w| w.set_cm(false)); // continuous mode
+
+        Self::clear_interrupt_flags();
+        Self::enable_irqs();
+        Self::toggle(true);
+
+        FrameRing {
+            _ring_buf: ring_buf,
+            data,
+            frame_len,
+            slots,
+            next: 0,
+            next_slot: 0,
+            dropped: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Continuous capture into a ring of frames, created by [`Dcmi::capture_ring()`].
+///
+/// The capture stops when this is dropped.
+pub struct FrameRing<'a, T: Instance> {
+    _ring_buf: ReadableRingBuffer<'a, u32>,
+    data: *const u32,
+    frame_len: usize,
+    slots: usize,
+    /// Sequence number of the next frame to return.
+    next: u32,
+    next_slot: usize,
+    dropped: u32,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T: Instance> FrameRing<'a, T> {
+    /// Wait for the next completed frame and copy it into `dst`.
+    ///
+    /// `dst` must be at least `frame_len` words long. Frames are returned in order. When the
+    /// DMA is about to write over frames that were not read yet, because this was not called
+    /// often enough, they are skipped and counted as dropped. So is a frame overwritten while
+    /// it was being copied.
+    ///
+    /// After an error, the capture is stopped. Drop this and call [`Dcmi::capture_ring()`] again
+    /// to restart it.
+    pub async fn next_frame(&mut self, dst: &mut [u32]) -> Result<Frame, Error> {
+        assert!(dst.len() >= self.frame_len);
+
+        let mut dropped = 0;
+        loop {
+            let next = self.next;
+            let completed = poll_fn(|cx| {
+                STATE.waker.register(cx.waker());
+
+                let ris = crate::pac::DCMI.ris().read();
+                if ris.err_ris() {
+                    crate::pac::DCMI.icr().write(|r| r.set_err_isc(true));
+                    Poll::Ready(Err(Error::PeripheralError))
+                } else if ris.ovr_ris() {
+                    crate::pac::DCMI.icr().write(|r| r.set_ovr_isc(true));
+                    Poll::Ready(Err(Error::Overrun))
+                } else {
+                    let completed = STATE.frames.load(Ordering::Acquire);
+                    if completed != next {
+                        Poll::Ready(Ok(completed))
+                    } else {
+                        Poll::Pending
+                    }
+                }
+            })
+            .await
+            .inspect_err(|_| Dcmi::<T>::toggle(false))?;
+
+            // Once a frame completes the DMA starts writing the oldest slot, so only the last
+            // `slots - 1` completed frames are intact.
+            let skipped = completed.wrapping_sub(self.next).saturating_sub(self.slots as u32 - 1);
+            dropped += skipped;
+            self.next = self.next.wrapping_add(skipped);
+            self.next_slot = (self.next_slot + skipped as usize) % self.slots;
+
+            let slot = self.next_slot;
+            let sequence = self.next;
+            self.next = self.next.wrapping_add(1);
+            self.next_slot = (self.next_slot + 1) % self.slots;
+
+            // The DMA keeps running, so the slot is only read through volatile accesses and no
+            // reference to it is ever handed out.
+            for (i, word) in dst[..self.frame_len].iter_mut().enumerate() {
+                // SAFETY: the slot is inside the buffer borrowed by the ring.
+                *word = unsafe { self.data.add(slot * self.frame_len + i).read_volatile() };
+            }
+            compiler_fence(Ordering::SeqCst);
+
+            // The DMA writes over the slot once `slots - 1` more frames complete.
+            if STATE.frames.load(Ordering::Acquire).wrapping_sub(sequence) < self.slots as u32 {
+                self.dropped = self.dropped.wrapping_add(dropped);
+                return Ok(Frame { sequence, dropped });
+            }
+            dropped += 1;
+        }
+    }
+
+    /// Total number of frames dropped since the capture started.
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Total number of frames captured since the capture started.
+    pub fn captured_frames(&self) -> u32 {
+        STATE.frames.load(Ordering::Relaxed)
+    }
+}
+
+impl<'a, T: Instance> Drop for FrameRing<'a, T> {
+    fn drop(&mut self) {
+        Dcmi::<T>::toggle(false);
+        crate::pac::DCMI.ier().write(|_| {});
+        STATE.continuous.store(false, Ordering::Relaxed);
+        crate::pac::DCMI.cr().modify(|w| w.set_cm(true)); // back to snapshot mode
+    }
+}
+
+/// Frame copied by [`FrameRing::next_frame()`].
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Frame {
+    sequence: u32,
+    dropped: u32,
+}
+
+impl Frame {
+    /// Sequence number of the frame, counted from the start of the capture.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Number of frames dropped just before this one.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
 }
 
 trait SealedInstance: crate::rcc::RccPeripheral {
@@ -0,0 +1,782 @@
+//! Digital Filter for Sigma-Delta Modulators (DFSDM)
+//!
+//! The DFSDM receives the bitstream of external sigma-delta modulators on its serial channels,
+//! and converts it to samples with its digital filters. Typical uses are isolated current or
+//! voltage measurement, where the modulator sits on the high voltage side of an isolation
+//! barrier, and digital microphones.
+//!
+//! [`Dfsdm`] owns the channels, which are configured with [`Dfsdm::enable_channel()`]. Each
+//! [`Filter`] converts one regular channel, or a group of injected channels, and has its own
+//! analog watchdog. Short-circuit and clock absence detection are reported by filter 0.
+
+#![macro_use]
+
+use core::future::poll_fn;
+use core::marker::PhantomData;
+use core::task::Poll;
+
+use embassy_hal_internal::PeripheralType;
+use embassy_hal_internal::drop::OnDrop;
+use embassy_sync::waitqueue::AtomicWaker;
+
+use crate::dma::{self, Channel, ReadableRingBuffer, TransferOptions, ringbuffer};
+use crate::gpio::{AfType, Flex, OutputType, Pull, Speed};
+use crate::interrupt::typelevel::{Binding, Handler, Interrupt};
+use crate::pac::dfsdm::Dfsdm as Regs;
+use crate::rcc::{self, RccPeripheral};
+use crate::{Peri, peripherals};
+
+/// DFSDM error.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The DMA ring buffer was not read fast enough.
+    Overrun,
+}
+
+impl From<ringbuffer::Error> for Error {
+    fn from(_: ringbuffer::Error) -> Self {
+        Self::Overrun
+    }
+}
+
+/// Serial interface of a channel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SerialInterface {
+    /// SPI, data sampled on the rising edge of the clock.
+    SpiRising,
+    /// SPI, data sampled on the falling edge of the clock.
+    SpiFalling,
+    /// Manchester coded, rising edge is a 0.
+    ManchesterRisingZero,
+    /// Manchester coded, rising edge is a 1.
+    ManchesterRisingOne,
+}
+
+impl SerialInterface {
+    const fn sitp(&self) -> u8 {
+        match self {
+            SerialInterface::SpiRising => 0,
+            SerialInterface::SpiFalling => 1,
+            SerialInterface::ManchesterRisingZero => 2,
+            SerialInterface::ManchesterRisingOne => 3,
+        }
+    }
+}
+
+/// Clock of a channel in SPI mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiClock {
+    /// External clock on the CKIN pin of the channel.
+    Ckin,
+    /// Internal CKOUT clock, also output to the modulator.
+    Ckout,
+    /// Half of the CKOUT clock, data sampled on the falling edges of CKOUT.
+    CkoutHalfFalling,
+    /// Half of the CKOUT clock, data sampled on the rising edges of CKOUT.
+    CkoutHalfRising,
+}
+
+impl SpiClock {
+    const fn spicksel(&self) -> u8 {
+        match self {
+            SpiClock::Ckin => 0,
+            SpiClock::Ckout => 1,
+            SpiClock::CkoutHalfFalling => 2,
+            SpiClock::CkoutHalfRising => 3,
+        }
+    }
+}
+
+/// Order of a sinc filter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[allow(missing_docs)]
+pub enum SincOrder {
+    FastSinc,
+    Sinc1,
+    Sinc2,
+    Sinc3,
+    Sinc4,
+    Sinc5,
+}
+
+/// Short-circuit detector configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ShortCircuitConfig {
+    /// Number of consecutive identical bits that trigger the detector, in `2..=256`.
+    pub threshold: u16,
+    /// Break outputs signalled on detection, bit `n` being break output `n`.
+    pub break_outputs: u8,
+}
+
+/// Channel configuration.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChannelConfig {
+    /// Serial interface.
+    pub interface: SerialInterface,
+    /// Clock in SPI mode.
+    pub clock: SpiClock,
+    /// Offset subtracted from the filter output, 24 bit signed.
+    pub offset: i32,
+    /// Right shift applied to the filter output, in `0..=31`.
+    pub right_shift: u8,
+    /// Detect the absence of the SPI clock.
+    pub clock_absence_detection: bool,
+    /// Short-circuit detector, `None` to disable it.
+    pub short_circuit: Option<ShortCircuitConfig>,
+    /// Order of the fast analog watchdog filter of the channel.
+    pub watchdog_order: SincOrder,
+    /// Oversampling of the fast analog watchdog filter of the channel, in `1..=32`.
+    pub watchdog_oversampling: u8,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            interface: SerialInterface::SpiRising,
+            clock: SpiClock::Ckout,
+            offset: 0,
+            right_shift: 0,
+            clock_absence_detection: false,
+            short_circuit: None,
+            watchdog_order: SincOrder::Sinc3,
+            watchdog_oversampling: 32,
+        }
+    }
+}
+
+/// DFSDM configuration.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Divider from the DFSDM clock to CKOUT, in `2..=256`.
+    pub ckout_divider: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { ckout_divider: 2 }
+    }
+}
+
+/// Filter configuration.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FilterConfig {
+    /// Order of the sinc filter.
+    pub order: SincOrder,
+    /// Oversampling (decimation) of the sinc filter, in `1..=1024`.
+    pub oversampling: u16,
+    /// Oversampling of the integrator following the sinc filter, in `1..=256`.
+    pub integrator_oversampling: u16,
+    /// Fast mode, for continuous conversions of a single channel.
+    pub fast_mode: bool,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            order: SincOrder::Sinc3,
+            oversampling: 64,
+            integrator_oversampling: 1,
+            fast_mode: false,
+        }
+    }
+}
+
+/// Input of the analog watchdog of a filter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WatchdogSource {
+    /// Output of the filter, only while it converts the watched channels.
+    Filter,
+    /// Fast watchdog filters of the channels, running continuously.
+    Channel,
+}
+
+/// Analog watchdog configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WatchdogConfig {
+    /// Watched channels, bit `n` being channel `n`.
+    pub channels: u8,
+    /// Input of the watchdog.
+    pub source: WatchdogSource,
+    /// High threshold, 24 bit signed.
+    pub high: i32,
+    /// Low threshold, 24 bit signed.
+    pub low: i32,
+}
+
+/// Analog watchdog event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WatchdogEvent {
+    /// Channels above the high threshold, bit `n` being channel `n`.
+    pub high: u8,
+    /// Channels below the low threshold, bit `n` being channel `n`.
+    pub low: u8,
+}
+
+fn sign_extend(value: u32) -> i32 {
+    ((value << 8) as i32) >> 8
+}
+
+/// DFSDM driver, owning the channels.
+pub struct Dfsdm<'d, T: Instance> {
+    _peri: Peri<'d, T>,
+    _ckout: Option<Flex<'d>>,
+    _datin: [Option<Flex<'d>>; 8],
+    _ckin: [Option<Flex<'d>>; 8],
+}
+
+/// Filters of each DFSDM instance, e.g. `Dfsdm1Filters`, returned by [`Dfsdm::new()`].
+pub use crate::_generated::dfsdm_filters::*;
+
+/// DFSDM filter, to be used with [`Filter::new()`].
+pub struct FilterPeri<'d, T: Instance, F: FilterInstance> {
+    _phantom: PhantomData<(&'d mut T, F)>,
+}
+
+impl<'d, T: Instance, F: FilterInstance> FilterPeri<'d, T, F> {
+    pub(crate) fn new() -> Self {
+        Self { _phantom: PhantomData }
+    }
+}
+
+impl<'d, T: Instance> Dfsdm<'d, T> {
+    /// Create a DFSDM driver, without CKOUT output.
+    pub fn new(peri: Peri<'d, T>, config: Config) -> (Self, T::Filters<'d>) {
+        Self::new_inner(peri, None, config)
+    }
+
+    /// Create a DFSDM driver, with CKOUT output to clock the modulators.
+    pub fn new_with_ckout(
+        peri: Peri<'d, T>,
+        ckout: Peri<'d, impl CkoutPin<T>>,
+        config: Config,
+    ) -> (Self, T::Filters<'d>) {
+        let ckout = new_pin!(ckout, AfType::output(OutputType::PushPull, Speed::VeryHigh));
+        Self::new_inner(peri, ckout, config)
+    }
+
+    fn new_inner(peri: Peri<'d, T>, ckout: Option<Flex<'d>>, config: Config) -> (Self, T::Filters<'d>) {
+        assert!((2..=256).contains(&config.ckout_divider));
+
+        rcc::enable_and_reset::<T>();
+
+        // The global settings live in the registers of channel 0.
+        T::REGS.ch(0).cfgr1().modify(|w| {
+            w.set_ckoutdiv((config.ckout_divider - 1) as u8);
+            w.set_dfsdmen(true);
+        });
+
+        let filters = T::Filters::new();
+        let dfsdm = Self {
+            _peri: peri,
+            _ckout: ckout,
+            _datin: [const { None }; 8],
+            _ckin: [const { None }; 8],
+        };
+        (dfsdm, filters)
+    }
+
+    /// Enable a channel clocked by CKOUT, or Manchester coded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.clock` is [`SpiClock::Ckin`], use
+    /// [`Self::enable_channel_with_ckin()`] instead.
+    pub fn enable_channel<C: ChannelInstance>(&mut self, datin: Peri<'d, impl DatinPin<T, C>>, config: ChannelConfig) {
+        assert!(config.clock != SpiClock::Ckin, "DFSDM: external clock needs a CKIN pin");
+
+        self._datin[C::INDEX] = new_pin!(datin, AfType::input(Pull::None));
+        Self::configure_channel(C::INDEX, &config);
+    }
+
+    /// Enable a channel clocked by its CKIN pin.
+    ///
+    /// `config.clock` is ignored.
+    pub fn enable_channel_with_ckin<C: ChannelInstance>(
+        &mut self,
+        datin: Peri<'d, impl DatinPin<T, C>>,
+        ckin: Peri<'d, impl CkinPin<T, C>>,
+        config: ChannelConfig,
+    ) {
+        self._datin[C::INDEX] = new_pin!(datin, AfType::input(Pull::None));
+        self._ckin[C::INDEX] = new_pin!(ckin, AfType::input(Pull::None));
+        Self::configure_channel(
+            C::INDEX,
+            &ChannelConfig {
+                clock: SpiClock::Ckin,
+                ..config
+            },
+        );
+    }
+
+    /// Disable a channel, releasing its pins.
+    pub fn disable_channel<C: ChannelInstance>(&mut self) {
+        T::REGS.ch(C::INDEX).cfgr1().modify(|w| w.set_chen(false));
+        self._datin[C::INDEX] = None;
+        self._ckin[C::INDEX] = None;
+    }
+
+    fn configure_channel(n: usize, config: &ChannelConfig) {
+        assert!(config.right_shift <= 31);
+        assert!((1..=32).contains(&config.watchdog_oversampling));
+
+        let ch = T::REGS.ch(n);
+
+        // The serial interface can only be changed while the channel is disabled.
+        ch.cfgr1().modify(|w| w.set_chen(false));
+
+        ch.cfgr2().write(|w| {
+            w.set_offset((config.offset as u32) & 0xFF_FFFF);
+            w.set_dtrbs(config.right_shift);
+        });
+
+        ch.awscdr().write(|w| {
+            w.set_awford(config.watchdog_order as u8);
+            w.set_awfosr(config.watchdog_oversampling - 1);
+            if let Some(scd) = config.short_circuit {
+                assert!((2..=256).contains(&scd.threshold));
+                w.set_scdt((scd.threshold - 1) as u8);
+                w.set_bkscd(scd.break_outputs);
+            }
+        });
+
+        ch.cfgr1().modify(|w| {
+            w.set_sitp(config.interface.sitp());
+            w.set_spicksel(config.clock.spicksel());
+            w.set_scden(config.short_circuit.is_some());
+            w.set_ckaben(config.clock_absence_detection);
+            w.set_chinsel(false);
+            w.set_datmpx(0);
+            w.set_datpack(0);
+            w.set_chen(true);
+        });
+    }
+}
+
+impl<'d, T: Instance> Drop for Dfsdm<'d, T> {
+    fn drop(&mut self) {
+        for n in 0..8 {
+            T::REGS.ch(n).cfgr1().modify(|w| w.set_chen(false));
+        }
+        T::REGS.ch(0).cfgr1().modify(|w| w.set_dfsdmen(false));
+        rcc::disable::<T>();
+    }
+}
+
+/// DFSDM filter driver.
+pub struct Filter<'d, T: Instance, F: FilterInstance> {
+    _peri: FilterPeri<'d, T, F>,
+    injected_channels: u8,
+}
+
+impl<'d, T: FilterInterrupt<F>, F: FilterInstance> Filter<'d, T, F> {
+    /// Create a filter driver.
+    pub fn new(
+        peri: FilterPeri<'d, T, F>,
+        _irq: impl Binding<T::Interrupt, InterruptHandler<T, F>> + 'd,
+        config: FilterConfig,
+    ) -> Self {
+        T::Interrupt::unpend();
+        unsafe { T::Interrupt::enable() };
+
+        let mut this = Self {
+            _peri: peri,
+            injected_channels: 0,
+        };
+        this.set_config(&config);
+        this
+    }
+}
+
+impl<'d, T: Instance, F: FilterInstance> Filter<'d, T, F> {
+    fn regs() -> crate::pac::dfsdm::Flt {
+        T::REGS.flt(F::INDEX)
+    }
+
+    /// Reconfigure the filter.
+    pub fn set_config(&mut self, config: &FilterConfig) {
+        assert!((1..=1024).contains(&config.oversampling));
+        assert!((1..=256).contains(&config.integrator_oversampling));
+
+        let r = Self::regs();
+        r.cr1().modify(|w| w.set_dfen(false));
+        r.fcr().write(|w| {
+            w.set_ford(config.order as u8);
+            w.set_fosr(config.oversampling - 1);
+            w.set_iosr((config.integrator_oversampling - 1) as u8);
+        });
+        r.cr1().modify(|w| {
+            w.set_fast(config.fast_mode);
+            w.set_dfen(true);
+        });
+    }
+
+    /// Select the channel of regular conversions.
+    pub fn set_regular_channel<C: ChannelInstance>(&mut self) {
+        Self::regs().cr1().modify(|w| w.set_rch(C::INDEX as u8));
+    }
+
+    /// Run a regular conversion, returning the 24 bit signed result.
+    pub async fn read_regular(&mut self) -> i32 {
+        let r = Self::regs();
+        let _on_drop = OnDrop::new(|| r.cr2().modify(|w| w.set_reocie(false)));
+
+        r.cr2().modify(|w| w.set_reocie(true));
+        r.cr1().modify(|w| w.set_rswstart(true));
+        self.wait_cleared(|| r.cr2().read().reocie()).await;
+
+        sign_extend(r.rdatar().read().rdata())
+    }
+
+    /// Select the channels of injected conversions, bit `n` being channel `n`.
+    ///
+    /// The channels are converted in increasing order.
+    pub fn set_injected_channels(&mut self, channels: u8) {
+        assert!(channels != 0);
+
+        let r = Self::regs();
+        r.cr1().modify(|w| w.set_dfen(false));
+        r.jchgr().write(|w| w.set_jchg(channels));
+        r.cr1().modify(|w| {
+            w.set_jscan(true);
+            w.set_dfen(true);
+        });
+        self.injected_channels = channels;
+    }
+
+    /// Run the injected conversions, writing the 24 bit signed results in channel order.
+    ///
+    /// Each result must be read before the next conversion ends. At high rates, use a lower
+    /// oversampling or fewer injected channels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values.len()` is not the number of injected channels.
+    pub async fn read_injected(&mut self, values: &mut [i32]) {
+        let channels = self.injected_channels;
+        assert_eq!(values.len(), channels.count_ones() as usize);
+
+        let r = Self::regs();
+        let _on_drop = OnDrop::new(|| r.cr2().modify(|w| w.set_jeocie(false)));
+
+        for i in 0..values.len() {
+            r.cr2().modify(|w| w.set_jeocie(true));
+            if i == 0 {
+                r.cr1().modify(|w| w.set_jswstart(true));
+            }
+            self.wait_cleared(|| r.cr2().read().jeocie()).await;
+
+            let data = r.jdatar().read();
+            let index = (channels & ((1 << data.jdatach()) - 1)).count_ones() as usize;
+            values[index] = sign_extend(data.jdata());
+        }
+    }
+
+    /// Configure the analog watchdog.
+    pub fn set_watchdog(&mut self, config: &WatchdogConfig) {
+        let r = Self::regs();
+        r.cr1().modify(|w| w.set_dfen(false));
+        r.awhtr().write(|w| w.set_awht((config.high as u32) & 0xFF_FFFF));
+        r.awltr().write(|w| w.set_awlt((config.low as u32) & 0xFF_FFFF));
+        r.cr2().modify(|w| w.set_awdch(config.channels));
+        r.cr1().modify(|w| {
+            w.set_awfsel(config.source == WatchdogSource::Channel);
+            w.set_dfen(true);
+        });
+    }
+
+    /// Wait until a watched channel crosses a threshold of the analog watchdog.
+    pub async fn wait_watchdog(&mut self) -> WatchdogEvent {
+        let r = Self::regs();
+        let _on_drop = OnDrop::new(|| r.cr2().modify(|w| w.set_awdie(false)));
+
+        r.cr2().modify(|w| w.set_awdie(true));
+        self.wait_cleared(|| r.cr2().read().awdie()).await;
+
+        let sr = r.awsr().read();
+        let event = WatchdogEvent {
+            high: sr.awhtf(),
+            low: sr.awltf(),
+        };
+        r.awcfr().write(|w| {
+            w.set_clrawhtf(event.high);
+            w.set_clrawltf(event.low);
+        });
+        event
+    }
+
+    /// Start continuous regular conversions of channel `C`, streamed by DMA to a ring buffer.
+    pub fn into_ring_buffered<C: ChannelInstance, D: Dma<T, F>>(
+        self,
+        dma: Peri<'d, D>,
+        dma_buf: &'d mut [u32],
+        irq: impl Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
+    ) -> RingBufferedFilter<'d, T, F> {
+        let r = Self::regs();
+        r.cr1().modify(|w| w.set_dfen(false));
+        r.cr1().modify(|w| {
+            w.set_rch(C::INDEX as u8);
+            w.set_rcont(true);
+            w.set_rdmaen(true);
+            w.set_dfen(true);
+        });
+
+        let opts = TransferOptions {
+            half_transfer_ir: true,
+            ..Default::default()
+        };
+        let request = dma.request();
+        let ring_buf = unsafe {
+            ReadableRingBuffer::new(
+                Channel::new(dma, irq),
+                request,
+                r.rdatar().as_ptr() as *mut u32,
+                dma_buf,
+                opts,
+            )
+        };
+
+        RingBufferedFilter { filter: self, ring_buf }
+    }
+
+    async fn wait_cleared(&mut self, enabled: impl Fn() -> bool) {
+        poll_fn(|cx| {
+            T::state().wakers[F::INDEX].register(cx.waker());
+            if enabled() { Poll::Pending } else { Poll::Ready(()) }
+        })
+        .await
+    }
+}
+
+impl<'d, T: Instance> Filter<'d, T, Flt0> {
+    /// Wait for a short circuit on a channel, returning the channels that detected one.
+    pub async fn wait_short_circuit(&mut self) -> u8 {
+        let r = Self::regs();
+        let _on_drop = OnDrop::new(|| r.cr2().modify(|w| w.set_scdie(false)));
+
+        r.cr2().modify(|w| w.set_scdie(true));
+        self.wait_cleared(|| r.cr2().read().scdie()).await;
+
+        let channels = r.isr().read().scdf();
+        r.icr().write(|w| w.set_clrscdf(channels));
+        channels
+    }
+
+    /// Wait for the SPI clock of a channel to stop, returning the channels that lost it.
+    pub async fn wait_clock_absence(&mut self) -> u8 {
+        let r = Self::regs();
+        let _on_drop = OnDrop::new(|| r.cr2().modify(|w| w.set_ckabie(false)));
+
+        // The flags are set until the clock is seen, clear the stale ones first.
+        r.icr().write(|w| w.set_clrckabf(0xFF));
+        r.cr2().modify(|w| w.set_ckabie(true));
+        self.wait_cleared(|| r.cr2().read().ckabie()).await;
+
+        let channels = r.isr().read().ckabf();
+        r.icr().write(|w| w.set_clrckabf(channels));
+        channels
+    }
+}
+
+impl<'d, T: Instance, F: FilterInstance> Drop for Filter<'d, T, F> {
+    fn drop(&mut self) {
+        Self::regs().cr1().modify(|w| w.set_dfen(false));
+    }
+}
+
+/// Filter with continuous regular conversions streamed by DMA.
+pub struct RingBufferedFilter<'d, T: Instance, F: FilterInstance> {
+    filter: Filter<'d, T, F>,
+    ring_buf: ReadableRingBuffer<'d, u32>,
+}
+
+impl<'d, T: Instance, F: FilterInstance> RingBufferedFilter<'d, T, F> {
+    /// Start the conversions.
+    pub fn start(&mut self) {
+        self.ring_buf.start();
+        Filter::<T, F>::regs().cr1().modify(|w| w.set_rswstart(true));
+    }
+
+    /// Read 24 bit signed samples from the ring buffer.
+    ///
+    /// This starts the conversions if needed, and waits until `samples` can be filled.
+    pub async fn read(&mut self, samples: &mut [i32]) -> Result<(), Error> {
+        if !self.ring_buf.is_running() {
+            self.start();
+        }
+
+        // SAFETY: i32 and u32 have the same layout.
+        let raw = unsafe { core::slice::from_raw_parts_mut(samples.as_mut_ptr() as *mut u32, samples.len()) };
+        self.ring_buf.read_exact(raw).await?;
+
+        // The data register holds the sample in its upper 24 bits.
+        for sample in samples {
+            *sample >>= 8;
+        }
+        Ok(())
+    }
+
+    /// Stop the conversions, returning the filter.
+    pub fn into_filter(mut self) -> Filter<'d, T, F> {
+        let r = Filter::<T, F>::regs();
+        r.cr1().modify(|w| w.set_dfen(false));
+        r.cr1().modify(|w| {
+            w.set_rcont(false);
+            w.set_rdmaen(false);
+            w.set_dfen(true);
+        });
+        self.ring_buf.request_pause();
+        self.filter
+    }
+}
+
+/// Filter interrupt handler.
+pub struct InterruptHandler<T: Instance, F: FilterInstance> {
+    _phantom: PhantomData<(T, F)>,
+}
+
+impl<T: FilterInterrupt<F>, F: FilterInstance> Handler<T::Interrupt> for InterruptHandler<T, F> {
+    unsafe fn on_interrupt() {
+        let r = T::REGS.flt(F::INDEX);
+        let isr = r.isr().read();
+
+        // Disable the interrupts that fired, the waiting tasks see it to know they are done.
+        r.cr2().modify(|w| {
+            if isr.reocf() {
+                w.set_reocie(false);
+            }
+            if isr.jeocf() {
+                w.set_jeocie(false);
+            }
+            if isr.awdf() {
+                w.set_awdie(false);
+            }
+            if isr.scdf() != 0 {
+                w.set_scdie(false);
+            }
+            if isr.ckabf() != 0 {
+                w.set_ckabie(false);
+            }
+        });
+
+        T::state().wakers[F::INDEX].wake();
+    }
+}
+
+struct State {
+    wakers: [AtomicWaker; 4],
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            wakers: [const { AtomicWaker::new() }; 4],
+        }
+    }
+}
+
+trait SealedInstance {
+    const REGS: Regs;
+    fn state() -> &'static State;
+}
+
+/// DFSDM instance trait.
+#[allow(private_bounds)]
+pub trait Instance: SealedInstance + PeripheralType + RccPeripheral + 'static {
+    /// Filters of the instance, returned by [`Dfsdm::new()`].
+    type Filters<'d>: SealedFilters;
+}
+
+/// DFSDM instance having filter `F`.
+pub trait FilterInterrupt<F: FilterInstance>: Instance {
+    /// Interrupt of the filter.
+    type Interrupt: Interrupt;
+}
+
+pub(crate) trait SealedFilters {
+    fn new() -> Self;
+}
+
+trait SealedChannelInstance {
+    const INDEX: usize;
+}
+
+/// DFSDM channel trait.
+#[allow(private_bounds)]
+pub trait ChannelInstance: SealedChannelInstance + 'static {}
+
+trait SealedFilterInstance {
+    const INDEX: usize;
+}
+
+/// DFSDM filter trait.
+#[allow(private_bounds)]
+pub trait FilterInstance: SealedFilterInstance + 'static {}
+
+macro_rules! impl_channel {
+    ($name:ident, $index:expr) => {
+        #[doc = concat!("Channel ", stringify!($index), ".")]
+        pub enum $name {}
+        impl SealedChannelInstance for $name {
+            const INDEX: usize = $index;
+        }
+        impl ChannelInstance for $name {}
+    };
+}
+
+impl_channel!(Ch0, 0);
+impl_channel!(Ch1, 1);
+impl_channel!(Ch2, 2);
+impl_channel!(Ch3, 3);
+impl_channel!(Ch4, 4);
+impl_channel!(Ch5, 5);
+impl_channel!(Ch6, 6);
+impl_channel!(Ch7, 7);
+
+macro_rules! impl_filter {
+    ($name:ident, $index:expr) => {
+        #[doc = concat!("Filter ", stringify!($index), ".")]
+        pub enum $name {}
+        impl SealedFilterInstance for $name {
+            const INDEX: usize = $index;
+        }
+        impl FilterInstance for $name {}
+    };
+}
+
+impl_filter!(Flt0, 0);
+impl_filter!(Flt1, 1);
+impl_filter!(Flt2, 2);
+impl_filter!(Flt3, 3);
+
+pin_trait!(CkoutPin, Instance);
+pin_trait!(CkinPin, Instance, ChannelInstance);
+pin_trait!(DatinPin, Instance, ChannelInstance);
+
+dma_trait!(Dma, Instance, FilterInstance);
+
+foreach_peripheral!(
+    (dfsdm, $inst:ident) => {
+        impl SealedInstance for peripherals::$inst {
+            const REGS: Regs = crate::pac::$inst;
+
+            fn state() -> &'static State {
+                static STATE: State = State::new();
+                &STATE
+            }
+        }
+    };
+);
@@ -511,6 +511,12 @@ impl<'d> Channel<'d> {
             #[cfg(dma)]
             DmaInfo::Dma(r) => {
                 assert!(mem_len > 0 && mem_len <= 0xFFFF);
+                // The peripheral port of DMA1 is not connected to the bus matrix.
+                #[cfg(any(stm32f2, stm32f4, stm32f7))]
+                assert!(
+                    dir != Dir::MemoryToMemory || r.as_ptr() != pac::DMA1.as_ptr(),
+                    "DMA1 can't do memory-to-memory transfers, use DMA2"
+                );
                 let state: &ChannelState = &STATE[self.channel as usize];
                 let ch = r.st(info.num);
 
@@ -519,21 +525,28 @@ impl<'d> Channel<'d> {
 
                 // NDTR is the number of transfers in the *peripheral* word size.
                 // ex: if mem_size=1, peri_size=4 and ndtr=3 it'll do 12 mem transfers, 3 peri transfers.
-                let ndtr = match (mem_size, peri_size) {
-                    (WordSize::FourBytes, WordSize::OneByte) => mem_len * 4,
-                    (WordSize::FourBytes, WordSize::TwoBytes) | (WordSize::TwoBytes, WordSize::OneByte) => mem_len * 2,
-                    (WordSize::FourBytes, WordSize::FourBytes)
-                    | (WordSize::TwoBytes, WordSize::TwoBytes)
-                    | (WordSize::OneByte, WordSize::OneByte) => mem_len,
-                    (WordSize::TwoBytes, WordSize::FourBytes) | (WordSize::OneByte, WordSize::TwoBytes) => {
-                        assert!(mem_len % 2 == 0);
-                        mem_len / 2
-                    }
-                    (WordSize::OneByte, WordSize::FourBytes) => {
-                        assert!(mem_len % 4 == 0);
-                        mem_len / 4
+                // Memory-to-memory transfers count words of the source, which is the peripheral side.
+                let ndtr = if dir == Dir::MemoryToMemory {
+                    mem_len
+                } else {
+                    match (mem_size, peri_size) {
+                        (WordSize::FourBytes, WordSize::OneByte) => mem_len * 4,
+                        (WordSize::FourBytes, WordSize::TwoBytes) | (WordSize::TwoBytes, WordSize::OneByte) => {
+                            mem_len * 2
+                        }
+                        (WordSize::FourBytes, WordSize::FourBytes)
+                        | (WordSize::TwoBytes, WordSize::TwoBytes)
+                        | (WordSize::OneByte, WordSize::OneByte) => mem_len,
+                        (WordSize::TwoBytes, WordSize::FourBytes) | (WordSize::OneByte, WordSize::TwoBytes) => {
+                            assert!(mem_len % 2 == 0);
+                            mem_len / 2
+                        }
+                        (WordSize::OneByte, WordSize::FourBytes) => {
+                            assert!(mem_len % 4 == 0);
+                            mem_len / 4
+                        }
+                        (WordSize::EightBytes, _) | (_, WordSize::EightBytes) => unimplemented!("invalid word size"),
                     }
-                    (WordSize::EightBytes, _) | (_, WordSize::EightBytes) => unimplemented!("invalid word size"),
                 };
 
                 assert!(ndtr > 0 && ndtr <= 0xFFFF);
@@ -606,8 +619,22 @@ impl<'d> Channel<'d> {
                 state.complete_count.store(0, Ordering::Release);
                 self.clear_irqs();
 
-                ch.par().write_value(peri_addr as u32);
-                ch.mar().write_value(mem_addr as u32);
+                // Memory-to-memory transfers read from CMAR and write to CPAR, while the source
+                // is passed in `peri_addr`/`peri_size`, so swap the two sides.
+                let mem2mem = dir == Dir::MemoryToMemory;
+                let (peri_addr, mem_addr, peri_size, mem_size, incr_mem) = if mem2mem {
+                    let incr = match incr_mem {
+                        Increment::Peripheral => Increment::Memory,
+                        Increment::Memory => Increment::Peripheral,
+                        incr => incr,
+                    };
+                    (mem_addr as u32, peri_addr as u32, mem_size, peri_size, incr)
+                } else {
+                    (peri_addr as u32, mem_addr as u32, peri_size, mem_size, incr_mem)
+                };
+
+                ch.par().write_value(peri_addr);
+                ch.mar().write_value(mem_addr);
                 ch.ndtr().write(|w| w.set_ndt(mem_len as u16));
                 ch.cr().write(|w| {
                     w.set_psize(peri_size.into());
@@ -631,6 +658,7 @@ impl<'d> Channel<'d> {
                         }
                     }
                     w.set_dir(dir.into());
+                    w.set_mem2mem(mem2mem);
                     w.set_teie(true);
                     w.set_tcie(options.complete_transfer_ir);
                     w.set_htie(options.half_transfer_ir);
@@ -690,6 +718,7 @@ impl<'d> Channel<'d> {
                 let (sinc, dinc) = match (incr_mem, dir) {
                     (Increment::None, _) => (Incmode::Fixed, Incmode::Fixed),
                     (Increment::Both, _) => (Incmode::Increment, Incmode::Increment),
+                    (Increment::Peripheral, Dir::MemoryToMemory) => (Incmode::Increment, Incmode::Fixed),
                     (_, Dir::MemoryToMemory) => (Incmode::Increment, Incmode::Increment),
                     (Increment::Peripheral, Dir::PeripheralToMemory) => (Incmode::Increment, Incmode::Fixed),
                     (Increment::Peripheral, Dir::MemoryToPeripheral) => (Incmode::Fixed, Incmode::Increment),
@@ -973,6 +1002,9 @@ impl<'d> Channel<'d> {
     }
 
     /// Create a memory DMA transfer (memory to memory), using raw pointers.
+    ///
+    /// `src_size` is the number of source words. If the word sizes differ, DMA controllers pack or
+    /// unpack the data through their FIFO, while BDMA controllers zero-extend or truncate each word.
     pub unsafe fn transfer_raw<'a, MW: Word, PW: Word>(
         &'a mut self,
         request: Request,
@@ -981,6 +1013,8 @@ impl<'d> Channel<'d> {
         dest_addr: *mut PW,
         options: TransferOptions,
     ) -> Transfer<'a> {
+        // The source is on the peripheral side of memory-to-memory transfers, so it also takes
+        // the peripheral word size.
         self.configure(
             request,
             Dir::MemoryToMemory,
@@ -988,8 +1022,40 @@ impl<'d> Channel<'d> {
             dest_addr as *mut u32,
             src_size,
             Increment::Both,
-            MW::size(),
             PW::size(),
+            MW::size(),
+            options,
+        );
+        self.start();
+        Transfer {
+            _wake_guard: self.info().wake_guard(),
+            channel: self.reborrow(),
+        }
+    }
+
+    /// Create a memory DMA transfer into a fixed address.
+    ///
+    /// The transfer runs without peripheral requests, so `dest_addr` can be the data register of a
+    /// peripheral without DMA requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics on DMA1 of STM32F2/F4/F7, which can't do memory-to-memory transfers.
+    pub unsafe fn write_to_fixed<'a, W: Word>(
+        &'a mut self,
+        buf: &'a [W],
+        dest_addr: *mut W,
+        options: TransferOptions,
+    ) -> Transfer<'a> {
+        self.configure(
+            Request::default(),
+            Dir::MemoryToMemory,
+            buf as *const [W] as *const W as *const u32,
+            dest_addr as *mut u32,
+            buf.len(),
+            Increment::Peripheral,
+            W::size(),
+            W::size(),
             options,
         );
         self.start();
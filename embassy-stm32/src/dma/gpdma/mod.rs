@@ -485,10 +485,12 @@ impl<'d> Channel<'d> {
     ) {
         // BNDT is the number of source bytes. For a packing/unpacking transfer
         // the memory side dictates how much data the caller wants moved.
+        //
+        // Memory-to-memory transfers are software requested, and move data from `mem_addr` to the
+        // fixed address `peri_addr`.
         let mem_size = match dir {
-            Dir::MemoryToPeripheral => data_size,
+            Dir::MemoryToPeripheral | Dir::MemoryToMemory => data_size,
             Dir::PeripheralToMemory => dst_size,
-            Dir::MemoryToMemory => panic!("memory-to-memory transfers not implemented for GPDMA"),
         };
         let Ok(bndt) = (mem_len * mem_size.bytes()).try_into() else {
             panic!("DMA transfers may not be larger than 65535 bytes.");
@@ -520,7 +522,7 @@ impl<'d> Channel<'d> {
         ch.tr1().write(|w| {
             w.set_sdw(data_size.into());
             w.set_ddw(dst_size.into());
-            w.set_sinc(dir == Dir::MemoryToPeripheral && incr_mem);
+            w.set_sinc(dir != Dir::PeripheralToMemory && incr_mem);
             w.set_dinc(dir == Dir::PeripheralToMemory && incr_mem);
             // Pack/unpack through the channel FIFO when source and destination
             // widths differ. The default (zero-extend / left-truncate) sends
@@ -530,14 +532,12 @@ impl<'d> Channel<'d> {
                 w.set_pam(vals::Pam::Pack);
             }
             w.set_dap(match dir {
-                Dir::MemoryToPeripheral => vals::Ap::Port1, // Destination is peripheral on AHB for HPDMA
+                Dir::MemoryToPeripheral | Dir::MemoryToMemory => vals::Ap::Port1, // Destination is peripheral on AHB for HPDMA
                 Dir::PeripheralToMemory => vals::Ap::Port0, // Destination is memory on AXI for HPDMA
-                Dir::MemoryToMemory => panic!("memory-to-memory transfers not implemented for GPDMA"),
             });
             w.set_sap(match dir {
-                Dir::MemoryToPeripheral => vals::Ap::Port0, // Source is memory on AXI for HPDMA
+                Dir::MemoryToPeripheral | Dir::MemoryToMemory => vals::Ap::Port0, // Source is memory on AXI for HPDMA
                 Dir::PeripheralToMemory => vals::Ap::Port1, // Source is peripheral on AHB for HPDMA
-                Dir::MemoryToMemory => panic!("memory-to-memory transfers not implemented for GPDMA"),
             });
             let bl: u8 = options.burst_length.into();
             w.set_sbl_1(bl);
@@ -550,10 +550,10 @@ impl<'d> Channel<'d> {
         });
         ch.tr2().write(|w| {
             w.set_dreq(match dir {
-                Dir::MemoryToPeripheral => vals::Dreq::DestinationPeripheral,
+                Dir::MemoryToPeripheral | Dir::MemoryToMemory => vals::Dreq::DestinationPeripheral,
                 Dir::PeripheralToMemory => vals::Dreq::SourcePeripheral,
-                Dir::MemoryToMemory => panic!("memory-to-memory transfers not implemented for GPDMA"),
             });
+            w.set_swreq(dir == Dir::MemoryToMemory);
             w.set_breq(options.request_mode.into());
             w.set_reqsel(request);
             if let Some(trigger) = options.trigger {
@@ -566,7 +566,7 @@ impl<'d> Channel<'d> {
         ch.br1().write(|w| w.set_bndt(bndt));
 
         match dir {
-            Dir::MemoryToPeripheral => {
+            Dir::MemoryToPeripheral | Dir::MemoryToMemory => {
                 ch.sar().write_value(mem_addr as _);
                 ch.dar().write_value(peri_addr as _);
             }
@@ -574,7 +574,6 @@ impl<'d> Channel<'d> {
                 ch.sar().write_value(peri_addr as _);
                 ch.dar().write_value(mem_addr as _);
             }
-            Dir::MemoryToMemory => panic!("memory-to-memory transfers not implemented for GPDMA"),
         }
 
         ch.cr().write(|w| {
@@ -847,6 +846,38 @@ impl<'d> Channel<'d> {
         }
     }
 
+    /// Create a memory DMA transfer into a fixed address.
+    ///
+    /// The transfer runs on software requests, so `dest_addr` can be the data register of a
+    /// peripheral without DMA requests.
+    pub unsafe fn write_to_fixed<'a, W: Word>(
+        &'a mut self,
+        buf: &'a [W],
+        dest_addr: *mut W,
+        options: TransferOptions,
+    ) -> Transfer<'a> {
+        let mem_len = buf.len();
+        assert!(mem_len > 0 && mem_len <= 0xFFFF);
+
+        self.configure(
+            Request::default(),
+            Dir::MemoryToMemory,
+            dest_addr as *const u32,
+            buf as *const [W] as *const W as *mut u32,
+            mem_len,
+            true,
+            W::size(),
+            W::size(),
+            options,
+        );
+        self.start();
+
+        Transfer {
+            _wake_guard: self.info().wake_guard(),
+            channel: self.reborrow(),
+        }
+    }
+
     /// Create a linked-list DMA transfer.
     pub unsafe fn linked_list<'a, const ITEM_COUNT: usize>(
         &'a mut self,
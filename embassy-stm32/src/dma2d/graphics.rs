@@ -0,0 +1,138 @@
+//! `embedded-graphics` support
+//!
+//! [`Dma2dFramebuffer`] implements [`DrawTarget`] over a framebuffer in memory. Single pixels are
+//! written by the CPU, while solid fills and clears, which make most of a typical UI, are done by
+//! the DMA2D.
+
+use core::marker::PhantomData;
+
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::{IntoStorage, PixelColor, Rgb565, Rgb888};
+use embedded_graphics_core::primitives::Rectangle;
+
+use super::{Buffer2D, Dma2d, Error, Instance, PixelFormat};
+
+trait SealedDma2dColor {
+    const FORMAT: PixelFormat;
+
+    /// Color as written in the framebuffer and in the output color register.
+    fn raw(self) -> u32;
+}
+
+/// Color type that can be drawn by a [`Dma2dFramebuffer`].
+#[allow(private_bounds)]
+pub trait Dma2dColor: PixelColor + SealedDma2dColor {}
+
+impl SealedDma2dColor for Rgb565 {
+    const FORMAT: PixelFormat = PixelFormat::Rgb565;
+
+    fn raw(self) -> u32 {
+        self.into_storage() as u32
+    }
+}
+impl Dma2dColor for Rgb565 {}
+
+/// [`Rgb888`] colors are stored as opaque ARGB8888.
+impl SealedDma2dColor for Rgb888 {
+    const FORMAT: PixelFormat = PixelFormat::Argb8888;
+
+    fn raw(self) -> u32 {
+        0xFF00_0000 | self.into_storage()
+    }
+}
+impl Dma2dColor for Rgb888 {}
+
+/// Framebuffer drawn with the DMA2D.
+///
+/// The DMA2D transfers are blocking, since [`DrawTarget`] is not async.
+pub struct Dma2dFramebuffer<'a, 'd, T: Instance, C: Dma2dColor> {
+    dma2d: &'a mut Dma2d<'d, T>,
+    buffer: Buffer2D,
+    _color: PhantomData<C>,
+}
+
+impl<'a, 'd, T: Instance, C: Dma2dColor> Dma2dFramebuffer<'a, 'd, T, C> {
+    /// Create a draw target over `buffer`.
+    ///
+    /// The pixel format of `buffer` must be [`PixelFormat::Rgb565`] for [`Rgb565`] and
+    /// [`PixelFormat::Argb8888`] for [`Rgb888`].
+    pub fn new(dma2d: &'a mut Dma2d<'d, T>, buffer: Buffer2D) -> Self {
+        assert_eq!(
+            buffer.format,
+            C::FORMAT,
+            "Buffer pixel format does not match the color type"
+        );
+        Self {
+            dma2d,
+            buffer,
+            _color: PhantomData,
+        }
+    }
+
+    /// Consume the draw target, returning the framebuffer.
+    pub fn into_buffer(self) -> Buffer2D {
+        self.buffer
+    }
+
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Default::default(), self.size())
+    }
+}
+
+impl<'a, 'd, T: Instance, C: Dma2dColor> OriginDimensions for Dma2dFramebuffer<'a, 'd, T, C> {
+    fn size(&self) -> Size {
+        Size::new(self.buffer.width as u32, self.buffer.height as u32)
+    }
+}
+
+impl<'a, 'd, T: Instance, C: Dma2dColor> DrawTarget for Dma2dFramebuffer<'a, 'd, T, C> {
+    type Color = C;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (width, height) = (self.buffer.width as i32, self.buffer.height as i32);
+        let base = self.buffer.ptr.as_ptr();
+
+        for Pixel(point, color) in pixels {
+            if !(0..width).contains(&point.x) || !(0..height).contains(&point.y) {
+                continue;
+            }
+
+            let index = point.y as usize * self.buffer.stride as usize + point.x as usize;
+            // SAFETY: the point is inside the buffer.
+            unsafe {
+                match C::FORMAT {
+                    PixelFormat::Rgb565 => (base as *mut u16).add(index).write_volatile(color.raw() as u16),
+                    _ => (base as *mut u32).add(index).write_volatile(color.raw()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+
+        let region = self.buffer.region(
+            area.top_left.x as u16,
+            area.top_left.y as u16,
+            area.size.width as u16,
+            area.size.height as u16,
+        );
+        self.dma2d.blocking_fill(&region, color.raw())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.bounding_box();
+        self.fill_solid(&area, color)
+    }
+}
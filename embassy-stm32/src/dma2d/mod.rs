@@ -14,6 +14,11 @@ use crate::peripherals::{self};
 use crate::rcc::{self, RccPeripheral};
 use crate::{Peri, interrupt};
 
+#[cfg(feature = "embedded-graphics")]
+mod graphics;
+#[cfg(feature = "embedded-graphics")]
+pub use graphics::{Dma2dColor, Dma2dFramebuffer};
+
 static DMA2D_WAKER: AtomicWaker = AtomicWaker::new();
 
 /// DMA2D Error
@@ -422,6 +427,17 @@ impl<'d, T: Instance> Dma2d<'d, T> {
 
     /// Fill the output buffer with a color into an output region
     pub async fn fill(&mut self, dest: &Region2D, color: u32) -> Result<(), Error> {
+        self.setup_fill(dest, color);
+        Self::transfer().await
+    }
+
+    /// Fill the output buffer with a color into an output region, blocking until done.
+    pub fn blocking_fill(&mut self, dest: &Region2D, color: u32) -> Result<(), Error> {
+        self.setup_fill(dest, color);
+        Self::blocking_transfer()
+    }
+
+    fn setup_fill(&mut self, dest: &Region2D, color: u32) {
         T::regs().opfccr().modify(|w| {
             w.set_cm(dest.format.into());
         });
@@ -432,7 +448,6 @@ impl<'d, T: Instance> Dma2d<'d, T> {
         #[cfg(dma2d_v1)]
         T::regs().ocolr().modify(|w| w.0 = color);
         T::regs().cr().modify(|w| w.set_mode(vals::Mode::RegisterToMemory));
-        Self::transfer().await
     }
 
     /// Copy a source foreground buffer to a destination output buffer
@@ -559,6 +574,32 @@ impl<'d, T: Instance> Dma2d<'d, T> {
         .await
     }
 
+    /// Start a transfer and poll until it completes
+    fn blocking_transfer() -> Result<(), Error> {
+        T::regs()
+            .cr()
+            .modify(|w| w.set_start(stm32_metapac::dma2d::vals::CrStart::Start));
+
+        loop {
+            let isr = T::regs().isr().read();
+
+            if isr.teif() {
+                T::regs().ifcr().modify(|w| w.set_cteif(vals::Cteif::Clear));
+                return Err(Error::TransferError);
+            }
+
+            if isr.ceif() {
+                T::regs().ifcr().modify(|w| w.set_cceif(vals::Cceif::Clear));
+                return Err(Error::ConfigError);
+            }
+
+            if isr.tcif() {
+                T::regs().ifcr().modify(|w| w.set_ctcif(vals::Ctcif::Clear));
+                return Ok(());
+            }
+        }
+    }
+
     /// Enable interrupts
     fn enable_interrupts(enable: bool) {
         T::Interrupt::unpend();
@@ -287,20 +287,47 @@ impl<'d, T: Instance> DsiHost<'d, T> {
         .await
     }
 
-    /// DCS or Generic short/long write command
+    /// DCS short/long write command
+    ///
+    /// `address` is the DCS command and `data` its parameters. A short packet is used for up to one
+    /// parameter, a long packet otherwise. See [`Self::write_generic`] for generic packets.
     pub fn write_cmd(&mut self, channel_id: u8, address: u8, data: &[u8]) -> Result<(), Error> {
         match data.len() {
             0 => self.short_write(channel_id, PacketType::DcsShortPktWriteP0, address, 0),
             1 => self.short_write(channel_id, PacketType::DcsShortPktWriteP1, address, data[0]),
-            _ => self.long_write(
-                channel_id,
-                PacketType::DcsLongPktWrite, // FIXME: This might be a generic long packet, as well...
-                address,
-                data,
-            ),
+            _ => self.long_write(channel_id, PacketType::DcsLongPktWrite, address, data),
+        }
+    }
+
+    /// Generic short/long write command
+    ///
+    /// Used by panels with manufacturer specific commands outside of DCS. A short packet is used for
+    /// up to two bytes, a long packet otherwise.
+    pub fn write_generic(&mut self, channel_id: u8, data: &[u8]) -> Result<(), Error> {
+        match data {
+            [] => self.short_write(channel_id, PacketType::GenShortPktWriteP0, 0, 0),
+            [p1] => self.short_write(channel_id, PacketType::GenShortPktWriteP1, *p1, 0),
+            [p1, p2] => self.short_write(channel_id, PacketType::GenShortPktWriteP2, *p1, *p2),
+            [first, rest @ ..] => self.long_write(channel_id, PacketType::GenLongPktWrite, *first, rest),
         }
     }
 
+    /// Read the response to DCS command `cmd` into `data`
+    pub fn read_dcs(&mut self, channel_id: u8, cmd: u8, data: &mut [u8]) -> Result<(), Error> {
+        self.read(channel_id, PacketType::DcsShortPktRead(cmd), data.len() as u16, data)
+    }
+
+    /// Generic read with up to two request parameters, the response is read into `data`
+    pub fn read_generic(&mut self, channel_id: u8, params: &[u8], data: &mut [u8]) -> Result<(), Error> {
+        let packet_type = match *params {
+            [] => PacketType::GenShortPktReadP0,
+            [p1] => PacketType::GenShortPktReadP1(p1),
+            [p1, p2] => PacketType::GenShortPktReadP2(p1, p2),
+            _ => return Err(Error::InvalidPacketType),
+        };
+        self.read(channel_id, packet_type, data.len() as u16, data)
+    }
+
     fn short_write(&mut self, channel_id: u8, packet_type: PacketType, param1: u8, param2: u8) -> Result<(), Error> {
         // Wait for Command FIFO empty
         self.wait_command_fifo_empty()?;
@@ -168,14 +168,18 @@ impl<SM: StationManagement> GenericPhy<SM> {
 
     // Writes a value to an extended PHY register in MMD address space
     fn smi_write_ext(&mut self, reg_addr: u16, reg_data: u16) {
-        self.sm.smi_write(self.phy_addr, PHY_REG_CTL, 0x0003); // set address
-        self.sm.smi_write(self.phy_addr, PHY_REG_ADDAR, reg_addr);
-        self.sm.smi_write(self.phy_addr, PHY_REG_CTL, 0x4003); // set data
-        self.sm.smi_write(self.phy_addr, PHY_REG_ADDAR, reg_data);
+        self.sm.smi_write_mmd(self.phy_addr, 3, reg_addr, reg_data);
     }
 
     /// Access the underlying station management.
     pub fn station_management(&mut self) -> &mut SM {
         &mut self.sm
     }
+
+    /// Return the SMI address of the PHY.
+    ///
+    /// With [`GenericPhy::new_auto`], this is only valid after the PHY has been reset by the driver.
+    pub fn phy_addr(&self) -> u8 {
+        self.phy_addr
+    }
 }
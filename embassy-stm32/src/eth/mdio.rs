@@ -0,0 +1,111 @@
+//! MDIO access to a single PHY
+
+#[cfg(feature = "time")]
+use embassy_time::{Duration, Timer};
+
+use crate::eth::StationManagement;
+
+/// Basic mode status register (clause 22 register 1).
+const REG_BSR: u8 = 0x01;
+/// Basic mode status register: link status.
+const BSR_LINK_UP: u16 = 1 << 2;
+
+/// MDIO access to the registers of a single PHY.
+///
+/// This pairs a [`StationManagement`] like [`Sma`](crate::eth::Sma) with the address of one PHY,
+/// independent of [`GenericPhy`](crate::eth::GenericPhy). It is meant as a building block for
+/// implementing [`Phy`](crate::eth::Phy) for PHYs that need vendor specific registers, like the
+/// KSZ9031, DP83825 or LAN8742 variants.
+pub struct Mdio<SM: StationManagement> {
+    sm: SM,
+    phy_addr: u8,
+}
+
+impl<SM: StationManagement> Mdio<SM> {
+    /// Create MDIO access to the PHY at `phy_addr`.
+    ///
+    /// # Panics
+    /// `phy_addr` must be in range `0..32`
+    pub fn new(sm: SM, phy_addr: u8) -> Self {
+        assert!(phy_addr < 32);
+        Self { sm, phy_addr }
+    }
+
+    /// Return the SMI address of the PHY.
+    pub fn phy_addr(&self) -> u8 {
+        self.phy_addr
+    }
+
+    /// Read a clause 22 register.
+    pub fn read(&mut self, reg: u8) -> u16 {
+        self.sm.smi_read(self.phy_addr, reg)
+    }
+
+    /// Write a clause 22 register.
+    pub fn write(&mut self, reg: u8, val: u16) {
+        self.sm.smi_write(self.phy_addr, reg, val)
+    }
+
+    /// Read-modify-write a clause 22 register.
+    pub fn modify(&mut self, reg: u8, f: impl FnOnce(u16) -> u16) {
+        let val = self.read(reg);
+        self.write(reg, f(val));
+    }
+
+    /// Read a register of MMD device `devad` with a clause 45 frame.
+    ///
+    /// See [`StationManagement::smi_read_c45`].
+    pub fn read_c45(&mut self, devad: u8, reg: u16) -> u16 {
+        self.sm.smi_read_c45(self.phy_addr, devad, reg)
+    }
+
+    /// Write a register of MMD device `devad` with a clause 45 frame.
+    ///
+    /// See [`StationManagement::smi_write_c45`].
+    pub fn write_c45(&mut self, devad: u8, reg: u16, val: u16) {
+        self.sm.smi_write_c45(self.phy_addr, devad, reg, val)
+    }
+
+    /// Read a register of MMD device `devad` through the clause 22 MMD access registers.
+    pub fn read_mmd(&mut self, devad: u8, reg: u16) -> u16 {
+        self.sm.smi_read_mmd(self.phy_addr, devad, reg)
+    }
+
+    /// Write a register of MMD device `devad` through the clause 22 MMD access registers.
+    pub fn write_mmd(&mut self, devad: u8, reg: u16, val: u16) {
+        self.sm.smi_write_mmd(self.phy_addr, devad, reg, val)
+    }
+
+    /// Check whether the link is up, according to the basic mode status register.
+    pub fn is_link_up(&mut self) -> bool {
+        // The link status bit latches low, read twice to get the current state.
+        self.read(REG_BSR);
+        self.read(REG_BSR) & BSR_LINK_UP != 0
+    }
+
+    /// Wait until the link is up, polling the PHY every `poll_interval`.
+    #[cfg(feature = "time")]
+    pub async fn wait_for_link_up(&mut self, poll_interval: Duration) {
+        while !self.is_link_up() {
+            Timer::after(poll_interval).await;
+        }
+    }
+
+    /// Wait until the link is down, polling the PHY every `poll_interval`.
+    #[cfg(feature = "time")]
+    pub async fn wait_for_link_down(&mut self, poll_interval: Duration) {
+        while self.is_link_up() {
+            Timer::after(poll_interval).await;
+        }
+    }
+
+    /// Access the underlying station management.
+    pub fn station_management(&mut self) -> &mut SM {
+        &mut self.sm
+    }
+
+    /// Release the underlying station management.
+    pub fn release(self) -> SM {
+        self.sm
+    }
+}
@@ -8,20 +8,23 @@ compile_error!("The 'ptp' feature is only supported on STM32 Ethernet MAC v2/v2a
 #[cfg_attr(any(eth_v2, eth_v2a), path = "v2/mod.rs")]
 mod _version;
 mod generic_phy;
+mod mdio;
 mod packet_state;
 mod ptp;
 mod sma;
 
+use core::marker::PhantomData;
 use core::mem::MaybeUninit;
 use core::ptr::addr_of_mut;
 use core::task::Context;
 
 use embassy_hal_internal::PeripheralType;
-use embassy_net_driver::{Capabilities, HardwareAddress, LinkState, PacketMeta};
+use embassy_net_driver::{Capabilities, Checksum, HardwareAddress, LinkState, PacketMeta};
 use embassy_sync::waitqueue::AtomicWaker;
 
 pub use self::_version::{InterruptHandler, *};
 pub use self::generic_phy::*;
+pub use self::mdio::Mdio;
 use self::packet_state::PacketState;
 use self::ptp::PtpTimestampSink;
 #[cfg(feature = "ptp")]
@@ -29,15 +32,51 @@ pub use self::ptp::{PtpTimestamp, PtpTimestampStore};
 pub use self::sma::{Instance as SmaInstance, Sma, StationManagement};
 use crate::rcc::RccPeripheral;
 
-#[allow(unused)]
 const MTU: usize = 1514;
-const TX_BUFFER_SIZE: usize = 1514;
-const RX_BUFFER_SIZE: usize = 1536;
+/// Default size of a transmit packet buffer in [`PacketQueue`].
+pub const TX_BUFFER_SIZE: usize = 1514;
+/// Default size of a receive packet buffer in [`PacketQueue`].
+pub const RX_BUFFER_SIZE: usize = 1536;
 
 #[repr(C, align(8))]
 #[derive(Copy, Clone)]
 pub(crate) struct Packet<const N: usize>([u8; N]);
 
+/// Packet buffers of a descriptor ring, with the buffer size erased from the type.
+pub(crate) struct PacketBuffers<'a> {
+    ptr: *mut u8,
+    len: usize,
+    stride: usize,
+    size: usize,
+    _marker: PhantomData<&'a mut [u8]>,
+}
+
+// Safety: `PacketBuffers` is an exclusive borrow of the packet memory, like `&'a mut [u8]`.
+unsafe impl Send for PacketBuffers<'_> {}
+
+impl<'a> PacketBuffers<'a> {
+    pub(crate) fn new<const N: usize>(buffers: &'a mut [Packet<N>]) -> Self {
+        Self {
+            ptr: buffers.as_mut_ptr() as *mut u8,
+            len: buffers.len(),
+            stride: size_of::<Packet<N>>(),
+            size: N,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Size of a single packet buffer in bytes.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    pub(crate) fn get(&mut self, index: usize) -> &mut [u8] {
+        assert!(index < self.len);
+        // Safety: `index` is in bounds, and the buffers are exclusively borrowed for `'a`.
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.add(index * self.stride), self.size) }
+    }
+}
+
 /// Ethernet packet queue.
 ///
 /// This struct owns the memory used for reading and writing packets.
@@ -46,15 +85,28 @@ pub(crate) struct Packet<const N: usize>([u8; N]);
 /// queue. A bigger queue allows the hardware to receive more packets while the
 /// CPU is busy doing other things, which may increase performance (especially for RX)
 /// at the cost of more RAM usage.
-pub struct PacketQueue<const TX: usize, const RX: usize> {
+///
+/// `TX_BUF` and `RX_BUF` are the sizes of a single packet buffer in bytes. The transmit buffer
+/// size limits the MTU reported to the network stack, and received frames that do not fit into
+/// one receive buffer are dropped. On ETH v2 the receive buffer size must be a multiple of 4.
+///
+/// The Ethernet DMA can only access some RAM regions on some chips (e.g. not the DTCM on STM32H7).
+/// To control the placement, put the queue in a `static` with a `#[link_section]` attribute and
+/// initialize it with [`PacketQueue::init`].
+pub struct PacketQueue<
+    const TX: usize,
+    const RX: usize,
+    const TX_BUF: usize = TX_BUFFER_SIZE,
+    const RX_BUF: usize = RX_BUFFER_SIZE,
+> {
     tx_desc: [TDes; TX],
     rx_desc: [RDes; RX],
-    tx_buf: [Packet<TX_BUFFER_SIZE>; TX],
-    rx_buf: [Packet<RX_BUFFER_SIZE>; RX],
+    tx_buf: [Packet<TX_BUF>; TX],
+    rx_buf: [Packet<RX_BUF>; RX],
     packet_state: PacketState<TX, RX>,
 }
 
-impl<const TX: usize, const RX: usize> PacketQueue<TX, RX> {
+impl<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize> PacketQueue<TX, RX, TX_BUF, RX_BUF> {
     /// Create a new packet queue.
     pub const fn new() -> Self {
         Self::new_inner(PtpTimestampSink::new())
@@ -78,8 +130,8 @@ impl<const TX: usize, const RX: usize> PacketQueue<TX, RX> {
         Self {
             tx_desc: [const { TDes::new() }; TX],
             rx_desc: [const { RDes::new() }; RX],
-            tx_buf: [Packet([0; TX_BUFFER_SIZE]); TX],
-            rx_buf: [Packet([0; RX_BUFFER_SIZE]); RX],
+            tx_buf: [Packet([0; TX_BUF]); TX],
+            rx_buf: [Packet([0; RX_BUF]); RX],
             packet_state: PacketState::new(ptp),
         }
     }
@@ -121,6 +173,18 @@ impl<const TX: usize, const RX: usize> PacketQueue<TX, RX> {
     }
 }
 
+/// Hardware checksum offload configuration, see [`Ethernet::set_checksum_offload`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChecksumOffload {
+    /// Insert the IPv4 header and TCP/UDP/ICMP checksums of transmitted frames in hardware.
+    pub tx: bool,
+    /// Verify the IPv4 header and TCP/UDP/ICMP checksums of received frames in hardware.
+    ///
+    /// Frames with a bad checksum are dropped by the driver.
+    pub rx: bool,
+}
+
 static WAKER: AtomicWaker = AtomicWaker::new();
 
 impl<'d, T: Instance, P: Phy> embassy_net_driver::Driver for Ethernet<'d, T, P> {
@@ -154,18 +218,20 @@ impl<'d, T: Instance, P: Phy> embassy_net_driver::Driver for Ethernet<'d, T, P>
 
     fn capabilities(&self) -> Capabilities {
         let mut caps = Capabilities::default();
-        caps.max_transmission_unit = MTU;
+        caps.max_transmission_unit = MTU.min(self.tx.buffer_size());
         caps.max_burst_size = Some(self.tx.len());
-        // The v2a MAC offloads the IPv4 header and TCP/UDP payload
-        // checksums in hardware (MACCR.IPC + TDES3.CIC; bad RX frames are dropped
-        // in the descriptor ring), so smoltcp can skip them.
-        #[cfg(eth_v2a)]
-        {
-            use embassy_net_driver::Checksum;
-            caps.checksum.ipv4 = Checksum::None;
-            caps.checksum.tcp = Checksum::None;
-            caps.checksum.udp = Checksum::None;
-        }
+        // Let smoltcp skip the checksums the MAC computes or verifies in hardware,
+        // see `Ethernet::set_checksum_offload`.
+        let checksum = match (self.tx.checksum_offload(), self.rx.checksum_offload()) {
+            (true, true) => Checksum::None,
+            (true, false) => Checksum::Rx,
+            (false, true) => Checksum::Tx,
+            (false, false) => Checksum::Both,
+        };
+        caps.checksum.ipv4 = checksum;
+        caps.checksum.tcp = checksum;
+        caps.checksum.udp = checksum;
+        caps.checksum.icmpv4 = checksum;
         caps
     }
 
@@ -236,6 +302,24 @@ pub trait Phy {
     fn poll_link(&mut self, cx: &mut Context) -> Option<bool>;
 }
 
+/// Index of a multicast address in the 64-bit MAC hash table.
+///
+/// This is the upper 6 bits of the bit-reversed, inverted CRC-32 of the address.
+fn multicast_hash(addr: &[u8; 6]) -> u8 {
+    let mut crc = !0u32;
+    for byte in addr {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    ((!crc).reverse_bits() >> 26) as u8
+}
+
 impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
     /// Access the user-supplied `Phy`.
     pub fn phy(&self) -> &P {
@@ -305,3 +389,16 @@ pin_trait!(RGMIITXD1Pin, Instance, @A);
 pin_trait!(RGMIITXD2Pin, Instance, @A);
 pin_trait!(RGMIITXD3Pin, Instance, @A);
 pin_trait!(RGMIICLK125Pin, Instance, @A);
+
+#[cfg(test)]
+mod tests {
+    use super::multicast_hash;
+
+    #[test]
+    fn multicast_hash_index() {
+        // IPv4 all-hosts, IPv6 all-nodes and mDNS.
+        assert_eq!(multicast_hash(&[0x01, 0x00, 0x5e, 0x00, 0x00, 0x01]), 32);
+        assert_eq!(multicast_hash(&[0x33, 0x33, 0x00, 0x00, 0x00, 0x01]), 1);
+        assert_eq!(multicast_hash(&[0x01, 0x00, 0x5e, 0x00, 0x00, 0xfb]), 48);
+    }
+}
@@ -11,12 +11,87 @@ use stm32_metapac::common::{RW, Reg};
 
 pub use self::_version::*;
 
+/// MMD access control register (clause 22 register 13).
+const REG_MMD_CTRL: u8 = 0x0D;
+/// MMD access address/data register (clause 22 register 14).
+const REG_MMD_ADDR_DATA: u8 = 0x0E;
+/// MMD access control function: address.
+const MMD_CTRL_ADDRESS: u16 = 0b00 << 14;
+/// MMD access control function: data, no post increment.
+const MMD_CTRL_DATA: u16 = 0b01 << 14;
+
 /// Station Management Interface (SMI).
+///
+/// Implementations only need to provide the clause 22 register accesses. Registers in the clause 45
+/// MMD address space are reached indirectly through the clause 22 MMD access registers 13 and 14,
+/// which is what most 10/100 and gigabit PHYs (e.g. LAN8742, KSZ9031, DP83825) expect.
 pub trait StationManagement {
     /// Read a register over SMI.
     fn smi_read(&mut self, phy_addr: u8, reg: u8) -> u16;
     /// Write a register over SMI.
     fn smi_write(&mut self, phy_addr: u8, reg: u8, val: u16);
+
+    /// Read a register of MMD device `devad` over SMI.
+    fn smi_read_mmd(&mut self, phy_addr: u8, devad: u8, reg: u16) -> u16 {
+        mmd_select(self, phy_addr, devad, reg);
+        self.smi_read(phy_addr, REG_MMD_ADDR_DATA)
+    }
+
+    /// Write a register of MMD device `devad` over SMI.
+    fn smi_write_mmd(&mut self, phy_addr: u8, devad: u8, reg: u16, val: u16) {
+        mmd_select(self, phy_addr, devad, reg);
+        self.smi_write(phy_addr, REG_MMD_ADDR_DATA, val);
+    }
+
+    /// Read a register of MMD device `devad` with a clause 45 frame.
+    ///
+    /// Implementations without native clause 45 frames fall back to [`smi_read_mmd`](Self::smi_read_mmd),
+    /// which reaches the same register on PHYs that also answer clause 22 frames.
+    fn smi_read_c45(&mut self, phy_addr: u8, devad: u8, reg: u16) -> u16 {
+        self.smi_read_mmd(phy_addr, devad, reg)
+    }
+
+    /// Write a register of MMD device `devad` with a clause 45 frame.
+    ///
+    /// Implementations without native clause 45 frames fall back to [`smi_write_mmd`](Self::smi_write_mmd),
+    /// which reaches the same register on PHYs that also answer clause 22 frames.
+    fn smi_write_c45(&mut self, phy_addr: u8, devad: u8, reg: u16, val: u16) {
+        self.smi_write_mmd(phy_addr, devad, reg, val)
+    }
+}
+
+impl<SM: StationManagement + ?Sized> StationManagement for &mut SM {
+    fn smi_read(&mut self, phy_addr: u8, reg: u8) -> u16 {
+        (**self).smi_read(phy_addr, reg)
+    }
+
+    fn smi_write(&mut self, phy_addr: u8, reg: u8, val: u16) {
+        (**self).smi_write(phy_addr, reg, val)
+    }
+
+    fn smi_read_mmd(&mut self, phy_addr: u8, devad: u8, reg: u16) -> u16 {
+        (**self).smi_read_mmd(phy_addr, devad, reg)
+    }
+
+    fn smi_write_mmd(&mut self, phy_addr: u8, devad: u8, reg: u16, val: u16) {
+        (**self).smi_write_mmd(phy_addr, devad, reg, val)
+    }
+
+    fn smi_read_c45(&mut self, phy_addr: u8, devad: u8, reg: u16) -> u16 {
+        (**self).smi_read_c45(phy_addr, devad, reg)
+    }
+
+    fn smi_write_c45(&mut self, phy_addr: u8, devad: u8, reg: u16, val: u16) {
+        (**self).smi_write_c45(phy_addr, devad, reg, val)
+    }
+}
+
+/// Select register `reg` of MMD device `devad` for the next access to the MMD data register.
+fn mmd_select<SM: StationManagement + ?Sized>(sm: &mut SM, phy_addr: u8, devad: u8, reg: u16) {
+    let devad = u16::from(devad & 0x1F);
+    sm.smi_write(phy_addr, REG_MMD_CTRL, MMD_CTRL_ADDRESS | devad);
+    sm.smi_write(phy_addr, REG_MMD_ADDR_DATA, reg);
+    sm.smi_write(phy_addr, REG_MMD_CTRL, MMD_CTRL_DATA | devad);
 }
 
 trait SealedInstance {
@@ -125,4 +125,65 @@ impl<T: Instance> StationManagement for Sma<'_, T> {
         #[cfg(eth_v2a)]
         while macmdioar.read().gb() {}
     }
+
+    fn smi_read_c45(&mut self, phy_addr: u8, devad: u8, reg: u16) -> u16 {
+        let (macmdioar, macmdiodr) = T::regs();
+
+        // In clause 45 frames, RDA holds the MMD and MACMDIODR.RA the register address.
+        macmdiodr.write(|w| w.set_ra(reg));
+        macmdioar.modify(|w| {
+            w.set_c45e(true);
+            w.set_pa(phy_addr);
+            w.set_rda(devad);
+            w.set_goc(0b11); // read
+            w.set_cr(self.clock_range);
+            #[cfg(not(eth_v2a))]
+            w.set_mb(true);
+            #[cfg(eth_v2a)]
+            w.set_gb(true);
+        });
+
+        #[cfg(not(eth_v2a))]
+        while macmdioar.read().mb() {}
+        #[cfg(eth_v2a)]
+        while macmdioar.read().gb() {}
+
+        macmdioar.modify(|w| w.set_c45e(false));
+
+        #[cfg(not(eth_v2a))]
+        let data = macmdiodr.read().md();
+        #[cfg(eth_v2a)]
+        let data = macmdiodr.read().gd();
+        data
+    }
+
+    fn smi_write_c45(&mut self, phy_addr: u8, devad: u8, reg: u16, val: u16) {
+        let (macmdioar, macmdiodr) = T::regs();
+
+        macmdiodr.write(|w| {
+            w.set_ra(reg);
+            #[cfg(not(eth_v2a))]
+            w.set_md(val);
+            #[cfg(eth_v2a)]
+            w.set_gd(val);
+        });
+        macmdioar.modify(|w| {
+            w.set_c45e(true);
+            w.set_pa(phy_addr);
+            w.set_rda(devad);
+            w.set_goc(0b01); // write
+            w.set_cr(self.clock_range);
+            #[cfg(not(eth_v2a))]
+            w.set_mb(true);
+            #[cfg(eth_v2a)]
+            w.set_gb(true);
+        });
+
+        #[cfg(not(eth_v2a))]
+        while macmdioar.read().mb() {}
+        #[cfg(eth_v2a)]
+        while macmdioar.read().gb() {}
+
+        macmdioar.modify(|w| w.set_c45e(false));
+    }
 }
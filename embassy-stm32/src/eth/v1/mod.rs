@@ -107,8 +107,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
     /// river with a non-standard PHY.
     ///
     /// safety: the returned instance is not leak-safe
-    pub fn new<const TX: usize, const RX: usize, #[cfg(afio)] A>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize, #[cfg(afio)] A>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<interrupt::typelevel::ETH, InterruptHandler> + 'd,
         ref_clk: Peri<'d, if_afio!(impl RefClkPin<T, A>)>,
@@ -138,8 +138,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
     ///
     /// See [`Ethernet::new_mii_with_phy`] for creating an RMII ethernet
     /// river with a non-standard PHY.
-    pub fn new_mii<const TX: usize, const RX: usize, #[cfg(afio)] A>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_mii<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize, #[cfg(afio)] A>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<interrupt::typelevel::ETH, InterruptHandler> + 'd,
         rx_clk: Peri<'d, if_afio!(impl RXClkPin<T, A>)>,
@@ -171,8 +171,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
 
 impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
     /// safety: the returned instance is not leak-safe
-    pub fn new_with_phy<const TX: usize, const RX: usize, #[cfg(afio)] A>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_with_phy<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize, #[cfg(afio)] A>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<interrupt::typelevel::ETH, InterruptHandler> + 'd,
         ref_clk: Peri<'d, if_afio!(impl RefClkPin<T, A>)>,
@@ -207,8 +207,8 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
         Self::new_inner(queue, peri, irq, pins, phy, mac_addr, true)
     }
 
-    fn new_inner<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    fn new_inner<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         _irq: impl interrupt::typelevel::Binding<interrupt::typelevel::ETH, InterruptHandler> + 'd,
         pins: Pins<'d>,
@@ -341,8 +341,14 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
     }
 
     /// Create a new MII ethernet driver using 12 pins.
-    pub fn new_mii_with_phy<const TX: usize, const RX: usize, #[cfg(afio)] A>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_mii_with_phy<
+        const TX: usize,
+        const RX: usize,
+        const TX_BUF: usize,
+        const RX_BUF: usize,
+        #[cfg(afio)] A,
+    >(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<interrupt::typelevel::ETH, InterruptHandler> + 'd,
         rx_clk: Peri<'d, if_afio!(impl RXClkPin<T, A>)>,
@@ -388,6 +394,101 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
 
         Self::new_inner(queue, peri, irq, pins, phy, mac_addr, false)
     }
+
+    /// Configure the hardware checksum offload.
+    ///
+    /// RX verification is done by the MAC (MACCR.IPCO), TX insertion is requested for each frame.
+    /// Offload is disabled by default.
+    ///
+    /// `embassy-net` reads the driver capabilities once when the stack is created, so call this
+    /// before handing the driver to the stack.
+    pub fn set_checksum_offload(&mut self, offload: ChecksumOffload) {
+        T::regs().ethernet_mac().maccr().modify(|w| w.set_ipco(offload.rx));
+        self.tx.set_checksum_offload(offload.tx);
+        self.rx.set_checksum_offload(offload.rx);
+    }
+
+    /// The current hardware checksum offload configuration.
+    pub fn checksum_offload(&self) -> ChecksumOffload {
+        ChecksumOffload {
+            tx: self.tx.checksum_offload(),
+            rx: self.rx.checksum_offload(),
+        }
+    }
+
+    /// Receive all frames, regardless of their destination address.
+    pub fn set_promiscuous(&mut self, enabled: bool) {
+        T::regs().ethernet_mac().macffr().modify(|w| w.set_pm(enabled));
+    }
+
+    /// Receive all multicast frames.
+    ///
+    /// This is enabled by default. When disabled, only multicast frames matching a perfect filter
+    /// slot or the hash filter are received, see [`Ethernet::add_multicast_hash`].
+    pub fn set_pass_all_multicast(&mut self, enabled: bool) {
+        T::regs().ethernet_mac().macffr().modify(|w| w.set_pam(enabled));
+    }
+
+    /// Set perfect filter address `slot` (1 to 3) to receive frames sent to `addr`, or clear it with `None`.
+    ///
+    /// Slot 0 holds the station MAC address passed to the constructor. The slots can hold unicast
+    /// as well as multicast addresses.
+    pub fn set_perfect_filter(&mut self, slot: usize, addr: Option<[u8; 6]>) {
+        let mac = T::regs().ethernet_mac();
+        let (hi, lo) = match addr {
+            Some(addr) => (
+                u16::from(addr[4]) | (u16::from(addr[5]) << 8),
+                u32::from_le_bytes([addr[0], addr[1], addr[2], addr[3]]),
+            ),
+            None => (0xFFFF, 0xFFFF_FFFF),
+        };
+
+        // Note: Writing to LR triggers synchronisation of both LR and HR into the MAC core,
+        // so the LR write must happen after the HR write.
+        macro_rules! set_slot {
+            ($hr:ident, $lr:ident, $set_hi:ident, $set_lo:ident) => {{
+                mac.$hr().write(|w| {
+                    w.$set_hi(hi);
+                    w.set_ae(addr.is_some());
+                });
+                mac.$lr().write(|w| w.$set_lo(lo));
+            }};
+        }
+
+        match slot {
+            1 => set_slot!(maca1hr, maca1lr, set_maca1h, set_maca1l),
+            2 => set_slot!(maca2hr, maca2lr, set_maca2h, set_maca2l),
+            3 => set_slot!(maca3hr, maca3lr, set_maca3h, set_maca3l),
+            _ => panic!("invalid perfect filter slot {}", slot),
+        }
+    }
+
+    /// Receive multicast frames sent to `addr` through the hash filter.
+    ///
+    /// The hash filter is imperfect: it also passes other multicast addresses that share the same
+    /// 6-bit hash, so the network stack must still check the destination address.
+    pub fn add_multicast_hash(&mut self, addr: [u8; 6]) {
+        let mac = T::regs().ethernet_mac();
+        let index = multicast_hash(&addr);
+        let bit = 1 << (index & 0x1F);
+        if index & 0x20 != 0 {
+            mac.machthr().modify(|w| w.set_hth(w.hth() | bit));
+        } else {
+            mac.machtlr().modify(|w| w.set_htl(w.htl() | bit));
+        }
+        mac.macffr().modify(|w| {
+            w.set_hm(true);
+            w.set_hpf(true);
+        });
+    }
+
+    /// Remove all addresses from the multicast hash filter.
+    pub fn clear_multicast_hash(&mut self) {
+        let mac = T::regs().ethernet_mac();
+        mac.macffr().modify(|w| w.set_hm(false));
+        mac.machtlr().write(|w| w.set_htl(0));
+        mac.machthr().write(|w| w.set_hth(0));
+    }
 }
 
 impl<'d, T: Instance, P: Phy> Drop for Ethernet<'d, T, P> {
@@ -4,7 +4,7 @@ use embassy_net_driver::PacketMeta;
 use stm32_metapac::eth::vals::{Rpd, Rps};
 use vcell::VolatileCell;
 
-use crate::eth::RX_BUFFER_SIZE;
+use crate::eth::PacketBuffers;
 use crate::eth::packet_state::RxPacketStateRing;
 use crate::pac::ETH;
 
@@ -17,6 +17,12 @@ mod rx_consts {
     pub const RXDESC_0_LS: u32 = 1 << 8;
     /// Error summary
     pub const RXDESC_0_ES: u32 = 1 << 15;
+    /// IPv4 header checksum error
+    pub const RXDESC_0_IPHCE: u32 = 1 << 7;
+    /// Frame type
+    pub const RXDESC_0_FT: u32 = 1 << 5;
+    /// Payload checksum error
+    pub const RXDESC_0_PCE: u32 = 1 << 0;
     /// Frame length
     pub const RXDESC_0_FL_MASK: u32 = 0x3FFF;
     pub const RXDESC_0_FL_SHIFT: usize = 16;
@@ -58,12 +64,20 @@ impl RDes {
 
     /// Return true if this RDes is acceptable to us
     #[inline(always)]
-    fn valid(&self) -> bool {
+    fn valid(&self, checksum_offload: bool) -> bool {
         // Write-back descriptor is valid if:
         //
         // Contains first buffer of packet AND contains last buf of
         // packet AND no errors
-        (self.rdes0.get() & (RXDESC_0_ES | RXDESC_0_FS | RXDESC_0_LS)) == (RXDESC_0_FS | RXDESC_0_LS)
+        let rdes0 = self.rdes0.get();
+        if rdes0 & (RXDESC_0_ES | RXDESC_0_FS | RXDESC_0_LS) != (RXDESC_0_FS | RXDESC_0_LS) {
+            return false;
+        }
+
+        // With checksum offload, FT set together with IPHCE or PCE reports an IPv4/IPv6 frame
+        // with a bad header or payload checksum. The other combinations are frames the MAC
+        // did not check.
+        !(checksum_offload && rdes0 & RXDESC_0_FT != 0 && rdes0 & (RXDESC_0_IPHCE | RXDESC_0_PCE) != 0)
     }
 
     /// Return true if this RDes is not currently owned by the DMA
@@ -74,9 +88,10 @@ impl RDes {
 
     /// Configures the reception buffer address and length and passed descriptor ownership to the DMA
     #[inline(always)]
-    fn set_ready(&self, buf: *mut u8) {
+    fn set_ready(&self, buf: &mut [u8]) {
         self.rdes1
-            .set(self.rdes1.get() | (RX_BUFFER_SIZE as u32) & RXDESC_1_RBS_MASK);
+            .set(self.rdes1.get() & !RXDESC_1_RBS_MASK | (buf.len() as u32) & RXDESC_1_RBS_MASK);
+        let buf = buf.as_mut_ptr();
         self.rdes2.set(buf as u32);
 
         // "Preceding reads and writes cannot be moved past subsequent writes."
@@ -107,7 +122,7 @@ impl RDes {
         ((self.rdes0.get() >> RXDESC_0_FL_SHIFT) & RXDESC_0_FL_MASK) as usize
     }
 
-    fn setup(&self, next: Option<&Self>, buf: *mut u8) {
+    fn setup(&self, next: Option<&Self>, buf: &mut [u8]) {
         // Defer this initialization to this function, so we can have `RingEntry` on bss.
         self.rdes1.set(self.rdes1.get() | RXDESC_1_RCH);
 
@@ -134,22 +149,25 @@ pub enum RunningState {
 /// Rx ring of descriptors and packets
 pub(crate) struct RDesRing<'a> {
     descriptors: &'a mut [RDes],
-    buffers: &'a mut [Packet<RX_BUFFER_SIZE>],
+    buffers: PacketBuffers<'a>,
     state: RxPacketStateRing<'a>,
     index: usize,
+    checksum_offload: bool,
 }
 
 impl<'a> RDesRing<'a> {
-    pub(crate) fn new(
+    pub(crate) fn new<const N: usize>(
         descriptors: &'a mut [RDes],
-        buffers: &'a mut [Packet<RX_BUFFER_SIZE>],
+        buffers: &'a mut [Packet<N>],
         state: RxPacketStateRing<'a>,
     ) -> Self {
         assert!(descriptors.len() > 1);
         assert!(descriptors.len() == buffers.len());
+        assert!(N as u32 <= RXDESC_1_RBS_MASK, "RX buffer size too large");
+        let mut buffers = PacketBuffers::new(buffers);
 
         for (i, entry) in descriptors.iter().enumerate() {
-            entry.setup(descriptors.get(i + 1), buffers[i].0.as_mut_ptr());
+            entry.setup(descriptors.get(i + 1), buffers.get(i));
         }
 
         // Register rx descriptor start
@@ -163,6 +181,7 @@ impl<'a> RDesRing<'a> {
             buffers,
             state,
             index: 0,
+            checksum_offload: false,
         }
     }
 
@@ -189,6 +208,15 @@ impl<'a> RDesRing<'a> {
         }
     }
 
+    pub(crate) fn checksum_offload(&self) -> bool {
+        self.checksum_offload
+    }
+
+    /// Drop received frames that fail the checksum verification of the MAC.
+    pub(crate) fn set_checksum_offload(&mut self, enabled: bool) {
+        self.checksum_offload = enabled;
+    }
+
     /// Get a received packet if any, or None.
     pub(crate) fn available(&mut self) -> Option<&mut [u8]> {
         if self.running_state() != RunningState::Running {
@@ -208,7 +236,7 @@ impl<'a> RDesRing<'a> {
             }
 
             // If packet is invalid, pop it and try again.
-            if !descriptor.valid() {
+            if !descriptor.valid(self.checksum_offload) {
                 warn!("invalid packet: {:08x}", descriptor.rdes0.get());
                 self.pop_packet();
                 continue;
@@ -220,7 +248,7 @@ impl<'a> RDesRing<'a> {
         let descriptor = &mut self.descriptors[self.index];
         let len = descriptor.packet_len();
         self.state.capture(self.index, None);
-        return Some(&mut self.buffers[self.index].0[..len]);
+        return Some(&mut self.buffers.get(self.index)[..len]);
     }
 
     pub(crate) fn meta(&self) -> PacketMeta {
@@ -233,7 +261,7 @@ impl<'a> RDesRing<'a> {
         assert!(descriptor.available());
 
         self.state.clear(self.index);
-        self.descriptors[self.index].set_ready(self.buffers[self.index].0.as_mut_ptr());
+        self.descriptors[self.index].set_ready(self.buffers.get(self.index));
 
         self.demand_poll();
 
@@ -3,7 +3,7 @@ use core::sync::atomic::{Ordering, compiler_fence, fence};
 use embassy_net_driver::PacketMeta;
 use vcell::VolatileCell;
 
-use crate::eth::TX_BUFFER_SIZE;
+use crate::eth::PacketBuffers;
 use crate::eth::packet_state::TxPacketStateRing;
 use crate::pac::ETH;
 
@@ -22,6 +22,8 @@ mod tx_consts {
     pub const TXDESC_0_TCH: u32 = 1 << 20;
     // Error status
     pub const TXDESC_0_ES: u32 = 1 << 15;
+    // Checksum insertion control: IP header and payload, pseudo-header computed by hardware
+    pub const TXDESC_0_CIC_FULL: u32 = 0b11 << 22;
 
     // Transmit buffer size
     pub const TXDESC_1_TBS_SHIFT: usize = 0;
@@ -77,6 +79,11 @@ impl TDes {
         self.tdes2.set(buffer as u32);
     }
 
+    fn set_checksum_insertion(&self, enabled: bool) {
+        let tdes0 = self.tdes0.get() & !TXDESC_0_CIC_FULL;
+        self.tdes0.set(if enabled { tdes0 | TXDESC_0_CIC_FULL } else { tdes0 });
+    }
+
     fn set_buffer1_len(&self, len: usize) {
         self.tdes1
             .set((self.tdes1.get() & !TXDESC_1_TBS_MASK) | ((len as u32) << TXDESC_1_TBS_SHIFT));
@@ -107,20 +114,22 @@ impl TDes {
 
 pub(crate) struct TDesRing<'a> {
     descriptors: &'a mut [TDes],
-    buffers: &'a mut [Packet<TX_BUFFER_SIZE>],
+    buffers: PacketBuffers<'a>,
     state: TxPacketStateRing<'a>,
     index: usize,
+    checksum_offload: bool,
 }
 
 impl<'a> TDesRing<'a> {
     /// Initialise this TDesRing. Assume TDesRing is corrupt
-    pub(crate) fn new(
+    pub(crate) fn new<const N: usize>(
         descriptors: &'a mut [TDes],
-        buffers: &'a mut [Packet<TX_BUFFER_SIZE>],
+        buffers: &'a mut [Packet<N>],
         state: TxPacketStateRing<'a>,
     ) -> Self {
         assert!(descriptors.len() > 0);
         assert!(descriptors.len() == buffers.len());
+        let buffers = PacketBuffers::new(buffers);
 
         for (i, entry) in descriptors.iter().enumerate() {
             entry.setup(descriptors.get(i + 1));
@@ -136,6 +145,7 @@ impl<'a> TDesRing<'a> {
             buffers,
             state,
             index: 0,
+            checksum_offload: false,
         }
     }
 
@@ -143,13 +153,27 @@ impl<'a> TDesRing<'a> {
         self.descriptors.len()
     }
 
+    /// Size of a single packet buffer in bytes.
+    pub(crate) fn buffer_size(&self) -> usize {
+        self.buffers.size()
+    }
+
+    pub(crate) fn checksum_offload(&self) -> bool {
+        self.checksum_offload
+    }
+
+    /// Request checksum insertion by the MAC for the following packets.
+    pub(crate) fn set_checksum_offload(&mut self, enabled: bool) {
+        self.checksum_offload = enabled;
+    }
+
     pub(crate) fn collect_completed(&mut self) {}
 
     /// Return the next available packet buffer for transmitting, or None
     pub(crate) fn available(&mut self) -> Option<&mut [u8]> {
         let descriptor = &mut self.descriptors[self.index];
         if descriptor.available() {
-            Some(&mut self.buffers[self.index].0)
+            Some(self.buffers.get(self.index))
         } else {
             None
         }
@@ -164,8 +188,9 @@ impl<'a> TDesRing<'a> {
         let descriptor = &mut self.descriptors[self.index];
         assert!(descriptor.available());
 
-        descriptor.set_buffer1(self.buffers[self.index].0.as_ptr());
+        descriptor.set_buffer1(self.buffers.get(self.index).as_ptr());
         descriptor.set_buffer1_len(len);
+        descriptor.set_checksum_insertion(self.checksum_offload);
         self.state.commit(self.index);
 
         descriptor.set_owned();
@@ -5,7 +5,7 @@ use vcell::VolatileCell;
 
 use crate::eth::packet_state::{RxPacketStateRing, TxPacketStateRing};
 use crate::eth::ptp::PtpTimestamp;
-use crate::eth::{Packet, RX_BUFFER_SIZE, TX_BUFFER_SIZE};
+use crate::eth::{Packet, PacketBuffers};
 #[cfg(eth_v2)]
 use crate::pac::ETH;
 #[cfg(eth_v2a)]
@@ -44,7 +44,7 @@ mod emac_consts {
 
     // TX checksum insertion control (TDES3, read format), bits [17:16]. 0b11 =
     // insert IP header + payload checksums, with the pseudo-header computed by
-    // hardware (full offload). eth_v2a only.
+    // hardware (full offload).
     pub const EMAC_TDES3_CIC_FULL: u32 = 0x0003_0000;
     pub const EMAC_TDES3_TTSS: u32 = 0x0002_0000;
 
@@ -55,12 +55,13 @@ mod emac_consts {
     pub const EMAC_RDES3_RS1V: u32 = 0x0400_0000;
 
     // RX checksum status (RDES1, write-back format). These are NOT folded into
-    // the RDES3 error summary, so they must be inspected separately. eth_v2a only.
+    // the RDES3 error summary, so they must be inspected separately.
     pub const EMAC_RDES1_IPHE: u32 = 0x0000_0008; // IP header checksum error
     pub const EMAC_RDES1_IPCE: u32 = 0x0000_0080; // IP payload (TCP/UDP/ICMP) checksum error
-    pub const EMAC_RDES1_PT: u32 = 0x0000_0003; // payload type
+    pub const EMAC_RDES1_PT: u32 = 0x0000_0007; // payload type
     pub const EMAC_RDES1_PT_UDP: u32 = 1;
     pub const EMAC_RDES1_PT_TCP: u32 = 2;
+    pub const EMAC_RDES1_PT_ICMP: u32 = 3;
     pub const EMAC_RDES1_TSA: u32 = 0x0000_4000; // timestamp available
 }
 use emac_consts::*;
@@ -105,22 +106,24 @@ impl TDes {
 
 pub(crate) struct TDesRing<'a> {
     descriptors: &'a mut [TDes],
-    buffers: &'a mut [Packet<TX_BUFFER_SIZE>],
+    buffers: PacketBuffers<'a>,
     state: TxPacketStateRing<'a>,
     index: usize,
+    checksum_offload: bool,
     #[cfg(feature = "ptp")]
     completion_index: usize,
 }
 
 impl<'a> TDesRing<'a> {
     /// Initialise this TDesRing. Assume TDesRing is corrupt.
-    pub fn new(
+    pub fn new<const N: usize>(
         descriptors: &'a mut [TDes],
-        buffers: &'a mut [Packet<TX_BUFFER_SIZE>],
+        buffers: &'a mut [Packet<N>],
         state: TxPacketStateRing<'a>,
     ) -> Self {
         assert!(descriptors.len() > 0);
         assert!(descriptors.len() == buffers.len());
+        let buffers = PacketBuffers::new(buffers);
 
         for td in descriptors.iter_mut() {
             *td = TDes::new();
@@ -138,6 +141,7 @@ impl<'a> TDesRing<'a> {
             buffers,
             state,
             index: 0,
+            checksum_offload: false,
             #[cfg(feature = "ptp")]
             completion_index: 0,
         }
@@ -147,13 +151,27 @@ impl<'a> TDesRing<'a> {
         self.descriptors.len()
     }
 
+    /// Size of a single packet buffer in bytes.
+    pub(crate) fn buffer_size(&self) -> usize {
+        self.buffers.size()
+    }
+
+    pub(crate) fn checksum_offload(&self) -> bool {
+        self.checksum_offload
+    }
+
+    /// Request checksum insertion by the MAC for the following packets.
+    pub(crate) fn set_checksum_offload(&mut self, enabled: bool) {
+        self.checksum_offload = enabled;
+    }
+
     /// Return the next available packet buffer for transmitting, or None
     pub(crate) fn available(&mut self) -> Option<&mut [u8]> {
         self.collect_completed();
 
         let d = &mut self.descriptors[self.index];
         if d.available() {
-            Some(&mut self.buffers[self.index].0)
+            Some(self.buffers.get(self.index))
         } else {
             None
         }
@@ -205,7 +223,7 @@ impl<'a> TDesRing<'a> {
         assert!(len as u32 <= EMAC_TDES2_B1L);
 
         // Read format
-        td.tdes0.set(self.buffers[self.index].0.as_ptr() as u32);
+        td.tdes0.set(self.buffers.get(self.index).as_ptr() as u32);
         let mut tdes2 = len as u32 & EMAC_TDES2_B1L;
         tdes2 |= EMAC_TDES2_IOC;
         #[cfg(feature = "ptp")]
@@ -225,10 +243,11 @@ impl<'a> TDesRing<'a> {
         // FD: Contains first buffer of packet
         // LD: Contains last buffer of packet
         // Give the DMA engine ownership
-        let tdes3 = EMAC_DES3_FD | EMAC_DES3_LD | EMAC_DES3_OWN;
-        // CIC_FULL: let the MAC compute and insert the IP/TCP/UDP checksums.
-        #[cfg(eth_v2a)]
-        let tdes3 = tdes3 | EMAC_TDES3_CIC_FULL;
+        let mut tdes3 = EMAC_DES3_FD | EMAC_DES3_LD | EMAC_DES3_OWN;
+        // CIC_FULL: let the MAC compute and insert the IP/TCP/UDP/ICMP checksums.
+        if self.checksum_offload {
+            tdes3 |= EMAC_TDES3_CIC_FULL;
+        }
         td.tdes3.set(tdes3);
 
         // Ensure changes to the descriptor are committed before DMA engine sees tail pointer store.
@@ -270,7 +289,7 @@ impl RDes {
 
     /// Return true if this RDes is acceptable to us
     #[inline(always)]
-    fn valid(&self) -> bool {
+    fn valid(&self, checksum_offload: bool) -> bool {
         // Write-back descriptor is valid if it contains the first AND last
         // buffer of the packet AND has no errors AND is not a context descriptor.
         if self.rdes3.get() & (EMAC_DES3_FD | EMAC_DES3_LD | EMAC_DES3_ES | EMAC_DES3_CTXT)
@@ -279,16 +298,15 @@ impl RDes {
             return false;
         }
 
-        // Hardware checksum offload (eth_v2a): the MAC verified the IPv4 header
-        // and the TCP/UDP payload checksums. smoltcp is told not to re-verify
+        // Hardware checksum offload: the MAC verified the IPv4 header and the
+        // TCP/UDP/ICMP payload checksums. smoltcp is told not to re-verify
         // these (see the driver `capabilities`), so a frame the MAC flagged as
         // bad must be dropped here.
-        #[cfg(eth_v2a)]
-        {
+        if checksum_offload {
             let rdes1 = self.rdes1.get();
             let pt = rdes1 & EMAC_RDES1_PT;
-            let tcp_or_udp = pt == EMAC_RDES1_PT_TCP || pt == EMAC_RDES1_PT_UDP;
-            if rdes1 & EMAC_RDES1_IPHE != 0 || (tcp_or_udp && rdes1 & EMAC_RDES1_IPCE != 0) {
+            let checked = pt == EMAC_RDES1_PT_TCP || pt == EMAC_RDES1_PT_UDP || pt == EMAC_RDES1_PT_ICMP;
+            if rdes1 & EMAC_RDES1_IPHE != 0 || (checked && rdes1 & EMAC_RDES1_IPCE != 0) {
                 return false;
             }
         }
@@ -324,24 +342,28 @@ impl RDes {
 /// Rx ring of descriptors and packets
 pub(crate) struct RDesRing<'a> {
     descriptors: &'a mut [RDes],
-    buffers: &'a mut [Packet<RX_BUFFER_SIZE>],
+    buffers: PacketBuffers<'a>,
     state: RxPacketStateRing<'a>,
     index: usize,
     consume_context: bool,
+    checksum_offload: bool,
 }
 
 impl<'a> RDesRing<'a> {
-    pub(crate) fn new(
+    pub(crate) fn new<const N: usize>(
         descriptors: &'a mut [RDes],
-        buffers: &'a mut [Packet<RX_BUFFER_SIZE>],
+        buffers: &'a mut [Packet<N>],
         state: RxPacketStateRing<'a>,
     ) -> Self {
         assert!(descriptors.len() > 1);
         assert!(descriptors.len() == buffers.len());
+        // DMACRXCR.RBSZ is 14 bits wide and its two LSBs are reserved.
+        assert!(N % 4 == 0 && N < 1 << 14, "invalid RX buffer size");
+        let mut buffers = PacketBuffers::new(buffers);
 
         for (i, desc) in descriptors.iter_mut().enumerate() {
             *desc = RDes::new();
-            desc.set_ready(buffers[i].0.as_mut_ptr());
+            desc.set_ready(buffers.get(i).as_mut_ptr());
         }
 
         let dma = ETH.ethernet_dma();
@@ -355,9 +377,19 @@ impl<'a> RDesRing<'a> {
             state,
             index: 0,
             consume_context: false,
+            checksum_offload: false,
         }
     }
 
+    pub(crate) fn checksum_offload(&self) -> bool {
+        self.checksum_offload
+    }
+
+    /// Drop received frames that fail the checksum verification of the MAC.
+    pub(crate) fn set_checksum_offload(&mut self, enabled: bool) {
+        self.checksum_offload = enabled;
+    }
+
     /// Get a received packet if any, or None.
     pub(crate) fn available(&mut self) -> Option<&mut [u8]> {
         // Not sure if the contents of the write buffer on the M7 can affects reads, so we are using
@@ -378,7 +410,7 @@ impl<'a> RDesRing<'a> {
             }
 
             // If packet is invalid, pop it and try again.
-            if !descriptor.valid() {
+            if !descriptor.valid(self.checksum_offload) {
                 warn!("invalid packet: {:08x}", descriptor.rdes0.get());
                 self.pop_current(false);
                 continue;
@@ -393,7 +425,7 @@ impl<'a> RDesRing<'a> {
         };
         self.consume_context = consume_context;
         self.state.capture(self.index, timestamp);
-        return Some(&mut self.buffers[self.index].0[..len]);
+        return Some(&mut self.buffers.get(self.index)[..len]);
     }
 
     fn timestamp(&self, index: usize) -> Option<(Option<PtpTimestamp>, bool)> {
@@ -444,7 +476,7 @@ impl<'a> RDesRing<'a> {
         if state {
             self.state.clear(self.index);
         }
-        rd.set_ready(self.buffers[self.index].0.as_mut_ptr());
+        rd.set_ready(self.buffers.get(self.index).as_mut_ptr());
 
         // "Preceding reads and writes cannot be moved past subsequent writes."
         fence(Ordering::Release);
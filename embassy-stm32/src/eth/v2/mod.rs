@@ -2,7 +2,9 @@ mod descriptors;
 #[cfg(feature = "ptp")]
 mod ptp;
 
-use core::sync::atomic::{Ordering, fence};
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicU8, Ordering, fence};
+use core::task::Poll;
 
 pub(crate) use descriptors::{RDes, RDesRing, TDes, TDesRing};
 use embassy_hal_internal::Peri;
@@ -21,6 +23,12 @@ use crate::pac::ETH;
 use crate::pac::ETH1 as ETH;
 use crate::rcc::WakeGuard;
 
+const WOL_MAGIC_PACKET: u8 = 1 << 0;
+const WOL_WAKEUP_FRAME: u8 = 1 << 1;
+
+static WOL_WAKER: AtomicWaker = AtomicWaker::new();
+static WOL_STATUS: AtomicU8 = AtomicU8::new(0);
+
 // The two MACs sit behind different interrupt lines.
 #[cfg(eth_v2)]
 type EthTypelevel = interrupt::typelevel::ETH;
@@ -62,13 +70,68 @@ impl interrupt::typelevel::Handler<EthTypelevel> for InterruptHandler {
         // Delay two peripheral's clock
         ch0!(dma, dmacsr).read();
         ch0!(dma, dmacsr).read();
+
+        let mac = ETH.ethernet_mac();
+        if mac.macisr().read().pmtis() {
+            // Reading MACPCSR clears the PMT status and interrupt.
+            let pcsr = mac.macpcsr().read();
+            let mut status = 0;
+            if pcsr.mgkprcvd() {
+                status |= WOL_MAGIC_PACKET;
+            }
+            if pcsr.rwkprcvd() {
+                status |= WOL_WAKEUP_FRAME;
+            }
+            WOL_STATUS.fetch_or(status, Ordering::Release);
+            WOL_WAKER.wake();
+        }
     }
 }
 
+/// Remote wakeup frame filter.
+///
+/// See the "Remote wakeup frame filter register" section of the reference manual for details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WakeupFilter {
+    /// Byte mask, bit N selects byte `offset + N` of the frame for the CRC.
+    pub byte_mask: u32,
+    /// Offset of the first byte checked by the filter, must be at least 12.
+    pub offset: u8,
+    /// CRC-16 of the selected bytes.
+    pub crc16: u16,
+    /// Only match multicast frames.
+    pub multicast: bool,
+}
+
+/// Wake-on-LAN configuration, see [`Ethernet::enter_wake_on_lan`].
+///
+/// Only available on the ETH v2 MAC, the ETH v1 MAC is not supported yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WakeOnLanConfig {
+    /// Wake up on a magic packet.
+    pub magic_packet: bool,
+    /// Wake up on frames matching one of these remote wakeup frame filters.
+    pub wakeup_frames: [Option<WakeupFilter>; 4],
+    /// Also wake up on any unicast frame for the station address, if `wakeup_frames` is enabled.
+    pub global_unicast: bool,
+}
+
+/// Reason the MAC left Wake-on-LAN power-down, see [`Ethernet::wait_for_wake`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WakeReason {
+    /// A magic packet was received.
+    MagicPacket,
+    /// A frame matching a remote wakeup frame filter was received.
+    WakeupFrame,
+}
+
 /// Ethernet driver.
 pub struct Ethernet<'d, T: Instance, P: Phy> {
     _peri: Peri<'d, T>,
-    _wake_guard: WakeGuard,
+    wake_guard: Option<WakeGuard>,
     pub(crate) link_state: LinkState,
     pub(crate) tx: TDesRing<'d>,
     pub(crate) rx: RDesRing<'d>,
@@ -115,8 +178,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
     ///
     /// See [`Ethernet::new_with_phy`] for creating an RMII ethernet
     /// river with a non-standard PHY.
-    pub fn new<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         ref_clk: Peri<'d, impl RefClkPin<T>>,
@@ -146,8 +209,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
     ///
     /// See [`Ethernet::new_mii_with_phy`] for creating an RMII ethernet
     /// river with a non-standard PHY.
-    pub fn new_mii<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_mii<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         rx_clk: Peri<'d, impl RXClkPin<T>>,
@@ -190,8 +253,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
     /// See [`Ethernet::new_rgmii_with_phy`] for creating an RGMII ethernet
     /// driver with a non-standard PHY.
     #[allow(clippy::too_many_arguments)]
-    pub fn new_rgmii<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_rgmii<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         gtx_clk: Peri<'d, impl RGMIIGTXClkPin<T>>,
@@ -225,8 +288,8 @@ impl<'d, T: Instance, SMA: sma::Instance> Ethernet<'d, T, GenericPhy<Sma<'d, SMA
 impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
     /// Create a new RMII ethernet driver using 7 pins.
     #[cfg(eth_v2)]
-    pub fn new_with_phy<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_with_phy<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         ref_clk: Peri<'d, impl RefClkPin<T>>,
@@ -256,8 +319,8 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
 
     /// Create a new MII ethernet driver using 12 pins.
     #[cfg(eth_v2)]
-    pub fn new_mii_with_phy<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_mii_with_phy<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         rx_clk: Peri<'d, impl RXClkPin<T>>,
@@ -300,8 +363,8 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
     /// Create a new RGMII ethernet driver using 13 pins.
     #[cfg(eth_v2a)]
     #[allow(clippy::too_many_arguments)]
-    pub fn new_rgmii_with_phy<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    pub fn new_rgmii_with_phy<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         gtx_clk: Peri<'d, impl RGMIIGTXClkPin<T>>,
@@ -343,8 +406,8 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
         Self::new_inner(queue, peri, irq, pins, phy, mac_addr)
     }
 
-    fn new_inner<const TX: usize, const RX: usize>(
-        queue: &'d mut PacketQueue<TX, RX>,
+    fn new_inner<const TX: usize, const RX: usize, const TX_BUF: usize, const RX_BUF: usize>(
+        queue: &'d mut PacketQueue<TX, RX, TX_BUF, RX_BUF>,
         peri: Peri<'d, T>,
         _irq: impl interrupt::typelevel::Binding<EthTypelevel, InterruptHandler> + 'd,
         pins: Pins<'d>,
@@ -405,12 +468,6 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
             w.set_ps(true);
             w.set_fes(true);
             w.set_dm(true);
-            // Enable RX IP header / payload checksum offload (COE). Requires RX
-            // store-and-forward (set below). TX insertion is requested per-frame
-            // via TDES3.CIC; together with the driver `Capabilities` this lets
-            // smoltcp skip software checksums.
-            #[cfg(eth_v2a)]
-            w.set_ipc(true);
             // TODO: Carrier sense ? ECRSFD
         });
 
@@ -495,14 +552,14 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
             w.set_rxpbl(1); // 32 ?
             #[cfg(eth_v2a)]
             w.set_rxpbl(32);
-            w.set_rbsz(RX_BUFFER_SIZE as u16);
+            w.set_rbsz(RX_BUF as u16);
         });
 
         let (tx_state, rx_state) = queue.packet_state.split();
 
         let mut this = Self {
             _peri: peri,
-            _wake_guard: T::RCC_INFO.wake_guard(),
+            wake_guard: Some(T::RCC_INFO.wake_guard()),
             tx: TDesRing::new(&mut queue.tx_desc, &mut queue.tx_buf, tx_state),
             rx: RDesRing::new(&mut queue.rx_desc, &mut queue.rx_buf, rx_state),
             _pins: pins,
@@ -513,6 +570,13 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
             ptp_clock_taken: false,
         };
 
+        // Checksum offload (COE) requires store-and-forward in both MTL queues, set above.
+        let offload = cfg!(eth_v2a);
+        this.set_checksum_offload(ChecksumOffload {
+            tx: offload,
+            rx: offload,
+        });
+
         fence(Ordering::SeqCst);
 
         let mac = T::regs().ethernet_mac();
@@ -552,6 +616,242 @@ impl<'d, T: Instance, P: Phy> Ethernet<'d, T, P> {
         this
     }
 
+    /// Put the MAC into Wake-on-LAN power-down.
+    ///
+    /// Pending transmissions are finished first, then the transmitter is stopped and the receiver
+    /// drops all frames until one of the wakeup conditions in `config` is met. The driver stops
+    /// blocking low-power Stop modes meanwhile, so the MCU can sleep until the PMT event arrives.
+    ///
+    /// The PMT event reaches the core through the ETH interrupt, which can't wake the MCU from
+    /// Stop on its own. This driver does not configure the ETH wakeup EXTI line; enable it to
+    /// wake from Stop, see the EXTI section of the reference manual for its number.
+    ///
+    /// Use [`Ethernet::wait_for_wake`] to wait for the wakeup, which also restores normal operation.
+    ///
+    /// Only available on the ETH v2 MAC.
+    pub async fn enter_wake_on_lan(&mut self, config: &WakeOnLanConfig) {
+        let mac = T::regs().ethernet_mac();
+
+        // Wait for pending transmissions, then stop the transmitter. Each transmitted frame
+        // raises the transmit interrupt, which wakes us to check again.
+        poll_fn(|cx| {
+            WAKER.register(cx.waker());
+            let txqueue = ch0!(T::regs().ethernet_mtl(), mtl_tx_qdr).read();
+            if txqueue.trcsts() == 0b01 || txqueue.txqsts() {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+        mac.maccr().modify(|w| w.set_te(false));
+
+        let wakeup_frames = config.wakeup_frames.iter().any(|f| f.is_some());
+        if wakeup_frames {
+            // The eight filter words are written in sequence after a pointer reset.
+            mac.macpcsr().modify(|w| w.set_rwkfiltrst(true));
+            while mac.macpcsr().read().rwkfiltrst() {}
+
+            let filters = config.wakeup_frames.map(|f| {
+                f.unwrap_or(WakeupFilter {
+                    byte_mask: 0,
+                    offset: 12,
+                    crc16: 0,
+                    multicast: false,
+                })
+            });
+            let mut command = 0u32;
+            let mut offsets = 0u32;
+            for (i, f) in config.wakeup_frames.iter().enumerate() {
+                if let Some(f) = f {
+                    command |= (1 | (u32::from(f.multicast) << 3)) << (i * 8);
+                }
+                offsets |= u32::from(filters[i].offset) << (i * 8);
+            }
+
+            let words = [
+                filters[0].byte_mask,
+                filters[1].byte_mask,
+                filters[2].byte_mask,
+                filters[3].byte_mask,
+                command,
+                offsets,
+                u32::from(filters[0].crc16) | (u32::from(filters[1].crc16) << 16),
+                u32::from(filters[2].crc16) | (u32::from(filters[3].crc16) << 16),
+            ];
+            for word in words {
+                mac.macrwkpfr().write(|w| w.0 = word);
+            }
+        }
+
+        WOL_STATUS.store(0, Ordering::Relaxed);
+        mac.macier().modify(|w| w.set_pmtie(true));
+        mac.macpcsr().write(|w| {
+            w.set_mgkpkten(config.magic_packet);
+            w.set_rwkpkten(wakeup_frames);
+            w.set_glblucast(config.global_unicast);
+            w.set_pwrdwn(true);
+        });
+
+        self.wake_guard = None;
+    }
+
+    /// Wait until the MAC leaves Wake-on-LAN power-down and restore normal operation.
+    pub async fn wait_for_wake(&mut self) -> WakeReason {
+        let status = poll_fn(|cx| {
+            WOL_WAKER.register(cx.waker());
+            match WOL_STATUS.swap(0, Ordering::Acquire) {
+                0 => Poll::Pending,
+                status => Poll::Ready(status),
+            }
+        })
+        .await;
+
+        self.wake_guard = Some(T::RCC_INFO.wake_guard());
+
+        // PWRDWN is cleared by hardware on wakeup.
+        let mac = T::regs().ethernet_mac();
+        mac.macier().modify(|w| w.set_pmtie(false));
+        mac.macpcsr().write(|_| {});
+        mac.maccr().modify(|w| w.set_te(true));
+
+        if status & WOL_MAGIC_PACKET != 0 {
+            WakeReason::MagicPacket
+        } else {
+            WakeReason::WakeupFrame
+        }
+    }
+
+    /// Configure the hardware checksum offload.
+    ///
+    /// RX verification is done by the MAC (MACCR.IPC), TX insertion is requested for each frame.
+    /// Offload is enabled in both directions by default on ETH v2a, and disabled on ETH v2.
+    ///
+    /// `embassy-net` reads the driver capabilities once when the stack is created, so call this
+    /// before handing the driver to the stack.
+    pub fn set_checksum_offload(&mut self, offload: ChecksumOffload) {
+        T::regs().ethernet_mac().maccr().modify(|w| w.set_ipc(offload.rx));
+        self.tx.set_checksum_offload(offload.tx);
+        self.rx.set_checksum_offload(offload.rx);
+    }
+
+    /// The current hardware checksum offload configuration.
+    pub fn checksum_offload(&self) -> ChecksumOffload {
+        ChecksumOffload {
+            tx: self.tx.checksum_offload(),
+            rx: self.rx.checksum_offload(),
+        }
+    }
+
+    /// Receive all frames, regardless of their destination address.
+    pub fn set_promiscuous(&mut self, enabled: bool) {
+        T::regs().ethernet_mac().macpfr().modify(|w| w.set_pr(enabled));
+    }
+
+    /// Receive all multicast frames.
+    ///
+    /// This is enabled by default. When disabled, only multicast frames matching a perfect filter
+    /// slot or the hash filter are received, see [`Ethernet::add_multicast_hash`].
+    pub fn set_pass_all_multicast(&mut self, enabled: bool) {
+        T::regs().ethernet_mac().macpfr().modify(|w| w.set_pm(enabled));
+    }
+
+    /// Set perfect filter address `slot` (1 to 3) to receive frames sent to `addr`, or clear it with `None`.
+    ///
+    /// Slot 0 holds the station MAC address passed to the constructor. The slots can hold unicast
+    /// as well as multicast addresses.
+    pub fn set_perfect_filter(&mut self, slot: usize, addr: Option<[u8; 6]>) {
+        let mac = T::regs().ethernet_mac();
+        let (hi, lo) = match addr {
+            Some(addr) => (
+                u16::from(addr[4]) | (u16::from(addr[5]) << 8),
+                u32::from_le_bytes([addr[0], addr[1], addr[2], addr[3]]),
+            ),
+            None => (0xFFFF, 0xFFFF_FFFF),
+        };
+
+        // Note: Writing to LR triggers synchronisation of both LR and HR into the MAC core,
+        // so the LR write must happen after the HR write.
+        macro_rules! set_slot {
+            ($hr:ident, $lr:ident) => {{
+                mac.$hr().write(|w| {
+                    w.set_addrhi(hi);
+                    w.set_ae(addr.is_some());
+                });
+                mac.$lr().write(|w| w.set_addrlo(lo));
+            }};
+        }
+
+        match slot {
+            1 => set_slot!(maca1hr, maca1lr),
+            2 => set_slot!(maca2hr, maca2lr),
+            3 => set_slot!(maca3hr, maca3lr),
+            _ => panic!("invalid perfect filter slot {}", slot),
+        }
+    }
+
+    /// Receive multicast frames sent to `addr` through the hash filter.
+    ///
+    /// The hash filter is imperfect: it also passes other multicast addresses that share the same
+    /// 6-bit hash, so the network stack must still check the destination address.
+    pub fn add_multicast_hash(&mut self, addr: [u8; 6]) {
+        let mac = T::regs().ethernet_mac();
+        let index = multicast_hash(&addr);
+        let bit = 1 << (index & 0x1F);
+        if index & 0x20 != 0 {
+            mac.macht1r().modify(|w| w.set_ht63t32(w.ht63t32() | bit));
+        } else {
+            mac.macht0r().modify(|w| w.set_ht31t0(w.ht31t0() | bit));
+        }
+        mac.macpfr().modify(|w| {
+            w.set_hmc(true);
+            w.set_hpf(true);
+        });
+    }
+
+    /// Remove all addresses from the multicast hash filter.
+    pub fn clear_multicast_hash(&mut self) {
+        let mac = T::regs().ethernet_mac();
+        mac.macpfr().modify(|w| w.set_hmc(false));
+        mac.macht0r().write(|w| w.set_ht31t0(0));
+        mac.macht1r().write(|w| w.set_ht63t32(0));
+    }
+
+    /// Only receive VLAN tagged frames with the VLAN identifier `vid`, or disable the filter with `None`.
+    ///
+    /// Tagged frames with a different identifier are dropped by the MAC.
+    pub fn set_vlan_filter(&mut self, vid: Option<u16>) {
+        let mac = T::regs().ethernet_mac();
+        mac.macvtr().modify(|w| {
+            w.set_vl(vid.unwrap_or(0) & 0x0fff);
+            // Compare the 12-bit VID only, not the priority and DEI bits.
+            w.set_etv(true);
+        });
+        mac.macpfr().modify(|w| w.set_vtfe(vid.is_some()));
+    }
+
+    /// Strip the VLAN tag from received frames before they are written to the RX buffers.
+    pub fn set_vlan_strip(&mut self, enabled: bool) {
+        // EVLS 0b11: always strip, 0b00: never strip.
+        T::regs()
+            .ethernet_mac()
+            .macvtr()
+            .modify(|w| w.set_evls(if enabled { 0b11 } else { 0b00 }));
+    }
+
+    /// Insert a VLAN tag into every transmitted frame, or stop inserting it with `None`.
+    ///
+    /// `tci` is the full tag control information: priority (PCP), DEI and the VLAN identifier.
+    pub fn set_vlan_insertion(&mut self, tci: Option<u16>) {
+        T::regs().ethernet_mac().macvir().write(|w| {
+            if let Some(tci) = tci {
+                w.set_vlt(tci);
+                // VLC 0b10: insert the tag from VLT, regardless of the descriptor.
+                w.set_vlc(0b10);
+            }
+        });
+    }
+
     /// Start the Ethernet MAC PTP clock.
     #[cfg(feature = "ptp")]
     pub fn start_ptp(&mut self, config: PtpClockConfig) -> PtpClock<T> {
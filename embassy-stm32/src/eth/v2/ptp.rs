@@ -76,6 +76,13 @@ impl ClockRate {
             nominal_addend: addend as u32,
         }
     }
+
+    /// Addend that runs the clock `ppb` parts per billion faster (or slower, if negative) than nominal.
+    pub(super) fn adjusted_addend(&self, ppb: i32) -> u32 {
+        let nominal = i64::from(self.nominal_addend);
+        let addend = nominal + (nominal * i64::from(ppb) + 500_000_000 * i64::from(ppb.signum())) / 1_000_000_000;
+        addend.clamp(1, i64::from(u32::MAX)) as u32
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -155,6 +162,15 @@ impl<T: Instance> PtpClock<T> {
         while mac.mactscr().read().tsaddreg() {}
     }
 
+    /// Tune the MAC PTP clock frequency by `ppb` parts per billion relative to nominal.
+    ///
+    /// Positive values speed the clock up, negative values slow it down. This is the frequency
+    /// correction a servo (e.g. a PTP or gPTP stack) applies on top of [`PtpClock::offset_time`] steps.
+    /// The correction is limited by the headroom between [`PtpClock::nominal_addend`] and `u32::MAX`.
+    pub fn adjust_frequency(&self, ppb: i32) {
+        self.set_addend(self.rate.adjusted_addend(ppb));
+    }
+
     fn configure(&self) {
         let mac = T::regs().ethernet_mac();
         mac.macier().modify(|w| w.set_tsie(false));
@@ -221,6 +237,15 @@ mod tests {
         assert_eq!(rate.nominal_addend, 0xd555_5555);
     }
 
+    #[test]
+    fn adjusted_addend() {
+        let rate = ClockRate::from_hclk(200_000_000, PtpSubsecondIncrement::NANOS_8);
+        assert_eq!(rate.adjusted_addend(0), 0xa000_0000);
+        assert_eq!(rate.adjusted_addend(1_000_000), 0xa000_0000 + 2_684_355);
+        assert_eq!(rate.adjusted_addend(-1_000_000), 0xa000_0000 - 2_684_355);
+        assert_eq!(rate.adjusted_addend(i32::MAX), u32::MAX);
+    }
+
     #[test]
     #[should_panic]
     fn addend_rejects_impossible_rate() {
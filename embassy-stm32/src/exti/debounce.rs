@@ -0,0 +1,138 @@
+//! Debounced EXTI inputs
+//!
+//! Buttons and mechanical switches bounce for a few milliseconds when they change state, so
+//! every edge seen by EXTI is not a real transition. The helpers in this module combine EXTI
+//! edges with [`embassy_time`] timers: a level is only accepted once the pin did not change
+//! for the whole debounce interval.
+
+use embassy_futures::select::{Either, select};
+use embassy_time::{Duration, Timer};
+
+use super::{ExtiInput, ExtiInputFuture, TriggerEdge};
+use crate::gpio::Level;
+use crate::mode::Async;
+
+impl<'d> ExtiInput<'d, Async> {
+    /// Asynchronously wait until the pin level has been stable for `debounce`, returning that level.
+    ///
+    /// The timer restarts on every edge, so this returns `debounce` after the last edge. If the
+    /// pin is quiet, it returns after `debounce`.
+    pub async fn wait_for_stable_level(&mut self, debounce: Duration) -> Level {
+        loop {
+            let level = self.get_level();
+            match select(self.wait_for_any_edge(), Timer::after(debounce)).await {
+                Either::First(()) => {}
+                // An edge between reading the level and arming EXTI is caught here.
+                Either::Second(()) if self.get_level() == level => return level,
+                Either::Second(()) => {}
+            }
+        }
+    }
+}
+
+/// Debounced EXTI input.
+///
+/// Keeps track of the last stable level of an [`ExtiInput`], and only reports a transition
+/// once the new level held for the debounce interval.
+pub struct DebouncedInput<'d> {
+    input: ExtiInput<'d, Async>,
+    debounce: Duration,
+    level: Level,
+}
+
+impl<'d> DebouncedInput<'d> {
+    /// Create a debounced input.
+    ///
+    /// The current level of the pin is taken as the initial stable level, without waiting for
+    /// it to settle. Use [`Self::new_settled()`] if the pin may be bouncing at this point.
+    pub fn new(input: ExtiInput<'d, Async>, debounce: Duration) -> Self {
+        let level = input.get_level();
+        Self { input, debounce, level }
+    }
+
+    /// Create a debounced input, waiting for the pin to settle first.
+    pub async fn new_settled(mut input: ExtiInput<'d, Async>, debounce: Duration) -> Self {
+        let level = input.wait_for_stable_level(debounce).await;
+        Self { input, debounce, level }
+    }
+
+    /// Get the last stable level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Get whether the last stable level is high.
+    pub fn is_high(&self) -> bool {
+        self.level == Level::High
+    }
+
+    /// Get whether the last stable level is low.
+    pub fn is_low(&self) -> bool {
+        self.level == Level::Low
+    }
+
+    /// Get the debounce interval.
+    pub fn debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Set the debounce interval.
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+
+    /// Asynchronously wait until the stable level changes, returning the new level.
+    pub async fn wait_for_change(&mut self) -> Level {
+        loop {
+            // Arm EXTI before sampling, so an edge right after the sample is not lost. The pin may
+            // also have changed while nobody was waiting, in which case there is no edge to wait
+            // for anymore.
+            let edge = ExtiInputFuture::new(&self.input.pin, TriggerEdge::Any, true);
+            if self.input.get_level() == self.level {
+                edge.await;
+            } else {
+                drop(edge);
+            }
+            let level = self.input.wait_for_stable_level(self.debounce).await;
+            if level != self.level {
+                self.level = level;
+                return level;
+            }
+        }
+    }
+
+    /// Asynchronously wait until the stable level is high.
+    ///
+    /// This returns immediately if the last stable level is high.
+    pub async fn wait_for_high(&mut self) {
+        while self.level != Level::High {
+            self.wait_for_change().await;
+        }
+    }
+
+    /// Asynchronously wait until the stable level is low.
+    ///
+    /// This returns immediately if the last stable level is low.
+    pub async fn wait_for_low(&mut self) {
+        while self.level != Level::Low {
+            self.wait_for_change().await;
+        }
+    }
+
+    /// Asynchronously wait for a debounced transition from low to high.
+    pub async fn wait_for_rising_edge(&mut self) {
+        self.wait_for_low().await;
+        self.wait_for_high().await;
+    }
+
+    /// Asynchronously wait for a debounced transition from high to low.
+    pub async fn wait_for_falling_edge(&mut self) {
+        self.wait_for_high().await;
+        self.wait_for_low().await;
+    }
+
+    /// Consume the debounced input, returning the underlying [`ExtiInput`].
+    pub fn into_inner(self) -> ExtiInput<'d, Async> {
+        self.input
+    }
+}
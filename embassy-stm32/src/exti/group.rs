@@ -0,0 +1,80 @@
+use core::future::poll_fn;
+use core::task::Poll;
+
+use futures_util::FutureExt;
+
+use super::{ExtiInput, ExtiInputFuture, TriggerEdge};
+use crate::mode::Async;
+
+/// Signal edge seen by an [`ExtiGroup`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// Low to high transition.
+    Rising,
+    /// High to low transition.
+    Falling,
+}
+
+/// Group of EXTI inputs awaited together.
+///
+/// Useful for keypads or several sensors with interrupt outputs, where a single task handles
+/// all of them.
+pub struct ExtiGroup<'d, const N: usize> {
+    inputs: [ExtiInput<'d, Async>; N],
+    trigger_edge: TriggerEdge,
+}
+
+impl<'d, const N: usize> ExtiGroup<'d, N> {
+    /// Create a group from `inputs`, waiting for `trigger_edge` on all of them.
+    pub fn new(inputs: [ExtiInput<'d, Async>; N], trigger_edge: TriggerEdge) -> Self {
+        Self { inputs, trigger_edge }
+    }
+
+    /// Asynchronously wait until one or more of the inputs see an edge.
+    ///
+    /// Returns the edge seen on each input, or `None` for the inputs that didn't fire. All
+    /// the inputs that fired before this returns are reported, including simultaneous edges.
+    /// Like the other EXTI waits, only edges occurring while this is awaited are seen.
+    ///
+    /// With [`TriggerEdge::Any`], the edge is derived from the level of the input after the
+    /// event, so a very short pulse may be reported with the wrong edge.
+    pub async fn wait(&mut self) -> [Option<Edge>; N] {
+        let mut futures: [ExtiInputFuture<'_>; N] =
+            core::array::from_fn(|i| ExtiInputFuture::new(&self.inputs[i].pin, self.trigger_edge, true));
+
+        // Poll every line on each wakeup, so that lines firing together are all seen before
+        // the futures are dropped and the lines masked again.
+        let fired = poll_fn(|cx| {
+            let mut fired = [false; N];
+            for (future, fired) in futures.iter_mut().zip(fired.iter_mut()) {
+                *fired = future.poll_unpin(cx).is_ready();
+            }
+            match fired.contains(&true) {
+                true => Poll::Ready(fired),
+                false => Poll::Pending,
+            }
+        })
+        .await;
+        drop(futures);
+
+        core::array::from_fn(|i| {
+            fired[i].then(|| match self.trigger_edge {
+                TriggerEdge::Rising => Edge::Rising,
+                TriggerEdge::Falling => Edge::Falling,
+                TriggerEdge::Any if self.inputs[i].is_high() => Edge::Rising,
+                TriggerEdge::Any => Edge::Falling,
+            })
+        })
+    }
+
+    /// Get an input of the group.
+    pub fn input(&self, index: usize) -> &ExtiInput<'d, Async> {
+        &self.inputs[index]
+    }
+
+    /// Consume the group, returning the inputs.
+    pub fn into_inputs(self) -> [ExtiInput<'d, Async>; N] {
+        self.inputs
+    }
+}
@@ -0,0 +1,197 @@
+//! Async events on internal EXTI lines
+//!
+//! Besides the 16 GPIO lines, EXTI has configurable lines connected to internal peripherals,
+//! like the PVD output, RTC alarms, USB wakeup or the comparator outputs. Their numbers are chip
+//! specific, see the EXTI chapter of the reference manual. The interrupt vector of each line is
+//! taken from the chip metadata, so [`Line`] is only implemented for lines that have one.
+//!
+//! Only configurable lines `16..32` are supported. Direct lines have no pending bit in EXTI,
+//! their events are handled by the peripheral they belong to.
+
+use core::future::poll_fn;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::low_level::{clear_exti_pending_mask, read_pending};
+use super::{BitIter, TriggerEdge, cpu_regs};
+use crate::interrupt::typelevel::{Binding, Handler, Interrupt as InterruptType};
+use crate::pac::EXTI;
+
+const FIRST_LINE: u8 = 16;
+const LINE_COUNT: usize = 16;
+static LINE_WAKERS: [AtomicWaker; LINE_COUNT] = [const { AtomicWaker::new() }; LINE_COUNT];
+/// Lines owned by an [`ExtiEvent`]. Other pending lines are left to their drivers.
+static LINES_IN_USE: AtomicU32 = AtomicU32::new(0);
+
+/// Interrupt handler for internal EXTI lines.
+///
+/// Bind it to the interrupt vector of the line, e.g. `PVD`, `RTC_Alarm`, `OTG_FS_WKUP` or `COMP`,
+/// depending on the chip. This is [`Line::IRQ`].
+pub struct LineInterruptHandler<T: LineInterrupt> {
+    _marker: PhantomData<T>,
+}
+
+impl<T: LineInterrupt> Handler<T> for LineInterruptHandler<T> {
+    unsafe fn on_interrupt() {
+        let bits = read_pending() & LINES_IN_USE.load(Ordering::Relaxed);
+
+        // Mask the lines that fired, the waiting tasks see the mask to know they are done.
+        cpu_regs().imr(0).modify(|w| w.0 &= !bits);
+
+        for line in BitIter(bits) {
+            LINE_WAKERS[line as usize - FIRST_LINE as usize].wake();
+        }
+
+        clear_exti_pending_mask(bits);
+    }
+}
+
+/// Error returned when creating an [`ExtiEvent`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LineError {
+    /// The line is already used by another [`ExtiEvent`].
+    InUse,
+}
+
+/// Async event on an internal EXTI line.
+///
+/// The line is released when the `ExtiEvent` is dropped.
+pub struct ExtiEvent {
+    line: u8,
+}
+
+impl ExtiEvent {
+    /// Create an event for configurable EXTI line `L`, triggering on `trigger_edge`.
+    ///
+    /// The Binding must bind the interrupt vector of the line to [`LineInterruptHandler`]. The
+    /// peripheral driving the line has to be configured separately.
+    pub fn new<L: Line>(
+        _line: L,
+        trigger_edge: TriggerEdge,
+        _irq: impl Binding<L::IRQ, LineInterruptHandler<L::IRQ>>,
+    ) -> Result<Self, LineError> {
+        let line = L::NUMBER;
+        let bit = 1 << line;
+        if LINES_IN_USE.fetch_or(bit, Ordering::Relaxed) & bit != 0 {
+            return Err(LineError::InUse);
+        }
+
+        let (rising, falling) = match trigger_edge {
+            TriggerEdge::Falling => (false, true),
+            TriggerEdge::Rising => (true, false),
+            TriggerEdge::Any => (true, true),
+        };
+
+        critical_section::with(|_| {
+            let n = line as usize;
+            cpu_regs().imr(0).modify(|w| w.set_line(n, false));
+            EXTI.rtsr(0).modify(|w| w.set_line(n, rising));
+            EXTI.ftsr(0).modify(|w| w.set_line(n, falling));
+            clear_exti_pending_mask(bit);
+        });
+
+        L::IRQ::unpend();
+        unsafe { L::IRQ::enable() };
+
+        Ok(Self { line })
+    }
+
+    /// EXTI line number of the event.
+    pub fn line(&self) -> u8 {
+        self.line
+    }
+
+    /// Asynchronously wait for the next event on the line.
+    ///
+    /// Only events occurring after this is called are seen.
+    pub async fn wait(&mut self) {
+        let n = self.line as usize;
+        critical_section::with(|_| {
+            clear_exti_pending_mask(1 << n);
+            cpu_regs().imr(0).modify(|w| w.set_line(n, true));
+        });
+
+        // Mask the line again if the future is dropped before the event.
+        let _guard = MaskOnDrop(n);
+
+        poll_fn(|cx| {
+            LINE_WAKERS[n - FIRST_LINE as usize].register(cx.waker());
+            if cpu_regs().imr(0).read().line(n) {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await
+    }
+}
+
+impl Drop for ExtiEvent {
+    fn drop(&mut self) {
+        let n = self.line as usize;
+        critical_section::with(|_| {
+            cpu_regs().imr(0).modify(|w| w.set_line(n, false));
+            EXTI.rtsr(0).modify(|w| w.set_line(n, false));
+            EXTI.ftsr(0).modify(|w| w.set_line(n, false));
+        });
+        LINES_IN_USE.fetch_and(!(1 << n), Ordering::Relaxed);
+    }
+}
+
+struct MaskOnDrop(usize);
+
+impl Drop for MaskOnDrop {
+    fn drop(&mut self) {
+        let n = self.0;
+        critical_section::with(|_| cpu_regs().imr(0).modify(|w| w.set_line(n, false)));
+    }
+}
+
+pub(crate) trait SealedLine {
+    const NUMBER: u8;
+}
+
+/// Configurable EXTI line with a known interrupt vector.
+#[allow(private_bounds)]
+pub trait Line: SealedLine {
+    /// Interrupt vector of the line, which may be shared with other lines.
+    type IRQ: LineInterrupt;
+}
+
+pub(crate) trait SealedLineInterrupt {}
+
+/// Interrupt vector of one or more configurable EXTI lines.
+#[allow(private_bounds)]
+pub trait LineInterrupt: SealedLineInterrupt + InterruptType {}
+
+macro_rules! impl_line {
+    ($name:ident, $number:expr) => {
+        #[doc = concat!("EXTI line ", stringify!($number), ".")]
+        pub struct $name;
+
+        impl SealedLine for $name {
+            const NUMBER: u8 = $number;
+        }
+    };
+}
+
+impl_line!(Line16, 16);
+impl_line!(Line17, 17);
+impl_line!(Line18, 18);
+impl_line!(Line19, 19);
+impl_line!(Line20, 20);
+impl_line!(Line21, 21);
+impl_line!(Line22, 22);
+impl_line!(Line23, 23);
+impl_line!(Line24, 24);
+impl_line!(Line25, 25);
+impl_line!(Line26, 26);
+impl_line!(Line27, 27);
+impl_line!(Line28, 28);
+impl_line!(Line29, 29);
+impl_line!(Line30, 30);
+impl_line!(Line31, 31);
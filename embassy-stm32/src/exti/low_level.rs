@@ -14,6 +14,7 @@ use crate::pac::exti::regs::Lines;
 /// - `Falling`: Only falling edges (high to low transitions) trigger interrupts
 /// - `Rising`: Only rising edges (low to high transitions) trigger interrupts
 /// - `Any`: Both rising and falling edges trigger interrupts
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TriggerEdge {
     /// Detect only falling edges (high to low transitions)
     Falling,
@@ -22,6 +22,18 @@ mod low_level;
 pub use low_level::{InterruptState, TriggerEdge};
 
 pub mod blocking;
+mod group;
+pub use group::{Edge, ExtiGroup};
+mod line;
+pub(crate) use line::SealedLineInterrupt;
+pub use line::{
+    ExtiEvent, Line, Line16, Line17, Line18, Line19, Line20, Line21, Line22, Line23, Line24, Line25, Line26, Line27,
+    Line28, Line29, Line30, Line31, LineError, LineInterrupt, LineInterruptHandler,
+};
+#[cfg(feature = "time")]
+mod debounce;
+#[cfg(feature = "time")]
+pub use debounce::DebouncedInput;
 
 const EXTI_COUNT: usize = 16;
 static EXTI_WAKERS: [AtomicWaker; EXTI_COUNT] = [const { AtomicWaker::new() }; EXTI_COUNT];
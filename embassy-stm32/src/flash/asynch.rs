@@ -6,8 +6,8 @@ use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::mutex::Mutex;
 
 use super::{
-    Async, Error, FLASH_BASE, FLASH_SIZE, Flash, FlashLayout, WRITE_SIZE, blocking_read, ensure_sector_aligned, family,
-    get_flash_regions, get_sector,
+    Async, Error, FLASH_BASE, FLASH_SIZE, Flash, FlashBank, FlashLayout, WRITE_SIZE, blocking_read,
+    ensure_sector_aligned, family, get_flash_regions, get_sector,
 };
 use crate::interrupt::InterruptExt;
 use crate::peripherals::FLASH;
@@ -131,6 +131,9 @@ pub(super) async unsafe fn erase_sectored(base: u32, from: u32, to: u32) -> Resu
     let mut address = start_address;
     while address < end_address {
         let sector = get_sector(address, regions)?;
+        if sector.bank == FlashBank::Otp {
+            return Err(Error::Protected);
+        }
         trace!("Erasing sector: {:?}", sector);
 
         family::clear_all_err();
@@ -146,6 +149,18 @@ pub(super) async unsafe fn erase_sectored(base: u32, from: u32, to: u32) -> Resu
     Ok(())
 }
 
+#[cfg(flash_otp)]
+impl crate::_generated::flash_regions::OTPRegion<'_, Async> {
+    /// Async program of one-time-programmable bytes.
+    ///
+    /// See [`OTPRegion::blocking_program`](crate::_generated::flash_regions::OTPRegion::blocking_program).
+    pub async fn program(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        let _guard = REGION_ACCESS.lock().await;
+        super::otp::ensure_blank(self.0, offset, bytes.len() as u32)?;
+        unsafe { write_chunked(self.0.base(), self.0.size, offset, bytes).await }
+    }
+}
+
 foreach_flash_region! {
     ($type_name:ident, $write_size:literal, $erase_size:literal) => {
         impl crate::_generated::flash_regions::$type_name<'_, Async> {
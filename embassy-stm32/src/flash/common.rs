@@ -155,6 +155,9 @@ pub(super) unsafe fn blocking_erase(
     let mut address = start_address;
     while address < end_address {
         let sector = get_sector(address, regions)?;
+        if sector.bank == FlashBank::Otp {
+            return Err(Error::Protected);
+        }
         trace!("Erasing sector: {:?}", sector);
         erase_sector(&sector)?;
         address += sector.size;
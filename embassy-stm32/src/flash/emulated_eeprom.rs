@@ -0,0 +1,851 @@
+//! Key-value storage emulating an EEPROM on two flash pages.
+//!
+//! Values are appended to the active page as records protected by a CRC, so updating a value
+//! doesn't erase anything. When the active page is full, the latest value of each key is copied
+//! to the other page, which then becomes the active one. Interrupted writes and page copies are
+//! detected and ignored when the storage is mounted again.
+use embedded_storage::nor_flash::{ErrorType, NorFlash, ReadNorFlash};
+use embedded_storage_async::nor_flash::{NorFlash as AsyncNorFlash, ReadNorFlash as AsyncReadNorFlash};
+
+// Page header: sequence number, then magic, so that a valid magic implies a valid sequence number.
+const PAGE_MAGIC: u32 = 0x4545_5045;
+// Record header: key, length, CRC of key, length and value. Key and length are stored inverted
+// when the flash erases to 0x00, so that `0xFFFF` always reads back from erased flash.
+const RECORD_HEADER_SIZE: u32 = 8;
+const ERASED_KEY: u16 = 0xFFFF;
+const ERASED_LEN: u16 = 0xFFFF;
+const TOMBSTONE: u16 = 0xFFFE;
+// Size of the buffer for copying records, a multiple of every write size.
+const CHUNK_SIZE: usize = 32;
+
+/// Emulated EEPROM error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EmulatedEepromError<E> {
+    /// Flash error.
+    Flash(E),
+    /// The key is reserved (`0xFFFF`).
+    InvalidKey,
+    /// The value doesn't fit in a page.
+    ValueTooLarge,
+    /// The buffer is smaller than the value.
+    BufferTooSmall,
+    /// The live values don't fit in a page anymore.
+    Full,
+}
+
+/// Key-value storage emulating an EEPROM on two flash pages.
+///
+/// The storage uses the `2 * page_size` bytes at `offset` in `flash`, `page_size` being a multiple
+/// of the erase size. Keys are `u16`, except `0xFFFF`. Each write costs the size of the value
+/// plus 8 bytes, rounded up to the write size, until the page is full.
+///
+/// The storage is mounted on first use.
+pub struct EmulatedEeprom<F> {
+    flash: F,
+    pages: Pages,
+}
+
+impl<F> EmulatedEeprom<F> {
+    /// Create an emulated EEPROM on the two pages of `page_size` bytes at `offset` in `flash`.
+    ///
+    /// `flash` is the internal flash, whose erase value depends on the chip.
+    pub const fn new(flash: F, offset: u32, page_size: u32) -> Self {
+        Self::new_with_erase_value(flash, offset, page_size, super::FLASH_REGIONS[0].erase_value)
+    }
+
+    /// Create an emulated EEPROM on the two pages of `page_size` bytes at `offset` in `flash`,
+    /// which reads `erase_value` once erased.
+    pub const fn new_with_erase_value(flash: F, offset: u32, page_size: u32, erase_value: u8) -> Self {
+        Self {
+            flash,
+            pages: Pages {
+                offset,
+                page_size,
+                erased: erase_value,
+                state: None,
+            },
+        }
+    }
+
+    /// Release the flash.
+    pub fn release(self) -> F {
+        self.flash
+    }
+}
+
+impl<F: NorFlash> EmulatedEeprom<F> {
+    /// Read the value of `key` into `buf`.
+    ///
+    /// Returns the length of the value, or [`None`] if there is no value for `key`.
+    pub fn blocking_read(&mut self, key: u16, buf: &mut [u8]) -> Result<Option<usize>, EmulatedEepromError<F::Error>> {
+        embassy_futures::block_on(self.pages.read(&mut BlockingFlash(&mut self.flash), key, buf))
+    }
+
+    /// Write the value of `key`.
+    ///
+    /// Nothing is written when the value doesn't change.
+    pub fn blocking_write(&mut self, key: u16, value: &[u8]) -> Result<(), EmulatedEepromError<F::Error>> {
+        embassy_futures::block_on(self.pages.write(&mut BlockingFlash(&mut self.flash), key, value))
+    }
+
+    /// Remove the value of `key`.
+    pub fn blocking_remove(&mut self, key: u16) -> Result<(), EmulatedEepromError<F::Error>> {
+        embassy_futures::block_on(self.pages.remove(&mut BlockingFlash(&mut self.flash), key))
+    }
+
+    /// Erase all values.
+    pub fn blocking_format(&mut self) -> Result<(), EmulatedEepromError<F::Error>> {
+        embassy_futures::block_on(self.pages.format(&mut BlockingFlash(&mut self.flash)))
+    }
+}
+
+impl<F: AsyncNorFlash> EmulatedEeprom<F> {
+    /// Read the value of `key` into `buf`.
+    ///
+    /// Returns the length of the value, or [`None`] if there is no value for `key`.
+    pub async fn read(&mut self, key: u16, buf: &mut [u8]) -> Result<Option<usize>, EmulatedEepromError<F::Error>> {
+        self.pages.read(&mut self.flash, key, buf).await
+    }
+
+    /// Write the value of `key`.
+    ///
+    /// Nothing is written when the value doesn't change.
+    pub async fn write(&mut self, key: u16, value: &[u8]) -> Result<(), EmulatedEepromError<F::Error>> {
+        self.pages.write(&mut self.flash, key, value).await
+    }
+
+    /// Remove the value of `key`.
+    pub async fn remove(&mut self, key: u16) -> Result<(), EmulatedEepromError<F::Error>> {
+        self.pages.remove(&mut self.flash, key).await
+    }
+
+    /// Erase all values.
+    pub async fn format(&mut self) -> Result<(), EmulatedEepromError<F::Error>> {
+        self.pages.format(&mut self.flash).await
+    }
+}
+
+/// Blocking flash used through the async implementation, whose futures then complete on the
+/// first poll.
+struct BlockingFlash<'a, F>(&'a mut F);
+
+impl<F: ErrorType> ErrorType for BlockingFlash<'_, F> {
+    type Error = F::Error;
+}
+
+impl<F: ReadNorFlash> AsyncReadNorFlash for BlockingFlash<'_, F> {
+    const READ_SIZE: usize = F::READ_SIZE;
+
+    async fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(offset, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+}
+
+impl<F: NorFlash> AsyncNorFlash for BlockingFlash<'_, F> {
+    const WRITE_SIZE: usize = F::WRITE_SIZE;
+    const ERASE_SIZE: usize = F::ERASE_SIZE;
+
+    async fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(offset, bytes)
+    }
+
+    async fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        self.0.erase(from, to)
+    }
+}
+
+#[derive(Copy, Clone)]
+struct State {
+    active: u32,
+    seq: u32,
+    // End of the valid records.
+    end: u32,
+    // Offset of the next record, the page size after an interrupted write.
+    write_offset: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Record {
+    key: u16,
+    len: u16,
+    size: u32,
+}
+
+enum Entry {
+    Erased,
+    Corrupt,
+    Valid(Record),
+}
+
+struct Pages {
+    offset: u32,
+    page_size: u32,
+    erased: u8,
+    state: Option<State>,
+}
+
+impl Pages {
+    fn base(&self, page: u32) -> u32 {
+        self.offset + page * self.page_size
+    }
+
+    /// Convert a key or length to or from its stored form.
+    fn code(&self, value: u16) -> u16 {
+        value ^ !u16::from_le_bytes([self.erased; 2])
+    }
+
+    async fn mount<S: AsyncNorFlash>(&mut self, flash: &mut S) -> Result<State, EmulatedEepromError<S::Error>> {
+        if let Some(state) = self.state {
+            return Ok(state);
+        }
+
+        assert!(S::READ_SIZE == 1);
+        assert!(S::WRITE_SIZE <= CHUNK_SIZE && CHUNK_SIZE.is_multiple_of(S::WRITE_SIZE));
+        assert!(
+            self.offset.is_multiple_of(S::ERASE_SIZE as u32) && self.page_size.is_multiple_of(S::ERASE_SIZE as u32)
+        );
+
+        let (active, seq) = match (self.page_seq(flash, 0).await?, self.page_seq(flash, 1).await?) {
+            (Some(a), Some(b)) if (b.wrapping_sub(a) as i32) > 0 => (1, b),
+            (Some(a), _) => (0, a),
+            (None, Some(b)) => (1, b),
+            (None, None) => {
+                self.format_page(flash, 0, 0).await?;
+                (0, 0)
+            }
+        };
+
+        let mut offset = header_slot::<S>();
+        let (end, write_offset) = loop {
+            match self.entry(flash, active, offset, true).await? {
+                Entry::Erased => break (offset, offset),
+                // Interrupted write, the next write copies the valid records to the other page.
+                Entry::Corrupt => break (offset, self.page_size),
+                Entry::Valid(record) => offset += record.size,
+            }
+        };
+
+        let state = State {
+            active,
+            seq,
+            end,
+            write_offset,
+        };
+        self.state = Some(state);
+        Ok(state)
+    }
+
+    async fn read<S: AsyncNorFlash>(
+        &mut self,
+        flash: &mut S,
+        key: u16,
+        buf: &mut [u8],
+    ) -> Result<Option<usize>, EmulatedEepromError<S::Error>> {
+        let state = self.mount(flash).await?;
+        let Some((offset, record)) = self.find(flash, &state, key).await? else {
+            return Ok(None);
+        };
+        if record.len == TOMBSTONE {
+            return Ok(None);
+        }
+
+        let len = record.len as usize;
+        let buf = buf.get_mut(..len).ok_or(EmulatedEepromError::BufferTooSmall)?;
+        flash
+            .read(self.base(state.active) + offset + RECORD_HEADER_SIZE, buf)
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+        Ok(Some(len))
+    }
+
+    async fn write<S: AsyncNorFlash>(
+        &mut self,
+        flash: &mut S,
+        key: u16,
+        value: &[u8],
+    ) -> Result<(), EmulatedEepromError<S::Error>> {
+        if key == ERASED_KEY {
+            return Err(EmulatedEepromError::InvalidKey);
+        }
+        let size = record_size::<S>(value.len() as u32);
+        if value.len() >= TOMBSTONE as usize || size > self.page_size - header_slot::<S>() {
+            return Err(EmulatedEepromError::ValueTooLarge);
+        }
+
+        let mut state = self.mount(flash).await?;
+        if let Some((offset, record)) = self.find(flash, &state, key).await? {
+            let data = self.base(state.active) + offset + RECORD_HEADER_SIZE;
+            if record.len as usize == value.len() && equals(flash, data, value).await? {
+                return Ok(());
+            }
+        }
+
+        if state.write_offset + size > self.page_size {
+            return self.compact(flash, key, Some(value)).await;
+        }
+
+        let address = self.base(state.active) + state.write_offset;
+        self.write_record(flash, address, key, value.len() as u16, value)
+            .await?;
+        state.end = state.write_offset + size;
+        state.write_offset = state.end;
+        self.state = Some(state);
+        Ok(())
+    }
+
+    async fn remove<S: AsyncNorFlash>(&mut self, flash: &mut S, key: u16) -> Result<(), EmulatedEepromError<S::Error>> {
+        if key == ERASED_KEY {
+            return Err(EmulatedEepromError::InvalidKey);
+        }
+
+        let state = self.mount(flash).await?;
+        match self.find(flash, &state, key).await? {
+            Some((_, record)) if record.len != TOMBSTONE => {}
+            _ => return Ok(()),
+        }
+
+        if state.write_offset + record_size::<S>(0) > self.page_size {
+            // The copy leaves the key out, no need for a tombstone.
+            return self.compact(flash, key, None).await;
+        }
+
+        let address = self.base(state.active) + state.write_offset;
+        self.write_record(flash, address, key, TOMBSTONE, &[]).await?;
+        let end = state.write_offset + record_size::<S>(0);
+        self.state = Some(State {
+            end,
+            write_offset: end,
+            ..state
+        });
+        Ok(())
+    }
+
+    async fn format<S: AsyncNorFlash>(&mut self, flash: &mut S) -> Result<(), EmulatedEepromError<S::Error>> {
+        self.state = None;
+        flash
+            .erase(self.base(1), self.base(2))
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+        self.format_page(flash, 0, 0).await
+    }
+
+    /// Sequence number of a page, or [`None`] if the page has no valid header.
+    async fn page_seq<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        page: u32,
+    ) -> Result<Option<u32>, EmulatedEepromError<S::Error>> {
+        let mut header = [0; 8];
+        flash
+            .read(self.base(page), &mut header)
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+        let seq = u32::from_le_bytes(unwrap!(header[..4].try_into()));
+        let magic = u32::from_le_bytes(unwrap!(header[4..].try_into()));
+        Ok((magic == PAGE_MAGIC).then_some(seq))
+    }
+
+    async fn format_page<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        page: u32,
+        seq: u32,
+    ) -> Result<(), EmulatedEepromError<S::Error>> {
+        let base = self.base(page);
+        flash
+            .erase(base, base + self.page_size)
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+        self.write_page_header(flash, page, seq).await
+    }
+
+    async fn write_page_header<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        page: u32,
+        seq: u32,
+    ) -> Result<(), EmulatedEepromError<S::Error>> {
+        let mut buf = [self.erased; CHUNK_SIZE];
+        buf[..4].copy_from_slice(&seq.to_le_bytes());
+        buf[4..8].copy_from_slice(&PAGE_MAGIC.to_le_bytes());
+        flash
+            .write(self.base(page), &buf[..header_slot::<S>() as usize])
+            .await
+            .map_err(EmulatedEepromError::Flash)
+    }
+
+    /// Read the record at `offset` of `page`, checking its CRC if `verify` is set.
+    async fn entry<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        page: u32,
+        offset: u32,
+        verify: bool,
+    ) -> Result<Entry, EmulatedEepromError<S::Error>> {
+        if offset + RECORD_HEADER_SIZE > self.page_size {
+            return Ok(Entry::Erased);
+        }
+
+        let base = self.base(page) + offset;
+        let mut header = [0; RECORD_HEADER_SIZE as usize];
+        flash
+            .read(base, &mut header)
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+        if header.iter().all(|&b| b == self.erased) {
+            return Ok(Entry::Erased);
+        }
+
+        let key = self.code(u16::from_le_bytes([header[0], header[1]]));
+        let len = self.code(u16::from_le_bytes([header[2], header[3]]));
+        let crc = u32::from_le_bytes(unwrap!(header[4..].try_into()));
+        if key == ERASED_KEY || len == ERASED_LEN {
+            return Ok(Entry::Corrupt);
+        }
+
+        let data_len = if len == TOMBSTONE { 0 } else { len as u32 };
+        let size = record_size::<S>(data_len);
+        if offset + size > self.page_size {
+            return Ok(Entry::Corrupt);
+        }
+
+        if verify {
+            let mut digest = Crc32::new();
+            digest.update(&header[..4]);
+            let mut buf = [0; CHUNK_SIZE];
+            let mut pos = 0;
+            while pos < data_len {
+                let n = (data_len - pos).min(CHUNK_SIZE as u32);
+                let chunk = &mut buf[..n as usize];
+                flash
+                    .read(base + RECORD_HEADER_SIZE + pos, chunk)
+                    .await
+                    .map_err(EmulatedEepromError::Flash)?;
+                digest.update(chunk);
+                pos += n;
+            }
+            if digest.finish() != crc {
+                return Ok(Entry::Corrupt);
+            }
+        }
+
+        Ok(Entry::Valid(Record { key, len, size }))
+    }
+
+    /// Find the latest record of `key` in the active page.
+    async fn find<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        state: &State,
+        key: u16,
+    ) -> Result<Option<(u32, Record)>, EmulatedEepromError<S::Error>> {
+        self.find_from(flash, state, header_slot::<S>(), key).await
+    }
+
+    async fn find_from<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        state: &State,
+        mut offset: u32,
+        key: u16,
+    ) -> Result<Option<(u32, Record)>, EmulatedEepromError<S::Error>> {
+        let mut found = None;
+        while offset < state.end {
+            let Entry::Valid(record) = self.entry(flash, state.active, offset, false).await? else {
+                break;
+            };
+            if record.key == key {
+                found = Some((offset, record));
+            }
+            offset += record.size;
+        }
+        Ok(found)
+    }
+
+    async fn write_record<S: AsyncNorFlash>(
+        &self,
+        flash: &mut S,
+        mut address: u32,
+        key: u16,
+        len: u16,
+        value: &[u8],
+    ) -> Result<(), EmulatedEepromError<S::Error>> {
+        let mut header = [0; RECORD_HEADER_SIZE as usize];
+        header[..2].copy_from_slice(&self.code(key).to_le_bytes());
+        header[2..4].copy_from_slice(&self.code(len).to_le_bytes());
+        let mut digest = Crc32::new();
+        digest.update(&header[..4]);
+        digest.update(value);
+        header[4..].copy_from_slice(&digest.finish().to_le_bytes());
+
+        let mut buf = [self.erased; CHUNK_SIZE];
+        let mut filled = 0;
+        for &b in header.iter().chain(value) {
+            buf[filled] = b;
+            filled += 1;
+            if filled == CHUNK_SIZE {
+                flash.write(address, &buf).await.map_err(EmulatedEepromError::Flash)?;
+                address += CHUNK_SIZE as u32;
+                buf = [self.erased; CHUNK_SIZE];
+                filled = 0;
+            }
+        }
+        if filled > 0 {
+            let n = filled.next_multiple_of(S::WRITE_SIZE);
+            flash
+                .write(address, &buf[..n])
+                .await
+                .map_err(EmulatedEepromError::Flash)?;
+        }
+        Ok(())
+    }
+
+    /// Copy the latest value of every key but `key` to the other page, followed by `value` for
+    /// `key` if any, and make it the active page.
+    async fn compact<S: AsyncNorFlash>(
+        &mut self,
+        flash: &mut S,
+        key: u16,
+        value: Option<&[u8]>,
+    ) -> Result<(), EmulatedEepromError<S::Error>> {
+        let state = self.mount(flash).await?;
+        let src = self.base(state.active);
+        let dst_page = 1 - state.active;
+        let dst = self.base(dst_page);
+        let seq = state.seq.wrapping_add(1);
+
+        flash
+            .erase(dst, dst + self.page_size)
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+
+        let mut offset = header_slot::<S>();
+        let mut dst_offset = offset;
+        while offset < state.end {
+            let Entry::Valid(record) = self.entry(flash, state.active, offset, false).await? else {
+                break;
+            };
+            let next = offset + record.size;
+
+            let live = record.key != key
+                && record.len != TOMBSTONE
+                && self.find_from(flash, &state, next, record.key).await?.is_none();
+            if live {
+                let mut buf = [0; CHUNK_SIZE];
+                let mut pos = 0;
+                while pos < record.size {
+                    let n = (record.size - pos).min(CHUNK_SIZE as u32);
+                    let chunk = &mut buf[..n as usize];
+                    flash
+                        .read(src + offset + pos, chunk)
+                        .await
+                        .map_err(EmulatedEepromError::Flash)?;
+                    flash
+                        .write(dst + dst_offset + pos, chunk)
+                        .await
+                        .map_err(EmulatedEepromError::Flash)?;
+                    pos += n;
+                }
+                dst_offset += record.size;
+            }
+
+            offset = next;
+        }
+
+        if let Some(value) = value {
+            let size = record_size::<S>(value.len() as u32);
+            if dst_offset + size > self.page_size {
+                return Err(EmulatedEepromError::Full);
+            }
+            self.write_record(flash, dst + dst_offset, key, value.len() as u16, value)
+                .await?;
+            dst_offset += size;
+        }
+
+        // The header is written last, an interrupted copy leaves the old page active.
+        self.write_page_header(flash, dst_page, seq).await?;
+
+        self.state = Some(State {
+            active: dst_page,
+            seq,
+            end: dst_offset,
+            write_offset: dst_offset,
+        });
+        Ok(())
+    }
+}
+
+async fn equals<S: AsyncNorFlash>(
+    flash: &mut S,
+    address: u32,
+    value: &[u8],
+) -> Result<bool, EmulatedEepromError<S::Error>> {
+    let mut buf = [0; CHUNK_SIZE];
+    for (i, expected) in value.chunks(CHUNK_SIZE).enumerate() {
+        let chunk = &mut buf[..expected.len()];
+        flash
+            .read(address + (i * CHUNK_SIZE) as u32, chunk)
+            .await
+            .map_err(EmulatedEepromError::Flash)?;
+        if chunk != expected {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn header_slot<S: AsyncNorFlash>() -> u32 {
+    8u32.next_multiple_of(S::WRITE_SIZE as u32)
+}
+
+fn record_size<S: AsyncNorFlash>(data_len: u32) -> u32 {
+    (RECORD_HEADER_SIZE + data_len).next_multiple_of(S::WRITE_SIZE as u32)
+}
+
+/// CRC-32 (IEEE 802.3).
+struct Crc32(u32);
+
+impl Crc32 {
+    fn new() -> Self {
+        Self(0xFFFF_FFFF)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.0 ^= b as u32;
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_storage::nor_flash::{ErrorType, NorFlash, NorFlashErrorKind, ReadNorFlash};
+
+    use super::*;
+
+    const PAGE_SIZE: u32 = 256;
+
+    /// Flash in RAM, only allowing writes to erased words.
+    struct MemFlash {
+        data: [u8; 2 * PAGE_SIZE as usize],
+        erased: u8,
+    }
+
+    impl MemFlash {
+        fn new(erased: u8) -> Self {
+            Self {
+                data: [erased; 2 * PAGE_SIZE as usize],
+                erased,
+            }
+        }
+    }
+
+    impl ErrorType for MemFlash {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl ReadNorFlash for MemFlash {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    impl NorFlash for MemFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = 128;
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            assert!(offset % Self::WRITE_SIZE == 0 && bytes.len() % Self::WRITE_SIZE == 0);
+            let target = &mut self.data[offset..offset + bytes.len()];
+            assert!(target.iter().all(|&b| b == self.erased), "write to programmed flash");
+            target.copy_from_slice(bytes);
+            Ok(())
+        }
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            self.data[from as usize..to as usize].fill(self.erased);
+            Ok(())
+        }
+    }
+
+    fn eeprom(erased: u8) -> EmulatedEeprom<MemFlash> {
+        EmulatedEeprom::new_with_erase_value(MemFlash::new(erased), 0, PAGE_SIZE, erased)
+    }
+
+    fn remount(eeprom: EmulatedEeprom<MemFlash>) -> EmulatedEeprom<MemFlash> {
+        let erased = eeprom.pages.erased;
+        EmulatedEeprom::new_with_erase_value(eeprom.release(), 0, PAGE_SIZE, erased)
+    }
+
+    fn read(eeprom: &mut EmulatedEeprom<MemFlash>, key: u16) -> Option<([u8; 32], usize)> {
+        let mut buf = [0; 32];
+        eeprom.blocking_read(key, &mut buf).unwrap().map(|len| (buf, len))
+    }
+
+    fn assert_value(eeprom: &mut EmulatedEeprom<MemFlash>, key: u16, value: &[u8]) {
+        let (buf, len) = read(eeprom, key).unwrap();
+        assert_eq!(&buf[..len], value);
+    }
+
+    #[test]
+    fn record_encoding() {
+        for erased in [0xFF, 0x00] {
+            let mut eeprom = eeprom(erased);
+            eeprom.blocking_write(0x1234, &[1, 2, 3]).unwrap();
+
+            // Page header, then the record: key, length, CRC and the value padded to the write size.
+            let data = &eeprom.flash.data;
+            assert_eq!(&data[4..8], &PAGE_MAGIC.to_le_bytes());
+            let code = |v: u16| eeprom.pages.code(v).to_le_bytes();
+            assert_eq!(&data[8..10], &code(0x1234));
+            assert_eq!(&data[10..12], &code(3));
+            assert_eq!(&data[16..20], &[1, 2, 3, erased]);
+            assert!(data[20..].iter().all(|&b| b == erased));
+        }
+    }
+
+    #[test]
+    fn read_write_remove() {
+        for erased in [0xFF, 0x00] {
+            let mut eeprom = eeprom(erased);
+            assert!(read(&mut eeprom, 1).is_none());
+
+            eeprom.blocking_write(1, &[1; 5]).unwrap();
+            eeprom.blocking_write(2, &[]).unwrap();
+            eeprom.blocking_write(1, &[2; 3]).unwrap();
+            assert_value(&mut eeprom, 1, &[2; 3]);
+            assert_value(&mut eeprom, 2, &[]);
+
+            eeprom.blocking_remove(1).unwrap();
+            assert!(read(&mut eeprom, 1).is_none());
+
+            let mut eeprom = remount(eeprom);
+            assert!(read(&mut eeprom, 1).is_none());
+            assert_value(&mut eeprom, 2, &[]);
+        }
+    }
+
+    #[test]
+    fn unchanged_value_is_not_written() {
+        let mut eeprom = eeprom(0xFF);
+        eeprom.blocking_write(1, &[7; 4]).unwrap();
+        let end = eeprom.pages.state.unwrap().end;
+        eeprom.blocking_write(1, &[7; 4]).unwrap();
+        assert_eq!(eeprom.pages.state.unwrap().end, end);
+    }
+
+    #[test]
+    fn invalid_key_and_size() {
+        let mut eeprom = eeprom(0xFF);
+        assert_eq!(
+            eeprom.blocking_write(ERASED_KEY, &[]),
+            Err(EmulatedEepromError::InvalidKey)
+        );
+        assert_eq!(
+            eeprom.blocking_write(1, &[0; PAGE_SIZE as usize]),
+            Err(EmulatedEepromError::ValueTooLarge)
+        );
+        eeprom.blocking_write(1, &[0; 8]).unwrap();
+        assert_eq!(
+            eeprom.blocking_read(1, &mut [0; 4]),
+            Err(EmulatedEepromError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn compaction_keeps_latest_values() {
+        for erased in [0xFF, 0x00] {
+            let mut eeprom = eeprom(erased);
+            eeprom.blocking_write(100, &[0xAA; 6]).unwrap();
+            eeprom.blocking_write(200, &[0xBB; 2]).unwrap();
+            eeprom.blocking_remove(200).unwrap();
+
+            // Each record takes 16 bytes, so this wraps around the two pages several times.
+            for i in 0..100u8 {
+                eeprom.blocking_write(1 + (i % 3) as u16, &[i; 8]).unwrap();
+            }
+            assert_value(&mut eeprom, 100, &[0xAA; 6]);
+            assert!(read(&mut eeprom, 200).is_none());
+            assert_value(&mut eeprom, 1, &[99; 8]);
+            assert_value(&mut eeprom, 2, &[97; 8]);
+            assert_value(&mut eeprom, 3, &[98; 8]);
+
+            let mut eeprom = remount(eeprom);
+            assert_value(&mut eeprom, 100, &[0xAA; 6]);
+            assert!(read(&mut eeprom, 200).is_none());
+            assert_value(&mut eeprom, 1, &[99; 8]);
+        }
+    }
+
+    #[test]
+    fn full() {
+        let mut eeprom = eeprom(0xFF);
+        let mut result = Ok(());
+        for key in 0..PAGE_SIZE as u16 {
+            result = eeprom.blocking_write(key, &[0; 8]);
+            if result.is_err() {
+                break;
+            }
+        }
+        assert_eq!(result, Err(EmulatedEepromError::Full));
+    }
+
+    #[test]
+    fn interrupted_write_is_ignored() {
+        for erased in [0xFF, 0x00] {
+            let mut eeprom = eeprom(erased);
+            eeprom.blocking_write(1, &[1; 8]).unwrap();
+            eeprom.blocking_write(1, &[2; 8]).unwrap();
+
+            // Tear the last record, as if the power failed while its value was written.
+            let end = eeprom.pages.state.unwrap().end as usize;
+            eeprom.flash.data[end - 4..end].fill(erased);
+
+            let mut eeprom = remount(eeprom);
+            assert_value(&mut eeprom, 1, &[1; 8]);
+
+            // The next write moves the valid records away from the torn one.
+            eeprom.blocking_write(2, &[3; 8]).unwrap();
+            assert_eq!(eeprom.pages.state.unwrap().active, 1);
+            let mut eeprom = remount(eeprom);
+            assert_value(&mut eeprom, 1, &[1; 8]);
+            assert_value(&mut eeprom, 2, &[3; 8]);
+        }
+    }
+
+    #[test]
+    fn interrupted_compaction_keeps_old_page() {
+        let mut eeprom = eeprom(0xFF);
+        eeprom.blocking_write(1, &[1; 8]).unwrap();
+
+        // A copy to the other page without its header, as left by a power failure.
+        let (page0, page1) = eeprom.flash.data.split_at_mut(PAGE_SIZE as usize);
+        page1[8..24].copy_from_slice(&page0[8..24]);
+
+        let mut eeprom = remount(eeprom);
+        assert_value(&mut eeprom, 1, &[1; 8]);
+        assert_eq!(eeprom.pages.state.unwrap().active, 0);
+    }
+}
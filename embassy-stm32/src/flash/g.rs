@@ -1,3 +1,5 @@
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+use core::ops::{Range, RangeInclusive};
 use core::ptr::write_volatile;
 #[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
 use core::sync::atomic::AtomicBool;
@@ -107,8 +109,11 @@ pub(crate) async unsafe fn erase_sector(sector: &FlashSector) -> Result<(), Erro
     interrupt::free(|_| {
         pac::FLASH.cr().modify(|w| {
             w.set_per(true);
-            #[cfg(any(flash_g0x0, flash_g0x1, flash_g4c3))]
+            #[cfg(any(flash_g0x0, flash_g0x1))]
             w.set_bker(sector.bank == crate::flash::FlashBank::Bank2);
+            // BKER selects a physical bank and ignores FB_MODE, so it has to be taken into account here
+            #[cfg(flash_g4c3)]
+            w.set_bker((sector.bank == crate::flash::FlashBank::Bank2) != banks_swapped());
             #[cfg(flash_g0x0)]
             w.set_pnb(sector.index_in_bank as u16);
             #[cfg(not(flash_g0x0))]
@@ -140,8 +145,11 @@ pub(crate) unsafe fn blocking_erase_sector(sector: &FlashSector) -> Result<(), E
     interrupt::free(|_| {
         pac::FLASH.cr().modify(|w| {
             w.set_per(true);
-            #[cfg(any(flash_g0x0, flash_g0x1, flash_g4c3))]
+            #[cfg(any(flash_g0x0, flash_g0x1))]
             w.set_bker(sector.bank == crate::flash::FlashBank::Bank2);
+            // BKER selects a physical bank and ignores FB_MODE, so it has to be taken into account here
+            #[cfg(flash_g4c3)]
+            w.set_bker((sector.bank == crate::flash::FlashBank::Bank2) != banks_swapped());
             #[cfg(flash_g0x0)]
             w.set_pnb(sector.index_in_bank as u16);
             #[cfg(not(flash_g0x0))]
@@ -243,6 +251,335 @@ fn save_data_cache_state() {}
 #[cfg(any(flash_g0x0, flash_g0x1))]
 fn restore_data_cache_state() {}
 
+/// Get whether bank 2 is mapped at the start of the flash memory (SYSCFG FB_MODE).
+///
+/// The mapping is set by the boot loader when booting from bank 2, see `perform_bank_swap()`.
+#[cfg(flash_g4c3)]
+pub fn banks_swapped() -> bool {
+    pac::SYSCFG.memrmp().read().fb_mode()
+}
+
+/// Logical, persistent swap of flash banks 1 and 2.
+///
+/// This allows the application to write a new firmware blob into bank 2, then
+/// swap the banks and boot the new firmware. The swap toggles the BFB2 option, the system
+/// boot loader then maps and boots the bank that is not currently mapped first, as long as it
+/// holds a valid stack pointer. Requires dual-bank mode.
+///
+/// The new option bytes are loaded right away, which resets the device.
+///
+/// PLEASE READ THE REFERENCE MANUAL - there are nuances to this feature. For
+/// instance, erase commands which take a flash bank as a parameter ignore the swap!
+#[cfg(flash_g4c3)]
+pub fn perform_bank_swap() -> ! {
+    unlock_option_bytes();
+
+    // toggle BFB2 option
+    pac::FLASH.optr().modify(|w| w.set_bfb2(!banks_swapped()));
+
+    // program option bytes
+    pac::FLASH.cr().modify(|w| w.set_optstrt(true));
+    wait_busy();
+
+    reload_option_bytes()
+}
+
+/// Readout protection level (RDP).
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadoutProtection {
+    /// No protection.
+    Level0,
+    /// Flash memory can't be read by a debugger or when booting from RAM or system memory.
+    /// Going back to level 0 mass erases the flash memory.
+    Level1,
+    /// Like level 1, and the debug port and boot from RAM or system memory are disabled.
+    /// This is permanent: the option bytes can't be changed anymore.
+    Level2,
+}
+
+/// Protection of one flash bank, stored in the option bytes.
+///
+/// Page ranges are page indices within the bank.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BankProtection {
+    /// Write protected area A (WRPxA).
+    pub write_protection_a: Option<RangeInclusive<u8>>,
+    /// Write protected area B (WRPxB).
+    pub write_protection_b: Option<RangeInclusive<u8>>,
+    /// Proprietary code readout protection area (PCROPx), as an absolute address range.
+    ///
+    /// Code in this area can only be executed, not read. Both ends must be aligned to
+    /// [`pcrop_granularity`].
+    pub pcrop: Option<Range<u32>>,
+    /// Number of pages at the start of the bank forming the securable memory area.
+    ///
+    /// Once [`secure_securable_area`] is called, the area can't be accessed until the next reset.
+    pub securable_pages: u8,
+}
+
+/// Flash memory protection, stored in the option bytes.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProtectionConfig {
+    /// Readout protection level.
+    pub readout: ReadoutProtection,
+    /// Protection of bank 1.
+    pub bank1: BankProtection,
+    /// Protection of bank 2.
+    #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+    pub bank2: BankProtection,
+    /// Erase the PCROP areas when the readout protection goes back from level 1 to level 0.
+    pub pcrop_erase_on_rdp_regression: bool,
+}
+
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+impl Default for ProtectionConfig {
+    fn default() -> Self {
+        Self {
+            readout: ReadoutProtection::Level0,
+            bank1: BankProtection::default(),
+            #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+            bank2: BankProtection::default(),
+            pcrop_erase_on_rdp_regression: false,
+        }
+    }
+}
+
+/// Raw option byte values of a [`BankProtection`], checked against its bank.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+struct BankOptionBytes {
+    wrp_a: (u8, u8),
+    wrp_b: (u8, u8),
+    pcrop: (u16, u16),
+    securable_pages: u8,
+}
+
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+impl BankProtection {
+    fn read(
+        wrp_a: (u8, u8),
+        wrp_b: (u8, u8),
+        pcrop: (u16, u16),
+        securable_pages: u8,
+        region: &super::FlashRegion,
+    ) -> Self {
+        let wrp = |(start, end): (u8, u8)| (start <= end).then_some(start..=end);
+        let granularity = pcrop_granularity();
+        let base = region.base();
+        let (pcrop_start, pcrop_end) = pcrop;
+
+        Self {
+            write_protection_a: wrp(wrp_a),
+            write_protection_b: wrp(wrp_b),
+            pcrop: (pcrop_start <= pcrop_end)
+                .then(|| base + pcrop_start as u32 * granularity..base + (pcrop_end as u32 + 1) * granularity),
+            securable_pages,
+        }
+    }
+
+    fn to_option_bytes(&self, region: &super::FlashRegion) -> Result<BankOptionBytes, Error> {
+        let pages = region.sectors() as u32;
+        let check_wrp = |area: &Option<RangeInclusive<u8>>| match area {
+            Some(area) if !area.is_empty() && *area.end() as u32 >= pages => Err(Error::Size),
+            // An empty range disables the area, like `None`.
+            Some(area) if !area.is_empty() => Ok((*area.start(), *area.end())),
+            _ => Ok((1, 0)),
+        };
+
+        let pcrop = match &self.pcrop {
+            Some(area) if !area.is_empty() => {
+                let base = region.base();
+                let granularity = pcrop_granularity();
+                if area.start < base || area.end > region.end() {
+                    return Err(Error::Size);
+                }
+                if area.start % granularity != 0 || area.end % granularity != 0 {
+                    return Err(Error::Unaligned);
+                }
+                (
+                    ((area.start - base) / granularity) as u16,
+                    ((area.end - base) / granularity - 1) as u16,
+                )
+            }
+            _ => (1, 0),
+        };
+
+        if self.securable_pages as u32 > pages {
+            return Err(Error::Size);
+        }
+
+        Ok(BankOptionBytes {
+            wrp_a: check_wrp(&self.write_protection_a)?,
+            wrp_b: check_wrp(&self.write_protection_b)?,
+            pcrop,
+            securable_pages: self.securable_pages,
+        })
+    }
+}
+
+/// Get the flash memory protection currently in effect.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+pub fn protection_config() -> ProtectionConfig {
+    let f = pac::FLASH;
+
+    let readout = match f.optr().read().rdp() {
+        0xAA => ReadoutProtection::Level0,
+        0xCC => ReadoutProtection::Level2,
+        _ => ReadoutProtection::Level1,
+    };
+
+    let wrp1ar = f.wrp1ar().read();
+    let wrp1br = f.wrp1br().read();
+    let pcrop1sr = f.pcrop1sr().read();
+    let pcrop1er = f.pcrop1er().read();
+    let bank1 = BankProtection::read(
+        (wrp1ar.wrp1a_strt(), wrp1ar.wrp1a_end()),
+        (wrp1br.wrp1b_strt(), wrp1br.wrp1b_end()),
+        (pcrop1sr.pcrop1_strt(), pcrop1er.pcrop1_end()),
+        f.sec1r().read().sec_size1(),
+        &super::BANK1_REGION,
+    );
+
+    #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+    let bank2 = {
+        let wrp2ar = f.wrp2ar().read();
+        let wrp2br = f.wrp2br().read();
+        BankProtection::read(
+            (wrp2ar.wrp2a_strt(), wrp2ar.wrp2a_end()),
+            (wrp2br.wrp2b_strt(), wrp2br.wrp2b_end()),
+            (f.pcrop2sr().read().pcrop2_strt(), f.pcrop2er().read().pcrop2_end()),
+            f.sec2r().read().sec_size2(),
+            &super::BANK2_REGION,
+        )
+    };
+
+    ProtectionConfig {
+        readout,
+        bank1,
+        #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+        bank2,
+        pcrop_erase_on_rdp_regression: pcrop1er.pcrop_rdp(),
+    }
+}
+
+/// Program the flash memory protection into the option bytes.
+///
+/// The new protection takes effect once the option bytes are loaded, see
+/// [`reload_option_bytes`].
+///
+/// PLEASE READ THE REFERENCE MANUAL before using this. Readout protection level 2 is
+/// permanent, and going back from level 1 to level 0 mass erases the flash memory.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+pub fn program_protection(config: &ProtectionConfig) -> Result<(), Error> {
+    let bank1 = config.bank1.to_option_bytes(&super::BANK1_REGION)?;
+    #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+    let bank2 = config.bank2.to_option_bytes(&super::BANK2_REGION)?;
+
+    let rdp = match config.readout {
+        ReadoutProtection::Level0 => 0xAA,
+        ReadoutProtection::Level1 => 0x55,
+        ReadoutProtection::Level2 => 0xCC,
+    };
+
+    unlock_option_bytes();
+
+    let f = pac::FLASH;
+    f.optr().modify(|w| w.set_rdp(rdp));
+    f.wrp1ar().modify(|w| {
+        w.set_wrp1a_strt(bank1.wrp_a.0);
+        w.set_wrp1a_end(bank1.wrp_a.1);
+    });
+    f.wrp1br().modify(|w| {
+        w.set_wrp1b_strt(bank1.wrp_b.0);
+        w.set_wrp1b_end(bank1.wrp_b.1);
+    });
+    f.pcrop1sr().modify(|w| w.set_pcrop1_strt(bank1.pcrop.0));
+    f.pcrop1er().modify(|w| {
+        w.set_pcrop1_end(bank1.pcrop.1);
+        w.set_pcrop_rdp(config.pcrop_erase_on_rdp_regression);
+    });
+    f.sec1r().modify(|w| w.set_sec_size1(bank1.securable_pages));
+
+    #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+    {
+        f.wrp2ar().modify(|w| {
+            w.set_wrp2a_strt(bank2.wrp_a.0);
+            w.set_wrp2a_end(bank2.wrp_a.1);
+        });
+        f.wrp2br().modify(|w| {
+            w.set_wrp2b_strt(bank2.wrp_b.0);
+            w.set_wrp2b_end(bank2.wrp_b.1);
+        });
+        f.pcrop2sr().modify(|w| w.set_pcrop2_strt(bank2.pcrop.0));
+        f.pcrop2er().modify(|w| w.set_pcrop2_end(bank2.pcrop.1));
+        f.sec2r().modify(|w| w.set_sec_size2(bank2.securable_pages));
+    }
+
+    f.cr().modify(|w| w.set_optstrt(true));
+    let ret = unsafe { wait_ready_blocking() };
+
+    f.cr().modify(|w| w.set_optlock(true));
+    unsafe {
+        clear_all_err();
+        lock();
+    }
+    ret
+}
+
+/// Granularity of the PCROP area, in bytes.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+pub fn pcrop_granularity() -> u32 {
+    // A double word, or 2 of them in single bank mode of category 3 devices.
+    #[cfg(flash_g4c3)]
+    if !pac::FLASH.optr().read().dbank() {
+        return 16;
+    }
+    8
+}
+
+/// Protect the securable memory areas of all banks until the next reset.
+///
+/// Code in the area, e.g. a secure boot loader, calls this before jumping to the application.
+/// Afterwards the area can't be read, written or executed.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+pub fn secure_securable_area() {
+    pac::FLASH.cr().modify(|w| {
+        w.set_sec_prot1(true);
+        #[cfg(all(flash_g4c3, feature = "dual-bank"))]
+        w.set_sec_prot2(true);
+    });
+}
+
+/// Load the option bytes, which resets the device.
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+pub fn reload_option_bytes() -> ! {
+    unlock_option_bytes();
+    pac::FLASH.cr().modify(|w| w.set_obl_launch(true));
+    cortex_m::peripheral::SCB::sys_reset()
+}
+
+#[cfg(any(flash_g4c2, flash_g4c3, flash_g4c4))]
+fn unlock_option_bytes() {
+    wait_busy();
+
+    unsafe {
+        clear_all_err();
+        unlock();
+    }
+
+    // unlock OPTLOCK
+    if pac::FLASH.cr().read().optlock() {
+        pac::FLASH.optkeyr().write_value(0x0819_2A3B);
+        pac::FLASH.optkeyr().write_value(0x4C5D_6E7F);
+    }
+}
+
 #[cfg(all(bank_setup_configurable, any(flash_g4c2, flash_g4c3, flash_g4c4)))]
 pub(crate) fn check_bank_setup() {
     if cfg!(feature = "single-bank") && pac::FLASH.optr().read().dbank() {
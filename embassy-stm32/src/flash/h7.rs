@@ -275,3 +275,46 @@ fn get_result(sr: Sr) -> Result<(), Error> {
         Ok(())
     }
 }
+
+/// Get the current SWAP_BANK option.
+///
+/// This value is only loaded on system or power-on reset. `perform_bank_swap()`
+/// will not reflect here.
+pub fn banks_swapped() -> bool {
+    pac::FLASH.optcr().read().swap_bank()
+}
+
+/// Logical, persistent swap of flash banks 1 and 2.
+///
+/// This allows the application to write a new firmware blob into bank 2, then
+/// swap the banks and boot the new firmware. Only available on parts with two
+/// banks.
+///
+/// The swap only takes effect on reset, so the device is reset once the new
+/// option bytes are programmed.
+///
+/// PLEASE READ THE REFERENCE MANUAL - there are nuances to this feature.
+pub fn perform_bank_swap() -> ! {
+    assert!(is_dual_bank());
+
+    let bank = pac::FLASH.bank(0);
+    while bank.sr().read().bsy() || bank.sr().read().qw() {}
+
+    // unlock OPTLOCK
+    if pac::FLASH.optcr().read().optlock() {
+        pac::FLASH.optkeyr().write_value(0x0819_2A3B);
+        pac::FLASH.optkeyr().write_value(0x4C5D_6E7F);
+    }
+
+    // toggle SWAP_BANK option
+    pac::FLASH.optsr_prg().modify(|w| w.set_swap_bank_opt(!banks_swapped()));
+
+    // program option bytes
+    pac::FLASH.optcr().modify(|w| w.set_optstart(true));
+    while pac::FLASH.optsr_cur().read().opt_busy() {}
+
+    // re-lock OPTLOCK
+    pac::FLASH.optcr().modify(|w| w.set_optlock(true));
+
+    cortex_m::peripheral::SCB::sys_reset()
+}
@@ -1,21 +1,21 @@
 use core::ptr::write_volatile;
 use core::sync::atomic::{Ordering, fence};
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 use cortex_m::interrupt;
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 use embassy_sync::waitqueue::AtomicWaker;
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 use pac::flash::regs::Sr;
 
 use super::{FlashSector, WRITE_SIZE};
 use crate::flash::Error;
 use crate::pac;
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 static WAKER: AtomicWaker = AtomicWaker::new();
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 pub(crate) unsafe fn on_interrupt() {
     // Clear IRQ flags (EOP and error flags are write-1-to-clear)
     pac::FLASH.sr().write(|w| {
@@ -35,7 +35,7 @@ pub(crate) unsafe fn on_interrupt() {
     WAKER.wake();
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 pub(crate) unsafe fn enable_write() {
     assert_eq!(0, WRITE_SIZE % 4);
     pac::FLASH.cr().write(|w| {
@@ -45,7 +45,7 @@ pub(crate) unsafe fn enable_write() {
     });
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 pub(crate) unsafe fn disable_write() {
     pac::FLASH.cr().write(|w| {
         w.set_pg(false);
@@ -54,7 +54,7 @@ pub(crate) unsafe fn disable_write() {
     });
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 unsafe fn write_start(start_address: u32, buf: &[u8; WRITE_SIZE]) {
     let mut address = start_address;
     for val in buf.chunks(4) {
@@ -66,22 +66,26 @@ unsafe fn write_start(start_address: u32, buf: &[u8; WRITE_SIZE]) {
     }
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 pub(crate) async unsafe fn write(start_address: u32, buf: &[u8; WRITE_SIZE]) -> Result<(), Error> {
     write_start(start_address, buf);
     wait_ready().await
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 pub(crate) async unsafe fn erase_sector(sector: &FlashSector) -> Result<(), Error> {
     interrupt::free(|_| {
         pac::FLASH.cr().modify(|w| {
             w.set_per(true);
             w.set_pnb(sector.index_in_bank);
+            #[cfg(flash_l4)]
             w.set_bker(sector.bank == crate::flash::FlashBank::Bank2);
             w.set_eopie(true);
             w.set_errie(true);
+            #[cfg(flash_l4)]
             w.set_start(true);
+            #[cfg(any(flash_wl, flash_wb))]
+            w.set_strt(true);
         });
     });
 
@@ -96,7 +100,7 @@ pub(crate) async unsafe fn erase_sector(sector: &FlashSector) -> Result<(), Erro
     ret
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 pub(crate) async fn wait_ready() -> Result<(), Error> {
     use core::future::poll_fn;
     use core::task::Poll;
@@ -114,7 +118,7 @@ pub(crate) async fn wait_ready() -> Result<(), Error> {
     .await
 }
 
-#[cfg(flash_l4)]
+#[cfg(any(flash_l4, flash_wl, flash_wb))]
 fn get_result(sr: Sr) -> Result<(), Error> {
     if sr.progerr() {
         Err(Error::Prog)
@@ -2,16 +2,22 @@
 use embedded_storage::nor_flash::{NorFlashError, NorFlashErrorKind};
 
 #[cfg(any(
-    flash_f4, flash_g0x0, flash_g0x1, flash_g4c2, flash_g4c3, flash_g4c4, flash_h7, flash_h7ab, flash_l4
+    flash_f2, flash_f4, flash_f7, flash_g0x0, flash_g0x1, flash_g4c2, flash_g4c3, flash_g4c4, flash_h7, flash_h7ab,
+    flash_l4, flash_wl, flash_wb
 ))]
 mod asynch;
 #[cfg(flash)]
 mod common;
 #[cfg(eeprom)]
 mod eeprom;
+#[cfg(flash)]
+mod emulated_eeprom;
+#[cfg(flash_otp)]
+mod otp;
 
 #[cfg(any(
-    flash_f4, flash_g0x0, flash_g0x1, flash_g4c2, flash_g4c3, flash_g4c4, flash_h7, flash_h7ab, flash_l4
+    flash_f2, flash_f4, flash_f7, flash_g0x0, flash_g0x1, flash_g4c2, flash_g4c3, flash_g4c4, flash_h7, flash_h7ab,
+    flash_l4, flash_wl, flash_wb
 ))]
 pub use asynch::InterruptHandler;
 #[cfg(flash)]
@@ -19,6 +25,8 @@ pub use common::*;
 #[cfg(eeprom)]
 #[allow(unused_imports)]
 pub use eeprom::*;
+#[cfg(flash)]
+pub use emulated_eeprom::{EmulatedEeprom, EmulatedEepromError};
 
 pub use crate::_generated::flash_regions::*;
 #[cfg(eeprom)]
@@ -143,6 +151,7 @@ pub enum Error {
     Protected,
     Unaligned,
     Parallelism,
+    AlreadyProgrammed,
 }
 
 impl NorFlashError for Error {
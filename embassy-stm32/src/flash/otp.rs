@@ -0,0 +1,58 @@
+use super::{Blocking, Error, FlashRegion, blocking_read, blocking_write, write_chunk_with_critical_section};
+use crate::_generated::flash_regions::OTPRegion;
+
+impl<MODE> OTPRegion<'_, MODE> {
+    /// Check whether `len` bytes at `offset` are still unprogrammed.
+    ///
+    /// NOTE: `offset` is an offset from the start of the OTP area, NOT an absolute address.
+    pub fn is_blank(&mut self, offset: u32, len: u32) -> Result<bool, Error> {
+        match ensure_blank(self.0, offset, len) {
+            Ok(()) => Ok(true),
+            Err(Error::AlreadyProgrammed) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl OTPRegion<'_, Blocking> {
+    /// Program one-time-programmable bytes.
+    ///
+    /// OTP bytes can't be erased, so this refuses to touch a range that has been programmed
+    /// before: if any byte of it is not blank, nothing is written and
+    /// `Error::AlreadyProgrammed` is returned. `offset` and the length of `bytes` must be
+    /// multiples of the write size.
+    ///
+    /// NOTE: `offset` is an offset from the start of the OTP area, NOT an absolute address.
+    pub fn blocking_program(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        ensure_blank(self.0, offset, bytes.len() as u32)?;
+        unsafe {
+            blocking_write(
+                self.0.base(),
+                self.0.size,
+                offset,
+                bytes,
+                write_chunk_with_critical_section,
+            )
+        }
+    }
+}
+
+pub(super) fn ensure_blank(region: &FlashRegion, offset: u32, len: u32) -> Result<(), Error> {
+    let end = offset
+        .checked_add(len)
+        .filter(|end| *end <= region.size)
+        .ok_or(Error::Size)?;
+
+    let mut buf = [0; 16];
+    let mut offset = offset;
+    while offset < end {
+        let n = (end - offset).min(buf.len() as u32);
+        let chunk = &mut buf[..n as usize];
+        blocking_read(region.base(), region.size, offset, chunk)?;
+        if chunk.iter().any(|&b| b != region.erase_value) {
+            return Err(Error::AlreadyProgrammed);
+        }
+        offset += n;
+    }
+    Ok(())
+}
@@ -84,23 +84,13 @@ pub(crate) unsafe fn blocking_erase_sector(sector: &FlashSector) -> Result<(), E
     pac::FLASH.seccr().modify(|w| {
         w.set_per(pac::flash::vals::SeccrPer::B0x1);
         w.set_pnb(sector.index_in_bank);
-        // TODO: add check for bank swap
-        w.set_bker(match sector.bank {
-            FlashBank::Bank1 => false,
-            FlashBank::Bank2 => true,
-            _ => unreachable!(),
-        });
+        w.set_bker(bker(sector.bank));
     });
     #[cfg(not(feature = "trustzone-secure"))]
     pac::FLASH.nscr().modify(|w| {
         w.set_per(true);
         w.set_pnb(sector.index_in_bank);
-        // TODO: add check for bank swap
-        w.set_bker(match sector.bank {
-            FlashBank::Bank1 => false,
-            FlashBank::Bank2 => true,
-            _ => unreachable!(),
-        });
+        w.set_bker(bker(sector.bank));
     });
 
     #[cfg(feature = "trustzone-secure")]
@@ -185,3 +175,63 @@ unsafe fn blocking_wait_ready() -> Result<(), Error> {
         }
     }
 }
+
+/// BKER selects a physical bank and ignores SWAP_BANK, so it has to be taken into account here.
+fn bker(bank: FlashBank) -> bool {
+    #[cfg(flash_u5)]
+    let swapped = banks_swapped();
+    #[cfg(not(flash_u5))]
+    let swapped = false;
+
+    match bank {
+        FlashBank::Bank1 => swapped,
+        FlashBank::Bank2 => !swapped,
+        _ => unreachable!(),
+    }
+}
+
+/// Get the current SWAP_BANK option.
+///
+/// This value is only loaded when the option bytes are loaded, i.e. on power-on reset or by
+/// `perform_bank_swap()`.
+#[cfg(flash_u5)]
+pub fn banks_swapped() -> bool {
+    pac::FLASH.optr().read().swap_bank()
+}
+
+/// Logical, persistent swap of flash banks 1 and 2.
+///
+/// This allows the application to write a new firmware blob into bank 2, then
+/// swap the banks and boot the new firmware.
+///
+/// The new option bytes are loaded right away, which resets the device.
+///
+/// PLEASE READ THE REFERENCE MANUAL - there are nuances to this feature. For
+/// instance, erase commands and interrupt enables which take a flash bank as a
+/// parameter ignore the swap!
+#[cfg(flash_u5)]
+pub fn perform_bank_swap() -> ! {
+    while pac::FLASH.nssr().read().bsy() {}
+
+    unsafe {
+        clear_all_err();
+        unlock();
+    }
+
+    // unlock OPTLOCK
+    if pac::FLASH.nscr().read().optlock() {
+        pac::FLASH.optkeyr().write_value(0x0819_2A3B);
+        pac::FLASH.optkeyr().write_value(0x4C5D_6E7F);
+    }
+
+    // toggle SWAP_BANK option
+    pac::FLASH.optr().modify(|w| w.set_swap_bank(!banks_swapped()));
+
+    // program option bytes
+    pac::FLASH.nscr().modify(|w| w.set_optstrt(true));
+    while pac::FLASH.nssr().read().bsy() {}
+
+    // load option bytes, this resets the device
+    pac::FLASH.nscr().modify(|w| w.set_obl_launch(true));
+    cortex_m::peripheral::SCB::sys_reset()
+}
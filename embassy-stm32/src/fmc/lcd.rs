@@ -0,0 +1,376 @@
+//! Intel 8080 parallel LCD interface
+//!
+//! Parallel TFT controllers (ILI9341, ST7789, ILI9488...) are driven as an 8- or 16-bit SRAM
+//! on a NOR/PSRAM/SRAM bank: NOE is the RD strobe, NWE the WR strobe, NEx the chip select, and
+//! one address line drives the D/CX (register select) input. Writing the command address sends
+//! a command, writing the data address sends data, at the speed of the bus.
+//!
+//! The FMC has no E strobe, so Motorola 6800 mode controllers must be strapped to their 8080
+//! interface.
+//!
+//! On Cortex-M7 chips, the NOR/PSRAM/SRAM banks are normal cacheable memory by default, so
+//! writes may be merged or reordered. Configure an MPU region over the bank as device memory
+//! before using the display.
+//!
+//! With the `display-interface` feature, [`Lcd`] implements
+//! [`WriteOnlyDataCommand`](display_interface::WriteOnlyDataCommand) with the same data format
+//! handling as `display-interface-parallel-gpio`, so display driver crates can use it directly.
+
+use core::marker::PhantomData;
+use core::ptr;
+
+use super::{
+    AccessCycles, BankControl, ChipSelect, D0Pin, D1Pin, D2Pin, D3Pin, D4Pin, D5Pin, D6Pin, D7Pin, D8Pin, D9Pin,
+    D10Pin, D11Pin, D12Pin, D13Pin, D14Pin, D15Pin, Instance, NOEPin, NWEPin,
+};
+use crate::Peri;
+use crate::gpio::{AfType, OutputType, Pull, Speed};
+
+/// Timing of the bus cycles, in nanoseconds.
+///
+/// The delays are rounded up to whole FMC kernel clock cycles. The D/CX setup time to WR or RD
+/// is the address setup, the strobe duration is the data phase.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LcdTiming {
+    /// Time from D/CX and chip select to the start of the strobe, at most 15 cycles.
+    pub address_setup_ns: u32,
+    /// Duration of the strobe, from 1 to 255 cycles. For reads, this is the access time.
+    pub data_phase_ns: u32,
+    /// Time after the strobe before the next cycle, at most 15 cycles.
+    pub bus_turnaround_ns: u32,
+}
+
+impl LcdTiming {
+    // Access mode A, NOE and NWE toggled around the data phase only.
+    fn cycles(&self, kernel_clock_hz: u32) -> AccessCycles {
+        AccessCycles::from_ns(
+            self.address_setup_ns,
+            self.data_phase_ns,
+            self.bus_turnaround_ns,
+            kernel_clock_hz,
+        )
+    }
+}
+
+/// LCD interface configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LcdConfig {
+    /// Write cycle timing.
+    pub write: LcdTiming,
+    /// Read cycle timing, usually much slower than writes on LCD controllers.
+    pub read: LcdTiming,
+}
+
+impl Default for LcdConfig {
+    /// Timing of an ILI9341, a common worst case.
+    fn default() -> Self {
+        Self {
+            write: LcdTiming {
+                address_setup_ns: 10,
+                data_phase_ns: 30,
+                bus_turnaround_ns: 10,
+            },
+            read: LcdTiming {
+                address_setup_ns: 10,
+                data_phase_ns: 360,
+                bus_turnaround_ns: 90,
+            },
+        }
+    }
+}
+
+/// Address line driving the D/CX input of the display, low for commands and high for data.
+pub struct RegisterSelect<'d, T: Instance> {
+    line: u8,
+    _pin: PhantomData<&'d mut T>,
+}
+
+macro_rules! register_select_constructor {
+    ($($name:ident: $signal:ident = $line:expr),*) => {
+        $(
+            #[doc = concat!("Drive D/CX from `", stringify!($name), "`.")]
+            pub fn $name(pin: Peri<'d, impl super::$signal<T>>) -> Self {
+                critical_section::with(|_| {
+                    set_as_af!(pin, AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::Up));
+                });
+                Self {
+                    line: $line,
+                    _pin: PhantomData,
+                }
+            }
+        )*
+    };
+}
+
+impl<'d, T: Instance> RegisterSelect<'d, T> {
+    register_select_constructor!(
+        a0: A0Pin = 0, a1: A1Pin = 1, a2: A2Pin = 2, a3: A3Pin = 3, a4: A4Pin = 4, a5: A5Pin = 5,
+        a6: A6Pin = 6, a7: A7Pin = 7, a8: A8Pin = 8, a9: A9Pin = 9, a10: A10Pin = 10, a11: A11Pin = 11,
+        a12: A12Pin = 12, a13: A13Pin = 13, a14: A14Pin = 14, a15: A15Pin = 15, a16: A16Pin = 16,
+        a17: A17Pin = 17, a18: A18Pin = 18, a19: A19Pin = 19, a20: A20Pin = 20, a21: A21Pin = 21,
+        a22: A22Pin = 22, a23: A23Pin = 23, a24: A24Pin = 24, a25: A25Pin = 25
+    );
+}
+
+trait SealedLcdWord: Copy {
+    const MWID: u32;
+
+    #[cfg(feature = "display-interface")]
+    fn write_u8(reg: *mut Self, value: u8);
+    #[cfg(feature = "display-interface")]
+    fn write_u16(reg: *mut Self, value: u16, big_endian: bool);
+}
+
+/// Width of the LCD data bus, `u8` or `u16`.
+#[allow(private_bounds)]
+pub trait LcdWord: SealedLcdWord {}
+
+impl SealedLcdWord for u8 {
+    const MWID: u32 = 0;
+
+    #[cfg(feature = "display-interface")]
+    fn write_u8(reg: *mut Self, value: u8) {
+        unsafe { ptr::write_volatile(reg, value) }
+    }
+
+    #[cfg(feature = "display-interface")]
+    fn write_u16(reg: *mut Self, value: u16, big_endian: bool) {
+        let bytes = match big_endian {
+            true => value.to_be_bytes(),
+            false => value.to_le_bytes(),
+        };
+        for byte in bytes {
+            unsafe { ptr::write_volatile(reg, byte) }
+        }
+    }
+}
+impl LcdWord for u8 {}
+
+impl SealedLcdWord for u16 {
+    const MWID: u32 = 1;
+
+    #[cfg(feature = "display-interface")]
+    fn write_u8(reg: *mut Self, value: u8) {
+        unsafe { ptr::write_volatile(reg, value as u16) }
+    }
+
+    #[cfg(feature = "display-interface")]
+    fn write_u16(reg: *mut Self, value: u16, _big_endian: bool) {
+        unsafe { ptr::write_volatile(reg, value) }
+    }
+}
+impl LcdWord for u16 {}
+
+/// Intel 8080 LCD interface driver.
+pub struct Lcd<'d, T: Instance, W: LcdWord> {
+    chip_select: ChipSelect<'d, T>,
+    command: *mut W,
+    data: *mut W,
+}
+
+unsafe impl<'d, T: Instance, W: LcdWord> Send for Lcd<'d, T, W> {}
+
+impl<'d, T: Instance> Lcd<'d, T, u8> {
+    /// Create an LCD interface with an 8-bit data bus.
+    pub fn new_8bit(
+        d0: Peri<'d, impl D0Pin<T>>,
+        d1: Peri<'d, impl D1Pin<T>>,
+        d2: Peri<'d, impl D2Pin<T>>,
+        d3: Peri<'d, impl D3Pin<T>>,
+        d4: Peri<'d, impl D4Pin<T>>,
+        d5: Peri<'d, impl D5Pin<T>>,
+        d6: Peri<'d, impl D6Pin<T>>,
+        d7: Peri<'d, impl D7Pin<T>>,
+        rd: Peri<'d, impl NOEPin<T>>,
+        wr: Peri<'d, impl NWEPin<T>>,
+        chip_select: ChipSelect<'d, T>,
+        register_select: RegisterSelect<'d, T>,
+        config: LcdConfig,
+    ) -> Self {
+        critical_section::with(|_| {
+            let af = AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::Up);
+            set_as_af!(d0, af);
+            set_as_af!(d1, af);
+            set_as_af!(d2, af);
+            set_as_af!(d3, af);
+            set_as_af!(d4, af);
+            set_as_af!(d5, af);
+            set_as_af!(d6, af);
+            set_as_af!(d7, af);
+            set_as_af!(rd, af);
+            set_as_af!(wr, af);
+        });
+
+        Self::new_inner(chip_select, register_select, config)
+    }
+}
+
+impl<'d, T: Instance> Lcd<'d, T, u16> {
+    /// Create an LCD interface with a 16-bit data bus.
+    pub fn new_16bit(
+        d0: Peri<'d, impl D0Pin<T>>,
+        d1: Peri<'d, impl D1Pin<T>>,
+        d2: Peri<'d, impl D2Pin<T>>,
+        d3: Peri<'d, impl D3Pin<T>>,
+        d4: Peri<'d, impl D4Pin<T>>,
+        d5: Peri<'d, impl D5Pin<T>>,
+        d6: Peri<'d, impl D6Pin<T>>,
+        d7: Peri<'d, impl D7Pin<T>>,
+        d8: Peri<'d, impl D8Pin<T>>,
+        d9: Peri<'d, impl D9Pin<T>>,
+        d10: Peri<'d, impl D10Pin<T>>,
+        d11: Peri<'d, impl D11Pin<T>>,
+        d12: Peri<'d, impl D12Pin<T>>,
+        d13: Peri<'d, impl D13Pin<T>>,
+        d14: Peri<'d, impl D14Pin<T>>,
+        d15: Peri<'d, impl D15Pin<T>>,
+        rd: Peri<'d, impl NOEPin<T>>,
+        wr: Peri<'d, impl NWEPin<T>>,
+        chip_select: ChipSelect<'d, T>,
+        register_select: RegisterSelect<'d, T>,
+        config: LcdConfig,
+    ) -> Self {
+        critical_section::with(|_| {
+            let af = AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::Up);
+            set_as_af!(d0, af);
+            set_as_af!(d1, af);
+            set_as_af!(d2, af);
+            set_as_af!(d3, af);
+            set_as_af!(d4, af);
+            set_as_af!(d5, af);
+            set_as_af!(d6, af);
+            set_as_af!(d7, af);
+            set_as_af!(d8, af);
+            set_as_af!(d9, af);
+            set_as_af!(d10, af);
+            set_as_af!(d11, af);
+            set_as_af!(d12, af);
+            set_as_af!(d13, af);
+            set_as_af!(d14, af);
+            set_as_af!(d15, af);
+            set_as_af!(rd, af);
+            set_as_af!(wr, af);
+        });
+
+        Self::new_inner(chip_select, register_select, config)
+    }
+}
+
+impl<'d, T: Instance, W: LcdWord> Lcd<'d, T, W> {
+    fn new_inner(chip_select: ChipSelect<'d, T>, register_select: RegisterSelect<'d, T>, config: LcdConfig) -> Self {
+        super::enable::<T>();
+
+        let bank = chip_select.bank();
+        // SRAM memory type, with separate read and write timings.
+        bank.configure::<T>(&BankControl {
+            width: W::MWID as u8,
+            write: true,
+            extended_mode: true,
+            ..Default::default()
+        });
+        super::memory_controller_enable::<T>();
+
+        // On a 16-bit bus, HADDR[25:1] drive A[24:0].
+        let base = bank.base_address();
+        let data = base | 1 << (register_select.line as u32 + W::MWID);
+
+        let mut this = Self {
+            chip_select,
+            command: base as *mut W,
+            data: data as *mut W,
+        };
+        this.set_config(config);
+        this
+    }
+
+    /// Change the bus timing.
+    ///
+    /// This must be called again if the FMC kernel clock changes.
+    pub fn set_config(&mut self, config: LcdConfig) {
+        let hz = <T as crate::rcc::SealedRccPeripheral>::frequency().0;
+        let bank = self.chip_select.bank();
+        bank.set_read_timing::<T>(config.read.cycles(hz), 0, 0);
+        bank.set_write_timing::<T>(config.write.cycles(hz));
+    }
+
+    /// Send a command.
+    pub fn write_command(&mut self, command: W) {
+        unsafe { ptr::write_volatile(self.command, command) }
+    }
+
+    /// Send data, usually the parameters of the previous command or pixels.
+    pub fn write_data(&mut self, data: &[W]) {
+        for &word in data {
+            unsafe { ptr::write_volatile(self.data, word) }
+        }
+    }
+
+    /// Send the same data word `count` times, e.g. to fill an area with a color.
+    pub fn write_data_repeated(&mut self, word: W, count: usize) {
+        for _ in 0..count {
+            unsafe { ptr::write_volatile(self.data, word) }
+        }
+    }
+
+    /// Send a command followed by its parameters.
+    pub fn write_command_with_data(&mut self, command: W, data: &[W]) {
+        self.write_command(command);
+        self.write_data(data);
+    }
+
+    /// Read data, usually the response to the previous command.
+    pub fn read_data(&mut self, data: &mut [W]) {
+        for word in data {
+            *word = unsafe { ptr::read_volatile(self.data) };
+        }
+    }
+
+    /// Address of the command register, e.g. to write it with memory-to-memory DMA.
+    pub fn command_address(&self) -> *mut W {
+        self.command
+    }
+
+    /// Address of the data register, e.g. to write pixels with memory-to-memory DMA.
+    pub fn data_address(&self) -> *mut W {
+        self.data
+    }
+
+    #[cfg(feature = "display-interface")]
+    fn send(reg: *mut W, format: display_interface::DataFormat<'_>) -> Result<(), display_interface::DisplayError> {
+        use display_interface::DataFormat;
+
+        match format {
+            DataFormat::U8(words) => words.iter().for_each(|&w| W::write_u8(reg, w)),
+            DataFormat::U16(words) => words
+                .iter()
+                .for_each(|&w| W::write_u16(reg, w, cfg!(target_endian = "big"))),
+            DataFormat::U16BE(words) => words.iter().for_each(|&w| W::write_u16(reg, w, true)),
+            DataFormat::U16LE(words) => words.iter().for_each(|&w| W::write_u16(reg, w, false)),
+            DataFormat::U8Iter(words) => words.for_each(|w| W::write_u8(reg, w)),
+            DataFormat::U16BEIter(words) => words.for_each(|w| W::write_u16(reg, w, true)),
+            DataFormat::U16LEIter(words) => words.for_each(|w| W::write_u16(reg, w, false)),
+            _ => return Err(display_interface::DisplayError::DataFormatNotImplemented),
+        }
+        Ok(())
+    }
+}
+
+impl<'d, T: Instance, W: LcdWord> Drop for Lcd<'d, T, W> {
+    fn drop(&mut self) {
+        self.chip_select.bank().disable::<T>();
+        super::disable::<T>();
+    }
+}
+
+#[cfg(feature = "display-interface")]
+impl<'d, T: Instance, W: LcdWord> display_interface::WriteOnlyDataCommand for Lcd<'d, T, W> {
+    fn send_commands(&mut self, cmd: display_interface::DataFormat<'_>) -> Result<(), display_interface::DisplayError> {
+        Self::send(self.command, cmd)
+    }
+
+    fn send_data(&mut self, buf: display_interface::DataFormat<'_>) -> Result<(), display_interface::DisplayError> {
+        Self::send(self.data, buf)
+    }
+}
@@ -1,12 +1,29 @@
 //! Flexible Memory Controller (FMC) / Flexible Static Memory Controller (FSMC)
 use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use embassy_hal_internal::PeripheralType;
 
 use crate::gpio::{AfType, OutputType, Pull, Speed};
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+use crate::pac::fmc::vals;
 use crate::{Peri, rcc};
 
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+mod lcd;
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+mod nor_sram;
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub use lcd::{Lcd, LcdConfig, LcdTiming, LcdWord, RegisterSelect};
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub use nor_sram::{
+    AccessMode, AsyncTiming, CramPageSize, DataWidth, MemoryType, NorSram, NorSramConfig, NorSramPins, SyncConfig,
+    WaitConfig, WaitPolarity,
+};
+
 /// FMC driver
+///
+/// Use [`Fmc::split()`] to drive several memories, e.g. an SDRAM and an LCD, from the same FMC.
 pub struct Fmc<'d, T: Instance> {
     peri: PhantomData<&'d mut T>,
 }
@@ -25,20 +42,32 @@ where
         Self { peri: PhantomData }
     }
 
-    /// Enable the FMC peripheral and reset it.
+    /// Split the FMC into its banks, so that memories of different types can be used at the same
+    /// time.
+    pub fn split(_instance: Peri<'d, T>) -> FmcBanks<'d, T> {
+        FmcBanks {
+            #[cfg(not(any(fmc_v4, fmc_n6)))]
+            nor_sram1: NorSramBankPeri { _phantom: PhantomData },
+            #[cfg(not(any(fmc_v4, fmc_n6)))]
+            nor_sram2: NorSramBankPeri { _phantom: PhantomData },
+            #[cfg(not(any(fmc_v4, fmc_n6)))]
+            nor_sram3: NorSramBankPeri { _phantom: PhantomData },
+            #[cfg(not(any(fmc_v4, fmc_n6)))]
+            nor_sram4: NorSramBankPeri { _phantom: PhantomData },
+            sdram: SdramBankPeri { _phantom: PhantomData },
+        }
+    }
+
+    /// Enable the FMC peripheral.
+    ///
+    /// It is only reset if no other driver uses it.
     pub fn enable(&mut self) {
-        rcc::enable_and_reset::<T>();
+        enable::<T>();
     }
 
     /// Enable the memory controller on applicable chips.
     pub fn memory_controller_enable(&mut self) {
-        // fmc v1 and v2 does not have the fmcen bit
-        // fsmc v1, v2 and v3 does not have the fmcen bit
-        // This is a "not" because it is expected that all future versions have this bit
-        #[cfg(not(any(fmc_v1x3, fmc_v2x1, fsmc_v1x0, fsmc_v1x3, fmc_v4, fmc_n6)))]
-        T::REGS.bcr1().modify(|r| r.set_fmcen(true));
-        #[cfg(any(fmc_v4, fmc_n6))]
-        T::REGS.nor_psram().bcr1().modify(|r| r.set_fmcen(true));
+        memory_controller_enable::<T>();
     }
 
     /// Get the kernel clock currently in use for this FMC instance.
@@ -54,17 +83,11 @@ where
     const REGISTERS: *const () = T::REGS.as_ptr() as *const _;
 
     fn enable(&mut self) {
-        rcc::enable_and_reset::<T>();
+        enable::<T>();
     }
 
     fn memory_controller_enable(&mut self) {
-        // fmc v1 and v2 does not have the fmcen bit
-        // fsmc v1, v2 and v3 does not have the fmcen bit
-        // This is a "not" because it is expected that all future versions have this bit
-        #[cfg(not(any(fmc_v1x3, fmc_v2x1, fsmc_v1x0, fsmc_v1x3, fmc_v4, fmc_n6)))]
-        T::REGS.bcr1().modify(|r| r.set_fmcen(true));
-        #[cfg(any(fmc_v4, fmc_n6))]
-        T::REGS.nor_psram().bcr1().modify(|r| r.set_fmcen(true));
+        memory_controller_enable::<T>();
     }
 
     fn source_clock_hz(&self) -> u32 {
@@ -72,6 +95,97 @@ where
     }
 }
 
+/// Number of drivers using the FMC.
+static USERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Enable the FMC for a driver. Only the first one resets it, the others must keep the
+/// configuration of the banks in use.
+fn enable<T: Instance>() {
+    critical_section::with(|cs| {
+        if USERS.fetch_add(1, Ordering::Relaxed) == 0 {
+            rcc::enable_and_reset_with_cs::<T>(cs);
+        } else {
+            rcc::enable_with_cs::<T>(cs);
+        }
+    });
+}
+
+/// Release the FMC of a driver, disabling it once no driver uses it.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+fn disable<T: Instance>() {
+    critical_section::with(|cs| {
+        if USERS.fetch_sub(1, Ordering::Relaxed) == 1 {
+            rcc::disable_with_cs::<T>(cs);
+        }
+    });
+}
+
+fn memory_controller_enable<T: Instance>() {
+    // fmc v1 and v2 does not have the fmcen bit
+    // fsmc v1, v2 and v3 does not have the fmcen bit
+    // This is a "not" because it is expected that all future versions have this bit
+    #[cfg(not(any(fmc_v1x3, fmc_v2x1, fsmc_v1x0, fsmc_v1x3, fmc_v4, fmc_n6)))]
+    T::REGS.bcr1().modify(|r| r.set_fmcen(true));
+    #[cfg(any(fmc_v4, fmc_n6))]
+    T::REGS.nor_psram().bcr1().modify(|r| r.set_fmcen(true));
+}
+
+/// Banks of an FMC, returned by [`Fmc::split()`].
+///
+/// Each memory driver takes the bank it uses instead of the whole FMC.
+pub struct FmcBanks<'d, T: Instance> {
+    /// NOR/PSRAM/SRAM bank 1, for [`ChipSelect::ne1()`].
+    #[cfg(not(any(fmc_v4, fmc_n6)))]
+    pub nor_sram1: NorSramBankPeri<'d, T, Ne1>,
+    /// NOR/PSRAM/SRAM bank 2, for [`ChipSelect::ne2()`].
+    #[cfg(not(any(fmc_v4, fmc_n6)))]
+    pub nor_sram2: NorSramBankPeri<'d, T, Ne2>,
+    /// NOR/PSRAM/SRAM bank 3, for [`ChipSelect::ne3()`].
+    #[cfg(not(any(fmc_v4, fmc_n6)))]
+    pub nor_sram3: NorSramBankPeri<'d, T, Ne3>,
+    /// NOR/PSRAM/SRAM bank 4, for [`ChipSelect::ne4()`].
+    #[cfg(not(any(fmc_v4, fmc_n6)))]
+    pub nor_sram4: NorSramBankPeri<'d, T, Ne4>,
+    /// SDRAM banks, for the SDRAM constructors of [`Fmc`].
+    pub sdram: SdramBankPeri<'d, T>,
+}
+
+/// NOR/PSRAM/SRAM bank of an FMC, selected by chip select `B`.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub struct NorSramBankPeri<'d, T: Instance, B> {
+    _phantom: PhantomData<(&'d mut T, B)>,
+}
+
+/// Chip select NE1.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub enum Ne1 {}
+/// Chip select NE2.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub enum Ne2 {}
+/// Chip select NE3.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub enum Ne3 {}
+/// Chip select NE4.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub enum Ne4 {}
+
+/// SDRAM banks of an FMC.
+pub struct SdramBankPeri<'d, T: Instance> {
+    _phantom: PhantomData<&'d mut T>,
+}
+
+trait SealedSdramPeri {}
+
+/// FMC usable by the SDRAM constructors of [`Fmc`]: the whole peripheral, or
+/// [`FmcBanks::sdram`].
+#[allow(private_bounds)]
+pub trait SdramPeri<'d, T: Instance>: SealedSdramPeri {}
+
+impl<'d, T: Instance> SealedSdramPeri for Peri<'d, T> {}
+impl<'d, T: Instance> SdramPeri<'d, T> for Peri<'d, T> {}
+impl<'d, T: Instance> SealedSdramPeri for SdramBankPeri<'d, T> {}
+impl<'d, T: Instance> SdramPeri<'d, T> for SdramBankPeri<'d, T> {}
+
 macro_rules! config_pins {
     ($($pin:ident),*) => {
                 $(
@@ -80,6 +194,204 @@ macro_rules! config_pins {
     };
 }
 
+/// NOR/PSRAM/SRAM bank, selected by one of the NE1..NE4 chip selects.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NorSramBank {
+    /// Bank 1, NE1.
+    Bank1 = 0,
+    /// Bank 2, NE2.
+    Bank2 = 1,
+    /// Bank 3, NE3.
+    Bank3 = 2,
+    /// Bank 4, NE4.
+    Bank4 = 3,
+}
+
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+impl NorSramBank {
+    /// Address the bank is mapped at.
+    pub const fn base_address(&self) -> usize {
+        0x6000_0000 + *self as usize * 0x0400_0000
+    }
+
+    /// Configure and enable the bank.
+    fn configure<T: Instance>(&self, control: &BankControl) {
+        // BCR1 has its own register type, with the global FMCEN and CCLKEN bits on top.
+        macro_rules! configure {
+            ($w:ident) => {{
+                $w.set_mbken(true);
+                $w.set_muxen(false);
+                $w.set_mtyp(vals::Mtyp::from_bits(control.memory_type));
+                $w.set_mwid(vals::Mwid::from_bits(control.width));
+                $w.set_faccen(control.flash_access);
+                $w.set_bursten(control.burst);
+                $w.set_waitpol(vals::Waitpol::from_bits(control.wait_active_high as u8));
+                $w.set_waitcfg(vals::Waitcfg::from_bits(control.wait_during as u8));
+                $w.set_wren(control.write);
+                $w.set_waiten(control.wait);
+                $w.set_extmod(control.extended_mode);
+                $w.set_asyncwait(control.async_wait);
+                $w.set_cpsize(vals::Cpsize::from_bits(control.cram_page_size));
+                $w.set_cburstrw(control.burst_write);
+            }};
+        }
+
+        match self {
+            NorSramBank::Bank1 => T::REGS.bcr1().modify(|w| configure!(w)),
+            bank => T::REGS.bcr(*bank as usize - 1).modify(|w| configure!(w)),
+        }
+    }
+
+    /// Disable the bank.
+    fn disable<T: Instance>(&self) {
+        match self {
+            NorSramBank::Bank1 => T::REGS.bcr1().modify(|w| w.set_mbken(false)),
+            bank => T::REGS.bcr(*bank as usize - 1).modify(|w| w.set_mbken(false)),
+        }
+    }
+
+    /// Set the read timing, or the timing of all accesses without extended mode.
+    ///
+    /// `clkdiv` and `datlat` are only used by synchronous accesses.
+    fn set_read_timing<T: Instance>(&self, cycles: AccessCycles, clkdiv: u8, datlat: u8) {
+        T::REGS.btr(*self as usize).write(|w| {
+            w.set_addset(cycles.address_setup);
+            w.set_datast(cycles.data_phase);
+            w.set_busturn(cycles.bus_turnaround);
+            w.set_clkdiv(clkdiv);
+            w.set_datlat(datlat);
+            w.set_accmod(vals::Accmod::from_bits(0));
+        });
+    }
+
+    /// Set the write timing, used in extended mode.
+    fn set_write_timing<T: Instance>(&self, cycles: AccessCycles) {
+        T::REGS.bwtr(*self as usize).write(|w| {
+            w.set_addset(cycles.address_setup);
+            w.set_datast(cycles.data_phase);
+            w.set_busturn(cycles.bus_turnaround);
+            w.set_accmod(vals::Accmod::from_bits(0));
+        });
+    }
+}
+
+/// BCR settings of a NOR/PSRAM/SRAM bank.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+#[derive(Default)]
+struct BankControl {
+    /// MTYP: 0 for SRAM, 1 for PSRAM, 2 for NOR flash.
+    memory_type: u8,
+    /// MWID: 0 for 8 bits, 1 for 16 bits.
+    width: u8,
+    flash_access: bool,
+    burst: bool,
+    wait_active_high: bool,
+    /// WAIT is asserted during the wait state, instead of one cycle before.
+    wait_during: bool,
+    write: bool,
+    wait: bool,
+    /// Separate read and write timings.
+    extended_mode: bool,
+    async_wait: bool,
+    cram_page_size: u8,
+    burst_write: bool,
+}
+
+/// Asynchronous access timing (access mode A), in FMC kernel clock cycles.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+#[derive(Clone, Copy)]
+struct AccessCycles {
+    address_setup: u8,
+    data_phase: u8,
+    bus_turnaround: u8,
+}
+
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+impl AccessCycles {
+    fn from_ns(address_setup_ns: u32, data_phase_ns: u32, bus_turnaround_ns: u32, kernel_clock_hz: u32) -> Self {
+        Self {
+            address_setup: ns_to_cycles(address_setup_ns, kernel_clock_hz).min(15) as u8,
+            data_phase: ns_to_cycles(data_phase_ns, kernel_clock_hz).clamp(1, 255) as u8,
+            bus_turnaround: ns_to_cycles(bus_turnaround_ns, kernel_clock_hz).min(15) as u8,
+        }
+    }
+}
+
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+fn ns_to_cycles(ns: u32, kernel_clock_hz: u32) -> u32 {
+    (ns as u64 * kernel_clock_hz as u64).div_ceil(1_000_000_000) as u32
+}
+
+/// Chip select of a NOR/PSRAM/SRAM bank, with its pin.
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+pub struct ChipSelect<'d, T: Instance> {
+    bank: NorSramBank,
+    _pin: PhantomData<&'d mut T>,
+}
+
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+macro_rules! chip_select_constructor {
+    ($name:ident, $signal:ident, $bank:ident, $marker:ident) => {
+        #[doc = concat!("Use [`NorSramBank::", stringify!($bank), "`], selected by `", stringify!($name), "`.")]
+        pub fn $name(_bank: NorSramBankPeri<'d, T, $marker>, pin: Peri<'d, impl $signal<T>>) -> Self {
+            critical_section::with(|_| config_pins!(pin));
+            Self {
+                bank: NorSramBank::$bank,
+                _pin: PhantomData,
+            }
+        }
+    };
+}
+
+#[cfg(not(any(fmc_v4, fmc_n6)))]
+impl<'d, T: Instance> ChipSelect<'d, T> {
+    chip_select_constructor!(ne1, NE1Pin, Bank1, Ne1);
+    chip_select_constructor!(ne2, NE2Pin, Bank2, Ne2);
+    chip_select_constructor!(ne3, NE3Pin, Bank3, Ne3);
+    chip_select_constructor!(ne4, NE4Pin, Bank4, Ne4);
+
+    /// Bank selected by this chip select.
+    pub fn bank(&self) -> NorSramBank {
+        self.bank
+    }
+}
+
+/// Size of an SDRAM device in bytes, computed from its configuration.
+pub const fn sdram_size<CHIP: stm32_fmc::SdramChip>() -> usize {
+    let config = CHIP::CONFIG;
+    (1 << (config.column_bits + config.row_bits))
+        * config.internal_banks as usize
+        * (config.memory_data_width as usize / 8)
+}
+
+/// Initialise an SDRAM device and get its memory.
+///
+/// This runs the power-up sequence of the device: clock enable, precharge all, auto-refresh,
+/// load mode register, then sets the refresh rate from the timing of `CHIP`. The returned
+/// slice covers the whole device, see [`sdram_size()`]. Its content is undefined.
+///
+/// The FMC is consumed for `'static`, so the mapping stays valid for the lifetime of the
+/// slice.
+///
+/// # Memory attributes
+///
+/// On Cortex-M7 chips the default memory map makes the SDRAM banks (`0xC000_0000` and
+/// `0xD000_0000`) device memory, which is not executable and faults on unaligned accesses. Before
+/// using it as a heap or framebuffer, configure an MPU region over it as normal memory, either
+/// write-back cacheable (then clean the D-cache before DMA or LTDC reads), or non-cacheable for
+/// buffers shared with other bus masters.
+pub fn init_sdram<T: Instance, CHIP: stm32_fmc::SdramChip>(
+    mut sdram: stm32_fmc::Sdram<Fmc<'static, T>, CHIP>,
+    delay: &mut impl embedded_hal_1::delay::DelayNs,
+) -> &'static mut [u32] {
+    let ptr = sdram.init(delay);
+    // SAFETY: the device is mapped at `ptr`, and `sdram` owning the FMC for `'static` is
+    // consumed, so this is the only reference to it.
+    unsafe { core::slice::from_raw_parts_mut(ptr, sdram_size::<CHIP>() / 4) }
+}
+
 macro_rules! fmc_sdram_constructor {
     ($name:ident: (
         bank: $bank:expr,
@@ -91,7 +403,7 @@ macro_rules! fmc_sdram_constructor {
     )) => {
         /// Create a new FMC instance.
         pub fn $name<CHIP: stm32_fmc::SdramChip>(
-            _instance: Peri<'d, T>,
+            _instance: impl SdramPeri<'d, T>,
             $($addr_pin_name: Peri<'d, impl $addr_signal<T>>),*,
             $($ba_pin_name: Peri<'d, impl $ba_signal<T>>),*,
             $($d_pin_name: Peri<'d, impl $d_signal<T>>),*,
@@ -0,0 +1,382 @@
+//! NOR flash, PSRAM and SRAM on the NOR/PSRAM/SRAM banks
+//!
+//! The memory is mapped at [`NorSramBank::base_address()`](super::NorSramBank::base_address),
+//! so code can execute from NOR flash and PSRAM can be used as working RAM.
+//!
+//! Asynchronous accesses work with all devices. PSRAM (CellularRAM) and burst NOR flash can also
+//! run synchronous bursts clocked by the CLK pin, with a fixed latency and an optional WAIT
+//! signal. The device must be switched to synchronous mode first, by writing its configuration
+//! register in asynchronous mode, as described in its datasheet.
+//!
+//! Timings are given in nanoseconds and converted to FMC kernel clock cycles. When the kernel
+//! clock (HCLK on most chips) changes, call [`NorSram::retime()`] to keep the same timings.
+
+use core::marker::PhantomData;
+
+use super::{
+    A0Pin, A1Pin, A2Pin, A3Pin, A4Pin, A5Pin, A6Pin, A7Pin, A8Pin, A9Pin, A10Pin, A11Pin, A12Pin, A13Pin, A14Pin,
+    A15Pin, A16Pin, A17Pin, A18Pin, A19Pin, A20Pin, A21Pin, A22Pin, A23Pin, A24Pin, A25Pin, AccessCycles, BankControl,
+    ChipSelect, ClkPin, D0Pin, D1Pin, D2Pin, D3Pin, D4Pin, D5Pin, D6Pin, D7Pin, D8Pin, D9Pin, D10Pin, D11Pin, D12Pin,
+    D13Pin, D14Pin, D15Pin, Instance, NBL0Pin, NBL1Pin, NLPin, NOEPin, NWEPin, NWaitPin,
+};
+use crate::Peri;
+use crate::gpio::{AfType, OutputType, Pull, Speed};
+use crate::time::Hertz;
+
+/// Memory device type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MemoryType {
+    /// SRAM.
+    Sram = 0,
+    /// PSRAM (CellularRAM).
+    Psram = 1,
+    /// NOR flash.
+    Nor = 2,
+}
+
+/// Data bus width.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataWidth {
+    /// 8 bits.
+    Bits8 = 0,
+    /// 16 bits.
+    Bits16 = 1,
+}
+
+/// CRAM page size. Synchronous bursts are split at page boundaries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CramPageSize {
+    /// Bursts are not split.
+    None = 0,
+    /// 128 bytes.
+    Bytes128 = 1,
+    /// 256 bytes.
+    Bytes256 = 2,
+    /// 512 bytes.
+    Bytes512 = 3,
+    /// 1024 bytes.
+    Bytes1024 = 4,
+}
+
+/// Active level of the WAIT signal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WaitPolarity {
+    /// WAIT is active low.
+    ActiveLow,
+    /// WAIT is active high.
+    ActiveHigh,
+}
+
+/// WAIT signal configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WaitConfig {
+    /// Active level.
+    pub polarity: WaitPolarity,
+    /// In synchronous mode, WAIT is asserted one clock cycle before the wait state, instead of
+    /// during it.
+    pub one_cycle_early: bool,
+    /// Also honor WAIT during asynchronous accesses.
+    pub asynchronous: bool,
+}
+
+/// Asynchronous access timing, in nanoseconds.
+///
+/// The delays are rounded up to whole FMC kernel clock cycles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AsyncTiming {
+    /// Time from the address and chip select to the start of NOE or NWE, at most 15 cycles.
+    pub address_setup_ns: u32,
+    /// Duration of NOE or NWE, from 1 to 255 cycles. For reads, this is the access time.
+    pub data_phase_ns: u32,
+    /// Time after an access before the next one, at most 15 cycles.
+    pub bus_turnaround_ns: u32,
+}
+
+impl AsyncTiming {
+    // Access mode A.
+    fn cycles(&self, kernel_clock_hz: u32) -> AccessCycles {
+        AccessCycles::from_ns(
+            self.address_setup_ns,
+            self.data_phase_ns,
+            self.bus_turnaround_ns,
+            kernel_clock_hz,
+        )
+    }
+}
+
+/// Synchronous burst configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SyncConfig {
+    /// Maximum frequency of the CLK pin. It runs at the kernel clock divided by 2 to 16.
+    pub max_clock: Hertz,
+    /// Number of CLK cycles before the first data, from 2 to 17. This is the latency programmed
+    /// in the configuration register of the device.
+    pub latency: u8,
+    /// Use synchronous bursts for writes too, otherwise writes are asynchronous.
+    pub write_burst: bool,
+    /// Split bursts at CRAM page boundaries.
+    pub page_size: CramPageSize,
+}
+
+/// Access mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessMode {
+    /// Asynchronous accesses, with separate read and write timings.
+    Asynchronous {
+        /// Read timing.
+        read: AsyncTiming,
+        /// Write timing.
+        write: AsyncTiming,
+    },
+    /// Synchronous burst reads.
+    ///
+    /// `asynchronous` is used for asynchronous writes, and for all accesses before the device
+    /// is switched to synchronous mode.
+    Synchronous {
+        /// Burst configuration.
+        sync: SyncConfig,
+        /// Timing of asynchronous accesses.
+        asynchronous: AsyncTiming,
+    },
+}
+
+/// NOR/PSRAM/SRAM configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NorSramConfig {
+    /// Memory device type.
+    pub memory_type: MemoryType,
+    /// Access mode and timing.
+    pub access: AccessMode,
+    /// WAIT signal, or `None` to ignore it.
+    pub wait: Option<WaitConfig>,
+    /// Allow writes. Disable for NOR flash used read-only.
+    pub write_enable: bool,
+}
+
+/// Pins of a NOR/PSRAM/SRAM device, apart from the chip select.
+///
+/// Start from the data and strobe pins, then add the address and other pins used by the
+/// device.
+pub struct NorSramPins<'d, T: Instance> {
+    width: DataWidth,
+    _pins: PhantomData<&'d mut T>,
+}
+
+macro_rules! nor_sram_pin {
+    ($($name:ident: $signal:ident),*) => {
+        $(
+            #[doc = concat!("Add the `", stringify!($name), "` pin.")]
+            pub fn $name(self, pin: Peri<'d, impl $signal<T>>) -> Self {
+                critical_section::with(|_| {
+                    set_as_af!(pin, AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::Up));
+                });
+                self
+            }
+        )*
+    };
+}
+
+impl<'d, T: Instance> NorSramPins<'d, T> {
+    /// 8-bit data bus.
+    pub fn new_8bit(
+        d0: Peri<'d, impl D0Pin<T>>,
+        d1: Peri<'d, impl D1Pin<T>>,
+        d2: Peri<'d, impl D2Pin<T>>,
+        d3: Peri<'d, impl D3Pin<T>>,
+        d4: Peri<'d, impl D4Pin<T>>,
+        d5: Peri<'d, impl D5Pin<T>>,
+        d6: Peri<'d, impl D6Pin<T>>,
+        d7: Peri<'d, impl D7Pin<T>>,
+        noe: Peri<'d, impl NOEPin<T>>,
+        nwe: Peri<'d, impl NWEPin<T>>,
+    ) -> Self {
+        critical_section::with(|_| {
+            let af = AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::Up);
+            set_as_af!(d0, af);
+            set_as_af!(d1, af);
+            set_as_af!(d2, af);
+            set_as_af!(d3, af);
+            set_as_af!(d4, af);
+            set_as_af!(d5, af);
+            set_as_af!(d6, af);
+            set_as_af!(d7, af);
+            set_as_af!(noe, af);
+            set_as_af!(nwe, af);
+        });
+
+        Self {
+            width: DataWidth::Bits8,
+            _pins: PhantomData,
+        }
+    }
+
+    /// 16-bit data bus, with byte lane selects.
+    pub fn new_16bit(
+        d0: Peri<'d, impl D0Pin<T>>,
+        d1: Peri<'d, impl D1Pin<T>>,
+        d2: Peri<'d, impl D2Pin<T>>,
+        d3: Peri<'d, impl D3Pin<T>>,
+        d4: Peri<'d, impl D4Pin<T>>,
+        d5: Peri<'d, impl D5Pin<T>>,
+        d6: Peri<'d, impl D6Pin<T>>,
+        d7: Peri<'d, impl D7Pin<T>>,
+        d8: Peri<'d, impl D8Pin<T>>,
+        d9: Peri<'d, impl D9Pin<T>>,
+        d10: Peri<'d, impl D10Pin<T>>,
+        d11: Peri<'d, impl D11Pin<T>>,
+        d12: Peri<'d, impl D12Pin<T>>,
+        d13: Peri<'d, impl D13Pin<T>>,
+        d14: Peri<'d, impl D14Pin<T>>,
+        d15: Peri<'d, impl D15Pin<T>>,
+        noe: Peri<'d, impl NOEPin<T>>,
+        nwe: Peri<'d, impl NWEPin<T>>,
+        nbl0: Peri<'d, impl NBL0Pin<T>>,
+        nbl1: Peri<'d, impl NBL1Pin<T>>,
+    ) -> Self {
+        critical_section::with(|_| {
+            let af = AfType::output_pull(OutputType::PushPull, Speed::VeryHigh, Pull::Up);
+            set_as_af!(d0, af);
+            set_as_af!(d1, af);
+            set_as_af!(d2, af);
+            set_as_af!(d3, af);
+            set_as_af!(d4, af);
+            set_as_af!(d5, af);
+            set_as_af!(d6, af);
+            set_as_af!(d7, af);
+            set_as_af!(d8, af);
+            set_as_af!(d9, af);
+            set_as_af!(d10, af);
+            set_as_af!(d11, af);
+            set_as_af!(d12, af);
+            set_as_af!(d13, af);
+            set_as_af!(d14, af);
+            set_as_af!(d15, af);
+            set_as_af!(noe, af);
+            set_as_af!(nwe, af);
+            set_as_af!(nbl0, af);
+            set_as_af!(nbl1, af);
+        });
+
+        Self {
+            width: DataWidth::Bits16,
+            _pins: PhantomData,
+        }
+    }
+
+    nor_sram_pin!(
+        a0: A0Pin, a1: A1Pin, a2: A2Pin, a3: A3Pin, a4: A4Pin, a5: A5Pin, a6: A6Pin, a7: A7Pin, a8: A8Pin,
+        a9: A9Pin, a10: A10Pin, a11: A11Pin, a12: A12Pin, a13: A13Pin, a14: A14Pin, a15: A15Pin, a16: A16Pin,
+        a17: A17Pin, a18: A18Pin, a19: A19Pin, a20: A20Pin, a21: A21Pin, a22: A22Pin, a23: A23Pin, a24: A24Pin,
+        a25: A25Pin, clk: ClkPin, nwait: NWaitPin, nadv: NLPin
+    );
+}
+
+/// NOR/PSRAM/SRAM driver.
+pub struct NorSram<'d, T: Instance> {
+    chip_select: ChipSelect<'d, T>,
+    width: DataWidth,
+    config: NorSramConfig,
+}
+
+impl<'d, T: Instance> NorSram<'d, T> {
+    /// Create a NOR/PSRAM/SRAM driver, and enable the bank.
+    pub fn new(pins: NorSramPins<'d, T>, chip_select: ChipSelect<'d, T>, config: NorSramConfig) -> Self {
+        super::enable::<T>();
+        super::memory_controller_enable::<T>();
+
+        let mut this = Self {
+            chip_select,
+            width: pins.width,
+            config,
+        };
+        this.retime();
+        this
+    }
+
+    /// Address the memory is mapped at.
+    pub fn base_address(&self) -> *mut u8 {
+        self.chip_select.bank().base_address() as *mut u8
+    }
+
+    /// Current configuration.
+    pub fn config(&self) -> &NorSramConfig {
+        &self.config
+    }
+
+    /// Change the configuration, e.g. to switch to synchronous mode once the configuration
+    /// register of the device has been written.
+    pub fn set_config(&mut self, config: NorSramConfig) {
+        self.config = config;
+        self.retime();
+    }
+
+    /// Recompute the timing registers from the current FMC kernel clock.
+    ///
+    /// Call this after changing the clocks. When running from the memory, call it from code
+    /// located elsewhere, and lower the clock only after retiming, raise it only before.
+    pub fn retime(&mut self) {
+        let hz = <T as crate::rcc::SealedRccPeripheral>::frequency().0;
+        let bank = self.chip_select.bank();
+        let config = &self.config;
+
+        let mut control = BankControl {
+            memory_type: config.memory_type as u8,
+            width: self.width as u8,
+            flash_access: config.memory_type == MemoryType::Nor,
+            write: config.write_enable,
+            ..Default::default()
+        };
+        if let Some(wait) = config.wait {
+            control.wait = true;
+            control.wait_active_high = wait.polarity == WaitPolarity::ActiveHigh;
+            control.wait_during = !wait.one_cycle_early;
+            control.async_wait = wait.asynchronous;
+        }
+
+        match config.access {
+            AccessMode::Asynchronous { read, write } => {
+                control.extended_mode = true;
+                bank.set_read_timing::<T>(read.cycles(hz), 0, 0);
+                bank.set_write_timing::<T>(write.cycles(hz));
+            }
+            AccessMode::Synchronous { sync, asynchronous } => {
+                assert!((2..=17).contains(&sync.latency));
+                control.burst = true;
+                control.cram_page_size = sync.page_size as u8;
+                control.burst_write = sync.write_burst;
+                // CLK runs at the kernel clock divided by CLKDIV + 1, at least 2.
+                let clkdiv = hz.div_ceil(sync.max_clock.0).clamp(2, 16) - 1;
+                let datlat = sync.latency - 2;
+                bank.set_read_timing::<T>(asynchronous.cycles(hz), clkdiv as u8, datlat);
+            }
+        }
+        bank.configure::<T>(&control);
+    }
+
+    /// Frequency of the CLK pin in synchronous mode, with the current kernel clock.
+    pub fn memory_clock(&self) -> Option<Hertz> {
+        match self.config.access {
+            AccessMode::Asynchronous { .. } => None,
+            AccessMode::Synchronous { sync, .. } => {
+                let hz = <T as crate::rcc::SealedRccPeripheral>::frequency().0;
+                Some(Hertz(hz / hz.div_ceil(sync.max_clock.0).clamp(2, 16)))
+            }
+        }
+    }
+}
+
+impl<'d, T: Instance> Drop for NorSram<'d, T> {
+    fn drop(&mut self) {
+        self.chip_select.bank().disable::<T>();
+        super::disable::<T>();
+    }
+}
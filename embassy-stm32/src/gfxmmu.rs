@@ -0,0 +1,286 @@
+//! Chrom-GRC graphic memory management unit (GFXMMU)
+//!
+//! The GFXMMU maps up to four virtual framebuffers onto physical memory, storing only the
+//! visible part of each line. For a round display, this saves about 20% of the framebuffer
+//! memory compared to the bounding square, and much more for other shapes.
+//!
+//! Each virtual buffer has 1024 lines of 192 or 256 blocks of 16 bytes. The look-up table (LUT)
+//! holds, for each line, the first and last visible blocks. Drawing code and the LTDC use the
+//! virtual buffer address from [`Gfxmmu::virtual_buffer()`], with a line pitch of
+//! [`BlockMode::line_bytes()`].
+
+use embassy_hal_internal::PeripheralType;
+
+use crate::{Peri, peripherals, rcc};
+
+/// Number of lines of a virtual buffer.
+pub const LINES: usize = 1024;
+
+/// Size of a block, in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+#[cfg(stm32l4)]
+const VIRTUAL_BUFFER_BASE: usize = 0x3000_0000;
+#[cfg(not(stm32l4))]
+const VIRTUAL_BUFFER_BASE: usize = 0x2400_0000;
+const VIRTUAL_BUFFER_SIZE: usize = 0x40_0000;
+
+/// Number of blocks per virtual line.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlockMode {
+    /// 192 blocks, 3072 bytes per line.
+    Blocks192,
+    /// 256 blocks, 4096 bytes per line.
+    Blocks256,
+}
+
+impl BlockMode {
+    /// Length of a virtual line, in bytes.
+    pub const fn line_bytes(&self) -> usize {
+        match self {
+            BlockMode::Blocks192 => 192 * BLOCK_SIZE,
+            BlockMode::Blocks256 => 256 * BLOCK_SIZE,
+        }
+    }
+
+    const fn blocks(&self) -> usize {
+        self.line_bytes() / BLOCK_SIZE
+    }
+}
+
+/// One of the four virtual buffers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VirtualBuffer {
+    /// Virtual buffer 0.
+    Buffer0 = 0,
+    /// Virtual buffer 1.
+    Buffer1 = 1,
+    /// Virtual buffer 2.
+    Buffer2 = 2,
+    /// Virtual buffer 3.
+    Buffer3 = 3,
+}
+
+/// Visible blocks of a virtual line, inclusive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LineBlocks {
+    /// First visible block.
+    pub first: u8,
+    /// Last visible block.
+    pub last: u8,
+}
+
+impl LineBlocks {
+    /// Number of visible blocks.
+    pub const fn len(&self) -> usize {
+        self.last as usize - self.first as usize + 1
+    }
+}
+
+/// GFXMMU configuration.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Number of blocks per virtual line.
+    pub block_mode: BlockMode,
+    /// Value read from the invisible parts of the virtual buffers.
+    pub default_value: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            block_mode: BlockMode::Blocks256,
+            default_value: 0,
+        }
+    }
+}
+
+/// GFXMMU error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// An access went past the end of the physical buffer of a virtual buffer.
+    BufferOverflow(VirtualBuffer),
+    /// An AHB master access failed.
+    AhbMasterError,
+    /// The LUT doesn't fit a virtual buffer, or a line is outside of a virtual line.
+    InvalidLut,
+}
+
+/// GFXMMU driver.
+pub struct Gfxmmu<'d, T: Instance> {
+    _peri: Peri<'d, T>,
+    block_mode: BlockMode,
+}
+
+impl<'d, T: Instance> Gfxmmu<'d, T> {
+    /// Create a new GFXMMU driver.
+    ///
+    /// All the lines of the LUT are disabled until [`Self::load_lut()`] is called.
+    pub fn new(peri: Peri<'d, T>, config: Config) -> Self {
+        rcc::enable_and_reset::<T>();
+
+        let r = T::regs();
+        r.cr().write(|w| w.set_bm192(config.block_mode == BlockMode::Blocks192));
+        r.dvr().write(|w| w.set_dv(config.default_value));
+
+        Self {
+            _peri: peri,
+            block_mode: config.block_mode,
+        }
+    }
+
+    /// Number of blocks per virtual line.
+    pub fn block_mode(&self) -> BlockMode {
+        self.block_mode
+    }
+
+    /// Load the LUT, shared by all the virtual buffers.
+    ///
+    /// `lines[n]` gives the visible blocks of line `n`, or `None` for an invisible line. The
+    /// lines past the end of `lines` are invisible. The visible blocks are packed one after the
+    /// other in the physical buffers, and their total size in bytes is returned.
+    pub fn load_lut(&mut self, lines: &[Option<LineBlocks>]) -> Result<usize, Error> {
+        if lines.len() > LINES {
+            return Err(Error::InvalidLut);
+        }
+
+        let r = T::regs();
+        let line_bytes = self.block_mode.line_bytes() as i32;
+        let mut physical_offset = 0i32;
+        for n in 0..LINES {
+            match lines.get(n).copied().flatten() {
+                Some(blocks) => {
+                    if blocks.first > blocks.last || blocks.last as usize >= self.block_mode.blocks() {
+                        return Err(Error::InvalidLut);
+                    }
+
+                    // The physical address of a block is its virtual offset plus the line offset.
+                    let virtual_offset = n as i32 * line_bytes + blocks.first as i32 * BLOCK_SIZE as i32;
+                    let line_offset = physical_offset - virtual_offset;
+                    r.lutl(n).write(|w| {
+                        w.set_en(true);
+                        w.set_fvb(blocks.first);
+                        w.set_lvb(blocks.last);
+                    });
+                    r.luth(n).write(|w| w.set_lo(((line_offset >> 4) as u32) & 0x3_FFFF));
+
+                    physical_offset += (blocks.len() * BLOCK_SIZE) as i32;
+                }
+                None => r.lutl(n).write(|w| w.set_en(false)),
+            }
+        }
+
+        Ok(physical_offset as usize)
+    }
+
+    /// Set the physical memory backing `buffer`.
+    ///
+    /// `physical` must be 16-byte aligned and hold the number of bytes returned by
+    /// [`Self::load_lut()`].
+    pub fn set_physical_buffer(&mut self, buffer: VirtualBuffer, physical: *mut u8) {
+        let addr = physical as u32;
+        assert!(addr % BLOCK_SIZE as u32 == 0, "Physical buffer must be 16-byte aligned");
+
+        T::regs().bcr(buffer as usize).write(|w| {
+            w.set_pbba(addr >> 23);
+            w.set_pbo((addr >> 4) & 0x7_FFFF);
+        });
+    }
+
+    /// Get the address of a virtual buffer.
+    ///
+    /// Pass it to [`Ltdc::init_buffer()`](crate::ltdc::Ltdc::init_buffer) or draw into it. Line
+    /// `n` starts at `n * block_mode.line_bytes()`.
+    pub fn virtual_buffer(&self, buffer: VirtualBuffer) -> *mut u8 {
+        (VIRTUAL_BUFFER_BASE + buffer as usize * VIRTUAL_BUFFER_SIZE) as *mut u8
+    }
+
+    /// Check and clear the error flags.
+    pub fn check_errors(&mut self) -> Result<(), Error> {
+        let r = T::regs();
+        let sr = r.sr().read();
+
+        if sr.amef() {
+            r.fcr().write(|w| w.set_camef(true));
+            return Err(Error::AhbMasterError);
+        }
+
+        for buffer in [
+            VirtualBuffer::Buffer0,
+            VirtualBuffer::Buffer1,
+            VirtualBuffer::Buffer2,
+            VirtualBuffer::Buffer3,
+        ] {
+            if sr.bof(buffer as usize) {
+                r.fcr().write(|w| w.set_cbof(buffer as usize, true));
+                return Err(Error::BufferOverflow(buffer));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the LUT of a round display of `diameter` pixels, with `bytes_per_pixel`.
+///
+/// The visible blocks of each line cover the pixels inside the circle. `lines` must hold at
+/// least `diameter` entries, and a line of the display must fit a virtual line.
+pub fn round_display_lut(diameter: u16, bytes_per_pixel: usize, lines: &mut [Option<LineBlocks>]) {
+    let d = diameter as u32;
+    assert!(lines.len() >= d as usize);
+
+    for (y, line) in lines.iter_mut().enumerate() {
+        if y as u32 >= d {
+            *line = None;
+            continue;
+        }
+
+        // Half chord at the center of the line, in doubled coordinates to stay in integers.
+        let dy = 2 * y as u32 + 1;
+        let dy = dy.abs_diff(d);
+        let half = isqrt(d * d - dy * dy).div_ceil(2);
+
+        let x0 = (d / 2).saturating_sub(half) as usize;
+        let x1 = ((d / 2 + half).min(d) as usize).max(x0 + 1);
+        *line = Some(LineBlocks {
+            first: (x0 * bytes_per_pixel / BLOCK_SIZE) as u8,
+            last: ((x1 * bytes_per_pixel - 1) / BLOCK_SIZE) as u8,
+        });
+    }
+}
+
+fn isqrt(n: u32) -> u32 {
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+trait SealedInstance: crate::rcc::RccPeripheral {
+    fn regs() -> crate::pac::gfxmmu::Gfxmmu;
+}
+
+/// GFXMMU instance.
+#[allow(private_bounds)]
+pub trait Instance: SealedInstance + PeripheralType + 'static + Send {}
+
+foreach_peripheral!(
+    (gfxmmu, $inst:ident) => {
+        impl SealedInstance for peripherals::$inst {
+            fn regs() -> crate::pac::gfxmmu::Gfxmmu {
+                crate::pac::$inst
+            }
+        }
+
+        impl Instance for peripherals::$inst {}
+    };
+);
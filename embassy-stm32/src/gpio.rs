@@ -246,6 +246,95 @@ impl<'d> Flex<'d> {
             self.set_low()
         }
     }
+
+    /// Change the output speed.
+    ///
+    #[cfg_attr(
+        gpio_v1,
+        doc = "On this chip the speed is part of the pin mode, so it only takes effect while the pin is an output."
+    )]
+    pub fn set_speed(&mut self, speed: Speed) {
+        critical_section::with(|_| {
+            let r = self.pin.block();
+            let n = self.pin.pin() as usize;
+            #[cfg(gpio_v1)]
+            if r.cr(n / 8).read().mode(n % 8) != vals::Mode::Input {
+                r.cr(n / 8).modify(|w| w.set_mode(n % 8, speed.to_mode()));
+            }
+            #[cfg(gpio_v2)]
+            r.ospeedr().modify(|w| w.set_ospeedr(n, speed.to_ospeedr()));
+        });
+    }
+
+    /// Get the output speed.
+    #[cfg(gpio_v2)]
+    pub fn speed(&self) -> Speed {
+        let n = self.pin.pin() as usize;
+        match self.pin.block().ospeedr().read().ospeedr(n) {
+            vals::Ospeedr::LowSpeed => Speed::Low,
+            vals::Ospeedr::MediumSpeed => Speed::Medium,
+            #[cfg(not(syscfg_f0))]
+            vals::Ospeedr::HighSpeed => Speed::High,
+            // `x0` is low speed on F0.
+            #[cfg(syscfg_f0)]
+            vals::Ospeedr::HighSpeed => Speed::Low,
+            vals::Ospeedr::VeryHighSpeed => Speed::VeryHigh,
+        }
+    }
+
+    /// Change the internal weak pull-up and pull-down resistors.
+    #[cfg(gpio_v2)]
+    pub fn set_pull(&mut self, pull: Pull) {
+        critical_section::with(|_| {
+            let n = self.pin.pin() as usize;
+            self.pin.block().pupdr().modify(|w| w.set_pupdr(n, pull.to_pupdr()));
+        });
+    }
+
+    /// Get the internal weak pull-up and pull-down resistor configuration.
+    #[cfg(not(stm32c5))]
+    pub fn pull(&self) -> Pull {
+        self.pin.pull()
+    }
+
+    /// Change the output type.
+    ///
+    /// This switches between push-pull and open-drain output without changing the mode.
+    #[cfg(gpio_v2)]
+    pub fn set_output_type(&mut self, output_type: OutputType) {
+        critical_section::with(|_| {
+            let n = self.pin.pin() as usize;
+            self.pin.block().otyper().modify(|w| w.set_ot(n, output_type.to_ot()));
+        });
+    }
+
+    /// Get the output type.
+    #[cfg(gpio_v2)]
+    pub fn output_type(&self) -> OutputType {
+        let n = self.pin.pin() as usize;
+        match self.pin.block().otyper().read().ot(n) {
+            vals::Ot::PushPull => OutputType::PushPull,
+            vals::Ot::OpenDrain => OutputType::OpenDrain,
+        }
+    }
+
+    /// Lock the configuration of the pin until the next reset.
+    ///
+    /// The mode, output type, speed, pull and alternate function of the pin can't be changed
+    /// anymore, the output level can. Dropping the pin leaves it as it is.
+    ///
+    /// The lock sequence freezes the lock register of the whole port, so this can only be done
+    /// once per port. To lock several pins of a port, group them in a [`PortBus`].
+    pub fn lock(&mut self) -> Result<(), LockError> {
+        lock_pins(self.pin.block(), 1 << self.pin.pin())
+    }
+
+    /// Get whether the configuration of the pin is locked.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        let lckr = self.pin.block().lckr().read().0;
+        lckr & LCKK != 0 && lckr & (1 << self.pin.pin()) != 0
+    }
 }
 
 impl<'d> Drop for Flex<'d> {
@@ -285,7 +374,7 @@ impl Pull {
 ///
 /// These vary depending on the chip, check the reference manual and datasheet ("I/O port
 /// characteristics") for details.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Speed {
     #[cfg_attr(gpio_v1, doc = "Output speed OUTPUT2MHZ")]
@@ -366,6 +455,20 @@ impl<'d> Input<'d> {
     pub fn get_level(&self) -> Level {
         self.pin.get_level()
     }
+
+    /// Change the internal weak pull-up and pull-down resistors.
+    #[inline]
+    #[cfg(gpio_v2)]
+    pub fn set_pull(&mut self, pull: Pull) {
+        self.pin.set_pull(pull)
+    }
+
+    /// Get the internal weak pull-up and pull-down resistor configuration.
+    #[inline]
+    #[cfg(not(stm32c5))]
+    pub fn pull(&self) -> Pull {
+        self.pin.pull()
+    }
 }
 
 /// Digital input or output level.
@@ -459,6 +562,27 @@ impl<'d> Output<'d> {
     pub fn toggle(&mut self) {
         self.pin.toggle();
     }
+
+    /// Change the output speed.
+    #[inline]
+    pub fn set_speed(&mut self, speed: Speed) {
+        self.pin.set_speed(speed)
+    }
+
+    /// Get the output speed.
+    #[inline]
+    #[cfg(gpio_v2)]
+    pub fn speed(&self) -> Speed {
+        self.pin.speed()
+    }
+
+    /// Lock the configuration of the pin until the next reset.
+    ///
+    /// See [`Flex::lock()`].
+    #[inline]
+    pub fn lock(&mut self) -> Result<(), LockError> {
+        self.pin.lock()
+    }
 }
 
 /// GPIO output open-drain driver.
@@ -556,10 +680,198 @@ impl<'d> OutputOpenDrain<'d> {
     pub fn toggle(&mut self) {
         self.pin.toggle()
     }
+
+    /// Change the internal weak pull-up and pull-down resistors.
+    #[inline]
+    #[cfg(gpio_v2)]
+    pub fn set_pull(&mut self, pull: Pull) {
+        self.pin.set_pull(pull)
+    }
+
+    /// Change the output speed.
+    #[inline]
+    pub fn set_speed(&mut self, speed: Speed) {
+        self.pin.set_speed(speed)
+    }
+
+    /// Get the output speed.
+    #[inline]
+    #[cfg(gpio_v2)]
+    pub fn speed(&self) -> Speed {
+        self.pin.speed()
+    }
+
+    /// Lock the configuration of the pin until the next reset.
+    ///
+    /// See [`Flex::lock()`].
+    #[inline]
+    pub fn lock(&mut self) -> Result<(), LockError> {
+        self.pin.lock()
+    }
+}
+
+/// Group of pins of one port, read and written as a single value.
+///
+/// Bit `i` of a value corresponds to `pins[i]`. Writes go through one BSRR access, so all pins
+/// of the group change at the same time, without the skew of setting them one after another.
+/// Reads sample IDR once. This is meant for parallel interfaces like 8080 LCD data buses,
+/// DIP switches or R2R DACs.
+///
+/// The pins are disconnected when the `PortBus` is dropped.
+pub struct PortBus<'d, const N: usize> {
+    pins: [Flex<'d>; N],
+    block: gpio::Gpio,
+    mask: u16,
+    /// Offset of the first pin, if the pins are consecutive and in ascending order.
+    shift: Option<u8>,
+}
+
+impl<'d, const N: usize> PortBus<'d, N> {
+    /// Create a bus from `pins`, with pin `i` being bit `i` of the bus value.
+    ///
+    /// The pins remain disconnected until one of the mode setters is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero or larger than 16, if the pins are not all on the same port, or if
+    /// a pin is given twice.
+    pub fn new(pins: [Peri<'d, AnyPin>; N]) -> Self {
+        assert!(N > 0 && N <= 16);
+
+        let port = pins[0].port();
+        let mut mask = 0u16;
+        for pin in pins.iter() {
+            assert!(pin.port() == port, "gpio: PortBus pins must be on the same port");
+            let bit = 1 << pin.pin();
+            assert!(mask & bit == 0, "gpio: PortBus pin used twice");
+            mask |= bit;
+        }
+
+        let first = pins[0].pin();
+        let shift = pins
+            .iter()
+            .enumerate()
+            .all(|(i, pin)| pin.pin() as usize == first as usize + i)
+            .then_some(first);
+
+        Self {
+            block: pins[0].block(),
+            pins: pins.map(Flex::new),
+            mask,
+            shift,
+        }
+    }
+
+    /// Put all pins into input mode.
+    ///
+    /// The internal weak pull-up and pull-down resistors will be enabled according to `pull`.
+    pub fn set_as_input(&mut self, pull: Pull) {
+        for pin in self.pins.iter_mut() {
+            pin.set_as_input(pull);
+        }
+    }
+
+    /// Put all pins into push-pull output mode.
+    ///
+    /// The pins output whatever was last written. To start at a specific value, call
+    /// [`Self::write()`] first.
+    pub fn set_as_output(&mut self, speed: Speed) {
+        for pin in self.pins.iter_mut() {
+            pin.set_as_output(speed);
+        }
+    }
+
+    /// Put all pins into input + open-drain output mode.
+    ///
+    /// See [`Flex::set_as_input_output()`].
+    pub fn set_as_input_output(&mut self, speed: Speed) {
+        for pin in self.pins.iter_mut() {
+            pin.set_as_input_output(speed);
+        }
+    }
+
+    /// Put all pins into analog mode.
+    pub fn set_as_analog(&mut self) {
+        for pin in self.pins.iter_mut() {
+            pin.set_as_analog();
+        }
+    }
+
+    /// Set the output of all pins at once.
+    ///
+    /// Bits of `value` above `N` are ignored.
+    #[inline]
+    pub fn write(&mut self, value: u16) {
+        let set = self.to_port(value);
+        let reset = !set & self.mask;
+        self.block
+            .bsrr()
+            .write_value(gpio::regs::Bsrr(set as u32 | (reset as u32) << 16));
+    }
+
+    /// Sample the input level of all pins at once.
+    #[inline]
+    pub fn read(&self) -> u16 {
+        self.from_port(self.block.idr().read().0 as u16)
+    }
+
+    /// Get the output value of all pins.
+    #[inline]
+    pub fn output_value(&self) -> u16 {
+        self.from_port(self.block.odr().read().0 as u16)
+    }
+
+    /// Mask of the pins in the port, with bit `n` set for pin `n`.
+    #[inline]
+    pub fn port_mask(&self) -> u16 {
+        self.mask
+    }
+
+    /// Lock the configuration of all pins until the next reset.
+    ///
+    /// See [`Flex::lock()`]. All pins are locked with a single lock sequence.
+    pub fn lock(&mut self) -> Result<(), LockError> {
+        lock_pins(self.block, self.mask)
+    }
+
+    /// Consume the bus, returning the individual pins.
+    pub fn into_pins(self) -> [Flex<'d>; N] {
+        self.pins
+    }
+
+    fn to_port(&self, value: u16) -> u16 {
+        match self.shift {
+            Some(shift) => (value << shift) & self.mask,
+            None => {
+                let mut bits = 0;
+                for (i, pin) in self.pins.iter().enumerate() {
+                    if value & (1 << i) != 0 {
+                        bits |= 1 << pin.pin.pin();
+                    }
+                }
+                bits
+            }
+        }
+    }
+
+    fn from_port(&self, bits: u16) -> u16 {
+        match self.shift {
+            Some(shift) => (bits & self.mask) >> shift,
+            None => {
+                let mut value = 0;
+                for (i, pin) in self.pins.iter().enumerate() {
+                    if bits & (1 << pin.pin.pin()) != 0 {
+                        value |= 1 << i;
+                    }
+                }
+                value
+            }
+        }
+    }
 }
 
 /// GPIO output type
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OutputType {
     /// Drive the pin both high or low.
@@ -757,6 +1069,86 @@ pub struct AfioRemap<const V: u8>;
 /// Holds the AFIO remap value for a peripheral's pin
 pub struct AfioRemapBool<const V: bool>;
 
+/// Analog switch of a dual-pad pin.
+///
+/// On most STM32H7 chips, `PA0`, `PA1`, `PC2` and `PC3` have a second, analog-only pad named
+/// `Pxy_C`, connected to the ADC. An analog switch connects the two pads. It must be open to use
+/// `Pxy` and `Pxy_C` independently, and closed to use `Pxy` as an analog input of the ADC
+/// channel of `Pxy_C`.
+#[cfg(stm32h7)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AnalogSwitch {
+    /// Switch between `PA0` and `PA0_C`.
+    PA0,
+    /// Switch between `PA1` and `PA1_C`.
+    PA1,
+    /// Switch between `PC2` and `PC2_C`.
+    PC2,
+    /// Switch between `PC3` and `PC3_C`.
+    PC3,
+}
+
+/// Open or close an analog switch.
+///
+/// The `split-*` features open the switches at init, this allows changing them at runtime.
+#[cfg(stm32h7)]
+pub fn set_analog_switch(switch: AnalogSwitch, closed: bool) {
+    critical_section::with(|_| {
+        crate::pac::SYSCFG.pmcr().modify(|w| match switch {
+            AnalogSwitch::PA0 => w.set_pa0so(!closed),
+            AnalogSwitch::PA1 => w.set_pa1so(!closed),
+            AnalogSwitch::PC2 => w.set_pc2so(!closed),
+            AnalogSwitch::PC3 => w.set_pc3so(!closed),
+        });
+    });
+}
+
+/// Get whether an analog switch is closed.
+#[cfg(stm32h7)]
+pub fn is_analog_switch_closed(switch: AnalogSwitch) -> bool {
+    let pmcr = crate::pac::SYSCFG.pmcr().read();
+    let open = match switch {
+        AnalogSwitch::PA0 => pmcr.pa0so(),
+        AnalogSwitch::PA1 => pmcr.pa1so(),
+        AnalogSwitch::PC2 => pmcr.pc2so(),
+        AnalogSwitch::PC3 => pmcr.pc3so(),
+    };
+    !open
+}
+
+/// Error returned when locking a pin configuration fails.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LockError {
+    /// The port was already locked since the last reset.
+    PortLocked,
+    /// The lock key sequence did not take effect.
+    Failed,
+}
+
+/// Lock key bit of LCKR.
+const LCKK: u32 = 1 << 16;
+
+fn lock_pins(r: gpio::Gpio, mask: u16) -> Result<(), LockError> {
+    if r.lckr().read().0 & LCKK != 0 {
+        return Err(LockError::PortLocked);
+    }
+
+    let mask = mask as u32;
+    let locked = critical_section::with(|_| {
+        // The key sequence must not be interrupted by another access to LCKR, and the mask
+        // must stay the same during the whole sequence.
+        r.lckr().write_value(gpio::regs::Lckr(LCKK | mask));
+        r.lckr().write_value(gpio::regs::Lckr(mask));
+        r.lckr().write_value(gpio::regs::Lckr(LCKK | mask));
+        let _ = r.lckr().read();
+        r.lckr().read().0 & LCKK != 0
+    });
+
+    if locked { Ok(()) } else { Err(LockError::Failed) }
+}
+
 #[cfg(afio)]
 /// Placeholder for a peripheral's pin which cannot be remapped via AFIO.
 pub struct AfioRemapNotApplicable;
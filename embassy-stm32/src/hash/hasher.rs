@@ -0,0 +1,163 @@
+use core::marker::PhantomData;
+
+#[cfg(any(hash_v1, hash_v2, hash_v4))]
+use digest::consts::U16;
+use digest::consts::{U20, U28, U32};
+#[cfg(hash_v3)]
+use digest::consts::{U48, U64};
+use digest::generic_array::ArrayLength;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+
+use super::{Algorithm, Context, DataType, Hash, Instance};
+use crate::mode::Mode;
+
+/// Hash algorithm with a digest size known at compile time, used by [`Hasher`].
+pub trait DigestAlgorithm {
+    /// Algorithm run by the peripheral.
+    const ALGORITHM: Algorithm;
+    /// Digest size in bytes.
+    type OutputSize: ArrayLength<u8> + 'static;
+}
+
+macro_rules! digest_algorithm {
+    ($name:ident, $algorithm:ident, $size:ty, $doc:literal) => {
+        #[doc = $doc]
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl DigestAlgorithm for $name {
+            const ALGORITHM: Algorithm = Algorithm::$algorithm;
+            type OutputSize = $size;
+        }
+    };
+}
+
+digest_algorithm!(Sha1, SHA1, U20, "SHA-1 algorithm for [`Hasher`].");
+#[cfg(any(hash_v1, hash_v2, hash_v4))]
+digest_algorithm!(Md5, MD5, U16, "MD5 algorithm for [`Hasher`].");
+digest_algorithm!(Sha224, SHA224, U28, "SHA-224 algorithm for [`Hasher`].");
+digest_algorithm!(Sha256, SHA256, U32, "SHA-256 algorithm for [`Hasher`].");
+#[cfg(hash_v3)]
+digest_algorithm!(Sha384, SHA384, U48, "SHA-384 algorithm for [`Hasher`].");
+#[cfg(hash_v3)]
+digest_algorithm!(Sha512_224, SHA512_224, U28, "SHA-512/224 algorithm for [`Hasher`].");
+#[cfg(hash_v3)]
+digest_algorithm!(Sha512_256, SHA512_256, U32, "SHA-512/256 algorithm for [`Hasher`].");
+#[cfg(hash_v3)]
+digest_algorithm!(Sha512, SHA512, U64, "SHA-512 algorithm for [`Hasher`].");
+
+/// Hasher implementing the [`digest`] traits on top of the HASH peripheral.
+///
+/// Created by [`Hash::hasher`]. Data is pushed with the blocking API, so this works with drivers
+/// in either mode, and can be passed to code generic over [`digest::Update`] and
+/// [`digest::FixedOutput`].
+pub struct Hasher<'h, 'd, T: Instance, M: Mode, A: DigestAlgorithm> {
+    hash: &'h mut Hash<'d, T, M>,
+    ctx: Context<'static>,
+    _algorithm: PhantomData<A>,
+}
+
+impl<'d, T: Instance, M: Mode> Hash<'d, T, M> {
+    /// Starts computation of a new hash with algorithm `A`, returning a [`Hasher`].
+    pub fn hasher<A: DigestAlgorithm>(&mut self) -> Hasher<'_, 'd, T, M, A> {
+        let ctx = self.start(A::ALGORITHM, DataType::Width8, None);
+        Hasher {
+            hash: self,
+            ctx,
+            _algorithm: PhantomData,
+        }
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> OutputSizeUser for Hasher<'_, '_, T, M, A> {
+    type OutputSize = A::OutputSize;
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> HashMarker for Hasher<'_, '_, T, M, A> {}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> Update for Hasher<'_, '_, T, M, A> {
+    fn update(&mut self, data: &[u8]) {
+        self.hash.update_blocking(&mut self.ctx, data);
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> FixedOutput for Hasher<'_, '_, T, M, A> {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        self.hash.finish_blocking(self.ctx, out);
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> Reset for Hasher<'_, '_, T, M, A> {
+    fn reset(&mut self) {
+        self.ctx = self.hash.start(A::ALGORITHM, DataType::Width8, None);
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> FixedOutputReset for Hasher<'_, '_, T, M, A> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        self.hash.finish_blocking(self.ctx.clone(), out);
+        self.reset();
+    }
+}
+
+/// HMAC computed by the HASH peripheral, implementing the [`digest`] update and output traits.
+///
+/// Created by [`Hash::hmac`]. Like [`Hasher`], data is pushed with the blocking API.
+pub struct Hmac<'h, 'd, T: Instance, M: Mode, A: DigestAlgorithm> {
+    hash: &'h mut Hash<'d, T, M>,
+    ctx: Context<'h>,
+    _algorithm: PhantomData<A>,
+}
+
+impl<'d, T: Instance, M: Mode> Hash<'d, T, M> {
+    /// Starts computation of a new HMAC with algorithm `A` and `key`, returning a [`Hmac`].
+    ///
+    /// Keys longer than the block size of the algorithm are hashed by the peripheral first, as
+    /// required by the HMAC specification.
+    pub fn hmac<'h, A: DigestAlgorithm>(&'h mut self, key: &'h [u8]) -> Hmac<'h, 'd, T, M, A> {
+        let ctx = self.start(A::ALGORITHM, DataType::Width8, Some(key));
+        Hmac {
+            hash: self,
+            ctx,
+            _algorithm: PhantomData,
+        }
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> Hmac<'_, '_, T, M, A> {
+    /// Finishes the computation, and compares the HMAC with `tag` in constant time.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        let mac = self.finalize_fixed();
+        // Don't return early on a mismatch, to not leak where it is.
+        tag.len() == mac.len() && mac.iter().zip(tag).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> OutputSizeUser for Hmac<'_, '_, T, M, A> {
+    type OutputSize = A::OutputSize;
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> Update for Hmac<'_, '_, T, M, A> {
+    fn update(&mut self, data: &[u8]) {
+        self.hash.update_blocking(&mut self.ctx, data);
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> FixedOutput for Hmac<'_, '_, T, M, A> {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        self.hash.finish_blocking(self.ctx, out);
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> Reset for Hmac<'_, '_, T, M, A> {
+    fn reset(&mut self) {
+        self.ctx = self.hash.start(A::ALGORITHM, DataType::Width8, self.ctx.key);
+    }
+}
+
+impl<T: Instance, M: Mode, A: DigestAlgorithm> FixedOutputReset for Hmac<'_, '_, T, M, A> {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        self.hash.finish_blocking(self.ctx.clone(), out);
+        self.reset();
+    }
+}
@@ -1,26 +1,31 @@
 //! Hash generator (HASH)
 use core::cmp::min;
-#[cfg(hash_v2)]
+#[cfg(not(hash_v1))]
 use core::future::poll_fn;
 use core::marker::PhantomData;
-#[cfg(hash_v2)]
+#[cfg(not(hash_v1))]
 use core::ptr;
-#[cfg(hash_v2)]
+#[cfg(not(hash_v1))]
 use core::task::Poll;
 
 use embassy_hal_internal::PeripheralType;
 use embassy_sync::waitqueue::AtomicWaker;
 use stm32_metapac::hash::regs::*;
 
-#[cfg(hash_v2)]
+#[cfg(not(hash_v1))]
 use crate::dma::ChannelAndRequest;
 use crate::interrupt::typelevel::Interrupt;
-#[cfg(hash_v2)]
+#[cfg(not(hash_v1))]
 use crate::mode::Async;
 use crate::mode::{Blocking, Mode};
 use crate::peripherals::HASH;
 use crate::{Peri, interrupt, pac, peripherals, rcc};
 
+#[cfg(feature = "digest")]
+mod hasher;
+#[cfg(feature = "digest")]
+pub use hasher::*;
+
 #[cfg(hash_v1)]
 const NUM_CONTEXT_REGS: usize = 51;
 #[cfg(hash_v3)]
@@ -53,7 +58,8 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
 }
 
 ///Hash algorithm selection
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Algorithm {
     /// SHA-1 Algorithm
     SHA1 = 0,
@@ -85,6 +91,17 @@ pub enum Algorithm {
     SHA512 = 15,
 }
 
+impl Algorithm {
+    /// Size of the blocks the algorithm works on, in bytes.
+    fn block_size(self) -> usize {
+        match self {
+            #[cfg(hash_v3)]
+            Algorithm::SHA384 | Algorithm::SHA512_224 | Algorithm::SHA512_256 | Algorithm::SHA512 => 128,
+            _ => 64,
+        }
+    }
+}
+
 /// Input data width selection
 #[repr(u8)]
 #[derive(Clone, Copy)]
@@ -122,7 +139,7 @@ type HmacKey<'k> = Option<&'k [u8]>;
 pub struct Hash<'d, T: Instance, M: Mode> {
     _peripheral: Peri<'d, T>,
     _marker: PhantomData<M>,
-    #[cfg(hash_v2)]
+    #[cfg(not(hash_v1))]
     dma: Option<ChannelAndRequest<'d>>,
 }
 
@@ -136,7 +153,7 @@ impl<'d, T: Instance> Hash<'d, T, Blocking> {
         let instance = Self {
             _peripheral: peripheral,
             _marker: PhantomData,
-            #[cfg(hash_v2)]
+            #[cfg(not(hash_v1))]
             dma: None,
         };
 
@@ -193,11 +210,10 @@ impl<'d, T: Instance, M: Mode> Hash<'d, T, M> {
         T::regs().cr().modify(|w| w.set_algo(ctx.algo as u8));
 
         // Configure HMAC mode if a key is provided.
+        // Keys longer than a block are hashed by the peripheral before use.
         if let Some(key) = ctx.key {
             T::regs().cr().modify(|w| w.set_mode(true));
-            if key.len() > 64 {
-                T::regs().cr().modify(|w| w.set_lkey(true));
-            }
+            T::regs().cr().modify(|w| w.set_lkey(key.len() > ctx.algo.block_size()));
         } else {
             T::regs().cr().modify(|w| w.set_mode(false));
         }
@@ -398,7 +414,7 @@ impl<'d, T: Instance, M: Mode> Hash<'d, T, M> {
     }
 }
 
-#[cfg(hash_v2)]
+#[cfg(not(hash_v1))]
 impl<'d, T: Instance> Hash<'d, T, Async> {
     /// Instantiates, resets, and enables the HASH peripheral.
     pub fn new<D: Dma<T>>(
@@ -431,7 +447,7 @@ impl<'d, T: Instance> Hash<'d, T, Async> {
         // Load the HMAC key if provided.
         if !ctx.key_sent {
             if let Some(key) = ctx.key {
-                self.accumulate(key).await;
+                self.accumulate_last(key).await;
             }
             ctx.key_sent = true;
         }
@@ -495,13 +511,14 @@ impl<'d, T: Instance> Hash<'d, T, Async> {
         // Must be cleared prior to the last DMA transfer.
         T::regs().cr().modify(|w| w.set_mdmat(false));
 
-        // Hash the leftover bytes, if any.
-        self.accumulate(&ctx.buffer[0..ctx.buflen]).await;
+        // Hash the leftover bytes, if any, and start the digest calculation.
+        self.accumulate_last(&ctx.buffer[0..ctx.buflen]).await;
         ctx.buflen = 0;
 
         // Load the HMAC key if provided.
         if let Some(key) = ctx.key {
-            self.accumulate(key).await;
+            self.wait_data_input().await;
+            self.accumulate_last(key).await;
         }
 
         // Wait for completion.
@@ -552,6 +569,37 @@ impl<'d, T: Instance> Hash<'d, T, Async> {
         digest_len_bytes
     }
 
+    /// Push the last data of a phase into the hash core, and start the digest calculation.
+    ///
+    /// The calculation starts at the end of the DMA transfer (MDMAT must be cleared), or
+    /// right away if there is no data.
+    async fn accumulate_last(&mut self, input: &[u8]) {
+        if input.is_empty() {
+            T::regs().str().write(|w| w.set_dcal(true));
+        } else {
+            self.accumulate(input).await;
+        }
+    }
+
+    /// Wait until the hash core accepts new data, e.g. between the HMAC phases.
+    async fn wait_data_input(&mut self) {
+        poll_fn(|cx| {
+            // Check if already ready.
+            if T::regs().sr().read().dinis() {
+                return Poll::Ready(());
+            }
+            // Register waker, then enable interrupts.
+            HASH_WAKER.register(cx.waker());
+            T::regs().imr().modify(|reg| reg.set_dinie(true));
+            if T::regs().sr().read().dinis() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+
     /// Push data into the hash core.
     async fn accumulate(&mut self, input: &[u8]) {
         // Ignore an input length of 0.
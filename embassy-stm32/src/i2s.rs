@@ -245,6 +245,8 @@ pub struct I2S<'d, W: Word> {
     _ws: Option<Flex<'d>>,
     _ck: Option<Flex<'d>>,
     _mck: Option<Flex<'d>>,
+    sample_rate: Hertz,
+    sample_rate_error_ppm: i32,
     tx_ring_buffer: Option<WritableRingBuffer<'d, W>>,
     rx_ring_buffer: Option<ReadableRingBuffer<'d, W>>,
 }
@@ -361,7 +363,7 @@ impl<'d, W: Word> I2S<'d, W> {
     }
 
     #[cfg(spi_v2_i2s)]
-    /// Create a transmitter driver.
+    /// Create a full duplex driver, transmitting on the SPI instance and receiving on its I2Sx_ext.
     pub fn new_full_duplex<T: I2sSExtInstance, D1: TxDma<T>, D2: RxDmaExt<T>, #[cfg(afio)] A>(
         peri: Peri<'d, T>,
         txsd: Peri<'d, if_afio!(impl MosiPin<T, A>)>,
@@ -393,6 +395,39 @@ impl<'d, W: Word> I2S<'d, W> {
         )
     }
 
+    #[cfg(spi_v2_i2s)]
+    /// Create a full duplex driver without a master clock pin, transmitting on the SPI instance and
+    /// receiving on its I2Sx_ext.
+    pub fn new_full_duplex_nomck<T: I2sSExtInstance, D1: TxDma<T>, D2: RxDmaExt<T>, #[cfg(afio)] A>(
+        peri: Peri<'d, T>,
+        txsd: Peri<'d, if_afio!(impl MosiPin<T, A>)>,
+        rxsd: Peri<'d, if_afio!(impl SdExtPin<T, A>)>,
+        ws: Peri<'d, if_afio!(impl WsPin<T, A>)>,
+        ck: Peri<'d, if_afio!(impl CkPin<T, A>)>,
+        txdma: Peri<'d, D1>,
+        txdma_buf: &'d mut [W],
+        rxdma: Peri<'d, D2>,
+        rxdma_buf: &'d mut [W],
+        _irq: impl crate::interrupt::typelevel::Binding<D1::Interrupt, crate::dma::InterruptHandler<D1>>
+        + crate::interrupt::typelevel::Binding<D2::Interrupt, crate::dma::InterruptHandler<D2>>
+        + 'd,
+        config: Config,
+    ) -> Self {
+        Self::new_inner(
+            peri,
+            Some(T::regs_ext()),
+            new_pin!(txsd, AfType::output(OutputType::PushPull, Speed::VeryHigh)),
+            new_pin!(rxsd, AfType::output(OutputType::PushPull, Speed::VeryHigh)),
+            ws,
+            ck,
+            None,
+            new_dma!(txdma, _irq).map(|d| (d, txdma_buf)),
+            new_dma!(rxdma, _irq).map(|d| (d, rxdma_buf)),
+            config,
+            Function::Transmit,
+        )
+    }
+
     #[cfg(any(spi_v4, spi_v5))]
     /// Create a full duplex driver.
     pub fn new_full_duplex<T: Instance, D1: TxDma<T>, D2: RxDma<T>, #[cfg(afio)] A>(
@@ -526,6 +561,22 @@ impl<'d, W: Word> I2S<'d, W> {
         }
     }
 
+    /// Get the sample rate achieved by the clock divider, in master mode.
+    ///
+    /// The divider is an integer, so the sample rate is only exact if the I2S kernel clock is a
+    /// suitable multiple of the requested one.
+    pub fn sample_rate(&self) -> Hertz {
+        self.sample_rate
+    }
+
+    /// Get the deviation of the achieved sample rate from the requested one, in parts per million.
+    ///
+    /// Errors above a few hundred ppm may be audible as a pitch shift, or cause drift against
+    /// other audio clocks.
+    pub fn sample_rate_error_ppm(&self) -> i32 {
+        self.sample_rate_error_ppm
+    }
+
     /// Stop I2S driver.
     pub async fn stop(&mut self) {
         let regs_tx = self.regs_tx();
@@ -604,6 +655,21 @@ impl<'d, W: Word> I2S<'d, W> {
         }
     }
 
+    /// Write `tx` to the I2S ringbuffer and read `rx` from it at the same time.
+    ///
+    /// This is for drivers created with `new_full_duplex`, which use the I2Sx_ext instance on
+    /// F4 and the full-duplex I2S mode on H7, e.g. a codec doing playback and recording. It waits
+    /// until both are done. The write and the read run independently on their own ring buffers:
+    /// `tx` is queued for transmission while `rx` is filled from already received data, so the
+    /// received samples are not aligned with the transmitted ones. The offset between the two
+    /// streams is set by the ring buffer fill levels when the transfer starts.
+    pub async fn transfer(&mut self, tx: &[W], rx: &mut [W]) -> Result<(), Error> {
+        let (mut reader, mut writer) = self.split()?;
+        let (read, write) = join(reader.read(rx), writer.write(tx)).await;
+        write?;
+        read
+    }
+
     /// Return the current write position in the TX DMA ring buffer.
     ///
     /// Immediately after a TX write error, this returns the DMA position recorded at ring buffer
@@ -664,6 +730,8 @@ impl<'d, W: Word> I2S<'d, W> {
         let pclk = T::frequency();
 
         let (odd, div) = compute_baud_rate(pclk, config.frequency, config.master_clock, config.format);
+        let (sample_rate, sample_rate_error_ppm) =
+            achieved_rate(pclk, config.frequency, config.master_clock, config.format, odd, div);
 
         #[cfg(any(spi_v4, spi_v5))]
         {
@@ -804,6 +872,8 @@ impl<'d, W: Word> I2S<'d, W> {
             _ws: new_pin!(ws, AfType::output(OutputType::PushPull, config.gpio_speed)),
             _ck: new_pin!(ck, AfType::output(OutputType::PushPull, config.gpio_speed)),
             _mck: mck.map(|w| w.into()),
+            sample_rate,
+            sample_rate_error_ppm,
             tx_ring_buffer: txdma.map(|(ch, buf)| unsafe {
                 WritableRingBuffer::new(ch.channel, ch.request, regs.tx_ptr() as *mut W, buf, opts)
             }),
@@ -832,13 +902,7 @@ impl<'d, W: Word> I2S<'d, W> {
 // note: division = (2 * div) + odd = (div << 1) + odd
 // in other word, from bits point of view, division[8:1] = div[7:0] and division[0] = odd
 fn compute_baud_rate(i2s_clock: Hertz, request_freq: Hertz, mclk: bool, data_format: Format) -> (bool, u8) {
-    let coef = if mclk {
-        256
-    } else if let Format::Data16Channel16 = data_format {
-        32
-    } else {
-        64
-    };
+    let coef = clock_coefficient(mclk, data_format);
 
     let (n, d) = (i2s_clock.0, coef * request_freq.0);
     let division = (n + (d >> 1)) / d;
@@ -852,6 +916,34 @@ fn compute_baud_rate(i2s_clock: Hertz, request_freq: Hertz, mclk: bool, data_for
     }
 }
 
+fn clock_coefficient(mclk: bool, data_format: Format) -> u32 {
+    if mclk {
+        256
+    } else if let Format::Data16Channel16 = data_format {
+        32
+    } else {
+        64
+    }
+}
+
+// Sample rate given by the clock divider, and its deviation from `request_freq` in ppm.
+fn achieved_rate(
+    i2s_clock: Hertz,
+    request_freq: Hertz,
+    mclk: bool,
+    data_format: Format,
+    odd: bool,
+    div: u8,
+) -> (Hertz, i32) {
+    let den = clock_coefficient(mclk, data_format) as u64 * (2 * div as u64 + odd as u64);
+    let clock = i2s_clock.0 as u64;
+    let rate = (clock + den / 2) / den;
+
+    let den = den * request_freq.0 as u64;
+    let ppm = (clock * 1_000_000 + den / 2) / den;
+    (Hertz(rate as u32), (ppm as i64 - 1_000_000) as i32)
+}
+
 #[cfg(any(spi_v4, spi_v5))]
 
 // The STM32H7 reference manual specifies that any incompatible bitfields should be reset
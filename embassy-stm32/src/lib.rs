@@ -53,6 +53,7 @@ macro_rules! dma_channel_impl {
 #[cfg(not(stm32c5))]
 pub mod dma;
 pub mod gpio;
+pub mod parallel_bus;
 pub mod rcc;
 #[cfg(feature = "_time-driver")]
 mod time_driver;
@@ -112,6 +113,8 @@ pub mod dac;
 pub mod dcmi;
 #[cfg(dcmipp)]
 pub mod dcmipp;
+#[cfg(dfsdm)]
+pub mod dfsdm;
 #[cfg(dlybsd)]
 pub mod dlyb;
 #[cfg(dma2d)]
@@ -130,6 +133,8 @@ pub mod flash;
 pub mod fmac;
 #[cfg(fmc)]
 pub mod fmc;
+#[cfg(gfxmmu)]
+pub mod gfxmmu;
 #[cfg(hash)]
 pub mod hash;
 #[cfg(hrtim)]
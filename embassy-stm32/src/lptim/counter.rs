@@ -0,0 +1,192 @@
+//! External pulse counter driver.
+//!
+//! The counter is incremented by the edges of Input1, e.g. from a wind or flow sensor, and
+//! keeps counting while the core is in Stop mode when the LPTIM is clocked from LSE or LSI.
+//! The kernel clock samples the input for the digital filter, so it must be at least twice as
+//! fast as the pulses. The 16-bit hardware counter is extended to 32 bits by counting its
+//! overflows, which wakes the core once every 65536 pulses.
+
+use core::future::poll_fn;
+use core::sync::atomic::Ordering;
+use core::task::Poll;
+
+use embassy_hal_internal::Peri;
+
+#[cfg(any(lptim_v2a, lptim_v2b))]
+use super::channel::Channel;
+use super::timer::Timer;
+use super::{Input1Pin, Instance, InterruptHandler, enable_event_interrupts, read_counter};
+use crate::gpio::{AfType, Flex, Pull};
+use crate::interrupt;
+use crate::interrupt::typelevel::Interrupt;
+use crate::pac::lptim::vals::{Ckpol, Filter};
+
+/// Edges of the input counted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CountedEdge {
+    /// Rising edges.
+    Rising,
+    /// Falling edges.
+    Falling,
+    /// Both edges, two counts per pulse.
+    Both,
+}
+
+/// Pulse counter configuration.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Edges counted.
+    pub edge: CountedEdge,
+    /// Digital filter on the input, in kernel clock cycles. An edge is only counted once the
+    /// input has been stable for this many cycles.
+    pub filter: Filter,
+    /// Pull resistor on the input.
+    pub pull: Pull,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            edge: CountedEdge::Rising,
+            filter: Filter::Count1,
+            pull: Pull::None,
+        }
+    }
+}
+
+/// External pulse counter driver.
+pub struct PulseCounter<'d, T: Instance> {
+    inner: Timer<'d, T>,
+    _in1: Flex<'d>,
+    origin: u32,
+}
+
+impl<'d, T: Instance> PulseCounter<'d, T> {
+    /// Create a new pulse counter driver, counting from zero.
+    pub fn new(
+        tim: Peri<'d, T>,
+        in1: Peri<'d, impl Input1Pin<T>>,
+        _irq: impl interrupt::typelevel::Binding<T::GlobalInterrupt, InterruptHandler<T>> + 'd,
+        config: Config,
+    ) -> Self {
+        critical_section::with(|_| {
+            set_as_af!(in1, AfType::input(config.pull));
+        });
+
+        let inner = Timer::new(tim);
+        let r = T::regs();
+        // The internal clock runs the filter, and Input1 clocks the counter.
+        r.cfgr().modify(|w| {
+            w.set_cksel(false);
+            w.set_countmode(true);
+            w.set_ckpol(match config.edge {
+                CountedEdge::Rising => Ckpol::RisingEdge,
+                CountedEdge::Falling => Ckpol::FallingEdge,
+                CountedEdge::Both => Ckpol::BothEdges,
+            });
+            w.set_ckflt(config.filter);
+            w.set_presc(crate::pac::lptim::vals::Presc::Div1);
+        });
+
+        T::state().overflows.store(0, Ordering::Relaxed);
+        #[cfg(not(any(lptim_v2a, lptim_v2b)))]
+        enable_event_interrupts::<T>();
+        inner.enable();
+        #[cfg(any(lptim_v2a, lptim_v2b))]
+        enable_event_interrupts::<T>();
+
+        inner.set_auto_reload_value(u16::MAX);
+        inner.continuous_mode_start();
+
+        let mut this = Self {
+            inner,
+            _in1: Flex::new(in1),
+            origin: 0,
+        };
+        this.reset();
+        this
+    }
+
+    /// Pulses counted since the last reset, wrapping around at `u32::MAX`.
+    pub fn count(&self) -> u32 {
+        self.total().wrapping_sub(self.origin)
+    }
+
+    /// Reset the count to zero.
+    pub fn reset(&mut self) {
+        self.origin = self.total();
+    }
+
+    /// Wait until the count reaches `count`.
+    ///
+    /// The core is only woken up by the compare match at `count`, and by the counter overflows
+    /// until then.
+    pub async fn wait_for_count(&mut self, count: u32) {
+        let target = self.origin.wrapping_add(count);
+        poll_fn(|cx| {
+            T::state().waker.register(cx.waker());
+
+            let total = self.total();
+            if total.wrapping_sub(self.origin) >= count {
+                return Poll::Ready(());
+            }
+
+            // The compare value only holds the low half, set it on the last counter period.
+            if total >> 16 == target >> 16 {
+                self.set_compare(target as u16);
+                if self.total().wrapping_sub(self.origin) >= count {
+                    cx.waker().wake_by_ref();
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Wait until `pulses` more pulses have been counted.
+    pub async fn wait_for_pulses(&mut self, pulses: u32) {
+        let count = self.count().wrapping_add(pulses);
+        self.wait_for_count(count).await
+    }
+
+    /// Pulses counted since the driver was created.
+    fn total(&self) -> u32 {
+        let r = T::regs();
+        critical_section::with(|_| {
+            loop {
+                // The auto-reload match flags the last count before the counter wraps, so the
+                // count and the pending flag must be read consistently.
+                let pending = r.isr().read().arrm();
+                let cnt = read_counter::<T>();
+                if r.isr().read().arrm() != pending {
+                    continue;
+                }
+
+                let mut matches = T::state()
+                    .overflows
+                    .load(Ordering::Relaxed)
+                    .wrapping_add(pending as u32);
+                if cnt == u16::MAX {
+                    matches = matches.wrapping_sub(1);
+                }
+                return matches << 16 | cnt as u32;
+            }
+        })
+    }
+
+    fn set_compare(&self, value: u16) {
+        #[cfg(not(any(lptim_v2a, lptim_v2b)))]
+        self.inner.set_compare_value(value);
+        #[cfg(any(lptim_v2a, lptim_v2b))]
+        self.inner.set_compare_value(Channel::Ch1, value);
+    }
+}
+
+impl<'d, T: Instance> Drop for PulseCounter<'d, T> {
+    fn drop(&mut self) {
+        self.inner.disable();
+        T::GlobalInterrupt::disable();
+    }
+}
@@ -0,0 +1,202 @@
+//! Quadrature encoder driver.
+//!
+//! The LPTIM decodes the quadrature signals on Input1 and Input2 in hardware, and keeps
+//! counting while the core is in Stop mode when clocked from LSE or LSI. The kernel clock
+//! samples the inputs, so it must be at least four times faster than the encoder edges.
+
+use core::future::poll_fn;
+use core::sync::atomic::Ordering;
+use core::task::Poll;
+
+use embassy_hal_internal::Peri;
+
+#[cfg(any(lptim_v2a, lptim_v2b))]
+use super::channel::Channel;
+use super::timer::Timer;
+use super::{Input1Pin, Input2Pin, Instance, InterruptHandler, enable_event_interrupts, read_counter};
+use crate::gpio::{AfType, Flex, Pull};
+use crate::interrupt;
+use crate::interrupt::typelevel::Interrupt;
+use crate::pac::lptim::vals::{Ckpol, Filter};
+
+/// Edges of the inputs counted by the encoder.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EncoderEdges {
+    /// Count the rising edges of the inputs.
+    Rising,
+    /// Count the falling edges of the inputs.
+    Falling,
+    /// Count both edges of the inputs, for the highest resolution.
+    Both,
+}
+
+/// Counting direction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// Counting up.
+    Upcounting,
+    /// Counting down.
+    Downcounting,
+}
+
+/// Encoder configuration.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Edges counted.
+    pub edges: EncoderEdges,
+    /// Digital filter on the inputs, in kernel clock cycles.
+    pub filter: Filter,
+    /// Counts between two detents of the encoder.
+    pub counts_per_detent: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            edges: EncoderEdges::Both,
+            filter: Filter::Count1,
+            counts_per_detent: 4,
+        }
+    }
+}
+
+/// Quadrature encoder driver.
+pub struct Encoder<'d, T: Instance> {
+    inner: Timer<'d, T>,
+    _in1: Flex<'d>,
+    _in2: Flex<'d>,
+    counts_per_detent: u16,
+    origin: u16,
+}
+
+impl<'d, T: Instance> Encoder<'d, T> {
+    /// Create a new encoder driver.
+    pub fn new(
+        tim: Peri<'d, T>,
+        in1: Peri<'d, impl Input1Pin<T>>,
+        in2: Peri<'d, impl Input2Pin<T>>,
+        _irq: impl interrupt::typelevel::Binding<T::GlobalInterrupt, InterruptHandler<T>> + 'd,
+        config: Config,
+    ) -> Self {
+        assert!(config.counts_per_detent > 0);
+
+        critical_section::with(|_| {
+            set_as_af!(in1, AfType::input(Pull::None));
+            set_as_af!(in2, AfType::input(Pull::None));
+        });
+
+        let inner = Timer::new(tim);
+        let r = T::regs();
+        // Encoder mode requires the internal clock and no prescaler.
+        r.cfgr().modify(|w| {
+            w.set_cksel(false);
+            w.set_ckpol(match config.edges {
+                EncoderEdges::Rising => Ckpol::RisingEdge,
+                EncoderEdges::Falling => Ckpol::FallingEdge,
+                EncoderEdges::Both => Ckpol::BothEdges,
+            });
+            w.set_ckflt(config.filter);
+            w.set_presc(crate::pac::lptim::vals::Presc::Div1);
+            w.set_enc(true);
+        });
+
+        #[cfg(not(any(lptim_v2a, lptim_v2b)))]
+        enable_event_interrupts::<T>();
+        inner.enable();
+        #[cfg(any(lptim_v2a, lptim_v2b))]
+        enable_event_interrupts::<T>();
+
+        inner.set_auto_reload_value(u16::MAX);
+        inner.continuous_mode_start();
+
+        let mut this = Self {
+            inner,
+            _in1: Flex::new(in1),
+            _in2: Flex::new(in2),
+            counts_per_detent: config.counts_per_detent,
+            origin: 0,
+        };
+        this.reset();
+        this
+    }
+
+    /// Raw value of the hardware counter.
+    pub fn raw_count(&self) -> u16 {
+        read_counter::<T>()
+    }
+
+    /// Counts since the last reset, wrapping around at the limits of `i16`.
+    pub fn count(&self) -> i16 {
+        self.raw_count().wrapping_sub(self.origin) as i16
+    }
+
+    /// Whole detents since the last reset.
+    pub fn detents(&self) -> i16 {
+        self.count() / self.counts_per_detent as i16
+    }
+
+    /// Reset the count to zero.
+    pub fn reset(&mut self) {
+        self.origin = self.raw_count();
+    }
+
+    /// Direction of the last change of the count.
+    pub fn read_direction(&self) -> Direction {
+        match T::state().counting_down.load(Ordering::Relaxed) {
+            false => Direction::Upcounting,
+            true => Direction::Downcounting,
+        }
+    }
+
+    /// Wait until the encoder has turned by `detents` detents in either direction.
+    ///
+    /// Returns the detents turned, positive when counting up. The core is only woken up when
+    /// the count reaches the target, or when the turning direction changes.
+    pub async fn wait_for_detents(&mut self, detents: u16) -> i16 {
+        let distance = detents as i32 * self.counts_per_detent as i32;
+        assert!(distance > 0 && distance <= i16::MAX as i32);
+
+        let start = self.raw_count();
+        poll_fn(|cx| {
+            T::state().waker.register(cx.waker());
+
+            let count = self.raw_count();
+            let moved = count.wrapping_sub(start) as i16 as i32;
+            if moved.abs() >= distance {
+                return Poll::Ready((moved / self.counts_per_detent as i32) as i16);
+            }
+
+            // Wait for the target in the current direction, a direction change wakes us up to
+            // retarget the other way.
+            let target = match T::state().counting_down.load(Ordering::Relaxed) {
+                false => start.wrapping_add(distance as u16),
+                true => start.wrapping_sub(distance as u16),
+            };
+            self.set_compare(target);
+
+            // The count may have passed the target while it was being written.
+            if self.raw_count() != count {
+                cx.waker().wake_by_ref();
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    fn set_compare(&self, value: u16) {
+        #[cfg(not(any(lptim_v2a, lptim_v2b)))]
+        self.inner.set_compare_value(value);
+        #[cfg(any(lptim_v2a, lptim_v2b))]
+        self.inner.set_compare_value(Channel::Ch1, value);
+    }
+}
+
+impl<'d, T: Instance> Drop for Encoder<'d, T> {
+    fn drop(&mut self) {
+        self.inner.disable();
+        T::GlobalInterrupt::disable();
+    }
+}
@@ -1,8 +1,15 @@
 //! Low-power timer (LPTIM)
 
+pub mod counter;
+pub mod encoder;
 pub mod pwm;
 pub mod timer;
 
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use embassy_sync::waitqueue::AtomicWaker;
+
 use crate::rcc::RccPeripheral;
 
 /// Timer channel.
@@ -13,13 +20,109 @@ pub use channel::Channel;
 use embassy_hal_internal::PeripheralType;
 
 use crate::interrupt;
+use crate::interrupt::typelevel::Interrupt as _;
 
 pin_trait!(OutputPin, BasicInstance);
 pin_trait!(Channel1Pin, BasicInstance);
 pin_trait!(Channel2Pin, BasicInstance);
+pin_trait!(Input1Pin, BasicInstance);
+pin_trait!(Input2Pin, BasicInstance);
+
+pub(crate) struct State {
+    waker: AtomicWaker,
+    counting_down: AtomicBool,
+    overflows: AtomicU32,
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            waker: AtomicWaker::new(),
+            counting_down: AtomicBool::new(false),
+            overflows: AtomicU32::new(0),
+        }
+    }
+}
 
 pub(crate) trait SealedInstance: RccPeripheral {
     fn regs() -> crate::pac::lptim::Lptim;
+    fn state() -> &'static State;
+}
+
+/// Interrupt handler for the event waits of the counting drivers.
+pub struct InterruptHandler<T: Instance> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Instance> interrupt::typelevel::Handler<T::GlobalInterrupt> for InterruptHandler<T> {
+    unsafe fn on_interrupt() {
+        let r = T::regs();
+        let isr = r.isr().read();
+
+        if isr.up() {
+            T::state().counting_down.store(false, Ordering::Relaxed);
+        }
+        if isr.down() {
+            T::state().counting_down.store(true, Ordering::Relaxed);
+        }
+        if isr.arrm() {
+            // Only written here, so this doesn't need a read-modify-write atomic (absent on v6-M).
+            let overflows = T::state().overflows.load(Ordering::Relaxed);
+            T::state().overflows.store(overflows.wrapping_add(1), Ordering::Relaxed);
+        }
+
+        // The interrupts stay enabled, the waiting tasks check the counter themselves.
+        r.icr().write(|w| {
+            #[cfg(not(any(lptim_v2a, lptim_v2b)))]
+            w.set_cmpmcf(isr.cmpm());
+            #[cfg(any(lptim_v2a, lptim_v2b))]
+            w.set_cccf(0, isr.ccif(0));
+            w.set_arrmcf(isr.arrm());
+            w.set_upcf(isr.up());
+            w.set_downcf(isr.down());
+        });
+
+        T::state().waker.wake();
+    }
+}
+
+/// Enable the compare match, auto-reload match and direction change interrupts.
+///
+/// On LPTIM v1, this must be done while the timer is disabled, and on v2 while it is enabled.
+fn enable_event_interrupts<T: Instance>() {
+    #[cfg(not(any(lptim_v2a, lptim_v2b)))]
+    T::regs().ier().modify(|w| {
+        w.set_cmpmie(true);
+        w.set_arrmie(true);
+        w.set_upie(true);
+        w.set_downie(true);
+    });
+    #[cfg(any(lptim_v2a, lptim_v2b))]
+    {
+        let r = T::regs();
+        r.icr().write(|w| w.set_dierokcf(true));
+        r.dier().modify(|w| {
+            w.set_ccie(0, true);
+            w.set_arrmie(true);
+            w.set_upie(true);
+            w.set_downie(true);
+        });
+        while !r.isr().read().dierok() {}
+    }
+
+    T::GlobalInterrupt::unpend();
+    unsafe { T::GlobalInterrupt::enable() };
+}
+
+/// Read the counter, which runs on its own clock, until two consecutive reads match.
+fn read_counter<T: Instance>() -> u16 {
+    let r = T::regs();
+    loop {
+        let a = r.cnt().read().cnt();
+        if r.cnt().read().cnt() == a {
+            return a;
+        }
+    }
 }
 
 pub(crate) trait SealedBasicInstance: RccPeripheral {
@@ -40,6 +143,11 @@ foreach_interrupt! {
             fn regs() -> crate::pac::lptim::Lptim {
                 crate::pac::$inst
             }
+
+            fn state() -> &'static State {
+                static STATE: State = State::new();
+                &STATE
+            }
         }
         impl SealedBasicInstance for crate::peripherals::$inst {
             type GlobalInterrupt = crate::interrupt::typelevel::$irq;
@@ -1,4 +1,8 @@
 //! PWM driver.
+//!
+//! Clocked from LSE or LSI, the LPTIM keeps driving its outputs while the core is in Stop mode.
+//! The auto-reload and compare values are only updated at the end of the current period, so
+//! frequency and duty changes don't produce glitches on the outputs.
 
 use core::marker::PhantomData;
 
@@ -115,6 +119,23 @@ impl<'d, T: Instance> Pwm<'d, T> {
     fn post_init(&mut self) {}
 }
 
+#[cfg(not(any(lptim_v2a, lptim_v2b)))]
+impl<'d, T: Instance> embedded_hal_1::pwm::ErrorType for Pwm<'d, T> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(not(any(lptim_v2a, lptim_v2b)))]
+impl<'d, T: Instance> embedded_hal_1::pwm::SetDutyCycle for Pwm<'d, T> {
+    fn max_duty_cycle(&self) -> u16 {
+        self.get_max_duty()
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.set_duty(duty);
+        Ok(())
+    }
+}
+
 #[cfg(any(lptim_v2a, lptim_v2b))]
 impl<'d, T: Instance> Pwm<'d, T> {
     /// Create a new PWM driver.
@@ -157,6 +178,11 @@ impl<'d, T: Instance> Pwm<'d, T> {
         self.inner.get_compare_value(channel)
     }
 
+    /// Get a PWM channel, implementing [`SetDutyCycle`](embedded_hal_1::pwm::SetDutyCycle).
+    pub fn channel(&mut self, channel: Channel) -> PwmChannel<'_, 'd, T> {
+        PwmChannel { pwm: self, channel }
+    }
+
     fn post_init(&mut self) {
         [Channel::Ch1, Channel::Ch2].iter().for_each(|&channel| {
             self.inner.set_channel_direction(channel, ChannelDirection::OutputPwm);
@@ -164,10 +190,36 @@ impl<'d, T: Instance> Pwm<'d, T> {
     }
 }
 
+/// A single channel of a [`Pwm`], borrowed with [`Pwm::channel`].
+#[cfg(any(lptim_v2a, lptim_v2b))]
+pub struct PwmChannel<'a, 'd, T: Instance> {
+    pwm: &'a mut Pwm<'d, T>,
+    channel: Channel,
+}
+
+#[cfg(any(lptim_v2a, lptim_v2b))]
+impl<'a, 'd, T: Instance> embedded_hal_1::pwm::ErrorType for PwmChannel<'a, 'd, T> {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(any(lptim_v2a, lptim_v2b))]
+impl<'a, 'd, T: Instance> embedded_hal_1::pwm::SetDutyCycle for PwmChannel<'a, 'd, T> {
+    fn max_duty_cycle(&self) -> u16 {
+        self.pwm.get_max_duty()
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        self.pwm.set_duty(self.channel, duty);
+        Ok(())
+    }
+}
+
 impl<'d, T: Instance> Pwm<'d, T> {
     fn new_inner(tim: Peri<'d, T>, freq: Hertz) -> Self {
         let mut this = Self { inner: Timer::new(tim) };
 
+        // Must be configured while the timer is disabled.
+        this.inner.set_preload(true);
         this.inner.enable();
         this.set_frequency(freq);
 
@@ -180,10 +232,15 @@ impl<'d, T: Instance> Pwm<'d, T> {
 
     /// Set PWM frequency.
     ///
+    /// The new period starts at the end of the current one, unless the prescaler has to change,
+    /// in which case the counter is restarted.
+    ///
     /// Note: when you call this, the max duty value changes, so you will have to
     /// call `set_duty` on all channels with the duty calculated based on the new max duty.
     pub fn set_frequency(&mut self, frequency: Hertz) {
-        self.inner.set_frequency(frequency);
+        if self.inner.set_frequency(frequency) {
+            self.inner.continuous_mode_start();
+        }
     }
 
     /// Get max duty value.
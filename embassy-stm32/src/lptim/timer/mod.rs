@@ -50,7 +50,13 @@ impl<'d, T: Instance> Timer<'d, T> {
     }
 
     /// Set the frequency of how many times per second the timer counts up to the max value or down to 0.
-    pub fn set_frequency(&self, frequency: Hertz) {
+    ///
+    /// The auto-reload value is capped at `0xFFFE`, so that the max duty of a PWM output still
+    /// fits in a `u16`.
+    ///
+    /// The prescaler can only be changed while the timer is disabled. Returns `true` if it
+    /// changed, in which case the counter was stopped and has to be started again.
+    pub fn set_frequency(&self, frequency: Hertz) -> bool {
         let f = frequency.0;
         assert!(f > 0);
 
@@ -59,10 +65,58 @@ impl<'d, T: Instance> Timer<'d, T> {
         let pclk_ticks_per_timer_period = pclk_f / f;
 
         let psc = Prescaler::from_ticks(pclk_ticks_per_timer_period);
-        let arr = psc.scale_down(pclk_ticks_per_timer_period);
+        let arr = psc.scale_down(pclk_ticks_per_timer_period).min(0xFFFE);
+
+        let r = T::regs();
+        let presc = (&psc).into();
+        let changed = r.cfgr().read().presc() != presc;
+        if changed {
+            let enabled = r.cr().read().enable();
+            self.disable();
+            r.cfgr().modify(|w| w.set_presc(presc));
+            if enabled {
+                self.enable();
+            }
+        }
+        self.set_auto_reload_value(arr);
+        changed
+    }
+
+    /// Set the auto-reload value, the last value of the counter before it wraps.
+    ///
+    /// The timer is briefly enabled if it was disabled, as the register can only be written
+    /// while it is enabled. This waits until the write has been taken into account, which
+    /// takes a few kernel clock cycles.
+    pub fn set_auto_reload_value(&self, arr: u16) {
+        self.while_enabled(|| {
+            let r = T::regs();
+            r.icr().write(|w| w.set_arrokcf(true));
+            r.arr().modify(|w| w.set_arr(arr));
+            while !r.isr().read().arrok() {}
+        });
+    }
+
+    /// Run `f` with the timer enabled, enabling it briefly if it was disabled.
+    fn while_enabled(&self, f: impl FnOnce()) {
+        let enabled = T::regs().cr().read().enable();
+        if !enabled {
+            self.enable();
+        }
 
-        T::regs().cfgr().modify(|r| r.set_presc((&psc).into()));
-        T::regs().arr().modify(|r| r.set_arr(arr.into()));
+        f();
+
+        if !enabled {
+            self.disable();
+        }
+    }
+
+    /// Select whether the auto-reload and compare values are updated at the end of the current
+    /// period, instead of immediately.
+    ///
+    /// Updating at the end of the period avoids glitches on the PWM outputs. This can only be
+    /// changed while the timer is disabled.
+    pub fn set_preload(&self, preload: bool) {
+        T::regs().cfgr().modify(|w| w.set_preload(preload));
     }
 
     /// Get the timer frequency.
@@ -128,8 +182,16 @@ impl<'d, T: Instance> Timer<'d, T> {
     }
 
     /// Set compare value for a channel.
+    ///
+    /// The timer is briefly enabled if it was disabled, as the register can only be written
+    /// while it is enabled. This waits until the write has been taken into account.
     pub fn set_compare_value(&self, channel: Channel, value: u16) {
-        T::regs().ccr(channel.index()).modify(|w| w.set_ccr(value));
+        self.while_enabled(|| {
+            let r = T::regs();
+            r.icr().write(|w| w.set_cmpokcf(channel.index(), true));
+            r.ccr(channel.index()).modify(|w| w.set_ccr(value));
+            while !r.isr().read().cmpok(channel.index()) {}
+        });
     }
 
     /// Get compare value for a channel.
@@ -174,8 +236,16 @@ impl<'d, T: Instance> Timer<'d, T> {
 #[cfg(not(any(lptim_v2a, lptim_v2b)))]
 impl<'d, T: Instance> Timer<'d, T> {
     /// Set compare value for a channel.
+    ///
+    /// The timer is briefly enabled if it was disabled, as the register can only be written
+    /// while it is enabled. This waits until the write has been taken into account.
     pub fn set_compare_value(&self, value: u16) {
-        T::regs().cmp().modify(|w| w.set_cmp(value));
+        self.while_enabled(|| {
+            let r = T::regs();
+            r.icr().write(|w| w.set_cmpokcf(true));
+            r.cmp().modify(|w| w.set_cmp(value));
+            while !r.isr().read().cmpok() {}
+        });
     }
 
     /// Get compare value for a channel.
@@ -245,6 +245,16 @@ impl PixelFormat {
     }
 }
 
+/// Blending factor applied to a layer, see [`Ltdc::set_blending()`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BlendingFactor {
+    /// Use the layer constant alpha only.
+    ConstantAlpha,
+    /// Use the pixel alpha multiplied by the layer constant alpha.
+    PixelAlpha,
+}
+
 /// Ltdc Blending Layer
 #[repr(usize)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -256,6 +266,37 @@ pub enum LtdcLayer {
     Layer2 = 1,
 }
 
+/// Pair of framebuffers for tear-free double buffering, see [`Ltdc::swap_buffers()`].
+///
+/// One buffer is scanned out by the LTDC (the front buffer), while the other one (the back
+/// buffer) is drawn.
+pub struct DoubleBuffer<B: 'static> {
+    buffers: [&'static mut B; 2],
+    front: usize,
+}
+
+impl<B: 'static> DoubleBuffer<B> {
+    /// Create a double buffer, `front` being displayed first.
+    ///
+    /// Set `front` as the layer buffer with [`Ltdc::init_buffer()`] before enabling the layer.
+    pub fn new(front: &'static mut B, back: &'static mut B) -> Self {
+        Self {
+            buffers: [front, back],
+            front: 0,
+        }
+    }
+
+    /// Get the back buffer, to draw the next frame.
+    pub fn back(&mut self) -> &mut B {
+        &mut self.buffers[1 - self.front]
+    }
+
+    /// Get the address of the front buffer.
+    pub fn front_addr(&self) -> *const () {
+        &*self.buffers[self.front] as *const B as *const ()
+    }
+}
+
 impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandler<T> {
     unsafe fn on_interrupt() {
         cortex_m::asm::dsb();
@@ -564,16 +605,159 @@ impl<'d, T: Instance, I: Interface> Ltdc<'d, T, I> {
 
         // 256 color look-up table for L8, AL88 and AL88 pixel formats
         if let Some(clut) = clut {
-            assert_eq!(clut.len(), 256, "Color lookup table must be exactly 256 in length");
-            for (index, color) in clut.iter().enumerate() {
-                layer.clutwr().write(|w| {
-                    w.set_clutadd(index as u8);
-                    w.set_red(color.red);
-                    w.set_green(color.green);
-                    w.set_blue(color.blue);
-                });
+            Self::write_clut(layer_config.layer, clut);
+        }
+
+        Self::configure_window(layer_config);
+
+        // set the default color value to transparent black
+        layer.dccr().write_value(Dccr::default());
+
+        // set the global constant alpha value
+        let alpha = 0xFF;
+        layer.cacr().write(|w| w.set_consta(alpha));
+
+        // set the blending factors.
+        layer.bfcr().modify(|w| {
+            w.set_bf1(Bf1::Pixel);
+            w.set_bf2(Bf2::Pixel);
+        });
+
+        // enable LTDC_Layer by setting LEN bit
+        layer.cr().modify(|w| {
+            if clut.is_some() {
+                w.set_cluten(true);
+            }
+            w.set_len(true);
+        });
+    }
+
+    /// Update the pixel format, window position and size of a layer.
+    ///
+    /// Like the other layer settings, this is written to the shadow registers and takes effect
+    /// after the next [`Self::reload()`]. The framebuffer has to match the new format and size.
+    pub fn update_layer(&mut self, layer_config: &LtdcLayerConfig) {
+        Self::configure_window(layer_config);
+    }
+
+    /// Move the window of a layer, keeping its size.
+    ///
+    /// `x0` and `y0` are the new top-left corner, in pixels. Takes effect after the next
+    /// [`Self::reload()`].
+    pub fn set_window_position(&mut self, layer: LtdcLayer, x0: u16, y0: u16) {
+        let ltdc = T::regs();
+        let bpcr = ltdc.bpcr().read();
+        let layer = ltdc.layer(layer as usize);
+
+        let whpcr = layer.whpcr().read();
+        let width = whpcr.whsppos() + 1 - whpcr.whstpos();
+        layer.whpcr().write(|w| {
+            w.set_whstpos(x0 + bpcr.ahbp() + 1);
+            w.set_whsppos(x0 + width + bpcr.ahbp());
+        });
+
+        let wvpcr = layer.wvpcr().read();
+        let height = wvpcr.wvsppos() + 1 - wvpcr.wvstpos();
+        layer.wvpcr().write(|w| {
+            w.set_wvstpos(y0 + bpcr.avbp() + 1);
+            w.set_wvsppos(y0 + height + bpcr.avbp());
+        });
+    }
+
+    /// Set the constant alpha of a layer, from `0` (transparent) to `255` (opaque).
+    ///
+    /// Takes effect after the next [`Self::reload()`].
+    pub fn set_constant_alpha(&mut self, layer: LtdcLayer, alpha: u8) {
+        T::regs().layer(layer as usize).cacr().write(|w| w.set_consta(alpha));
+    }
+
+    /// Set how a layer is blended with the layers below it.
+    ///
+    /// With [`BlendingFactor::PixelAlpha`], the alpha channel of the pixel format is used, scaled
+    /// by the constant alpha. Takes effect after the next [`Self::reload()`].
+    pub fn set_blending(&mut self, layer: LtdcLayer, factor: BlendingFactor) {
+        T::regs().layer(layer as usize).bfcr().modify(|w| match factor {
+            BlendingFactor::ConstantAlpha => {
+                w.set_bf1(Bf1::Constant);
+                w.set_bf2(Bf2::Constant);
             }
+            BlendingFactor::PixelAlpha => {
+                w.set_bf1(Bf1::Pixel);
+                w.set_bf2(Bf2::Pixel);
+            }
+        });
+    }
+
+    /// Set the color of a layer outside of its window, with its alpha.
+    ///
+    /// Takes effect after the next [`Self::reload()`].
+    pub fn set_default_color(&mut self, layer: LtdcLayer, color: RgbColor, alpha: u8) {
+        T::regs().layer(layer as usize).dccr().write(|w| {
+            w.set_dcred(color.red);
+            w.set_dcgreen(color.green);
+            w.set_dcblue(color.blue);
+            w.set_dcalpha(alpha);
+        });
+    }
+
+    /// Set the color key of a layer, or disable color keying with `None`.
+    ///
+    /// Pixels matching the key color are made transparent, showing the layers below. Takes
+    /// effect after the next [`Self::reload()`].
+    pub fn set_color_key(&mut self, layer: LtdcLayer, key: Option<RgbColor>) {
+        let layer = T::regs().layer(layer as usize);
+        if let Some(key) = key {
+            layer.ckcr().write(|w| {
+                w.set_ckred(key.red);
+                w.set_ckgreen(key.green);
+                w.set_ckblue(key.blue);
+            });
+        }
+        layer.cr().modify(|w| w.set_colken(key.is_some()));
+    }
+
+    /// Set the distance between the starts of two lines of the layer framebuffer, in bytes.
+    ///
+    /// By default this is the window width. A GFXMMU virtual buffer needs its line length, see
+    /// [`BlockMode::line_bytes()`](crate::gfxmmu::BlockMode::line_bytes). Takes effect after the
+    /// next [`Self::reload()`].
+    pub fn set_pitch(&mut self, layer: LtdcLayer, pitch: u16) {
+        T::regs().layer(layer as usize).cfblr().modify(|w| w.set_cfbp(pitch));
+    }
+
+    /// Load the color look-up table of a layer and enable it.
+    ///
+    /// The table is used by the L8, AL44 and AL88 pixel formats and must hold 256 colors. It is
+    /// written immediately, so it should only be loaded while the layer is disabled or during
+    /// vertical blanking, see [`Self::wait_for_vsync()`].
+    pub fn load_clut(&mut self, layer: LtdcLayer, clut: &[RgbColor]) {
+        Self::write_clut(layer, clut);
+        T::regs().layer(layer as usize).cr().modify(|w| w.set_cluten(true));
+    }
+
+    /// Enable or disable a layer.
+    ///
+    /// Takes effect after the next [`Self::reload()`].
+    pub fn set_layer_enabled(&mut self, layer: LtdcLayer, enabled: bool) {
+        T::regs().layer(layer as usize).cr().modify(|w| w.set_len(enabled));
+    }
+
+    fn write_clut(layer: LtdcLayer, clut: &[RgbColor]) {
+        assert_eq!(clut.len(), 256, "Color lookup table must be exactly 256 in length");
+        let layer = T::regs().layer(layer as usize);
+        for (index, color) in clut.iter().enumerate() {
+            layer.clutwr().write(|w| {
+                w.set_clutadd(index as u8);
+                w.set_red(color.red);
+                w.set_green(color.green);
+                w.set_blue(color.blue);
+            });
         }
+    }
+
+    fn configure_window(layer_config: &LtdcLayerConfig) {
+        let ltdc = T::regs();
+        let layer = ltdc.layer(layer_config.layer as usize);
 
         // configure the horizontal start and stop position
         let h_win_start = layer_config.window_x0 + ltdc.bpcr().read().ahbp() + 1;
@@ -596,19 +780,6 @@ impl<'d, T: Instance, I: Interface> Ltdc<'d, T, I> {
             .pfcr()
             .write(|w| w.set_pf(Pf::from_bits(layer_config.pixel_format as u8)));
 
-        // set the default color value to transparent black
-        layer.dccr().write_value(Dccr::default());
-
-        // set the global constant alpha value
-        let alpha = 0xFF;
-        layer.cacr().write(|w| w.set_consta(alpha));
-
-        // set the blending factors.
-        layer.bfcr().modify(|w| {
-            w.set_bf1(Bf1::Pixel);
-            w.set_bf2(Bf2::Pixel);
-        });
-
         // calculate framebuffer pixel size in bytes
         let bytes_per_pixel = layer_config.pixel_format.bytes_per_pixel() as u16;
         let width = layer_config.window_x1 - layer_config.window_x0;
@@ -625,14 +796,6 @@ impl<'d, T: Instance, I: Interface> Ltdc<'d, T, I> {
 
         // framebuffer line number
         layer.cfblnr().modify(|w| w.set_cfblnbr(height));
-
-        // enable LTDC_Layer by setting LEN bit
-        layer.cr().modify(|w| {
-            if clut.is_some() {
-                w.set_cluten(true);
-            }
-            w.set_len(true);
-        });
     }
 
     /// Set the framebuffer address for a layer, but do not update the shadow registers
@@ -742,6 +905,29 @@ impl<'d, T: Instance, I: Interface> Ltdc<'d, T, I> {
         result
     }
 
+    /// Wait for the start of the vertical blanking period, after the last active line.
+    pub async fn wait_for_vsync(&mut self) {
+        let last_active_line = T::regs().awcr().read().aah();
+        self.wait_line(last_active_line + 1).await
+    }
+
+    /// Display the back buffer of `buffers` on `layer`, without tearing.
+    ///
+    /// The new framebuffer address is latched by the LTDC at the start of the next vertical
+    /// blanking period. This waits for it, so once this returns the previous front buffer is
+    /// no longer read and becomes the back buffer.
+    ///
+    /// If the future is dropped early, the swap still happens at the next vertical blanking.
+    pub async fn swap_buffers<B>(&mut self, layer: LtdcLayer, buffers: &mut DoubleBuffer<B>) -> Result<(), Error> {
+        buffers.front = 1 - buffers.front;
+
+        // Drop stale flags, so that only the reload requested here completes the wait.
+        Self::clear_interrupt_flags();
+        let layer = T::regs().layer(layer as usize);
+        layer.cfbar().write(|w| w.set_cfbadd(buffers.front_addr() as u32));
+        self.reload().await
+    }
+
     /// Get the total height including blanking.
     pub fn total_height(&self) -> u16 {
         T::regs().twcr().read().totalh()
@@ -24,6 +24,65 @@ pub enum OpAmpGain {
     Mul64,
 }
 
+impl OpAmpGain {
+    /// Gain field of PGA_GAIN, without the mode bits.
+    fn bits(self) -> u8 {
+        match self {
+            OpAmpGain::Mul2 => 0b000,
+            OpAmpGain::Mul4 => 0b001,
+            OpAmpGain::Mul8 => 0b010,
+            OpAmpGain::Mul16 => 0b011,
+            #[cfg(opamp_v5)]
+            OpAmpGain::Mul32 => 0b100,
+            #[cfg(opamp_v5)]
+            OpAmpGain::Mul64 => 0b101,
+        }
+    }
+
+    #[cfg(opamp_v5)]
+    fn pga_gain(self) -> PgaGain {
+        match self {
+            OpAmpGain::Mul2 => PgaGain::Gain2,
+            OpAmpGain::Mul4 => PgaGain::Gain4,
+            OpAmpGain::Mul8 => PgaGain::Gain8,
+            OpAmpGain::Mul16 => PgaGain::Gain16,
+            OpAmpGain::Mul32 => PgaGain::Gain32,
+            OpAmpGain::Mul64 => PgaGain::Gain64,
+        }
+    }
+
+    #[cfg(not(opamp_v5))]
+    fn pga_gain(self) -> PgaGain {
+        PgaGain::from_bits(self.bits())
+    }
+
+    #[cfg(opamp_v5)]
+    fn pga_gain_biased(self) -> PgaGain {
+        match self {
+            OpAmpGain::Mul2 => PgaGain::Gain2InputVinm0,
+            OpAmpGain::Mul4 => PgaGain::Gain4InputVinm0,
+            OpAmpGain::Mul8 => PgaGain::Gain8InputVinm0,
+            OpAmpGain::Mul16 => PgaGain::Gain16InputVinm0,
+            OpAmpGain::Mul32 => PgaGain::Gain32InputVinm0,
+            OpAmpGain::Mul64 => PgaGain::Gain64InputVinm0,
+        }
+    }
+}
+
+/// Offset trimming values of the differential pairs.
+///
+/// Obtained with [`OpAmp::calibrate`] and [`OpAmp::trim`]. They can be stored and restored with
+/// [`OpAmp::set_trim`] to skip the calibration on the next boot.
+#[cfg(opamp_v5)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OpAmpTrim {
+    /// Trimming value of the PMOS differential pair, `0..=31`.
+    pub p: u8,
+    /// Trimming value of the NMOS differential pair, `0..=31`.
+    pub n: u8,
+}
+
 #[cfg(opamp_v5)]
 enum OpAmpDifferentialPair {
     P,
@@ -131,27 +190,10 @@ impl<'d, T: Instance> OpAmp<'d, T> {
         #[cfg(not(opamp_v5))]
         let vm_sel = VmSel::from_bits(0b10);
 
-        #[cfg(opamp_v5)]
-        let pga_gain = match gain {
-            OpAmpGain::Mul2 => PgaGain::Gain2,
-            OpAmpGain::Mul4 => PgaGain::Gain4,
-            OpAmpGain::Mul8 => PgaGain::Gain8,
-            OpAmpGain::Mul16 => PgaGain::Gain16,
-            OpAmpGain::Mul32 => PgaGain::Gain32,
-            OpAmpGain::Mul64 => PgaGain::Gain64,
-        };
-        #[cfg(not(opamp_v5))]
-        let pga_gain = PgaGain::from_bits(match gain {
-            OpAmpGain::Mul2 => 0b00,
-            OpAmpGain::Mul4 => 0b01,
-            OpAmpGain::Mul8 => 0b10,
-            OpAmpGain::Mul16 => 0b11,
-        });
-
         T::regs().csr().modify(|w| {
             w.set_vp_sel(VpSel::from_bits(in_pin.channel()));
             w.set_vm_sel(vm_sel);
-            w.set_pga_gain(pga_gain);
+            w.set_pga_gain(gain.pga_gain());
             #[cfg(opamp_v5)]
             w.set_opaintoen(false);
             w.set_opampen(true);
@@ -184,19 +226,10 @@ impl<'d, T: Instance> OpAmp<'d, T> {
         bias_pin.set_as_analog();
         out_pin.set_as_analog();
 
-        let pga_gain = match gain {
-            OpAmpGain::Mul2 => PgaGain::Gain2InputVinm0,
-            OpAmpGain::Mul4 => PgaGain::Gain4InputVinm0,
-            OpAmpGain::Mul8 => PgaGain::Gain8InputVinm0,
-            OpAmpGain::Mul16 => PgaGain::Gain16InputVinm0,
-            OpAmpGain::Mul32 => PgaGain::Gain32InputVinm0,
-            OpAmpGain::Mul64 => PgaGain::Gain64InputVinm0,
-        };
-
         T::regs().csr().modify(|w| {
             w.set_vp_sel(VpSel::from_bits(in_pin.channel()));
             w.set_vm_sel(VmSel::Pga);
-            w.set_pga_gain(pga_gain);
+            w.set_pga_gain(gain.pga_gain_biased());
             w.set_opaintoen(false);
             w.set_opampen(true);
         });
@@ -207,6 +240,11 @@ impl<'d, T: Instance> OpAmp<'d, T> {
     /// Configure the OpAmp as a buffer for the DAC it is connected to,
     /// outputting to the provided output pin, and enable the opamp.
     ///
+    /// The non-inverting input is driven by the DAC channel wired to this opamp instance:
+    /// DAC3_CH1 for OPAMP1 and OPAMP3, DAC3_CH2 for OPAMP2 and OPAMP6, DAC4_CH1 for OPAMP4
+    /// and DAC4_CH2 for OPAMP5. The DAC channel output should be configured as internal only,
+    /// so that it is not connected to its own pin.
+    ///
     /// The output pin is held within the returned [`OpAmpOutput`] struct,
     /// preventing it being used elsewhere. The `OpAmpOutput` can then be
     /// directly used as an ADC input. The opamp will be disabled when the
@@ -219,7 +257,7 @@ impl<'d, T: Instance> OpAmp<'d, T> {
             use crate::pac::opamp::vals::*;
 
             w.set_vm_sel(VmSel::Output);
-            w.set_vp_sel(VpSel::Dac3Ch1);
+            w.set_vp_sel(dac_vp_sel());
             w.set_opaintoen(false);
             w.set_opampen(true);
         });
@@ -227,6 +265,26 @@ impl<'d, T: Instance> OpAmp<'d, T> {
         OpAmpOutput { _inner: self }
     }
 
+    /// Configure the OpAmp as a buffer for the DAC it is connected to,
+    /// with the output only used internally, and enable the opamp.
+    ///
+    /// This gives a low impedance DAC signal to the ADC, e.g. for self-test. The DAC channel
+    /// is the one wired to this opamp instance, see [`OpAmp::buffer_dac`].
+    ///
+    /// The returned `OpAmpInternalOutput` struct may be used as an ADC input.
+    /// The opamp output will be disabled when it is dropped.
+    #[cfg(opamp_v5)]
+    pub fn buffer_dac_int(&mut self) -> OpAmpInternalOutput<'_, T> {
+        T::regs().csr().modify(|w| {
+            w.set_vm_sel(VmSel::Output);
+            w.set_vp_sel(dac_vp_sel());
+            w.set_opaintoen(true);
+            w.set_opampen(true);
+        });
+
+        OpAmpInternalOutput { _inner: self }
+    }
+
     /// Configure the OpAmp as a buffer for the provided input pin,
     /// with the output only used internally, and enable the opamp.
     ///
@@ -269,19 +327,10 @@ impl<'d, T: Instance> OpAmp<'d, T> {
     ) -> OpAmpInternalOutput<'_, T> {
         pin.set_as_analog();
 
-        let pga_gain = match gain {
-            OpAmpGain::Mul2 => PgaGain::Gain2,
-            OpAmpGain::Mul4 => PgaGain::Gain4,
-            OpAmpGain::Mul8 => PgaGain::Gain8,
-            OpAmpGain::Mul16 => PgaGain::Gain16,
-            OpAmpGain::Mul32 => PgaGain::Gain32,
-            OpAmpGain::Mul64 => PgaGain::Gain64,
-        };
-
         T::regs().csr().modify(|w| {
             w.set_vp_sel(VpSel::from_bits(pin.channel()));
             w.set_vm_sel(VmSel::Pga);
-            w.set_pga_gain(pga_gain);
+            w.set_pga_gain(gain.pga_gain());
             w.set_opaintoen(true);
             w.set_opampen(true);
         });
@@ -309,19 +358,10 @@ impl<'d, T: Instance> OpAmp<'d, T> {
         in_pin.set_as_analog();
         bias_pin.set_as_analog();
 
-        let pga_gain = match gain {
-            OpAmpGain::Mul2 => PgaGain::Gain2InputVinm0,
-            OpAmpGain::Mul4 => PgaGain::Gain4InputVinm0,
-            OpAmpGain::Mul8 => PgaGain::Gain8InputVinm0,
-            OpAmpGain::Mul16 => PgaGain::Gain16InputVinm0,
-            OpAmpGain::Mul32 => PgaGain::Gain32InputVinm0,
-            OpAmpGain::Mul64 => PgaGain::Gain64InputVinm0,
-        };
-
         T::regs().csr().modify(|w| {
             w.set_vp_sel(VpSel::from_bits(in_pin.channel()));
             w.set_vm_sel(VmSel::Pga);
-            w.set_pga_gain(pga_gain);
+            w.set_pga_gain(gain.pga_gain_biased());
             w.set_opaintoen(true);
             w.set_opampen(true);
         });
@@ -347,7 +387,7 @@ impl<'d, T: Instance> OpAmp<'d, T> {
 
         T::regs().csr().modify(|w| {
             use crate::pac::opamp::vals::*;
-            w.set_vp_sel(VpSel::Dac3Ch1); // Actually DAC3_CHx
+            w.set_vp_sel(dac_vp_sel());
             w.set_vm_sel(VmSel::from_bits(m_pin.channel()));
             w.set_opaintoen(true);
             w.set_opampen(true);
@@ -377,7 +417,7 @@ impl<'d, T: Instance> OpAmp<'d, T> {
 
         T::regs().csr().modify(|w| {
             use crate::pac::opamp::vals::*;
-            w.set_vp_sel(VpSel::Dac3Ch1); // Actually DAC3_CHx
+            w.set_vp_sel(dac_vp_sel());
             w.set_vm_sel(VmSel::from_bits(m_pin.channel()));
             w.set_opaintoen(false);
             w.set_opampen(true);
@@ -396,7 +436,6 @@ impl<'d, T: Instance> OpAmp<'d, T> {
     /// The output pin is held within the returned [`OpAmpOutput`] struct,
     /// preventing it being used elsewhere. The opamp will be disabled when
     /// the [`OpAmpOutput`] is dropped.
-    #[cfg(opamp_v5)]
     pub fn standalone_ext(
         &mut self,
         p_pin: Peri<'d, impl NonInvertingPin<T> + crate::gpio::Pin>,
@@ -408,9 +447,9 @@ impl<'d, T: Instance> OpAmp<'d, T> {
         out_pin.set_as_analog();
 
         T::regs().csr().modify(|w| {
-            use crate::pac::opamp::vals::*;
             w.set_vp_sel(VpSel::from_bits(p_pin.channel()));
             w.set_vm_sel(VmSel::from_bits(m_pin.channel()));
+            #[cfg(opamp_v5)]
             w.set_opaintoen(false);
             w.set_opampen(true);
         });
@@ -476,6 +515,36 @@ impl<'d, T: Instance> OpAmp<'d, T> {
         });
     }
 
+    /// Get the offset trimming values currently in use by the user trim mode.
+    #[cfg(opamp_v5)]
+    pub fn trim(&self) -> OpAmpTrim {
+        let csr = T::regs().csr().read();
+        OpAmpTrim {
+            p: csr.trimoffsetp(),
+            n: csr.trimoffsetn(),
+        }
+    }
+
+    /// Use the provided offset trimming values, e.g. from an earlier [`Self::calibrate`].
+    ///
+    /// This selects the user trim mode.
+    #[cfg(opamp_v5)]
+    pub fn set_trim(&mut self, trim: OpAmpTrim) {
+        T::regs().csr().modify(|w| {
+            w.set_trimoffsetp(trim.p.min(31));
+            w.set_trimoffsetn(trim.n.min(31));
+            w.set_usertrim(true);
+        });
+    }
+
+    /// Go back to the factory offset trimming values.
+    #[cfg(opamp_v5)]
+    pub fn use_factory_trim(&mut self) {
+        T::regs().csr().modify(|w| {
+            w.set_usertrim(false);
+        });
+    }
+
     /// Calibrate differential pair.
     ///
     /// The calibration is done by trying different offset values and
@@ -540,6 +609,47 @@ impl<'d, T: Instance> Drop for OpAmp<'d, T> {
     }
 }
 
+impl<'d, T: Instance> OpAmpOutput<'d, T> {
+    /// Change the gain of the PGA.
+    ///
+    /// Only meaningful if the opamp was configured as a PGA. The bias configuration of the gain
+    /// network is kept.
+    pub fn set_gain(&mut self, gain: OpAmpGain) {
+        set_gain::<T>(gain);
+    }
+}
+
+#[cfg(opamp_v5)]
+impl<'d, T: Instance> OpAmpInternalOutput<'d, T> {
+    /// Change the gain of the PGA.
+    ///
+    /// Only meaningful if the opamp was configured as a PGA. The bias configuration of the gain
+    /// network is kept.
+    pub fn set_gain(&mut self, gain: OpAmpGain) {
+        set_gain::<T>(gain);
+    }
+}
+
+/// VP_SEL value selecting the DAC channel wired to the opamp instance.
+///
+/// The PAC names it after OPAMP1's DAC3_CH1, but the same value selects the instance's own
+/// channel on every opamp, e.g. DAC4_CH2 on OPAMP5.
+#[cfg(opamp_v5)]
+fn dac_vp_sel() -> VpSel {
+    VpSel::from_bits(0b11)
+}
+
+fn set_gain<T: Instance>(gain: OpAmpGain) {
+    T::regs().csr().modify(|w| {
+        // The upper bits of PGA_GAIN select how the gain network is connected.
+        #[cfg(opamp_v5)]
+        let bits = (w.pga_gain().to_bits() & !0b111) | gain.bits();
+        #[cfg(not(opamp_v5))]
+        let bits = gain.bits();
+        w.set_pga_gain(PgaGain::from_bits(bits));
+    });
+}
+
 impl<'d, T: Instance> Drop for OpAmpOutput<'d, T> {
     fn drop(&mut self) {
         T::regs().csr().modify(|w| {
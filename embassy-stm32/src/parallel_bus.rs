@@ -0,0 +1,210 @@
+//! Bit-banged parallel bus
+//!
+//! Drives 8080-style parallel peripherals (LCD controllers, latches, external ADCs...) with
+//! GPIOs, where FMC can't be used. The data lines are a [`PortBus`], so all of them change with a
+//! single register write, and the active-low control strobes are plain outputs. Delays between
+//! edges are done by cycle counting on the core clock.
+//!
+//! Chip select is not toggled by the read and write cycles, so that several cycles can be
+//! grouped, e.g. a command followed by its parameters. Use [`ParallelBus::select()`] and
+//! [`ParallelBus::deselect()`] around them.
+
+use crate::gpio::{AnyPin, Level, Output, PortBus, Pull, Speed};
+use crate::time::Hertz;
+use crate::{Peri, rcc};
+
+/// Timing of the bus cycles, in nanoseconds.
+///
+/// The delays are rounded up to whole core clock cycles, and come on top of the time it takes
+/// to write the GPIO registers, so the actual timing is always a bit slower.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timing {
+    /// Time from the data being driven to the start of the WR strobe.
+    pub setup_ns: u32,
+    /// Duration of the WR strobe.
+    pub write_strobe_ns: u32,
+    /// Time from the start of the RD strobe until the data is sampled.
+    pub read_access_ns: u32,
+    /// Time after releasing a strobe before the next cycle.
+    pub hold_ns: u32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Direction {
+    Read,
+    Write,
+}
+
+#[derive(Copy, Clone)]
+struct Cycles {
+    setup: u32,
+    write_strobe: u32,
+    read_access: u32,
+    hold: u32,
+}
+
+/// Bit-banged parallel bus driver.
+pub struct ParallelBus<'d, const N: usize> {
+    data: PortBus<'d, N>,
+    wr: Output<'d>,
+    rd: Option<Output<'d>>,
+    cs: Option<Output<'d>>,
+    speed: Speed,
+    direction: Direction,
+    cycles: Cycles,
+}
+
+impl<'d, const N: usize> ParallelBus<'d, N> {
+    /// Create a bus with write and read strobes.
+    ///
+    /// `speed` is used for the data lines and the strobes. The bus starts deselected, with the
+    /// data lines driven.
+    pub fn new(
+        data: PortBus<'d, N>,
+        wr: Peri<'d, AnyPin>,
+        rd: Peri<'d, AnyPin>,
+        cs: Option<Peri<'d, AnyPin>>,
+        speed: Speed,
+        timing: Timing,
+    ) -> Self {
+        Self::new_inner(data, wr, Some(rd), cs, speed, timing)
+    }
+
+    /// Create a write-only bus, without read strobe.
+    pub fn new_write_only(
+        data: PortBus<'d, N>,
+        wr: Peri<'d, AnyPin>,
+        cs: Option<Peri<'d, AnyPin>>,
+        speed: Speed,
+        timing: Timing,
+    ) -> Self {
+        Self::new_inner(data, wr, None, cs, speed, timing)
+    }
+
+    fn new_inner(
+        mut data: PortBus<'d, N>,
+        wr: Peri<'d, AnyPin>,
+        rd: Option<Peri<'d, AnyPin>>,
+        cs: Option<Peri<'d, AnyPin>>,
+        speed: Speed,
+        timing: Timing,
+    ) -> Self {
+        data.set_as_output(speed);
+        Self {
+            data,
+            wr: Output::new(wr, Level::High, speed),
+            rd: rd.map(|rd| Output::new(rd, Level::High, speed)),
+            cs: cs.map(|cs| Output::new(cs, Level::High, speed)),
+            speed,
+            direction: Direction::Write,
+            cycles: Self::to_cycles(&timing),
+        }
+    }
+
+    fn to_cycles(timing: &Timing) -> Cycles {
+        let hz = core_clock().0 as u64;
+        let cycles = |ns: u32| (ns as u64 * hz).div_ceil(1_000_000_000) as u32;
+        Cycles {
+            setup: cycles(timing.setup_ns),
+            write_strobe: cycles(timing.write_strobe_ns),
+            read_access: cycles(timing.read_access_ns),
+            hold: cycles(timing.hold_ns),
+        }
+    }
+
+    /// Change the timing of the bus cycles.
+    ///
+    /// This must be called again if the core clock changes.
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.cycles = Self::to_cycles(&timing);
+    }
+
+    /// Assert chip select.
+    pub fn select(&mut self) {
+        if let Some(cs) = &mut self.cs {
+            cs.set_low();
+        }
+    }
+
+    /// Release chip select.
+    pub fn deselect(&mut self) {
+        if let Some(cs) = &mut self.cs {
+            cs.set_high();
+        }
+    }
+
+    /// Run a write cycle.
+    pub fn write(&mut self, value: u16) {
+        if self.direction != Direction::Write {
+            self.data.set_as_output(self.speed);
+            self.direction = Direction::Write;
+        }
+
+        self.data.write(value);
+        delay(self.cycles.setup);
+        self.wr.set_low();
+        delay(self.cycles.write_strobe);
+        self.wr.set_high();
+        delay(self.cycles.hold);
+    }
+
+    /// Run a read cycle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bus was created without read strobe.
+    pub fn read(&mut self) -> u16 {
+        if self.direction != Direction::Read {
+            // Release the data lines before the peripheral starts driving them.
+            self.data.set_as_input(Pull::None);
+            self.direction = Direction::Read;
+        }
+
+        let rd = unwrap!(self.rd.as_mut());
+        rd.set_low();
+        delay(self.cycles.read_access);
+        let value = self.data.read();
+        rd.set_high();
+        delay(self.cycles.hold);
+        value
+    }
+
+    /// Run a write cycle for each value of `values`.
+    pub fn write_all(&mut self, values: &[u16]) {
+        for &value in values {
+            self.write(value);
+        }
+    }
+
+    /// Run a read cycle for each value of `values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bus was created without read strobe.
+    pub fn read_into(&mut self, values: &mut [u16]) {
+        for value in values {
+            *value = self.read();
+        }
+    }
+}
+
+#[inline(always)]
+fn delay(cycles: u32) {
+    if cycles > 0 {
+        cortex_m::asm::delay(cycles);
+    }
+}
+
+/// Frequency of the core running the delay loops.
+fn core_clock() -> Hertz {
+    let freqs = unsafe { rcc::get_freqs() };
+    // On H7 the core runs from SYSCLK divided by the core prescaler, ahead of the AHB prescaler,
+    // so HCLK would underestimate it. A second core running from HCLK only gets longer delays.
+    #[cfg(stm32h7)]
+    return freqs.sys.to_hertz().unwrap() / crate::pac::RCC.d1cfgr().read().d1cpre();
+    #[cfg(stm32h7rs)]
+    return freqs.sys.to_hertz().unwrap() / crate::pac::RCC.cdcfgr().read().cpre();
+    #[cfg(not(any(stm32h7, stm32h7rs)))]
+    freqs.hclk1.to_hertz().unwrap()
+}
@@ -21,6 +21,9 @@
 //! | ECDSA Verify | 0x26 | Verify ECDSA signatures |
 //! | Point Check | 0x28 | Validate point is on curve |
 //!
+//! Curve parameters for NIST P-192, P-224, P-256, P-384 and P-521 are provided by
+//! [`EcdsaCurveParams`].
+//!
 //! # Example - ECDSA Signature Verification (async)
 //!
 //! ```no_run
@@ -42,6 +45,18 @@
 //! let valid = pka.ecdsa_verify(&params, &public_key, &signature, &hash).await?;
 //! ```
 //!
+//! # Example - ECDH Key Agreement (async)
+//!
+//! ```no_run
+//! let curve = EcdsaCurveParams::nist_p256();
+//!
+//! let mut public_key = EccPoint::new(32);
+//! pka.ecdh_public_key(&curve, &private_key, &mut public_key).await?;
+//!
+//! let mut shared_secret = [0u8; 32];
+//! pka.ecdh(&curve, &private_key, &peer_public, &mut shared_secret).await?;
+//! ```
+//!
 //! For blocking use, swap `Pka::new` for `Pka::new_blocking` and call
 //! `pka.ecdsa_verify_blocking(...)` etc. without `.await`.
 //!
@@ -416,6 +431,58 @@ impl EcdsaCurveParams {
             order: &P256_N,
         }
     }
+
+    /// NIST P-192 (secp192r1) curve parameters
+    pub const fn nist_p192() -> Self {
+        Self {
+            p_modulus: &P192_P,
+            a_coefficient: &P192_A,
+            a_coefficient_sign: 1, // a = -3
+            b_coefficient: &P192_B,
+            generator_x: &P192_GX,
+            generator_y: &P192_GY,
+            order: &P192_N,
+        }
+    }
+
+    /// NIST P-224 (secp224r1) curve parameters
+    pub const fn nist_p224() -> Self {
+        Self {
+            p_modulus: &P224_P,
+            a_coefficient: &P224_A,
+            a_coefficient_sign: 1, // a = -3
+            b_coefficient: &P224_B,
+            generator_x: &P224_GX,
+            generator_y: &P224_GY,
+            order: &P224_N,
+        }
+    }
+
+    /// NIST P-384 (secp384r1) curve parameters
+    pub const fn nist_p384() -> Self {
+        Self {
+            p_modulus: &P384_P,
+            a_coefficient: &P384_A,
+            a_coefficient_sign: 1, // a = -3
+            b_coefficient: &P384_B,
+            generator_x: &P384_GX,
+            generator_y: &P384_GY,
+            order: &P384_N,
+        }
+    }
+
+    /// NIST P-521 (secp521r1) curve parameters
+    pub const fn nist_p521() -> Self {
+        Self {
+            p_modulus: &P521_P,
+            a_coefficient: &P521_A,
+            a_coefficient_sign: 1, // a = -3
+            b_coefficient: &P521_B,
+            generator_x: &P521_GX,
+            generator_y: &P521_GY,
+            order: &P521_N,
+        }
+    }
 }
 
 // NIST P-256 curve parameters (big-endian)
@@ -445,6 +512,128 @@ const P256_N: [u8; 32] = [
     0xAD, 0xA7, 0x17, 0x9E, 0x84, 0xF3, 0xB9, 0xCA, 0xC2, 0xFC, 0x63, 0x25, 0x51,
 ];
 
+// NIST P-192 curve parameters (big-endian)
+const P192_P: [u8; 24] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+const P192_A: [u8; 24] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x03,
+];
+const P192_B: [u8; 24] = [
+    0x64, 0x21, 0x05, 0x19, 0xE5, 0x9C, 0x80, 0xE7, 0x0F, 0xA7, 0xE9, 0xAB, 0x72, 0x24, 0x30, 0x49, 0xFE, 0xB8, 0xDE,
+    0xEC, 0xC1, 0x46, 0xB9, 0xB1,
+];
+const P192_GX: [u8; 24] = [
+    0x18, 0x8D, 0xA8, 0x0E, 0xB0, 0x30, 0x90, 0xF6, 0x7C, 0xBF, 0x20, 0xEB, 0x43, 0xA1, 0x88, 0x00, 0xF4, 0xFF, 0x0A,
+    0xFD, 0x82, 0xFF, 0x10, 0x12,
+];
+const P192_GY: [u8; 24] = [
+    0x07, 0x19, 0x2B, 0x95, 0xFF, 0xC8, 0xDA, 0x78, 0x63, 0x10, 0x11, 0xED, 0x6B, 0x24, 0xCD, 0xD5, 0x73, 0xF9, 0x77,
+    0xA1, 0x1E, 0x79, 0x48, 0x11,
+];
+const P192_N: [u8; 24] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x99, 0xDE, 0xF8, 0x36, 0x14, 0x6B, 0xC9,
+    0xB1, 0xB4, 0xD2, 0x28, 0x31,
+];
+
+// NIST P-224 curve parameters (big-endian)
+const P224_P: [u8; 28] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+const P224_A: [u8; 28] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+];
+const P224_B: [u8; 28] = [
+    0xB4, 0x05, 0x0A, 0x85, 0x0C, 0x04, 0xB3, 0xAB, 0xF5, 0x41, 0x32, 0x56, 0x50, 0x44, 0xB0, 0xB7, 0xD7, 0xBF, 0xD8,
+    0xBA, 0x27, 0x0B, 0x39, 0x43, 0x23, 0x55, 0xFF, 0xB4,
+];
+const P224_GX: [u8; 28] = [
+    0xB7, 0x0E, 0x0C, 0xBD, 0x6B, 0xB4, 0xBF, 0x7F, 0x32, 0x13, 0x90, 0xB9, 0x4A, 0x03, 0xC1, 0xD3, 0x56, 0xC2, 0x11,
+    0x22, 0x34, 0x32, 0x80, 0xD6, 0x11, 0x5C, 0x1D, 0x21,
+];
+const P224_GY: [u8; 28] = [
+    0xBD, 0x37, 0x63, 0x88, 0xB5, 0xF7, 0x23, 0xFB, 0x4C, 0x22, 0xDF, 0xE6, 0xCD, 0x43, 0x75, 0xA0, 0x5A, 0x07, 0x47,
+    0x64, 0x44, 0xD5, 0x81, 0x99, 0x85, 0x00, 0x7E, 0x34,
+];
+const P224_N: [u8; 28] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x16, 0xA2, 0xE0, 0xB8, 0xF0,
+    0x3E, 0x13, 0xDD, 0x29, 0x45, 0x5C, 0x5C, 0x2A, 0x3D,
+];
+
+// NIST P-384 curve parameters (big-endian)
+const P384_P: [u8; 48] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+const P384_A: [u8; 48] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+];
+const P384_B: [u8; 48] = [
+    0xB3, 0x31, 0x2F, 0xA7, 0xE2, 0x3E, 0xE7, 0xE4, 0x98, 0x8E, 0x05, 0x6B, 0xE3, 0xF8, 0x2D, 0x19, 0x18, 0x1D, 0x9C,
+    0x6E, 0xFE, 0x81, 0x41, 0x12, 0x03, 0x14, 0x08, 0x8F, 0x50, 0x13, 0x87, 0x5A, 0xC6, 0x56, 0x39, 0x8D, 0x8A, 0x2E,
+    0xD1, 0x9D, 0x2A, 0x85, 0xC8, 0xED, 0xD3, 0xEC, 0x2A, 0xEF,
+];
+const P384_GX: [u8; 48] = [
+    0xAA, 0x87, 0xCA, 0x22, 0xBE, 0x8B, 0x05, 0x37, 0x8E, 0xB1, 0xC7, 0x1E, 0xF3, 0x20, 0xAD, 0x74, 0x6E, 0x1D, 0x3B,
+    0x62, 0x8B, 0xA7, 0x9B, 0x98, 0x59, 0xF7, 0x41, 0xE0, 0x82, 0x54, 0x2A, 0x38, 0x55, 0x02, 0xF2, 0x5D, 0xBF, 0x55,
+    0x29, 0x6C, 0x3A, 0x54, 0x5E, 0x38, 0x72, 0x76, 0x0A, 0xB7,
+];
+const P384_GY: [u8; 48] = [
+    0x36, 0x17, 0xDE, 0x4A, 0x96, 0x26, 0x2C, 0x6F, 0x5D, 0x9E, 0x98, 0xBF, 0x92, 0x92, 0xDC, 0x29, 0xF8, 0xF4, 0x1D,
+    0xBD, 0x28, 0x9A, 0x14, 0x7C, 0xE9, 0xDA, 0x31, 0x13, 0xB5, 0xF0, 0xB8, 0xC0, 0x0A, 0x60, 0xB1, 0xCE, 0x1D, 0x7E,
+    0x81, 0x9D, 0x7A, 0x43, 0x1D, 0x7C, 0x90, 0xEA, 0x0E, 0x5F,
+];
+const P384_N: [u8; 48] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xC7, 0x63, 0x4D, 0x81, 0xF4, 0x37, 0x2D, 0xDF, 0x58, 0x1A, 0x0D, 0xB2, 0x48, 0xB0,
+    0xA7, 0x7A, 0xEC, 0xEC, 0x19, 0x6A, 0xCC, 0xC5, 0x29, 0x73,
+];
+
+// NIST P-521 curve parameters (big-endian)
+const P521_P: [u8; 66] = [
+    0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+];
+const P521_A: [u8; 66] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+];
+const P521_B: [u8; 66] = [
+    0x00, 0x51, 0x95, 0x3E, 0xB9, 0x61, 0x8E, 0x1C, 0x9A, 0x1F, 0x92, 0x9A, 0x21, 0xA0, 0xB6, 0x85, 0x40, 0xEE, 0xA2,
+    0xDA, 0x72, 0x5B, 0x99, 0xB3, 0x15, 0xF3, 0xB8, 0xB4, 0x89, 0x91, 0x8E, 0xF1, 0x09, 0xE1, 0x56, 0x19, 0x39, 0x51,
+    0xEC, 0x7E, 0x93, 0x7B, 0x16, 0x52, 0xC0, 0xBD, 0x3B, 0xB1, 0xBF, 0x07, 0x35, 0x73, 0xDF, 0x88, 0x3D, 0x2C, 0x34,
+    0xF1, 0xEF, 0x45, 0x1F, 0xD4, 0x6B, 0x50, 0x3F, 0x00,
+];
+const P521_GX: [u8; 66] = [
+    0x00, 0xC6, 0x85, 0x8E, 0x06, 0xB7, 0x04, 0x04, 0xE9, 0xCD, 0x9E, 0x3E, 0xCB, 0x66, 0x23, 0x95, 0xB4, 0x42, 0x9C,
+    0x64, 0x81, 0x39, 0x05, 0x3F, 0xB5, 0x21, 0xF8, 0x28, 0xAF, 0x60, 0x6B, 0x4D, 0x3D, 0xBA, 0xA1, 0x4B, 0x5E, 0x77,
+    0xEF, 0xE7, 0x59, 0x28, 0xFE, 0x1D, 0xC1, 0x27, 0xA2, 0xFF, 0xA8, 0xDE, 0x33, 0x48, 0xB3, 0xC1, 0x85, 0x6A, 0x42,
+    0x9B, 0xF9, 0x7E, 0x7E, 0x31, 0xC2, 0xE5, 0xBD, 0x66,
+];
+const P521_GY: [u8; 66] = [
+    0x01, 0x18, 0x39, 0x29, 0x6A, 0x78, 0x9A, 0x3B, 0xC0, 0x04, 0x5C, 0x8A, 0x5F, 0xB4, 0x2C, 0x7D, 0x1B, 0xD9, 0x98,
+    0xF5, 0x44, 0x49, 0x57, 0x9B, 0x44, 0x68, 0x17, 0xAF, 0xBD, 0x17, 0x27, 0x3E, 0x66, 0x2C, 0x97, 0xEE, 0x72, 0x99,
+    0x5E, 0xF4, 0x26, 0x40, 0xC5, 0x50, 0xB9, 0x01, 0x3F, 0xAD, 0x07, 0x61, 0x35, 0x3C, 0x70, 0x86, 0xA2, 0x72, 0xC2,
+    0x40, 0x88, 0xBE, 0x94, 0x76, 0x9F, 0xD1, 0x66, 0x50,
+];
+const P521_N: [u8; 66] = [
+    0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFA, 0x51, 0x86, 0x87, 0x83,
+    0xBF, 0x2F, 0x96, 0x6B, 0x7F, 0xCC, 0x01, 0x48, 0xF7, 0x09, 0xA5, 0xD0, 0x3B, 0xB5, 0xC9, 0xB8, 0x89, 0x9C, 0x47,
+    0xAE, 0xBB, 0x6F, 0xB7, 0x1E, 0x91, 0x38, 0x64, 0x09,
+];
+
 /// ECDSA public key
 pub struct EcdsaPublicKey<'a> {
     /// Public key x-coordinate
@@ -1781,6 +1970,47 @@ impl<'d, T: Instance> Pka<'d, T, Blocking> {
         self.read_point_check()
     }
 
+    /// Compute an ECDH public key: `public = private_key * G`.
+    ///
+    /// `public_key` must be initialized with the size of the curve modulus.
+    pub fn ecdh_public_key_blocking(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        private_key: &[u8],
+        public_key: &mut EccPoint,
+    ) -> Result<(), Error> {
+        self.ecc_mul_blocking(curve, private_key, curve.generator_x, curve.generator_y, public_key)
+    }
+
+    /// Compute an ECDH shared secret with the public key of the peer.
+    ///
+    /// The peer's key is validated with a point check first, returning
+    /// `Error::PointNotOnCurve` if it is not on the curve. The shared secret is the
+    /// x-coordinate of `private_key * peer_public`, written to the first modulus-size bytes
+    /// of `shared_secret`.
+    pub fn ecdh_blocking(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        private_key: &[u8],
+        peer_public: &EcdsaPublicKey,
+        shared_secret: &mut [u8],
+    ) -> Result<(), Error> {
+        let size = curve.p_modulus.len();
+        if shared_secret.len() < size {
+            return Err(Error::InvalidSize);
+        }
+        if !self.point_check_blocking(curve, peer_public.x, peer_public.y)? {
+            return Err(Error::PointNotOnCurve);
+        }
+
+        let mut point = EccPoint::new(size);
+        self.ecc_mul_blocking(curve, private_key, peer_public.x, peer_public.y, &mut point)?;
+        shared_secret[..size].copy_from_slice(&point.x[..size]);
+        point.x.fill(0);
+        point.y.fill(0);
+        Ok(())
+    }
+
     /// Perform modular exponentiation: `result = base^exp mod n`.
     ///
     /// This is the core RSA operation:
@@ -2203,6 +2433,49 @@ impl<'d, T: Instance> Pka<'d, T, Async> {
         self.read_point_check()
     }
 
+    /// Compute an ECDH public key: `public = private_key * G`.
+    ///
+    /// `public_key` must be initialized with the size of the curve modulus.
+    pub async fn ecdh_public_key(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        private_key: &[u8],
+        public_key: &mut EccPoint,
+    ) -> Result<(), Error> {
+        self.ecc_mul(curve, private_key, curve.generator_x, curve.generator_y, public_key)
+            .await
+    }
+
+    /// Compute an ECDH shared secret with the public key of the peer.
+    ///
+    /// The peer's key is validated with a point check first, returning
+    /// `Error::PointNotOnCurve` if it is not on the curve. The shared secret is the
+    /// x-coordinate of `private_key * peer_public`, written to the first modulus-size bytes
+    /// of `shared_secret`.
+    pub async fn ecdh(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        private_key: &[u8],
+        peer_public: &EcdsaPublicKey<'_>,
+        shared_secret: &mut [u8],
+    ) -> Result<(), Error> {
+        let size = curve.p_modulus.len();
+        if shared_secret.len() < size {
+            return Err(Error::InvalidSize);
+        }
+        if !self.point_check(curve, peer_public.x, peer_public.y).await? {
+            return Err(Error::PointNotOnCurve);
+        }
+
+        let mut point = EccPoint::new(size);
+        self.ecc_mul(curve, private_key, peer_public.x, peer_public.y, &mut point)
+            .await?;
+        shared_secret[..size].copy_from_slice(&point.x[..size]);
+        point.x.fill(0);
+        point.y.fill(0);
+        Ok(())
+    }
+
     /// Perform modular exponentiation: `result = base^exp mod n`.
     ///
     /// This is the core RSA operation:
@@ -64,11 +64,13 @@ type Bdcr = crate::pac::rcc::regs::Csr;
 #[cfg(any(stm32c0))]
 type Bdcr = crate::pac::rcc::regs::Csr1;
 
+/// Disable backup domain write protection.
 #[cfg(any(stm32c0))]
-fn unlock() {}
+pub(crate) fn unlock_backup_domain() {}
 
+/// Disable backup domain write protection.
 #[cfg(not(any(stm32c0)))]
-fn unlock() {
+pub(crate) fn unlock_backup_domain() {
     #[cfg(any(stm32f0, stm32f1, stm32f2, stm32f3, stm32l0, stm32l1))]
     let cr = crate::pac::PWR.cr();
     #[cfg(not(any(
@@ -185,7 +187,7 @@ impl LsConfig {
         _ = lse_drv; // not all chips have it.
 
         // Disable backup domain write protection
-        unlock();
+        unlock_backup_domain();
 
         if self.lsi {
             #[cfg(any(stm32u5, stm32h5, stm32wba))]
@@ -14,6 +14,9 @@ use crate::{Peri, interrupt, pac, peripherals, rcc};
 
 static RNG_WAKER: AtomicWaker = AtomicWaker::new();
 
+/// Number of seed error recoveries [`Rng::wait_ready`] tries before giving up.
+const MAX_SEED_RECOVERIES: u32 = 3;
+
 #[cfg(not(rng_v1))]
 /// Health-test programming profile used during RNG conditioning reset.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -98,6 +101,18 @@ pub enum Error {
     ClockError,
 }
 
+/// Health-test failure counters, see [`Rng::health_stats`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HealthStats {
+    /// Seed errors, i.e. failed noise source health tests.
+    pub seed_errors: u32,
+    /// Clock errors.
+    pub clock_errors: u32,
+    /// Seed errors that were still present after recovery.
+    pub failed_recoveries: u32,
+}
+
 /// RNG interrupt handler.
 pub struct InterruptHandler<T: Instance> {
     _marker: PhantomData<T>,
@@ -116,6 +131,9 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
 /// RNG driver.
 pub struct Rng<'d, T: Instance> {
     _inner: Peri<'d, T>,
+    #[cfg(not(rng_v1))]
+    config: RngConfig,
+    stats: HealthStats,
 }
 
 impl<'d, T: Instance> Rng<'d, T> {
@@ -151,7 +169,10 @@ impl<'d, T: Instance> Rng<'d, T> {
         // Verify clock is available
         T::frequency();
 
-        let mut random = Self { _inner: inner };
+        let mut random = Self {
+            _inner: inner,
+            stats: HealthStats::default(),
+        };
         random.reset();
 
         T::Interrupt::unpend();
@@ -167,7 +188,11 @@ impl<'d, T: Instance> Rng<'d, T> {
         // Verify clock is available
         T::frequency();
 
-        let mut random = Self { _inner: inner };
+        let mut random = Self {
+            _inner: inner,
+            config,
+            stats: HealthStats::default(),
+        };
         random.reset_with_config(config);
 
         T::Interrupt::unpend();
@@ -193,15 +218,29 @@ impl<'d, T: Instance> Rng<'d, T> {
         let _ = self.next_u32();
     }
 
-    /// Reset the RNG.
+    /// Reset the RNG, keeping the configuration of the driver.
     #[cfg(not(rng_v1))]
     pub fn reset(&mut self) {
-        self.reset_with_config(RngConfig::default());
+        self.reset_with_config(self.config);
     }
 
     #[cfg(not(rng_v1))]
     /// Reset the RNG with a caller-provided configuration policy.
+    ///
+    /// The configuration is kept, and used again when recovering from seed errors.
     pub fn reset_with_config(&mut self, config: RngConfig) {
+        self.config = config;
+        self.conditioning_reset();
+
+        // According to reference manual: after software reset, wait for random number to be ready
+        // The next_u32() call will wait for DRDY, completing the initialization
+        let _ = self.next_u32();
+    }
+
+    /// Run the conditioning reset (CONDRST) sequence with the configuration of the driver.
+    #[cfg(not(rng_v1))]
+    fn conditioning_reset(&mut self) {
+        let config = self.config;
         T::regs().cr().write(|reg| {
             reg.set_condrst(true);
             reg.set_nistc(config.nistc);
@@ -255,17 +294,31 @@ impl<'d, T: Instance> Rng<'d, T> {
         T::regs().sr().modify(|reg| {
             reg.set_seis(false);
         });
+    }
 
-        // According to reference manual: after software reset, wait for random number to be ready
-        // The next_u32() call will wait for DRDY, completing the initialization
-        let _ = self.next_u32();
+    /// Restart the noise source after a seed error, without waiting for it to recover.
+    fn start_seed_recovery(&mut self) {
+        #[cfg(rng_v1)]
+        self.reset();
+
+        #[cfg(not(rng_v1))]
+        if T::regs().cr().read().configlock() {
+            // CONDRST can't be written anymore, rely on the automatic reset of the hardware.
+            T::regs().sr().modify(|reg| reg.set_seis(false));
+        } else {
+            self.conditioning_reset();
+        }
     }
 
     /// Try to recover from a seed error.
+    ///
+    /// On RNGv2 and later this runs the conditioning reset (CONDRST) sequence with the
+    /// configuration of the driver, on RNGv1 the RNG is disabled and enabled again.
     pub fn recover_seed_error(&mut self) {
-        self.reset();
+        self.start_seed_recovery();
         // reset should also clear the SEIS flag
         if T::regs().sr().read().seis() {
+            self.stats.failed_recoveries = self.stats.failed_recoveries.saturating_add(1);
             warn!("recovering from seed error failed");
             return;
         }
@@ -273,57 +326,92 @@ impl<'d, T: Instance> Rng<'d, T> {
         while T::regs().sr().read().secs() {}
     }
 
+    /// Health-test failures counted since the driver was created or the counters were cleared.
+    pub fn health_stats(&self) -> HealthStats {
+        self.stats
+    }
+
+    /// Clear the health-test failure counters.
+    pub fn clear_health_stats(&mut self) {
+        self.stats = HealthStats::default();
+    }
+
+    /// Wait until a random number is ready.
+    ///
+    /// Seed errors are recovered from, and clock errors are cleared, while waiting. Both are
+    /// counted in [`Self::health_stats`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Error::SeedError` if seed errors keep coming back after recovery, and
+    /// `Error::ClockError` if the RNG clock is still out of spec.
+    pub async fn wait_ready(&mut self) -> Result<(), Error> {
+        let mut recoveries = 0;
+        loop {
+            let sr = T::regs().sr().read();
+            if sr.seis() {
+                self.stats.seed_errors = self.stats.seed_errors.saturating_add(1);
+                if recoveries == MAX_SEED_RECOVERIES {
+                    self.stats.failed_recoveries = self.stats.failed_recoveries.saturating_add(1);
+                    return Err(Error::SeedError);
+                }
+                // the data in DR must not be used, the recovery discards it
+                recoveries += 1;
+                self.start_seed_recovery();
+            } else if sr.ceis() {
+                // the RNG keeps running once the clock is back in spec
+                self.stats.clock_errors = self.stats.clock_errors.saturating_add(1);
+                T::regs().sr().modify(|sr| sr.set_ceis(false));
+                if T::regs().sr().read().cecs() {
+                    return Err(Error::ClockError);
+                }
+            } else if sr.drdy() {
+                return Ok(());
+            } else {
+                Self::wait_for_event().await;
+            }
+        }
+    }
+
+    async fn wait_for_event() {
+        poll_fn(|cx| {
+            // quick check to avoid registration if already done.
+            let bits = T::regs().sr().read();
+            if bits.drdy() || bits.seis() || bits.ceis() {
+                return Poll::Ready(());
+            }
+            RNG_WAKER.register(cx.waker());
+            T::regs().cr().modify(|reg| reg.set_ie(true));
+            // Need to check condition **after** `register` to avoid a race
+            // condition that would result in lost notifications.
+            let bits = T::regs().sr().read();
+            if bits.drdy() || bits.seis() || bits.ceis() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
     /// Fill the given slice with random values.
+    ///
+    /// Health-test failures are recovered from as in [`Self::wait_ready`].
     pub async fn async_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
         for chunk in dest.chunks_mut(4) {
-            let mut bits = T::regs().sr().read();
-            if !bits.seis() && !bits.ceis() && !bits.drdy() {
-                // wait for interrupt
-                poll_fn(|cx| {
-                    // quick check to avoid registration if already done.
-                    let bits = T::regs().sr().read();
-                    if bits.drdy() || bits.seis() || bits.ceis() {
-                        return Poll::Ready(());
-                    }
-                    RNG_WAKER.register(cx.waker());
-                    T::regs().cr().modify(|reg| reg.set_ie(true));
-                    // Need to check condition **after** `register` to avoid a race
-                    // condition that would result in lost notifications.
-                    let bits = T::regs().sr().read();
-                    if bits.drdy() || bits.seis() || bits.ceis() {
-                        Poll::Ready(())
-                    } else {
-                        Poll::Pending
-                    }
-                })
-                .await;
-
-                // Re-read the status register after wait.
-                bits = T::regs().sr().read()
-            }
-            if bits.seis() {
-                // in case of noise-source or seed error we try to recover here
-                // but we must not use the data in DR and we return an error
-                // to leave retry-logic to the application
-                self.recover_seed_error();
-                return Err(Error::SeedError);
-            } else if bits.ceis() {
-                // clock error detected, DR could still be used but keep it safe,
-                // clear the error and abort
-                T::regs().sr().modify(|sr| sr.set_ceis(false));
-                return Err(Error::ClockError);
-            } else if bits.drdy() {
+            let random_word = loop {
+                self.wait_ready().await?;
                 // DR can be read up to four times until the output buffer is empty
                 // DRDY is cleared automatically when that happens
                 let random_word = T::regs().dr().read();
-                // reference manual: always check if DR is zero
-                if random_word == 0 {
-                    return Err(Error::SeedError);
-                }
-                // write bytes to chunk
-                for (dest, src) in chunk.iter_mut().zip(random_word.to_ne_bytes().iter()) {
-                    *dest = *src
+                // reference manual: always check if DR is zero, a seed error is pending then
+                if random_word != 0 {
+                    break random_word;
                 }
+            };
+            // write bytes to chunk
+            for (dest, src) in chunk.iter_mut().zip(random_word.to_ne_bytes().iter()) {
+                *dest = *src
             }
         }
 
@@ -331,13 +419,24 @@ impl<'d, T: Instance> Rng<'d, T> {
     }
 
     /// Get a random u32
+    ///
+    /// Seed errors are recovered from, and clock errors are cleared, while waiting. This blocks
+    /// until a valid random number is available.
     pub fn next_u32(&mut self) -> u32 {
         loop {
             let sr = T::regs().sr().read();
-            if sr.seis() | sr.ceis() {
-                self.reset();
+            if sr.seis() {
+                self.stats.seed_errors = self.stats.seed_errors.saturating_add(1);
+                self.recover_seed_error();
+            } else if sr.ceis() {
+                self.stats.clock_errors = self.stats.clock_errors.saturating_add(1);
+                T::regs().sr().modify(|sr| sr.set_ceis(false));
             } else if sr.drdy() {
-                return T::regs().dr().read();
+                let random_word = T::regs().dr().read();
+                // a zero word means a seed error is pending
+                if random_word != 0 {
+                    return random_word;
+                }
             }
         }
     }
@@ -380,21 +479,7 @@ impl<'d, T: Instance> crate::low_power::SealedSuspendablePeripheral for Rng<'d,
     fn suspend(self) -> Self::InternalState {
         #[cfg(not(rng_v1))]
         {
-            let cr = T::regs().cr().read();
-
-            let config = RngConfig {
-                nistc: cr.nistc(),
-                clkdiv: cr.clkdiv(),
-                rng_config1: cr.rng_config1(),
-                rng_config2: cr.rng_config2(),
-                rng_config3: cr.rng_config3(),
-                clock_error_detector: !cr.ced(),
-                #[cfg(any(rng_v3, rng_wba6))]
-                auto_reset_disable: cr.ardis(),
-                ..Default::default()
-            };
-
-            unsafe { (self._inner.clone_unchecked(), config) }
+            unsafe { (self._inner.clone_unchecked(), self.config) }
         }
 
         #[cfg(rng_v1)]
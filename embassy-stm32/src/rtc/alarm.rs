@@ -0,0 +1,294 @@
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::datetime::day_of_week_to_u8;
+use super::{
+    DateTime, DateTimeError, DayOfWeek, InterruptHandler, Rtc, RtcError, RtcInterrupt, SealedInstance, byte_to_bcd2,
+};
+use crate::interrupt::typelevel::Binding;
+use crate::pac::rtc::regs::Alrmr;
+#[cfg(not(rtc_v2_f2))]
+use crate::pac::rtc::regs::Alrmssr;
+use crate::pac::rtc::vals::{AlrmrMsk, AlrmrPm, AlrmrWdsel};
+use crate::peripherals::RTC;
+
+static ALARM_WAKERS: [AtomicWaker; 2] = [const { AtomicWaker::new() }; 2];
+static ALARM_FIRED: [AtomicBool; 2] = [const { AtomicBool::new(false) }; 2];
+
+/// RTC alarm.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Alarm {
+    /// Alarm A
+    #[default]
+    A = 0,
+    /// Alarm B
+    B = 1,
+}
+
+/// Date part of an alarm.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlarmDate {
+    /// Match every day.
+    #[default]
+    Any,
+    /// Match a day of the month, `1..=31`.
+    Day(u8),
+    /// Match a day of the week.
+    Weekday(DayOfWeek),
+}
+
+/// Sub-second part of an alarm.
+///
+/// The sub-second counter counts down from the synchronous prescaler value (`frequency - 1` of
+/// [`RtcConfig`](super::RtcConfig)) to 0 within each second.
+#[cfg(not(rtc_v2_f2))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlarmSubsecond {
+    /// Value to match.
+    pub value: u16,
+    /// Number of least significant bits of the counter compared to `value`, `1..=15`.
+    ///
+    /// The alarm fires every `2^bits` counter ticks within the other matched fields.
+    pub bits: u8,
+}
+
+/// Alarm configuration.
+///
+/// Fields set to [`None`] (or [`AlarmDate::Any`]) are masked and match any value, e.g. an
+/// alarm with only `second: Some(0)` fires at the start of every minute. The default
+/// configuration masks everything and fires every second.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlarmConfig {
+    /// Alarm to use.
+    pub alarm: Alarm,
+    /// Day to match.
+    pub date: AlarmDate,
+    /// Hour to match, `0..=23`.
+    pub hour: Option<u8>,
+    /// Minute to match, `0..=59`.
+    pub minute: Option<u8>,
+    /// Second to match, `0..=59`.
+    pub second: Option<u8>,
+    /// Sub-second to match.
+    #[cfg(not(rtc_v2_f2))]
+    pub subsecond: Option<AlarmSubsecond>,
+}
+
+impl AlarmConfig {
+    /// Alarm firing once at `t`, matching day of the month, hour, minute and second.
+    ///
+    /// The alarm has no month or year, so it fires again in the next month with that day.
+    pub fn at(alarm: Alarm, t: &DateTime) -> Self {
+        Self {
+            alarm,
+            date: AlarmDate::Day(t.day()),
+            hour: Some(t.hour()),
+            minute: Some(t.minute()),
+            second: Some(t.second()),
+            #[cfg(not(rtc_v2_f2))]
+            subsecond: None,
+        }
+    }
+
+    /// Alarm firing every day at the given time.
+    pub fn daily(alarm: Alarm, hour: u8, minute: u8, second: u8) -> Self {
+        Self {
+            alarm,
+            date: AlarmDate::Any,
+            hour: Some(hour),
+            minute: Some(minute),
+            second: Some(second),
+            #[cfg(not(rtc_v2_f2))]
+            subsecond: None,
+        }
+    }
+
+    fn to_register(&self) -> Result<Alrmr, RtcError> {
+        let check = |v: Option<u8>, max: u8, err: DateTimeError| match v {
+            Some(v) if v > max => Err(RtcError::InvalidDateTime(err)),
+            v => Ok(v),
+        };
+        let hour = check(self.hour, 23, DateTimeError::InvalidHour)?;
+        let minute = check(self.minute, 59, DateTimeError::InvalidMinute)?;
+        let second = check(self.second, 59, DateTimeError::InvalidSecond)?;
+
+        let msk = |v: Option<u8>| match v {
+            Some(_) => AlrmrMsk::ToMatch,
+            None => AlrmrMsk::NotMatch,
+        };
+
+        let mut w = Alrmr(0);
+        w.set_pm(AlrmrPm::Am);
+
+        let (st, su) = byte_to_bcd2(second.unwrap_or(0));
+        w.set_st(st);
+        w.set_su(su);
+        w.set_msk1(msk(second));
+
+        let (mnt, mnu) = byte_to_bcd2(minute.unwrap_or(0));
+        w.set_mnt(mnt);
+        w.set_mnu(mnu);
+        w.set_msk2(msk(minute));
+
+        let (ht, hu) = byte_to_bcd2(hour.unwrap_or(0));
+        w.set_ht(ht);
+        w.set_hu(hu);
+        w.set_msk3(msk(hour));
+
+        match self.date {
+            AlarmDate::Any => {
+                w.set_wdsel(AlrmrWdsel::DateUnits);
+                w.set_du(1);
+                w.set_msk4(AlrmrMsk::NotMatch);
+            }
+            AlarmDate::Day(day) => {
+                if !(1..=31).contains(&day) {
+                    return Err(RtcError::InvalidDateTime(DateTimeError::InvalidDay));
+                }
+                let (dt, du) = byte_to_bcd2(day);
+                w.set_wdsel(AlrmrWdsel::DateUnits);
+                w.set_dt(dt);
+                w.set_du(du);
+                w.set_msk4(AlrmrMsk::ToMatch);
+            }
+            AlarmDate::Weekday(day) => {
+                w.set_wdsel(AlrmrWdsel::WeekDay);
+                w.set_du(day_of_week_to_u8(day));
+                w.set_msk4(AlrmrMsk::ToMatch);
+            }
+        }
+
+        Ok(w)
+    }
+
+    #[cfg(not(rtc_v2_f2))]
+    fn to_subsecond_register(&self) -> Alrmssr {
+        let mut w = Alrmssr(0);
+        // MASKSS = 0 leaves the sub-seconds out of the comparison.
+        if let Some(ss) = self.subsecond {
+            w.set_maskss(ss.bits.min(15));
+            w.set_ss(ss.value & 0x7fff);
+        }
+        w
+    }
+}
+
+impl Rtc {
+    /// Wait until the calendar matches `config`.
+    ///
+    /// `_irq` must bind the RTC alarm interrupt vector to [`InterruptHandler`]. The alarm stays
+    /// armed after it fired, so calling this again with the same configuration waits for the
+    /// next match. On RTCv2 parts the alarm EXTI line is enabled, so the alarm wakes the core
+    /// from Stop.
+    ///
+    /// The alarm lives in the backup domain. When it is still armed with the same configuration
+    /// and already fired, e.g. because it woke the device from Standby, this returns immediately.
+    ///
+    /// # Errors
+    ///
+    /// Will return `RtcError::InvalidDateTime` if a field of `config` is out of range.
+    pub async fn wait_for_alarm<I: RtcInterrupt>(
+        &mut self,
+        _irq: impl Binding<I, InterruptHandler<I>>,
+        config: AlarmConfig,
+    ) -> Result<(), RtcError> {
+        let n = config.alarm as usize;
+        let alrmr = config.to_register()?;
+        #[cfg(not(rtc_v2_f2))]
+        let alrmssr = config.to_subsecond_register();
+
+        let r = RTC::regs();
+        let armed = r.cr().read().alre(n) && r.cr().read().alrie(n) && r.alrmr(n).read().0 == alrmr.0;
+        #[cfg(not(rtc_v2_f2))]
+        let armed = armed && r.alrmssr(n).read().0 == alrmssr.0;
+        if !armed {
+            self.write(false, |r| {
+                r.cr().modify(|w| {
+                    w.set_alre(n, false);
+                    w.set_alrie(n, false);
+                });
+                clear_alarm_flag(n);
+
+                #[cfg(rtc_v2)]
+                while !r.isr().read().alrwf(n) {}
+
+                r.alrmr(n).write_value(alrmr);
+                #[cfg(not(rtc_v2_f2))]
+                r.alrmssr(n).write_value(alrmssr);
+
+                r.cr().modify(|w| {
+                    w.set_alre(n, true);
+                    w.set_alrie(n, true);
+                });
+            });
+            ALARM_FIRED[n].store(false, Ordering::Relaxed);
+        }
+
+        #[cfg(rtc_v2)]
+        super::enable_exti_line(RTC::EXTI_ALARM_LINE);
+        unsafe { I::enable() };
+
+        poll_fn(|cx| {
+            ALARM_WAKERS[n].register(cx.waker());
+
+            // The flag is checked directly as well, an alarm that fired before a reset from
+            // Standby has no pending interrupt.
+            let fired = critical_section::with(|_| ALARM_FIRED[n].swap(false, Ordering::Relaxed) || take_alarm_flag(n));
+            if fired { Poll::Ready(Ok(())) } else { Poll::Pending }
+        })
+        .await
+    }
+
+    /// Disable an alarm.
+    pub fn disable_alarm(&mut self, alarm: Alarm) {
+        let n = alarm as usize;
+        self.write(false, |r| {
+            r.cr().modify(|w| {
+                w.set_alre(n, false);
+                w.set_alrie(n, false);
+            });
+            clear_alarm_flag(n);
+        });
+        ALARM_FIRED[n].store(false, Ordering::Relaxed);
+    }
+}
+
+fn clear_alarm_flag(n: usize) {
+    let r = RTC::regs();
+    #[cfg(rtc_v2)]
+    r.isr().modify(|w| w.set_alrf(n, false));
+    #[cfg(rtc_v3)]
+    r.scr().write(|w| w.set_calrf(n, crate::pac::rtc::vals::Calrf::Clear));
+}
+
+fn take_alarm_flag(n: usize) -> bool {
+    let r = RTC::regs();
+    #[cfg(rtc_v2)]
+    let fired = r.isr().read().alrf(n);
+    #[cfg(rtc_v3)]
+    let fired = r.sr().read().alrf(n);
+
+    if fired {
+        clear_alarm_flag(n);
+    }
+    fired
+}
+
+pub(super) fn on_interrupt() {
+    for n in 0..2 {
+        if take_alarm_flag(n) {
+            ALARM_FIRED[n].store(true, Ordering::Relaxed);
+            ALARM_WAKERS[n].wake();
+        }
+    }
+
+    #[cfg(rtc_v2)]
+    super::clear_exti_line(RTC::EXTI_ALARM_LINE);
+}
@@ -0,0 +1,57 @@
+use super::{Rtc, SealedInstance};
+use crate::peripherals::RTC;
+
+/// Backup registers reserved for the HAL, at the end of the register file.
+#[cfg(feature = "rtc-reserve-backup-register")]
+const RESERVED: usize = 1;
+#[cfg(not(feature = "rtc-reserve-backup-register"))]
+const RESERVED: usize = 0;
+
+/// Index of a backup register available to the application.
+///
+/// The index is checked when the value is created, so reads and writes through it can't go out of
+/// range. With the `rtc-reserve-backup-register` feature the last register is reserved for the HAL
+/// and can't be named.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BackupRegister(usize);
+
+impl BackupRegister {
+    /// Number of backup registers available to the application.
+    pub const COUNT: usize = RTC::BACKUP_REGISTER_COUNT.saturating_sub(RESERVED);
+
+    /// Backup register `index`, or [`None`] if there is no such register.
+    pub const fn new(index: usize) -> Option<Self> {
+        if index < Self::COUNT { Some(Self(index)) } else { None }
+    }
+
+    /// Backup register `N`, checked at compile time.
+    pub const fn at<const N: usize>() -> Self {
+        const { assert!(N < Self::COUNT, "backup register index out of range") };
+        Self(N)
+    }
+
+    /// Index of the register.
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl Rtc {
+    /// Read a backup register.
+    ///
+    /// The registers retain their values during wakes from standby mode or system resets. They also
+    /// retain their value when Vdd is switched off as long as V_BAT is powered. They are erased on
+    /// a tamper event, unless that is disabled for the tamper input.
+    pub fn read_backup(&self, register: BackupRegister) -> u32 {
+        unwrap!(RTC::read_backup_register(RTC::regs(), register.0))
+    }
+
+    /// Write a backup register.
+    ///
+    /// Backup domain write protection is disabled as needed.
+    pub fn write_backup(&self, register: BackupRegister, value: u32) {
+        crate::rcc::unlock_backup_domain();
+        RTC::write_backup_register(RTC::regs(), register.0, value)
+    }
+}
@@ -1,11 +1,20 @@
 //! Real Time Clock (RTC)
+mod alarm;
+mod backup;
 mod datetime;
+// RTCv3 tamper inputs are in the TAMP peripheral, which some parts don't have.
+#[cfg(any(rtc_v2, peri_tamp))]
+mod tamper;
+mod timestamp;
 
 #[cfg(all(feature = "low-power", not(feature = "_lp-time-driver")))]
 mod low_power;
+#[cfg(any(not(feature = "low-power"), feature = "_lp-time-driver"))]
+mod wakeup_timer;
 
 #[cfg(all(feature = "low-power", not(feature = "_lp-time-driver")))]
 use core::cell::{RefCell, RefMut};
+use core::marker::PhantomData;
 #[cfg(all(feature = "low-power", not(feature = "_lp-time-driver")))]
 use core::ops;
 
@@ -16,8 +25,19 @@ use embassy_sync::blocking_mutex::Mutex;
 #[cfg(all(feature = "low-power", not(feature = "_lp-time-driver")))]
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 
+#[cfg(not(rtc_v2_f2))]
+pub use self::alarm::AlarmSubsecond;
+pub use self::alarm::{Alarm, AlarmConfig, AlarmDate};
+pub use self::backup::BackupRegister;
 pub use self::datetime::{DateTime, DayOfWeek, Error as DateTimeError};
 use self::datetime::{day_of_week_from_u8, day_of_week_to_u8};
+#[cfg(any(rtc_v2, peri_tamp))]
+pub use self::tamper::{
+    TAMPER_COUNT, TamperConfig, TamperFilter, TamperInputConfig, TamperPrecharge, TamperSampleFrequency,
+};
+pub use self::timestamp::{Timestamp, TimestampEdge};
+#[cfg(any(not(feature = "low-power"), feature = "_lp-time-driver"))]
+pub use self::wakeup_timer::WakeupClock;
 use crate::pac::rtc::regs::{Dr, Tr};
 use crate::time::Hertz;
 
@@ -30,8 +50,54 @@ mod _version;
 pub use _version::*;
 
 use crate::Peri;
+use crate::interrupt::typelevel::{Handler, Interrupt};
 use crate::peripherals::RTC;
 
+/// RTC interrupt handler.
+///
+/// Bind the RTC alarm, wakeup timer, tamper and timestamp interrupt vectors to this handler:
+/// `RTC_ALARM`, `RTC_WKUP` and `TAMP_STAMP` on most RTCv2 parts, `RTC`, `TAMP` or `RTC_TAMP` on
+/// the others. Like the EXTI handler, it is generic over the interrupt because the vectors differ
+/// between families, but it only accepts the vectors of the RTC and TAMP peripherals. With the
+/// `low-power` feature the wakeup timer and its vector are used by the time driver, so the
+/// wakeup timer API is not available and shared vectors can't be bound.
+pub struct InterruptHandler<I: RtcInterrupt> {
+    _marker: PhantomData<I>,
+}
+
+impl<I: RtcInterrupt> Handler<I> for InterruptHandler<I> {
+    unsafe fn on_interrupt() {
+        alarm::on_interrupt();
+        #[cfg(any(rtc_v2, peri_tamp))]
+        tamper::on_interrupt();
+        timestamp::on_interrupt();
+        #[cfg(any(not(feature = "low-power"), feature = "_lp-time-driver"))]
+        wakeup_timer::on_interrupt();
+    }
+}
+
+/// Enable a configurable EXTI line of an RTC event, so the event wakes the core from Stop.
+#[allow(dead_code)]
+fn enable_exti_line(line: usize) {
+    use crate::pac::EXTI;
+
+    critical_section::with(|_| {
+        EXTI.rtsr(0).modify(|w| w.set_line(line, true));
+
+        #[cfg(not(any(stm32wb, stm32wl5x, stm32h7)))]
+        EXTI.imr(0).modify(|w| w.set_line(line, true));
+        #[cfg(any(stm32wb, stm32wl5x, stm32h7))]
+        EXTI.cpu(0).imr(0).modify(|w| w.set_line(line, true));
+    });
+}
+
+/// Clear the pending state of a configurable EXTI line of an RTC event.
+#[allow(dead_code, unused_variables)]
+fn clear_exti_line(line: usize) {
+    #[cfg(any(exti_v1, stm32h7, stm32wb))]
+    crate::pac::EXTI.pr(0).write(|w| w.set_line(line, true));
+}
+
 /// Errors that can occur on methods on [Rtc]
 #[non_exhaustive]
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -66,7 +132,8 @@ impl RtcTimeProvider {
     /// Will return an `RtcError::InvalidDateTime` if the stored value in the system is not a valid [`DayOfWeek`].
     pub fn now(&self) -> Result<DateTime, RtcError> {
         self.read(|dr, tr, _ss| {
-            let second = bcd2_to_byte((tr.st(), tr.su()));
+            #[allow(unused_mut)]
+            let mut second = bcd2_to_byte((tr.st(), tr.su()));
             let minute = bcd2_to_byte((tr.mnt(), tr.mnu()));
             let hour = bcd2_to_byte((tr.ht(), tr.hu()));
 
@@ -75,12 +142,19 @@ impl RtcTimeProvider {
             let month = bcd2_to_byte((dr.mt() as u8, dr.mu()));
             let year = bcd2_to_byte((dr.yt(), dr.yu())) as u16 + 2000_u16;
 
-            // Calculate second fraction and multiply to microseconds
-            // Formula from RM0410
             #[cfg(not(rtc_v2_f2))]
             let us = {
-                let prediv = RTC::regs().prer().read().prediv_s() as f32;
-                (((prediv - _ss as f32) / (prediv + 1.0)) * 1e6).min(999_999.0) as u32
+                let (us, borrow) = subsecond_micros(_ss);
+                match (borrow, second) {
+                    (false, _) => us,
+                    (true, 1..) => {
+                        second -= 1;
+                        us
+                    }
+                    // Borrowing from the minute would ripple through the whole date for a window of
+                    // a few ticks after a shift. Report the start of the second instead.
+                    (true, 0) => 0,
+                }
             };
             #[cfg(rtc_v2_f2)]
             let us = 0;
@@ -346,10 +420,9 @@ impl Rtc {
     ///
     /// The registers retain their values during wakes from standby mode or system resets. They also
     /// retain their value when Vdd is switched off as long as V_BAT is powered.
-    #[cfg_attr(
-        rtc_v3,
-        doc = "\n\nAlways returns [`None`]. Use the [`TAMP`](crate::peripherals::TAMP) peripheral instead."
-    )]
+    ///
+    /// Returns [`None`] if `register` is out of range. See [`Rtc::read_backup`] for an index-checked
+    /// API.
     pub fn read_backup_register(&self, register: usize) -> Option<u32> {
         RTC::read_backup_register(RTC::regs(), register)
     }
@@ -358,11 +431,11 @@ impl Rtc {
     ///
     /// The registers retain their values during wakes from standby mode or system resets. They also
     /// retain their value when Vdd is switched off as long as V_BAT is powered.
-    #[cfg_attr(
-        rtc_v3,
-        doc = "\n\nDoes not write to the register. Use the [`TAMP`](crate::peripherals::TAMP) peripheral instead."
-    )]
+    ///
+    /// Does nothing if `register` is out of range. See [`Rtc::write_backup`] for an index-checked
+    /// API.
     pub fn write_backup_register(&self, register: usize, value: u32) {
+        crate::rcc::unlock_backup_domain();
         RTC::write_backup_register(RTC::regs(), register, value)
     }
 }
@@ -426,6 +499,23 @@ impl AnyRtc for Rtc {
     }
 }
 
+/// Convert the sub-second counter to microseconds into the second.
+///
+/// The counter counts down from PREDIV_S (formula from RM0410). After a shift that subtracts a
+/// fraction it may be above PREDIV_S, meaning the calendar registers are one second ahead; this is
+/// reported by the returned flag.
+#[cfg(not(rtc_v2_f2))]
+pub(crate) fn subsecond_micros(ss: u16) -> (u32, bool) {
+    let prediv = RTC::regs().prer().read().prediv_s() as u64;
+    let ss = ss as u64;
+    let (ticks, borrow) = if ss <= prediv {
+        (prediv - ss, false)
+    } else {
+        ((2 * prediv + 1).saturating_sub(ss), true)
+    };
+    ((ticks * 1_000_000 / (prediv + 1)) as u32, borrow)
+}
+
 pub(crate) fn byte_to_bcd2(byte: u8) -> (u8, u8) {
     let mut bcd_high: u8 = 0;
     let mut value = byte;
@@ -446,9 +536,19 @@ pub(crate) fn bcd2_to_byte(bcd: (u8, u8)) -> u8 {
     tmp + (value & 0x0F)
 }
 
+pub(crate) trait SealedRtcInterrupt {}
+
+/// An interrupt vector of the RTC or TAMP peripheral.
+#[allow(private_bounds)]
+pub trait RtcInterrupt: SealedRtcInterrupt + Interrupt {}
+
 trait SealedInstance {
     const BACKUP_REGISTER_COUNT: usize;
 
+    /// EXTI line of the RTC alarms.
+    #[cfg(rtc_v2)]
+    const EXTI_ALARM_LINE: usize;
+
     #[cfg(feature = "low-power")]
     #[cfg(not(feature = "_lp-time-driver"))]
     #[cfg(not(any(stm32wba, stm32u5, stm32u3, stm32u0)))]
@@ -0,0 +1,318 @@
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+#[cfg(not(rtc_v3))]
+use super::SealedInstance;
+use super::{InterruptHandler, Rtc, RtcInterrupt};
+use crate::gpio::Level;
+use crate::interrupt::typelevel::Binding;
+#[cfg(not(rtc_v3))]
+use crate::peripherals::RTC;
+
+static TAMPER_WAKER: AtomicWaker = AtomicWaker::new();
+static TAMPER_FIRED: AtomicU8 = AtomicU8::new(0);
+static TAMPER_LEVEL_MODE: AtomicBool = AtomicBool::new(false);
+
+cfg_if::cfg_if! {
+    if #[cfg(rtc_v3)] {
+        /// Number of tamper inputs. Not all of them are bonded out on every part.
+        pub const TAMPER_COUNT: usize = 8;
+    } else if #[cfg(any(stm32f2, stm32f4))] {
+        /// Number of tamper inputs.
+        pub const TAMPER_COUNT: usize = 2;
+    } else {
+        /// Number of tamper inputs.
+        pub const TAMPER_COUNT: usize = 3;
+    }
+}
+
+// Tamper control register of RTCv2 parts. The older TAFCR has no per-input backup register
+// erase bit.
+#[cfg(any(stm32l0, stm32l4, stm32wb, stm32h7))]
+macro_rules! tamper_cr {
+    ($r:expr) => {
+        $r.tampcr()
+    };
+}
+#[cfg(all(rtc_v2, not(any(stm32l0, stm32l4, stm32wb, stm32h7))))]
+macro_rules! tamper_cr {
+    ($r:expr) => {
+        $r.tafcr()
+    };
+}
+
+// EXTI line of the tamper and timestamp events on RTCv2 parts.
+cfg_if::cfg_if! {
+    if #[cfg(rtc_v3)] {
+        // Routed through the TAMP interrupt.
+    } else if #[cfg(any(stm32f2, stm32f4, stm32f7))] {
+        pub(super) const EXTI_LINE: Option<usize> = Some(21);
+    } else if #[cfg(any(stm32f3, stm32l0, stm32l1, stm32l4))] {
+        pub(super) const EXTI_LINE: Option<usize> = Some(19);
+    } else if #[cfg(any(stm32wb, stm32h7))] {
+        pub(super) const EXTI_LINE: Option<usize> = Some(18);
+    } else {
+        pub(super) const EXTI_LINE: Option<usize> = None;
+    }
+}
+
+/// Tamper input filtering.
+///
+/// Without filtering, tamper inputs are edge triggered. With filtering, they are level
+/// triggered and the level has to be seen on consecutive samples.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TamperFilter {
+    /// Edge detection.
+    #[default]
+    Edge = 0,
+    /// Level detection, 2 consecutive samples.
+    Samples2 = 1,
+    /// Level detection, 4 consecutive samples.
+    Samples4 = 2,
+    /// Level detection, 8 consecutive samples.
+    Samples8 = 3,
+}
+
+/// Tamper input sampling frequency, as a division of RTCCLK.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TamperSampleFrequency {
+    /// RTCCLK / 32768, 1 Hz with a 32.768 kHz clock.
+    #[default]
+    Div32768 = 0,
+    /// RTCCLK / 16384
+    Div16384 = 1,
+    /// RTCCLK / 8192
+    Div8192 = 2,
+    /// RTCCLK / 4096
+    Div4096 = 3,
+    /// RTCCLK / 2048
+    Div2048 = 4,
+    /// RTCCLK / 1024
+    Div1024 = 5,
+    /// RTCCLK / 512
+    Div512 = 6,
+    /// RTCCLK / 256
+    Div256 = 7,
+}
+
+/// Duration of the tamper input precharge before each sample, in RTCCLK cycles.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TamperPrecharge {
+    /// 1 cycle
+    #[default]
+    Cycles1 = 0,
+    /// 2 cycles
+    Cycles2 = 1,
+    /// 4 cycles
+    Cycles4 = 2,
+    /// 8 cycles
+    Cycles8 = 3,
+}
+
+/// Configuration shared by all tamper inputs.
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TamperConfig {
+    /// Input filtering.
+    pub filter: TamperFilter,
+    /// Sampling frequency, used with filtering.
+    pub sample_frequency: TamperSampleFrequency,
+    /// Precharge duration, used with filtering and the pull-up.
+    pub precharge: TamperPrecharge,
+    /// Disable the precharge of the inputs through the internal pull-up before sampling.
+    pub disable_pull_up: bool,
+}
+
+/// Configuration of a tamper input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TamperInputConfig {
+    /// Level of an active tamper input.
+    ///
+    /// Without filtering, a tamper event is an edge towards this level. With filtering, it is
+    /// the input staying at this level.
+    pub active_level: Level,
+    /// Erase the backup registers on a tamper event.
+    ///
+    /// Parts whose tamper control register has no per-input erase bit always erase them.
+    pub erase_backup_registers: bool,
+}
+
+impl Default for TamperInputConfig {
+    fn default() -> Self {
+        Self {
+            active_level: Level::High,
+            erase_backup_registers: true,
+        }
+    }
+}
+
+impl Rtc {
+    /// Configure filtering, sampling and precharge of the tamper inputs.
+    ///
+    /// Call this before enabling inputs, their trigger polarity depends on the filter mode.
+    pub fn configure_tamper(&mut self, config: TamperConfig) {
+        let filter = config.filter as u8;
+        let freq = config.sample_frequency as u8;
+        let prch = config.precharge as u8;
+        let pudis = config.disable_pull_up;
+        TAMPER_LEVEL_MODE.store(config.filter != TamperFilter::Edge, Ordering::Relaxed);
+
+        #[cfg(rtc_v3)]
+        crate::pac::TAMP.fltcr().write(|w| {
+            w.set_tampflt(filter.into());
+            w.set_tampfreq(freq.into());
+            w.set_tampprch(prch.into());
+            w.set_tamppudis(pudis);
+        });
+
+        #[cfg(not(rtc_v3))]
+        self.write(false, |r| {
+            tamper_cr!(r).modify(|w| {
+                w.set_tampflt(filter.into());
+                w.set_tampfreq(freq.into());
+                w.set_tampprch(prch.into());
+                w.set_tamppudis(pudis);
+            })
+        });
+    }
+
+    /// Enable tamper input `input`, 0 being TAMP1.
+    ///
+    /// The RTC takes over the pin of the input. Events are reported by [`Rtc::wait_for_tamper`].
+    pub fn enable_tamper(&mut self, input: usize, config: TamperInputConfig) {
+        assert!(input < TAMPER_COUNT);
+
+        // Edge mode triggers on rising edges when TAMPxTRG is clear, level mode on a low level.
+        let level_mode = TAMPER_LEVEL_MODE.load(Ordering::Relaxed);
+        let trg = level_mode == (config.active_level == Level::High);
+
+        #[cfg(rtc_v3)]
+        {
+            let tamp = crate::pac::TAMP;
+            tamp.cr2().modify(|w| {
+                w.set_tamptrg(input, trg);
+                w.set_tampnoer(input, !config.erase_backup_registers);
+            });
+            tamp.scr().write(|w| w.set_ctampf(input, true));
+            tamp.ier().modify(|w| w.set_tampie(input, true));
+            tamp.cr1().modify(|w| w.set_tampe(input, true));
+        }
+
+        #[cfg(not(rtc_v3))]
+        self.write(false, |r| {
+            tamper_cr!(r).modify(|w| {
+                w.set_tamptrg(input, trg);
+                #[cfg(any(stm32l0, stm32l4, stm32wb, stm32h7))]
+                w.set_tampnoerase(input, !config.erase_backup_registers);
+                w.set_tampie(true);
+                w.set_tampe(input, true);
+            });
+            r.isr().modify(|w| w.set_tampf(input, false));
+        });
+
+        #[cfg(not(rtc_v3))]
+        if let Some(line) = EXTI_LINE {
+            super::enable_exti_line(line);
+        }
+    }
+
+    /// Disable tamper input `input`.
+    pub fn disable_tamper(&mut self, input: usize) {
+        assert!(input < TAMPER_COUNT);
+
+        #[cfg(rtc_v3)]
+        {
+            let tamp = crate::pac::TAMP;
+            tamp.cr1().modify(|w| w.set_tampe(input, false));
+            tamp.ier().modify(|w| w.set_tampie(input, false));
+            tamp.scr().write(|w| w.set_ctampf(input, true));
+        }
+
+        #[cfg(not(rtc_v3))]
+        self.write(false, |r| {
+            tamper_cr!(r).modify(|w| w.set_tampe(input, false));
+            r.isr().modify(|w| w.set_tampf(input, false));
+        });
+
+        TAMPER_FIRED.fetch_and(!(1 << input), Ordering::Relaxed);
+    }
+
+    /// Wait for a tamper event and return the input that triggered it.
+    ///
+    /// `_irq` must bind the RTC tamper interrupt vector (`TAMP_STAMP` on most RTCv2 parts, `TAMP`
+    /// or `RTC_TAMP` on the others) to [`InterruptHandler`].
+    pub async fn wait_for_tamper<I: RtcInterrupt>(&mut self, _irq: impl Binding<I, InterruptHandler<I>>) -> usize {
+        unsafe { I::enable() };
+
+        poll_fn(|cx| {
+            TAMPER_WAKER.register(cx.waker());
+
+            let fired = critical_section::with(|_| {
+                let fired = TAMPER_FIRED.load(Ordering::Relaxed) | take_tamper_flags();
+                if fired == 0 {
+                    return None;
+                }
+                let input = fired.trailing_zeros() as usize;
+                TAMPER_FIRED.store(fired & !(1 << input), Ordering::Relaxed);
+                Some(input)
+            });
+            match fired {
+                Some(input) => Poll::Ready(input),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+/// Read and clear the tamper flags, as a bit mask of inputs.
+fn take_tamper_flags() -> u8 {
+    let mut fired = 0;
+
+    #[cfg(rtc_v3)]
+    {
+        let tamp = crate::pac::TAMP;
+        let sr = tamp.sr().read();
+        for n in 0..TAMPER_COUNT {
+            if sr.tampf(n) {
+                fired |= 1 << n;
+                tamp.scr().write(|w| w.set_ctampf(n, true));
+            }
+        }
+    }
+
+    #[cfg(not(rtc_v3))]
+    {
+        let r = RTC::regs();
+        let isr = r.isr().read();
+        for n in 0..TAMPER_COUNT {
+            if isr.tampf(n) {
+                fired |= 1 << n;
+                r.isr().modify(|w| w.set_tampf(n, false));
+            }
+        }
+    }
+
+    fired
+}
+
+pub(super) fn on_interrupt() {
+    let fired = take_tamper_flags();
+    if fired != 0 {
+        TAMPER_FIRED.fetch_or(fired, Ordering::Relaxed);
+        TAMPER_WAKER.wake();
+    }
+
+    #[cfg(not(rtc_v3))]
+    if let Some(line) = EXTI_LINE {
+        super::clear_exti_line(line);
+    }
+}
@@ -0,0 +1,210 @@
+use core::cell::Cell;
+use core::future::poll_fn;
+use core::task::Poll;
+
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::datetime::day_of_week_from_u8;
+use super::{DateTime, InterruptHandler, Rtc, RtcError, RtcInterrupt, RtcTimeProvider, SealedInstance, bcd2_to_byte};
+use crate::interrupt::typelevel::Binding;
+use crate::pac::rtc::regs::{Tsdr, Tstr};
+use crate::peripherals::RTC;
+
+static TIMESTAMP_WAKER: AtomicWaker = AtomicWaker::new();
+static CAPTURED: Mutex<CriticalSectionRawMutex, Cell<Option<RawTimestamp>>> = Mutex::new(Cell::new(None));
+
+#[derive(Copy, Clone)]
+struct RawTimestamp {
+    tr: Tstr,
+    dr: Tsdr,
+    ss: u16,
+    overflow: bool,
+}
+
+/// Edge of the RTC_TS pin that captures a timestamp.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimestampEdge {
+    /// Rising edge
+    Rising,
+    /// Falling edge
+    Falling,
+}
+
+/// A captured timestamp.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timestamp {
+    /// Calendar time of the event.
+    ///
+    /// The hardware doesn't capture the year, it is taken from the current calendar.
+    pub datetime: DateTime,
+    /// Another event happened while this timestamp was pending, and was lost.
+    pub overflow: bool,
+}
+
+impl Rtc {
+    /// Capture a timestamp on an edge of the RTC_TS pin.
+    pub fn enable_timestamp(&mut self, edge: TimestampEdge) {
+        self.write(false, |r| {
+            r.cr().modify(|w| w.set_tse(false));
+            clear_timestamp_flags();
+            r.cr().modify(|w| {
+                w.set_tsedge(edge == TimestampEdge::Falling);
+                w.set_tse(true);
+                w.set_tsie(true);
+            });
+        });
+        critical_section::with(|cs| CAPTURED.borrow(cs).set(None));
+
+        #[cfg(not(rtc_v3))]
+        if let Some(line) = super::tamper::EXTI_LINE {
+            super::enable_exti_line(line);
+        }
+    }
+
+    /// Capture a timestamp on internal events, i.e. the switch to V_BAT.
+    #[cfg(rtc_v3)]
+    pub fn enable_internal_timestamp(&mut self) {
+        self.write(false, |r| {
+            r.cr().modify(|w| {
+                w.set_itse(true);
+                w.set_tsie(true);
+            })
+        });
+    }
+
+    /// Stop capturing timestamps.
+    pub fn disable_timestamp(&mut self) {
+        self.write(false, |r| {
+            r.cr().modify(|w| {
+                w.set_tse(false);
+                #[cfg(rtc_v3)]
+                w.set_itse(false);
+                w.set_tsie(false);
+            });
+            clear_timestamp_flags();
+        });
+        critical_section::with(|cs| CAPTURED.borrow(cs).set(None));
+    }
+
+    /// Wait for the next timestamp.
+    ///
+    /// `_irq` must bind the RTC timestamp interrupt vector (`TAMP_STAMP` on most RTCv2 parts,
+    /// `RTC` on the others) to [`InterruptHandler`]. A timestamp captured before the call, and not
+    /// yet returned, is returned immediately.
+    ///
+    /// # Errors
+    ///
+    /// Will return `RtcError::InvalidDateTime` if the captured date is not valid.
+    pub async fn wait_for_timestamp<I: RtcInterrupt>(
+        &mut self,
+        _irq: impl Binding<I, InterruptHandler<I>>,
+    ) -> Result<Timestamp, RtcError> {
+        unsafe { I::enable() };
+
+        let raw = poll_fn(|cx| {
+            TIMESTAMP_WAKER.register(cx.waker());
+
+            match critical_section::with(|cs| CAPTURED.borrow(cs).take().or_else(take_timestamp)) {
+                Some(raw) => Poll::Ready(raw),
+                None => Poll::Pending,
+            }
+        })
+        .await;
+
+        let second = bcd2_to_byte((raw.tr.st(), raw.tr.su()));
+        let minute = bcd2_to_byte((raw.tr.mnt(), raw.tr.mnu()));
+        let hour = bcd2_to_byte((raw.tr.ht(), raw.tr.hu()));
+
+        let weekday = day_of_week_from_u8(raw.dr.wdu()).map_err(RtcError::InvalidDateTime)?;
+        let day = bcd2_to_byte((raw.dr.dt(), raw.dr.du()));
+        let month = bcd2_to_byte((raw.dr.mt() as u8, raw.dr.mu()));
+
+        // A timestamp from a later month than the current one is from last year.
+        let now = RtcTimeProvider::new().now()?;
+        let year = if month > now.month() {
+            now.year() - 1
+        } else {
+            now.year()
+        };
+
+        // The rare borrow of a timestamp right after a shift is not corrected.
+        #[cfg(not(rtc_v2_f2))]
+        let us = super::subsecond_micros(raw.ss).0;
+        #[cfg(rtc_v2_f2)]
+        let us = 0;
+
+        let datetime =
+            DateTime::from(year, month, day, weekday, hour, minute, second, us).map_err(RtcError::InvalidDateTime)?;
+
+        Ok(Timestamp {
+            datetime,
+            overflow: raw.overflow,
+        })
+    }
+}
+
+fn clear_timestamp_flags() {
+    let r = RTC::regs();
+    #[cfg(rtc_v2)]
+    r.isr().modify(|w| {
+        w.set_tsf(false);
+        w.set_tsovf(false);
+    });
+    #[cfg(rtc_v3)]
+    r.scr().write(|w| {
+        use crate::pac::rtc::vals::Calrf;
+        w.set_ctsf(Calrf::Clear);
+        w.set_ctsovf(Calrf::Clear);
+        w.set_citsf(Calrf::Clear);
+    });
+}
+
+/// Read a pending timestamp and clear its flags.
+fn take_timestamp() -> Option<RawTimestamp> {
+    let r = RTC::regs();
+    #[cfg(rtc_v2)]
+    let (pending, overflow) = {
+        let isr = r.isr().read();
+        (isr.tsf(), isr.tsovf())
+    };
+    #[cfg(rtc_v3)]
+    let (pending, overflow) = {
+        let sr = r.sr().read();
+        (sr.tsf(), sr.tsovf())
+    };
+
+    if !pending {
+        return None;
+    }
+
+    // The timestamp registers are only valid until TSF is cleared.
+    let raw = RawTimestamp {
+        tr: r.tstr().read(),
+        dr: r.tsdr().read(),
+        #[cfg(not(rtc_v2_f2))]
+        ss: r.tsssr().read().ss() as u16,
+        #[cfg(rtc_v2_f2)]
+        ss: 0,
+        overflow,
+    };
+    clear_timestamp_flags();
+
+    Some(raw)
+}
+
+pub(super) fn on_interrupt() {
+    critical_section::with(|cs| {
+        if let Some(raw) = take_timestamp() {
+            // Keep the overflow of a timestamp that wasn't picked up yet.
+            let overflow = CAPTURED.borrow(cs).get().is_some();
+            CAPTURED.borrow(cs).set(Some(RawTimestamp {
+                overflow: raw.overflow || overflow,
+                ..raw
+            }));
+            TIMESTAMP_WAKER.wake();
+        }
+    });
+}
@@ -128,6 +128,11 @@ impl super::Rtc {
 impl SealedInstance for crate::peripherals::RTC {
     const BACKUP_REGISTER_COUNT: usize = 20;
 
+    #[cfg(stm32l4)]
+    const EXTI_ALARM_LINE: usize = 18;
+    #[cfg(not(stm32l4))]
+    const EXTI_ALARM_LINE: usize = 17;
+
     #[cfg(all(feature = "low-power", stm32f4))]
     const EXTI_WAKEUP_LINE: usize = 22;
 
@@ -135,7 +135,10 @@ impl super::Rtc {
 }
 
 impl SealedInstance for crate::peripherals::RTC {
+    #[cfg(peri_tamp)]
     const BACKUP_REGISTER_COUNT: usize = 32;
+    #[cfg(not(peri_tamp))]
+    const BACKUP_REGISTER_COUNT: usize = 0;
 
     #[cfg(feature = "low-power")]
     #[cfg(not(feature = "_lp-time-driver"))]
@@ -167,20 +170,29 @@ impl SealedInstance for crate::peripherals::RTC {
         Self::regs().icsr().read().shpf()
     }
 
+    // RTC3 backup registers come from the TAMP peripheral, not RTC.
+    #[cfg(peri_tamp)]
     fn read_backup_register(_rtc: Rtc, register: usize) -> Option<u32> {
-        #[allow(clippy::if_same_then_else)]
         if register < Self::BACKUP_REGISTER_COUNT {
-            //Some(rtc.bkpr()[register].read().bits())
-            None // RTC3 backup registers come from the TAMP peripheral, not RTC. Not() even in the L412 PAC
+            Some(crate::pac::TAMP.bkpr(register).read().bkp())
         } else {
             None
         }
     }
 
-    fn write_backup_register(_rtc: Rtc, register: usize, _value: u32) {
+    #[cfg(peri_tamp)]
+    fn write_backup_register(_rtc: Rtc, register: usize, value: u32) {
         if register < Self::BACKUP_REGISTER_COUNT {
-            // RTC3 backup registers come from the TAMP peripheral, not RTC. Not() even in the L412 PAC
-            //self.rtc.bkpr()[register].write(|w| w.bits(value))
+            crate::pac::TAMP.bkpr(register).write(|w| w.set_bkp(value));
         }
     }
+
+    // Not even in the L412 PAC.
+    #[cfg(not(peri_tamp))]
+    fn read_backup_register(_rtc: Rtc, _register: usize) -> Option<u32> {
+        None
+    }
+
+    #[cfg(not(peri_tamp))]
+    fn write_backup_register(_rtc: Rtc, _register: usize, _value: u32) {}
 }
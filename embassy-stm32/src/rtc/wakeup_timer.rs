@@ -0,0 +1,165 @@
+use core::future::poll_fn;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::Poll;
+
+use embassy_sync::waitqueue::AtomicWaker;
+
+use super::{InterruptHandler, Rtc, RtcInterrupt, SealedInstance};
+use crate::interrupt::typelevel::Binding;
+use crate::pac::rtc::vals::Wucksel;
+use crate::peripherals::RTC;
+
+static WAKEUP_WAKER: AtomicWaker = AtomicWaker::new();
+static WAKEUP_FIRED: AtomicBool = AtomicBool::new(false);
+
+// EXTI line of the wakeup timer, where it is a configurable line that has to be enabled for the
+// timer to wake the core from Stop.
+cfg_if::cfg_if! {
+    if #[cfg(any(stm32f2, stm32f4, stm32f7))] {
+        const EXTI_LINE: Option<usize> = Some(22);
+    } else if #[cfg(any(stm32f0, stm32f3, stm32l0, stm32l1, stm32l4, stm32g4, stm32wl))] {
+        const EXTI_LINE: Option<usize> = Some(20);
+    } else if #[cfg(any(stm32wb, stm32h7, stm32g0))] {
+        const EXTI_LINE: Option<usize> = Some(19);
+    } else if #[cfg(any(stm32l5, stm32h5))] {
+        const EXTI_LINE: Option<usize> = Some(17);
+    } else {
+        const EXTI_LINE: Option<usize> = None;
+    }
+}
+
+/// Clock of the wakeup timer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WakeupClock {
+    /// RTCCLK / 16
+    Div16,
+    /// RTCCLK / 8
+    Div8,
+    /// RTCCLK / 4
+    Div4,
+    /// RTCCLK / 2
+    Div2,
+    /// The 1 Hz calendar clock, for periods of 1 s to 18 h.
+    Spare,
+    /// The 1 Hz calendar clock with 2^16 added to the reload value, for periods of 18 h to 36 h.
+    SpareExtended,
+}
+
+impl From<WakeupClock> for Wucksel {
+    fn from(clock: WakeupClock) -> Self {
+        match clock {
+            WakeupClock::Div16 => Wucksel::Div16,
+            WakeupClock::Div8 => Wucksel::Div8,
+            WakeupClock::Div4 => Wucksel::Div4,
+            WakeupClock::Div2 => Wucksel::Div2,
+            WakeupClock::Spare => Wucksel::ClockSpare,
+            WakeupClock::SpareExtended => Wucksel::ClockSpareWithOffset,
+        }
+    }
+}
+
+impl Rtc {
+    /// Start the periodic wakeup timer.
+    ///
+    /// The timer fires every `reload + 1` cycles of `clock` (plus 2^16 for
+    /// [`WakeupClock::SpareExtended`]), and keeps running in Stop and Standby.
+    pub fn start_wakeup_timer(&mut self, clock: WakeupClock, reload: u16) {
+        self.write(false, |regs| {
+            regs.cr().modify(|w| {
+                w.set_wute(false);
+                w.set_wutie(false);
+            });
+            clear_wakeup_flag();
+
+            #[cfg(rtc_v2)]
+            while !regs.isr().read().wutwf() {}
+            #[cfg(rtc_v3)]
+            while !regs.icsr().read().wutwf() {}
+
+            regs.cr().modify(|w| w.set_wucksel(clock.into()));
+            regs.wutr().write(|w| w.set_wut(reload));
+            regs.cr().modify(|w| {
+                w.set_wute(true);
+                w.set_wutie(true);
+            });
+        });
+        WAKEUP_FIRED.store(false, Ordering::Relaxed);
+    }
+
+    /// Start the periodic wakeup timer with a period in seconds.
+    ///
+    /// `period_secs` is clamped to 1 s – 36 h (`1..=131_072`).
+    pub fn start_wakeup_timer_secs(&mut self, period_secs: u32) {
+        let period_secs = period_secs.clamp(1, 2 << 16);
+        if period_secs <= 1 << 16 {
+            self.start_wakeup_timer(WakeupClock::Spare, (period_secs - 1) as u16)
+        } else {
+            self.start_wakeup_timer(WakeupClock::SpareExtended, (period_secs - 1 - (1 << 16)) as u16)
+        }
+    }
+
+    /// Stop the periodic wakeup timer.
+    pub fn stop_wakeup_timer(&mut self) {
+        self.write(false, |regs| {
+            regs.cr().modify(|w| {
+                w.set_wutie(false);
+                w.set_wute(false);
+            });
+            clear_wakeup_flag();
+        });
+        WAKEUP_FIRED.store(false, Ordering::Relaxed);
+    }
+
+    /// Wait for the next event of the wakeup timer.
+    ///
+    /// `_irq` must bind the RTC wakeup interrupt vector to [`InterruptHandler`]. Events that
+    /// happened since the previous call are not queued: if one or more fired in between, this
+    /// returns immediately once.
+    pub async fn wait_for_wakeup<I: RtcInterrupt>(&mut self, _irq: impl Binding<I, InterruptHandler<I>>) {
+        if let Some(line) = EXTI_LINE {
+            super::enable_exti_line(line);
+        }
+        unsafe { I::enable() };
+
+        poll_fn(|cx| {
+            WAKEUP_WAKER.register(cx.waker());
+
+            let fired = critical_section::with(|_| WAKEUP_FIRED.swap(false, Ordering::Relaxed) || take_wakeup_flag());
+            if fired { Poll::Ready(()) } else { Poll::Pending }
+        })
+        .await
+    }
+}
+
+fn clear_wakeup_flag() {
+    let r = RTC::regs();
+    #[cfg(rtc_v2)]
+    r.isr().modify(|w| w.set_wutf(false));
+    #[cfg(rtc_v3)]
+    r.scr().write(|w| w.set_cwutf(crate::pac::rtc::vals::Calrf::Clear));
+}
+
+fn take_wakeup_flag() -> bool {
+    let r = RTC::regs();
+    #[cfg(rtc_v2)]
+    let fired = r.isr().read().wutf();
+    #[cfg(rtc_v3)]
+    let fired = r.sr().read().wutf();
+
+    if fired {
+        clear_wakeup_flag();
+    }
+    fired
+}
+
+pub(super) fn on_interrupt() {
+    if take_wakeup_flag() {
+        WAKEUP_FIRED.store(true, Ordering::Relaxed);
+        WAKEUP_WAKER.wake();
+    }
+
+    if let Some(line) = EXTI_LINE {
+        super::clear_exti_line(line);
+    }
+}
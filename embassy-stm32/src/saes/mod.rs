@@ -60,14 +60,31 @@
 //! saes.finish_blocking(ctx);
 //! ```
 //!
+//! ## Wrapping Application Keys
+//!
+//! ```no_run
+//! use embassy_stm32::saes::{Saes, AesGcm, Direction, HardwareKeySource};
+//!
+//! // Provisioning: wrap the key with the device-unique key, store `wrapped` in flash
+//! let mut wrapped = [0u8; 32];
+//! saes.wrap_key(HardwareKeySource::DHUK, &app_key, &mut wrapped)?;
+//!
+//! // At runtime: unwrap it into the key registers, it is never readable by software
+//! saes.unwrap_key(HardwareKeySource::DHUK, &wrapped)?;
+//! let size = [0u8; 32]; // only the key size is used
+//! let cipher = AesGcm::new(&size, &iv);
+//! let mut ctx = saes.start_with_unwrapped_key(&cipher, Direction::Encrypt)?;
+//! ```
+//!
 //! ## Key Sharing Between Peripherals
 //!
 //! ```no_run
-//! use embassy_stm32::saes::{Saes, KeyShareTarget};
+//! use embassy_stm32::saes::{Saes, HardwareKeySource, KeyShareTarget};
 //!
-//! // After unwrapping a key with SAES, share it with AES peripheral
-//! saes.share_key_with(KeyShareTarget::AES);
-//! // Now AES peripheral can use the unwrapped key
+//! // Unwrap a key and share it with the AES peripheral
+//! saes.share_key(HardwareKeySource::DHUK, &wrapped, KeyShareTarget::AES)?;
+//! // The key is transferred when AES starts in shared-key mode
+//! let mut ctx = aes.start_shared_key(&cipher, Direction::Encrypt);
 //! ```
 //!
 //! # Security Features
@@ -171,6 +188,17 @@ pub enum KeyShareTarget {
     AES = 0,
 }
 
+/// Where the key of an operation comes from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeyLoad {
+    /// Written by software from the cipher.
+    Software,
+    /// Fetched by the peripheral from a hardware source.
+    Hardware(HardwareKeySource),
+    /// Left in the key registers by [`Saes::unwrap_key`].
+    Unwrapped,
+}
+
 /// SAES driver.
 pub struct Saes<'d, T: Instance, M: Mode> {
     _peripheral: Peri<'d, T>,
@@ -279,7 +307,7 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
     where
         C: Cipher<'c> + CipherSized + IVSized,
     {
-        self.start_with_key_mode(cipher, dir, KeyMode::Normal, None)
+        self.start_with_key_mode(cipher, dir, KeyMode::Normal, KeyLoad::Software)
     }
 
     /// Starts a new cipher operation with a hardware-derived key.
@@ -292,7 +320,7 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
     where
         C: Cipher<'c> + CipherSized + IVSized,
     {
-        self.start_with_key_mode(cipher, dir, KeyMode::Normal, Some(key_source))
+        self.start_with_key_mode(cipher, dir, KeyMode::Normal, KeyLoad::Hardware(key_source))
     }
 
     /// Starts a new cipher operation with an explicit SAES key mode.
@@ -309,7 +337,8 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
     where
         C: Cipher<'c> + CipherSized + IVSized,
     {
-        self.start_with_key_mode(cipher, dir, key_mode, hw_key)
+        let key_load = hw_key.map_or(KeyLoad::Software, KeyLoad::Hardware);
+        self.start_with_key_mode(cipher, dir, key_mode, key_load)
     }
 
     /// Starts a new cipher operation in wrapped-key mode.
@@ -317,7 +346,7 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
     where
         C: Cipher<'c> + CipherSized + IVSized,
     {
-        self.start_with_key_mode(cipher, dir, KeyMode::WrappedKey, None)
+        self.start_with_key_mode(cipher, dir, KeyMode::WrappedKey, KeyLoad::Software)
     }
 
     /// Starts a new cipher operation in shared-key mode.
@@ -325,7 +354,105 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
     where
         C: Cipher<'c> + CipherSized + IVSized,
     {
-        self.start_with_key_mode(cipher, dir, KeyMode::SharedKey, None)
+        self.start_with_key_mode(cipher, dir, KeyMode::SharedKey, KeyLoad::Software)
+    }
+
+    /// Starts a new cipher operation with the key left in the key registers by
+    /// [`unwrap_key`](Self::unwrap_key).
+    ///
+    /// The key of `cipher` is not used, only its size, which must be the size of the unwrapped key.
+    /// The unwrapped key stays loaded across operations, until another key is loaded.
+    pub fn start_with_unwrapped_key<'c, C>(&mut self, cipher: &'c C, dir: Direction) -> Result<Context<'c, C>, Error>
+    where
+        C: Cipher<'c> + CipherSized + IVSized,
+    {
+        let p = T::regs();
+        if !p.sr().read().keyvalid() || p.cr().read().keysize() != keysize(cipher.key_size()) {
+            return Err(Error::KeyError);
+        }
+        Ok(self.start_with_key_mode(cipher, dir, KeyMode::Normal, KeyLoad::Unwrapped))
+    }
+
+    /// Wraps (encrypts) an application key with a hardware key, in ECB mode.
+    ///
+    /// The wrapped key can be stored in non-volatile memory: it can only be unwrapped by this
+    /// device (with [`HardwareKeySource::DHUK`]) and is never readable again once unwrapped.
+    pub fn wrap_key<const KEY_SIZE: usize>(
+        &mut self,
+        source: HardwareKeySource,
+        key: &[u8; KEY_SIZE],
+        wrapped: &mut [u8; KEY_SIZE],
+    ) -> Result<(), Error>
+    where
+        for<'c> AesEcb<'c, KEY_SIZE>: CipherSized,
+    {
+        let size = [0; KEY_SIZE];
+        let cipher = AesEcb::new(&size);
+        let mut ctx = self.start_with_key_mode(
+            &cipher,
+            Direction::Encrypt,
+            KeyMode::WrappedKey,
+            KeyLoad::Hardware(source),
+        );
+        self.payload_blocking(&mut ctx, key, wrapped, true)?;
+        self.finish_blocking(ctx)?;
+        Ok(())
+    }
+
+    /// Unwraps a key wrapped by [`wrap_key`](Self::wrap_key), directly into the key registers.
+    ///
+    /// The plaintext key never leaves the peripheral. Use it with
+    /// [`start_with_unwrapped_key`](Self::start_with_unwrapped_key).
+    pub fn unwrap_key<const KEY_SIZE: usize>(
+        &mut self,
+        source: HardwareKeySource,
+        wrapped: &[u8; KEY_SIZE],
+    ) -> Result<(), Error>
+    where
+        for<'c> AesEcb<'c, KEY_SIZE>: CipherSized,
+    {
+        self.decrypt_key(source, KeyMode::WrappedKey, wrapped)
+    }
+
+    /// Unwraps a key wrapped by [`wrap_key`](Self::wrap_key), and shares it with `target`.
+    ///
+    /// The key is transferred by hardware when the target peripheral starts an operation in
+    /// shared-key mode, e.g. with [`Aes::start_shared_key`](crate::aes::Aes::start_shared_key).
+    pub fn share_key<const KEY_SIZE: usize>(
+        &mut self,
+        source: HardwareKeySource,
+        wrapped: &[u8; KEY_SIZE],
+        target: KeyShareTarget,
+    ) -> Result<(), Error>
+    where
+        for<'c> AesEcb<'c, KEY_SIZE>: CipherSized,
+    {
+        self.share_key_with(target);
+        self.decrypt_key(source, KeyMode::SharedKey, wrapped)
+    }
+
+    /// Decrypt a wrapped key into the key registers, in wrapped-key or shared-key mode.
+    fn decrypt_key<const KEY_SIZE: usize>(
+        &mut self,
+        source: HardwareKeySource,
+        key_mode: KeyMode,
+        wrapped: &[u8; KEY_SIZE],
+    ) -> Result<(), Error>
+    where
+        for<'c> AesEcb<'c, KEY_SIZE>: CipherSized,
+    {
+        let size = [0; KEY_SIZE];
+        let cipher = AesEcb::new(&size);
+        let mut ctx = self.start_with_key_mode(&cipher, Direction::Decrypt, key_mode, KeyLoad::Hardware(source));
+        // The output data register reads as zero in these modes.
+        let mut discard = [0; KEY_SIZE];
+        self.payload_blocking(&mut ctx, wrapped, &mut discard, true)?;
+        self.finish_blocking(ctx)?;
+
+        if !T::regs().sr().read().keyvalid() {
+            return Err(Error::KeyError);
+        }
+        Ok(())
     }
 
     /// Internal start method with full control over key mode.
@@ -334,7 +461,7 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
         cipher: &'c C,
         dir: Direction,
         key_mode: KeyMode,
-        hw_key: Option<HardwareKeySource>,
+        key_load: KeyLoad,
     ) -> Context<'c, C>
     where
         C: Cipher<'c> + CipherSized + IVSized,
@@ -351,8 +478,12 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
         // switching to 256-bit, causing the KEYVALID wait to exit before all 8 key
         // registers are written (producing silent wrong-key encryption).
         // Mirrors HAL_CRYP_DeInit(): SET_BIT(CR, IPRST) then CLEAR_BIT(CR, IPRST).
-        p.cr().modify(|w| w.set_iprst(true));
-        p.cr().modify(|w| w.set_iprst(false));
+        // An unwrapped key is kept, it would be lost as well. KSHAREID is restored below.
+        let kshareid = p.cr().read().kshareid();
+        if key_load != KeyLoad::Unwrapped {
+            p.cr().modify(|w| w.set_iprst(true));
+            p.cr().modify(|w| w.set_iprst(false));
+        }
 
         // Clear all pending flags — in particular KEIF, which if left set will permanently
         // block KEYVALID from being asserted, making the key load silently fail.
@@ -363,12 +494,7 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
             .modify(|w| w.set_datatype(pac::saes::vals::Datatype::from_bits(cipher.datatype())));
 
         // Configure key size
-        let keysize = cipher.key_size();
-        let keysize_val = match keysize {
-            KeySize::Bits128 => pac::saes::vals::Keysize::Bits128,
-            KeySize::Bits256 => pac::saes::vals::Keysize::Bits256,
-        };
-        p.cr().modify(|w| w.set_keysize(keysize_val));
+        p.cr().modify(|w| w.set_keysize(keysize(cipher.key_size())));
         // Changing KEYSIZE may trigger a new RNG mask fetch (BUSY=1) inside SAES,
         // particularly when switching from 128-bit to 256-bit, which needs a larger mask.
         while p.sr().read().busy() {}
@@ -384,9 +510,12 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
         };
         p.cr().modify(|w| w.set_mode(mode_val));
 
-        // Set key mode
+        // Set key mode, keeping the target selected by `share_key_with` for shared-key mode
         let kmod_val = pac::saes::vals::Kmod::from_bits(key_mode as u8);
-        p.cr().modify(|w| w.set_kmod(kmod_val));
+        p.cr().modify(|w| {
+            w.set_kmod(kmod_val);
+            w.set_kshareid(kshareid);
+        });
 
         // For GCM/CCM (authenticated) modes, set GCMPH=0 (init phase) BEFORE loading the key.
         if is_gcm_ccm {
@@ -394,18 +523,23 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
         }
 
         // Configure and load the key (after GCMPH is set).
-        if let Some(hw_key_src) = hw_key {
-            let keysel_val = pac::saes::vals::Keysel::from_bits(hw_key_src as u8);
-            p.cr().modify(|w| w.set_keysel(keysel_val));
-            p.cr().modify(|w| w.set_keyprot(true));
-            // For hardware keys (non-SW), SAES fetches the key autonomously: wait for KEYVALID.
-            while !p.sr().read().keyvalid() {}
-        } else {
-            // Load software key, then wait for KEYVALID.
-            // Unlike plain AES, SAES validates the key register write sequence; KEYVALID must
-            // be set before EN can be asserted (RM: "EN cannot be set as long as KEYVALID = 0").
-            self.load_key(cipher.key());
-            while !p.sr().read().keyvalid() {}
+        match key_load {
+            KeyLoad::Hardware(hw_key_src) => {
+                let keysel_val = pac::saes::vals::Keysel::from_bits(hw_key_src as u8);
+                p.cr().modify(|w| w.set_keysel(keysel_val));
+                p.cr().modify(|w| w.set_keyprot(true));
+                // For hardware keys (non-SW), SAES fetches the key autonomously: wait for KEYVALID.
+                while !p.sr().read().keyvalid() {}
+            }
+            KeyLoad::Software => {
+                // Load software key, then wait for KEYVALID.
+                // Unlike plain AES, SAES validates the key register write sequence; KEYVALID must
+                // be set before EN can be asserted (RM: "EN cannot be set as long as KEYVALID = 0").
+                self.load_key(cipher.key());
+                while !p.sr().read().keyvalid() {}
+            }
+            // The key is already loaded, KEYSEL and KEYSIZE are unchanged so it stays valid.
+            KeyLoad::Unwrapped => {}
         }
 
         // For ECB/CBC decryption, perform key derivation (MODE=1) before the actual operation.
@@ -458,8 +592,9 @@ impl<'d, T: Instance, M: Mode> Saes<'d, T, M> {
         }
     }
 
-    /// Share the current unwrapped key with another peripheral.
-    /// This must be called after a decryption operation that unwrapped a key.
+    /// Select the peripheral a key decrypted in shared-key mode is shared with.
+    ///
+    /// [`share_key`](Self::share_key) does this and the decryption in one call.
     pub fn share_key_with(&mut self, target: KeyShareTarget) {
         let p = T::regs();
         let kshareid_val = match target {
@@ -801,6 +936,13 @@ impl<'d, T: Instance> Saes<'d, T, Async> {
     }
 }
 
+fn keysize(size: KeySize) -> pac::saes::vals::Keysize {
+    match size {
+        KeySize::Bits128 => pac::saes::vals::Keysize::Bits128,
+        KeySize::Bits256 => pac::saes::vals::Keysize::Bits256,
+    }
+}
+
 trait SealedInstance {
     fn regs() -> pac::saes::Saes;
 }
@@ -2,6 +2,7 @@
 #![macro_use]
 
 use core::marker::PhantomData;
+use core::ops::RangeInclusive;
 
 use embassy_hal_internal::PeripheralType;
 
@@ -11,8 +12,14 @@ use crate::gpio::{AfType, Flex, OutputType, Pull, Speed};
 pub use crate::pac::sai::vals::Mckdiv as MasterClockDivider;
 use crate::pac::sai::{Sai as Regs, vals};
 use crate::rcc::{self, RccPeripheral};
+use crate::time::Hertz;
 use crate::{Peri, interrupt, peripherals};
 
+#[cfg(any(sai_v1_4pdm, sai_v3_2pdm, sai_v3_4pdm, sai_v4_2pdm, sai_v4_4pdm))]
+mod pdm;
+#[cfg(any(sai_v1_4pdm, sai_v3_2pdm, sai_v3_4pdm, sai_v4_2pdm, sai_v4_4pdm))]
+pub use pdm::{PdmConfig, PdmDecimator, PdmWord, SaiPdm};
+
 /// SAI error
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -112,6 +119,17 @@ impl DataSize {
             DataSize::Data32 => vals::Ds::Bit32,
         }
     }
+
+    const fn bits(&self) -> u16 {
+        match self {
+            DataSize::Data8 => 8,
+            DataSize::Data10 => 10,
+            DataSize::Data16 => 16,
+            DataSize::Data20 => 20,
+            DataSize::Data24 => 24,
+            DataSize::Data32 => 32,
+        }
+    }
 }
 
 /// FIFO threshold level.
@@ -198,6 +216,9 @@ impl SyncInput {
 }
 
 /// SAI instance to sync from.
+///
+/// The SAI providing the clocks must have [`Config::sync_output`] set on its asynchronous
+/// sub-block. Only one sub-block of a SAI can output its clocks to the other instances.
 #[cfg(any(sai_v3, sai_v4))]
 #[derive(Copy, Clone, PartialEq)]
 #[allow(missing_docs)]
@@ -443,6 +464,204 @@ impl Config {
     pub fn new() -> Self {
         return Default::default();
     }
+
+    /// Create a config for a TDM frame of `slots` slots, all of them active.
+    ///
+    /// The frame is started by a one bit wide, active high frame sync pulse, one bit before the
+    /// first bit of slot 0, as used by most TDM codecs and amplifiers (also called DSP mode A).
+    /// Data is sent MSB first. Use [`Self::set_active_slots`] to only use some of the slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slots` is not in `1..=16`, or if the frame is longer than 256 bits.
+    pub fn tdm(slots: u8, slot_size: SlotSize, data_size: DataSize) -> Self {
+        assert!((1..=16).contains(&slots), "SAI: TDM supports 1 to 16 slots");
+
+        let slot_bits = match slot_size {
+            SlotSize::DataSize => data_size.bits(),
+            SlotSize::Channel16 => 16,
+            SlotSize::Channel32 => 32,
+        };
+        let frame_length = slots as u16 * slot_bits;
+        assert!(frame_length <= 256, "SAI: TDM frame longer than 256 bits");
+
+        Self {
+            protocol: Protocol::Free,
+            slot_size,
+            slot_count: word::U4(slots),
+            slot_enable: ((1u32 << slots) - 1) as u16,
+            data_size,
+            stereo_mono: StereoMono::Stereo,
+            bit_order: BitOrder::MsbFirst,
+            frame_sync_offset: FrameSyncOffset::BeforeFirstBit,
+            frame_sync_polarity: FrameSyncPolarity::ActiveHigh,
+            frame_sync_active_level_length: word::U7(1),
+            frame_sync_definition: FrameSyncDefinition::StartOfFrame,
+            frame_length,
+            ..Default::default()
+        }
+    }
+
+    /// Select the active slots of the frame, bit `n` of `mask` being slot `n`.
+    ///
+    /// Inactive slots are skipped by DMA: only data of the active slots is read or written,
+    /// in slot order.
+    pub fn set_active_slots(&mut self, mask: u16) {
+        self.slot_enable = mask;
+    }
+
+    /// Set the clock dividers for `sample_rate`, returning the achieved sample rate.
+    ///
+    /// `kernel_clock` is the frequency of the SAI kernel clock, see
+    /// [`rcc::frequency()`](crate::rcc::frequency). Without `master_clock`, the frame length
+    /// must already be set. See [`compute_clock_config()`].
+    pub fn set_sample_rate(&mut self, kernel_clock: Hertz, sample_rate: Hertz, master_clock: bool) -> SampleRate {
+        let clock = compute_clock_config(kernel_clock, sample_rate, self.frame_length, master_clock);
+        self.master_clock_divider = clock.master_clock_divider;
+        self.nodiv = clock.nodiv;
+        clock.sample_rate
+    }
+}
+
+#[cfg(any(sai_v1, sai_v2))]
+const MCKDIV_MAX: u8 = 15;
+#[cfg(not(any(sai_v1, sai_v2)))]
+const MCKDIV_MAX: u8 = 63;
+
+/// Division of the kernel clock by the master clock divider.
+const fn divider_ratio(mckdiv: u8) -> u32 {
+    #[cfg(any(sai_v1, sai_v2))]
+    return if mckdiv == 0 { 1 } else { 2 * mckdiv as u32 };
+    #[cfg(not(any(sai_v1, sai_v2)))]
+    return if mckdiv == 0 { 1 } else { mckdiv as u32 };
+}
+
+/// Sample rate achieved by a clock configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SampleRate {
+    /// Achieved sample rate, rounded to the nearest Hertz.
+    pub sample_rate: Hertz,
+    /// Deviation of the achieved sample rate from the requested one, in parts per million.
+    pub error_ppm: i32,
+}
+
+/// Clock dividers for a sample rate, computed by [`compute_clock_config()`].
+#[derive(Copy, Clone)]
+pub struct ClockConfig {
+    /// Master clock divider.
+    pub master_clock_divider: MasterClockDivider,
+    /// Bit clock divided directly from the kernel clock, without MCLK.
+    pub nodiv: bool,
+    /// Achieved sample rate.
+    pub sample_rate: SampleRate,
+}
+
+/// Compute the clock dividers giving the sample rate closest to `sample_rate`.
+///
+/// With `master_clock`, MCLK runs at 256 times the sample rate, and the bit clock is derived
+/// from it. Without it, the bit clock is divided directly from the kernel clock, and runs at
+/// `frame_length` times the sample rate, so `frame_length` must not be zero.
+pub fn compute_clock_config(
+    kernel_clock: Hertz,
+    sample_rate: Hertz,
+    frame_length: u16,
+    master_clock: bool,
+) -> ClockConfig {
+    assert!(master_clock || frame_length > 0, "SAI: frame length must not be zero");
+    let bits = if master_clock { 256 } else { frame_length as u32 };
+    let (mckdiv, sample_rate) = closest_divider(kernel_clock, sample_rate, bits);
+    ClockConfig {
+        master_clock_divider: MasterClockDivider::from_bits(mckdiv),
+        nodiv: !master_clock,
+        sample_rate,
+    }
+}
+
+/// Find the divider of `kernel_clock` closest to `rate * bits`.
+fn closest_divider(kernel_clock: Hertz, rate: Hertz, bits: u32) -> (u8, SampleRate) {
+    let ker = kernel_clock.0 as u64;
+    let target = rate.0 as u64 * bits as u64;
+
+    let err = |mckdiv: u8| (ker / divider_ratio(mckdiv) as u64).abs_diff(target);
+    let mut best = 0;
+    for mckdiv in 1..=MCKDIV_MAX {
+        if err(mckdiv) < err(best) {
+            best = mckdiv;
+        }
+    }
+
+    let ratio = divider_ratio(best) as u64;
+    let achieved = (ker + ratio * bits as u64 / 2) / (ratio * bits as u64);
+    // achieved / requested = ker / (ratio * target)
+    let den = ratio * target;
+    let ppm = (ker * 1_000_000 + den / 2) / den;
+    (
+        best,
+        SampleRate {
+            sample_rate: Hertz(achieved as u32),
+            error_ppm: (ppm as i64 - 1_000_000) as i32,
+        },
+    )
+}
+
+/// PLL settings found by [`solve_pll()`].
+#[derive(Copy, Clone)]
+pub struct PllSolution {
+    /// PLL multiplier.
+    pub mul: u32,
+    /// PLL output divider.
+    pub div: u32,
+    /// Resulting SAI kernel clock.
+    pub kernel_clock: Hertz,
+    /// SAI clock dividers and achieved sample rate.
+    pub clock: ClockConfig,
+}
+
+/// Search the PLL settings giving the sample rate closest to `sample_rate`.
+///
+/// The PLL is modelled as `pll_input * mul / div`, with `mul` in `mul_range`, `div` in
+/// `div_range`, and the VCO frequency `pll_input * mul` in `vco_range`. Convert the result to
+/// the PLL configuration of the chip, e.g. `PllMul` and `PllDiv` of the output clocking the SAI.
+///
+/// `frame_length` and `master_clock` are as in [`compute_clock_config()`]. Returns `None` if no
+/// setting keeps the VCO in range.
+pub fn solve_pll(
+    pll_input: Hertz,
+    mul_range: RangeInclusive<u32>,
+    div_range: RangeInclusive<u32>,
+    vco_range: RangeInclusive<Hertz>,
+    sample_rate: Hertz,
+    frame_length: u16,
+    master_clock: bool,
+) -> Option<PllSolution> {
+    let mut best: Option<PllSolution> = None;
+    for mul in mul_range {
+        let vco = pll_input.0 as u64 * mul as u64;
+        if vco < vco_range.start().0 as u64 || vco > vco_range.end().0 as u64 {
+            continue;
+        }
+        for div in div_range.clone() {
+            let kernel_clock = Hertz((vco / div as u64) as u32);
+            let clock = compute_clock_config(kernel_clock, sample_rate, frame_length, master_clock);
+            let better = match &best {
+                Some(best) => clock.sample_rate.error_ppm.abs() < best.clock.sample_rate.error_ppm.abs(),
+                None => true,
+            };
+            if better {
+                best = Some(PllSolution {
+                    mul,
+                    div,
+                    kernel_clock,
+                    clock,
+                });
+                if clock.sample_rate.error_ppm == 0 {
+                    return best;
+                }
+            }
+        }
+    }
+    best
 }
 
 enum RingBuffer<'d, W: word::Word> {
@@ -561,9 +780,9 @@ impl<'d, T: Instance, W: word::Word> Sai<'d, T, W> {
         config: Config,
     ) -> Self {
         let (_sd_af_type, ck_af_type) = get_af_types(config.mode, config.tx_rx);
-        set_as_af!(mclk, ck_af_type);
+        let mclk = new_pin!(mclk, ck_af_type);
 
-        Self::new_asynchronous(peri, sck, sd, fs, dma, dma_buf, _irq, config)
+        Self::new_asynchronous_inner(peri, sck, sd, fs, mclk, dma, dma_buf, _irq, config)
     }
 
     /// Create a new SAI driver in asynchronous mode without MCLK.
@@ -578,6 +797,20 @@ impl<'d, T: Instance, W: word::Word> Sai<'d, T, W> {
         dma_buf: &'d mut [W],
         irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
         config: Config,
+    ) -> Self {
+        Self::new_asynchronous_inner(peri, sck, sd, fs, None, dma, dma_buf, irq, config)
+    }
+
+    fn new_asynchronous_inner<S: SubBlockInstance, D: Dma<T, S>>(
+        peri: SubBlock<'d, T, S>,
+        sck: Peri<'d, impl SckPin<T, S>>,
+        sd: Peri<'d, impl SdPin<T, S>>,
+        fs: Peri<'d, impl FsPin<T, S>>,
+        mclk: Option<Flex<'d>>,
+        dma: Peri<'d, D>,
+        dma_buf: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
+        config: Config,
     ) -> Self {
         let peri = peri.peri;
 
@@ -590,7 +823,7 @@ impl<'d, T: Instance, W: word::Word> Sai<'d, T, W> {
             peri,
             sub_block,
             new_pin!(sck, ck_af_type),
-            None,
+            mclk,
             new_pin!(sd, sd_af_type),
             new_pin!(fs, ck_af_type),
             get_ring_buffer::<T, W>(Channel::new(dma, irq), dma_buf, request, sub_block, config.tx_rx),
@@ -630,6 +863,115 @@ impl<'d, T: Instance, W: word::Word> Sai<'d, T, W> {
         )
     }
 
+    /// Create a full-duplex pair of drivers on the two sub-blocks of a SAI.
+    ///
+    /// Sub-block A is asynchronous and transmits on `sd_a`. It drives SCK and FS in master
+    /// mode, or receives them in slave mode. Sub-block B is synchronous to A and receives on
+    /// `sd_b`, so both directions share one clock domain, as most codecs expect.
+    ///
+    /// `config.tx_rx` and the sync settings are ignored. Returns the transmitter and the receiver.
+    pub fn new_full_duplex<DA: Dma<T, A>, DB: Dma<T, B>>(
+        peri: Peri<'d, T>,
+        sck: Peri<'d, impl SckPin<T, A>>,
+        fs: Peri<'d, impl FsPin<T, A>>,
+        sd_a: Peri<'d, impl SdPin<T, A>>,
+        sd_b: Peri<'d, impl SdPin<T, B>>,
+        dma_a: Peri<'d, DA>,
+        dma_buf_a: &'d mut [W],
+        dma_b: Peri<'d, DB>,
+        dma_buf_b: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<DA::Interrupt, dma::InterruptHandler<DA>>
+        + interrupt::typelevel::Binding<DB::Interrupt, dma::InterruptHandler<DB>>
+        + Copy
+        + 'd,
+        config: Config,
+    ) -> (Self, Self) {
+        Self::new_full_duplex_inner(
+            peri, sck, fs, None, sd_a, sd_b, dma_a, dma_buf_a, dma_b, dma_buf_b, irq, config,
+        )
+    }
+
+    /// Create a full-duplex pair of drivers on the two sub-blocks of a SAI, with MCLK.
+    ///
+    /// See [`Self::new_full_duplex()`].
+    pub fn new_full_duplex_with_mclk<DA: Dma<T, A>, DB: Dma<T, B>>(
+        peri: Peri<'d, T>,
+        sck: Peri<'d, impl SckPin<T, A>>,
+        fs: Peri<'d, impl FsPin<T, A>>,
+        mclk: Peri<'d, impl MclkPin<T, A>>,
+        sd_a: Peri<'d, impl SdPin<T, A>>,
+        sd_b: Peri<'d, impl SdPin<T, B>>,
+        dma_a: Peri<'d, DA>,
+        dma_buf_a: &'d mut [W],
+        dma_b: Peri<'d, DB>,
+        dma_buf_b: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<DA::Interrupt, dma::InterruptHandler<DA>>
+        + interrupt::typelevel::Binding<DB::Interrupt, dma::InterruptHandler<DB>>
+        + Copy
+        + 'd,
+        config: Config,
+    ) -> (Self, Self) {
+        let (_sd_af_type, ck_af_type) = get_af_types(config.mode, TxRx::Transmitter);
+        let mclk = new_pin!(mclk, ck_af_type);
+
+        Self::new_full_duplex_inner(
+            peri, sck, fs, mclk, sd_a, sd_b, dma_a, dma_buf_a, dma_b, dma_buf_b, irq, config,
+        )
+    }
+
+    fn new_full_duplex_inner<DA: Dma<T, A>, DB: Dma<T, B>>(
+        peri: Peri<'d, T>,
+        sck: Peri<'d, impl SckPin<T, A>>,
+        fs: Peri<'d, impl FsPin<T, A>>,
+        mclk: Option<Flex<'d>>,
+        sd_a: Peri<'d, impl SdPin<T, A>>,
+        sd_b: Peri<'d, impl SdPin<T, B>>,
+        dma_a: Peri<'d, DA>,
+        dma_buf_a: &'d mut [W],
+        dma_b: Peri<'d, DB>,
+        dma_buf_b: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<DA::Interrupt, dma::InterruptHandler<DA>>
+        + interrupt::typelevel::Binding<DB::Interrupt, dma::InterruptHandler<DB>>
+        + Copy
+        + 'd,
+        config: Config,
+    ) -> (Self, Self) {
+        let (sub_block_a, sub_block_b) = split_subblocks(peri);
+
+        // The synchronous block is enabled first, so that it doesn't miss the first frame
+        // clocked by the asynchronous one.
+        let rx = Self::new_synchronous(
+            sub_block_b,
+            sd_b,
+            dma_b,
+            dma_buf_b,
+            irq,
+            Config {
+                tx_rx: TxRx::Receiver,
+                sync_input: SyncInput::Internal,
+                sync_output: false,
+                ..config
+            },
+        );
+        let tx = Self::new_asynchronous_inner(
+            sub_block_a,
+            sck,
+            sd_a,
+            fs,
+            mclk,
+            dma_a,
+            dma_buf_a,
+            irq,
+            Config {
+                tx_rx: TxRx::Transmitter,
+                sync_input: SyncInput::None,
+                sync_output: false,
+                ..config
+            },
+        );
+        (tx, rx)
+    }
+
     fn new_inner(
         peri: Peri<'d, T>,
         sub_block: WhichSubBlock,
@@ -640,6 +982,8 @@ impl<'d, T: Instance, W: word::Word> Sai<'d, T, W> {
         ring_buffer: RingBuffer<'d, W>,
         config: Config,
     ) -> Self {
+        assert!(config.frame_length > 0, "SAI: frame length must not be zero");
+
         let ch = T::REGS.ch(sub_block as usize);
 
         ch.cr1().modify(|w| w.set_saien(false));
@@ -680,6 +1024,8 @@ impl<'d, T: Instance, W: word::Word> Sai<'d, T, W> {
             w.set_outdriv(config.output_drive.outdriv());
             w.set_mckdiv(config.master_clock_divider);
             w.set_nodiv(config.nodiv);
+            #[cfg(any(sai_v3, sai_v4))]
+            w.set_mcken(mclk.is_some());
             w.set_dmaen(true);
         });
 
@@ -875,6 +1221,11 @@ pin_trait!(SckPin, Instance, SubBlockInstance);
 pin_trait!(FsPin, Instance, SubBlockInstance);
 pin_trait!(SdPin, Instance, SubBlockInstance);
 pin_trait!(MclkPin, Instance, SubBlockInstance);
+pin_trait!(Ck1Pin, Instance);
+pin_trait!(D1Pin, Instance);
+pin_trait!(D2Pin, Instance);
+pin_trait!(D3Pin, Instance);
+pin_trait!(D4Pin, Instance);
 
 dma_trait!(Dma, Instance, SubBlockInstance);
 
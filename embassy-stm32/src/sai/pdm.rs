@@ -0,0 +1,393 @@
+//! PDM microphone input
+//!
+//! Sub-block A of the SAI can receive the bitstream of PDM microphones: the SAI_CKx pins output
+//! the microphone clock, and each SAI_Dx pin carries two microphones, one sampled on the rising
+//! edge and one on the falling edge of the clock. The SAI de-interleaves the bitstreams, so the
+//! received words hold consecutive PDM bits of each microphone in turn.
+//!
+//! [`PdmDecimator`] converts these bitstreams to PCM samples with a CIC filter in software. It
+//! is enough for voice applications; for high quality audio, a sharper FIR filter should follow
+//! it.
+
+use super::{
+    A, Config, DataSize, Dma, Error, FrameSyncDefinition, FrameSyncOffset, FrameSyncPolarity, Instance,
+    MasterClockDivider, Mode, Protocol, Sai, SubBlock, TxRx, closest_divider, get_af_types, get_ring_buffer, word,
+};
+use crate::dma::{self, Channel};
+use crate::gpio::Flex;
+use crate::time::Hertz;
+use crate::{Peri, interrupt, rcc};
+
+trait SealedPdmWord {
+    const DATA_SIZE: DataSize;
+    fn to_u32(self) -> u32;
+}
+
+/// Word size of the raw PDM data.
+///
+/// Each word holds 8 or 16 consecutive bits of one microphone, the oldest bit first.
+#[allow(private_bounds)]
+pub trait PdmWord: SealedPdmWord + word::Word {}
+
+impl SealedPdmWord for u8 {
+    const DATA_SIZE: DataSize = DataSize::Data8;
+    fn to_u32(self) -> u32 {
+        self as u32
+    }
+}
+impl PdmWord for u8 {}
+
+impl SealedPdmWord for u16 {
+    const DATA_SIZE: DataSize = DataSize::Data16;
+    fn to_u32(self) -> u32 {
+        self as u32
+    }
+}
+impl PdmWord for u16 {}
+
+/// [`SaiPdm`] configuration.
+#[non_exhaustive]
+#[derive(Copy, Clone)]
+pub struct PdmConfig {
+    /// Frequency of the microphone clock.
+    ///
+    /// The clock is derived from the SAI kernel clock by an integer divider, so the actual
+    /// frequency may differ. Use [`SaiPdm::clock_frequency()`] to get it.
+    pub clock_frequency: Hertz,
+}
+
+impl Default for PdmConfig {
+    fn default() -> Self {
+        Self {
+            clock_frequency: Hertz::khz(3072),
+        }
+    }
+}
+
+/// PDM microphone receiver on SAI sub-block A.
+pub struct SaiPdm<'d, T: Instance, W: PdmWord> {
+    sai: Sai<'d, T, W>,
+    _ck: Flex<'d>,
+    _data: [Option<Flex<'d>>; 4],
+    mics: usize,
+    clock_frequency: Hertz,
+}
+
+impl<'d, T: Instance, W: PdmWord> SaiPdm<'d, T, W> {
+    /// Create a PDM receiver for one data line, with up to two microphones.
+    ///
+    /// The microphone with its select pin tied for the rising edge comes first in the received
+    /// data. You can obtain the [`SubBlock`] with [`split_subblocks`](super::split_subblocks).
+    pub fn new<D: Dma<T, A>>(
+        peri: SubBlock<'d, T, A>,
+        ck1: Peri<'d, impl super::Ck1Pin<T>>,
+        d1: Peri<'d, impl super::D1Pin<T>>,
+        dma: Peri<'d, D>,
+        dma_buf: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
+        config: PdmConfig,
+    ) -> Self {
+        let (d_af_type, _) = get_af_types(Mode::Master, TxRx::Receiver);
+        let data = [new_pin!(d1, d_af_type), None, None, None];
+        Self::new_inner(peri, ck1, data, 1, dma, dma_buf, irq, config)
+    }
+
+    /// Create a PDM receiver for two data lines, with up to four microphones.
+    ///
+    /// All microphones are clocked by `ck1`. The microphones of `d1` come first in the
+    /// received data.
+    pub fn new_2_lines<D: Dma<T, A>>(
+        peri: SubBlock<'d, T, A>,
+        ck1: Peri<'d, impl super::Ck1Pin<T>>,
+        d1: Peri<'d, impl super::D1Pin<T>>,
+        d2: Peri<'d, impl super::D2Pin<T>>,
+        dma: Peri<'d, D>,
+        dma_buf: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
+        config: PdmConfig,
+    ) -> Self {
+        let (d_af_type, _) = get_af_types(Mode::Master, TxRx::Receiver);
+        let data = [new_pin!(d1, d_af_type), new_pin!(d2, d_af_type), None, None];
+        Self::new_inner(peri, ck1, data, 2, dma, dma_buf, irq, config)
+    }
+
+    /// Create a PDM receiver for four data lines, with up to eight microphones.
+    ///
+    /// All microphones are clocked by `ck1`. The microphones come in data line order in the
+    /// received data.
+    #[cfg(any(sai_v1_4pdm, sai_v3_4pdm, sai_v4_4pdm))]
+    pub fn new_4_lines<D: Dma<T, A>>(
+        peri: SubBlock<'d, T, A>,
+        ck1: Peri<'d, impl super::Ck1Pin<T>>,
+        d1: Peri<'d, impl super::D1Pin<T>>,
+        d2: Peri<'d, impl super::D2Pin<T>>,
+        d3: Peri<'d, impl super::D3Pin<T>>,
+        d4: Peri<'d, impl super::D4Pin<T>>,
+        dma: Peri<'d, D>,
+        dma_buf: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
+        config: PdmConfig,
+    ) -> Self {
+        let (d_af_type, _) = get_af_types(Mode::Master, TxRx::Receiver);
+        let data = [
+            new_pin!(d1, d_af_type),
+            new_pin!(d2, d_af_type),
+            new_pin!(d3, d_af_type),
+            new_pin!(d4, d_af_type),
+        ];
+        Self::new_inner(peri, ck1, data, 4, dma, dma_buf, irq, config)
+    }
+
+    fn new_inner<D: Dma<T, A>>(
+        peri: SubBlock<'d, T, A>,
+        ck1: Peri<'d, impl super::Ck1Pin<T>>,
+        data: [Option<Flex<'d>>; 4],
+        lines: usize,
+        dma: Peri<'d, D>,
+        dma_buf: &'d mut [W],
+        irq: impl interrupt::typelevel::Binding<D::Interrupt, dma::InterruptHandler<D>> + 'd,
+        config: PdmConfig,
+    ) -> Self {
+        let peri = peri.peri;
+        let mics = 2 * lines;
+
+        // Each microphone clock period carries one bit of every microphone, so the bit clock of
+        // the sub-block runs `mics` times faster than the microphone clock.
+        let (mckdiv, rate) = closest_divider(rcc::frequency::<T>(), config.clock_frequency, mics as u32);
+
+        let slot_bits = W::bits() as u16;
+        let frame_length = mics as u16 * slot_bits;
+        let sai_config = Config {
+            mode: Mode::Master,
+            tx_rx: TxRx::Receiver,
+            protocol: Protocol::Free,
+            data_size: W::DATA_SIZE,
+            slot_count: word::U4(mics as u8),
+            slot_enable: (1 << mics) - 1,
+            bit_order: super::BitOrder::MsbFirst,
+            frame_sync_offset: FrameSyncOffset::OnFirstBit,
+            frame_sync_polarity: FrameSyncPolarity::ActiveHigh,
+            frame_sync_active_level_length: word::U7((frame_length / 2) as u8),
+            frame_sync_definition: FrameSyncDefinition::StartOfFrame,
+            frame_length,
+            master_clock_divider: MasterClockDivider::from_bits(mckdiv),
+            nodiv: true,
+            ..Default::default()
+        };
+
+        // The PDM interface must be enabled before the sub-block.
+        T::REGS.pdmcr().modify(|w| {
+            w.set_micnbr(lines as u8 - 1);
+            w.set_cken(0, true);
+            w.set_pdmen(true);
+        });
+
+        let (_, ck_af_type) = get_af_types(Mode::Master, TxRx::Receiver);
+        let ck = unwrap!(new_pin!(ck1, ck_af_type));
+
+        let request = dma.request();
+        let ring_buffer = get_ring_buffer::<T, W>(
+            Channel::new(dma, irq),
+            dma_buf,
+            request,
+            super::WhichSubBlock::A,
+            TxRx::Receiver,
+        );
+        let sai = Sai::new_inner(
+            peri,
+            super::WhichSubBlock::A,
+            None,
+            None,
+            None,
+            None,
+            ring_buffer,
+            sai_config,
+        );
+
+        Self {
+            sai,
+            _ck: ck,
+            _data: data,
+            mics,
+            clock_frequency: rate.sample_rate,
+        }
+    }
+
+    /// Get the number of microphone channels in the received data.
+    pub fn channels(&self) -> usize {
+        self.mics
+    }
+
+    /// Get the actual frequency of the microphone clock.
+    pub fn clock_frequency(&self) -> Hertz {
+        self.clock_frequency
+    }
+
+    /// Start receiving.
+    pub fn start(&mut self) -> Result<(), Error> {
+        self.sai.start()
+    }
+
+    /// Read raw PDM data from the ring buffer.
+    ///
+    /// The words of the microphones are interleaved, in channel order.
+    pub async fn read(&mut self, data: &mut [W]) -> Result<(), Error> {
+        self.sai.read(data).await
+    }
+
+    /// Read PCM samples, decimating the PDM data with `decimator`.
+    ///
+    /// The samples of the microphones are interleaved, in channel order. `pcm.len()` must be a
+    /// multiple of the number of channels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the channel count of `decimator` doesn't match the receiver, or if its
+    /// decimation factor is not a multiple of the word size.
+    pub async fn read_pcm<const C: usize>(
+        &mut self,
+        decimator: &mut PdmDecimator<C>,
+        pcm: &mut [i16],
+    ) -> Result<(), Error> {
+        const SCRATCH_LEN: usize = 256;
+
+        assert_eq!(C, self.mics, "SAI: decimator channel count mismatch");
+        assert!(pcm.len() % C == 0);
+        let decimation = decimator.decimation() as usize;
+        assert!(
+            decimation % W::bits() == 0,
+            "SAI: decimation must be a multiple of the word size"
+        );
+
+        let words_per_frame = C * decimation / W::bits();
+        assert!(words_per_frame <= SCRATCH_LEN);
+        let frames_per_chunk = SCRATCH_LEN / words_per_frame;
+
+        let mut scratch = [W::default(); SCRATCH_LEN];
+        for pcm in pcm.chunks_mut(frames_per_chunk * C) {
+            let raw = &mut scratch[..pcm.len() / C * words_per_frame];
+            self.sai.read(raw).await?;
+            decimator.process(raw, pcm);
+        }
+        Ok(())
+    }
+}
+
+impl<'d, T: Instance, W: PdmWord> Drop for SaiPdm<'d, T, W> {
+    fn drop(&mut self) {
+        // The sub-block must be disabled before the PDM interface.
+        let ch = T::REGS.ch(super::WhichSubBlock::A as usize);
+        ch.cr1().modify(|w| w.set_saien(false));
+        T::REGS.pdmcr().modify(|w| {
+            w.set_pdmen(false);
+            w.set_cken(0, false);
+        });
+    }
+}
+
+const MAX_ORDER: usize = 5;
+
+#[derive(Copy, Clone, Default)]
+struct Cic {
+    integrators: [i32; MAX_ORDER],
+    combs: [i32; MAX_ORDER],
+    count: u16,
+}
+
+/// PDM to PCM decimator for `C` interleaved channels.
+///
+/// Each channel goes through a CIC (sinc) filter of order `order`, keeping one sample out of
+/// `decimation`. The output sample rate is the microphone clock divided by `decimation`, e.g.
+/// 48 kHz from a 3.072 MHz clock with a decimation of 64.
+pub struct PdmDecimator<const C: usize> {
+    order: usize,
+    decimation: u16,
+    shift: u32,
+    channels: [Cic; C],
+}
+
+impl<const C: usize> PdmDecimator<C> {
+    /// Create a decimator.
+    ///
+    /// An order of 4 or 5 gives a good enough attenuation of the PDM noise for voice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is not in `1..=5`, if `decimation` is lower than 2, or if the filter
+    /// gain doesn't fit 32 bits (`order * log2(decimation)` must be below 32).
+    pub fn new(order: u8, decimation: u16) -> Self {
+        assert!((1..=MAX_ORDER as u8).contains(&order), "PDM: CIC order must be 1 to 5");
+        assert!(decimation >= 2, "PDM: decimation must be at least 2");
+
+        // The gain of the filter is decimation^order, round it up to a power of two.
+        let gain_bits = order as u32 * (u16::BITS - (decimation - 1).leading_zeros());
+        assert!(gain_bits < 32, "PDM: CIC gain overflows 32 bits");
+
+        Self {
+            order: order as usize,
+            decimation,
+            shift: gain_bits.saturating_sub(15),
+            channels: [Cic::default(); C],
+        }
+    }
+
+    /// Get the decimation factor.
+    pub fn decimation(&self) -> u16 {
+        self.decimation
+    }
+
+    /// Clear the filter state, e.g. after an overrun.
+    pub fn reset(&mut self) {
+        self.channels = [Cic::default(); C];
+    }
+
+    /// Decimate interleaved PDM words into interleaved PCM samples.
+    ///
+    /// The filter state is kept between calls, so the PDM data can be fed in chunks of any
+    /// number of frames. Returns the number of samples written to `pcm`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pdm.len()` is not a multiple of `C`, if the decimation factor is not a
+    /// multiple of the word size, or if `pcm` is too small.
+    pub fn process<W: PdmWord>(&mut self, pdm: &[W], pcm: &mut [i16]) -> usize {
+        assert!(pdm.len() % C == 0);
+        assert!(self.decimation as usize % W::bits() == 0);
+
+        let mut written = 0;
+        for frame in pdm.chunks_exact(C) {
+            let mut frame_done = false;
+            for (ch, (cic, &word)) in self.channels.iter_mut().zip(frame).enumerate() {
+                let word = word.to_u32();
+                for bit in (0..W::bits()).rev() {
+                    let x = if word & (1 << bit) != 0 { 1 } else { -1 };
+                    cic.integrators[0] = cic.integrators[0].wrapping_add(x);
+                    for i in 1..self.order {
+                        cic.integrators[i] = cic.integrators[i].wrapping_add(cic.integrators[i - 1]);
+                    }
+
+                    cic.count += 1;
+                    if cic.count == self.decimation {
+                        cic.count = 0;
+                        let mut v = cic.integrators[self.order - 1];
+                        for comb in &mut cic.combs[..self.order] {
+                            let prev = *comb;
+                            *comb = v;
+                            v = v.wrapping_sub(prev);
+                        }
+                        let sample = (v >> self.shift).clamp(i16::MIN as i32, i16::MAX as i32);
+
+                        // All channels reach the end of a sample period on the last bit of
+                        // the same frame.
+                        pcm[written + ch] = sample as i16;
+                        frame_done = true;
+                    }
+                }
+            }
+            if frame_done {
+                written += C;
+            }
+        }
+        written
+    }
+}
@@ -0,0 +1,73 @@
+//! Card detect switch of an SD card socket.
+
+use embassy_time::Duration;
+
+use crate::exti::{DebouncedInput, ExtiInput};
+use crate::gpio::Level;
+use crate::mode::Async;
+
+/// Time the card detect switch needs to settle after an edge.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Card insertion or removal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CardEvent {
+    /// A card was inserted into the socket.
+    Inserted,
+    /// The card was removed from the socket.
+    Removed,
+}
+
+/// Card detect switch of an SD card socket.
+///
+/// Use with [`StorageDevice::acquire_on_insertion`](super::sd::StorageDevice::acquire_on_insertion)
+/// to initialize a card when it is plugged in.
+pub struct CardDetect<'d> {
+    input: DebouncedInput<'d>,
+    inserted_level: Level,
+}
+
+impl<'d> CardDetect<'d> {
+    /// Create a card detect from an EXTI input.
+    ///
+    /// `inserted_level` is the pin level while a card is in the socket. Most
+    /// sockets close the switch to ground, i.e. [`Level::Low`] with a pull-up.
+    pub fn new(pin: ExtiInput<'d, Async>, inserted_level: Level) -> Self {
+        Self {
+            input: DebouncedInput::new(pin, DEBOUNCE),
+            inserted_level,
+        }
+    }
+
+    /// Whether a card is in the socket, according to the last debounced state.
+    pub fn is_inserted(&self) -> bool {
+        self.input.level() == self.inserted_level
+    }
+
+    /// Wait for the next insertion or removal.
+    ///
+    /// Edges are debounced, and an event is only reported when the debounced
+    /// state differs from the previously reported one.
+    pub async fn wait_for_event(&mut self) -> CardEvent {
+        if self.input.wait_for_change().await == self.inserted_level {
+            CardEvent::Inserted
+        } else {
+            CardEvent::Removed
+        }
+    }
+
+    /// Wait until a card is in the socket. Returns immediately if there already is one.
+    pub async fn wait_for_insertion(&mut self) {
+        while !self.is_inserted() {
+            self.wait_for_event().await;
+        }
+    }
+
+    /// Wait until the socket is empty. Returns immediately if it already is.
+    pub async fn wait_for_removal(&mut self) {
+        while self.is_inserted() {
+            self.wait_for_event().await;
+        }
+    }
+}
@@ -4,7 +4,11 @@
 use core::default::Default;
 use core::future::poll_fn;
 use core::marker::PhantomData;
+#[cfg(sdmmc_v2)]
+use core::ptr;
 use core::slice;
+#[cfg(sdmmc_v2)]
+use core::sync::atomic::{AtomicPtr, AtomicUsize};
 use core::sync::atomic::{Ordering, fence};
 use core::task::Poll;
 
@@ -41,6 +45,11 @@ pub mod sd;
 /// Module for SDIO interface
 pub mod sdio;
 
+#[cfg(all(feature = "exti", feature = "time"))]
+mod card_detect;
+#[cfg(all(feature = "exti", feature = "time"))]
+pub use card_detect::{CardDetect, CardEvent};
+
 #[cfg(sdmmc_dlyb)]
 mod dlyb;
 
@@ -67,10 +76,42 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
             T::state().it_waker.wake();
         }
 
+        // The IDMA of a scatter-gather transfer moved on to the other buffer,
+        // point the one it just finished at the next block.
+        #[cfg(sdmmc_v2)]
+        if status.idmabtc() {
+            let regs = T::info().regs;
+            regs.icr().write(|w| w.set_idmabtcc(true));
+            if let Some(address) = T::state().scatter.next() {
+                match regs.idmactrlr().read().idmabact() {
+                    true => regs.idmabase0r().write(|w| w.set_idmabase0(address)),
+                    false => regs.idmabase1r().write(|w| w.set_idmabase1(address)),
+                }
+            }
+        }
+
         T::info().regs.maskr().modify(|w| {
             if status.sdioit() {
                 w.set_sdioitie(false);
             }
+            // FIFO service requests of a transfer without DMA. Re-armed once
+            // the FIFO has been serviced.
+            #[cfg(sdmmc_v1)]
+            if status.rxfifohf() {
+                w.set_rxfifohfie(false);
+            }
+            #[cfg(sdmmc_v1)]
+            if status.txfifohe() {
+                w.set_txfifoheie(false);
+            }
+            #[cfg(sdmmc_v1)]
+            if status.rxoverr() {
+                w.set_rxoverrie(false);
+            }
+            #[cfg(sdmmc_v1)]
+            if status.txunderr() {
+                w.set_txunderrie(false);
+            }
             if status.dcrcfail() {
                 w.set_dcrcfailie(false);
             }
@@ -176,6 +217,23 @@ impl Default for Signalling {
     }
 }
 
+/// Output driver strength of an SD card in the UHS-I modes
+///
+/// See PLSS v7_10 §4.3.10.4, function group 3 of CMD6.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DriverStrength {
+    /// Type B, 50 Ω. Supported by all UHS-I cards.
+    #[default]
+    TypeB = 0,
+    /// Type A, 33 Ω
+    TypeA = 1,
+    /// Type C, 66 Ω
+    TypeC = 2,
+    /// Type D, 100 Ω
+    TypeD = 3,
+}
+
 const fn aligned_mut(x: &mut [u32]) -> &mut Aligned<A4, [u8]> {
     let len = x.len() * 4;
     unsafe { core::mem::transmute(slice::from_raw_parts_mut(x.as_mut_ptr() as *mut u8, len)) }
@@ -241,9 +299,15 @@ pub enum Error {
     VoltageSwitchFailed,
     /// Underrun error
     Underrun,
+    /// The SDIO card reported an error in an IO_RW_DIRECT (CMD52) response.
+    SdioResponse,
     /// ST bit error.
     #[cfg(sdmmc_v1)]
     StBitErr,
+    /// Receive FIFO overrun. Only happens without DMA, when the FIFO isn't
+    /// drained in time; lower the bus frequency.
+    #[cfg(sdmmc_v1)]
+    Overrun,
 }
 
 #[repr(u8)]
@@ -363,22 +427,39 @@ fn clk_div(ker_ck: Hertz, sdmmc_ck: Hertz) -> Result<(bool, u16, Hertz), Error>
 }
 
 #[cfg(sdmmc_v1)]
-type Transfer<'a> = crate::dma::Transfer<'a>;
+enum Transfer<'a> {
+    Dma(#[allow(dead_code)] crate::dma::Transfer<'a>),
+    /// FIFO serviced by the CPU, for drivers created without a DMA channel.
+    Fifo(FifoTransfer<'a>),
+}
+
+/// Position of a CPU-driven transfer through its buffer.
+#[cfg(sdmmc_v1)]
+enum FifoTransfer<'a> {
+    Read(&'a mut [u32]),
+    Write(&'a [u32]),
+}
+
+/// Number of words the FIFO can take or give when its half empty/half full
+/// flag is set.
+#[cfg(sdmmc_v1)]
+const FIFO_HALF_WORDS: usize = 8;
 #[cfg(any(sdmmc_v2, sdmmc_v3))]
 struct Transfer<'a> {
     _dummy: PhantomData<&'a ()>,
 }
 
 struct WrappedTransfer<'a> {
-    _transfer: Transfer<'a>,
+    #[cfg_attr(any(sdmmc_v2, sdmmc_v3), allow(dead_code))]
+    transfer: Transfer<'a>,
     sdmmc: &'a Sdmmc<'a>,
     defused: bool,
 }
 
 impl<'a> WrappedTransfer<'a> {
-    pub const fn new(_transfer: Transfer<'a>, sdmmc: &'a Sdmmc) -> Self {
+    pub const fn new(transfer: Transfer<'a>, sdmmc: &'a Sdmmc) -> Self {
         Self {
-            _transfer,
+            transfer,
             sdmmc,
             defused: false,
         }
@@ -422,12 +503,13 @@ const DMA_TRANSFER_OPTIONS: crate::dma::TransferOptions = crate::dma::TransferOp
 /// data_transfer_timeout: 5_000_000
 /// use_cmd23: false
 /// use_acmd23: false
+/// driver_strength: DriverStrength::TypeB
 #[non_exhaustive]
 pub struct Config {
     /// The timeout to be set for data transfers, in card bus clock periods
     pub data_transfer_timeout: u32,
 
-    /// Pre-declare block count via CMD23 before CMD25, skipping CMD12.
+    /// Pre-declare block count via CMD23 before CMD18/CMD25, skipping CMD12.
     /// Honored only if the card advertises support; falls back silently
     /// otherwise (see [`StorageDevice::supports_cmd23`]).
     pub use_cmd23: bool,
@@ -435,6 +517,22 @@ pub struct Config {
     /// Send ACMD23 (SET_WR_BLK_ERASE_COUNT) before CMD25 as a pre-erase
     /// hint. Mandatory in SD spec v2+, so honored unconditionally.
     pub use_acmd23: bool,
+
+    /// Card output driver strength requested when switching to a UHS-I mode.
+    pub driver_strength: DriverStrength,
+
+    /// Allow switching a UHS-I SD card on a 4-bit bus to DDR50.
+    ///
+    /// DDR50 is only used when neither SDR50 nor SDR104 can be, as they need a receive clock
+    /// source. It samples data on both clock edges, so only enable this if the board layout
+    /// supports it.
+    pub ddr50: bool,
+
+    /// Allow switching an eMMC to HS200 above 52 MHz.
+    ///
+    /// HS200 requires the eMMC I/O to be powered from 1.8V, which can't be detected, so only
+    /// enable this on such boards. If the switch or the tuning fails, High Speed is used.
+    pub emmc_hs200: bool,
 }
 
 impl Default for Config {
@@ -443,6 +541,9 @@ impl Default for Config {
             data_transfer_timeout: 5_000_000,
             use_cmd23: false,
             use_acmd23: false,
+            driver_strength: DriverStrength::TypeB,
+            ddr50: false,
+            emmc_hs200: false,
         }
     }
 }
@@ -453,7 +554,7 @@ pub struct Sdmmc<'d> {
     state: &'static State,
     ker_clk: Hertz,
     #[cfg(sdmmc_v1)]
-    dma: ChannelAndRequest<'d>,
+    dma: Option<ChannelAndRequest<'d>>,
 
     _clk: Flex<'d>,
     _cmd: Flex<'d>,
@@ -490,6 +591,11 @@ pub struct Sdmmc<'d> {
     #[cfg(sdmmc_uhs)]
     feedback_clk: bool,
 
+    /// `true` after a successful UHS-DDR50 negotiation. Sets `CLKCR.ddr`
+    /// so data is transferred on both clock edges.
+    #[cfg(sdmmc_uhs)]
+    ddr: bool,
+
     /// Optional CKIN feedback-clock input pin. `Some` only when the user
     /// constructed via `*_with_vswitch_ckin`. `acquire()` checks this
     /// at the SDR25→SDR50 decision: if absent, SDR50 isn't attempted
@@ -545,7 +651,7 @@ impl<'d> Sdmmc<'d> {
     ) -> Self {
         Self::new_inner(
             sdmmc,
-            new_dma_nonopt!(dma, _irq),
+            Some(new_dma_nonopt!(dma, _irq)),
             new_pin!(clk, CLK_AF).unwrap(),
             new_pin!(cmd, CMD_AF).unwrap(),
             new_pin!(d0, DATA_AF).unwrap(),
@@ -583,7 +689,7 @@ impl<'d> Sdmmc<'d> {
     ) -> Self {
         Self::new_inner(
             sdmmc,
-            new_dma_nonopt!(dma, _irq),
+            Some(new_dma_nonopt!(dma, _irq)),
             new_pin!(clk, CLK_AF).unwrap(),
             new_pin!(cmd, CMD_AF).unwrap(),
             new_pin!(d0, DATA_AF).unwrap(),
@@ -628,7 +734,123 @@ impl<'d> Sdmmc<'d> {
     ) -> Self {
         Self::new_inner(
             sdmmc,
-            new_dma_nonopt!(dma, _irq),
+            Some(new_dma_nonopt!(dma, _irq)),
+            new_pin!(clk, CLK_AF).unwrap(),
+            new_pin!(cmd, CMD_AF).unwrap(),
+            new_pin!(d0, DATA_AF).unwrap(),
+            new_pin!(d1, DATA_AF),
+            new_pin!(d2, DATA_AF),
+            new_pin!(d3, DATA_AF),
+            new_pin!(d4, DATA_AF),
+            new_pin!(d5, DATA_AF),
+            new_pin!(d6, DATA_AF),
+            new_pin!(d7, DATA_AF),
+            #[cfg(sdmmc_uhs)]
+            None,
+            #[cfg(sdmmc_uhs)]
+            None,
+            #[cfg(sdmmc_dlyb)]
+            None,
+            config,
+        )
+    }
+}
+
+/// Constructors for parts where no DMA channel is available for the SDMMC.
+///
+/// The data FIFO is serviced by the CPU from the FIFO half full/half empty
+/// interrupts. This is considerably slower than DMA, and since hardware flow
+/// control is unusable on this peripheral, a FIFO that isn't serviced in time
+/// ends the transfer with [`Error::Overrun`] or [`Error::Underrun`]. Keep the
+/// bus frequency low enough for the executor's latency.
+#[cfg(sdmmc_v1)]
+impl<'d> Sdmmc<'d> {
+    /// Create a new SDMMC driver without DMA, with 1 data lane.
+    pub fn new_1bit_no_dma<T: Instance>(
+        sdmmc: Peri<'d, T>,
+        _irq: impl interrupt::typelevel::Binding<T::Interrupt, InterruptHandler<T>> + 'd,
+        clk: Peri<'d, impl CkPin<T>>,
+        cmd: Peri<'d, impl CmdPin<T>>,
+        d0: Peri<'d, impl D0Pin<T>>,
+        config: Config,
+    ) -> Self {
+        Self::new_inner(
+            sdmmc,
+            None,
+            new_pin!(clk, CLK_AF).unwrap(),
+            new_pin!(cmd, CMD_AF).unwrap(),
+            new_pin!(d0, DATA_AF).unwrap(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            #[cfg(sdmmc_uhs)]
+            None,
+            #[cfg(sdmmc_uhs)]
+            None,
+            #[cfg(sdmmc_dlyb)]
+            None,
+            config,
+        )
+    }
+
+    /// Create a new SDMMC driver without DMA, with 4 data lanes.
+    pub fn new_4bit_no_dma<T: Instance>(
+        sdmmc: Peri<'d, T>,
+        _irq: impl interrupt::typelevel::Binding<T::Interrupt, InterruptHandler<T>> + 'd,
+        clk: Peri<'d, impl CkPin<T>>,
+        cmd: Peri<'d, impl CmdPin<T>>,
+        d0: Peri<'d, impl D0Pin<T>>,
+        d1: Peri<'d, impl D1Pin<T>>,
+        d2: Peri<'d, impl D2Pin<T>>,
+        d3: Peri<'d, impl D3Pin<T>>,
+        config: Config,
+    ) -> Self {
+        Self::new_inner(
+            sdmmc,
+            None,
+            new_pin!(clk, CLK_AF).unwrap(),
+            new_pin!(cmd, CMD_AF).unwrap(),
+            new_pin!(d0, DATA_AF).unwrap(),
+            new_pin!(d1, DATA_AF),
+            new_pin!(d2, DATA_AF),
+            new_pin!(d3, DATA_AF),
+            None,
+            None,
+            None,
+            None,
+            #[cfg(sdmmc_uhs)]
+            None,
+            #[cfg(sdmmc_uhs)]
+            None,
+            #[cfg(sdmmc_dlyb)]
+            None,
+            config,
+        )
+    }
+
+    /// Create a new SDMMC driver without DMA, with 8 data lanes.
+    pub fn new_8bit_no_dma<T: Instance>(
+        sdmmc: Peri<'d, T>,
+        _irq: impl interrupt::typelevel::Binding<T::Interrupt, InterruptHandler<T>> + 'd,
+        clk: Peri<'d, impl CkPin<T>>,
+        cmd: Peri<'d, impl CmdPin<T>>,
+        d0: Peri<'d, impl D0Pin<T>>,
+        d1: Peri<'d, impl D1Pin<T>>,
+        d2: Peri<'d, impl D2Pin<T>>,
+        d3: Peri<'d, impl D3Pin<T>>,
+        d4: Peri<'d, impl D4Pin<T>>,
+        d5: Peri<'d, impl D5Pin<T>>,
+        d6: Peri<'d, impl D6Pin<T>>,
+        d7: Peri<'d, impl D7Pin<T>>,
+        config: Config,
+    ) -> Self {
+        Self::new_inner(
+            sdmmc,
+            None,
             new_pin!(clk, CLK_AF).unwrap(),
             new_pin!(cmd, CMD_AF).unwrap(),
             new_pin!(d0, DATA_AF).unwrap(),
@@ -1023,6 +1245,17 @@ impl<'d> Sdmmc<'d> {
         let _ = on;
     }
 
+    /// Set the DDR flag (`CLKCR.DDR`). Caller must follow with a
+    /// `clkcr_set_clkdiv` to write the bit. No-op outside `cfg(sdmmc_uhs)`.
+    fn set_ddr(&mut self, on: bool) {
+        #[cfg(sdmmc_uhs)]
+        {
+            self.ddr = on;
+        }
+        #[cfg(not(sdmmc_uhs))]
+        let _ = on;
+    }
+
     /// True if this driver owns a UHS-I level-shifter pin. Always
     /// `false` outside `cfg(sdmmc_uhs)`.
     fn has_vswitch(&self) -> bool {
@@ -1063,9 +1296,11 @@ impl<'d> Sdmmc<'d> {
             self.info.regs.clkcr().modify(|w| {
                 w.set_busspeed(false);
                 w.set_selclkrx(0);
+                w.set_ddr(false);
             });
             self.uhs_active = false;
             self.feedback_clk = false;
+            self.ddr = false;
         }
         #[cfg(sdmmc_dlyb)]
         {
@@ -1090,13 +1325,76 @@ impl<'d> Sdmmc<'d> {
                 w.set_stbiterre(true);
                 #[cfg(any(sdmmc_v2, sdmmc_v3))]
                 w.set_dabortie(true);
+
+                // Without DMA, a FIFO that isn't serviced in time aborts the
+                // transfer without raising DATAEND.
+                #[cfg(sdmmc_v1)]
+                if self.dma.is_none() {
+                    w.set_rxoverrie(true);
+                    w.set_txunderrie(true);
+                }
             });
         });
     }
 
+    /// Move data between the FIFO and the buffer of a transfer without DMA.
+    ///
+    /// Re-arms the FIFO interrupt while the buffer isn't done. With `drain`,
+    /// called once the data has ended, received words are read one by one
+    /// until the FIFO is empty, for the tail that doesn't fill half the FIFO.
+    #[cfg(sdmmc_v1)]
+    fn service_fifo(&self, fifo: &mut FifoTransfer<'_>, drain: bool) {
+        let regs = self.info.regs;
+
+        let rearm = match fifo {
+            FifoTransfer::Read(buf) => {
+                loop {
+                    let status = regs.star().read();
+                    let n = if status.rxfifohf() {
+                        FIFO_HALF_WORDS
+                    } else if drain && status.rxdavl() {
+                        1
+                    } else {
+                        break;
+                    };
+                    let n = n.min(buf.len());
+                    if n == 0 {
+                        break;
+                    }
+
+                    let (head, tail) = core::mem::take(buf).split_at_mut(n);
+                    for word in head {
+                        *word = regs.fifor().read().fifodata();
+                    }
+                    *buf = tail;
+                }
+                !buf.is_empty()
+            }
+            FifoTransfer::Write(buf) => {
+                while !buf.is_empty() && regs.star().read().txfifohe() {
+                    let (head, tail) = buf.split_at(FIFO_HALF_WORDS.min(buf.len()));
+                    for word in head {
+                        regs.fifor().write(|w| w.set_fifodata(*word));
+                    }
+                    *buf = tail;
+                }
+                !buf.is_empty()
+            }
+        };
+
+        if rearm && !drain {
+            critical_section::with(|_| {
+                regs.maskr().modify(|w| match fifo {
+                    FifoTransfer::Read(_) => w.set_rxfifohfie(true),
+                    FifoTransfer::Write(_) => w.set_txfifoheie(true),
+                })
+            });
+        }
+    }
+
     fn new_inner<T: Instance>(
         _sdmmc: Peri<'d, T>,
-        #[cfg(sdmmc_v1)] dma: ChannelAndRequest<'d>,
+        #[cfg(sdmmc_v1)] dma: Option<ChannelAndRequest<'d>>,
         clk: Flex<'d>,
         cmd: Flex<'d>,
         d0: Flex<'d>,
@@ -1165,6 +1463,8 @@ impl<'d> Sdmmc<'d> {
             #[cfg(sdmmc_uhs)]
             feedback_clk: false,
             #[cfg(sdmmc_uhs)]
+            ddr: false,
+            #[cfg(sdmmc_uhs)]
             ckin_pin,
 
             #[cfg(sdmmc_dlyb)]
@@ -1240,20 +1540,25 @@ impl<'d> Sdmmc<'d> {
 
         // SAFETY: No other functions use the dma
         #[cfg(sdmmc_v1)]
-        let transfer = unsafe {
-            self.dma
-                .clone_unchecked()
-                .read(
-                    regs.fifor().as_ptr() as *mut u32,
-                    slice32_mut(buffer),
-                    DMA_TRANSFER_OPTIONS,
-                )
-                .unchecked_extend_lifetime()
+        let transfer = match &self.dma {
+            Some(dma) => Transfer::Dma(unsafe {
+                dma.clone_unchecked()
+                    .read(
+                        regs.fifor().as_ptr() as *mut u32,
+                        slice32_mut(buffer),
+                        DMA_TRANSFER_OPTIONS,
+                    )
+                    .unchecked_extend_lifetime()
+            }),
+            None => Transfer::Fifo(FifoTransfer::Read(slice32_mut(buffer))),
         };
         #[cfg(sdmmc_v2)]
         let transfer = {
             regs.idmabase0r().write(|w| w.set_idmabase0(buffer.as_mut_ptr() as u32));
-            regs.idmactrlr().modify(|w| w.set_idmaen(true));
+            regs.idmactrlr().modify(|w| {
+                w.set_idmabmode(false);
+                w.set_idmaen(true);
+            });
             Transfer {
                 _dummy: core::marker::PhantomData,
             }
@@ -1278,7 +1583,7 @@ impl<'d> Sdmmc<'d> {
             w.set_dtdir(true);
             #[cfg(sdmmc_v1)]
             {
-                w.set_dmaen(true);
+                w.set_dmaen(self.dma.is_some());
                 w.set_dten(true);
             }
         });
@@ -1312,20 +1617,25 @@ impl<'d> Sdmmc<'d> {
 
         // SAFETY: No other functions use the dma
         #[cfg(sdmmc_v1)]
-        let transfer = unsafe {
-            self.dma
-                .clone_unchecked()
-                .write(
-                    slice32_ref(buffer),
-                    regs.fifor().as_ptr() as *mut u32,
-                    DMA_TRANSFER_OPTIONS,
-                )
-                .unchecked_extend_lifetime()
+        let transfer = match &self.dma {
+            Some(dma) => Transfer::Dma(unsafe {
+                dma.clone_unchecked()
+                    .write(
+                        slice32_ref(buffer),
+                        regs.fifor().as_ptr() as *mut u32,
+                        DMA_TRANSFER_OPTIONS,
+                    )
+                    .unchecked_extend_lifetime()
+            }),
+            None => Transfer::Fifo(FifoTransfer::Write(slice32_ref(buffer))),
         };
         #[cfg(sdmmc_v2)]
         let transfer = {
             regs.idmabase0r().write(|w| w.set_idmabase0(buffer.as_ptr() as u32));
-            regs.idmactrlr().modify(|w| w.set_idmaen(true));
+            regs.idmactrlr().modify(|w| {
+                w.set_idmabmode(false);
+                w.set_idmaen(true);
+            });
             Transfer {
                 _dummy: core::marker::PhantomData,
             }
@@ -1349,7 +1659,7 @@ impl<'d> Sdmmc<'d> {
             w.set_dtdir(false);
             #[cfg(sdmmc_v1)]
             {
-                w.set_dmaen(true);
+                w.set_dmaen(self.dma.is_some());
                 w.set_dten(true);
             }
         });
@@ -1362,6 +1672,62 @@ impl<'d> Sdmmc<'d> {
         WrappedTransfer::new(transfer, &self)
     }
 
+    /// Prepare a multi-block transfer scattered over (read) or gathered from (write) `bufs`.
+    ///
+    /// The IDMA runs in double-buffer mode with one block per buffer. Each time it is done
+    /// with a buffer, the interrupt handler points that buffer at the next block, so the
+    /// interrupt latency must stay below the time to transfer one block.
+    ///
+    /// # Safety
+    ///
+    /// The blocks of `bufs` must be valid for the whole transfer
+    #[cfg(sdmmc_v2)]
+    fn prepare_datapath_scatter<'a>(&'a self, bufs: &'a [*const [sd::DataBlock]], read: bool) -> WrappedTransfer<'a> {
+        let regs = self.info.regs;
+        let blocks: usize = bufs.iter().map(|b| b.len()).sum();
+
+        // Command AND Data state machines must be idle
+        self.wait_idle();
+        self.clear_interrupt_flags();
+
+        regs.dlenr()
+            .write(|w| w.set_datalength((blocks * size_of::<sd::DataBlock>()) as u32));
+
+        let scatter = &self.state.scatter;
+        scatter.start(bufs);
+        let first = unwrap!(scatter.next());
+        let second = scatter.next().unwrap_or(first);
+
+        regs.idmabase0r().write(|w| w.set_idmabase0(first));
+        regs.idmabase1r().write(|w| w.set_idmabase1(second));
+        // In units of 32 bytes.
+        regs.idmabsizer()
+            .write(|w| w.set_idmabndt((size_of::<sd::DataBlock>() / 32) as u8));
+        regs.idmactrlr().modify(|w| {
+            w.set_idmabmode(true);
+            w.set_idmaen(true);
+        });
+
+        regs.dctrl().modify(|w| {
+            w.set_dtmode(0);
+            w.set_dblocksize(block_size(size_of::<sd::DataBlock>()) as u8);
+            w.set_dtdir(read);
+        });
+
+        // Memory barrier after DMA setup to ensure register writes complete before command
+        fence(Ordering::SeqCst);
+
+        self.enable_interrupts();
+        critical_section::with(|_| regs.maskr().modify(|w| w.set_idmabtcie(true)));
+
+        WrappedTransfer::new(
+            Transfer {
+                _dummy: core::marker::PhantomData,
+            },
+            &self,
+        )
+    }
+
     /// Stops the DMA datapath
     fn stop_datapath(&self) {
         let regs = self.info.regs;
@@ -1373,6 +1739,11 @@ impl<'d> Sdmmc<'d> {
         });
         #[cfg(any(sdmmc_v2, sdmmc_v3))]
         regs.idmactrlr().modify(|w| w.set_idmaen(false));
+        #[cfg(sdmmc_v2)]
+        {
+            critical_section::with(|_| regs.maskr().modify(|w| w.set_idmabtcie(false)));
+            self.state.scatter.stop();
+        }
     }
 
     fn init_idle(&mut self) -> Result<CommandResponse<Rz>, Error> {
@@ -1396,6 +1767,9 @@ impl<'d> Sdmmc<'d> {
 
         let (widbus, width_u32) = bus_width_vals(width);
         let (_bypass, clkdiv, new_clock) = clk_div(self.ker_clk, freq)?;
+        // CLKDIV = 0 (SDMMC_CK = kernel clock) is not allowed in DDR mode.
+        #[cfg(sdmmc_uhs)]
+        let clkdiv = if self.ddr { clkdiv.max(1) } else { clkdiv };
 
         trace!("sdmmc: set clock to {}", new_clock);
 
@@ -1410,6 +1784,8 @@ impl<'d> Sdmmc<'d> {
         let self_uhs_active = self.uhs_active;
         #[cfg(sdmmc_uhs)]
         let self_feedback_clk = self.feedback_clk;
+        #[cfg(sdmmc_uhs)]
+        let self_ddr = self.ddr;
         #[cfg(sdmmc_dlyb)]
         let self_dlyb_active = self.dlyb_active;
         regs.clkcr().modify(|w| {
@@ -1436,6 +1812,11 @@ impl<'d> Sdmmc<'d> {
             if self_feedback_clk {
                 w.set_selclkrx(1);
             }
+            // DDR50 transfers data on both clock edges.
+            #[cfg(sdmmc_uhs)]
+            if self_ddr {
+                w.set_ddr(true);
+            }
             #[cfg(sdmmc_dlyb)]
             if self_dlyb_active {
                 w.set_selclkrx(2);
@@ -1731,6 +2112,12 @@ impl<'d> Sdmmc<'d> {
             // Compiler might not be sufficiently constrained here
             // https://github.com/embassy-rs/embassy/issues/4723
             self.state.tx_waker.register(cx.waker());
+
+            #[cfg(sdmmc_v1)]
+            if let Transfer::Fifo(fifo) = &mut transfer.transfer {
+                self.service_fifo(fifo, false);
+            }
+
             let status = self.info.regs.star().read();
 
             if status.dcrcfail() {
@@ -1747,6 +2134,10 @@ impl<'d> Sdmmc<'d> {
                 return Poll::Ready(Err(Error::StBitErr));
             }
             #[cfg(sdmmc_v1)]
+            if status.rxoverr() {
+                return Poll::Ready(Err(Error::Overrun));
+            }
+            #[cfg(sdmmc_v1)]
             let done = match block {
                 true => status.dbckend(),
                 false => status.dataend(),
@@ -1754,6 +2145,11 @@ impl<'d> Sdmmc<'d> {
             #[cfg(any(sdmmc_v2, sdmmc_v3))]
             let done = status.dataend();
             if done {
+                // Pick up the words that didn't reach the FIFO threshold.
+                #[cfg(sdmmc_v1)]
+                if let Transfer::Fifo(fifo) = &mut transfer.transfer {
+                    self.service_fifo(fifo, true);
+                }
                 return Poll::Ready(Ok(()));
             }
             Poll::Pending
@@ -1795,6 +2191,8 @@ struct Info {
 struct State {
     tx_waker: AtomicWaker,
     it_waker: AtomicWaker,
+    #[cfg(sdmmc_v2)]
+    scatter: Scatter,
 }
 
 impl State {
@@ -1802,7 +2200,67 @@ impl State {
         Self {
             tx_waker: AtomicWaker::new(),
             it_waker: AtomicWaker::new(),
+            #[cfg(sdmmc_v2)]
+            scatter: Scatter::new(),
+        }
+    }
+}
+
+/// Blocks of a scatter-gather transfer, handed to the IDMA one at a time by the
+/// interrupt handler.
+#[cfg(sdmmc_v2)]
+struct Scatter {
+    bufs: AtomicPtr<*const [sd::DataBlock]>,
+    len: AtomicUsize,
+    /// Position of the next block to hand out.
+    buf: AtomicUsize,
+    block: AtomicUsize,
+}
+
+#[cfg(sdmmc_v2)]
+impl Scatter {
+    const fn new() -> Self {
+        Self {
+            bufs: AtomicPtr::new(ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            buf: AtomicUsize::new(0),
+            block: AtomicUsize::new(0),
+        }
+    }
+
+    fn start(&self, bufs: &[*const [sd::DataBlock]]) {
+        self.bufs.store(bufs.as_ptr() as *mut _, Ordering::Relaxed);
+        self.len.store(bufs.len(), Ordering::Relaxed);
+        self.buf.store(0, Ordering::Relaxed);
+        self.block.store(0, Ordering::Relaxed);
+    }
+
+    fn stop(&self) {
+        self.len.store(0, Ordering::Relaxed);
+    }
+
+    /// Address of the next block, or `None` once all of them have been handed out.
+    fn next(&self) -> Option<u32> {
+        let bufs = self.bufs.load(Ordering::Relaxed);
+        let len = self.len.load(Ordering::Relaxed);
+        let mut buf = self.buf.load(Ordering::Relaxed);
+        let mut block = self.block.load(Ordering::Relaxed);
+
+        while buf < len {
+            // SAFETY: `bufs` is valid for `len` entries until `stop()`, and only
+            // the addresses of the blocks are taken.
+            let blocks = unsafe { *bufs.add(buf) };
+            if block < blocks.len() {
+                self.buf.store(buf, Ordering::Relaxed);
+                self.block.store(block + 1, Ordering::Relaxed);
+                return Some(unsafe { (blocks as *const sd::DataBlock).add(block) } as u32);
+            }
+            buf += 1;
+            block = 0;
         }
+
+        self.buf.store(buf, Ordering::Relaxed);
+        None
     }
 }
 
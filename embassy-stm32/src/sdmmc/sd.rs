@@ -2,11 +2,15 @@ use core::default::Default;
 use core::ops::{Deref, DerefMut};
 
 use sdio_host::common_cmd::R3;
+#[cfg(sdmmc_dlyb)]
+use sdio_host::common_cmd::{R1, cmd};
 use sdio_host::emmc::{EMMC, ExtCSD};
 use sdio_host::sd::{BusWidth, CIC, CID, CSD, CardCapacity, CardStatus, CurrentState, OCR, RCA, SCR, SD, SDStatus};
 use sdio_host::sd_cmd::{R6, R7};
 use sdio_host::{common_cmd, emmc_cmd, sd_cmd};
 
+#[cfg(all(feature = "exti", feature = "time"))]
+use crate::sdmmc::CardDetect;
 use crate::sdmmc::{
     BlockSize, CommandResponse, DatapathMode, Error, Sdmmc, Signalling, TypedResp, aligned_mut, aligned_ref,
     block_size, bus_width_vals, slice8_mut, slice8_ref,
@@ -14,6 +18,50 @@ use crate::sdmmc::{
 use crate::time::{Hertz, mhz};
 use crate::wait::try_until;
 
+/// EXT_CSD byte index of BUS_WIDTH
+const EXT_CSD_BUS_WIDTH: u8 = 183;
+/// EXT_CSD byte index of HS_TIMING
+const EXT_CSD_HS_TIMING: u8 = 185;
+/// EXT_CSD byte index of DEVICE_TYPE
+const EXT_CSD_DEVICE_TYPE: usize = 196;
+
+/// DEVICE_TYPE: High Speed eMMC at 26 MHz
+const DEVICE_TYPE_HS_26: u8 = 1 << 0;
+/// DEVICE_TYPE: High Speed eMMC at 52 MHz
+const DEVICE_TYPE_HS_52: u8 = 1 << 1;
+/// DEVICE_TYPE: HS200 at 1.8V I/O
+#[cfg(sdmmc_dlyb)]
+const DEVICE_TYPE_HS200_1V8: u8 = 1 << 4;
+
+/// SEND_TUNING_BLOCK, SD UHS-I
+#[cfg(sdmmc_dlyb)]
+const CMD_SEND_TUNING_BLOCK: u8 = 19;
+/// SEND_TUNING_BLOCK, eMMC HS200
+#[cfg(sdmmc_dlyb)]
+const CMD_SEND_TUNING_BLOCK_HS200: u8 = 21;
+
+/// Tuning block returned by CMD19/CMD21 on a 4-bit bus
+#[cfg(sdmmc_dlyb)]
+const TUNING_BLOCK_4BIT: [u8; 64] = [
+    0xff, 0x0f, 0xff, 0x00, 0xff, 0xcc, 0xc3, 0xcc, 0xc3, 0x3c, 0xcc, 0xff, 0xfe, 0xff, 0xfe, 0xef, //
+    0xff, 0xdf, 0xff, 0xdd, 0xff, 0xfb, 0xff, 0xfb, 0xbf, 0xff, 0x7f, 0xff, 0x77, 0xf7, 0xbd, 0xef, //
+    0xff, 0xf0, 0xff, 0xf0, 0x0f, 0xfc, 0xcc, 0x3c, 0xcc, 0x33, 0xcc, 0xcf, 0xff, 0xef, 0xff, 0xee, //
+    0xff, 0xfd, 0xff, 0xfd, 0xdf, 0xff, 0xbf, 0xff, 0xbb, 0xff, 0xf7, 0xff, 0xf7, 0x7f, 0x7b, 0xde, //
+];
+
+/// Tuning block returned by CMD21 on an 8-bit bus
+#[cfg(sdmmc_dlyb)]
+const TUNING_BLOCK_8BIT: [u8; 128] = [
+    0xff, 0xff, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0xcc, 0xcc, 0xcc, 0x33, 0xcc, 0xcc, //
+    0xcc, 0x33, 0x33, 0xcc, 0xcc, 0xcc, 0xff, 0xff, 0xff, 0xee, 0xff, 0xff, 0xff, 0xee, 0xee, 0xff, //
+    0xff, 0xff, 0xdd, 0xff, 0xff, 0xff, 0xdd, 0xdd, 0xff, 0xff, 0xff, 0xbb, 0xff, 0xff, 0xff, 0xbb, //
+    0xbb, 0xff, 0xff, 0xff, 0x77, 0xff, 0xff, 0xff, 0x77, 0x77, 0xff, 0x77, 0xbb, 0xdd, 0xee, 0xff, //
+    0xff, 0xff, 0xff, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0xff, 0xff, 0xcc, 0xcc, 0xcc, 0x33, 0xcc, //
+    0xcc, 0xcc, 0x33, 0x33, 0xcc, 0xcc, 0xcc, 0xff, 0xff, 0xff, 0xee, 0xff, 0xff, 0xff, 0xee, 0xee, //
+    0xff, 0xff, 0xff, 0xdd, 0xff, 0xff, 0xff, 0xdd, 0xdd, 0xff, 0xff, 0xff, 0xbb, 0xff, 0xff, 0xff, //
+    0xbb, 0xbb, 0xff, 0xff, 0xff, 0x77, 0xff, 0xff, 0xff, 0x77, 0x77, 0xff, 0x77, 0xbb, 0xdd, 0xee, //
+];
+
 impl TypedResp for R3 {
     type Word = u32;
 }
@@ -157,6 +205,20 @@ impl<'a, 'b> StorageDevice<'a, 'b, Card> {
         self.acquire(cmd_block, freq).await
     }
 
+    /// Wait until a card is in the socket, then initialize it.
+    ///
+    /// Call this again after [`CardDetect::wait_for_removal`] to handle card swaps at runtime.
+    #[cfg(all(feature = "exti", feature = "time"))]
+    pub async fn acquire_on_insertion(
+        &mut self,
+        card_detect: &mut CardDetect<'_>,
+        cmd_block: &mut CmdBlock,
+        freq: Hertz,
+    ) -> Result<(), Error> {
+        card_detect.wait_for_insertion().await;
+        self.reacquire(cmd_block, freq).await
+    }
+
     /// Initializes the card into a known state (or at least tries to).
     async fn acquire(&mut self, cmd_block: &mut CmdBlock, freq: Hertz) -> Result<(), Error> {
         let _scoped_wake_guard = self.sdmmc.info.rcc.wake_guard();
@@ -273,6 +335,10 @@ impl<'a, 'b> StorageDevice<'a, 'b, Card> {
                 Signalling::SDR104
             } else if self.sdmmc.uhs_active() && (self.sdmmc.has_ckin() || self.sdmmc.has_dlyb()) && freq > mhz(50) {
                 Signalling::SDR50
+            } else if self.sdmmc.uhs_active() && self.sdmmc.config.ddr50 && matches!(bus_width, BusWidth::Four) {
+                // Without a receive clock source for SDR50, DDR50 still
+                // doubles the throughput of SDR25 at the same clock.
+                Signalling::DDR50
             } else {
                 Signalling::SDR25
             };
@@ -283,7 +349,7 @@ impl<'a, 'b> StorageDevice<'a, 'b, Card> {
                 if matches!(signalling, Signalling::SDR50 | Signalling::SDR104) {
                     #[cfg(sdmmc_dlyb)]
                     let tuned = if self.sdmmc.has_dlyb() {
-                        self.tune_dlyb(freq, bus_width)?;
+                        self.tune_dlyb(freq, bus_width, CMD_SEND_TUNING_BLOCK).await?;
                         true
                     } else {
                         false
@@ -294,6 +360,9 @@ impl<'a, 'b> StorageDevice<'a, 'b, Card> {
                         self.sdmmc.set_feedback_clk(true);
                         self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
                     }
+                } else if signalling == Signalling::DDR50 {
+                    self.sdmmc.set_ddr(true);
+                    self.sdmmc.clkcr_set_clkdiv(freq.clamp(mhz(0), mhz(50)), bus_width)?;
                 } else {
                     self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
                 }
@@ -311,54 +380,6 @@ impl<'a, 'b> StorageDevice<'a, 'b, Card> {
         Ok(())
     }
 
-    #[cfg(sdmmc_dlyb)]
-    fn tune_dlyb(&mut self, freq: Hertz, bus_width: BusWidth) -> Result<(), Error> {
-        // DLL needs a stable input clock at the target rate to lock.
-        self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
-
-        if self.sdmmc.dlyb_enable_lock().is_err() {
-            return Err(Error::SignalingSwitchFailed);
-        }
-
-        self.sdmmc.set_dlyb_active(true);
-        self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
-
-        let mut best_start = 0u8;
-        let mut best_len = 0u8;
-        let mut run_start = 0u8;
-        let mut run_len = 0u8;
-
-        for tap in 0..32u8 {
-            if self.sdmmc.dlyb_set_tap(tap).is_err() {
-                run_len = 0;
-                continue;
-            }
-            if self.sdmmc.read_status(self.info.rca).is_ok() {
-                if run_len == 0 {
-                    run_start = tap;
-                }
-                run_len += 1;
-                if run_len > best_len {
-                    best_start = run_start;
-                    best_len = run_len;
-                }
-            } else {
-                run_len = 0;
-            }
-        }
-        debug!("dlyb tune: window start={} len={}", best_start, best_len);
-
-        if best_len == 0 {
-            self.sdmmc.dlyb_disable();
-            self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
-            return Err(Error::SignalingSwitchFailed);
-        }
-
-        let chosen = best_start + best_len / 2;
-        self.sdmmc.dlyb_set_tap(chosen)?;
-        Ok(())
-    }
-
     /// Switch mode using CMD6.
     ///
     /// Attempt to set a new signalling mode. The selected
@@ -384,6 +405,14 @@ impl<'a, 'b> StorageDevice<'a, 'b, Card> {
                 Signalling::SDR12 => 0xFF_FF00,
             };
 
+        // Function group 3 selects the driver strength, which only
+        // applies to the UHS-I modes at 1.8V.
+        let set_function = if self.sdmmc.uhs_active() {
+            set_function & !0xF00 | (self.sdmmc.config.driver_strength as u32) << 8
+        } else {
+            set_function
+        };
+
         let buffer = &mut aligned_mut(&mut cmd_block.0)[..64];
         let mode = DatapathMode::Block(block_size(size_of_val(buffer)));
         let transfer = self.sdmmc.prepare_datapath_read(buffer, mode);
@@ -491,6 +520,10 @@ impl<'a, 'b> StorageDevice<'a, 'b, Emmc> {
 
         let bus_width = self.sdmmc.bus_width();
 
+        // Start from the default timing, a previous `acquire` may have
+        // left the host in HS200 mode.
+        self.sdmmc.reset_uhs_state();
+
         // While the SD/SDIO card or eMMC is in identification mode,
         // the SDMMC_CK frequency must be no more than 400 kHz.
         self.sdmmc.init_idle()?;
@@ -524,32 +557,116 @@ impl<'a, 'b> StorageDevice<'a, 'b, Emmc> {
         self.sdmmc.select_card(Some(self.info.get_address()))?;
 
         let (widbus, _) = bus_width_vals(bus_width);
+        self.switch_ext_csd(EXT_CSD_BUS_WIDTH, widbus).await?;
+
+        self.sdmmc.clkcr_set_clkdiv(freq.clamp(mhz(0), mhz(25)), bus_width)?;
 
-        // Write bus width to ExtCSD byte 183
+        let ext_csd = self.read_ext_csd().await?;
+        self.info.device_type = ext_csd[EXT_CSD_DEVICE_TYPE];
+        self.info.ext_csd = ext_csd.0.into();
+
+        if freq > mhz(25) {
+            self.select_timing(freq, bus_width).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Select the fastest bus timing supported by both the device and the host,
+    /// up to `freq`.
+    ///
+    /// HS200 is only used if enabled with [`Config::emmc_hs200`](crate::sdmmc::Config::emmc_hs200)
+    /// and needs DLYB tuning, otherwise High Speed (up to 52 MHz) is used.
+    ///
+    /// eMMC only.
+    async fn select_timing(&mut self, freq: Hertz, bus_width: BusWidth) -> Result<(), Error> {
+        let device_type = self.info.device_type;
+
+        #[cfg(sdmmc_dlyb)]
+        if freq > mhz(52)
+            && self.sdmmc.config.emmc_hs200
+            && device_type & DEVICE_TYPE_HS200_1V8 != 0
+            && !matches!(bus_width, BusWidth::One)
+            && self.sdmmc.has_dlyb()
+        {
+            match self.select_hs200(freq, bus_width).await {
+                Ok(()) => {
+                    info!("sdmmc: switched eMMC to HS200");
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("sdmmc: HS200 failed ({:?}), falling back to High Speed", e);
+                    // Back to the default host timing at a clock the device
+                    // accepts in either timing mode.
+                    self.sdmmc.uhs_active = false;
+                    self.sdmmc.reset_uhs_state();
+                    self.sdmmc.clkcr_set_clkdiv(mhz(25), bus_width)?;
+                }
+            }
+        }
+
+        let max_freq = if device_type & DEVICE_TYPE_HS_52 != 0 {
+            mhz(52)
+        } else if device_type & DEVICE_TYPE_HS_26 != 0 {
+            mhz(26)
+        } else {
+            return Ok(());
+        };
+
+        self.switch_ext_csd(EXT_CSD_HS_TIMING, 1).await?;
+        self.sdmmc.clkcr_set_clkdiv(freq.clamp(mhz(0), max_freq), bus_width)?;
+
+        let status: CardStatus<EMMC> = self.sdmmc.read_status(self.info.rca)?.into();
+        if status.state() != CurrentState::Transfer {
+            return Err(Error::SignalingSwitchFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Switch the device to HS200 and tune the receive delay.
+    ///
+    /// eMMC only.
+    #[cfg(sdmmc_dlyb)]
+    async fn select_hs200(&mut self, freq: Hertz, bus_width: BusWidth) -> Result<(), Error> {
+        self.switch_ext_csd(EXT_CSD_HS_TIMING, 2).await?;
+
+        // HS200 uses the same host timing as the SD UHS-I modes.
+        self.sdmmc.uhs_active = true;
+        self.tune_dlyb(freq.clamp(mhz(0), mhz(200)), bus_width, CMD_SEND_TUNING_BLOCK_HS200)
+            .await
+    }
+
+    /// Write a byte of the EXT_CSD register with CMD6, and wait for the device
+    /// to leave the busy state.
+    ///
+    /// eMMC only.
+    async fn switch_ext_csd(&mut self, index: u8, value: u8) -> Result<(), Error> {
         self.sdmmc.cmd(
-            emmc_cmd::modify_ext_csd(emmc_cmd::AccessMode::WriteByte, 183, widbus),
+            emmc_cmd::modify_ext_csd(emmc_cmd::AccessMode::WriteByte, index, value),
             true,
             false,
         )?;
 
         // Wait for ready after R1b response
-        loop {
-            let status: CardStatus<EMMC> = self.sdmmc.read_status(self.info.rca)?.into();
-            if status.ready_for_data() {
-                break;
-            }
-        }
-
-        self.sdmmc.clkcr_set_clkdiv(freq.clamp(mhz(0), mhz(25)), bus_width)?;
-        self.info.ext_csd = self.read_ext_csd().await?;
+        try_until(
+            async || {
+                let Ok(status) = self.sdmmc.read_status(self.info.rca) else {
+                    return false;
+                };
 
-        Ok(())
+                CardStatus::<EMMC>::from(status).ready_for_data()
+            },
+            500_000,
+        )
+        .await
+        .map_err(|_| Error::SoftwareTimeout)
     }
 
     /// Gets the EXT_CSD register.
     ///
     /// eMMC only.
-    async fn read_ext_csd(&self) -> Result<ExtCSD, Error> {
+    async fn read_ext_csd(&self) -> Result<DataBlock, Error> {
         // Note: cmd_block can't be used because ExtCSD is too long to fit.
         let mut data_block = DataBlock::new();
 
@@ -565,7 +682,7 @@ impl<'a, 'b> StorageDevice<'a, 'b, Emmc> {
 
         self.sdmmc.complete_datapath_transfer(transfer, true).await?;
 
-        Ok(data_block.0.into())
+        Ok(data_block)
     }
 }
 
@@ -622,9 +739,18 @@ impl<'a, 'b, A: Addressable> StorageDevice<'a, 'b, A> {
             CardCapacity::StandardCapacity => block_idx * size_of::<DataBlock>() as u32,
             _ => block_idx,
         };
+        // With CMD23 the card stops after the announced number of blocks,
+        // so no CMD12 is needed.
+        let use_cmd23 = self.sdmmc.config.use_cmd23 && self.info.supports_cmd23();
+
         self.sdmmc
             .cmd(common_cmd::set_block_length(size_of::<DataBlock>() as u32), true, false)?; // CMD16
 
+        if use_cmd23 {
+            self.sdmmc
+                .cmd(sd_cmd::set_block_count(blocks.len() as u32), true, false)?; // CMD23
+        }
+
         let transfer = self.sdmmc.prepare_datapath_read(
             aligned_mut(buffer),
             DatapathMode::Block(block_size(size_of::<DataBlock>())),
@@ -633,7 +759,9 @@ impl<'a, 'b, A: Addressable> StorageDevice<'a, 'b, A> {
 
         self.sdmmc.complete_datapath_transfer(transfer, false).await?;
 
-        self.sdmmc.cmd(common_cmd::stop_transmission(), true, false)?; // CMD12
+        if !use_cmd23 {
+            self.sdmmc.cmd(common_cmd::stop_transmission(), true, false)?; // CMD12
+        }
         self.sdmmc.clear_interrupt_flags();
 
         Ok(())
@@ -681,6 +809,212 @@ impl<'a, 'b, A: Addressable> StorageDevice<'a, 'b, A> {
         .map_err(|_| Error::SoftwareTimeout)
     }
 
+    /// Tune the DLYB receive delay for SDR50, SDR104 or HS200 sampling.
+    ///
+    /// Sweeps all taps, and picks the middle of the longest run of taps at
+    /// which the device returns a correct tuning block for `tuning_cmd`.
+    #[cfg(sdmmc_dlyb)]
+    async fn tune_dlyb(&mut self, freq: Hertz, bus_width: BusWidth, tuning_cmd: u8) -> Result<(), Error> {
+        // DLL needs a stable input clock at the target rate to lock.
+        self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
+
+        if self.sdmmc.dlyb_enable_lock().is_err() {
+            return Err(Error::SignalingSwitchFailed);
+        }
+
+        self.sdmmc.set_dlyb_active(true);
+        self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
+
+        let mut best_start = 0u8;
+        let mut best_len = 0u8;
+        let mut run_start = 0u8;
+        let mut run_len = 0u8;
+
+        for tap in 0..32u8 {
+            if self.sdmmc.dlyb_set_tap(tap).is_err() {
+                run_len = 0;
+                continue;
+            }
+            if self.tuning_block_ok(tuning_cmd, bus_width).await {
+                if run_len == 0 {
+                    run_start = tap;
+                }
+                run_len += 1;
+                if run_len > best_len {
+                    best_start = run_start;
+                    best_len = run_len;
+                }
+            } else {
+                run_len = 0;
+            }
+        }
+        debug!("dlyb tune: window start={} len={}", best_start, best_len);
+
+        if best_len == 0 {
+            self.sdmmc.dlyb_disable();
+            self.sdmmc.clkcr_set_clkdiv(freq, bus_width)?;
+            return Err(Error::SignalingSwitchFailed);
+        }
+
+        let chosen = best_start + best_len / 2;
+        self.sdmmc.dlyb_set_tap(chosen)?;
+        Ok(())
+    }
+
+    /// Read one tuning block and compare it with the expected pattern.
+    #[cfg(sdmmc_dlyb)]
+    async fn tuning_block_ok(&mut self, tuning_cmd: u8, bus_width: BusWidth) -> bool {
+        let pattern: &[u8] = match bus_width {
+            BusWidth::Eight => &TUNING_BLOCK_8BIT,
+            _ => &TUNING_BLOCK_4BIT,
+        };
+
+        let mut data_block = DataBlock::new();
+        let buffer = &mut aligned_mut(&mut data_block.0)[..pattern.len()];
+        let mode = DatapathMode::Block(block_size(pattern.len()));
+        let transfer = self.sdmmc.prepare_datapath_read(buffer, mode);
+
+        if self.sdmmc.cmd(cmd::<R1>(tuning_cmd, 0), true, true).is_err() {
+            return false;
+        }
+        if self.sdmmc.complete_datapath_transfer(transfer, true).await.is_err() {
+            return false;
+        }
+
+        data_block[..pattern.len()] == *pattern
+    }
+
+    /// Read consecutive blocks starting at `block_idx` into several buffers.
+    ///
+    /// On `sdmmc_v2`, all the buffers are filled by a single multi-block read, with the IDMA
+    /// moving from one block to the next from the SDMMC interrupt. The interrupt latency must
+    /// then stay below the time to transfer one block. On other versions, each buffer is filled
+    /// by one multi-block read, see [`Self::read_blocks`].
+    pub async fn read_blocks_vectored(&mut self, block_idx: u32, bufs: &mut [&mut [DataBlock]]) -> Result<(), Error> {
+        #[cfg(sdmmc_v2)]
+        {
+            let _scoped_wake_guard = self.sdmmc.info.rcc.wake_guard();
+
+            let count: usize = bufs.iter().map(|b| b.len()).sum();
+            if count == 0 {
+                return Ok(());
+            }
+
+            let address = match self.info.get_capacity() {
+                CardCapacity::StandardCapacity => block_idx * size_of::<DataBlock>() as u32,
+                _ => block_idx,
+            };
+            let use_cmd23 = self.sdmmc.config.use_cmd23 && self.info.supports_cmd23();
+
+            self.sdmmc
+                .cmd(common_cmd::set_block_length(size_of::<DataBlock>() as u32), true, false)?; // CMD16
+
+            if use_cmd23 {
+                self.sdmmc.cmd(sd_cmd::set_block_count(count as u32), true, false)?; // CMD23
+            }
+
+            // NOTE(unsafe) reinterpret the buffers as raw block slices for the IDMA
+            let bufs = unsafe { &*(bufs as *const [&mut [DataBlock]] as *const [*const [DataBlock]]) };
+            let transfer = self.sdmmc.prepare_datapath_scatter(bufs, true);
+            self.sdmmc.cmd(common_cmd::read_multiple_blocks(address), true, true)?; // CMD18
+
+            self.sdmmc.complete_datapath_transfer(transfer, false).await?;
+
+            if !use_cmd23 {
+                self.sdmmc.cmd(common_cmd::stop_transmission(), true, false)?; // CMD12
+            }
+            self.sdmmc.clear_interrupt_flags();
+
+            Ok(())
+        }
+
+        #[cfg(not(sdmmc_v2))]
+        {
+            let mut block_idx = block_idx;
+            for blocks in bufs.iter_mut().filter(|b| !b.is_empty()) {
+                self.read_blocks(block_idx, blocks).await?;
+                block_idx += blocks.len() as u32;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Write several buffers to consecutive blocks starting at `block_idx`.
+    ///
+    /// On `sdmmc_v2`, all the buffers are written by a single multi-block write, with the IDMA
+    /// moving from one block to the next from the SDMMC interrupt. The interrupt latency must
+    /// then stay below the time to transfer one block. On other versions, each buffer is written
+    /// by one multi-block write, see [`Self::write_blocks`].
+    pub async fn write_blocks_vectored(&mut self, block_idx: u32, bufs: &[&[DataBlock]]) -> Result<(), Error> {
+        #[cfg(sdmmc_v2)]
+        {
+            let _scoped_wake_guard = self.sdmmc.info.rcc.wake_guard();
+
+            let count: usize = bufs.iter().map(|b| b.len()).sum();
+            if count == 0 {
+                return Ok(());
+            }
+
+            let address = match self.info.get_capacity() {
+                CardCapacity::StandardCapacity => block_idx * size_of::<DataBlock>() as u32,
+                _ => block_idx,
+            };
+            let use_cmd23 = self.sdmmc.config.use_cmd23 && self.info.supports_cmd23();
+
+            self.sdmmc
+                .cmd(common_cmd::set_block_length(size_of::<DataBlock>() as u32), true, false)?; // CMD16
+
+            if self.sdmmc.config.use_acmd23 {
+                // CMD55 + cmd(23) = ACMD23 (SET_WR_BLK_ERASE_COUNT).
+                self.sdmmc
+                    .cmd(common_cmd::app_cmd(self.info.get_address()), true, false)?;
+                self.sdmmc.cmd(sd_cmd::set_block_count(count as u32), true, false)?;
+            }
+
+            if use_cmd23 {
+                self.sdmmc.cmd(sd_cmd::set_block_count(count as u32), true, false)?; // CMD23
+            }
+
+            // NOTE(unsafe) reinterpret the buffers as raw block slices for the IDMA
+            let bufs = unsafe { &*(bufs as *const [&[DataBlock]] as *const [*const [DataBlock]]) };
+            let transfer = self.sdmmc.prepare_datapath_scatter(bufs, false);
+            self.sdmmc.cmd(common_cmd::write_multiple_blocks(address), true, true)?; // CMD25
+
+            self.sdmmc.complete_datapath_transfer(transfer, false).await?;
+
+            if !use_cmd23 {
+                self.sdmmc.cmd(common_cmd::stop_transmission(), true, false)?; // CMD12
+            }
+            self.sdmmc.clear_interrupt_flags();
+
+            // Wait for up to 100 ms
+            try_until(
+                async || {
+                    let Ok(status) = self.sdmmc.read_status(self.info.get_address()) else {
+                        return false;
+                    };
+
+                    CardStatus::<A::Ext>::from(status).ready_for_data()
+                },
+                500_000,
+            )
+            .await
+            .map_err(|_| Error::SoftwareTimeout)
+        }
+
+        #[cfg(not(sdmmc_v2))]
+        {
+            let mut block_idx = block_idx;
+            for blocks in bufs.iter().filter(|b| !b.is_empty()) {
+                self.write_blocks(block_idx, blocks).await?;
+                block_idx += blocks.len() as u32;
+            }
+
+            Ok(())
+        }
+    }
+
     /// Probe whether [`Config::use_cmd23`] will engage on this device.
     pub fn supports_cmd23(&self) -> bool {
         self.info.supports_cmd23()
@@ -758,6 +1092,35 @@ impl<'a, 'b, A: Addressable> StorageDevice<'a, 'b, A> {
         .await
         .map_err(|_| Error::SoftwareTimeout)
     }
+
+    /// Read a data block, blocking until it is done.
+    ///
+    /// For callers without an executor, such as blocking filesystem crates. The SDMMC
+    /// interrupt must still be bound, see [`Self::read_block`].
+    pub fn blocking_read_block(&mut self, block_idx: u32, data_block: &mut DataBlock) -> Result<(), Error> {
+        embassy_futures::block_on(self.read_block(block_idx, data_block))
+    }
+
+    /// Read multiple data blocks, blocking until it is done.
+    ///
+    /// See [`Self::read_blocks`].
+    pub fn blocking_read_blocks(&mut self, block_idx: u32, blocks: &mut [DataBlock]) -> Result<(), Error> {
+        embassy_futures::block_on(self.read_blocks(block_idx, blocks))
+    }
+
+    /// Write a data block, blocking until it is done.
+    ///
+    /// See [`Self::write_block`].
+    pub fn blocking_write_block(&mut self, block_idx: u32, buffer: &DataBlock) -> Result<(), Error> {
+        embassy_futures::block_on(self.write_block(block_idx, buffer))
+    }
+
+    /// Write multiple data blocks, blocking until it is done.
+    ///
+    /// See [`Self::write_blocks`].
+    pub fn blocking_write_blocks(&mut self, block_idx: u32, blocks: &[DataBlock]) -> Result<(), Error> {
+        embassy_futures::block_on(self.write_blocks(block_idx, blocks))
+    }
 }
 
 impl<'a, 'b, A: Addressable> Drop for StorageDevice<'a, 'b, A> {
@@ -825,6 +1188,8 @@ pub struct Emmc {
     pub csd: CSD<EMMC>,
     /// Extended Card Specific Data
     pub ext_csd: ExtCSD,
+    /// Supported bus timings (EXT_CSD DEVICE_TYPE)
+    pub device_type: u8,
 }
 
 impl Addressable for Emmc {
@@ -850,6 +1215,14 @@ impl Addressable for Emmc {
     }
 }
 
+/// Block device for filesystem crates such as `embedded-fatfs`.
+///
+/// Block addresses are absolute on the device; partition offsets are applied by
+/// the filesystem layer. Reads and writes of more than one block use the
+/// multi-block commands, with CMD23 if enabled in [`Config`](super::Config).
+///
+/// Blocking callers can use [`StorageDevice::blocking_read_blocks`] and
+/// [`StorageDevice::blocking_write_blocks`] instead.
 impl<'d, 'e, A: Addressable> block_device_driver::BlockDevice<512> for StorageDevice<'d, 'e, A> {
     type Error = Error;
     type Align = aligned::A4;
@@ -859,7 +1232,6 @@ impl<'d, 'e, A: Addressable> block_device_driver::BlockDevice<512> for StorageDe
         block_address: u32,
         buf: &mut [aligned::Aligned<Self::Align, [u8; 512]>],
     ) -> Result<(), Self::Error> {
-        // TODO: I think block_address needs to be adjusted by the partition start offset
         if buf.len() == 1 {
             let block = unsafe { &mut *(&mut buf[0] as *mut _ as *mut DataBlock) };
             self.read_block(block_address, block).await?;
@@ -876,7 +1248,6 @@ impl<'d, 'e, A: Addressable> block_device_driver::BlockDevice<512> for StorageDe
         block_address: u32,
         buf: &[aligned::Aligned<Self::Align, [u8; 512]>],
     ) -> Result<(), Self::Error> {
-        // TODO: I think block_address needs to be adjusted by the partition start offset
         if buf.len() == 1 {
             let block = unsafe { &*(&buf[0] as *const _ as *const DataBlock) };
             self.write_block(block_address, block).await?;
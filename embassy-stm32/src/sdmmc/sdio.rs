@@ -15,6 +15,23 @@ use crate::sdmmc::{
     slice8_ref,
 };
 use crate::time::Hertz;
+use crate::wait::try_until;
+
+/// CCCR: I/O enable
+const CCCR_IO_ENABLE: u32 = 0x02;
+/// CCCR: I/O ready
+const CCCR_IO_READY: u32 = 0x03;
+/// CCCR: interrupt enable
+const CCCR_INT_ENABLE: u32 = 0x04;
+/// FBR: standard SDIO function interface code
+const FBR_INTERFACE_CODE: u32 = 0x00;
+/// FBR: pointer to the function CIS
+const FBR_CIS_POINTER: u32 = 0x09;
+/// FBR: I/O block size
+const FBR_BLOCK_SIZE: u32 = 0x10;
+
+/// R5 flags: COM_CRC_ERROR, ILLEGAL_COMMAND, ERROR, FUNCTION_NUMBER, OUT_OF_RANGE
+const R5_ERROR_FLAGS: u16 = 0xCB00;
 
 /// R4: OCR register
 pub struct R4;
@@ -51,6 +68,47 @@ pub fn io_send_op_cond(switch_to_1_8v_request: bool, voltage_window: u16) -> Cmd
     cmd(5, arg)
 }
 
+/// Argument of an IO_RW_DIRECT (CMD52) command.
+///
+/// `address` is the 17-bit register address in the space of `function`, and
+/// `data` is only used for writes.
+pub const fn io_rw_direct_arg(write: bool, function: u8, address: u32, data: u8) -> u32 {
+    (write as u32) << 31 | ((function as u32) & 0x7) << 28 | (address & 0x1_FFFF) << 9 | data as u32
+}
+
+/// Argument of an IO_RW_EXTENDED (CMD53) command.
+///
+/// `count` is the number of blocks in block mode, or the number of bytes in byte
+/// mode (0 means 512). With `incrementing` the register address is incremented
+/// after each byte, otherwise all bytes go to the same address (e.g. a FIFO).
+pub const fn io_rw_extended_arg(
+    write: bool,
+    function: u8,
+    block_mode: bool,
+    incrementing: bool,
+    address: u32,
+    count: u16,
+) -> u32 {
+    (write as u32) << 31
+        | ((function as u32) & 0x7) << 28
+        | (block_mode as u32) << 27
+        | (incrementing as u32) << 26
+        | (address & 0x1_FFFF) << 9
+        | (count as u32) & 0x1FF
+}
+
+/// Standard information about an SDIO function, read from its Function Basic Registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FunctionInfo {
+    /// Function number, 1 to 7.
+    pub number: u8,
+    /// Standard SDIO function interface code, 0 if the function has no standard interface.
+    pub interface_code: u8,
+    /// Address of the function CIS in the common register space.
+    pub cis_pointer: u32,
+}
+
 /// Aligned data block for SDMMC transfers.
 ///
 /// This is a 64-byte array, aligned to 4 bytes to satisfy DMA requirements.
@@ -84,13 +142,14 @@ impl DerefMut for DataBlock {
 pub struct SerialDataInterface<'a, 'b> {
     /// Inner member
     sdmmc: &'a mut Sdmmc<'b>,
+    functions: u8,
 }
 
 /// Card Storage Device
 impl<'a, 'b> SerialDataInterface<'a, 'b> {
     /// Create a new SD card
     pub async fn new(sdmmc: &'a mut Sdmmc<'b>, freq: Hertz) -> Result<Self, Error> {
-        let mut s = Self { sdmmc };
+        let mut s = Self { sdmmc, functions: 0 };
 
         s.acquire(freq).await?;
 
@@ -115,7 +174,7 @@ impl<'a, 'b> SerialDataInterface<'a, 'b> {
         let mut ticker = Ticker::every(Duration::from_millis(1));
         let mut i = 0;
 
-        let _ocr: OCR<SD> = loop {
+        let ocr: CommandResponse<R4> = loop {
             match self.sdmmc.cmd(io_send_op_cond(false, 0x0), false, false) {
                 Ok(r) => break Ok(r),
                 Err(Error::Timeout) if i == 500 => break Err(Error::Timeout),
@@ -127,8 +186,10 @@ impl<'a, 'b> SerialDataInterface<'a, 'b> {
                 }
                 Err(e) => break Err(e),
             }
-        }?
-        .into();
+        }?;
+
+        // Number of I/O functions, bits [30:28] of the R4 response
+        self.functions = ((ocr.0 >> 28) & 0x7) as u8;
 
         // UDB-based SDIO does not support io volt switch sequence
 
@@ -157,6 +218,98 @@ impl<'a, 'b> SerialDataInterface<'a, 'b> {
             .map(|r| r.0.try_into().unwrap())
     }
 
+    /// Number of I/O functions of the card, excluding function 0.
+    pub fn num_functions(&self) -> u8 {
+        self.functions
+    }
+
+    /// Read a register of `function` with CMD52.
+    pub async fn read_byte(&mut self, function: u8, address: u32) -> Result<u8, Error> {
+        self.io_rw_direct(io_rw_direct_arg(false, function, address, 0)).await
+    }
+
+    /// Write a register of `function` with CMD52.
+    pub async fn write_byte(&mut self, function: u8, address: u32, value: u8) -> Result<(), Error> {
+        self.io_rw_direct(io_rw_direct_arg(true, function, address, value))
+            .await
+            .map(|_| ())
+    }
+
+    async fn io_rw_direct(&mut self, arg: u32) -> Result<u8, Error> {
+        let response = self.cmd52(arg).await?;
+        if response & R5_ERROR_FLAGS != 0 {
+            return Err(Error::SdioResponse);
+        }
+
+        Ok(response as u8)
+    }
+
+    /// Read the standard information of I/O function `function` from its FBR.
+    pub async fn function_info(&mut self, function: u8) -> Result<FunctionInfo, Error> {
+        assert!(function >= 1 && function <= self.functions, "invalid SDIO function");
+
+        let fbr = u32::from(function) << 8;
+        let interface_code = self.read_byte(0, fbr + FBR_INTERFACE_CODE).await? & 0xF;
+        let mut cis_pointer = 0;
+        for i in 0..3 {
+            cis_pointer |= u32::from(self.read_byte(0, fbr + FBR_CIS_POINTER + i).await?) << (8 * i);
+        }
+
+        Ok(FunctionInfo {
+            number: function,
+            interface_code,
+            cis_pointer,
+        })
+    }
+
+    /// Enable I/O function `function` and wait until it reports ready.
+    pub async fn enable_function(&mut self, function: u8) -> Result<(), Error> {
+        assert!(function >= 1 && function <= self.functions, "invalid SDIO function");
+
+        let mask = 1 << function;
+        let enabled = self.read_byte(0, CCCR_IO_ENABLE).await?;
+        self.write_byte(0, CCCR_IO_ENABLE, enabled | mask).await?;
+
+        // Wait for up to 1 s
+        try_until(
+            async || matches!(self.read_byte(0, CCCR_IO_READY).await, Ok(ready) if ready & mask != 0),
+            1_000_000,
+        )
+        .await
+        .map_err(|_| Error::SoftwareTimeout)
+    }
+
+    /// Disable I/O function `function`.
+    pub async fn disable_function(&mut self, function: u8) -> Result<(), Error> {
+        assert!(function >= 1 && function <= self.functions, "invalid SDIO function");
+
+        let enabled = self.read_byte(0, CCCR_IO_ENABLE).await?;
+        self.write_byte(0, CCCR_IO_ENABLE, enabled & !(1 << function)).await
+    }
+
+    /// Set the CMD53 block size of `function`, or of function 0 for `function == 0`.
+    pub async fn set_block_size(&mut self, function: u8, block_size: u16) -> Result<(), Error> {
+        let fbr = u32::from(function) << 8;
+        let [low, high] = block_size.to_le_bytes();
+        self.write_byte(0, fbr + FBR_BLOCK_SIZE, low).await?;
+        self.write_byte(0, fbr + FBR_BLOCK_SIZE + 1, high).await
+    }
+
+    /// Enable or disable the interrupt of I/O function `function`.
+    ///
+    /// The master interrupt enable is set while any function interrupt is enabled.
+    /// Interrupts signalled on DAT1 are awaited with [`Self::wait_for_event`].
+    pub async fn set_function_interrupt(&mut self, function: u8, enabled: bool) -> Result<(), Error> {
+        assert!(function >= 1 && function <= self.functions, "invalid SDIO function");
+
+        let mask = 1 << function;
+        let ien = self.read_byte(0, CCCR_INT_ENABLE).await?;
+        let ien = if enabled { ien | mask } else { ien & !mask };
+        // Bit 0 is IENM, the master interrupt enable.
+        let ien = if ien & !1 != 0 { ien | 1 } else { 0 };
+        self.write_byte(0, CCCR_INT_ENABLE, ien).await
+    }
+
     /// Read in block mode using cmd53
     pub async fn cmd53_block_read(&mut self, arg: u32, blocks: &mut [DataBlock]) -> Result<(), Error> {
         let _scoped_wake_guard = self.sdmmc.info.rcc.wake_guard();
@@ -2,8 +2,12 @@
 #![macro_use]
 #![cfg_attr(gpdma, allow(unused))]
 
+use core::future::poll_fn;
 use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicU16, Ordering};
+use core::task::Poll;
 
+use embassy_hal_internal::drop::OnDrop;
 use embassy_sync::waitqueue::AtomicWaker;
 
 use crate::dma::ringbuffer::Error as RingbufferError;
@@ -13,6 +17,7 @@ use crate::gpio::{AfType, Flex, Pull};
 use crate::interrupt::typelevel::Interrupt;
 use crate::pac::spdifrx::Spdifrx as Regs;
 use crate::rcc::{RccInfo, SealedRccPeripheral};
+use crate::time::Hertz;
 use crate::{Peri, interrupt, peripherals};
 
 /// Possible S/PDIF preamble types.
@@ -115,6 +120,56 @@ impl Default for Config {
     }
 }
 
+/// Number of channel status bytes in a block of 192 frames.
+const CS_BLOCK_LEN: usize = 24;
+
+/// Channel status and user data of one block of 192 frames.
+///
+/// The channel status is from the channel chosen by [`Config::control_channel_selection`]. Bit
+/// `n` of the block is bit `n % 8` of byte `n / 8`, as numbered by IEC 60958.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ControlBlock {
+    /// Channel status bits.
+    pub channel_status: [u8; CS_BLOCK_LEN],
+    /// User data bits, of both channels, 16 bits per group of 8 frames.
+    pub user_data: [u16; CS_BLOCK_LEN],
+}
+
+impl ControlBlock {
+    /// Get whether the stream uses the professional channel status format (AES3).
+    pub fn is_professional(&self) -> bool {
+        self.channel_status[0] & 0x01 != 0
+    }
+
+    /// Get whether the samples are linear PCM audio, and not compressed data (IEC 61937).
+    pub fn is_linear_pcm(&self) -> bool {
+        self.channel_status[0] & 0x02 == 0
+    }
+
+    /// Get whether copying is permitted, in the consumer format.
+    pub fn is_copy_permitted(&self) -> bool {
+        self.channel_status[0] & 0x04 != 0
+    }
+
+    /// Get the sample rate indicated in the consumer format, if any.
+    pub fn sample_rate(&self) -> Option<Hertz> {
+        if self.is_professional() {
+            return None;
+        }
+        match self.channel_status[3] & 0x0F {
+            0b0000 => Some(Hertz(44_100)),
+            0b0010 => Some(Hertz(48_000)),
+            0b0011 => Some(Hertz(32_000)),
+            0b1000 => Some(Hertz(88_200)),
+            0b1010 => Some(Hertz(96_000)),
+            0b1100 => Some(Hertz(176_400)),
+            0b1110 => Some(Hertz(192_000)),
+            _ => None,
+        }
+    }
+}
+
 impl<'d, T: Instance> Spdifrx<'d, T> {
     fn dma_opts() -> TransferOptions {
         TransferOptions {
@@ -205,6 +260,7 @@ impl<'d, T: Instance> Spdifrx<'d, T> {
 
     /// Start the SPDIFRX driver.
     pub fn start(&mut self) {
+        T::state().synchronized.store(false, Ordering::Relaxed);
         self.data_ring_buffer.start();
 
         T::info().regs.cr().modify(|cr| {
@@ -212,6 +268,73 @@ impl<'d, T: Instance> Spdifrx<'d, T> {
         });
     }
 
+    /// Get whether the receiver is synchronized to the incoming stream.
+    pub fn is_synchronized(&self) -> bool {
+        T::state().synchronized.load(Ordering::Relaxed)
+    }
+
+    /// Wait until the receiver is synchronized to the incoming stream.
+    ///
+    /// This returns immediately if it already is. The receiver must have been started with
+    /// [`Self::start()`].
+    pub async fn wait_for_sync(&mut self) {
+        poll_fn(|cx| {
+            T::state().waker.register(cx.waker());
+            if self.is_synchronized() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Estimate the sample rate of the incoming stream from the measured symbol width.
+    ///
+    /// Returns `None` if the receiver is not synchronized.
+    pub fn sample_rate(&self) -> Option<Hertz> {
+        if !self.is_synchronized() {
+            return None;
+        }
+
+        // WIDTH5 counts the kernel clock cycles in 5 symbols, there are 64 symbols per frame.
+        let width5 = T::info().regs.sr().read().width5() as u64;
+        if width5 == 0 {
+            return None;
+        }
+        let clock = T::frequency().0 as u64;
+        Some(Hertz(((5 * clock + width5 * 32) / (width5 * 64)) as u32))
+    }
+
+    /// Read the channel status and user data of the next complete block of 192 frames.
+    ///
+    /// This waits for the start of a block, so it takes up to two blocks, about 8 ms at 48 kHz.
+    pub async fn read_control_block(&mut self) -> ControlBlock {
+        let state = T::state();
+        let regs = T::info().regs;
+
+        state.cs_index.store(CS_WAIT_FOR_BLOCK, Ordering::Relaxed);
+        state.cs_ready.store(false, Ordering::Relaxed);
+
+        let _on_drop = OnDrop::new(|| regs.imr().modify(|w| w.set_csrneie(false)));
+        regs.imr().modify(|w| w.set_csrneie(true));
+
+        poll_fn(|cx| {
+            state.waker.register(cx.waker());
+            if state.cs_ready.load(Ordering::Acquire) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+
+        ControlBlock {
+            channel_status: core::array::from_fn(|i| state.channel_status[i].load(Ordering::Relaxed)),
+            user_data: core::array::from_fn(|i| state.user_data[i].load(Ordering::Relaxed)),
+        }
+    }
+
     /// Read from the SPDIFRX data ring buffer.
     ///
     /// SPDIFRX is always receiving data in the background. This function pops already-received
@@ -247,18 +370,31 @@ impl<'d, T: Instance> Spdifrx<'d, T> {
 impl<'d, T: Instance> Drop for Spdifrx<'d, T> {
     fn drop(&mut self) {
         T::info().regs.cr().modify(|cr| cr.set_spdifen(0x00));
+        T::state().synchronized.store(false, Ordering::Relaxed);
     }
 }
 
+/// Channel status capture is idle, or waiting for the start of a block.
+const CS_WAIT_FOR_BLOCK: u8 = 0xFF;
+
 struct State {
-    #[allow(unused)]
     waker: AtomicWaker,
+    synchronized: AtomicBool,
+    cs_index: AtomicU8,
+    cs_ready: AtomicBool,
+    channel_status: [AtomicU8; CS_BLOCK_LEN],
+    user_data: [AtomicU16; CS_BLOCK_LEN],
 }
 
 impl State {
     const fn new() -> Self {
         Self {
             waker: AtomicWaker::new(),
+            synchronized: AtomicBool::new(false),
+            cs_index: AtomicU8::new(CS_WAIT_FOR_BLOCK),
+            cs_ready: AtomicBool::new(false),
+            channel_status: [const { AtomicU8::new(0) }; CS_BLOCK_LEN],
+            user_data: [const { AtomicU16::new(0) }; CS_BLOCK_LEN],
         }
     }
 }
@@ -289,13 +425,35 @@ pub struct GlobalInterruptHandler<T: Instance> {
 
 impl<T: Instance> interrupt::typelevel::Handler<T::GlobalInterrupt> for GlobalInterruptHandler<T> {
     unsafe fn on_interrupt() {
-        T::state().waker.wake();
+        let state = T::state();
+        state.waker.wake();
 
         let regs = T::info().regs;
         let sr = regs.sr().read();
 
+        if sr.csrne() && regs.imr().read().csrneie() {
+            // Reading CSR clears CSRNE.
+            let csr = regs.csr().read();
+            let mut index = state.cs_index.load(Ordering::Relaxed);
+            if csr.sob() {
+                index = 0;
+            }
+            if (index as usize) < CS_BLOCK_LEN {
+                state.channel_status[index as usize].store(csr.cs(), Ordering::Relaxed);
+                state.user_data[index as usize].store(csr.usr(), Ordering::Relaxed);
+                index += 1;
+                if index as usize == CS_BLOCK_LEN {
+                    regs.imr().modify(|w| w.set_csrneie(false));
+                    state.cs_ready.store(true, Ordering::Release);
+                    index = CS_WAIT_FOR_BLOCK;
+                }
+                state.cs_index.store(index, Ordering::Relaxed);
+            }
+        }
+
         if sr.serr() || sr.terr() || sr.ferr() {
             trace!("SPDIFRX error, resync");
+            state.synchronized.store(false, Ordering::Relaxed);
 
             // Clear errors by disabling SPDIFRX, then reenable.
             regs.cr().modify(|cr| cr.set_spdifen(0x00));
@@ -303,6 +461,7 @@ impl<T: Instance> interrupt::typelevel::Handler<T::GlobalInterrupt> for GlobalIn
         } else if sr.syncd() {
             // Synchronization was successful.
             trace!("SPDIFRX sync success");
+            state.synchronized.store(true, Ordering::Relaxed);
         }
 
         // Clear interrupt flags.
@@ -35,35 +35,50 @@ pub(crate) fn init(
     #[cfg(all(peri_ucpd1, not(stm32n6)))] ucpd1_db_enable: bool,
     #[cfg(peri_ucpd2)] ucpd2_db_enable: bool,
 ) {
-    #[cfg(stm32g0x1)]
-    {
+    #[cfg(all(peri_ucpd1, not(stm32n6)))]
+    set_ucpd1_dead_battery(ucpd1_db_enable);
+    #[cfg(peri_ucpd2)]
+    set_ucpd2_dead_battery(ucpd2_db_enable);
+}
+
+/// Enable or disable the dead battery pull-downs of UCPD1.
+///
+/// With dead battery enabled, the CC pins present Rd so that a source provides VBUS even
+/// while the chip is unpowered. It has to be disabled before the CC pins can be used for
+/// anything else, including as ADC inputs or GPIOs.
+///
+/// On STM32G0, dead battery can only be disabled, and this must be done while UCPD1 is
+/// disabled, see RM0444 section 8.1.1.
+#[cfg(all(peri_ucpd1, not(stm32n6)))]
+pub fn set_ucpd1_dead_battery(enable: bool) {
+    critical_section::with(|_| {
         // according to RM0444 (STM32G0x1) section 8.1.1:
         // when UCPD is disabled setting the strobe will disable dead battery
         // (which is enabled after reset) but if UCPD is enabled, setting the
         // strobe will apply the CC pin configuration from the control register
         // (which is why we need to be careful about when we call this)
-        crate::pac::SYSCFG.cfgr1().modify(|w| {
-            w.set_ucpd1_strobe(!ucpd1_db_enable);
-            w.set_ucpd2_strobe(!ucpd2_db_enable);
-        });
-    }
+        #[cfg(stm32g0x1)]
+        crate::pac::SYSCFG.cfgr1().modify(|w| w.set_ucpd1_strobe(!enable));
 
-    #[cfg(any(stm32g4, stm32l5))]
-    {
-        crate::pac::PWR.cr3().modify(|w| {
-            #[cfg(stm32g4)]
-            w.set_ucpd1_dbdis(!ucpd1_db_enable);
-            #[cfg(stm32l5)]
-            w.set_ucpd_dbdis(!ucpd1_db_enable);
-        })
-    }
+        #[cfg(stm32g4)]
+        crate::pac::PWR.cr3().modify(|w| w.set_ucpd1_dbdis(!enable));
+        #[cfg(stm32l5)]
+        crate::pac::PWR.cr3().modify(|w| w.set_ucpd_dbdis(!enable));
 
-    #[cfg(any(stm32h5, stm32u5, stm32h7rs))]
-    {
-        crate::pac::PWR.ucpdr().modify(|w| {
-            w.set_ucpd_dbdis(!ucpd1_db_enable);
-        })
-    }
+        #[cfg(any(stm32h5, stm32u5, stm32h7rs))]
+        crate::pac::PWR.ucpdr().modify(|w| w.set_ucpd_dbdis(!enable));
+    });
+}
+
+/// Enable or disable the dead battery pull-downs of UCPD2.
+///
+/// See [`set_ucpd1_dead_battery()`].
+#[cfg(peri_ucpd2)]
+pub fn set_ucpd2_dead_battery(enable: bool) {
+    critical_section::with(|_| {
+        #[cfg(stm32g0x1)]
+        crate::pac::SYSCFG.cfgr1().modify(|w| w.set_ucpd2_strobe(!enable));
+    });
 }
 
 /// Pull-up or Pull-down resistor state of both CC lines.
@@ -10,9 +10,9 @@ use embassy_usb_synopsys_otg::{
     on_interrupt as on_interrupt_impl,
 };
 
-use crate::gpio::{AfType, OutputType, Speed};
+use crate::gpio::{AfType, Level, Output, OutputType, Pin, Speed};
 use crate::interrupt::typelevel::Interrupt;
-use crate::rcc::{self, RccPeripheral};
+use crate::rcc::{self, RccPeripheral, WakeGuard};
 use crate::{Peri, interrupt};
 
 /// Interrupt handler.
@@ -43,10 +43,21 @@ macro_rules! config_ulpi_pins {
 // The following numbers are pessimistic and were figured out empirically.
 const RX_FIFO_EXTRA_SIZE_WORDS: u16 = 30;
 
+/// How long the external PHY is held in reset, in microseconds.
+const ULPI_RESET_PULSE_US: u64 = 10;
+
+/// Reset line of an external ULPI PHY.
+struct PhyReset<'d> {
+    pin: Output<'d>,
+    active: Level,
+    inactive: Level,
+}
+
 /// USB driver.
 pub struct Driver<'d, T: Instance> {
     phantom: PhantomData<&'d mut T>,
     inner: OtgDriver<'d>,
+    phy_reset: Option<PhyReset<'d>>,
 }
 
 impl<'d, T: Instance> Driver<'d, T> {
@@ -87,6 +98,7 @@ impl<'d, T: Instance> Driver<'d, T> {
         Self {
             inner: OtgDriver::new(ep_out_buffer, instance, config),
             phantom: PhantomData,
+            phy_reset: None,
         }
     }
 
@@ -125,6 +137,7 @@ impl<'d, T: Instance> Driver<'d, T> {
         Self {
             inner: OtgDriver::new(ep_out_buffer, instance, config),
             phantom: PhantomData,
+            phy_reset: None,
         }
     }
 
@@ -170,6 +183,7 @@ impl<'d, T: Instance> Driver<'d, T> {
         Self {
             inner: OtgDriver::new(ep_out_buffer, instance, config),
             phantom: PhantomData,
+            phy_reset: None,
         }
     }
 
@@ -217,8 +231,28 @@ impl<'d, T: Instance> Driver<'d, T> {
         Self {
             inner: OtgDriver::new(ep_out_buffer, instance, config),
             phantom: PhantomData,
+            phy_reset: None,
         }
     }
+
+    /// Drive the reset line of the external ULPI PHY.
+    ///
+    /// The PHY is held in reset while the USB and ULPI clocks are enabled and released before the
+    /// core reset, which only completes once the PHY outputs its 60 MHz clock. `active` is the level
+    /// that asserts the reset, e.g. [`Level::Low`] for a `RESETB` input.
+    ///
+    /// Only useful with [`Driver::new_fs_ulpi`] and [`Driver::new_hs_ulpi`].
+    pub fn set_ulpi_reset_pin(&mut self, pin: Peri<'d, impl Pin>, active: Level) {
+        let inactive = match active {
+            Level::Low => Level::High,
+            Level::High => Level::Low,
+        };
+        self.phy_reset = Some(PhyReset {
+            pin: Output::new(pin, inactive, Speed::Low),
+            active,
+            inactive,
+        });
+    }
 }
 
 impl<'d, T: Instance> embassy_usb_driver::Driver<'d> for Driver<'d, T> {
@@ -257,6 +291,8 @@ impl<'d, T: Instance> embassy_usb_driver::Driver<'d> for Driver<'d, T> {
                 phantom: PhantomData,
                 inner: bus,
                 inited: false,
+                phy_reset: self.phy_reset,
+                wake_guard: None,
             },
             cp,
         )
@@ -268,16 +304,24 @@ pub struct Bus<'d, T: Instance> {
     phantom: PhantomData<&'d mut T>,
     inner: OtgBus<'d>,
     inited: bool,
+    phy_reset: Option<PhyReset<'d>>,
+    wake_guard: Option<WakeGuard>,
 }
 
 impl<'d, T: Instance> Bus<'d, T> {
     fn init(&mut self) {
         super::common_init::<T>();
 
+        self.wake_guard = Some(T::RCC_INFO.wake_guard());
+
         // Enable ULPI clock if external PHY is used
         let phy_type = self.inner.phy_type();
         let _ulpien = !phy_type.internal();
 
+        if let Some(reset) = &mut self.phy_reset {
+            reset.pin.set_level(reset.active);
+        }
+
         #[cfg(any(stm32f2, stm32f4, stm32f7))]
         if T::HIGH_SPEED {
             critical_section::with(|_| {
@@ -349,12 +393,25 @@ impl<'d, T: Instance> Bus<'d, T> {
         let core_id = r.cid().read().0;
         trace!("Core id {:08x}", core_id);
 
+        if let Some(reset) = &mut self.phy_reset {
+            crate::wait::block_for_us(ULPI_RESET_PULSE_US);
+            reset.pin.set_level(reset.inactive);
+        }
+
         // Wait for AHB ready.
         while !r.grstctl().read().ahbidl() {}
 
         // Configure as device.
         self.inner.configure_as_device();
 
+        if !phy_type.internal() {
+            // Reset the core after selecting the ULPI interface so the link comes up with the PHY
+            // clock. This blocks until the PHY provides its clock.
+            trace!("ULPI core reset");
+            self.inner.core_soft_reset();
+            while r.gintsts().read().cmod() {}
+        }
+
         // Configuring Vbus sense and SOF output
         match core_id {
             0x0000_1200 | 0x0000_1100 | 0x0000_1000 => self.inner.config_v1(),
@@ -369,6 +426,7 @@ impl<'d, T: Instance> Bus<'d, T> {
 
         rcc::disable::<T>();
         self.inited = false;
+        self.wake_guard = None;
 
         #[cfg(stm32l4)]
         crate::pac::PWR.cr2().modify(|w| w.set_usv(false));
@@ -383,7 +441,19 @@ impl<'d, T: Instance> embassy_usb_driver::Bus for Bus<'d, T> {
             self.inited = true;
         }
 
-        self.inner.poll().await
+        let event = self.inner.poll().await;
+
+        // With `Config::low_power_suspend` the PHY clock is stopped during suspend, so nothing
+        // keeps the chip from entering Stop until the host resumes the bus.
+        match event {
+            Event::Suspend if self.inner.config().low_power_suspend => self.wake_guard = None,
+            Event::Resume | Event::Reset if self.wake_guard.is_none() => {
+                self.wake_guard = Some(T::RCC_INFO.wake_guard());
+            }
+            _ => {}
+        }
+
+        event
     }
 
     fn endpoint_set_stalled(&mut self, ep_addr: EndpointAddress, stalled: bool) {
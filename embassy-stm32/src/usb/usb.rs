@@ -17,6 +17,10 @@ use embassy_usb_driver::{
 #[cfg(usb_v4)]
 pub use usb_host::*;
 
+#[cfg(feature = "exti")]
+use crate::exti::ExtiInput;
+#[cfg(feature = "exti")]
+use crate::mode::Async;
 use crate::pac::USBRAM;
 use crate::pac::usb::regs;
 use crate::pac::usb::vals::{EpType, Stat};
@@ -87,8 +91,19 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
             let index = istr.ep_id() as usize;
 
             let mut epr = regs.epr(index).read();
+            let mut toggle_sw_buf = false;
             if epr.ctr_rx() {
-                RX_COMPLETE[index].store(true, Ordering::Relaxed);
+                if is_double_buffered_bulk(epr) {
+                    // The peripheral filled its buffer. If the application consumed the one it holds,
+                    // swap them right away by toggling `SW_BUF` (`DTOG_TX`), so the peripheral can
+                    // receive the next packet while the application reads this one.
+                    if !RX_COMPLETE[index].load(Ordering::Relaxed) && epr.dtog_rx() == epr.dtog_tx() {
+                        toggle_sw_buf = true;
+                        RX_COMPLETE[index].store(true, Ordering::Relaxed);
+                    }
+                } else {
+                    RX_COMPLETE[index].store(true, Ordering::Relaxed);
+                }
                 if index == 0 && epr.setup() {
                     EP0_SETUP.store(true, Ordering::Relaxed);
                 }
@@ -101,7 +116,7 @@ impl<T: Instance> interrupt::typelevel::Handler<T::Interrupt> for InterruptHandl
                 EP_IN_WAKERS[index].wake();
             }
             epr.set_dtog_rx(false);
-            epr.set_dtog_tx(false);
+            epr.set_dtog_tx(toggle_sw_buf);
             epr.set_stat_rx(Stat::from_bits(0));
             epr.set_stat_tx(Stat::from_bits(0));
             epr.set_ctr_rx(!epr.ctr_rx());
@@ -145,6 +160,11 @@ fn convert_type(t: EndpointType) -> EpType {
     }
 }
 
+/// Whether the endpoint register belongs to a double-buffered bulk endpoint.
+fn is_double_buffered_bulk(r: regs::Epr) -> bool {
+    r.ep_type() == EpType::Bulk && r.ep_kind()
+}
+
 fn invariant(mut r: regs::Epr) -> regs::Epr {
     r.set_ctr_rx(true); // don't clear
     r.set_ctr_tx(true); // don't clear
@@ -155,6 +175,29 @@ fn invariant(mut r: regs::Epr) -> regs::Epr {
     r
 }
 
+/// Connect or disconnect the internal D+ pull-up, on chips that have one.
+fn set_pullup<T: Instance>(enabled: bool) {
+    #[cfg(any(usb_v3, usb_v4))]
+    T::regs().bcdr().modify(|w| w.set_dppu(enabled));
+
+    #[cfg(stm32l1)]
+    crate::pac::SYSCFG.pmc().modify(|w| w.set_usb_pu(enabled));
+
+    #[cfg(not(any(usb_v3, usb_v4, stm32l1)))]
+    let _ = enabled;
+}
+
+async fn delay_ms(ms: u32) {
+    #[cfg(feature = "time")]
+    embassy_time::Timer::after_millis(ms as u64).await;
+    #[cfg(not(feature = "time"))]
+    {
+        let freq = unsafe { crate::rcc::get_freqs() }.sys.to_hertz().unwrap().0 as u64;
+        let cycles = freq * ms as u64 / 1_000;
+        cortex_m::asm::delay(cycles as u32);
+    }
+}
+
 fn align_len_up(len: u16) -> u16 {
     ((len as usize + USBRAM_ALIGN - 1) / USBRAM_ALIGN * USBRAM_ALIGN) as u16
 }
@@ -282,13 +325,43 @@ struct EndpointData {
     ep_type: EndpointType, // only valid if used_in || used_out
     used_in: bool,
     used_out: bool,
+    double_buffered: bool, // only valid if used_in || used_out
 }
 
+/// Type of the port the device is attached to, see [`Driver::detect_charger`].
+#[cfg(any(usb_v3, usb_v4))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargerType {
+    /// Standard downstream port, a regular host port. The device may draw 100 mA until it is
+    /// configured with a higher current.
+    Sdp,
+    /// Charging downstream port, a host port that also supplies up to 1.5 A.
+    Cdp,
+    /// Dedicated charging port, a charger without data lines.
+    Dcp,
+    /// PS/2 port or proprietary charger, detected by D+ and D- being pulled high.
+    NonStandard,
+}
+
+/// Data contact detection timeout (`T_DCD_TIMEOUT`).
+#[cfg(any(usb_v3, usb_v4))]
+const DCD_TIMEOUT_MS: u32 = 900;
+/// Data contact detection debounce time (`T_DCD_DBNC`).
+#[cfg(any(usb_v3, usb_v4))]
+const DCD_DEBOUNCE_MS: u32 = 10;
+/// Time the primary and secondary detection voltage sources are turned on (`T_VDPSRC_ON`).
+#[cfg(any(usb_v3, usb_v4))]
+const DETECTION_MS: u32 = 40;
+
 /// USB driver.
 pub struct Driver<'d, T: Instance> {
     phantom: PhantomData<&'d mut T>,
     alloc: [EndpointData; EP_COUNT],
     ep_mem_free: u16, // first free address in EP mem, in bytes.
+    double_buffered_bulk: bool,
+    #[cfg(feature = "exti")]
+    vbus: Option<ExtiInput<'d, Async>>,
 }
 
 impl<'d, T: Instance> Driver<'d, T> {
@@ -349,8 +422,107 @@ impl<'d, T: Instance> Driver<'d, T> {
                 ep_type: EndpointType::Bulk,
                 used_in: false,
                 used_out: false,
+                double_buffered: false,
             }; EP_COUNT],
             ep_mem_free: EP_COUNT as u16 * 8, // for each EP, 4 regs, so 8 bytes
+            double_buffered_bulk: false,
+            #[cfg(feature = "exti")]
+            vbus: None,
+        }
+    }
+
+    /// Enable VBUS detection through a GPIO pin.
+    ///
+    /// Without VBUS detection the bus reports power as soon as it is started and the D+ pull-up is
+    /// always connected. A self-powered device must not drive D+ while VBUS is absent, so it has to
+    /// sense VBUS, usually through a resistor divider on a 5V tolerant pin.
+    ///
+    /// With VBUS detection, the bus reports `PowerDetected` when `vbus` goes high and `PowerRemoved`
+    /// when it goes low. The D+ pull-up is only connected while the bus is enabled, that is, while
+    /// VBUS is present.
+    #[cfg(feature = "exti")]
+    pub fn set_vbus_detection(&mut self, vbus: ExtiInput<'d, Async>) {
+        self.vbus = Some(vbus);
+    }
+
+    /// Detect the type of port the device is attached to using the battery charging detection (BCD) block.
+    ///
+    /// This runs the data contact, primary and secondary detection of the USB Battery Charging
+    /// specification 1.2, so the application can pick a charge current before the device enumerates.
+    /// It must be called while VBUS is present and before the driver is passed to the USB device builder,
+    /// as the D+ pull-up must be disconnected during detection.
+    ///
+    /// Detection takes about 100 ms, or up to a second if the data lines do not make contact.
+    #[cfg(any(usb_v3, usb_v4))]
+    pub async fn detect_charger(&mut self) -> ChargerType {
+        let regs = T::regs();
+
+        regs.bcdr().modify(|w| {
+            w.set_dppu(false);
+            w.set_bcden(true);
+        });
+
+        // Data contact detection. If the contact is not detected in time, continue anyway as the
+        // specification requires.
+        regs.bcdr().modify(|w| w.set_dcden(true));
+        let mut elapsed = 0;
+        while !regs.bcdr().read().dcdet() && elapsed < DCD_TIMEOUT_MS {
+            delay_ms(1).await;
+            elapsed += 1;
+        }
+        delay_ms(DCD_DEBOUNCE_MS).await;
+        regs.bcdr().modify(|w| w.set_dcden(false));
+
+        // Primary detection tells a standard downstream port from a charging port.
+        regs.bcdr().modify(|w| w.set_pden(true));
+        delay_ms(DETECTION_MS).await;
+        let bcdr = regs.bcdr().read();
+        regs.bcdr().modify(|w| w.set_pden(false));
+
+        let charger = if bcdr.ps2det() {
+            ChargerType::NonStandard
+        } else if !bcdr.pdet() {
+            ChargerType::Sdp
+        } else {
+            // Secondary detection tells a dedicated charger from a charging downstream port.
+            regs.bcdr().modify(|w| w.set_sden(true));
+            delay_ms(DETECTION_MS).await;
+            let sdet = regs.bcdr().read().sdet();
+            regs.bcdr().modify(|w| w.set_sden(false));
+
+            match sdet {
+                true => ChargerType::Dcp,
+                false => ChargerType::Cdp,
+            }
+        };
+
+        // The BCD block must be disabled before the D+ pull-up is connected.
+        regs.bcdr().modify(|w| w.set_bcden(false));
+
+        trace!("charger detection: {:?}", charger);
+        charger
+    }
+
+    /// Enable double-buffering for bulk endpoints.
+    ///
+    /// A double-buffered bulk endpoint has two packet buffers, so the peripheral can accept (or send) the next
+    /// packet while the application is still handling the previous one instead of NAKing the host. This
+    /// considerably improves the throughput of classes like CDC-ACM or MSC.
+    ///
+    /// Double-buffered endpoints use twice the packet memory and occupy a whole endpoint register, so an IN
+    /// and an OUT endpoint can no longer share the same endpoint number.
+    ///
+    /// This only affects bulk endpoints that are allocated afterwards, so it must be called before the driver
+    /// is passed to the USB device builder.
+    pub fn set_double_buffered_bulk(&mut self, enabled: bool) {
+        self.double_buffered_bulk = enabled;
+    }
+
+    fn is_double_buffered(&self, ep_type: EndpointType) -> bool {
+        match ep_type {
+            EndpointType::Isochronous => true,
+            EndpointType::Bulk => self.double_buffered_bulk,
+            _ => false,
         }
     }
 
@@ -376,11 +548,10 @@ impl<'d, T: Instance> Driver<'d, T> {
 
         let used = ep.used_out || ep.used_in;
 
-        if used && ep.ep_type == EndpointType::Isochronous {
-            // Isochronous endpoints are always double-buffered.
+        if used && (ep.double_buffered || self.is_double_buffered(ep_type)) {
+            // Isochronous endpoints are always double-buffered, bulk endpoints optionally.
             // Their corresponding endpoint/channel registers are forced to be unidirectional.
             // Do not reuse this index.
-            // FIXME: Bulk endpoints can be double buffered, but are not in the current implementation.
             return false;
         }
 
@@ -416,14 +587,18 @@ impl<'d, T: Instance> Driver<'d, T> {
             (0..self.alloc.len()).find(|&i| self.is_endpoint_available::<D>(i, ep_type))
         };
 
+        let double_buffered_bulk = ep_type == EndpointType::Bulk && self.double_buffered_bulk;
         let (index, ep) = match index {
             Some(i) => (i, &mut self.alloc[i]),
             None => return Err(EndpointAllocError),
         };
 
         ep.ep_type = ep_type;
+        ep.double_buffered = double_buffered_bulk || ep_type == EndpointType::Isochronous;
 
-        let buf = match D::dir() {
+        // Double-buffered bulk endpoints get a separate second buffer, which is used through the RX descriptor
+        // for IN endpoints and the TX descriptor for OUT endpoints.
+        let (buf, buf_alt) = match D::dir() {
             Direction::Out => {
                 assert!(!ep.used_out);
                 ep.used_out = true;
@@ -434,15 +609,27 @@ impl<'d, T: Instance> Driver<'d, T> {
                 trace!("  len_bits = {:04x}", len_bits);
                 btable::write_out_rx::<T>(index, addr, len_bits);
 
-                if ep_type == EndpointType::Isochronous {
-                    btable::write_out_tx::<T>(index, addr, len_bits);
+                let alt_addr = if double_buffered_bulk {
+                    self.alloc_ep_mem(len)
+                } else {
+                    addr
+                };
+                if ep_type == EndpointType::Isochronous || double_buffered_bulk {
+                    btable::write_out_tx::<T>(index, alt_addr, len_bits);
                 }
 
-                EndpointBuffer {
-                    addr,
-                    len,
-                    _marker: PhantomData,
-                }
+                (
+                    EndpointBuffer {
+                        addr,
+                        len,
+                        _marker: PhantomData,
+                    },
+                    double_buffered_bulk.then_some(EndpointBuffer {
+                        addr: alt_addr,
+                        len,
+                        _marker: PhantomData,
+                    }),
+                )
             }
             Direction::In => {
                 assert!(!ep.used_in);
@@ -450,14 +637,19 @@ impl<'d, T: Instance> Driver<'d, T> {
 
                 let len = align_len_up(max_packet_size);
                 let addr = self.alloc_ep_mem(len);
+                let alt_addr = if double_buffered_bulk {
+                    self.alloc_ep_mem(len)
+                } else {
+                    addr
+                };
 
                 #[cfg(not(any(usbram_32_2048, usbram_32_1024)))]
                 {
                     // ep_in_len is written when actually transmitting packets.
                     btable::write_in_tx::<T>(index, addr);
 
-                    if ep_type == EndpointType::Isochronous {
-                        btable::write_in_rx::<T>(index, addr);
+                    if ep_type == EndpointType::Isochronous || double_buffered_bulk {
+                        btable::write_in_rx::<T>(index, alt_addr);
                     }
                 }
 
@@ -465,16 +657,23 @@ impl<'d, T: Instance> Driver<'d, T> {
                 {
                     btable::write_in_len_tx::<T>(index, addr, 0);
 
-                    if ep_type == EndpointType::Isochronous {
-                        btable::write_in_len_rx::<T>(index, addr, 0);
+                    if ep_type == EndpointType::Isochronous || double_buffered_bulk {
+                        btable::write_in_len_rx::<T>(index, alt_addr, 0);
                     }
                 }
 
-                EndpointBuffer {
-                    addr,
-                    len,
-                    _marker: PhantomData,
-                }
+                (
+                    EndpointBuffer {
+                        addr,
+                        len,
+                        _marker: PhantomData,
+                    },
+                    double_buffered_bulk.then_some(EndpointBuffer {
+                        addr: alt_addr,
+                        len,
+                        _marker: PhantomData,
+                    }),
+                )
             }
         };
 
@@ -489,6 +688,7 @@ impl<'d, T: Instance> Driver<'d, T> {
                 interval_ms,
             },
             buf,
+            buf_alt,
         })
     }
 }
@@ -540,24 +740,25 @@ impl<'d, T: Instance> driver::Driver<'d> for Driver<'d, T> {
             w.set_ctrm(true);
         });
 
-        #[cfg(any(usb_v3, usb_v4))]
-        regs.bcdr().write(|w| w.set_dppu(true));
-
-        #[cfg(stm32l1)]
-        crate::pac::SYSCFG.pmc().modify(|w| w.set_usb_pu(true));
-
         trace!("enabled");
 
         let mut ep_types = [EpType::Bulk; EP_COUNT - 1];
+        let mut ep_double_buffered = [false; EP_COUNT - 1];
         for i in 1..EP_COUNT {
             ep_types[i - 1] = convert_type(self.alloc[i].ep_type);
+            // EP_KIND selects double-buffering for bulk endpoints, isochronous endpoints always are.
+            ep_double_buffered[i - 1] = self.alloc[i].ep_type == EndpointType::Bulk && self.alloc[i].double_buffered;
         }
 
         (
             Bus {
                 phantom: PhantomData,
                 ep_types,
+                ep_double_buffered,
                 inited: false,
+                powered: false,
+                #[cfg(feature = "exti")]
+                vbus: self.vbus,
             },
             ControlPipe {
                 _marker: PhantomData,
@@ -573,7 +774,11 @@ impl<'d, T: Instance> driver::Driver<'d> for Driver<'d, T> {
 pub struct Bus<'d, T: Instance> {
     phantom: PhantomData<&'d mut T>,
     ep_types: [EpType; EP_COUNT - 1],
+    ep_double_buffered: [bool; EP_COUNT - 1],
     inited: bool,
+    powered: bool,
+    #[cfg(feature = "exti")]
+    vbus: Option<ExtiInput<'d, Async>>,
 }
 
 impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
@@ -581,9 +786,25 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
         poll_fn(move |cx| {
             BUS_WAKER.register(cx.waker());
 
-            // TODO: implement VBUS detection.
+            #[cfg(feature = "exti")]
+            if let Some(vbus) = &mut self.vbus {
+                // Arm the EXTI line before sampling, so no edge can be missed.
+                vbus.poll_for_any_edge(cx);
+                self.inited = true;
+
+                let powered = vbus.is_high();
+                if powered != self.powered {
+                    self.powered = powered;
+                    return Poll::Ready(match powered {
+                        true => Event::PowerDetected,
+                        false => Event::PowerRemoved,
+                    });
+                }
+            }
+
             if !self.inited {
                 self.inited = true;
+                self.powered = true;
                 return Poll::Ready(Event::PowerDetected);
             }
 
@@ -613,6 +834,7 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
                     regs.epr(i).write(|w| {
                         w.set_ea(i as _);
                         w.set_ep_type(self.ep_types[i - 1]);
+                        w.set_ep_kind(self.ep_double_buffered[i - 1]);
                     })
                 }
 
@@ -648,6 +870,7 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
                         Stat::Stall => break,    // done!
                         _ => {
                             let want_stat = match stalled {
+                                false if is_double_buffered_bulk(r) => Stat::Valid,
                                 false => Stat::Nak,
                                 true => Stat::Stall,
                             };
@@ -698,19 +921,23 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
         match ep_addr.direction() {
             Direction::In => {
                 loop {
+                    let r = epr.read();
                     let want_stat = match enabled {
                         false => Stat::Disabled,
-                        true => match epr.read().ep_type() {
-                            EpType::Iso => Stat::Valid,
-                            _ => Stat::Nak,
-                        },
+                        true if r.ep_type() == EpType::Iso || is_double_buffered_bulk(r) => Stat::Valid,
+                        true => Stat::Nak,
                     };
-                    let r = epr.read();
                     if r.stat_tx() == want_stat {
                         break;
                     }
                     let mut w = invariant(r);
                     w.set_stat_tx(Stat::from_bits(r.stat_tx().to_bits() ^ want_stat.to_bits()));
+                    if enabled && is_double_buffered_bulk(r) {
+                        // Start with DTOG_TX = SW_BUF = 0: both buffers are free and the peripheral NAKs until
+                        // the first packet is handed over.
+                        w.set_dtog_tx(r.dtog_tx());
+                        w.set_dtog_rx(r.dtog_rx());
+                    }
                     epr.write_value(w);
                 }
                 EP_IN_WAKERS[ep_addr.index()].wake();
@@ -727,6 +954,13 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
                     }
                     let mut w = invariant(r);
                     w.set_stat_rx(Stat::from_bits(r.stat_rx().to_bits() ^ want_stat.to_bits()));
+                    if enabled && is_double_buffered_bulk(r) {
+                        // Start with DTOG_RX = 0 and SW_BUF = 1: the peripheral receives into buffer 0 while
+                        // the application holds the empty buffer 1.
+                        w.set_dtog_rx(r.dtog_rx());
+                        w.set_dtog_tx(!r.dtog_tx());
+                        RX_COMPLETE[ep_addr.index()].store(false, Ordering::Relaxed);
+                    }
                     epr.write_value(w);
                 }
                 EP_OUT_WAKERS[ep_addr.index()].wake();
@@ -735,8 +969,14 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
         trace!("EPR after: {:04x}", epr.read().0);
     }
 
-    async fn enable(&mut self) {}
-    async fn disable(&mut self) {}
+    async fn enable(&mut self) {
+        set_pullup::<T>(true);
+    }
+
+    async fn disable(&mut self) {
+        // Stop driving D+, the host must not see the device when VBUS is absent.
+        set_pullup::<T>(false);
+    }
 
     async fn remote_wakeup(&mut self) -> Result<(), Unsupported> {
         let regs = T::regs();
@@ -744,14 +984,7 @@ impl<'d, T: Instance> driver::Bus for Bus<'d, T> {
         regs.cntr().modify(|w| w.set_lpmode(false));
         // Drive K-state while FSUSP is still set
         regs.cntr().modify(|w| w.set_resume(true));
-        #[cfg(feature = "time")]
-        embassy_time::Timer::after_millis(10).await;
-        #[cfg(not(feature = "time"))]
-        {
-            let freq = unsafe { crate::rcc::get_freqs() }.sys.to_hertz().unwrap().0 as u64;
-            let cycles = freq * 10 / 1_000;
-            cortex_m::asm::delay(cycles as u32);
-        }
+        delay_ms(10).await;
         regs.cntr().modify(|w| w.set_resume(false));
         // Exit forced-suspend so the peripheral can handle the
         // host's resume response
@@ -798,22 +1031,37 @@ pub struct Endpoint<'d, T: Instance, D> {
     _marker: PhantomData<(&'d mut T, D)>,
     info: EndpointInfo,
     buf: EndpointBuffer<T>,
+    /// Second packet buffer of a double-buffered bulk endpoint.
+    buf_alt: Option<EndpointBuffer<T>>,
 }
 
 impl<'d, T: Instance, D> Endpoint<'d, T, D> {
+    /// Memory of a packet buffer.
+    ///
+    /// Isochronous and single-buffered endpoints only have one buffer in memory, double-buffered bulk endpoints
+    /// have a separate one for the packet buffer that is not used by their direction in single-buffered mode.
+    fn packet_buffer_mem(&mut self, packet_buffer: PacketBuffer) -> &mut EndpointBuffer<T> {
+        match (self.info.addr.direction(), packet_buffer, &mut self.buf_alt) {
+            (Direction::In, PacketBuffer::Rx, Some(buf)) | (Direction::Out, PacketBuffer::Tx, Some(buf)) => buf,
+            _ => &mut self.buf,
+        }
+    }
+
     /// Write to a double-buffered endpoint.
     ///
-    /// For double-buffered endpoints, the data buffers overlap, but we still need to write to the right counter field.
+    /// For isochronous endpoints, the data buffers overlap, but we still need to write to the right counter field.
     /// The DTOG_TX bit indicates the buffer that is currently in use by the USB peripheral, that is, the buffer in
     /// which the next transmit packet will be stored, so we need to write the counter of the OTHER buffer, which is
     /// where the last transmitted packet was stored.
     fn write_data_double_buffered(&mut self, buf: &[u8], packet_buffer: PacketBuffer) {
         let index = self.info.addr.index();
-        self.buf.write(buf);
+        let mem = self.packet_buffer_mem(packet_buffer);
+        mem.write(buf);
+        let addr = mem.addr;
 
         match packet_buffer {
-            PacketBuffer::Rx => btable::write_in_len_rx::<T>(index, self.buf.addr, buf.len() as _),
-            PacketBuffer::Tx => btable::write_in_len_tx::<T>(index, self.buf.addr, buf.len() as _),
+            PacketBuffer::Rx => btable::write_in_len_rx::<T>(index, addr, buf.len() as _),
+            PacketBuffer::Tx => btable::write_in_len_tx::<T>(index, addr, buf.len() as _),
         }
     }
 
@@ -824,7 +1072,7 @@ impl<'d, T: Instance, D> Endpoint<'d, T, D> {
 
     /// Read from a double-buffered endpoint.
     ///
-    /// For double-buffered endpoints, the data buffers overlap, but we still need to read from the right counter field.
+    /// For isochronous endpoints, the data buffers overlap, but we still need to read from the right counter field.
     /// The DTOG_RX bit indicates the buffer that is currently in use by the USB peripheral, that is, the buffer in
     /// which the next received packet will be stored, so we need to read the counter of the OTHER buffer, which is
     /// where the last received packet was stored.
@@ -845,7 +1093,7 @@ impl<'d, T: Instance, D> Endpoint<'d, T, D> {
         if rx_len > buf.len() {
             return Err(EndpointError::BufferOverflow);
         }
-        self.buf.read(&mut buf[..rx_len]);
+        self.packet_buffer_mem(packet_buffer).read(&mut buf[..rx_len]);
         Ok(rx_len)
     }
 
@@ -906,8 +1154,17 @@ impl<'d, T: Instance> driver::EndpointOut for Endpoint<'d, T, Out> {
         let stat = poll_fn(|cx| {
             EP_OUT_WAKERS[index].register(cx.waker());
             let regs = T::regs();
-            let stat = regs.epr(index).read().stat_rx();
-            if self.info.ep_type == EndpointType::Isochronous {
+            let epr = regs.epr(index).read();
+            let stat = epr.stat_rx();
+            if self.buf_alt.is_some() {
+                // A double-buffered bulk endpoint keeps `STAT_RX` at `VALID`. A packet is ready once the interrupt
+                // handed the filled buffer over to the application.
+                if stat == Stat::Disabled || RX_COMPLETE[index].load(Ordering::Relaxed) {
+                    Poll::Ready(stat)
+                } else {
+                    Poll::Pending
+                }
+            } else if self.info.ep_type == EndpointType::Isochronous {
                 // The isochronous endpoint does not change its `STAT_RX` field to `NAK` when receiving a packet.
                 // Therefore, this instead waits until the `CTR` interrupt was triggered.
                 if matches!(stat, Stat::Disabled) || RX_COMPLETE[index].load(Ordering::Relaxed) {
@@ -945,7 +1202,9 @@ impl<'d, T: Instance> driver::EndpointOut for Endpoint<'d, T, Out> {
             }
         }
 
-        RX_COMPLETE[index].store(false, Ordering::Relaxed);
+        if self.buf_alt.is_none() {
+            RX_COMPLETE[index].store(false, Ordering::Relaxed);
+        }
 
         if stat == Stat::Disabled {
             return Err(EndpointError::Disabled);
@@ -961,6 +1220,30 @@ impl<'d, T: Instance> driver::EndpointOut for Endpoint<'d, T, Out> {
                 PacketBuffer::Rx
             };
             self.read_data_double_buffered(buf, packet_buffer)?
+        } else if self.buf_alt.is_some() {
+            // `SW_BUF` (`DTOG_TX`) points to the buffer held by the application, which holds the packet.
+            let packet_buffer = if regs.epr(index).read().dtog_tx() {
+                PacketBuffer::Rx
+            } else {
+                PacketBuffer::Tx
+            };
+            let len = self.read_data_double_buffered(buf, packet_buffer);
+
+            critical_section::with(|_| {
+                RX_COMPLETE[index].store(false, Ordering::Relaxed);
+
+                // The peripheral may have filled its buffer meanwhile, and is NAKing until it gets the one
+                // just consumed. Swap them here, as the interrupt has already passed.
+                let r = regs.epr(index).read();
+                if r.dtog_rx() == r.dtog_tx() {
+                    let mut w = invariant(r);
+                    w.set_dtog_tx(true);
+                    regs.epr(index).write_value(w);
+                    RX_COMPLETE[index].store(true, Ordering::Relaxed);
+                }
+            });
+
+            len?
         } else {
             let len = self.read_data(buf)?;
 
@@ -999,14 +1282,32 @@ impl<'d, T: Instance> driver::EndpointIn for Endpoint<'d, T, In> {
                 PacketBuffer::Tx
             };
 
+            self.write_data_double_buffered(buf, packet_buffer);
+        } else if self.buf_alt.is_some() {
+            // The buffer held by the application, selected by `SW_BUF` (`DTOG_RX`), is always free.
+            let packet_buffer = if regs.epr(index).read().dtog_rx() {
+                PacketBuffer::Rx
+            } else {
+                PacketBuffer::Tx
+            };
+
             self.write_data_double_buffered(buf, packet_buffer);
         }
 
         let stat = poll_fn(|cx| {
             EP_IN_WAKERS[index].register(cx.waker());
             let regs = T::regs();
-            let stat = regs.epr(index).read().stat_tx();
-            if self.info.ep_type == EndpointType::Isochronous {
+            let epr = regs.epr(index).read();
+            let stat = epr.stat_tx();
+            if self.buf_alt.is_some() {
+                // A double-buffered bulk endpoint keeps `STAT_TX` at `VALID`. The buffer can be handed over once the
+                // peripheral sent the other one, that is, when `DTOG_TX` equals `SW_BUF`.
+                if stat == Stat::Disabled || epr.dtog_tx() == epr.dtog_rx() {
+                    Poll::Ready(stat)
+                } else {
+                    Poll::Pending
+                }
+            } else if self.info.ep_type == EndpointType::Isochronous {
                 // The isochronous endpoint does not change its `STAT_TX` field to `NAK` after sending a packet.
                 // Therefore, this instead waits until the `CTR` interrupt was triggered.
                 if matches!(stat, Stat::Disabled) || !TX_PENDING[index].load(Ordering::Relaxed) {
@@ -1029,7 +1330,12 @@ impl<'d, T: Instance> driver::EndpointIn for Endpoint<'d, T, In> {
             return Err(EndpointError::Disabled);
         }
 
-        if self.info.ep_type != EndpointType::Isochronous {
+        if self.buf_alt.is_some() {
+            // Toggle `SW_BUF` to hand the filled buffer to the peripheral and claim the one it has just sent.
+            let mut w = invariant(regs.epr(index).read());
+            w.set_dtog_rx(true);
+            regs.epr(index).write_value(w);
+        } else if self.info.ep_type != EndpointType::Isochronous {
             self.write_data(buf);
 
             regs.epr(index).write(|w| {
@@ -32,6 +32,7 @@ use otg_v1::{Otg, regs, vals};
 pub unsafe fn on_interrupt(r: Otg, state: &State<'_>) {
     trace!("irq");
     let ep_count = state.endpoint_count();
+    let dma = state.dma.load(Ordering::Relaxed);
 
     let ints = r.gintsts().read();
     if ints.wkupint() || ints.usbsusp() || ints.usbrst() || ints.enumdne() || ints.otgint() || ints.srqint() {
@@ -39,13 +40,14 @@ pub unsafe fn on_interrupt(r: Otg, state: &State<'_>) {
         r.gintmsk().write(|w| {
             w.set_iepint(true);
             w.set_oepint(true);
-            w.set_rxflvlm(true);
+            w.set_rxflvlm(!dma);
+            w.set_eopfm(state.has_iso_endpoints());
         });
         state.bus_waker.wake();
     }
 
-    // Handle RX
-    while r.gintsts().read().rxflvl() {
+    // Handle RX, in DMA mode the core copies the data itself
+    while !dma && r.gintsts().read().rxflvl() {
         let status = r.grxstsp().read();
         trace!("=== status {:08x}", status.0);
         let ep_num = status.epnum() as usize;
@@ -160,7 +162,12 @@ pub unsafe fn on_interrupt(r: Otg, state: &State<'_>) {
                 r.doepint(ep_num).write_value(ep_ints);
 
                 if ep_ints.stup() {
+                    if dma {
+                        dma_receive_setup(r, state);
+                    }
                     state.cp_state.setup_ready.store(true, Ordering::Release);
+                } else if dma && ep_ints.xfrc() && !ep_ints.stpktrx() {
+                    dma_receive_out(r, state, ep_num);
                 }
                 state.ep_states[ep_num].out_waker.wake();
                 trace!("out ep={} irq val={:08x}", ep_num, ep_ints.0);
@@ -172,6 +179,8 @@ pub unsafe fn on_interrupt(r: Otg, state: &State<'_>) {
     }
 
     if ints.eopf() {
+        r.gintsts().write(|w| w.set_eopf(true));
+
         let frame_number = r.dsts().read().fnsof();
         let frame_is_odd = frame_number & 0x01 == 1;
 
@@ -218,9 +227,79 @@ pub unsafe fn on_interrupt(r: Otg, state: &State<'_>) {
                 w.set_epena(true);
             });
         }
+
+        // Same for isochronous OUT endpoints that were armed for this frame but did not receive
+        // anything: move them to the next frame so the host's next packet is accepted.
+        for ep_num in (0..ep_count).filter(|ep_num| {
+            let doepctl = r.doepctl(*ep_num).read();
+            doepctl.eptyp() == vals::Eptyp::ISOCHRONOUS && doepctl.epena() && doepctl.eonum_dpid() == frame_is_odd
+        }) {
+            trace!("Missed OUT packet at EOF for ep: {}, frame: {}", ep_num, frame_number);
+
+            r.doepctl(ep_num).modify(|r| {
+                if frame_is_odd {
+                    r.set_sd0pid_sevnfrm(true);
+                } else {
+                    r.set_soddfrm(true);
+                }
+            });
+        }
+    }
+}
+
+/// Copies the last SETUP packet written by the DMA and re-arms EP0 OUT.
+fn dma_receive_setup(r: Otg, state: &State<'_>) {
+    let base = unsafe { *state.ep_states[0].out_buffer.get() };
+
+    // The DMA address advances by 8 bytes for each back-to-back SETUP packet, the last one is the
+    // one to handle.
+    let end = r.doepdma(0).read() as usize;
+    let offset = end.saturating_sub(base as usize + 8).min(16) & !7;
+
+    for (i, word) in state.cp_state.setup_data.iter().enumerate() {
+        // SAFETY: EP0 OUT has a word aligned DMA buffer of at least 24 bytes.
+        let data = unsafe { core::ptr::read_volatile(base.add(offset + i * 4) as *const u32) };
+        word.store(data, Ordering::Relaxed);
+    }
+
+    if let Some(ep) = state.ep_alloc_get(Direction::Out, 0) {
+        dma_arm_out(r, 0, base, ep.max_packet_size);
+    }
+}
+
+/// Stores the length of a packet written by the DMA into the endpoint's OUT buffer.
+fn dma_receive_out(r: Otg, state: &State<'_>, ep_num: usize) {
+    let Some(ep) = state.ep_alloc_get(Direction::Out, ep_num) else {
+        return;
+    };
+    let remaining = r.doeptsiz(ep_num).read().xfrsiz() as u16;
+    let len = ep.max_packet_size.saturating_sub(remaining);
+    trace!("DMA OUT ep={} len={}", ep_num, len);
+
+    let out_size = &state.ep_states[ep_num].out_size;
+    if out_size.load(Ordering::Acquire) == EP_OUT_BUFFER_EMPTY {
+        out_size.store(len, Ordering::Release);
+    } else {
+        error!("ep_out buffer overflow index={}", ep_num);
     }
 }
 
+/// Points the DMA of an OUT endpoint to its buffer and enables it to receive one packet.
+fn dma_arm_out(r: Otg, ep_num: usize, buffer: *mut u8, max_packet_size: u16) {
+    r.doepdma(ep_num).write_value(buffer as u32);
+    r.doeptsiz(ep_num).modify(|w| {
+        w.set_xfrsiz(max_packet_size as _);
+        w.set_pktcnt(1);
+        if ep_num == 0 {
+            w.set_rxdpid_stupcnt(3);
+        }
+    });
+    r.doepctl(ep_num).modify(|w| {
+        w.set_cnak(true);
+        w.set_epena(true);
+    });
+}
+
 /// USB PHY type
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum PhyType {
@@ -275,12 +354,15 @@ struct EpState {
     /// Buffers are ready when associated [State::ep_out_size] != [EP_OUT_BUFFER_EMPTY].
     out_buffer: UnsafeCell<*mut u8>,
     out_size: AtomicU16,
+    /// Word aligned buffer the DMA reads IN packets from, only used with [`Config::dma`].
+    in_buffer: UnsafeCell<*mut u8>,
     // Written once during endpoint allocation (before Driver::start), read-only afterward.
     in_alloc: UnsafeCell<Option<EndpointData>>,
     out_alloc: UnsafeCell<Option<EndpointData>>,
 }
 
-// SAFETY: `out_buffer` access is synchronized via `out_size`. `in_alloc`/`out_alloc` are written
+// SAFETY: `out_buffer` access is synchronized via `out_size`, `in_buffer` is only written by the
+// endpoint while the previous transfer is complete. `in_alloc`/`out_alloc` are written
 // only during endpoint allocation before the USB stack starts; afterward they are read-only.
 unsafe impl Send for EpState {}
 unsafe impl Sync for EpState {}
@@ -307,6 +389,7 @@ pub struct State<'d> {
     cp_state: &'d ControlPipeSetupState,
     ep_states: &'d [EpState],
     bus_waker: &'d AtomicWaker,
+    dma: &'d AtomicBool,
 }
 
 impl State<'_> {
@@ -350,6 +433,15 @@ impl<'d> State<'d> {
         })
     }
 
+    /// Whether any isochronous endpoint is allocated, these need the end of periodic frame interrupt.
+    pub(crate) fn has_iso_endpoints(&self) -> bool {
+        (0..self.endpoint_count()).any(|i| {
+            [Direction::In, Direction::Out]
+                .into_iter()
+                .any(|dir| matches!(self.ep_alloc_get(dir, i), Some(ep) if ep.ep_type == EndpointType::Isochronous))
+        })
+    }
+
     pub(crate) fn ep_irq_mask_out(&self) -> u16 {
         (0..self.endpoint_count()).fold(0, |mask, i| {
             if self.ep_alloc_get(Direction::Out, i).is_some() {
@@ -376,6 +468,7 @@ pub struct StateStorage<const EP_COUNT: usize> {
     cp_state: ControlPipeSetupState,
     ep_states: [EpState; EP_COUNT],
     bus_waker: AtomicWaker,
+    dma: AtomicBool,
 }
 
 impl<const EP_COUNT: usize> StateStorage<EP_COUNT> {
@@ -392,11 +485,13 @@ impl<const EP_COUNT: usize> StateStorage<EP_COUNT> {
                     out_waker: AtomicWaker::new(),
                     out_buffer: UnsafeCell::new(0 as _),
                     out_size: AtomicU16::new(EP_OUT_BUFFER_EMPTY),
+                    in_buffer: UnsafeCell::new(0 as _),
                     in_alloc: UnsafeCell::new(None),
                     out_alloc: UnsafeCell::new(None),
                 }
             }; EP_COUNT],
             bus_waker: AtomicWaker::new(),
+            dma: AtomicBool::new(false),
         }
     }
 
@@ -406,6 +501,7 @@ impl<const EP_COUNT: usize> StateStorage<EP_COUNT> {
             cp_state: &self.cp_state,
             ep_states: self.ep_states.as_slice(),
             bus_waker: &self.bus_waker,
+            dma: &self.dma,
         }
     }
 }
@@ -437,6 +533,20 @@ pub struct Config {
     /// enumerates in FS mode. Some USB Link IP like those in the STM32H7 series support adding this delay to work with
     /// the affected PHYs.
     pub xcvrdly: bool,
+
+    /// Stop the PHY clock while the bus is suspended.
+    ///
+    /// The clock is restarted when the host resumes or resets the bus, or on remote wakeup. HALs may use this
+    /// to let the MCU enter a deeper low-power mode between [`Event::Suspend`] and [`Event::Resume`].
+    pub low_power_suspend: bool,
+
+    /// Use the internal DMA of the core for endpoint transfers.
+    ///
+    /// Packets are moved between the FIFOs and the endpoint buffer by the core instead of the CPU. Only
+    /// available on cores with an internal DMA, usually the high-speed ones. The endpoint buffer passed to
+    /// [`Driver::new`] must then be word aligned, located in memory the core's AHB master can access and
+    /// not cached, and it also has to hold the IN packets.
+    pub dma: bool,
 }
 
 impl Default for Config {
@@ -444,6 +554,8 @@ impl Default for Config {
         Self {
             vbus_detection: false,
             xcvrdly: false,
+            low_power_suspend: false,
+            dma: false,
         }
     }
 }
@@ -462,11 +574,20 @@ impl<'d> Driver<'d> {
     /// # Arguments
     ///
     /// * `ep_out_buffer` - An internal buffer used to temporarily store received packets.
-    /// Must be large enough to fit all OUT endpoint max packet sizes.
+    /// Must be large enough to fit all OUT endpoint max packet sizes, and with [`Config::dma`]
+    /// also all IN endpoint max packet sizes, each rounded up to a multiple of 4.
     /// Endpoint allocation will fail if it is too small.
     /// * `instance` - The USB OTG peripheral instance and its configuration.
     /// * `config` - The USB driver configuration.
     pub fn new(ep_out_buffer: &'d mut [u8], instance: OtgInstance<'d>, config: Config) -> Self {
+        if config.dma {
+            assert!(
+                ep_out_buffer.as_ptr() as usize & 3 == 0,
+                "endpoint buffer must be word aligned for DMA"
+            );
+        }
+        instance.state.dma.store(config.dma, Ordering::Relaxed);
+
         Self {
             config,
             ep_out_buffer,
@@ -497,13 +618,19 @@ impl<'d> Driver<'d> {
             D::dir()
         );
 
-        if D::dir() == Direction::Out {
-            if self.ep_out_buffer_offset + max_packet_size as usize > self.ep_out_buffer.len() {
-                error!("Not enough endpoint out buffer capacity");
-                return Err(EndpointAllocError);
-            }
+        let buffer_size = match (D::dir(), self.config.dma) {
+            (Direction::Out, false) => max_packet_size as usize,
+            (Direction::In, false) => 0,
+            // The DMA needs word aligned buffers, EP0 OUT also receives up to three back-to-back SETUP packets.
+            (Direction::Out, true) if ep_type == EndpointType::Control => (max_packet_size.max(24) as usize + 3) & !3,
+            (_, true) => (max_packet_size as usize + 3) & !3,
         };
 
+        if self.ep_out_buffer_offset + buffer_size > self.ep_out_buffer.len() {
+            error!("Not enough endpoint buffer capacity");
+            return Err(EndpointAllocError);
+        }
+
         let fifo_size_words = match D::dir() {
             Direction::Out => (max_packet_size + 3) / 4,
             // INEPTXFD requires minimum size of 16 words
@@ -567,17 +694,22 @@ impl<'d> Driver<'d> {
         trace!("  index={}", index);
 
         let ep_state = &self.instance.state.ep_states[index];
-        if D::dir() == Direction::Out {
+        if buffer_size > 0 {
             // Buffer capacity check was done above, now allocation cannot fail
             unsafe {
-                *ep_state.out_buffer.get() = self.ep_out_buffer.as_mut_ptr().offset(self.ep_out_buffer_offset as _);
+                let buffer = self.ep_out_buffer.as_mut_ptr().add(self.ep_out_buffer_offset);
+                match D::dir() {
+                    Direction::Out => *ep_state.out_buffer.get() = buffer,
+                    Direction::In => *ep_state.in_buffer.get() = buffer,
+                }
             }
-            self.ep_out_buffer_offset += max_packet_size as usize;
+            self.ep_out_buffer_offset += buffer_size;
         }
 
         Ok(Endpoint {
             _phantom: PhantomData,
             regs: self.instance.regs,
+            dma: self.config.dma,
             state: ep_state,
             info: EndpointInfo {
                 addr: EndpointAddress::from_parts(index, D::dir()),
@@ -662,9 +794,10 @@ impl<'d> Bus<'d> {
             w.set_wuim(true);
             w.set_iepint(true);
             w.set_oepint(true);
-            w.set_rxflvlm(true);
+            w.set_rxflvlm(!self.config.dma);
             w.set_srqim(true);
             w.set_otgint(true);
+            w.set_eopfm(self.instance.state.has_iso_endpoints());
         });
     }
 
@@ -673,6 +806,23 @@ impl<'d> Bus<'d> {
         self.instance.phy_type
     }
 
+    /// Returns the bus configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Restarts the PHY clock if it was stopped during suspend.
+    fn exit_low_power(&mut self) {
+        // See RM0368 §22.8 "OTG low-power modes" (STPPCLK / GATEHCLK).
+        self.instance.regs.pcgcctl().modify(|w| {
+            w.set_stppclk(false);
+            // GATEHCLK is only present on HS cores.
+            if self.instance.phy_type.high_speed() {
+                w.set_gatehclk(false);
+            }
+        });
+    }
+
     /// Applies a DWC2 core soft reset.
     pub fn core_soft_reset(&mut self) {
         let r = self.instance.regs;
@@ -840,6 +990,8 @@ impl<'d> Bus<'d> {
         // Unmask SETUP received EP interrupt
         r.doepmsk().write(|w| {
             w.set_stupm(true);
+            // Without DMA, received packets are handled through RXFLVL instead.
+            w.set_xfrcm(self.config.dma);
         });
 
         // Unmask and clear core interrupts
@@ -847,8 +999,16 @@ impl<'d> Bus<'d> {
         r.gintsts().write_value(regs::Gintsts(0xFFFF_FFFF));
 
         // Unmask global interrupt
+        if self.config.dma {
+            assert!(r.hwcfg2().read().otgarch() == 2, "USB core has no internal DMA");
+        }
+
         r.gahbcfg().write(|w| {
             w.set_gint(true); // unmask global interrupt
+            if self.config.dma {
+                w.set_dmaen(true);
+                w.set_hbstlen(0b0011); // INCR4
+            }
         });
 
         // Connect
@@ -957,6 +1117,10 @@ impl<'d> Bus<'d> {
                     });
 
                     if index == 0 {
+                        if self.config.dma {
+                            regs.doepdma(index)
+                                .write_value(unsafe { *st.ep_states[index].out_buffer.get() } as u32);
+                        }
                         regs.doepctl(index).modify(|w| {
                             w.set_epena(true);
                             w.set_cnak(true);
@@ -1041,6 +1205,8 @@ impl<'d> embassy_usb_driver::Bus for Bus<'d> {
             if ints.usbrst() {
                 trace!("reset");
 
+                self.exit_low_power();
+
                 self.init_fifo();
                 self.configure_endpoints();
 
@@ -1073,11 +1239,18 @@ impl<'d> embassy_usb_driver::Bus for Bus<'d> {
                 trace!("suspend");
                 regs.gintsts().write(|w| w.set_usbsusp(true)); // clear
                 self.restore_irqs();
+
+                if self.config.low_power_suspend && regs.dsts().read().suspsts() {
+                    trace!("stopping PHY clock");
+                    regs.pcgcctl().modify(|w| w.set_stppclk(true));
+                }
+
                 return Poll::Ready(Event::Suspend);
             }
 
             if ints.wkupint() {
                 trace!("resume");
+                self.exit_low_power();
                 regs.gintsts().write(|w| w.set_wkupint(true)); // clear
                 self.restore_irqs();
                 return Poll::Ready(Event::Resume);
@@ -1166,6 +1339,10 @@ impl<'d> embassy_usb_driver::Bus for Bus<'d> {
                     // Without this, the endpoint stays idle after reconnect and silently drops data.
                     if enabled && ep_addr.index() != 0 {
                         if let Some(ep) = st.ep_alloc_get(Direction::Out, ep_addr.index()) {
+                            if self.config.dma {
+                                let buffer = unsafe { *st.ep_states[ep_addr.index()].out_buffer.get() };
+                                regs.doepdma(ep_addr.index()).write_value(buffer as u32);
+                            }
                             regs.doeptsiz(ep_addr.index()).modify(|w| {
                                 w.set_xfrsiz(ep.max_packet_size as _);
                                 w.set_pktcnt(1);
@@ -1236,14 +1413,7 @@ impl<'d> embassy_usb_driver::Bus for Bus<'d> {
         let r = self.instance.regs;
 
         // Re-enable PHY clock gated during suspend.
-        // See RM0368 §22.8 "OTG low-power modes" (STPPCLK / GATEHCLK).
-        r.pcgcctl().modify(|w| {
-            w.set_stppclk(false);
-            // GATEHCLK is only present on HS cores.
-            if self.instance.phy_type.high_speed() {
-                w.set_gatehclk(false);
-            }
-        });
+        self.exit_low_power();
 
         // Assert resume K-state on D+/D-.
         // USB 2.0 spec §7.1.7.7: TDRSMUP requires 1–15 ms of resume signaling.
@@ -1281,6 +1451,7 @@ impl Dir for Out {
 pub struct Endpoint<'d, D> {
     _phantom: PhantomData<D>,
     regs: Otg,
+    dma: bool,
     info: EndpointInfo,
     state: &'d EpState,
 }
@@ -1358,10 +1529,19 @@ impl<'d> embassy_usb_driver::EndpointOut for Endpoint<'d, Out> {
                 self.state.out_size.store(EP_OUT_BUFFER_EMPTY, Ordering::Release);
 
                 critical_section::with(|_| {
+                    if self.dma {
+                        self.regs
+                            .doepdma(index)
+                            .write_value(unsafe { *self.state.out_buffer.get() } as u32);
+                    }
+
                     // Receive 1 packet
                     self.regs.doeptsiz(index).modify(|w| {
                         w.set_xfrsiz(self.info.max_packet_size as _);
                         w.set_pktcnt(1);
+                        if self.dma && index == 0 {
+                            w.set_rxdpid_stupcnt(3);
+                        }
                     });
 
                     if self.info.ep_type == EndpointType::Isochronous {
@@ -1382,6 +1562,11 @@ impl<'d> embassy_usb_driver::EndpointOut for Endpoint<'d, Out> {
                     // Clear NAK to indicate we are ready to receive more data
                     self.regs.doepctl(index).modify(|w| {
                         w.set_cnak(true);
+                        // Isochronous endpoints, and all endpoints in DMA mode, are disabled by the
+                        // core after every transfer and have to be re-armed.
+                        if self.dma || self.info.ep_type == EndpointType::Isochronous {
+                            w.set_epena(true);
+                        }
                     });
                 });
 
@@ -1426,7 +1611,7 @@ impl<'d> embassy_usb_driver::EndpointIn for Endpoint<'d, In> {
         })
         .await?;
 
-        if buf.len() > 0 {
+        if buf.len() > 0 && !self.dma {
             poll_fn(|cx| {
                 self.state.in_waker.register(cx.waker());
 
@@ -1457,6 +1642,13 @@ impl<'d> embassy_usb_driver::EndpointIn for Endpoint<'d, In> {
         //
         // Prevent the interrupt (which might poke FIFOs) from executing while copying data to FIFOs.
         critical_section::with(|_| {
+            if self.dma {
+                // SAFETY: the previous transfer is complete, so the DMA no longer reads the buffer.
+                let buffer = unsafe { *self.state.in_buffer.get() };
+                unsafe { core::ptr::copy_nonoverlapping(buf.as_ptr(), buffer, buf.len()) };
+                self.regs.diepdma(index).write_value(buffer as u32);
+            }
+
             // Setup transfer size
             self.regs.dieptsiz(index).write(|w| {
                 w.set_mcnt(1);
@@ -1485,6 +1677,10 @@ impl<'d> embassy_usb_driver::EndpointIn for Endpoint<'d, In> {
                 w.set_epena(true);
             });
 
+            if self.dma {
+                return;
+            }
+
             // Write data to FIFO
             let fifo = self.regs.fifo(index);
             let mut chunks = buf.chunks_exact(4);
@@ -1532,15 +1728,18 @@ impl<'d> embassy_usb_driver::ControlPipe for ControlPipe<'d> {
                 data[4..8].copy_from_slice(&self.setup_state.setup_data[1].load(Ordering::Relaxed).to_ne_bytes());
                 self.setup_state.setup_ready.store(false, Ordering::Release);
 
-                // EP0 should not be controlled by `Bus` so this RMW does not need a critical section
-                self.regs.doeptsiz(self.ep_out.info.addr.index()).modify(|w| {
-                    w.set_rxdpid_stupcnt(3);
-                });
+                // In DMA mode EP0 OUT is re-armed by the interrupt handler
+                if !self.ep_out.dma {
+                    // EP0 should not be controlled by `Bus` so this RMW does not need a critical section
+                    self.regs.doeptsiz(self.ep_out.info.addr.index()).modify(|w| {
+                        w.set_rxdpid_stupcnt(3);
+                    });
 
-                // Clear NAK to indicate we are ready to receive more data
-                self.regs
-                    .doepctl(self.ep_out.info.addr.index())
-                    .modify(|w| w.set_cnak(true));
+                    // Clear NAK to indicate we are ready to receive more data
+                    self.regs
+                        .doepctl(self.ep_out.info.addr.index())
+                        .modify(|w| w.set_cnak(true));
+                }
 
                 trace!("SETUP received: {:?}", Bytes(&data));
                 Poll::Ready(data)
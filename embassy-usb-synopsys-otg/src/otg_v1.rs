@@ -1,6 +1,11 @@
 //! Register definitions for Synopsys DesignWare USB OTG core
 //! This core is well known for being poorly documented publicly, but register descriptions are available at:
 //!  https://www.intel.com/content/www/us/en/programmable/hps/agilex5/index_frames.html under USBOTG
+//!
+//! The internal DMA mode uses registers and fields that the generated description lacks: the
+//! `DIEPDMA` registers, the `DOEPINT` STSPHSRX and STPKTRX flags and the `GHWCFG2` OTGARCH field.
+//! They are written in the same form as the generated code, and must be carried over when the
+//! file is regenerated.
 
 #![allow(missing_docs)]
 
@@ -405,6 +410,12 @@ impl Otg {
         core::assert!(n < 16usize);
         unsafe { Reg::from_ptr(self.ptr.add(0x0910usize + n * 32usize) as _) }
     }
+    #[doc = "Device IN endpoint DMA address register"]
+    #[inline(always)]
+    pub const fn diepdma(self, n: usize) -> Reg<u32, RW> {
+        core::assert!(n < 16usize);
+        unsafe { Reg::from_ptr(self.ptr.add(0x0914usize + n * 32usize) as _) }
+    }
     #[doc = "Device IN endpoint transmit FIFO status register"]
     #[inline(always)]
     pub const fn dtxfsts(self, n: usize) -> Reg<regs::Dtxfsts, R> {
@@ -1479,6 +1490,17 @@ pub mod regs {
         pub fn set_otepdis(&mut self, val: bool) {
             self.0 = (self.0 & !(0x01 << 4usize)) | (((val as u32) & 0x01) << 4usize);
         }
+        #[doc = "STSPHSRX"]
+        #[inline(always)]
+        pub const fn stsphsrx(&self) -> bool {
+            let val = (self.0 >> 5usize) & 0x01;
+            val != 0
+        }
+        #[doc = "STSPHSRX"]
+        #[inline(always)]
+        pub fn set_stsphsrx(&mut self, val: bool) {
+            self.0 = (self.0 & !(0x01 << 5usize)) | (((val as u32) & 0x01) << 5usize);
+        }
         #[doc = "B2BSTUP"]
         #[inline(always)]
         pub const fn b2bstup(&self) -> bool {
@@ -1490,6 +1512,17 @@ pub mod regs {
         pub fn set_b2bstup(&mut self, val: bool) {
             self.0 = (self.0 & !(0x01 << 6usize)) | (((val as u32) & 0x01) << 6usize);
         }
+        #[doc = "STPKTRX"]
+        #[inline(always)]
+        pub const fn stpktrx(&self) -> bool {
+            let val = (self.0 >> 15usize) & 0x01;
+            val != 0
+        }
+        #[doc = "STPKTRX"]
+        #[inline(always)]
+        pub fn set_stpktrx(&mut self, val: bool) {
+            self.0 = (self.0 & !(0x01 << 15usize)) | (((val as u32) & 0x01) << 15usize);
+        }
     }
     impl Default for Doepint {
         #[inline(always)]
@@ -3879,6 +3912,12 @@ pub mod regs {
     #[derive(Copy, Clone, Eq, PartialEq)]
     pub struct Ghwcfg2(pub u32);
     impl Ghwcfg2 {
+        #[doc = "Architecture (0=slave only, 1=external DMA, 2=internal DMA)"]
+        #[inline(always)]
+        pub const fn otgarch(&self) -> u8 {
+            let val = (self.0 >> 3usize) & 0x03;
+            val as u8
+        }
         #[doc = "High-speed PHY type (0=not supported, 1=UTMI+, 2=ULPI, 3=UTMI+ and ULPI)"]
         #[inline(always)]
         pub const fn hs_phy_type(&self) -> u8 {
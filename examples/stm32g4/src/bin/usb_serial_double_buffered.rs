@@ -0,0 +1,126 @@
+//! Receives data from the host as fast as possible over double-buffered bulk endpoints.
+//!
+//! Send a large file to the serial port, e.g. `cat /dev/zero > /dev/ttyACM0`, and watch the
+//! throughput. The peripheral accepts the next packet into its second buffer while the previous
+//! one is being read, so the host rarely sees a NAK.
+
+#![no_std]
+#![no_main]
+
+use defmt::{panic, *};
+use embassy_executor::Spawner;
+use embassy_futures::join::join;
+use embassy_stm32::time::Hertz;
+use embassy_stm32::usb::{self, Driver, Instance};
+use embassy_stm32::{Config, bind_interrupts, peripherals};
+use embassy_time::Instant;
+use embassy_usb::Builder;
+use embassy_usb::class::cdc_acm::{CdcAcmClass, State};
+use embassy_usb::driver::EndpointError;
+use {defmt_rtt as _, panic_probe as _};
+
+bind_interrupts!(struct Irqs {
+    USB_LP => usb::InterruptHandler<peripherals::USB>;
+});
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let mut config = Config::default();
+    {
+        use embassy_stm32::rcc::*;
+        config.rcc.hsi48 = Some(Hsi48Config { sync_from_usb: true });
+        config.rcc.hse = Some(Hse {
+            freq: Hertz(8_000_000),
+            mode: HseMode::Oscillator,
+        });
+        config.rcc.pll = Some(Pll {
+            source: PllSource::Hse,
+            prediv: PllPreDiv::Div2,
+            mul: PllMul::Mul72,
+            divp: None,
+            divq: Some(PllQDiv::Div6), // 48mhz
+            divr: Some(PllRDiv::Div2), // Main system clock at 144 MHz
+        });
+        config.rcc.sys = Sysclk::Pll1R;
+        config.rcc.boost = true;
+        config.rcc.mux.clk48sel = mux::Clk48sel::Hsi48;
+    }
+    let p = embassy_stm32::init(config);
+
+    info!("Hello World!");
+
+    let mut driver = Driver::new(p.USB, Irqs, p.PA12, p.PA11);
+    // Must be enabled before the endpoints are allocated by the class.
+    driver.set_double_buffered_bulk(true);
+
+    let mut config = embassy_usb::Config::new(0xc0de, 0xcafe);
+    config.manufacturer = Some("Embassy");
+    config.product = Some("USB-Serial Double Buffered Example");
+    config.serial_number = Some("123456");
+
+    let mut config_descriptor = [0; 256];
+    let mut bos_descriptor = [0; 256];
+    let mut control_buf = [0; 64];
+
+    let mut state = State::new();
+
+    let mut builder = Builder::new(
+        driver,
+        config,
+        &mut config_descriptor,
+        &mut bos_descriptor,
+        &mut [], // no msos descriptors
+        &mut control_buf,
+    );
+
+    let mut class = CdcAcmClass::new(&mut builder, &mut state, 64);
+
+    let mut usb = builder.build();
+
+    let usb_fut = usb.run();
+
+    let sink_fut = async {
+        loop {
+            class.wait_connection().await;
+            info!("Connected");
+            let _ = sink(&mut class).await;
+            info!("Disconnected");
+        }
+    };
+
+    join(usb_fut, sink_fut).await;
+}
+
+struct Disconnected {}
+
+impl From<EndpointError> for Disconnected {
+    fn from(val: EndpointError) -> Self {
+        match val {
+            EndpointError::BufferOverflow => panic!("Buffer overflow"),
+            EndpointError::Disabled => Disconnected {},
+        }
+    }
+}
+
+async fn sink<'d, T: Instance + 'd>(class: &mut CdcAcmClass<'d, Driver<'d, T>>) -> Result<(), Disconnected> {
+    let mut buf = [0; 64];
+    let mut bytes = 0u32;
+    let mut packets = 0u32;
+    let mut start = Instant::now();
+    loop {
+        bytes += class.read_packet(&mut buf).await? as u32;
+        packets += 1;
+
+        let elapsed = start.elapsed();
+        if elapsed.as_millis() >= 1000 {
+            info!(
+                "{} packets, {} kB/s",
+                packets,
+                bytes as u64 * 1000 / elapsed.as_millis() / 1024
+            );
+            bytes = 0;
+            packets = 0;
+            start = Instant::now();
+        }
+    }
+}
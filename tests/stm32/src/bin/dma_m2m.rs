@@ -0,0 +1,81 @@
+// required-features: not-gpdma
+
+#![no_std]
+#![no_main]
+#[path = "../common.rs"]
+mod common;
+
+use common::*;
+use defmt::assert_eq;
+use embassy_executor::Spawner;
+use embassy_stm32::dma::{Channel, TransferOptions};
+
+// These chips run the UART DMA channel on a DMA controller, which packs words through its FIFO.
+// All other chips run it on a BDMA controller, which zero-extends or truncates each word.
+const PACKING: bool = cfg!(any(
+    feature = "stm32f207zg",
+    feature = "stm32f429zi",
+    feature = "stm32f446re",
+    feature = "stm32f767zi",
+    feature = "stm32h753zi",
+    feature = "stm32h755zi",
+    feature = "stm32h7a3zi",
+));
+
+#[cfg_attr(
+    feature = "stop",
+    embassy_executor::main(executor = "embassy_stm32::executor::Executor", entry = "cortex_m_rt::entry")
+)]
+#[cfg_attr(not(feature = "stop"), embassy_executor::main)]
+async fn main(_spawner: Spawner) {
+    let p = init();
+    info!("Hello World!");
+
+    let mut dma = Channel::new(peri!(p, UART_TX_DMA), irqs!(UART));
+
+    // Narrow source, wide destination.
+    let src: [u8; 4] = [0x11, 0x22, 0x33, 0x44];
+    let mut dst = [0u32; 4];
+    unsafe {
+        dma.transfer_raw(
+            Default::default(),
+            src.as_ptr(),
+            src.len(),
+            dst.as_mut_ptr(),
+            TransferOptions::default(),
+        )
+    }
+    .await;
+    if PACKING {
+        assert_eq!(dst, [0x4433_2211, 0, 0, 0]);
+    } else {
+        assert_eq!(dst, [0x11, 0x22, 0x33, 0x44]);
+    }
+
+    // Wide source, narrow destination.
+    let src: [u32; 4] = [0x4433_2211, 0x8877_6655, 0xccbb_aa99, 0x00ff_eedd];
+    let mut dst = [0u8; 16];
+    unsafe {
+        dma.transfer_raw(
+            Default::default(),
+            src.as_ptr(),
+            src.len(),
+            dst.as_mut_ptr(),
+            TransferOptions::default(),
+        )
+    }
+    .await;
+    if PACKING {
+        assert_eq!(
+            dst,
+            [
+                0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff, 0x00
+            ]
+        );
+    } else {
+        assert_eq!(dst, [0x11, 0x55, 0x99, 0xdd, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    info!("Test OK");
+    cortex_m::asm::bkpt();
+}